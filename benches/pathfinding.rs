@@ -0,0 +1,174 @@
+//! Path finding benchmarks.
+//!
+//! Run with `cargo bench --bench pathfinding`.
+
+use fyrox::{
+    core::algebra::Vector3,
+    rand::{self, Rng},
+    utils::astar::{HierarchicalPathFinder, PathFinder, PathVertex, PathfindingContext},
+};
+use std::time::Instant;
+
+fn make_grid(size: usize) -> PathFinder {
+    let mut pathfinder = PathFinder::new();
+    let mut vertices = Vec::new();
+    for y in 0..size {
+        for x in 0..size {
+            vertices.push(PathVertex::new(Vector3::new(x as f32, y as f32, 0.0)));
+        }
+    }
+    pathfinder.set_vertices(vertices);
+    for y in 0..(size - 1) {
+        for x in 0..(size - 1) {
+            pathfinder.link_bidirect(y * size + x, y * size + x + 1);
+            pathfinder.link_bidirect(y * size + x, (y + 1) * size + x);
+        }
+    }
+    pathfinder
+}
+
+// Randomized depth-first maze with some extra passages, every vertex is reachable.
+fn make_random_maze(size: usize) -> PathFinder {
+    let mut pathfinder = PathFinder::new();
+    let mut vertices = Vec::new();
+    for y in 0..size {
+        for x in 0..size {
+            vertices.push(PathVertex::new(Vector3::new(x as f32, y as f32, 0.0)));
+        }
+    }
+    pathfinder.set_vertices(vertices);
+
+    let mut rng = rand::thread_rng();
+    let mut visited = vec![false; size * size];
+    let mut stack = vec![0];
+    visited[0] = true;
+    while let Some(&current) = stack.last() {
+        let (x, y) = (current % size, current / size);
+        let mut candidates = Vec::new();
+        if x > 0 {
+            candidates.push(current - 1);
+        }
+        if x + 1 < size {
+            candidates.push(current + 1);
+        }
+        if y > 0 {
+            candidates.push(current - size);
+        }
+        if y + 1 < size {
+            candidates.push(current + size);
+        }
+        candidates.retain(|c| !visited[*c]);
+        if candidates.is_empty() {
+            stack.pop();
+        } else {
+            let next = candidates[rng.gen_range(0..candidates.len())];
+            visited[next] = true;
+            pathfinder.link_bidirect(current, next);
+            stack.push(next);
+        }
+    }
+
+    for _ in 0..(size * size / 10) {
+        let x = rng.gen_range(0..(size - 1));
+        let y = rng.gen_range(0..(size - 1));
+        pathfinder.link_bidirect(y * size + x, y * size + x + 1);
+    }
+
+    pathfinder
+}
+
+fn path_length(path: &[Vector3<f32>]) -> f32 {
+    path.windows(2).map(|w| w[0].metric_distance(&w[1])).sum()
+}
+
+fn random_queries(vertex_count: usize, count: usize) -> Vec<(usize, usize)> {
+    let mut rng = rand::thread_rng();
+    (0..count)
+        .map(|_| {
+            (
+                rng.gen_range(0..vertex_count),
+                rng.gen_range(0..vertex_count),
+            )
+        })
+        .collect()
+}
+
+fn context_reuse() {
+    let size = 20;
+    let mut pathfinder = make_grid(size);
+    let queries = random_queries(size * size, 10_000);
+    let mut path = Vec::new();
+
+    let now = Instant::now();
+    for &(from, to) in queries.iter() {
+        pathfinder.build(from, to, &mut path).unwrap();
+    }
+    let fresh = now.elapsed();
+
+    let mut ctx = PathfindingContext::new();
+    let now = Instant::now();
+    for &(from, to) in queries.iter() {
+        pathfinder.build_in(&mut ctx, from, to, &mut path).unwrap();
+    }
+    let reused = now.elapsed();
+
+    println!(
+        "10k searches: fresh allocation - {:?}, reused context - {:?}",
+        fresh, reused
+    );
+}
+
+fn hierarchical() {
+    let size = 512;
+    let now = Instant::now();
+    let mut hierarchical = HierarchicalPathFinder::new(make_random_maze(size), 16.0);
+    let construction = now.elapsed();
+
+    let queries = random_queries(size * size, 100);
+    let mut path = Vec::new();
+
+    let mut ctx = PathfindingContext::new();
+    let now = Instant::now();
+    let mut flat_length = 0.0;
+    for &(from, to) in queries.iter() {
+        hierarchical
+            .path_finder()
+            .build_in(&mut ctx, from, to, &mut path)
+            .unwrap();
+        flat_length += path_length(&path);
+    }
+    let flat = now.elapsed();
+
+    let mut measure = |hierarchical: &HierarchicalPathFinder| {
+        let now = Instant::now();
+        let mut length = 0.0;
+        for &(from, to) in queries.iter() {
+            hierarchical.build(from, to, &mut path).unwrap();
+            length += path_length(&path);
+        }
+        (now.elapsed(), length)
+    };
+    let (hierarchical_time, hierarchical_length) = measure(&hierarchical);
+    hierarchical.set_heuristic_weight(4.0);
+    let (weighted_time, weighted_length) = measure(&hierarchical);
+
+    println!(
+        "{}x{} maze: construction - {:?}, 100 searches: flat - {:?} (total length {}), \
+        hierarchical - {:?} (total length {}), hierarchical with heuristic weight 4 - {:?} \
+        (total length {})",
+        size,
+        size,
+        construction,
+        flat,
+        flat_length,
+        hierarchical_time,
+        hierarchical_length,
+        weighted_time,
+        weighted_length
+    );
+}
+
+fn main() {
+    context_reuse();
+    hierarchical();
+}
@@ -87,6 +87,7 @@ struct RootMotionDropdownArea {
     ignore_y: Handle<UiNode>,
     ignore_z: Handle<UiNode>,
     ignore_rotation: Handle<UiNode>,
+    yaw_only: Handle<UiNode>,
     node_selector: Handle<UiNode>,
 }
 
@@ -123,10 +124,11 @@ impl RootMotionDropdownArea {
         let ignore_y = check_box(3, ctx);
         let ignore_z = check_box(4, ctx);
         let ignore_rotation = check_box(5, ctx);
+        let yaw_only = check_box(6, ctx);
         let popup = PopupBuilder::new(
             WidgetBuilder::new()
                 .with_width(220.0)
-                .with_height(135.0)
+                .with_height(157.0)
                 .with_visibility(false),
         )
         .stays_open(false)
@@ -155,7 +157,9 @@ impl RootMotionDropdownArea {
                     .with_child(text("Ignore Z", 4, ctx))
                     .with_child(ignore_z)
                     .with_child(text("Ignore Rotation", 5, ctx))
-                    .with_child(ignore_rotation),
+                    .with_child(ignore_rotation)
+                    .with_child(text("Yaw Only", 6, ctx))
+                    .with_child(yaw_only),
             )
             .add_column(Column::strict(90.0))
             .add_column(Column::stretch())
@@ -165,6 +169,7 @@ impl RootMotionDropdownArea {
             .add_row(Row::strict(22.0))
             .add_row(Row::strict(22.0))
             .add_row(Row::strict(22.0))
+            .add_row(Row::strict(22.0))
             .add_row(Row::stretch())
             .build(ctx),
         )
@@ -178,6 +183,7 @@ impl RootMotionDropdownArea {
             ignore_y,
             ignore_z,
             ignore_rotation,
+            yaw_only,
             node_selector: Default::default(),
         }
     }
@@ -235,6 +241,13 @@ impl RootMotionDropdownArea {
                                 ..*settings
                             }));
                         }
+                    } else if message.destination() == self.yaw_only {
+                        if let Some(settings) = animation.root_motion_settings_ref() {
+                            send_command(Some(RootMotionSettings {
+                                yaw_only: *value,
+                                ..*settings
+                            }));
+                        }
                     }
                 }
             } else if let Some(ButtonMessage::Click) = message.data() {
@@ -328,6 +341,7 @@ impl RootMotionDropdownArea {
                 self.ignore_y,
                 self.ignore_z,
                 self.ignore_rotation,
+                self.yaw_only,
             ] {
                 send_sync_message(
                     ui,
@@ -356,6 +370,7 @@ impl RootMotionDropdownArea {
                 sync_checked(ui, self.ignore_y, settings.ignore_y_movement);
                 sync_checked(ui, self.ignore_z, settings.ignore_z_movement);
                 sync_checked(ui, self.ignore_rotation, settings.ignore_rotations);
+                sync_checked(ui, self.yaw_only, settings.yaw_only);
             }
         }
     }
@@ -6,7 +6,7 @@ use fyrox::{
     },
     core::{append_extension, futures::executor::block_on, log::Log, reflect::prelude::*},
     gui::inspector::{PropertyAction, PropertyChanged},
-    resource::model::ModelImportOptions,
+    resource::model::{Model, ModelImportOptions},
 };
 use std::path::{Path, PathBuf};
 
@@ -25,11 +25,14 @@ impl ModelImportOptionsHandler {
 }
 
 impl ImportOptionsHandler for ModelImportOptionsHandler {
-    fn apply(&self, _resource_manager: ResourceManager) {
-        // TODO: Reload model.
-
+    fn apply(&self, resource_manager: ResourceManager) {
         self.options
             .save(&append_extension(&self.resource_path, "options"));
+
+        let model = resource_manager.request::<Model, _>(&self.resource_path);
+        resource_manager
+            .state()
+            .reload_resource(model.into_untyped());
     }
 
     fn revert(&mut self) {
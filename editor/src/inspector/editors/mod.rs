@@ -24,13 +24,17 @@ use fyrox::{
                 BasePoseNode,
             },
             state::{StateAction, StateActionWrapper},
-            transition::{AndNode, LogicNode, NotNode, OrNode, XorNode},
+            transition::{
+                AndNode, CompareNode, ComparisonOperator, LogicNode, NotNode, OrNode, XorNode,
+            },
             BlendAnimations, BlendAnimationsByIndex, BlendPose, IndexedBlendInput, Machine,
             PlayAnimation, PoseNode, PoseWeight, State,
         },
         Animation, AnimationContainer,
     },
+    asset::upload::UploadPriority,
     core::{
+        color::Color,
         futures::executor::block_on,
         parking_lot::Mutex,
         pool::{ErasedHandle, Handle},
@@ -47,7 +51,7 @@ use fyrox::{
     renderer::framework::state::PolygonFillMode,
     resource::{
         curve::{CurveResource, CurveResourceState},
-        model::{MaterialSearchOptions, Model, ModelResource},
+        model::{MaterialSearchOptions, Model, ModelAxis, ModelResource},
         texture::{
             CompressionOptions, MipFilter, TextureMagnificationFilter, TextureMinificationFilter,
             TextureResource, TextureWrapMode,
@@ -69,6 +73,7 @@ use fyrox::{
         joint::*,
         light::{
             directional::{CsmOptions, FrustumSplitOptions},
+            falloff::{LightFalloffCurve, LightFalloffPoint},
             BaseLight,
         },
         mesh::{
@@ -83,6 +88,7 @@ use fyrox::{
             },
             ParticleSystemRng,
         },
+        reflection_probe::{ReflectionProbeShape, ReflectionProbeUpdateMode},
         rigidbody::RigidBodyType,
         sound::{
             self,
@@ -90,6 +96,7 @@ use fyrox::{
                 AllPassFilterEffect, BandPassFilterEffect, HighPassFilterEffect,
                 HighShelfFilterEffect, LowPassFilterEffect, LowShelfFilterEffect,
             },
+            occlusion::SoundOcclusion,
             reverb::Reverb,
             Attenuate, AudioBus, Biquad, DistanceModel, Effect, SoundBuffer, SoundBufferResource,
             Status,
@@ -156,6 +163,8 @@ pub fn make_property_editors_container(sender: MessageSender) -> PropertyEditorD
     container.register_inheritable_vec_collection::<LevelOfDetail>();
     container.register_inheritable_inspectable::<LevelOfDetail>();
 
+    container.register_inheritable_inspectable::<SoundOcclusion>();
+
     container.register_inheritable_vec_collection::<ErasedHandle>();
     container.register_inheritable_inspectable::<ErasedHandle>();
 
@@ -232,6 +241,7 @@ pub fn make_property_editors_container(sender: MessageSender) -> PropertyEditorD
     container.register_inheritable_inspectable::<dim2::joint::FixedJoint>();
     container.register_inheritable_inspectable::<RevoluteJoint>();
     container.register_inheritable_inspectable::<PrismaticJoint>();
+    container.register_inheritable_inspectable::<JointMotor>();
     container.register_inheritable_inspectable::<dim2::joint::PrismaticJoint>();
 
     container.register_inheritable_inspectable::<Base>();
@@ -271,6 +281,12 @@ pub fn make_property_editors_container(sender: MessageSender) -> PropertyEditorD
     container.register_inheritable_option::<ColorGradingLut>();
     container.register_inheritable_option::<Biquad>();
     container.register_inheritable_option::<SkyBox>();
+    container.register_inheritable_option::<Color>();
+
+    container.register_inheritable_option::<LightFalloffCurve>();
+    container.register_inheritable_inspectable::<LightFalloffCurve>();
+    container.insert(InspectablePropertyEditorDefinition::<LightFalloffPoint>::new());
+    container.insert(VecCollectionPropertyEditorDefinition::<LightFalloffPoint>::new());
 
     container.register_inheritable_inspectable::<SkyBox>();
 
@@ -288,6 +304,7 @@ pub fn make_property_editors_container(sender: MessageSender) -> PropertyEditorD
     container.register_inheritable_enum::<Exposure, _>();
     container.register_inheritable_enum::<FrustumSplitOptions, _>();
     container.register_inheritable_enum::<MaterialSearchOptions, _>();
+    container.register_inheritable_enum::<ModelAxis, _>();
     container.register_inheritable_enum::<DistanceModel, _>();
     container.register_inheritable_enum::<sound::Renderer, _>();
     container.register_inheritable_enum::<RenderPath, _>();
@@ -351,11 +368,17 @@ pub fn make_property_editors_container(sender: MessageSender) -> PropertyEditorD
     container.insert(InspectablePropertyEditorDefinition::<OrNode>::new());
     container.insert(InspectablePropertyEditorDefinition::<XorNode>::new());
     container.insert(InspectablePropertyEditorDefinition::<NotNode>::new());
+    container.insert(InspectablePropertyEditorDefinition::<CompareNode>::new());
+    container.insert(EnumPropertyEditorDefinition::<ComparisonOperator>::new());
 
     container.insert(InspectablePropertyEditorDefinition::<ParticleSystemRng>::new());
     container.insert(EnumPropertyEditorDefinition::<PolygonFillMode>::new());
 
     container.insert(EnumPropertyEditorDefinition::<MipFilter>::new());
+    container.insert(EnumPropertyEditorDefinition::<UploadPriority>::new());
+
+    container.insert(EnumPropertyEditorDefinition::<ReflectionProbeShape>::new());
+    container.insert(EnumPropertyEditorDefinition::<ReflectionProbeUpdateMode>::new());
 
     container.register_inheritable_inspectable::<Limb>();
     container.insert(VecCollectionPropertyEditorDefinition::<Limb>::new());
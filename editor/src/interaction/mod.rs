@@ -123,7 +123,7 @@ pub fn calculate_gizmo_distance_scaling(
                     .global_position()
                     .metric_distance(&graph[camera].global_position())
         }
-        Projection::Orthographic(ortho) => 0.4 * ortho.vertical_size,
+        Projection::Orthographic(ortho) => 0.4 * ortho.zoomed_vertical_size(),
     };
 
     Vector3::new(s, s, s)
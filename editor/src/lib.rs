@@ -1914,7 +1914,7 @@ impl Editor {
     fn create_new_scene(&mut self) {
         let mut scene = Scene::new();
 
-        scene.ambient_lighting_color = Color::opaque(200, 200, 200);
+        scene.environment.ambient_lighting_color = Color::opaque(200, 200, 200);
 
         self.add_scene(scene, None);
     }
@@ -14,7 +14,7 @@ use fyrox::{
         window::{WindowBuilder, WindowTitle},
         Thickness, UiNode,
     },
-    utils::lightmap::Lightmap,
+    utils::lightmap::{Lightmap, LightmapSettings},
 };
 use std::{path::PathBuf, rc::Rc};
 
@@ -39,6 +39,57 @@ struct LightmapperSettings {
         step = 0.001
     )]
     spacing: f32,
+    #[reflect(
+        description = "Scale of the light emitted by surfaces with emissive materials. Every triangle of such \
+    surfaces is treated as an area light source. Zero disables emissive lighting. Default value is 1.0.",
+        min_value = 0.0,
+        step = 0.1
+    )]
+    emission_scale: f32,
+    #[reflect(
+        description = "Amount of indirect light bounces. Zero gives direct lighting only. Every bounce gathers light \
+    reflected by the surfaces of the scene, so each bounce takes much more time than direct lighting - its cost is \
+    proportional to the amount of texels multiplied by the amount of rays. Default value is 1.",
+        min_value = 0.0,
+        max_value = 8.0
+    )]
+    bounces: u32,
+    #[reflect(
+        description = "Scale of the indirect (bounced) light. Default value is 1.0.",
+        min_value = 0.0,
+        step = 0.1
+    )]
+    indirect_scale: f32,
+    #[reflect(
+        description = "Amount of rays per texel, that are used to gather bounced light. The more rays, the less \
+    noise in the indirect lighting. Default value is 64.",
+        min_value = 1.0,
+        max_value = 1024.0
+    )]
+    indirect_ray_count: u32,
+    #[reflect(
+        description = "Enables ambient occlusion - darkening of creases, corners and places near other objects."
+    )]
+    ambient_occlusion: bool,
+    #[reflect(
+        description = "Amount of rays per texel, that are used to calculate ambient occlusion. Default value is 32.",
+        min_value = 1.0,
+        max_value = 1024.0
+    )]
+    ao_ray_count: u32,
+    #[reflect(
+        description = "Maximum distance at which geometry occludes a texel. Default value is 1.0.",
+        min_value = 0.0,
+        step = 0.1
+    )]
+    ao_max_distance: f32,
+    #[reflect(
+        description = "Defines how much ambient occlusion darkens the lighting. Default value is 1.0.",
+        min_value = 0.0,
+        max_value = 1.0,
+        step = 0.05
+    )]
+    ao_strength: f32,
     #[reflect(
         description = "Path to the directory which will be used to save the generated light maps. Keep in mind, that \
     the lightmapper automatically generates names for the files."
@@ -48,14 +99,40 @@ struct LightmapperSettings {
 
 impl Default for LightmapperSettings {
     fn default() -> Self {
+        let defaults = LightmapSettings::default();
         Self {
             texels_per_unit: 64,
-            spacing: 0.005,
+            spacing: defaults.uv_spacing,
+            emission_scale: defaults.emission_scale,
+            bounces: defaults.bounces,
+            indirect_scale: defaults.indirect_scale,
+            indirect_ray_count: defaults.indirect_ray_count,
+            ambient_occlusion: defaults.ambient_occlusion,
+            ao_ray_count: defaults.ao_ray_count,
+            ao_max_distance: defaults.ao_max_distance,
+            ao_strength: defaults.ao_strength,
             path: Default::default(),
         }
     }
 }
 
+impl LightmapperSettings {
+    fn lightmap_settings(&self) -> LightmapSettings {
+        LightmapSettings {
+            texels_per_unit: self.texels_per_unit,
+            uv_spacing: self.spacing,
+            emission_scale: self.emission_scale,
+            bounces: self.bounces,
+            indirect_scale: self.indirect_scale,
+            indirect_ray_count: self.indirect_ray_count,
+            ambient_occlusion: self.ambient_occlusion,
+            ao_ray_count: self.ao_ray_count,
+            ao_max_distance: self.ao_max_distance,
+            ao_strength: self.ao_strength,
+        }
+    }
+}
+
 pub struct LightPanel {
     pub window: Handle<UiNode>,
     inspector: Handle<UiNode>,
@@ -148,8 +225,7 @@ impl LightPanel {
 
                 let lightmap = Lightmap::new(
                     scene,
-                    self.settings.texels_per_unit,
-                    self.settings.spacing,
+                    &self.settings.lightmap_settings(),
                     |handle, _| handle != editor_scene.editor_objects_root,
                     Default::default(),
                     Default::default(),
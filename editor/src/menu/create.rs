@@ -37,6 +37,7 @@ use fyrox::{
             ParticleSystemBuilder,
         },
         pivot::PivotBuilder,
+        reflection_probe::ReflectionProbeBuilder,
         sound::{listener::ListenerBuilder, SoundBuilder},
         sprite::SpriteBuilder,
         terrain::{Layer, TerrainBuilder},
@@ -90,6 +91,7 @@ pub struct CreateEntityMenu {
     create_spot_light: Handle<UiNode>,
     create_directional_light: Handle<UiNode>,
     create_navmesh: Handle<UiNode>,
+    create_reflection_probe: Handle<UiNode>,
     create_terrain: Handle<UiNode>,
     create_camera: Handle<UiNode>,
     create_sprite: Handle<UiNode>,
@@ -116,6 +118,7 @@ impl CreateEntityMenu {
         let create_sprite;
         let create_decal;
         let create_navmesh;
+        let create_reflection_probe;
         let create_particle_system;
         let create_terrain;
         let create_pivot;
@@ -218,6 +221,10 @@ impl CreateEntityMenu {
                 create_navmesh = create_menu_item("Navmesh", vec![], ctx);
                 create_navmesh
             },
+            {
+                create_reflection_probe = create_menu_item("Reflection Probe", vec![], ctx);
+                create_reflection_probe
+            },
         ];
 
         (
@@ -238,6 +245,7 @@ impl CreateEntityMenu {
                 create_sound_source,
                 create_listener,
                 create_navmesh,
+                create_reflection_probe,
                 create_decal,
                 physics_menu,
                 physics2d_menu,
@@ -379,6 +387,13 @@ impl CreateEntityMenu {
                         )
                     } else if message.destination() == self.create_decal {
                         Some(DecalBuilder::new(BaseBuilder::new().with_name("Decal")).build_node())
+                    } else if message.destination() == self.create_reflection_probe {
+                        Some(
+                            ReflectionProbeBuilder::new(
+                                BaseBuilder::new().with_name("ReflectionProbe"),
+                            )
+                            .build_node(),
+                        )
                     } else if message.destination() == self.create_listener {
                         Some(
                             ListenerBuilder::new(BaseBuilder::new().with_name("Listener"))
@@ -157,7 +157,7 @@ impl PreviewPanel {
         )
         .build(&mut scene.graph);
 
-        scene.ambient_lighting_color = Color::opaque(80, 80, 80);
+        scene.environment.ambient_lighting_color = Color::opaque(80, 80, 80);
 
         let render_target = TextureResource::new_render_target(width, height);
         scene.render_target = Some(render_target.clone());
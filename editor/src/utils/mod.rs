@@ -126,7 +126,7 @@ pub fn is_native_scene(path: &Path) -> bool {
     if let Ok(mut file) = File::open(path) {
         let mut magic: [u8; 4] = Default::default();
         if file.read_exact(&mut magic).is_ok() {
-            return magic.eq(Visitor::MAGIC.as_bytes());
+            return Visitor::is_supported_magic(&magic);
         }
     }
     false
@@ -38,7 +38,7 @@ impl SceneLoader {
     fn load_with(resource_manager: ResourceManager) -> Self {
         let mut scene = Scene::new();
 
-        scene.ambient_lighting_color = Color::opaque(50, 50, 50);
+        scene.environment.ambient_lighting_color = Color::opaque(50, 50, 50);
 
         // Create camera first.
         let camera = CameraBuilder::new(BaseBuilder::new())
@@ -46,6 +46,7 @@ impl SceneLoader {
                 z_near: -0.1,
                 z_far: 16.0,
                 vertical_size: 4.0,
+                ..Default::default()
             }))
             .build(&mut scene.graph);
 
@@ -100,7 +100,7 @@ impl SceneLoader {
         let mut scene = Scene::new();
 
         // Set ambient light.
-        scene.ambient_lighting_color = Color::opaque(200, 200, 200);
+        scene.environment.ambient_lighting_color = Color::opaque(200, 200, 200);
 
         // It is important to lock context for short period of time so other thread can
         // read data from it as soon as possible - not when everything was loaded.
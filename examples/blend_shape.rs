@@ -47,7 +47,7 @@ impl GameSceneLoader {
     fn load_with(resource_manager: ResourceManager, ui: &mut UserInterface) -> Self {
         let mut scene = Scene::new();
 
-        scene.ambient_lighting_color = Color::opaque(150, 150, 150);
+        scene.environment.ambient_lighting_color = Color::opaque(150, 150, 150);
 
         block_on(create_camera(
             resource_manager.clone(),
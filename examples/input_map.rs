@@ -0,0 +1,198 @@
+//! Example - Input mapping.
+//!
+//! Difficulty: Easy.
+//!
+//! This example shows how to use input map to bind named actions and axes to keyboard, mouse and
+//! gamepad, and how to rebind them at runtime. Use A/D keys (or the left stick of a gamepad) to
+//! change the color of the window, Space (or the bottom face button) to reset it. Press F1 and
+//! then any key to rebind the reset action. The map is saved to `input_map.txt` on exit, the file
+//! is human-readable and could be edited by hand.
+
+use fyrox::{
+    core::{
+        color::{Color, Hsv},
+        futures::executor::block_on,
+        log::Log,
+        pool::Handle,
+        visitor::VisitorFormat,
+    },
+    engine::{executor::Executor, GraphicsContext, GraphicsContextParams},
+    event::{ElementState, Event, WindowEvent},
+    event_loop::ControlFlow,
+    keyboard::KeyCode,
+    plugin::{Plugin, PluginConstructor, PluginContext},
+    scene::Scene,
+    utils::input::{GamepadAxis, GamepadButton, InputBinding, InputMap, InputSource, InputState},
+    window::WindowAttributes,
+};
+
+const INPUT_MAP_PATH: &str = "input_map.txt";
+
+fn default_input_map() -> InputMap {
+    let mut map = InputMap::new();
+    map.add_action(
+        "Reset",
+        vec![
+            InputBinding::key(KeyCode::Space),
+            InputBinding::gamepad_button(GamepadButton::South),
+        ],
+    );
+    map.add_axis(
+        "Hue",
+        vec![
+            InputBinding::key(KeyCode::KeyD),
+            InputBinding::key(KeyCode::KeyA).inverted(),
+            InputBinding::gamepad_axis(GamepadAxis::LeftStickX),
+        ],
+    );
+    map.add_axis(
+        "Brightness",
+        vec![
+            InputBinding::key(KeyCode::KeyW),
+            InputBinding::key(KeyCode::KeyS).inverted(),
+            InputBinding::gamepad_axis(GamepadAxis::LeftStickY),
+        ],
+    );
+    map
+}
+
+struct Game {
+    state: InputState,
+    map: InputMap,
+    waiting_for_rebind: bool,
+    hue: f32,
+    brightness: f32,
+}
+
+impl Game {
+    fn rebind_reset(&mut self, key: KeyCode) {
+        let new = InputSource::Key(key);
+        let old = self
+            .map
+            .action("Reset")
+            .and_then(|a| {
+                a.bindings
+                    .iter()
+                    .find(|b| matches!(b.source, InputSource::Key(_)))
+            })
+            .map(|b| b.source);
+
+        match old {
+            Some(old) => {
+                self.map.rebind("Reset", old, new);
+            }
+            None => {
+                if let Some(action) = self.map.action_mut("Reset") {
+                    action.bindings.push(InputBinding::new(new));
+                }
+            }
+        }
+
+        Log::info(format!("Reset action is now bound to {:?}", key));
+
+        for conflict in self.map.conflicts() {
+            Log::warn(format!(
+                "{:?} is used by both {} and {}",
+                conflict.source, conflict.first, conflict.second
+            ));
+        }
+    }
+}
+
+impl Plugin for Game {
+    fn on_deinit(&mut self, _context: PluginContext) {
+        if let Err(e) = self.map.save(INPUT_MAP_PATH, VisitorFormat::Text) {
+            Log::err(format!("Unable to save input map. Reason: {:?}", e));
+        }
+    }
+
+    fn update(&mut self, context: &mut PluginContext, _control_flow: &mut ControlFlow) {
+        self.map.update(&self.state);
+        self.state.end_frame();
+
+        self.hue += self.map.axis_value("Hue").clamp(-1.0, 1.0) * 90.0 * context.dt;
+        self.brightness = (self.brightness
+            + self.map.axis_value("Brightness").clamp(-1.0, 1.0) * 50.0 * context.dt)
+            .clamp(0.0, 100.0);
+
+        if self.map.is_action_just_pressed("Reset") {
+            self.hue = 0.0;
+            self.brightness = 100.0;
+        }
+
+        if let GraphicsContext::Initialized(ref mut graphics_context) = context.graphics_context {
+            graphics_context
+                .renderer
+                .set_backbuffer_clear_color(Color::from(Hsv::new(
+                    self.hue.rem_euclid(360.0),
+                    100.0,
+                    self.brightness,
+                )))
+        }
+    }
+
+    fn on_os_event(
+        &mut self,
+        event: &Event<()>,
+        _context: PluginContext,
+        _control_flow: &mut ControlFlow,
+    ) {
+        if let Event::WindowEvent {
+            event: WindowEvent::KeyboardInput { event: input, .. },
+            ..
+        } = event
+        {
+            if input.state == ElementState::Pressed {
+                if self.waiting_for_rebind {
+                    self.waiting_for_rebind = false;
+                    self.rebind_reset(input.physical_key);
+                    return;
+                } else if input.physical_key == KeyCode::F1 {
+                    Log::info("Press any key to rebind Reset action.");
+                    self.waiting_for_rebind = true;
+                    return;
+                }
+            }
+        }
+
+        // The engine does not read gamepads, feed gamepad events from a gamepad library here,
+        // for example: `self.state.set_gamepad_axis(0, GamepadAxis::LeftStickX, value)`.
+        self.state.process_event(event);
+    }
+}
+
+struct GameConstructor;
+
+impl PluginConstructor for GameConstructor {
+    fn create_instance(
+        &self,
+        _override_scene: Handle<Scene>,
+        _context: PluginContext,
+    ) -> Box<dyn Plugin> {
+        // Use previously saved bindings, if any.
+        let map = block_on(InputMap::load(INPUT_MAP_PATH)).unwrap_or_else(|_| default_input_map());
+
+        Box::new(Game {
+            state: InputState::new(),
+            map,
+            waiting_for_rebind: false,
+            hue: 0.0,
+            brightness: 100.0,
+        })
+    }
+}
+
+fn main() {
+    let mut executor = Executor::from_params(
+        Default::default(),
+        GraphicsContextParams {
+            window_attributes: WindowAttributes {
+                title: "Example - Input Map".to_string(),
+                ..Default::default()
+            },
+            vsync: true,
+        },
+    );
+    executor.add_plugin_constructor(GameConstructor);
+    executor.run()
+}
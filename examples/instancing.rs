@@ -57,7 +57,7 @@ impl SceneLoader {
         let mut scene = Scene::new();
 
         // Set ambient light.
-        scene.ambient_lighting_color = Color::opaque(100, 100, 100);
+        scene.environment.ambient_lighting_color = Color::opaque(100, 100, 100);
 
         // Camera is our eyes in the world - you won't see anything without it.
         let camera = create_camera(
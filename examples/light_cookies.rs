@@ -0,0 +1,293 @@
+//! Example - Light cookies and falloff curves.
+//!
+//! Difficulty: Easy.
+//!
+//! This example shows how to use cookie textures and custom falloff curves of lights. The spot light
+//! projects a procedural "window" pattern, the point light uses latitude-longitude cookie with
+//! stripes and a falloff curve with a bright ring.
+//!
+//! Controls: `C` - toggle cookies, `F` - toggle falloff curve.
+
+use fyrox::{
+    core::{
+        algebra::{Matrix4, UnitQuaternion, Vector3},
+        color::Color,
+        pool::Handle,
+    },
+    engine::{executor::Executor, GraphicsContext, GraphicsContextParams},
+    event::{ElementState, Event, WindowEvent},
+    event_loop::ControlFlow,
+    gui::{
+        message::MessageDirection,
+        text::{TextBuilder, TextMessage},
+        widget::WidgetBuilder,
+        UiNode,
+    },
+    plugin::{Plugin, PluginConstructor, PluginContext},
+    resource::texture::{TextureKind, TexturePixelKind, TextureResource, TextureResourceExtension},
+    scene::{
+        base::BaseBuilder,
+        camera::CameraBuilder,
+        light::{
+            falloff::{LightFalloffCurve, LightFalloffPoint},
+            point::{PointLight, PointLightBuilder},
+            spot::{SpotLight, SpotLightBuilder},
+            BaseLightBuilder,
+        },
+        mesh::{
+            surface::{Surface, SurfaceBuilder, SurfaceData, SurfaceSharedData},
+            MeshBuilder,
+        },
+        node::Node,
+        transform::TransformBuilder,
+        Scene,
+    },
+    window::WindowAttributes,
+};
+use winit::keyboard::KeyCode;
+
+// 2x2 window frame, black frame lines on white background.
+fn make_window_cookie() -> TextureResource {
+    let size = 64;
+    let mut bytes = Vec::with_capacity(size * size);
+    for y in 0..size {
+        for x in 0..size {
+            let is_frame =
+                |c: usize| c < 4 || c >= size - 4 || (c as i32 - size as i32 / 2).abs() < 2;
+            bytes.push(if is_frame(x) || is_frame(y) { 0 } else { 255 });
+        }
+    }
+    TextureResource::from_bytes(
+        TextureKind::Rectangle {
+            width: size as u32,
+            height: size as u32,
+        },
+        TexturePixelKind::R8,
+        bytes,
+        false,
+    )
+    .unwrap()
+}
+
+// Latitude-longitude texture with colored vertical stripes.
+fn make_stripes_cookie() -> TextureResource {
+    let (width, height) = (128, 64);
+    let mut bytes = Vec::with_capacity(width * height * 3);
+    for _ in 0..height {
+        for x in 0..width {
+            let color = match (x / 8) % 4 {
+                0 => [255, 60, 60],
+                1 => [60, 255, 60],
+                2 => [60, 60, 255],
+                _ => [0, 0, 0],
+            };
+            bytes.extend_from_slice(&color);
+        }
+    }
+    TextureResource::from_bytes(
+        TextureKind::Rectangle {
+            width: width as u32,
+            height: height as u32,
+        },
+        TexturePixelKind::RGB8,
+        bytes,
+        false,
+    )
+    .unwrap()
+}
+
+fn make_falloff_curve() -> LightFalloffCurve {
+    LightFalloffCurve::new(vec![
+        LightFalloffPoint::new(0.0, 1.0),
+        LightFalloffPoint::new(0.3, 0.2),
+        LightFalloffPoint::new(0.5, 1.0),
+        LightFalloffPoint::new(0.6, 0.2),
+        LightFalloffPoint::new(1.0, 0.0),
+    ])
+}
+
+fn create_scene() -> (Scene, Handle<Node>, Handle<Node>) {
+    let mut scene = Scene::new();
+
+    scene.environment.ambient_lighting_color = Color::opaque(30, 30, 30);
+
+    CameraBuilder::new(
+        BaseBuilder::new().with_local_transform(
+            TransformBuilder::new()
+                .with_local_position(Vector3::new(0.0, 6.0, -10.0))
+                .with_local_rotation(UnitQuaternion::from_axis_angle(
+                    &Vector3::x_axis(),
+                    30.0f32.to_radians(),
+                ))
+                .build(),
+        ),
+    )
+    .build(&mut scene.graph);
+
+    MeshBuilder::new(BaseBuilder::new())
+        .with_surface(Surface::plane(30.0, 30.0, 1, 1))
+        .build(&mut scene.graph);
+
+    // A few cubes to catch the patterns.
+    for (x, z) in [(-4.0, 2.0), (3.0, 3.0), (0.0, 5.0)] {
+        MeshBuilder::new(
+            BaseBuilder::new().with_local_transform(
+                TransformBuilder::new()
+                    .with_local_position(Vector3::new(x, 1.0, z))
+                    .build(),
+            ),
+        )
+        .with_surfaces(vec![SurfaceBuilder::new(SurfaceSharedData::new(
+            SurfaceData::make_cube(Matrix4::new_scaling(2.0)),
+        ))
+        .build()])
+        .build(&mut scene.graph);
+    }
+
+    let spot_light = SpotLightBuilder::new(BaseLightBuilder::new(
+        BaseBuilder::new().with_local_transform(
+            TransformBuilder::new()
+                .with_local_position(Vector3::new(-5.0, 8.0, 0.0))
+                .with_local_rotation(UnitQuaternion::from_axis_angle(
+                    &Vector3::z_axis(),
+                    -30.0f32.to_radians(),
+                ))
+                .build(),
+        ),
+    ))
+    .with_distance(20.0)
+    .with_hotspot_cone_angle(40.0f32.to_radians())
+    .with_cookie_texture(make_window_cookie())
+    .build(&mut scene.graph);
+
+    let point_light = PointLightBuilder::new(BaseLightBuilder::new(
+        BaseBuilder::new().with_local_transform(
+            TransformBuilder::new()
+                .with_local_position(Vector3::new(2.0, 2.5, 0.0))
+                .build(),
+        ),
+    ))
+    .with_radius(12.0)
+    .with_cookie_texture(make_stripes_cookie())
+    .with_falloff_curve(make_falloff_curve())
+    .build(&mut scene.graph);
+
+    (scene, spot_light, point_light)
+}
+
+struct Game {
+    scene: Handle<Scene>,
+    spot_light: Handle<Node>,
+    point_light: Handle<Node>,
+    cookies: bool,
+    falloff: bool,
+    angle: f32,
+    debug_text: Handle<UiNode>,
+}
+
+impl Plugin for Game {
+    fn update(&mut self, context: &mut PluginContext, _control_flow: &mut ControlFlow) {
+        let graph = &mut context.scenes[self.scene].graph;
+
+        // Rotate the point light, so its cookie rotates too.
+        self.angle += context.dt;
+        graph[self.point_light].local_transform_mut().set_rotation(
+            UnitQuaternion::from_axis_angle(&Vector3::y_axis(), self.angle),
+        );
+
+        if let GraphicsContext::Initialized(ref graphics_context) = context.graphics_context {
+            context.user_interface.send_message(TextMessage::text(
+                self.debug_text,
+                MessageDirection::ToWidget,
+                format!(
+                    "Example - Light Cookies\n\
+                    C - toggle cookies ({}), F - toggle falloff curve ({})\n\
+                    {}",
+                    self.cookies,
+                    self.falloff,
+                    graphics_context.renderer.get_statistics().lighting
+                ),
+            ));
+        }
+    }
+
+    fn on_os_event(
+        &mut self,
+        event: &Event<()>,
+        context: PluginContext,
+        _control_flow: &mut ControlFlow,
+    ) {
+        if let Event::WindowEvent {
+            event: WindowEvent::KeyboardInput { event: input, .. },
+            ..
+        } = event
+        {
+            if input.state != ElementState::Pressed || input.repeat {
+                return;
+            }
+
+            let graph = &mut context.scenes[self.scene].graph;
+            match input.physical_key {
+                KeyCode::KeyC => {
+                    self.cookies = !self.cookies;
+                    let spot_cookie = self.cookies.then(make_window_cookie);
+                    let point_cookie = self.cookies.then(make_stripes_cookie);
+                    graph[self.spot_light]
+                        .cast_mut::<SpotLight>()
+                        .unwrap()
+                        .set_cookie_texture(spot_cookie);
+                    graph[self.point_light]
+                        .cast_mut::<PointLight>()
+                        .unwrap()
+                        .set_cookie_texture(point_cookie);
+                }
+                KeyCode::KeyF => {
+                    self.falloff = !self.falloff;
+                    graph[self.point_light]
+                        .cast_mut::<PointLight>()
+                        .unwrap()
+                        .set_falloff_curve(self.falloff.then(make_falloff_curve));
+                }
+                _ => (),
+            }
+        }
+    }
+}
+
+struct GameConstructor;
+
+impl PluginConstructor for GameConstructor {
+    fn create_instance(
+        &self,
+        _override_scene: Handle<Scene>,
+        context: PluginContext,
+    ) -> Box<dyn Plugin> {
+        let (scene, spot_light, point_light) = create_scene();
+
+        Box::new(Game {
+            scene: context.scenes.add(scene),
+            spot_light,
+            point_light,
+            cookies: true,
+            falloff: true,
+            angle: 0.0,
+            debug_text: TextBuilder::new(WidgetBuilder::new())
+                .build(&mut context.user_interface.build_ctx()),
+        })
+    }
+}
+
+fn main() {
+    let mut executor = Executor::from_params(
+        Default::default(),
+        GraphicsContextParams {
+            window_attributes: WindowAttributes {
+                title: "Example - Light Cookies".to_string(),
+                ..Default::default()
+            },
+            vsync: true,
+        },
+    );
+    executor.add_plugin_constructor(GameConstructor);
+    executor.run()
+}
@@ -37,7 +37,9 @@ use fyrox::{
     resource::model::{Model, ModelResourceExtension},
     scene::{light::BaseLight, node::Node, Scene, SceneLoader},
     utils::{
-        lightmap::{CancellationToken, Lightmap, ProgressIndicator, ProgressStage},
+        lightmap::{
+            CancellationToken, Lightmap, LightmapSettings, ProgressIndicator, ProgressStage,
+        },
         translate_event,
     },
     window::WindowAttributes,
@@ -227,7 +229,7 @@ fn create_scene_async(
                 let mut scene = Scene::new();
 
                 // Set ambient light.
-                scene.ambient_lighting_color = Color::opaque(80, 80, 80);
+                scene.environment.ambient_lighting_color = Color::opaque(80, 80, 80);
 
                 // Camera is our eyes in the world - you won't see anything without it.
                 create_camera(
@@ -247,8 +249,10 @@ fn create_scene_async(
 
                 if let Ok(lightmap) = Lightmap::new(
                     &mut scene,
-                    64,
-                    0.005,
+                    &LightmapSettings {
+                        texels_per_unit: 64,
+                        ..Default::default()
+                    },
                     |_, _| true,
                     cancellation_token,
                     progress_indicator,
@@ -407,6 +411,10 @@ fn main() {
                             ProgressStage::UvGeneration => "Generating UVs",
                             ProgressStage::GeometryCaching => "Caching Geometry",
                             ProgressStage::CalculatingLight => "Calculating Light",
+                            ProgressStage::CalculatingIndirectLight => "Calculating Indirect Light",
+                            ProgressStage::CalculatingAmbientOcclusion => {
+                                "Calculating Ambient Occlusion"
+                            }
                         };
 
                         let message = if load_context.generate_lightmap {
@@ -103,7 +103,7 @@ impl PluginConstructor for GameConstructor {
         let mut scene = Scene::new();
 
         // Set ambient light.
-        scene.ambient_lighting_color = Color::opaque(0, 0, 0);
+        scene.environment.ambient_lighting_color = Color::opaque(0, 0, 0);
 
         // Camera is our eyes in the world - you won't see anything without it.
         block_on(create_camera(
@@ -11,13 +11,12 @@ use fyrox::scene::node::NodeTrait;
 use fyrox::{
     asset::manager::ResourceManager,
     core::{
-        algebra::{Matrix4, Point3, UnitQuaternion, Vector2, Vector3},
+        algebra::{Matrix4, Point3, UnitQuaternion, Vector3},
         arrayvec::ArrayVec,
         color::Color,
         pool::Handle,
         sstorage::ImmutableString,
     },
-    dpi::LogicalPosition,
     engine::{executor::Executor, GraphicsContext, GraphicsContextParams},
     event::{ElementState, Event, WindowEvent},
     event_loop::ControlFlow,
@@ -62,7 +61,7 @@ async fn create_scene(resource_manager: ResourceManager) -> GameScene {
     let mut scene = Scene::new();
 
     // Set ambient light.
-    scene.ambient_lighting_color = Color::opaque(200, 200, 200);
+    scene.environment.ambient_lighting_color = Color::opaque(200, 200, 200);
 
     // Camera is our eyes in the world - you won't see anything without it.
     let camera = create_camera(
@@ -144,7 +143,6 @@ struct Game {
     cursor: Handle<Node>,
     camera: Handle<Node>,
     target_position: Vector3<f32>,
-    mouse_position: Vector2<f32>,
     navmesh_agent: NavmeshAgent,
     debug_text: Handle<UiNode>,
 }
@@ -159,7 +157,7 @@ impl Plugin for Game {
 
         if let GraphicsContext::Initialized(ref graphics_context) = context.graphics_context {
             let ray = scene.graph[self.camera].as_camera().make_ray(
-                self.mouse_position,
+                context.input.cursor_position_logical(),
                 graphics_context.renderer.get_frame_bounds(),
             );
 
@@ -222,28 +220,20 @@ impl Plugin for Game {
     fn on_os_event(
         &mut self,
         event: &Event<()>,
-        context: PluginContext,
+        _context: PluginContext,
         _control_flow: &mut ControlFlow,
     ) {
-        if let Event::WindowEvent { event, .. } = event {
-            match event {
-                WindowEvent::KeyboardInput { event: input, .. } => match input.physical_key {
-                    KeyCode::KeyA => {
-                        self.input_controller.rotate_left = input.state == ElementState::Pressed
-                    }
-                    KeyCode::KeyD => {
-                        self.input_controller.rotate_right = input.state == ElementState::Pressed
-                    }
-                    _ => (),
-                },
-                WindowEvent::CursorMoved { position, .. } => {
-                    if let GraphicsContext::Initialized(ref graphics_context) =
-                        context.graphics_context
-                    {
-                        let p: LogicalPosition<f32> =
-                            position.to_logical(graphics_context.window.scale_factor());
-                        self.mouse_position = Vector2::new(p.x, p.y);
-                    }
+        if let Event::WindowEvent {
+            event: WindowEvent::KeyboardInput { event: input, .. },
+            ..
+        } = event
+        {
+            match input.physical_key {
+                KeyCode::KeyA => {
+                    self.input_controller.rotate_left = input.state == ElementState::Pressed
+                }
+                KeyCode::KeyD => {
+                    self.input_controller.rotate_right = input.state == ElementState::Pressed
                 }
                 _ => (),
             }
@@ -286,7 +276,6 @@ impl PluginConstructor for GameConstructor {
             cursor,
             camera,
             target_position: Default::default(),
-            mouse_position: Default::default(),
             navmesh_agent,
             debug_text,
         })
@@ -49,7 +49,7 @@ async fn create_scene(resource_manager: ResourceManager) -> GameScene {
     let mut scene = Scene::new();
 
     // Set ambient light.
-    scene.ambient_lighting_color = Color::opaque(80, 80, 80);
+    scene.environment.ambient_lighting_color = Color::opaque(80, 80, 80);
 
     // Camera is our eyes in the world - you won't see anything without it.
     let pivot = PivotBuilder::new(
@@ -355,6 +355,7 @@ impl LocomotionMachine {
             // the model explicitly (via keys, not via root motion). This can be improved, but will
             // significantly increase complexity of the example.
             ignore_rotations: true,
+            yaw_only: false,
         };
 
         let animations_container = scene.graph[animation_player]
@@ -786,7 +787,7 @@ pub fn create_scene_async(resource_manager: ResourceManager) -> Arc<Mutex<SceneL
             let mut scene = Scene::new();
 
             // Set ambient light.
-            scene.ambient_lighting_color = Color::opaque(80, 80, 80);
+            scene.environment.ambient_lighting_color = Color::opaque(80, 80, 80);
 
             // Create reverb effect for more natural sound - our player walks in some sort of cathedral,
             // so there will be pretty decent echo.
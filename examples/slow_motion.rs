@@ -0,0 +1,225 @@
+//! Example - Slow motion.
+//!
+//! Difficulty: Easy.
+//!
+//! This example shows how to use time scale of a scene to make slow motion or to pause the game,
+//! while the user interface keeps working with real time.
+//!
+//! Controls: `1` - normal speed, `2` - slow motion, `Space` - pause/resume, `R` - throw cubes again.
+
+use fyrox::{
+    core::{
+        algebra::{Matrix4, UnitQuaternion, Vector3},
+        color::Color,
+        pool::Handle,
+    },
+    engine::{executor::Executor, GraphicsContextParams},
+    event::{ElementState, Event, WindowEvent},
+    event_loop::ControlFlow,
+    gui::{
+        message::MessageDirection,
+        text::{TextBuilder, TextMessage},
+        widget::WidgetBuilder,
+        UiNode,
+    },
+    plugin::{Plugin, PluginConstructor, PluginContext},
+    scene::{
+        base::BaseBuilder,
+        camera::CameraBuilder,
+        collider::{ColliderBuilder, ColliderShape},
+        graph::Graph,
+        light::{point::PointLightBuilder, BaseLightBuilder},
+        mesh::{
+            surface::{Surface, SurfaceBuilder, SurfaceData, SurfaceSharedData},
+            MeshBuilder,
+        },
+        node::Node,
+        rigidbody::{RigidBodyBuilder, RigidBodyType},
+        transform::TransformBuilder,
+        Scene,
+    },
+    window::WindowAttributes,
+};
+use winit::keyboard::KeyCode;
+
+const SLOW_MOTION_SCALE: f32 = 0.2;
+
+fn create_cubes(graph: &mut Graph) -> Vec<Handle<Node>> {
+    let mut cubes = Vec::new();
+    for y in 0..4 {
+        for x in 0..4 {
+            let mesh = MeshBuilder::new(BaseBuilder::new())
+                .with_surfaces(vec![SurfaceBuilder::new(SurfaceSharedData::new(
+                    SurfaceData::make_cube(Matrix4::new_scaling(0.5)),
+                ))
+                .build()])
+                .build(graph);
+            let collider = ColliderBuilder::new(BaseBuilder::new())
+                .with_shape(ColliderShape::cuboid(0.25, 0.25, 0.25))
+                .build(graph);
+            let body = RigidBodyBuilder::new(
+                BaseBuilder::new()
+                    .with_local_transform(
+                        TransformBuilder::new()
+                            .with_local_position(Vector3::new(
+                                x as f32 * 0.6 - 1.0,
+                                3.0 + y as f32 * 0.6,
+                                x as f32 * 0.1,
+                            ))
+                            .with_local_rotation(UnitQuaternion::from_euler_angles(
+                                0.3 * y as f32,
+                                0.2 * x as f32,
+                                0.0,
+                            ))
+                            .build(),
+                    )
+                    .with_children(&[mesh, collider]),
+            )
+            .with_lin_vel(Vector3::new(0.0, 2.0, 0.0))
+            .build(graph);
+            cubes.push(body);
+        }
+    }
+    cubes
+}
+
+fn create_scene() -> (Scene, Vec<Handle<Node>>) {
+    let mut scene = Scene::new();
+
+    scene.environment.ambient_lighting_color = Color::opaque(100, 100, 100);
+
+    CameraBuilder::new(
+        BaseBuilder::new().with_local_transform(
+            TransformBuilder::new()
+                .with_local_position(Vector3::new(0.0, 3.0, -8.0))
+                .with_local_rotation(UnitQuaternion::from_axis_angle(
+                    &Vector3::x_axis(),
+                    15.0f32.to_radians(),
+                ))
+                .build(),
+        ),
+    )
+    .build(&mut scene.graph);
+
+    PointLightBuilder::new(BaseLightBuilder::new(
+        BaseBuilder::new().with_local_transform(
+            TransformBuilder::new()
+                .with_local_position(Vector3::new(0.0, 6.0, -3.0))
+                .build(),
+        ),
+    ))
+    .with_radius(15.0)
+    .build(&mut scene.graph);
+
+    // Ground.
+    let ground_mesh = MeshBuilder::new(BaseBuilder::new())
+        .with_surface(Surface::plane(20.0, 20.0, 1, 1))
+        .build(&mut scene.graph);
+    let ground_collider = ColliderBuilder::new(BaseBuilder::new())
+        .with_shape(ColliderShape::cuboid(10.0, 0.01, 10.0))
+        .build(&mut scene.graph);
+    RigidBodyBuilder::new(BaseBuilder::new().with_children(&[ground_mesh, ground_collider]))
+        .with_body_type(RigidBodyType::Static)
+        .build(&mut scene.graph);
+
+    let cubes = create_cubes(&mut scene.graph);
+
+    (scene, cubes)
+}
+
+struct Game {
+    scene: Handle<Scene>,
+    cubes: Vec<Handle<Node>>,
+    paused: bool,
+    time_scale: f32,
+    debug_text: Handle<UiNode>,
+}
+
+impl Plugin for Game {
+    fn update(&mut self, context: &mut PluginContext, _control_flow: &mut ControlFlow) {
+        let scene = &mut context.scenes[self.scene];
+
+        // Time scale of the scene affects physics, animations, particles, sounds and scripts of
+        // the scene. The user interface still receives real time delta.
+        scene.time_scale = if self.paused { 0.0 } else { self.time_scale };
+
+        context.user_interface.send_message(TextMessage::text(
+            self.debug_text,
+            MessageDirection::ToWidget,
+            format!(
+                "Example - Slow Motion\n\
+                1 - normal speed, 2 - slow motion, Space - pause, R - throw cubes again\n\
+                Time scale: {}{}",
+                self.time_scale,
+                if self.paused { " (paused)" } else { "" }
+            ),
+        ));
+    }
+
+    fn on_os_event(
+        &mut self,
+        event: &Event<()>,
+        context: PluginContext,
+        _control_flow: &mut ControlFlow,
+    ) {
+        if let Event::WindowEvent {
+            event: WindowEvent::KeyboardInput { event: input, .. },
+            ..
+        } = event
+        {
+            if input.state != ElementState::Pressed || input.repeat {
+                return;
+            }
+
+            match input.physical_key {
+                KeyCode::Digit1 => self.time_scale = 1.0,
+                KeyCode::Digit2 => self.time_scale = SLOW_MOTION_SCALE,
+                KeyCode::Space => self.paused = !self.paused,
+                KeyCode::KeyR => {
+                    let graph = &mut context.scenes[self.scene].graph;
+                    for cube in self.cubes.drain(..) {
+                        graph.remove_node(cube);
+                    }
+                    self.cubes = create_cubes(graph);
+                }
+                _ => (),
+            }
+        }
+    }
+}
+
+struct GameConstructor;
+
+impl PluginConstructor for GameConstructor {
+    fn create_instance(
+        &self,
+        _override_scene: Handle<Scene>,
+        context: PluginContext,
+    ) -> Box<dyn Plugin> {
+        let (scene, cubes) = create_scene();
+
+        Box::new(Game {
+            scene: context.scenes.add(scene),
+            cubes,
+            paused: false,
+            time_scale: 1.0,
+            debug_text: TextBuilder::new(WidgetBuilder::new())
+                .build(&mut context.user_interface.build_ctx()),
+        })
+    }
+}
+
+fn main() {
+    let mut executor = Executor::from_params(
+        Default::default(),
+        GraphicsContextParams {
+            window_attributes: WindowAttributes {
+                title: "Example - Slow Motion".to_string(),
+                ..Default::default()
+            },
+            vsync: true,
+        },
+    );
+    executor.add_plugin_constructor(GameConstructor);
+    executor.run()
+}
@@ -0,0 +1,263 @@
+//! Example - Sound occlusion.
+//!
+//! Difficulty: Easy.
+//!
+//! This example shows how to make sounds muffled when there's an obstacle between a sound source
+//! and the listener. A buzzing sound source is placed behind a wall, walk around the wall to hear
+//! the difference.
+//!
+//! Controls: `A`/`D` - move left/right, `O` - toggle occlusion.
+
+use fyrox::{
+    core::{
+        algebra::{Matrix4, UnitQuaternion, Vector3},
+        color::Color,
+        pool::Handle,
+    },
+    engine::{executor::Executor, GraphicsContextParams},
+    event::{ElementState, Event, WindowEvent},
+    event_loop::ControlFlow,
+    gui::{
+        message::MessageDirection,
+        text::{TextBuilder, TextMessage},
+        widget::WidgetBuilder,
+        UiNode,
+    },
+    plugin::{Plugin, PluginConstructor, PluginContext},
+    scene::{
+        base::BaseBuilder,
+        camera::CameraBuilder,
+        collider::{ColliderBuilder, ColliderShape},
+        light::{point::PointLightBuilder, BaseLightBuilder},
+        mesh::{
+            surface::{Surface, SurfaceBuilder, SurfaceData, SurfaceSharedData},
+            MeshBuilder,
+        },
+        node::Node,
+        rigidbody::{RigidBodyBuilder, RigidBodyType},
+        sound::{
+            listener::ListenerBuilder, occlusion::SoundOcclusion, DataSource, SoundBufferResource,
+            SoundBuilder, Status, SAMPLE_RATE,
+        },
+        transform::TransformBuilder,
+        Scene,
+    },
+    window::WindowAttributes,
+};
+use fyrox_sound::buffer::SoundBufferResourceExtension;
+use winit::keyboard::KeyCode;
+
+const SPEED: f32 = 4.0;
+
+// One second of a sawtooth wave - it has lots of high frequencies, so the lowpass filter of an
+// occluded sound is clearly audible.
+fn make_buzz() -> SoundBufferResource {
+    let frequency = 110.0;
+    let samples = (0..SAMPLE_RATE)
+        .map(|i| {
+            let t = i as f32 * frequency / SAMPLE_RATE as f32;
+            0.5 * (2.0 * t.fract() - 1.0)
+        })
+        .collect();
+    SoundBufferResource::new_generic(DataSource::Raw {
+        sample_rate: SAMPLE_RATE as usize,
+        channel_count: 1,
+        samples,
+    })
+    .unwrap()
+}
+
+fn create_scene() -> (Scene, Handle<Node>, Handle<Node>) {
+    let mut scene = Scene::new();
+
+    scene.environment.ambient_lighting_color = Color::opaque(100, 100, 100);
+
+    let listener = ListenerBuilder::new(BaseBuilder::new()).build(&mut scene.graph);
+    let camera = CameraBuilder::new(
+        BaseBuilder::new()
+            .with_local_transform(
+                TransformBuilder::new()
+                    .with_local_position(Vector3::new(0.0, 1.5, -6.0))
+                    .with_local_rotation(UnitQuaternion::from_axis_angle(
+                        &Vector3::x_axis(),
+                        10.0f32.to_radians(),
+                    ))
+                    .build(),
+            )
+            .with_children(&[listener]),
+    )
+    .build(&mut scene.graph);
+
+    PointLightBuilder::new(BaseLightBuilder::new(
+        BaseBuilder::new().with_local_transform(
+            TransformBuilder::new()
+                .with_local_position(Vector3::new(0.0, 6.0, -3.0))
+                .build(),
+        ),
+    ))
+    .with_radius(20.0)
+    .build(&mut scene.graph);
+
+    MeshBuilder::new(BaseBuilder::new())
+        .with_surface(Surface::plane(20.0, 20.0, 1, 1))
+        .build(&mut scene.graph);
+
+    // The wall.
+    let wall_mesh = MeshBuilder::new(BaseBuilder::new())
+        .with_surfaces(vec![SurfaceBuilder::new(SurfaceSharedData::new(
+            SurfaceData::make_cube(Matrix4::new_nonuniform_scaling(&Vector3::new(
+                4.0, 3.0, 0.2,
+            ))),
+        ))
+        .build()])
+        .build(&mut scene.graph);
+    let wall_collider = ColliderBuilder::new(BaseBuilder::new())
+        .with_shape(ColliderShape::cuboid(2.0, 1.5, 0.1))
+        .build(&mut scene.graph);
+    RigidBodyBuilder::new(
+        BaseBuilder::new()
+            .with_local_transform(
+                TransformBuilder::new()
+                    .with_local_position(Vector3::new(0.0, 1.5, 0.0))
+                    .build(),
+            )
+            .with_children(&[wall_mesh, wall_collider]),
+    )
+    .with_body_type(RigidBodyType::Static)
+    .build(&mut scene.graph);
+
+    // The sound source behind the wall, the small cube shows its position.
+    let marker = MeshBuilder::new(BaseBuilder::new())
+        .with_surfaces(vec![SurfaceBuilder::new(SurfaceSharedData::new(
+            SurfaceData::make_cube(Matrix4::new_scaling(0.3)),
+        ))
+        .build()])
+        .build(&mut scene.graph);
+    let sound = SoundBuilder::new(
+        BaseBuilder::new()
+            .with_local_transform(
+                TransformBuilder::new()
+                    .with_local_position(Vector3::new(0.0, 1.5, 2.0))
+                    .build(),
+            )
+            .with_children(&[marker]),
+    )
+    .with_buffer(Some(make_buzz()))
+    .with_looping(true)
+    .with_status(Status::Playing)
+    .with_gain(0.5)
+    .with_radius(2.0)
+    .with_occlusion(SoundOcclusion {
+        enabled: true,
+        ..Default::default()
+    })
+    .build(&mut scene.graph);
+
+    (scene, camera, sound)
+}
+
+struct Game {
+    scene: Handle<Scene>,
+    camera: Handle<Node>,
+    sound: Handle<Node>,
+    move_left: bool,
+    move_right: bool,
+    debug_text: Handle<UiNode>,
+}
+
+impl Plugin for Game {
+    fn update(&mut self, context: &mut PluginContext, _control_flow: &mut ControlFlow) {
+        let graph = &mut context.scenes[self.scene].graph;
+
+        let mut offset = 0.0;
+        if self.move_left {
+            offset += SPEED * context.dt;
+        }
+        if self.move_right {
+            offset -= SPEED * context.dt;
+        }
+        let transform = graph[self.camera].local_transform_mut();
+        let position = **transform.position();
+        transform.set_position(Vector3::new(
+            (position.x + offset).clamp(-6.0, 6.0),
+            position.y,
+            position.z,
+        ));
+
+        let sound = graph[self.sound].as_sound();
+        context.user_interface.send_message(TextMessage::text(
+            self.debug_text,
+            MessageDirection::ToWidget,
+            format!(
+                "Example - Sound Occlusion\n\
+                A/D - move, O - toggle occlusion ({})\n\
+                Occlusion level: {:.2}",
+                sound.occlusion().enabled,
+                sound.occlusion_level()
+            ),
+        ));
+    }
+
+    fn on_os_event(
+        &mut self,
+        event: &Event<()>,
+        context: PluginContext,
+        _control_flow: &mut ControlFlow,
+    ) {
+        if let Event::WindowEvent {
+            event: WindowEvent::KeyboardInput { event: input, .. },
+            ..
+        } = event
+        {
+            let pressed = input.state == ElementState::Pressed;
+            match input.physical_key {
+                KeyCode::KeyA => self.move_left = pressed,
+                KeyCode::KeyD => self.move_right = pressed,
+                KeyCode::KeyO if pressed && !input.repeat => {
+                    let sound = context.scenes[self.scene].graph[self.sound].as_sound_mut();
+                    let mut occlusion = sound.occlusion().clone();
+                    occlusion.enabled = !occlusion.enabled;
+                    sound.set_occlusion(occlusion);
+                }
+                _ => (),
+            }
+        }
+    }
+}
+
+struct GameConstructor;
+
+impl PluginConstructor for GameConstructor {
+    fn create_instance(
+        &self,
+        _override_scene: Handle<Scene>,
+        context: PluginContext,
+    ) -> Box<dyn Plugin> {
+        let (scene, camera, sound) = create_scene();
+
+        Box::new(Game {
+            scene: context.scenes.add(scene),
+            camera,
+            sound,
+            move_left: false,
+            move_right: false,
+            debug_text: TextBuilder::new(WidgetBuilder::new())
+                .build(&mut context.user_interface.build_ctx()),
+        })
+    }
+}
+
+fn main() {
+    let mut executor = Executor::from_params(
+        Default::default(),
+        GraphicsContextParams {
+            window_attributes: WindowAttributes {
+                title: "Example - Sound Occlusion".to_string(),
+                ..Default::default()
+            },
+            vsync: true,
+        },
+    );
+    executor.add_plugin_constructor(GameConstructor);
+    executor.run()
+}
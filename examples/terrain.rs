@@ -81,7 +81,7 @@ impl SceneLoader {
         let mut scene = Scene::new();
 
         // Set ambient light.
-        scene.ambient_lighting_color = Color::opaque(200, 200, 200);
+        scene.environment.ambient_lighting_color = Color::opaque(200, 200, 200);
 
         // Camera is our eyes in the world - you won't see anything without it.
         let model_handle = create_camera(
@@ -0,0 +1,188 @@
+//! Example - Order-independent transparency
+//!
+//! Difficulty: Easy.
+//!
+//! This example shows three intersecting transparent planes. Sorting of transparent objects is done
+//! per-object, so there is no correct order for such planes and they pop when the camera moves.
+//! Order-independent transparency renders them without ordering artifacts. Press Space to toggle it.
+
+use fyrox::{
+    core::{
+        algebra::{Matrix4, UnitQuaternion, Vector3},
+        color::Color,
+        pool::Handle,
+        sstorage::ImmutableString,
+    },
+    engine::{executor::Executor, GraphicsContext, GraphicsContextParams},
+    event::{ElementState, Event, WindowEvent},
+    event_loop::ControlFlow,
+    gui::{
+        message::MessageDirection,
+        text::{TextBuilder, TextMessage},
+        widget::WidgetBuilder,
+        UiNode,
+    },
+    keyboard::KeyCode,
+    material::{Material, PropertyValue, SharedMaterial},
+    plugin::{Plugin, PluginConstructor, PluginContext},
+    scene::{
+        base::BaseBuilder,
+        camera::CameraBuilder,
+        mesh::{
+            surface::{SurfaceBuilder, SurfaceData, SurfaceSharedData},
+            MeshBuilder, RenderPath,
+        },
+        node::Node,
+        pivot::PivotBuilder,
+        transform::TransformBuilder,
+        Scene,
+    },
+    window::WindowAttributes,
+};
+
+struct Game {
+    scene: Handle<Scene>,
+    pivot: Handle<Node>,
+    angle: f32,
+    debug_text: Handle<UiNode>,
+}
+
+impl Plugin for Game {
+    fn update(&mut self, context: &mut PluginContext, _control_flow: &mut ControlFlow) {
+        self.angle += context.dt * 0.5;
+
+        context.scenes[self.scene].graph[self.pivot]
+            .local_transform_mut()
+            .set_rotation(UnitQuaternion::from_axis_angle(
+                &Vector3::y_axis(),
+                self.angle,
+            ));
+
+        if let GraphicsContext::Initialized(ref graphics_context) = context.graphics_context {
+            let renderer = &graphics_context.renderer;
+            context.user_interface.send_message(TextMessage::text(
+                self.debug_text,
+                MessageDirection::ToWidget,
+                format!(
+                    "Example - Order-Independent Transparency\n\
+                    Press Space to toggle OIT (enabled: {})\n{}",
+                    renderer.get_quality_settings().use_oit,
+                    renderer.get_statistics()
+                ),
+            ));
+        }
+    }
+
+    fn on_os_event(
+        &mut self,
+        event: &Event<()>,
+        context: PluginContext,
+        _control_flow: &mut ControlFlow,
+    ) {
+        if let Event::WindowEvent {
+            event: WindowEvent::KeyboardInput { event: input, .. },
+            ..
+        } = event
+        {
+            if input.state == ElementState::Pressed && input.physical_key == KeyCode::Space {
+                if let GraphicsContext::Initialized(ref mut graphics_context) =
+                    context.graphics_context
+                {
+                    let mut settings = graphics_context.renderer.get_quality_settings();
+                    settings.use_oit = !settings.use_oit;
+                    graphics_context
+                        .renderer
+                        .set_quality_settings(&settings)
+                        .unwrap();
+                }
+            }
+        }
+    }
+}
+
+fn make_plane(scene: &mut Scene, angle: f32, color: Color) -> Handle<Node> {
+    let mut material = Material::standard_two_sides();
+    material
+        .set_property(
+            &ImmutableString::new("diffuseColor"),
+            PropertyValue::Color(color),
+        )
+        .unwrap();
+
+    MeshBuilder::new(
+        BaseBuilder::new().with_local_transform(
+            TransformBuilder::new()
+                .with_local_rotation(UnitQuaternion::from_axis_angle(
+                    &Vector3::y_axis(),
+                    angle.to_radians(),
+                ))
+                .with_local_scale(Vector3::new(2.0, 2.0, 2.0))
+                .build(),
+        ),
+    )
+    .with_surfaces(vec![SurfaceBuilder::new(SurfaceSharedData::new(
+        SurfaceData::make_quad(&Matrix4::identity()),
+    ))
+    .with_material(SharedMaterial::new(material))
+    .build()])
+    .with_render_path(RenderPath::Forward)
+    .build(&mut scene.graph)
+}
+
+struct GameConstructor;
+
+impl PluginConstructor for GameConstructor {
+    fn create_instance(
+        &self,
+        _override_scene: Handle<Scene>,
+        context: PluginContext,
+    ) -> Box<dyn Plugin> {
+        let mut scene = Scene::new();
+
+        CameraBuilder::new(
+            BaseBuilder::new().with_local_transform(
+                TransformBuilder::new()
+                    .with_local_position(Vector3::new(0.0, 1.0, -3.0))
+                    .with_local_rotation(UnitQuaternion::from_axis_angle(
+                        &Vector3::x_axis(),
+                        20.0f32.to_radians(),
+                    ))
+                    .build(),
+            ),
+        )
+        .build(&mut scene.graph);
+
+        // Three planes intersect each other along the vertical axis.
+        let planes = [
+            make_plane(&mut scene, 0.0, Color::from_rgba(255, 0, 0, 128)),
+            make_plane(&mut scene, 60.0, Color::from_rgba(0, 255, 0, 128)),
+            make_plane(&mut scene, 120.0, Color::from_rgba(0, 0, 255, 128)),
+        ];
+
+        let pivot =
+            PivotBuilder::new(BaseBuilder::new().with_children(&planes)).build(&mut scene.graph);
+
+        Box::new(Game {
+            scene: context.scenes.add(scene),
+            pivot,
+            angle: 0.0,
+            debug_text: TextBuilder::new(WidgetBuilder::new())
+                .build(&mut context.user_interface.build_ctx()),
+        })
+    }
+}
+
+fn main() {
+    let mut executor = Executor::from_params(
+        Default::default(),
+        GraphicsContextParams {
+            window_attributes: WindowAttributes {
+                title: "Example - Order-Independent Transparency".to_string(),
+                ..Default::default()
+            },
+            vsync: true,
+        },
+    );
+    executor.add_plugin_constructor(GameConstructor);
+    executor.run()
+}
@@ -279,7 +279,7 @@ async fn create_scene(resource_manager: ResourceManager) -> GameScene {
     let mut scene = Scene::new();
 
     // Set ambient light.
-    scene.ambient_lighting_color = Color::opaque(200, 200, 200);
+    scene.environment.ambient_lighting_color = Color::opaque(200, 200, 200);
 
     // Camera is our eyes in the world - you won't see anything without it.
     create_camera(
@@ -189,7 +189,7 @@ async fn create_scene(resource_manager: ResourceManager, context: Arc<Mutex<Scen
         .with_status(Status::Playing)
         .build(&mut scene.graph);
 
-    scene.ambient_lighting_color = Color::opaque(200, 200, 200);
+    scene.environment.ambient_lighting_color = Color::opaque(200, 200, 200);
 
     create_camera(
         resource_manager.clone(),
@@ -7,7 +7,7 @@ use std::fmt::{Debug, Display};
 use crate::instant::Instant;
 #[cfg(not(target_arch = "wasm32"))]
 use std::io::{self, Write};
-use std::sync::mpsc::Sender;
+use std::sync::{mpsc::Sender, Arc};
 use std::time::Duration;
 
 #[cfg(target_arch = "wasm32")]
@@ -39,6 +39,7 @@ lazy_static! {
         file: std::fs::File::create("fyrox.log").unwrap(),
         verbosity: MessageKind::Information,
         listeners: Default::default(),
+        sink: None,
         time_origin: Instant::now()
     });
 }
@@ -65,17 +66,22 @@ impl MessageKind {
     }
 }
 
+/// A callback, that receives every message passed into the log. See [`Log::set_sink`] for more info.
+pub type LogSink = Arc<dyn Fn(MessageKind, &str) + Send + Sync>;
+
 /// See module docs.
 pub struct Log {
     #[cfg(all(not(target_arch = "wasm32"), not(target_os = "android")))]
     file: std::fs::File,
     verbosity: MessageKind,
     listeners: Vec<Sender<LogMessage>>,
+    sink: Option<LogSink>,
     time_origin: Instant,
 }
 
 impl Log {
-    fn write_internal<S>(&mut self, kind: MessageKind, message: S)
+    // Returns a sink, that must be called with the message after the log is unlocked.
+    fn write_internal<S>(&mut self, kind: MessageKind, message: S) -> Option<LogSink>
     where
         S: AsRef<str>,
     {
@@ -106,16 +112,11 @@ impl Log {
             {
                 let _ = io::stdout().write_all(msg.as_bytes());
             }
-        }
-    }
 
-    fn writeln_internal<S>(&mut self, kind: MessageKind, message: S)
-    where
-        S: AsRef<str>,
-    {
-        let mut msg = message.as_ref().to_owned();
-        msg.push('\n');
-        self.write_internal(kind, msg)
+            self.sink.clone()
+        } else {
+            None
+        }
     }
 
     /// Writes string into console and into file.
@@ -123,7 +124,11 @@ impl Log {
     where
         S: AsRef<str>,
     {
-        LOG.lock().write_internal(kind, msg);
+        // The lock is released before calling the sink, so the sink could use the log too.
+        let sink = LOG.lock().write_internal(kind, msg.as_ref());
+        if let Some(sink) = sink {
+            sink(kind, msg.as_ref());
+        }
     }
 
     /// Writes line into console and into file.
@@ -131,7 +136,9 @@ impl Log {
     where
         S: AsRef<str>,
     {
-        LOG.lock().writeln_internal(kind, msg);
+        let mut msg = msg.as_ref().to_owned();
+        msg.push('\n');
+        Self::write(kind, msg)
     }
 
     /// Writes information message.
@@ -168,6 +175,36 @@ impl Log {
         LOG.lock().listeners.push(listener)
     }
 
+    /// Sets a callback, that will be called for every message passed into the log (messages filtered
+    /// out by verbosity level are ignored). Unlike listeners, the sink is called synchronously, so it
+    /// could be used to forward engine messages (resource loading errors, shader compilation errors,
+    /// etc.) to a custom logger or to an in-game console. Messages are still written to the console
+    /// and to the file. There could be only one sink, setting a new one replaces the previous one.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// use fyrox_core::log::{Log, MessageKind};
+    ///
+    /// Log::set_sink(|kind, message| {
+    ///     if kind == MessageKind::Error {
+    ///         // Forward the message to a custom logger here.
+    ///         let _ = message;
+    ///     }
+    /// });
+    /// ```
+    pub fn set_sink<F>(sink: F)
+    where
+        F: Fn(MessageKind, &str) + Send + Sync + 'static,
+    {
+        LOG.lock().sink = Some(Arc::new(sink));
+    }
+
+    /// Removes current sink, see [`Self::set_sink`] for more info.
+    pub fn remove_sink() {
+        LOG.lock().sink = None;
+    }
+
     /// Allows you to verify that the result of operation is Ok, or print the error in the log.
     ///
     /// # Use cases
@@ -202,3 +239,39 @@ impl Log {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        log::{Log, MessageKind},
+        parking_lot::Mutex,
+    };
+    use std::sync::Arc;
+
+    #[test]
+    fn test_log_sink() {
+        let messages = Arc::new(Mutex::new(Vec::new()));
+        {
+            let messages = messages.clone();
+            Log::set_sink(move |kind, message| {
+                // Other tests could write to the log at the same time.
+                if message.starts_with("Sink test") {
+                    messages.lock().push((kind, message.to_owned()));
+                }
+            });
+        }
+
+        Log::err("Sink test error");
+        Log::write(MessageKind::Warning, "Sink test warning");
+        Log::remove_sink();
+        Log::info("Sink test info");
+
+        assert!(
+            *messages.lock()
+                == vec![
+                    (MessageKind::Error, "Sink test error\n".to_string()),
+                    (MessageKind::Warning, "Sink test warning".to_string())
+                ]
+        );
+    }
+}
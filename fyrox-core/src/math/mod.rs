@@ -9,7 +9,8 @@ pub mod triangulator;
 
 use crate::{
     algebra::{
-        Matrix3, Matrix4, RealField, Scalar, SimdRealField, UnitQuaternion, Vector2, Vector3,
+        Matrix3, Matrix4, RealField, Rotation3, Scalar, SimdRealField, UnitQuaternion, Vector2,
+        Vector3,
     },
     math::ray::IntersectionResult,
     num_traits::{NumAssign, Zero},
@@ -1109,18 +1110,85 @@ pub fn m4x4_approx_eq(a: &Matrix4<f32>, b: &Matrix4<f32>) -> bool {
         .all(|(a, b)| (*a - *b).abs() <= 0.001)
 }
 
+/// Decomposes an affine transformation matrix into translation, rotation and scale, so that
+/// `translation * rotation * scale` gives the original matrix. Shear and projection are discarded.
+/// Reflection (negative determinant of the basis) is represented by negative scale along X axis.
+pub fn decompose_transform(
+    transform: &Matrix4<f32>,
+) -> (Vector3<f32>, UnitQuaternion<f32>, Vector3<f32>) {
+    let mut basis = transform.basis();
+    let mut scale = Vector3::new(
+        basis.column(0).norm(),
+        basis.column(1).norm(),
+        basis.column(2).norm(),
+    );
+    if basis.determinant() < 0.0 {
+        scale.x = -scale.x;
+    }
+    for i in 0..3 {
+        // Degenerate axes are left as is, the closest rotation is still valid.
+        if scale[i].abs() > f32::EPSILON {
+            let axis = basis.column(i) / scale[i];
+            basis.set_column(i, &axis);
+        }
+    }
+    // Direct conversion is exact for orthonormal basis (including rotations by 180 degrees, where
+    // iterative method gets stuck), the iterative method then finds the closest rotation if the
+    // basis has shear.
+    let guess = UnitQuaternion::try_new(
+        UnitQuaternion::from_rotation_matrix(&Rotation3::from_matrix_unchecked(basis)).into_inner(),
+        f32::EPSILON,
+    )
+    .unwrap_or_else(UnitQuaternion::identity);
+    let rotation = UnitQuaternion::from_matrix_eps(&basis, f32::EPSILON, 16, guess);
+    (transform.position(), rotation, scale)
+}
+
+/// Spherical linear interpolation of rotations, that always takes the shortest path. Unlike
+/// [`UnitQuaternion::slerp`], it does not panic on rotations, that are 180 degrees apart, and
+/// falls back to normalized linear interpolation on nearly identical rotations.
+pub fn slerp_rotation(
+    a: &UnitQuaternion<f32>,
+    b: &UnitQuaternion<f32>,
+    t: f32,
+) -> UnitQuaternion<f32> {
+    // `q` and `-q` define the same rotation, pick the one that is closer to `a`.
+    let b = if a.coords.dot(&b.coords) < 0.0 {
+        UnitQuaternion::new_unchecked(-b.into_inner())
+    } else {
+        *b
+    };
+    a.try_slerp(&b, t, f32::EPSILON)
+        .unwrap_or_else(|| a.nlerp(&b, t))
+}
+
+/// Interpolates between two affine transformation matrices. Both matrices are decomposed into
+/// translation, rotation and scale (see [`decompose_transform`]), translation and scale are
+/// interpolated linearly, rotation is interpolated using [`slerp_rotation`], then the parts are
+/// composed back. It is useful to blend transforms of network snapshots or animation poses, where
+/// linear interpolation of the matrices would distort the shape. `t = 0.0` gives `a`, `t = 1.0`
+/// gives `b` (without shear and projection).
+pub fn lerp_transform(a: &Matrix4<f32>, b: &Matrix4<f32>, t: f32) -> Matrix4<f32> {
+    let (a_translation, a_rotation, a_scale) = decompose_transform(a);
+    let (b_translation, b_rotation, b_scale) = decompose_transform(b);
+    Matrix4::new_translation(&a_translation.lerp(&b_translation, t))
+        * slerp_rotation(&a_rotation, &b_rotation, t).to_homogeneous()
+        * Matrix4::new_nonuniform_scaling(&a_scale.lerp(&b_scale, t))
+}
+
 #[cfg(test)]
 mod test {
     use nalgebra::{Matrix3, Matrix4, UnitQuaternion, Vector3};
     use num_traits::Zero;
 
     use super::{
-        barycentric_is_inside, barycentric_to_world, cubicf_derivative, get_barycentric_coords,
-        get_barycentric_coords_2d, get_closest_point, get_closest_point_triangle_set,
-        get_closest_point_triangles, get_farthest_point, get_signed_triangle_area, ieee_remainder,
-        inf_sup_cubicf, m4x4_approx_eq, quat_from_euler, round_to_step, spherical_to_cartesian,
-        triangle_area, wrap_angle, wrapf, Matrix3Ext, Matrix4Ext, PositionProvider, Rect,
-        RotationOrder, SmoothAngle, TriangleDefinition, TriangleEdge, Vector2Ext, Vector3Ext,
+        barycentric_is_inside, barycentric_to_world, cubicf_derivative, decompose_transform,
+        get_barycentric_coords, get_barycentric_coords_2d, get_closest_point,
+        get_closest_point_triangle_set, get_closest_point_triangles, get_farthest_point,
+        get_signed_triangle_area, ieee_remainder, inf_sup_cubicf, lerp_transform, m4x4_approx_eq,
+        quat_from_euler, round_to_step, slerp_rotation, spherical_to_cartesian, triangle_area,
+        wrap_angle, wrapf, Matrix3Ext, Matrix4Ext, PositionProvider, Rect, RotationOrder,
+        SmoothAngle, TriangleDefinition, TriangleEdge, Vector2Ext, Vector3Ext,
     };
     use crate::algebra::Vector2;
 
@@ -1820,4 +1888,71 @@ mod test {
             )
         ),);
     }
+
+    #[test]
+    fn test_decompose_transform() {
+        let translation = Vector3::new(1.0, 2.0, 3.0);
+        let rotation = UnitQuaternion::from_euler_angles(0.3, -1.2, 2.0);
+        let scale = Vector3::new(-2.0, 0.5, 3.0);
+        let transform = Matrix4::new_translation(&translation)
+            * rotation.to_homogeneous()
+            * Matrix4::new_nonuniform_scaling(&scale);
+
+        let (t, r, s) = decompose_transform(&transform);
+        assert!((t - translation).norm() < 1.0e-5);
+        assert!((s - scale).norm() < 1.0e-5);
+        assert!(r.angle_to(&rotation) < 1.0e-3);
+
+        // Reflection along any axis is moved to X axis, but the matrix stays the same.
+        let reflected = Matrix4::new_nonuniform_scaling(&Vector3::new(1.0, -1.0, 1.0));
+        let (t, r, s) = decompose_transform(&reflected);
+        assert!(s.x < 0.0);
+        assert!(m4x4_approx_eq(
+            &(Matrix4::new_translation(&t)
+                * r.to_homogeneous()
+                * Matrix4::new_nonuniform_scaling(&s)),
+            &reflected
+        ));
+    }
+
+    #[test]
+    fn test_slerp_rotation() {
+        let a = UnitQuaternion::identity();
+
+        // `q` and `-q` are the same rotation, there must be no spin between them.
+        let q = UnitQuaternion::from_euler_angles(0.0, 1.0, 0.0);
+        let negated = UnitQuaternion::new_unchecked(-q.into_inner());
+        for t in [0.0, 0.25, 0.5, 1.0] {
+            assert!(slerp_rotation(&q, &negated, t).angle_to(&q) < 1.0e-3);
+        }
+
+        // Nearly opposite rotations.
+        let b = UnitQuaternion::from_axis_angle(&Vector3::y_axis(), 179.9f32.to_radians());
+        let half = slerp_rotation(&a, &b, 0.5);
+        let expected = UnitQuaternion::from_axis_angle(&Vector3::y_axis(), 89.95f32.to_radians());
+        assert!(half.angle_to(&expected) < 1.0e-3);
+
+        // Nearly identical rotations.
+        let c = UnitQuaternion::from_axis_angle(&Vector3::x_axis(), 1.0e-7);
+        assert!(slerp_rotation(&a, &c, 0.5).angle() < 1.0e-3);
+    }
+
+    #[test]
+    fn test_lerp_transform() {
+        let a = Matrix4::new_translation(&Vector3::new(0.0, 0.0, 0.0))
+            * Matrix4::new_nonuniform_scaling(&Vector3::new(-1.0, 1.0, 1.0));
+        let b = Matrix4::new_translation(&Vector3::new(2.0, 4.0, 0.0))
+            * UnitQuaternion::from_axis_angle(&Vector3::z_axis(), 90.0f32.to_radians())
+                .to_homogeneous()
+            * Matrix4::new_nonuniform_scaling(&Vector3::new(-3.0, 1.0, 1.0));
+
+        assert!(m4x4_approx_eq(&lerp_transform(&a, &b, 0.0), &a));
+        assert!(m4x4_approx_eq(&lerp_transform(&a, &b, 1.0), &b));
+
+        let expected = Matrix4::new_translation(&Vector3::new(1.0, 2.0, 0.0))
+            * UnitQuaternion::from_axis_angle(&Vector3::z_axis(), 45.0f32.to_radians())
+                .to_homogeneous()
+            * Matrix4::new_nonuniform_scaling(&Vector3::new(-2.0, 1.0, 1.0));
+        assert!(m4x4_approx_eq(&lerp_transform(&a, &b, 0.5), &expected));
+    }
 }
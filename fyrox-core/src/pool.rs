@@ -1326,6 +1326,42 @@ where
         }
     }
 
+    /// Retains pool records selected by `pred`, that receives both a handle and a mutable reference
+    /// to each object. Handles of the retained objects stay valid.
+    pub fn retain_pairs<F>(&mut self, mut pred: F)
+    where
+        F: FnMut(Handle<T>, &mut T) -> bool,
+    {
+        for (i, record) in self.records.iter_mut().enumerate() {
+            let handle = Handle::new(i as u32, record.generation);
+
+            let retain = if let Some(payload) = record.payload.as_mut() {
+                pred(handle, payload)
+            } else {
+                continue;
+            };
+
+            if !retain {
+                self.free_stack.push(i as u32);
+                record.payload.take(); // and Drop
+            }
+        }
+    }
+
+    /// Creates an iterator, that removes objects selected by `pred` from the pool and yields them
+    /// together with their (now invalid) handles. Handles of the remaining objects stay valid. If the
+    /// iterator is dropped before it is exhausted, the rest of the objects are kept in the pool.
+    pub fn drain_filter<F>(&mut self, pred: F) -> PoolDrainFilter<'_, T, P, F>
+    where
+        F: FnMut(Handle<T>, &mut T) -> bool,
+    {
+        PoolDrainFilter {
+            pool: self,
+            current: 0,
+            pred,
+        }
+    }
+
     /// Begins multi-borrow that allows you to borrow as many (`N`) **unique** references to the pool
     /// elements as you need. See [`MultiBorrowContext::try_get`] for more info.
     pub fn begin_multi_borrow<const N: usize>(&mut self) -> MultiBorrowContext<N, T, P> {
@@ -1552,6 +1588,45 @@ where
     }
 }
 
+/// An iterator, that removes selected objects from a pool. See [`Pool::drain_filter`] docs for more
+/// info.
+pub struct PoolDrainFilter<'a, T, P, F>
+where
+    T: Sized,
+    P: PayloadContainer<Element = T> + 'static,
+    F: FnMut(Handle<T>, &mut T) -> bool,
+{
+    pool: &'a mut Pool<T, P>,
+    current: usize,
+    pred: F,
+}
+
+impl<'a, T, P, F> Iterator for PoolDrainFilter<'a, T, P, F>
+where
+    T: Sized,
+    P: PayloadContainer<Element = T> + 'static,
+    F: FnMut(Handle<T>, &mut T) -> bool,
+{
+    type Item = (Handle<T>, T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(record) = self.pool.records.get_mut(self.current) {
+            let index = self.current;
+            self.current += 1;
+
+            let handle = Handle::new(index as u32, record.generation);
+            if let Some(payload) = record.payload.as_mut() {
+                if (self.pred)(handle, payload) {
+                    self.pool.free_stack.push(index as u32);
+                    return record.payload.take().map(|payload| (handle, payload));
+                }
+            }
+        }
+
+        None
+    }
+}
+
 /// Multi-borrow context allows you to get as many **unique** references to elements in
 /// a pool as you want.
 pub struct MultiBorrowContext<'a, const N: usize, T, P = Option<T>>
@@ -2134,6 +2209,35 @@ mod test {
         assert_eq!(pool.replace(b, 5), None);
     }
 
+    #[test]
+    fn pool_retain_pairs_and_drain_filter() {
+        let mut pool = Pool::<u32>::new();
+        let a = pool.spawn(1);
+        let b = pool.spawn(2);
+        let c = pool.spawn(3);
+        let d = pool.spawn(4);
+
+        pool.retain_pairs(|handle, value| {
+            *value *= 10;
+            handle != b
+        });
+        assert!(!pool.is_valid_handle(b));
+        assert_eq!(pool[a], 10);
+        assert_eq!(pool[c], 30);
+
+        let drained = pool
+            .drain_filter(|_, value| *value > 20)
+            .collect::<Vec<_>>();
+        assert_eq!(drained, vec![(c, 30), (d, 40)]);
+        assert_eq!(pool.alive_count(), 1);
+        assert_eq!(pool[a], 10);
+
+        // Freed records must be reused.
+        let e = pool.spawn(5);
+        assert_ne!(e, a);
+        assert_eq!(pool.alive_count(), 2);
+    }
+
     #[test]
     fn pool_pair_iter() {
         let pool = Pool::<u32>::new();
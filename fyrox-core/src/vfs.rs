@@ -0,0 +1,770 @@
+//! Virtual file system. All file loading in the engine goes through a mount table of IO providers
+//! (see [`IoProvider`]), sorted by priority. When a file is requested, providers are asked one by
+//! one, starting from the one with the highest priority, until one of them has the file. This way
+//! a patch archive could override a base archive, that in its turn overrides loose files.
+//!
+//! Paths are still the keys for resource identity, an archive just provides data for the paths it
+//! contains. By default, the mount table contains [`FsIoProvider`] (loose files) with
+//! [`LOOSE_FILES_PRIORITY`].
+//!
+//! There's a global mount table, but a file could be loaded using any other mount table, see
+//! [`with_vfs`]. For example, every resource manager has its own mount table.
+
+use crate::{
+    byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt},
+    io::FileLoadError,
+    parking_lot::RwLock,
+};
+use fxhash::{FxHashMap, FxHashSet};
+use std::{
+    cell::RefCell,
+    future::Future,
+    io::{Cursor, ErrorKind, Read, Write},
+    path::{Path, PathBuf},
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+};
+
+/// Future type of IO providers.
+#[cfg(not(target_arch = "wasm32"))]
+pub type IoFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+/// Future type of IO providers.
+#[cfg(target_arch = "wasm32")]
+pub type IoFuture<'a, T> = Pin<Box<dyn Future<Output = T> + 'a>>;
+
+/// Priority of the default provider of loose files.
+pub const LOOSE_FILES_PRIORITY: i32 = 0;
+
+/// A source of files for the virtual file system.
+pub trait IoProvider: Send + Sync + 'static {
+    /// Loads the whole file at the given path. Must return [`ErrorKind::NotFound`] IO error if there's
+    /// no such file, so the next provider in the mount table could be asked.
+    fn load_file<'a>(&'a self, path: &'a Path) -> IoFuture<'a, Result<Vec<u8>, FileLoadError>>;
+
+    /// Returns `true` if the file at the given path exists.
+    fn exists<'a>(&'a self, path: &'a Path) -> IoFuture<'a, bool>;
+
+    /// Returns paths of files and directories inside the given directory. Could be empty if the
+    /// provider does not support listing (for example, HTTP fetch).
+    fn read_dir(&self, path: &Path) -> Vec<PathBuf>;
+}
+
+fn not_found(path: &Path) -> FileLoadError {
+    FileLoadError::Io(std::io::Error::new(
+        ErrorKind::NotFound,
+        format!("File {} not found!", path.display()),
+    ))
+}
+
+fn is_not_found(error: &FileLoadError) -> bool {
+    matches!(error, FileLoadError::Io(error) if error.kind() == ErrorKind::NotFound)
+}
+
+/// Converts the path to the form that is used as a key in archives: forward slashes and no leading
+/// `./`.
+pub fn normalize_path(path: &Path) -> String {
+    let path = path.to_string_lossy().replace('\\', "/");
+    let mut normalized = path.as_str();
+    while let Some(stripped) = normalized.strip_prefix("./") {
+        normalized = stripped;
+    }
+    normalized.trim_end_matches('/').to_string()
+}
+
+/// Provider of loose files. It uses the file system on PC, the asset manager on Android (see
+/// [`crate::io::ANDROID_APP`]) and HTTP fetch on WebAssembly.
+#[derive(Default, Debug)]
+pub struct FsIoProvider;
+
+impl IoProvider for FsIoProvider {
+    fn load_file<'a>(&'a self, path: &'a Path) -> IoFuture<'a, Result<Vec<u8>, FileLoadError>> {
+        Box::pin(async move {
+            #[cfg(all(not(target_os = "android"), not(target_arch = "wasm32")))]
+            {
+                use std::fs::File;
+
+                let mut file = File::open(path)?;
+                let mut buffer = Vec::new();
+                file.read_to_end(&mut buffer)?;
+                Ok(buffer)
+            }
+
+            #[cfg(target_os = "android")]
+            {
+                let asset_manager = crate::io::ANDROID_APP
+                    .get()
+                    .ok_or_else(|| FileLoadError::Custom("ANDROID_APP is not set".to_string()))?
+                    .asset_manager();
+                let mut opened_asset = asset_manager
+                    .open(&std::ffi::CString::new(path.to_str().unwrap()).unwrap())
+                    .ok_or_else(|| not_found(path))?;
+                let bytes = opened_asset.get_buffer()?;
+                Ok(bytes.to_vec())
+            }
+
+            #[cfg(target_arch = "wasm32")]
+            {
+                use js_sys::Uint8Array;
+                use wasm_bindgen::JsCast;
+                use wasm_bindgen_futures::JsFuture;
+
+                match web_sys::window() {
+                    Some(window) => {
+                        let resp_value =
+                            JsFuture::from(window.fetch_with_str(path.to_str().unwrap())).await?;
+
+                        let resp: web_sys::Response = resp_value.dyn_into().unwrap();
+                        if resp.status() == 404 {
+                            return Err(not_found(path));
+                        }
+                        let data = JsFuture::from(resp.array_buffer().unwrap()).await?;
+                        let bytes = Uint8Array::new(&data).to_vec();
+                        Ok(bytes)
+                    }
+                    None => Err(FileLoadError::Custom("Window not found!".to_owned())),
+                }
+            }
+        })
+    }
+
+    fn exists<'a>(&'a self, path: &'a Path) -> IoFuture<'a, bool> {
+        Box::pin(async move {
+            #[cfg(all(not(target_os = "android"), not(target_arch = "wasm32")))]
+            {
+                path.exists()
+            }
+
+            #[cfg(target_os = "android")]
+            {
+                crate::io::ANDROID_APP
+                    .get()
+                    .map(|v| {
+                        v.asset_manager()
+                            .open(&std::ffi::CString::new(path.to_str().unwrap()).unwrap())
+                            .is_some()
+                    })
+                    .unwrap_or_default()
+            }
+
+            #[cfg(target_arch = "wasm32")]
+            {
+                use wasm_bindgen::JsCast;
+                use wasm_bindgen_futures::JsFuture;
+
+                match web_sys::window() {
+                    Some(window) => {
+                        if let Ok(resp_value) =
+                            JsFuture::from(window.fetch_with_str(path.to_str().unwrap())).await
+                        {
+                            let resp: web_sys::Response = resp_value.dyn_into().unwrap();
+
+                            resp.status() == 200
+                        } else {
+                            false
+                        }
+                    }
+                    None => false,
+                }
+            }
+        })
+    }
+
+    fn read_dir(&self, path: &Path) -> Vec<PathBuf> {
+        #[cfg(all(not(target_os = "android"), not(target_arch = "wasm32")))]
+        {
+            std::fs::read_dir(path)
+                .map(|entries| {
+                    entries
+                        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                        .collect()
+                })
+                .unwrap_or_default()
+        }
+
+        #[cfg(any(target_os = "android", target_arch = "wasm32"))]
+        {
+            let _ = path;
+            Vec::new()
+        }
+    }
+}
+
+const ARCHIVE_MAGIC: &[u8; 4] = b"FPAK";
+const ARCHIVE_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Copy)]
+struct ArchiveEntry {
+    offset: u64,
+    size: u64,
+}
+
+#[derive(Debug)]
+enum ArchiveSource {
+    Memory(Vec<u8>),
+    #[cfg(all(not(target_os = "android"), not(target_arch = "wasm32")))]
+    File(PathBuf),
+}
+
+/// Provider of files packed into a single archive. The archive has a simple uncompressed format: a
+/// header with a table of `path -> (offset, size)` entries followed by the data of the files. Use
+/// [`ArchiveBuilder`] to create archives.
+#[derive(Debug)]
+pub struct ArchiveIoProvider {
+    entries: FxHashMap<String, ArchiveEntry>,
+    source: ArchiveSource,
+}
+
+fn read_archive_index<R: Read>(
+    reader: &mut R,
+) -> Result<FxHashMap<String, ArchiveEntry>, FileLoadError> {
+    let mut magic = [0u8; 4];
+    reader.read_exact(&mut magic)?;
+    if &magic != ARCHIVE_MAGIC {
+        return Err(FileLoadError::Custom("Not an archive!".to_string()));
+    }
+
+    let version = reader.read_u32::<LittleEndian>()?;
+    if version != ARCHIVE_VERSION {
+        return Err(FileLoadError::Custom(format!(
+            "Unsupported archive version {version}!"
+        )));
+    }
+
+    let count = reader.read_u32::<LittleEndian>()?;
+    let mut entries = FxHashMap::default();
+    for _ in 0..count {
+        let path_len = reader.read_u32::<LittleEndian>()?;
+        let mut path = vec![0u8; path_len as usize];
+        reader.read_exact(&mut path)?;
+        let path = String::from_utf8(path)
+            .map_err(|_| FileLoadError::Custom("Invalid path in archive!".to_string()))?;
+        let offset = reader.read_u64::<LittleEndian>()?;
+        let size = reader.read_u64::<LittleEndian>()?;
+        entries.insert(path, ArchiveEntry { offset, size });
+    }
+
+    Ok(entries)
+}
+
+impl ArchiveIoProvider {
+    /// Creates new provider from the archive data in memory.
+    pub fn from_bytes(data: Vec<u8>) -> Result<Self, FileLoadError> {
+        let entries = read_archive_index(&mut Cursor::new(&data))?;
+
+        for entry in entries.values() {
+            if entry.offset.saturating_add(entry.size) > data.len() as u64 {
+                return Err(FileLoadError::Custom("Archive is truncated!".to_string()));
+            }
+        }
+
+        Ok(Self {
+            entries,
+            source: ArchiveSource::Memory(data),
+        })
+    }
+
+    /// Creates new provider from the archive file. Only the index of the archive is read, files are
+    /// read on demand.
+    #[cfg(all(not(target_os = "android"), not(target_arch = "wasm32")))]
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, FileLoadError> {
+        let mut reader = std::io::BufReader::new(std::fs::File::open(path.as_ref())?);
+        Ok(Self {
+            entries: read_archive_index(&mut reader)?,
+            source: ArchiveSource::File(path.as_ref().to_path_buf()),
+        })
+    }
+
+    /// Returns `true` if the archive contains a file with the given path.
+    pub fn contains(&self, path: &Path) -> bool {
+        self.entries.contains_key(&normalize_path(path))
+    }
+
+    /// Returns amount of files in the archive.
+    pub fn file_count(&self) -> usize {
+        self.entries.len()
+    }
+
+    fn read(&self, path: &Path) -> Result<Vec<u8>, FileLoadError> {
+        let entry = *self
+            .entries
+            .get(&normalize_path(path))
+            .ok_or_else(|| not_found(path))?;
+
+        match self.source {
+            ArchiveSource::Memory(ref data) => {
+                Ok(data[entry.offset as usize..(entry.offset + entry.size) as usize].to_vec())
+            }
+            #[cfg(all(not(target_os = "android"), not(target_arch = "wasm32")))]
+            ArchiveSource::File(ref archive_path) => {
+                use std::io::{Seek, SeekFrom};
+
+                let mut file = std::fs::File::open(archive_path)?;
+                file.seek(SeekFrom::Start(entry.offset))?;
+                let mut buffer = vec![0u8; entry.size as usize];
+                file.read_exact(&mut buffer)?;
+                Ok(buffer)
+            }
+        }
+    }
+}
+
+impl IoProvider for ArchiveIoProvider {
+    fn load_file<'a>(&'a self, path: &'a Path) -> IoFuture<'a, Result<Vec<u8>, FileLoadError>> {
+        Box::pin(async move { self.read(path) })
+    }
+
+    fn exists<'a>(&'a self, path: &'a Path) -> IoFuture<'a, bool> {
+        Box::pin(async move { self.contains(path) })
+    }
+
+    fn read_dir(&self, path: &Path) -> Vec<PathBuf> {
+        let dir = normalize_path(path);
+        let mut children = FxHashSet::default();
+        for file_path in self.entries.keys() {
+            let relative = if dir.is_empty() {
+                file_path.as_str()
+            } else if let Some(relative) = file_path
+                .strip_prefix(dir.as_str())
+                .and_then(|rest| rest.strip_prefix('/'))
+            {
+                relative
+            } else {
+                continue;
+            };
+
+            if let Some(name) = relative.split('/').next() {
+                children.insert(Path::new(&dir).join(name));
+            }
+        }
+        let mut children = children.into_iter().collect::<Vec<_>>();
+        children.sort();
+        children
+    }
+}
+
+/// Creates archives, that could be mounted using [`ArchiveIoProvider`].
+#[derive(Default, Debug)]
+pub struct ArchiveBuilder {
+    files: Vec<(String, Vec<u8>)>,
+}
+
+impl ArchiveBuilder {
+    /// Creates new empty archive builder.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a file with the given path and data. The path must be the same as the path used to
+    /// request the file (for example a resource path).
+    pub fn with_file<P: AsRef<Path>>(mut self, path: P, data: Vec<u8>) -> Self {
+        self.add_file(path, data);
+        self
+    }
+
+    /// Adds a file with the given path and data, replaces existing file with the same path.
+    pub fn add_file<P: AsRef<Path>>(&mut self, path: P, data: Vec<u8>) {
+        let path = normalize_path(path.as_ref());
+        if let Some(existing) = self.files.iter_mut().find(|(p, _)| *p == path) {
+            existing.1 = data;
+        } else {
+            self.files.push((path, data));
+        }
+    }
+
+    /// Writes the archive to the given writer.
+    pub fn write<W: Write>(&self, writer: &mut W) -> std::io::Result<()> {
+        let index_size = 12
+            + self
+                .files
+                .iter()
+                .map(|(path, _)| 4 + path.len() as u64 + 16)
+                .sum::<u64>();
+
+        writer.write_all(ARCHIVE_MAGIC)?;
+        writer.write_u32::<LittleEndian>(ARCHIVE_VERSION)?;
+        writer.write_u32::<LittleEndian>(self.files.len() as u32)?;
+        let mut offset = index_size;
+        for (path, data) in self.files.iter() {
+            writer.write_u32::<LittleEndian>(path.len() as u32)?;
+            writer.write_all(path.as_bytes())?;
+            writer.write_u64::<LittleEndian>(offset)?;
+            writer.write_u64::<LittleEndian>(data.len() as u64)?;
+            offset += data.len() as u64;
+        }
+        for (_, data) in self.files.iter() {
+            writer.write_all(data)?;
+        }
+        Ok(())
+    }
+
+    /// Writes the archive to a vector of bytes.
+    pub fn build(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        // Writing to a vector can't fail.
+        self.write(&mut bytes).unwrap();
+        bytes
+    }
+
+    /// Saves the archive to the given file.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> std::io::Result<()> {
+        let mut writer = std::io::BufWriter::new(std::fs::File::create(path)?);
+        self.write(&mut writer)?;
+        writer.flush()
+    }
+}
+
+/// A handle of a mounted provider, that could be used to unmount it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct MountId(u64);
+
+struct Mount {
+    id: MountId,
+    priority: i32,
+    provider: Arc<dyn IoProvider>,
+}
+
+/// Mount table of IO providers. See module docs for more info.
+pub struct Vfs {
+    // Sorted by priority, from highest to lowest.
+    mounts: Vec<Mount>,
+    next_id: u64,
+}
+
+impl Default for Vfs {
+    fn default() -> Self {
+        let mut vfs = Self::empty();
+        vfs.mount(FsIoProvider, LOOSE_FILES_PRIORITY);
+        vfs
+    }
+}
+
+impl Vfs {
+    /// Creates new mount table with [`FsIoProvider`] mounted with [`LOOSE_FILES_PRIORITY`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates new mount table without any providers.
+    pub fn empty() -> Self {
+        Self {
+            mounts: Default::default(),
+            next_id: 0,
+        }
+    }
+
+    /// Mounts the provider with the given priority. Providers with higher priority override files of
+    /// providers with lower priority. If priorities are equal, the last mounted provider wins.
+    pub fn mount<P: IoProvider>(&mut self, provider: P, priority: i32) -> MountId {
+        let id = MountId(self.next_id);
+        self.next_id += 1;
+        let position = self
+            .mounts
+            .iter()
+            .position(|mount| mount.priority <= priority)
+            .unwrap_or(self.mounts.len());
+        self.mounts.insert(
+            position,
+            Mount {
+                id,
+                priority,
+                provider: Arc::new(provider),
+            },
+        );
+        id
+    }
+
+    /// Unmounts the provider. Returns `false` if there's no such provider.
+    pub fn unmount(&mut self, id: MountId) -> bool {
+        if let Some(position) = self.mounts.iter().position(|mount| mount.id == id) {
+            self.mounts.remove(position);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Returns amount of mounted providers.
+    pub fn mount_count(&self) -> usize {
+        self.mounts.len()
+    }
+
+    fn providers(&self) -> Vec<Arc<dyn IoProvider>> {
+        self.mounts
+            .iter()
+            .map(|mount| mount.provider.clone())
+            .collect()
+    }
+
+    /// Loads the file from the provider with the highest priority, that has the file. The returned
+    /// future does not borrow the mount table.
+    pub fn load_file(
+        &self,
+        path: &Path,
+    ) -> impl Future<Output = Result<Vec<u8>, FileLoadError>> + 'static {
+        let providers = self.providers();
+        let path = path.to_path_buf();
+        async move {
+            for provider in providers {
+                match provider.load_file(&path).await {
+                    Err(error) if is_not_found(&error) => continue,
+                    result => return result,
+                }
+            }
+            Err(not_found(&path))
+        }
+    }
+
+    /// Returns `true` if any of mounted providers has the file. The returned future does not borrow
+    /// the mount table.
+    pub fn exists(&self, path: &Path) -> impl Future<Output = bool> + 'static {
+        let providers = self.providers();
+        let path = path.to_path_buf();
+        async move {
+            for provider in providers {
+                if provider.exists(&path).await {
+                    return true;
+                }
+            }
+            false
+        }
+    }
+
+    /// Returns paths of files and directories inside the given directory of all mounted providers.
+    pub fn read_dir(&self, path: &Path) -> Vec<PathBuf> {
+        let mut visited = FxHashSet::default();
+        let mut children = Vec::new();
+        for mount in self.mounts.iter() {
+            for child in mount.provider.read_dir(path) {
+                if visited.insert(normalize_path(&child)) {
+                    children.push(child);
+                }
+            }
+        }
+        children
+    }
+}
+
+lazy_static! {
+    static ref VFS: RwLock<Vfs> = RwLock::new(Vfs::default());
+}
+
+/// Mount table that is shared between its owner (for example, a resource manager) and the tasks
+/// that load files through it, see [`with_vfs`].
+pub type SharedVfs = Arc<RwLock<Vfs>>;
+
+thread_local! {
+    // Mount table of a task that is being polled on this thread, see `with_vfs`.
+    static CURRENT_VFS: RefCell<Option<SharedVfs>> = RefCell::new(None);
+}
+
+fn with_current<R>(func: impl FnOnce(&Vfs) -> R) -> R {
+    let current = CURRENT_VFS.with(|current| current.borrow().clone());
+    match current {
+        Some(vfs) => func(&vfs.read()),
+        None => func(&VFS.read()),
+    }
+}
+
+// Restores previous mount table of the thread even if the task panics.
+struct CurrentVfsGuard {
+    previous: Option<SharedVfs>,
+}
+
+impl Drop for CurrentVfsGuard {
+    fn drop(&mut self) {
+        let previous = self.previous.take();
+        CURRENT_VFS.with(|current| *current.borrow_mut() = previous);
+    }
+}
+
+/// A future that loads files using the given mount table instead of the global one, see
+/// [`with_vfs`].
+pub struct WithVfs<F> {
+    vfs: SharedVfs,
+    future: F,
+}
+
+impl<F> Future for WithVfs<F>
+where
+    F: Future + Unpin,
+{
+    type Output = F::Output;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let vfs = self.vfs.clone();
+        let _guard = CurrentVfsGuard {
+            previous: CURRENT_VFS.with(|current| current.borrow_mut().replace(vfs)),
+        };
+        Pin::new(&mut self.future).poll(cx)
+    }
+}
+
+/// Wraps the future, so every call of [`load_file`], [`exists`] and [`read_dir`] (and so
+/// [`crate::io::load_file`] and [`crate::io::exists`]) made while the future is polled uses the
+/// given mount table instead of the global one. This is how resource managers keep their own mount
+/// tables.
+pub fn with_vfs<F>(vfs: SharedVfs, future: F) -> WithVfs<F>
+where
+    F: Future + Unpin,
+{
+    WithVfs { vfs, future }
+}
+
+/// Mounts the provider to the global mount table, that is used by [`crate::io::load_file`]. See
+/// [`Vfs::mount`] for more info.
+pub fn mount<P: IoProvider>(provider: P, priority: i32) -> MountId {
+    VFS.write().mount(provider, priority)
+}
+
+/// Unmounts the provider from the global mount table.
+pub fn unmount(id: MountId) -> bool {
+    VFS.write().unmount(id)
+}
+
+/// Loads the file using the current mount table (see [`with_vfs`]) or the global one.
+pub fn load_file(path: &Path) -> impl Future<Output = Result<Vec<u8>, FileLoadError>> + 'static {
+    with_current(|vfs| vfs.load_file(path))
+}
+
+/// Checks if the file exists using the current mount table (see [`with_vfs`]) or the global one.
+pub fn exists(path: &Path) -> impl Future<Output = bool> + 'static {
+    with_current(|vfs| vfs.exists(path))
+}
+
+/// Lists the directory using the current mount table (see [`with_vfs`]) or the global one.
+pub fn read_dir(path: &Path) -> Vec<PathBuf> {
+    with_current(|vfs| vfs.read_dir(path))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        futures::executor::block_on,
+        parking_lot::RwLock,
+        vfs::{with_vfs, ArchiveBuilder, ArchiveIoProvider, Vfs},
+    };
+    use std::{
+        path::{Path, PathBuf},
+        sync::Arc,
+    };
+
+    fn archive(files: &[(&str, &str)]) -> ArchiveIoProvider {
+        let mut builder = ArchiveBuilder::new();
+        for (path, content) in files {
+            builder.add_file(path, content.as_bytes().to_vec());
+        }
+        ArchiveIoProvider::from_bytes(builder.build()).unwrap()
+    }
+
+    fn load(vfs: &Vfs, path: &str) -> Option<String> {
+        block_on(vfs.load_file(Path::new(path)))
+            .ok()
+            .map(|data| String::from_utf8(data).unwrap())
+    }
+
+    #[test]
+    fn test_override_precedence() {
+        let mut vfs = Vfs::empty();
+
+        // Mount order must not matter.
+        let patch = vfs.mount(archive(&[("data/a.txt", "patch")]), 20);
+        vfs.mount(
+            archive(&[
+                ("data/a.txt", "loose"),
+                ("data/b.txt", "loose"),
+                ("data/c.txt", "loose"),
+            ]),
+            0,
+        );
+        vfs.mount(
+            archive(&[("data/a.txt", "base"), ("data/b.txt", "base")]),
+            10,
+        );
+
+        assert_eq!(load(&vfs, "data/a.txt").as_deref(), Some("patch"));
+        assert_eq!(load(&vfs, "./data/b.txt").as_deref(), Some("base"));
+        assert_eq!(load(&vfs, "data\\c.txt").as_deref(), Some("loose"));
+        assert_eq!(load(&vfs, "data/d.txt"), None);
+        assert!(block_on(vfs.exists(Path::new("data/c.txt"))));
+        assert!(!block_on(vfs.exists(Path::new("data/d.txt"))));
+
+        assert!(vfs.unmount(patch));
+        assert!(!vfs.unmount(patch));
+        assert_eq!(load(&vfs, "data/a.txt").as_deref(), Some("base"));
+    }
+
+    #[test]
+    fn test_with_vfs() {
+        let mut vfs = Vfs::empty();
+        vfs.mount(archive(&[("fyrox_with_vfs_test.txt", "archive")]), 0);
+        let vfs = Arc::new(RwLock::new(vfs));
+
+        let load_global = || {
+            Box::pin(async {
+                super::load_file(Path::new("fyrox_with_vfs_test.txt"))
+                    .await
+                    .ok()
+            })
+        };
+
+        assert_eq!(
+            block_on(with_vfs(vfs, load_global())).as_deref(),
+            Some(&b"archive"[..])
+        );
+        // The mount table must be restored when the future is polled.
+        assert_eq!(block_on(load_global()), None);
+    }
+
+    #[test]
+    fn test_same_priority() {
+        let mut vfs = Vfs::empty();
+        vfs.mount(archive(&[("a.txt", "first")]), 0);
+        vfs.mount(archive(&[("a.txt", "second")]), 0);
+        assert_eq!(load(&vfs, "a.txt").as_deref(), Some("second"));
+    }
+
+    #[test]
+    fn test_read_dir() {
+        let mut vfs = Vfs::empty();
+        vfs.mount(archive(&[("data/a.txt", ""), ("data/sub/b.txt", "")]), 0);
+        vfs.mount(archive(&[("data/a.txt", ""), ("data/c.txt", "")]), 1);
+
+        let mut children = vfs.read_dir(Path::new("data"));
+        children.sort();
+        assert_eq!(
+            children,
+            vec![
+                PathBuf::from("data/a.txt"),
+                PathBuf::from("data/c.txt"),
+                PathBuf::from("data/sub")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_archive_file() {
+        let path = std::env::temp_dir().join("fyrox_vfs_test_archive.pak");
+        ArchiveBuilder::new()
+            .with_file("a.txt", b"foo".to_vec())
+            .with_file("b/c.txt", b"bar".to_vec())
+            .save(&path)
+            .unwrap();
+
+        let provider = ArchiveIoProvider::from_file(&path).unwrap();
+        assert_eq!(provider.file_count(), 2);
+
+        let mut vfs = Vfs::empty();
+        vfs.mount(provider, 0);
+        assert_eq!(load(&vfs, "a.txt").as_deref(), Some("foo"));
+        assert_eq!(load(&vfs, "b/c.txt").as_deref(), Some("bar"));
+
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn test_invalid_archive() {
+        assert!(ArchiveIoProvider::from_bytes(b"junk".to_vec()).is_err());
+    }
+}
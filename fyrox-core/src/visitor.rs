@@ -7,6 +7,33 @@
 //! container for data fields. Data field is tuple of name and value, value can be any of simple Rust
 //! types and some of basic structures of the crate. Main criteria of what could be the field and what
 //! not is the ability to be represented as set of bytes without any aliasing issues.
+//!
+//! # Supported types
+//!
+//! Besides primitive types, strings, paths and math types, [`Visit`] is implemented for common std
+//! containers: `Vec<T>`, `Option<T>`, `Box<T>`, fixed-size arrays `[T; N]`, `HashMap<K, V>` (with
+//! any key type that implements `Visit + Default + Hash + Eq`), `HashSet<T>`, `Cell<T>`,
+//! `RefCell<T>`, `Mutex<T>`, `RwLock<T>`, `Rc<T>`, `Arc<T>` and their weak counterparts. Shared
+//! pointers are saved only once, so `Rc<RefCell<T>>` or `Arc<Mutex<T>>` that point to the same data
+//! will still point to the same data after loading. Containers create new items using [`Default`]
+//! when reading.
+//!
+//! # Adding new fields
+//!
+//! When a field is added to a structure, data saved by previous versions won't have it and
+//! reading of such data fails by default. Mark the field with `#[visit(optional)]` (or ignore the
+//! result of `visit` in manual implementations) to keep its default value if it is missing:
+//!
+//! ```rust
+//! # use fyrox_core::visitor::prelude::*;
+//! #[derive(Visit, Default)]
+//! struct Inventory {
+//!     gold: u32,
+//!     // Added later, old saves will have zero gems.
+//!     #[visit(optional)]
+//!     gems: u32,
+//! }
+//! ```
 
 pub use fyrox_core_derive::Visit;
 
@@ -15,10 +42,12 @@ pub mod prelude {
     pub use super::{Visit, VisitError, VisitResult, Visitor};
 }
 
+mod text;
+
 use crate::{
     algebra::{
         Complex, Const, Matrix, Matrix2, Matrix3, Matrix4, Quaternion, RawStorage, RawStorageMut,
-        SVector, Scalar, UnitComplex, UnitQuaternion, Vector2, Vector3, Vector4, U1,
+        SMatrix, SVector, Scalar, UnitComplex, UnitQuaternion, Vector2, Vector3, Vector4, U1,
     },
     io::{self, FileLoadError},
     pool::{Handle, Pool},
@@ -558,6 +587,10 @@ pub enum VisitError {
     UnexpectedRcNullIndex,
     PoisonedMutex,
     FileLoadError(FileLoadError),
+    /// Data was written by a newer version of the format.
+    UnsupportedVersion(u32),
+    /// Text data is malformed.
+    InvalidTextFormat(String),
 }
 
 impl Display for VisitError {
@@ -580,6 +613,13 @@ impl Display for VisitError {
             Self::UnexpectedRcNullIndex => write!(f, "unexpected rc null index"),
             Self::PoisonedMutex => write!(f, "attempt to lock poisoned mutex"),
             Self::FileLoadError(e) => write!(f, "file load error: {:?}", e),
+            Self::UnsupportedVersion(version) => write!(
+                f,
+                "unsupported format version {} (max supported is {})",
+                version,
+                Visitor::CURRENT_VERSION
+            ),
+            Self::InvalidTextFormat(reason) => write!(f, "invalid text format: {}", reason),
         }
     }
 }
@@ -883,7 +923,7 @@ impl Field {
         Ok(())
     }
 
-    fn load(file: &mut dyn Read) -> Result<Field, VisitError> {
+    fn load(file: &mut dyn Read, version: u32) -> Result<Field, VisitError> {
         fn read_vec_n<T, S, const N: usize>(
             file: &mut dyn Read,
         ) -> Result<Matrix<T, Const<N>, U1, S>, VisitError>
@@ -898,6 +938,23 @@ impl Field {
             Ok(vec)
         }
 
+        fn read_matrix<const N: usize>(
+            file: &mut dyn Read,
+            version: u32,
+        ) -> Result<SMatrix<f32, N, N>, VisitError> {
+            let mut matrix = SMatrix::<f32, N, N>::zeros();
+            for v in matrix.iter_mut() {
+                *v = file.read_f32::<LittleEndian>()?;
+            }
+            // Matrices are written in column-major order, but older versions were reading them
+            // in row-major order. Keep the old behavior for the old data, so it is loaded the
+            // same way as before.
+            if version < Visitor::COLUMN_MAJOR_VERSION {
+                matrix.transpose_mut();
+            }
+            Ok(matrix)
+        }
+
         let name_len = file.read_u32::<LittleEndian>()? as usize;
         let mut raw_name = vec![Default::default(); name_len];
         file.read_exact(raw_name.as_mut_slice())?;
@@ -926,15 +983,16 @@ impl Field {
                     let y = file.read_f32::<LittleEndian>()?;
                     let z = file.read_f32::<LittleEndian>()?;
                     let w = file.read_f32::<LittleEndian>()?;
-                    UnitQuaternion::new_normalize(Quaternion::new(w, x, y, z))
-                }),
-                13 => FieldKind::Matrix4({
-                    let mut f = [0.0f32; 16];
-                    for n in &mut f {
-                        *n = file.read_f32::<LittleEndian>()?;
+                    let quaternion = Quaternion::new(w, x, y, z);
+                    if version < Visitor::COLUMN_MAJOR_VERSION {
+                        UnitQuaternion::new_normalize(quaternion)
+                    } else {
+                        // Stored quaternion is already normalized, normalizing it again
+                        // changes its bits and breaks exact round trips.
+                        UnitQuaternion::new_unchecked(quaternion)
                     }
-                    Matrix4::from_row_slice(&f)
                 }),
+                13 => FieldKind::Matrix4(read_matrix(file, version)?),
                 14 => FieldKind::BinaryBlob({
                     let len = file.read_u32::<LittleEndian>()? as usize;
                     let mut vec = vec![Default::default(); len];
@@ -942,13 +1000,7 @@ impl Field {
                     vec
                 }),
                 15 => FieldKind::Bool(file.read_u8()? != 0),
-                16 => FieldKind::Matrix3({
-                    let mut f = [0.0f32; 9];
-                    for n in &mut f {
-                        *n = file.read_f32::<LittleEndian>()?;
-                    }
-                    Matrix3::from_row_slice(&f)
-                }),
+                16 => FieldKind::Matrix3(read_matrix(file, version)?),
                 17 => FieldKind::Vector2F32({
                     let x = file.read_f32::<LittleEndian>()?;
                     let y = file.read_f32::<LittleEndian>()?;
@@ -969,7 +1021,12 @@ impl Field {
                 20 => FieldKind::UnitComplex({
                     let re = file.read_f32::<LittleEndian>()?;
                     let im = file.read_f32::<LittleEndian>()?;
-                    UnitComplex::from_complex(Complex::new(re, im))
+                    let complex = Complex::new(re, im);
+                    if version < Visitor::COLUMN_MAJOR_VERSION {
+                        UnitComplex::from_complex(complex)
+                    } else {
+                        UnitComplex::new_unchecked(complex)
+                    }
                 }),
                 21 => {
                     let type_id = file.read_u8()?;
@@ -983,13 +1040,9 @@ impl Field {
                         bytes,
                     }
                 }
-                22 => FieldKind::Matrix2({
-                    let mut f = [0.0f32; 3];
-                    for n in &mut f {
-                        *n = file.read_f32::<LittleEndian>()?;
-                    }
-                    Matrix2::from_row_slice(&f)
-                }),
+                // Every version writes all four elements of 2x2 matrix, older versions were
+                // reading only three of them.
+                22 => FieldKind::Matrix2(read_matrix(file, version)?),
                 23 => FieldKind::Vector2F64(read_vec_n(file)?),
                 24 => FieldKind::Vector3F64(read_vec_n(file)?),
                 25 => FieldKind::Vector4F64(read_vec_n(file)?),
@@ -1128,9 +1181,21 @@ pub struct Visitor {
     reading: bool,
     current_node: Handle<VisitorNode>,
     root: Handle<VisitorNode>,
+    version: u32,
     pub blackboard: Blackboard,
 }
 
+/// Data format of the visitor.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default)]
+pub enum VisitorFormat {
+    /// Compact binary format.
+    #[default]
+    Binary,
+    /// Human-readable text format, its output is deterministic and suitable for diffing. See
+    /// [`Visitor::save_text_format`] for more info.
+    Text,
+}
+
 pub trait Visit {
     fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult;
 }
@@ -1142,8 +1207,31 @@ impl Default for Visitor {
 }
 
 impl Visitor {
+    /// Magic of binary data written before the format versioning was added.
     pub const MAGIC: &'static str = "RG3D";
 
+    /// Magic of versioned binary data.
+    pub const MAGIC_BINARY: &'static str = "FBAF";
+
+    /// Magic of text data.
+    pub const MAGIC_TEXT: &'static str = "FTAF";
+
+    /// Current version of the format. It is written in the header of both binary and text data,
+    /// data with higher version cannot be read. Data without version (see [`Self::MAGIC`]) has
+    /// version 0.
+    pub const CURRENT_VERSION: u32 = 2;
+
+    /// The first version that reads matrices in column-major order, keeps exact values of unit
+    /// quaternions and complex numbers, and writes child nodes in their original order.
+    pub const COLUMN_MAJOR_VERSION: u32 = 2;
+
+    /// Returns true if the given magic (first four bytes of data) belongs to any supported format.
+    pub fn is_supported_magic(magic: &[u8]) -> bool {
+        [Self::MAGIC, Self::MAGIC_BINARY, Self::MAGIC_TEXT]
+            .iter()
+            .any(|m| m.as_bytes() == magic)
+    }
+
     pub fn new() -> Self {
         let mut nodes = Pool::new();
         let root = nodes.spawn(VisitorNode::new("__ROOT__", Handle::NONE));
@@ -1154,10 +1242,17 @@ impl Visitor {
             reading: false,
             current_node: root,
             root,
+            version: Self::CURRENT_VERSION,
             blackboard: Blackboard::new(),
         }
     }
 
+    /// Returns version of the format of the data. In read mode it is the version of the loaded data,
+    /// so it could be used to convert data written by older versions.
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+
     fn find_field(&mut self, name: &str) -> Option<&mut Field> {
         self.nodes
             .borrow_mut(self.current_node)
@@ -1253,14 +1348,40 @@ impl Visitor {
         }
     }
 
+    /// Returns compact dump of the visitor tree (one line per region), it is useful for debugging.
+    /// The dump cannot be read back, use [`Self::save_text_format`] if you need that.
     pub fn save_text(&self) -> String {
         let mut out_string = String::new();
         self.print_node(self.root, 0, &mut out_string);
         out_string
     }
 
+    /// Writes the entire content of the visitor in human-readable text format. The output is
+    /// deterministic - the same data always produces the same text, which makes it suitable for
+    /// diffing. Use [`Self::load_text_format`] to read the data back.
+    pub fn save_text_format(&self) -> String {
+        text::write(self)
+    }
+
+    /// Reads the visitor from the text produced by [`Self::save_text_format`].
+    pub fn load_text_format(text: &str) -> Result<Self, VisitError> {
+        text::read(text)
+    }
+
+    /// Saves the visitor to a file in the given format.
+    pub fn save_to_file<P: AsRef<Path>>(&self, path: P, format: VisitorFormat) -> VisitResult {
+        match format {
+            VisitorFormat::Binary => self.save_binary(path),
+            VisitorFormat::Text => {
+                std::fs::write(path, self.save_text_format())?;
+                Ok(())
+            }
+        }
+    }
+
     pub fn save_binary_to_memory<W: Write>(&self, mut writer: W) -> VisitResult {
-        writer.write_all(Self::MAGIC.as_bytes())?;
+        writer.write_all(Self::MAGIC_BINARY.as_bytes())?;
+        writer.write_u32::<LittleEndian>(Self::CURRENT_VERSION)?;
         let mut stack = vec![self.root];
         while let Some(node_handle) = stack.pop() {
             let node = self.nodes.borrow(node_handle);
@@ -1274,7 +1395,9 @@ impl Visitor {
             }
 
             writer.write_u32::<LittleEndian>(node.children.len() as u32)?;
-            stack.extend_from_slice(&node.children);
+            // Children are pushed in reverse order, so they're written (and then read) in
+            // their original order.
+            stack.extend(node.children.iter().rev());
         }
         Ok(())
     }
@@ -1302,7 +1425,7 @@ impl Visitor {
 
         let field_count = file.read_u32::<LittleEndian>()? as usize;
         for _ in 0..field_count {
-            let field = Field::load(file)?;
+            let field = Field::load(file, self.version)?;
             node.fields.push(field);
         }
 
@@ -1311,6 +1434,10 @@ impl Visitor {
         for _ in 0..child_count {
             children.push(self.load_node_binary(file)?);
         }
+        if self.version < Self::COLUMN_MAJOR_VERSION {
+            // Older versions were writing child nodes in reverse order.
+            children.reverse();
+        }
 
         node.children = children.clone();
 
@@ -1323,16 +1450,31 @@ impl Visitor {
         Ok(handle)
     }
 
+    /// Loads the visitor from a file. The format (binary or text) is detected automatically.
     pub async fn load_binary<P: AsRef<Path>>(path: P) -> Result<Self, VisitError> {
         Self::load_from_memory(io::load_file(path).await?)
     }
 
+    /// Loads the visitor from the data. The format (binary or text) is detected automatically.
     pub fn load_from_memory(data: Vec<u8>) -> Result<Self, VisitError> {
+        if data.starts_with(Self::MAGIC_TEXT.as_bytes()) {
+            return Self::load_text_format(
+                std::str::from_utf8(&data).map_err(|_| VisitError::NotSupportedFormat)?,
+            );
+        }
+
         let mut reader = Cursor::new(data);
         let mut magic: [u8; 4] = Default::default();
         reader.read_exact(&mut magic)?;
-        if !magic.eq(Self::MAGIC.as_bytes()) {
+        let version = if magic.eq(Self::MAGIC.as_bytes()) {
+            0
+        } else if magic.eq(Self::MAGIC_BINARY.as_bytes()) {
+            reader.read_u32::<LittleEndian>()?
+        } else {
             return Err(VisitError::NotSupportedFormat);
+        };
+        if version > Self::CURRENT_VERSION {
+            return Err(VisitError::UnsupportedVersion(version));
         }
         let mut visitor = Self {
             nodes: Pool::new(),
@@ -1341,6 +1483,7 @@ impl Visitor {
             reading: true,
             current_node: Handle::NONE,
             root: Handle::NONE,
+            version,
             blackboard: Blackboard::new(),
         };
         visitor.root = visitor.load_node_binary(&mut reader)?;
@@ -1722,9 +1865,9 @@ where
 
 impl<K, V, S> Visit for HashMap<K, V, S>
 where
-    K: Visit + Default + Clone + Hash + Eq,
+    K: Visit + Default + Hash + Eq,
     V: Visit + Default,
-    S: BuildHasher + Clone,
+    S: BuildHasher,
 {
     fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
         let mut region = visitor.enter_region(name)?;
@@ -1748,16 +1891,21 @@ where
                 self.insert(key, value);
             }
         } else {
-            for (i, (key, value)) in self.iter_mut().enumerate() {
-                let name = format!("Item{}", i);
-
-                let mut region = region.enter_region(name.as_str())?;
-
-                let mut key = key.clone();
-                key.visit("Key", &mut region)?;
-
-                value.visit("Value", &mut region)?;
-            }
+            // Keys can't be borrowed mutably, so the entries are taken out and put back.
+            let mut entries = self.drain().collect::<Vec<_>>();
+            let result = entries
+                .iter_mut()
+                .enumerate()
+                .try_for_each(|(i, (key, value))| {
+                    let name = format!("Item{}", i);
+
+                    let mut region = region.enter_region(name.as_str())?;
+
+                    key.visit("Key", &mut region)?;
+                    value.visit("Value", &mut region)
+                });
+            self.extend(entries);
+            result?;
         }
 
         Ok(())
@@ -1766,8 +1914,8 @@ where
 
 impl<K, S> Visit for HashSet<K, S>
 where
-    K: Visit + Default + Clone + Hash + Eq,
-    S: BuildHasher + Clone,
+    K: Visit + Default + Hash + Eq,
+    S: BuildHasher,
 {
     fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
         let mut region = visitor.enter_region(name)?;
@@ -1788,13 +1936,17 @@ where
                 self.insert(key);
             }
         } else {
-            for (i, mut key) in self.clone().into_iter().enumerate() {
+            // Keys can't be borrowed mutably, so the keys are taken out and put back.
+            let mut keys = self.drain().collect::<Vec<_>>();
+            let result = keys.iter_mut().enumerate().try_for_each(|(i, key)| {
                 let name = format!("Item{}", i);
 
                 let mut region = region.enter_region(name.as_str())?;
 
-                key.visit("Key", &mut region)?;
-            }
+                key.visit("Key", &mut region)
+            });
+            self.extend(keys);
+            result?;
         }
 
         Ok(())
@@ -2210,4 +2362,334 @@ mod test {
             "<vec4i64 = 0; 0; 0; 0>, ".to_string()
         );
     }
+
+    #[derive(Visit, Default)]
+    struct Everything {
+        flag: bool,
+        byte: u8,
+        signed: i64,
+        float: f32,
+        double: f64,
+        position: Vector3<f32>,
+        color: Vector4<u8>,
+        rotation: UnitQuaternion<f32>,
+        angle: UnitComplex<f32>,
+        basis: Matrix3<f32>,
+        transform: Matrix4<f32>,
+        id: Uuid,
+        name: String,
+        path: PathBuf,
+        items: Vec<Vector2<f64>>,
+        shared: Option<Rc<Resource>>,
+        raw: Vec<u8>,
+    }
+
+    fn make_everything_visitor() -> Visitor {
+        let resource = Rc::new(Resource::new(ResourceKind::Texture(Texture {
+            data: vec![0, 159, 146, 150],
+        })));
+
+        let mut objects = vec![
+            Everything {
+                flag: true,
+                byte: 42,
+                signed: -123456789,
+                float: 0.1,
+                double: -1.0e-30,
+                position: Vector3::new(1.0, -2.5, f32::MAX),
+                color: Vector4::new(1, 2, 3, 255),
+                rotation: UnitQuaternion::from_euler_angles(0.1, 0.2, 0.3),
+                angle: UnitComplex::new(1.0),
+                basis: Matrix3::new(1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 9.0),
+                transform: Matrix4::new_translation(&Vector3::new(1.0, 2.0, 3.0)),
+                id: Uuid::from_u128(0x1234_5678_9abc_def0),
+                name: "Quoted \"name\"\n\twith escapes \\ and unicode: \u{1F600} \u{7}".to_string(),
+                path: PathBuf::from("data/models/model.fbx"),
+                items: vec![Vector2::new(0.5, 0.25), Vector2::new(-3.0, 1e100)],
+                shared: Some(resource.clone()),
+                raw: vec![1, 2, 3],
+            },
+            Everything {
+                shared: Some(resource),
+                ..Default::default()
+            },
+        ];
+
+        let mut visitor = Visitor::new();
+        objects.visit("Objects", &mut visitor).unwrap();
+        visitor
+    }
+
+    #[test]
+    fn text_round_trip() {
+        let binary = make_everything_visitor().save_binary_to_vec().unwrap();
+
+        let visitor = Visitor::load_from_memory(binary.clone()).unwrap();
+        let text = visitor.save_text_format();
+        assert!(text.starts_with(&format!("FTAF {}\n", Visitor::CURRENT_VERSION)));
+
+        let mut from_text = Visitor::load_text_format(&text).unwrap();
+        assert_eq!(from_text.version(), Visitor::CURRENT_VERSION);
+        assert_eq!(from_text.save_text_format(), text);
+        assert_eq!(from_text.save_binary_to_vec().unwrap(), binary);
+
+        // Text data is detected automatically.
+        assert!(Visitor::load_from_memory(text.into_bytes()).is_ok());
+
+        let mut objects = Vec::<Everything>::new();
+        objects.visit("Objects", &mut from_text).unwrap();
+        assert_eq!(objects.len(), 2);
+        assert_eq!(objects[0].byte, 42);
+        assert_eq!(objects[0].float, 0.1);
+        assert_eq!(objects[0].position, Vector3::new(1.0, -2.5, f32::MAX));
+        assert_eq!(
+            objects[0].name,
+            "Quoted \"name\"\n\twith escapes \\ and unicode: \u{1F600} \u{7}"
+        );
+        assert_eq!(objects[0].items[1], Vector2::new(-3.0, 1e100));
+        assert!(Rc::ptr_eq(
+            objects[0].shared.as_ref().unwrap(),
+            objects[1].shared.as_ref().unwrap()
+        ));
+    }
+
+    #[test]
+    fn format_versions() {
+        let visitor = make_everything_visitor();
+
+        let binary = visitor.save_binary_to_vec().unwrap();
+        assert!(binary.starts_with(Visitor::MAGIC_BINARY.as_bytes()));
+        assert_eq!(
+            Visitor::load_from_memory(binary.clone()).unwrap().version(),
+            Visitor::CURRENT_VERSION
+        );
+
+        // Data without version.
+        let mut legacy = Visitor::MAGIC.as_bytes().to_vec();
+        legacy.extend_from_slice(&binary[8..]);
+        assert_eq!(Visitor::load_from_memory(legacy).unwrap().version(), 0);
+
+        // Data from the future.
+        let mut future = binary;
+        future[4..8].copy_from_slice(&(Visitor::CURRENT_VERSION + 1).to_le_bytes());
+        assert!(matches!(
+            Visitor::load_from_memory(future),
+            Err(VisitError::UnsupportedVersion(v)) if v == Visitor::CURRENT_VERSION + 1
+        ));
+
+        let text = visitor.save_text_format().replacen(
+            &format!("FTAF {}", Visitor::CURRENT_VERSION),
+            &format!("FTAF {}", Visitor::CURRENT_VERSION + 1),
+            1,
+        );
+        assert!(matches!(
+            Visitor::load_text_format(&text),
+            Err(VisitError::UnsupportedVersion(_))
+        ));
+
+        assert!(matches!(
+            Visitor::load_text_format("FTAF 1\n\"__ROOT__\" {\n    \"Field\": u8 256\n}\n"),
+            Err(VisitError::InvalidTextFormat(_))
+        ));
+        assert!(matches!(
+            Visitor::load_text_format("FTAF 1\n\"__ROOT__\" {\n"),
+            Err(VisitError::InvalidTextFormat(_))
+        ));
+    }
+
+    #[derive(Visit, Default)]
+    struct Child {
+        value: u32,
+    }
+
+    #[derive(Visit, Default)]
+    struct Legacy {
+        first: Child,
+        second: Child,
+        basis: Matrix3<f32>,
+        rotation: UnitQuaternion<f32>,
+        angle: UnitComplex<f32>,
+    }
+
+    // `Legacy` with `first.value = 1`, `second.value = 2`, `basis = Matrix3::new(1.0, ..., 9.0)`,
+    // `rotation` of 90 degrees around Z axis and `angle` of 180 degrees, saved by the version of
+    // the visitor without format version in the header.
+    const LEGACY_DATA: [u8; 198] = [
+        82, 71, 51, 68, 8, 0, 0, 0, 95, 95, 82, 79, 79, 84, 95, 95, 0, 0, 0, 0, 1, 0, 0, 0, 6, 0,
+        0, 0, 76, 101, 103, 97, 99, 121, 3, 0, 0, 0, 5, 0, 0, 0, 66, 97, 115, 105, 115, 16, 0, 0,
+        128, 63, 0, 0, 128, 64, 0, 0, 224, 64, 0, 0, 0, 64, 0, 0, 160, 64, 0, 0, 0, 65, 0, 0, 64,
+        64, 0, 0, 192, 64, 0, 0, 16, 65, 8, 0, 0, 0, 82, 111, 116, 97, 116, 105, 111, 110, 12, 0,
+        0, 0, 0, 0, 0, 0, 0, 243, 4, 53, 63, 243, 4, 53, 63, 5, 0, 0, 0, 65, 110, 103, 108, 101,
+        20, 0, 0, 128, 191, 46, 189, 187, 179, 2, 0, 0, 0, 6, 0, 0, 0, 83, 101, 99, 111, 110, 100,
+        1, 0, 0, 0, 5, 0, 0, 0, 86, 97, 108, 117, 101, 5, 2, 0, 0, 0, 0, 0, 0, 0, 5, 0, 0, 0, 70,
+        105, 114, 115, 116, 1, 0, 0, 0, 5, 0, 0, 0, 86, 97, 108, 117, 101, 5, 1, 0, 0, 0, 0, 0, 0,
+        0,
+    ];
+
+    #[test]
+    fn load_legacy_data() {
+        let mut visitor = Visitor::load_from_memory(LEGACY_DATA.to_vec()).unwrap();
+        assert_eq!(visitor.version(), 0);
+
+        // Child nodes are restored in their original order.
+        let root = visitor.nodes.borrow(visitor.root);
+        let legacy = visitor.nodes.borrow(root.children[0]);
+        let names = legacy
+            .children
+            .iter()
+            .map(|c| visitor.nodes.borrow(*c).name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["First", "Second"]);
+
+        let mut legacy = Legacy::default();
+        legacy.visit("Legacy", &mut visitor).unwrap();
+        assert_eq!(legacy.first.value, 1);
+        assert_eq!(legacy.second.value, 2);
+        // Old data is loaded exactly as it was loaded by the old version.
+        assert_eq!(
+            legacy.basis,
+            Matrix3::new(1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 9.0).transpose()
+        );
+        let rotation = UnitQuaternion::from_euler_angles(0.0, 0.0, std::f32::consts::FRAC_PI_2);
+        assert!(legacy.rotation.angle_to(&rotation) < 1.0e-6);
+        assert!((legacy.angle.angle().abs() - std::f32::consts::PI).abs() < 1.0e-6);
+    }
+
+    #[test]
+    fn binary_round_trip_is_exact() {
+        let mut source = Legacy {
+            first: Child { value: 1 },
+            second: Child { value: 2 },
+            basis: Matrix3::new(1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 9.0),
+            rotation: UnitQuaternion::from_euler_angles(0.1, 0.2, 0.3),
+            angle: UnitComplex::new(0.3),
+        };
+        let mut visitor = Visitor::new();
+        source.visit("Legacy", &mut visitor).unwrap();
+        let binary = visitor.save_binary_to_vec().unwrap();
+
+        let mut visitor = Visitor::load_from_memory(binary.clone()).unwrap();
+        assert_eq!(visitor.save_binary_to_vec().unwrap(), binary);
+
+        let mut loaded = Legacy::default();
+        loaded.visit("Legacy", &mut visitor).unwrap();
+        assert_eq!(loaded.basis, source.basis);
+        assert_eq!(loaded.rotation, source.rotation);
+        assert_eq!(loaded.angle, source.angle);
+    }
+
+    fn round_trip<T: Visit + Default>(value: &mut T) -> T {
+        let mut visitor = Visitor::new();
+        value.visit("Value", &mut visitor).unwrap();
+        let data = visitor.save_binary_to_vec().unwrap();
+
+        let mut visitor = Visitor::load_from_memory(data).unwrap();
+        let mut loaded = T::default();
+        loaded.visit("Value", &mut visitor).unwrap();
+        loaded
+    }
+
+    // Intentionally not `Clone`.
+    #[derive(Visit, Default, Debug, PartialEq, Eq, Hash)]
+    struct ItemId(u32);
+
+    #[test]
+    fn test_hash_map_round_trip() {
+        let mut empty = HashMap::<ItemId, String>::new();
+        assert_eq!(round_trip(&mut empty), empty);
+
+        let mut single = HashMap::from([(ItemId(1), "Sword".to_string())]);
+        assert_eq!(round_trip(&mut single), single);
+
+        let mut many = (0..10)
+            .map(|i| (ItemId(i), i as u64 * 100))
+            .collect::<HashMap<_, _>>();
+        assert_eq!(round_trip(&mut many), many);
+        // Writing must keep the contents intact.
+        assert_eq!(many.len(), 10);
+    }
+
+    #[test]
+    fn test_hash_set_round_trip() {
+        let mut empty = HashSet::<ItemId>::new();
+        assert_eq!(round_trip(&mut empty), empty);
+
+        let mut single = HashSet::from([ItemId(7)]);
+        assert_eq!(round_trip(&mut single), single);
+
+        let mut many = (0..10).map(ItemId).collect::<HashSet<_>>();
+        assert_eq!(round_trip(&mut many), many);
+        assert_eq!(many.len(), 10);
+    }
+
+    #[test]
+    fn test_option_box_and_array_round_trip() {
+        let mut none: Option<Box<u32>> = None;
+        assert_eq!(round_trip(&mut none), None);
+
+        let mut some = Some(Box::new(42u32));
+        assert_eq!(round_trip(&mut some), some);
+
+        let mut empty: [u32; 0] = [];
+        assert_eq!(round_trip(&mut empty), empty);
+
+        let mut single = [ItemId(3)];
+        assert_eq!(round_trip(&mut single), single);
+
+        let mut many = [1.0f32, 2.0, 3.0, 4.0];
+        assert_eq!(round_trip(&mut many), many);
+    }
+
+    #[derive(Visit, Default)]
+    struct Shared {
+        a: Rc<RefCell<u32>>,
+        b: Rc<RefCell<u32>>,
+        c: Arc<Mutex<u32>>,
+        d: Arc<Mutex<u32>>,
+    }
+
+    #[test]
+    fn test_shared_pointers_round_trip() {
+        let rc = Rc::new(RefCell::new(1));
+        let arc = Arc::new(Mutex::new(2));
+        let mut shared = Shared {
+            a: rc.clone(),
+            b: rc,
+            c: arc.clone(),
+            d: arc,
+        };
+
+        let loaded = round_trip(&mut shared);
+        assert_eq!(*loaded.a.borrow(), 1);
+        assert!(Rc::ptr_eq(&loaded.a, &loaded.b));
+        assert_eq!(*loaded.c.lock().unwrap(), 2);
+        assert!(Arc::ptr_eq(&loaded.c, &loaded.d));
+    }
+
+    #[derive(Visit, Default)]
+    struct InventoryV1 {
+        gold: u32,
+    }
+
+    #[derive(Visit, Default)]
+    struct InventoryV2 {
+        gold: u32,
+        #[visit(optional)]
+        items: HashMap<ItemId, u32>,
+    }
+
+    #[test]
+    fn test_missing_optional_field_keeps_default() {
+        let mut visitor = Visitor::new();
+        InventoryV1 { gold: 10 }
+            .visit("Inventory", &mut visitor)
+            .unwrap();
+        let data = visitor.save_binary_to_vec().unwrap();
+
+        let mut visitor = Visitor::load_from_memory(data).unwrap();
+        let mut inventory = InventoryV2::default();
+        inventory.visit("Inventory", &mut visitor).unwrap();
+        assert_eq!(inventory.gold, 10);
+        assert!(inventory.items.is_empty());
+    }
 }
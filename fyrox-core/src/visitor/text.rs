@@ -0,0 +1,377 @@
+//! Human-readable text format of the visitor. The format is deterministic (the same data always
+//! produces the same text) and line-based, which makes it suitable for diffing in version control
+//! systems.
+//!
+//! # Format
+//!
+//! ```text
+//! FTAF 1
+//! "__ROOT__" {
+//!     "Speed": f32 1.5
+//!     "Name": str "Player"
+//!     "Transform" {
+//!         "Position": vec3f32 1.0 2.0 3.0
+//!     }
+//! }
+//! ```
+//!
+//! The first line is the header with the format version. Every region is written as a quoted name
+//! followed by its fields and child regions in braces. Every field is a quoted name, type and
+//! space-separated values.
+
+use crate::{
+    algebra::{
+        Complex, Matrix2, Matrix3, Matrix4, Quaternion, SVector, Scalar, UnitComplex,
+        UnitQuaternion,
+    },
+    pool::{Handle, Pool},
+    visitor::{Blackboard, Field, FieldKind, VisitError, Visitor, VisitorNode},
+};
+use base64::Engine;
+use std::{
+    fmt::{Debug, Write},
+    str::FromStr,
+};
+use uuid::Uuid;
+
+macro_rules! define_vector_kinds {
+    ($($variant:ident => $token:literal),* $(,)?) => {
+        fn vector_token(kind: &FieldKind) -> Option<(&'static str, Vec<String>)> {
+            match kind {
+                $(FieldKind::$variant(v) => {
+                    Some(($token, v.iter().map(|x| format!("{:?}", x)).collect()))
+                })*
+                _ => None,
+            }
+        }
+
+        fn parse_vector(token: &str, values: &[&str]) -> Result<Option<FieldKind>, String> {
+            match token {
+                $($token => Ok(Some(FieldKind::$variant(parse_vector_values(values)?))),)*
+                _ => Ok(None),
+            }
+        }
+    };
+}
+
+define_vector_kinds! {
+    Vector2F32 => "vec2f32",
+    Vector3F32 => "vec3f32",
+    Vector4F32 => "vec4f32",
+    Vector2F64 => "vec2f64",
+    Vector3F64 => "vec3f64",
+    Vector4F64 => "vec4f64",
+    Vector2U8 => "vec2u8",
+    Vector3U8 => "vec3u8",
+    Vector4U8 => "vec4u8",
+    Vector2I8 => "vec2i8",
+    Vector3I8 => "vec3i8",
+    Vector4I8 => "vec4i8",
+    Vector2U16 => "vec2u16",
+    Vector3U16 => "vec3u16",
+    Vector4U16 => "vec4u16",
+    Vector2I16 => "vec2i16",
+    Vector3I16 => "vec3i16",
+    Vector4I16 => "vec4i16",
+    Vector2U32 => "vec2u32",
+    Vector3U32 => "vec3u32",
+    Vector4U32 => "vec4u32",
+    Vector2I32 => "vec2i32",
+    Vector3I32 => "vec3i32",
+    Vector4I32 => "vec4i32",
+    Vector2U64 => "vec2u64",
+    Vector3U64 => "vec3u64",
+    Vector4U64 => "vec4u64",
+    Vector2I64 => "vec2i64",
+    Vector3I64 => "vec3i64",
+    Vector4I64 => "vec4i64",
+}
+
+fn join<T: Debug>(values: impl Iterator<Item = T>) -> String {
+    values
+        .map(|v| format!("{:?}", v))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn encode(bytes: &[u8]) -> String {
+    base64::engine::general_purpose::STANDARD.encode(bytes)
+}
+
+fn write_field_kind(kind: &FieldKind, out: &mut String) {
+    if let Some((token, values)) = vector_token(kind) {
+        let _ = write!(out, "{} {}", token, values.join(" "));
+        return;
+    }
+
+    let _ = match kind {
+        FieldKind::Bool(v) => write!(out, "bool {}", v),
+        FieldKind::U8(v) => write!(out, "u8 {}", v),
+        FieldKind::I8(v) => write!(out, "i8 {}", v),
+        FieldKind::U16(v) => write!(out, "u16 {}", v),
+        FieldKind::I16(v) => write!(out, "i16 {}", v),
+        FieldKind::U32(v) => write!(out, "u32 {}", v),
+        FieldKind::I32(v) => write!(out, "i32 {}", v),
+        FieldKind::U64(v) => write!(out, "u64 {}", v),
+        FieldKind::I64(v) => write!(out, "i64 {}", v),
+        FieldKind::F32(v) => write!(out, "f32 {:?}", v),
+        FieldKind::F64(v) => write!(out, "f64 {:?}", v),
+        FieldKind::UnitQuaternion(v) => write!(out, "quat {:?} {:?} {:?} {:?}", v.i, v.j, v.k, v.w),
+        FieldKind::UnitComplex(v) => write!(out, "complex {:?} {:?}", v.re, v.im),
+        // Matrices are written in column-major order.
+        FieldKind::Matrix2(v) => write!(out, "mat2 {}", join(v.iter())),
+        FieldKind::Matrix3(v) => write!(out, "mat3 {}", join(v.iter())),
+        FieldKind::Matrix4(v) => write!(out, "mat4 {}", join(v.iter())),
+        FieldKind::Uuid(v) => write!(out, "uuid {}", v),
+        FieldKind::BinaryBlob(v) => match std::str::from_utf8(v) {
+            Ok(string) => write!(out, "str {:?}", string),
+            Err(_) => write!(out, "blob {}", encode(v)),
+        },
+        FieldKind::PodArray {
+            type_id,
+            element_size,
+            bytes,
+        } => write!(out, "pod {} {} {}", type_id, element_size, encode(bytes)),
+        _ => unreachable!("vector kinds are handled above"),
+    };
+}
+
+fn write_node(visitor: &Visitor, handle: Handle<VisitorNode>, depth: usize, out: &mut String) {
+    let indent = "    ".repeat(depth);
+    let node = visitor.nodes.borrow(handle);
+
+    let _ = writeln!(out, "{}{:?} {{", indent, node.name);
+    for field in node.fields.iter() {
+        let _ = write!(out, "{}    {:?}: ", indent, field.name);
+        write_field_kind(&field.kind, out);
+        out.push('\n');
+    }
+    for &child in node.children.iter() {
+        write_node(visitor, child, depth + 1, out);
+    }
+    let _ = writeln!(out, "{}}}", indent);
+}
+
+/// Writes the entire content of the visitor in the text format.
+pub(super) fn write(visitor: &Visitor) -> String {
+    let mut out = format!("{} {}\n", Visitor::MAGIC_TEXT, Visitor::CURRENT_VERSION);
+    write_node(visitor, visitor.root, 0, &mut out);
+    out
+}
+
+/// Splits a line that starts with a quoted string into the unescaped string and the rest of the
+/// line. Supports escape sequences produced by `{:?}` formatting of strings.
+fn parse_quoted(line: &str) -> Result<(String, &str), String> {
+    let mut chars = line.char_indices();
+    if !matches!(chars.next(), Some((_, '"'))) {
+        return Err("expected quoted string".to_string());
+    }
+
+    let mut string = String::new();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '"' => return Ok((string, &line[i + 1..])),
+            '\\' => match chars.next().map(|(_, c)| c) {
+                Some('n') => string.push('\n'),
+                Some('r') => string.push('\r'),
+                Some('t') => string.push('\t'),
+                Some('0') => string.push('\0'),
+                Some('\\') => string.push('\\'),
+                Some('"') => string.push('"'),
+                Some('\'') => string.push('\''),
+                Some('u') => {
+                    let rest = &line[i + 2..];
+                    let end = rest
+                        .find('}')
+                        .ok_or_else(|| "unterminated unicode escape".to_string())?;
+                    let code = rest
+                        .get(1..end)
+                        .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+                        .and_then(char::from_u32)
+                        .ok_or_else(|| "invalid unicode escape".to_string())?;
+                    string.push(code);
+                    for _ in 0..=end {
+                        chars.next();
+                    }
+                }
+                _ => return Err("invalid escape sequence".to_string()),
+            },
+            _ => string.push(c),
+        }
+    }
+
+    Err("unterminated quoted string".to_string())
+}
+
+fn parse_value<T: FromStr>(value: Option<&&str>) -> Result<T, String> {
+    value
+        .and_then(|v| v.parse().ok())
+        .ok_or_else(|| format!("invalid value {:?}", value))
+}
+
+fn parse_values<T: FromStr>(values: &[&str], count: usize) -> Result<Vec<T>, String> {
+    if values.len() != count {
+        return Err(format!("expected {} values, got {}", count, values.len()));
+    }
+    values.iter().map(|v| parse_value(Some(v))).collect()
+}
+
+fn parse_vector_values<T, const N: usize>(values: &[&str]) -> Result<SVector<T, N>, String>
+where
+    T: FromStr + Scalar,
+{
+    Ok(SVector::from_vec(parse_values(values, N)?))
+}
+
+fn decode(value: Option<&&str>) -> Result<Vec<u8>, String> {
+    base64::engine::general_purpose::STANDARD
+        .decode(value.copied().unwrap_or_default())
+        .map_err(|e| e.to_string())
+}
+
+fn parse_field_kind(definition: &str) -> Result<FieldKind, String> {
+    let definition = definition.trim_start();
+    let (token, rest) = definition
+        .split_once(' ')
+        .unwrap_or((definition, Default::default()));
+
+    if token == "str" {
+        let (string, _) = parse_quoted(rest.trim())?;
+        return Ok(FieldKind::BinaryBlob(string.into_bytes()));
+    }
+
+    let values = rest.split_whitespace().collect::<Vec<_>>();
+
+    if let Some(kind) = parse_vector(token, &values)? {
+        return Ok(kind);
+    }
+
+    Ok(match token {
+        "bool" => FieldKind::Bool(parse_value(values.first())?),
+        "u8" => FieldKind::U8(parse_value(values.first())?),
+        "i8" => FieldKind::I8(parse_value(values.first())?),
+        "u16" => FieldKind::U16(parse_value(values.first())?),
+        "i16" => FieldKind::I16(parse_value(values.first())?),
+        "u32" => FieldKind::U32(parse_value(values.first())?),
+        "i32" => FieldKind::I32(parse_value(values.first())?),
+        "u64" => FieldKind::U64(parse_value(values.first())?),
+        "i64" => FieldKind::I64(parse_value(values.first())?),
+        "f32" => FieldKind::F32(parse_value(values.first())?),
+        "f64" => FieldKind::F64(parse_value(values.first())?),
+        "quat" => {
+            let v = parse_values::<f32>(&values, 4)?;
+            // Values are written exactly, so there is no need to re-normalize them.
+            FieldKind::UnitQuaternion(UnitQuaternion::new_unchecked(Quaternion::new(
+                v[3], v[0], v[1], v[2],
+            )))
+        }
+        "complex" => {
+            let v = parse_values::<f32>(&values, 2)?;
+            FieldKind::UnitComplex(UnitComplex::new_unchecked(Complex::new(v[0], v[1])))
+        }
+        "mat2" => FieldKind::Matrix2(Matrix2::from_column_slice(&parse_values(&values, 4)?)),
+        "mat3" => FieldKind::Matrix3(Matrix3::from_column_slice(&parse_values(&values, 9)?)),
+        "mat4" => FieldKind::Matrix4(Matrix4::from_column_slice(&parse_values(&values, 16)?)),
+        "uuid" => FieldKind::Uuid(parse_value::<Uuid>(values.first())?),
+        "blob" => FieldKind::BinaryBlob(decode(values.first())?),
+        "pod" => FieldKind::PodArray {
+            type_id: parse_value(values.first())?,
+            element_size: parse_value(values.get(1))?,
+            bytes: decode(values.get(2))?,
+        },
+        _ => return Err(format!("unknown field type {}", token)),
+    })
+}
+
+fn parse_line(
+    visitor: &mut Visitor,
+    stack: &mut Vec<Handle<VisitorNode>>,
+    line: &str,
+) -> Result<(), String> {
+    if line == "}" {
+        return stack
+            .pop()
+            .map(|_| ())
+            .ok_or_else(|| "unexpected closing brace".to_string());
+    }
+
+    let (name, rest) = parse_quoted(line)?;
+    let rest = rest.trim();
+    if rest == "{" {
+        let parent = stack.last().copied().unwrap_or_default();
+        if parent.is_none() && visitor.root.is_some() {
+            return Err("multiple root regions".to_string());
+        }
+
+        let handle = visitor.nodes.spawn(VisitorNode::new(&name, parent));
+        if parent.is_some() {
+            visitor.nodes.borrow_mut(parent).children.push(handle);
+        } else {
+            visitor.root = handle;
+        }
+        stack.push(handle);
+    } else if let Some(definition) = rest.strip_prefix(':') {
+        let current = *stack
+            .last()
+            .ok_or_else(|| "field outside of a region".to_string())?;
+        let kind = parse_field_kind(definition)?;
+        visitor
+            .nodes
+            .borrow_mut(current)
+            .fields
+            .push(Field::new(&name, kind));
+    } else {
+        return Err(format!("unexpected {:?}", rest));
+    }
+
+    Ok(())
+}
+
+/// Reads the visitor in read mode from the text produced by [`write`].
+pub(super) fn read(text: &str) -> Result<Visitor, VisitError> {
+    let mut lines = text.lines().enumerate();
+
+    let header = lines.next().map(|(_, line)| line).unwrap_or_default();
+    let version = match header.trim().split_once(' ') {
+        Some((magic, version)) if magic == Visitor::MAGIC_TEXT => version
+            .trim()
+            .parse::<u32>()
+            .map_err(|_| VisitError::NotSupportedFormat)?,
+        _ => return Err(VisitError::NotSupportedFormat),
+    };
+    if version > Visitor::CURRENT_VERSION {
+        return Err(VisitError::UnsupportedVersion(version));
+    }
+
+    let mut visitor = Visitor {
+        nodes: Pool::new(),
+        rc_map: Default::default(),
+        arc_map: Default::default(),
+        reading: true,
+        current_node: Handle::NONE,
+        root: Handle::NONE,
+        version,
+        blackboard: Blackboard::new(),
+    };
+
+    let mut stack = Vec::new();
+    for (index, line) in lines {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        parse_line(&mut visitor, &mut stack, line).map_err(|reason| {
+            VisitError::InvalidTextFormat(format!("line {}: {}", index + 1, reason))
+        })?;
+    }
+
+    if visitor.root.is_none() || !stack.is_empty() {
+        return Err(VisitError::InvalidTextFormat(
+            "unexpected end of data".to_string(),
+        ));
+    }
+
+    visitor.current_node = visitor.root;
+    Ok(visitor)
+}
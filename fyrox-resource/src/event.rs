@@ -24,6 +24,11 @@ pub enum ResourceEvent {
 
     /// Occurs when a resource was removed from a resource container.
     Removed(PathBuf),
+
+    /// Occurs right after [`ResourceEvent::Removed`] and carries a key of the removed resource
+    /// (see [`UntypedResource::key`]). It could be used to release any data associated with the
+    /// resource, for example GPU objects.
+    Unloaded(usize),
 }
 
 /// Type alias for event sender.
@@ -23,7 +23,7 @@ use std::{
     future::Future,
     hash::{Hash, Hasher},
     marker::PhantomData,
-    ops::{Deref, DerefMut},
+    ops::{AddAssign, Deref, DerefMut},
     path::{Path, PathBuf},
     pin::Pin,
     sync::Arc,
@@ -42,6 +42,7 @@ pub mod options;
 pub mod state;
 mod task;
 pub mod untyped;
+pub mod upload;
 
 /// Type UUID of texture resource. It is defined here to load old versions of resources.
 pub const TEXTURE_RESOURCE_UUID: Uuid = uuid!("02c23a44-55fa-411a-bc39-eb7a5eadf15c");
@@ -70,6 +71,28 @@ pub trait ResourceData: 'static + Debug + Visit + Send + Reflect {
 
     /// Returns unique data type id.
     fn type_uuid(&self) -> Uuid;
+
+    /// Returns estimated amount of memory used by the resource data. Default implementation returns
+    /// zero usage, which means that the amount is unknown.
+    fn memory_usage(&self) -> ResourceMemoryUsage {
+        Default::default()
+    }
+}
+
+/// Estimated amount of memory used by a resource.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct ResourceMemoryUsage {
+    /// Amount of bytes used in CPU memory.
+    pub cpu_bytes: usize,
+    /// Amount of bytes used in GPU memory.
+    pub gpu_bytes: usize,
+}
+
+impl AddAssign for ResourceMemoryUsage {
+    fn add_assign(&mut self, rhs: Self) {
+        self.cpu_bytes += rhs.cpu_bytes;
+        self.gpu_bytes += rhs.gpu_bytes;
+    }
 }
 
 /// A trait for resource load error.
@@ -7,15 +7,18 @@ use crate::{
     loader::ResourceLoadersContainer,
     state::ResourceState,
     task::TaskPool,
-    Resource, ResourceData, UntypedResource,
+    upload::SharedUploadQueue,
+    Resource, ResourceData, ResourceLoadError, ResourceMemoryUsage, UntypedResource,
 };
 use fxhash::FxHashMap;
 use fyrox_core::{
     futures::future::join_all,
+    io::FileLoadError,
     log::Log,
     make_relative_path, notify,
-    parking_lot::{Mutex, MutexGuard},
+    parking_lot::{Mutex, MutexGuard, RwLock},
     uuid::Uuid,
+    vfs::{self, IoProvider, MountId, SharedVfs, Vfs},
     watcher::FileSystemWatcher,
     TypeUuidProvider,
 };
@@ -23,11 +26,19 @@ use std::path::PathBuf;
 use std::{
     ffi::OsStr,
     fmt::{Debug, Display, Formatter},
+    future::Future,
     marker::PhantomData,
     path::Path,
+    pin::Pin,
     sync::Arc,
+    task::{Context, Poll},
+    time::Duration,
 };
 
+/// A callback, that is called when a resource is loaded or failed to load. See
+/// [`ResourceManager::on_loaded`] for more info.
+pub type ResourceLoadCallback = Box<dyn FnOnce(UntypedResource) + Send>;
+
 /// A set of resources that can be waited for.
 #[must_use]
 #[derive(Default)]
@@ -49,6 +60,222 @@ impl ResourceWaitContext {
     }
 }
 
+/// A list of resources that failed to load, each entry contains a path of the resource and the
+/// error.
+pub type ResourceBatchErrors = Vec<(PathBuf, Option<Arc<dyn ResourceLoadError>>)>;
+
+/// A set of resource requests, that should be tracked together. Use [`ResourceManager::begin_batch`]
+/// to create a batch, request resources using its methods and then call [`Self::finish`] to get a
+/// handle that can be used to track loading of the whole batch. Resources requested directly from
+/// the resource manager are not tracked by the batch.
+#[must_use]
+pub struct ResourceBatch {
+    manager: ResourceManager,
+    resources: Vec<UntypedResource>,
+}
+
+impl ResourceBatch {
+    /// Requests a resource (see [`ResourceManager::request`]) and adds it to the batch.
+    pub fn request<T, P>(&mut self, path: P) -> Resource<T>
+    where
+        P: AsRef<Path>,
+        T: ResourceData + TypeUuidProvider,
+    {
+        let resource = self.manager.request::<T, P>(path);
+        self.resources.push(resource.clone().into_untyped());
+        resource
+    }
+
+    /// Same as [`Self::request`], but returns untyped resource.
+    pub fn request_untyped<P>(&mut self, path: P, type_uuid: Uuid) -> UntypedResource
+    where
+        P: AsRef<Path>,
+    {
+        let resource = self.manager.request_untyped(path, type_uuid);
+        self.add(resource.clone());
+        resource
+    }
+
+    /// Adds a resource, that was requested in some other way, to the batch.
+    pub fn add(&mut self, resource: UntypedResource) {
+        self.resources.push(resource);
+    }
+
+    /// Returns total amount of resources in the batch.
+    pub fn len(&self) -> usize {
+        self.resources.len()
+    }
+
+    /// Returns `true` if the batch has no resources.
+    pub fn is_empty(&self) -> bool {
+        self.resources.is_empty()
+    }
+
+    /// Finishes the batch and returns a handle, that can be used to track its loading progress.
+    pub fn finish(self) -> ResourceBatchHandle {
+        ResourceBatchHandle {
+            resources: Arc::new(self.resources),
+        }
+    }
+}
+
+/// A handle of a finished resource batch, see [`ResourceBatch`] docs for more info. The handle
+/// can be awaited, the future is resolved when every resource of the batch is loaded (or failed
+/// to load) and returns a list of errors. It does not block the thread, so it can be used on
+/// every platform (including WebAssembly).
+#[must_use]
+#[derive(Clone)]
+pub struct ResourceBatchHandle {
+    resources: Arc<Vec<UntypedResource>>,
+}
+
+impl ResourceBatchHandle {
+    /// Returns loading progress of the batch in `[0; 1]` range. Every resource is weighted by its
+    /// size in bytes (see [`ResourceData::memory_usage`]), the size of a resource becomes known only
+    /// after it is loaded, so an average size of loaded resources is used for the rest of them.
+    /// If no sizes are known, every resource has the same weight. Resources that failed to load
+    /// are considered finished.
+    pub fn progress(&self) -> f32 {
+        if self.resources.is_empty() {
+            return 1.0;
+        }
+
+        // (size, is_finished)
+        let entries = self
+            .resources
+            .iter()
+            .map(|resource| match *resource.0.lock() {
+                ResourceState::Pending { .. } => (None, false),
+                ResourceState::LoadError { .. } => (None, true),
+                ResourceState::Ok(ref data) => {
+                    let usage = data.memory_usage();
+                    let size = usage.cpu_bytes + usage.gpu_bytes;
+                    (if size > 0 { Some(size as f64) } else { None }, true)
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let (known_total, known_count) = entries
+            .iter()
+            .filter_map(|(size, _)| *size)
+            .fold((0.0, 0), |(total, count), size| (total + size, count + 1));
+        let default_size = if known_count > 0 {
+            known_total / known_count as f64
+        } else {
+            1.0
+        };
+
+        let mut total = 0.0;
+        let mut finished = 0.0;
+        for (size, is_finished) in entries {
+            let size = size.unwrap_or(default_size);
+            total += size;
+            if is_finished {
+                finished += size;
+            }
+        }
+
+        (finished / total) as f32
+    }
+
+    /// Returns `true` if every resource of the batch is loaded (or failed to load).
+    pub fn is_done(&self) -> bool {
+        self.resources.iter().all(|resource| !resource.is_loading())
+    }
+
+    /// Returns a list of resources, that failed to load, with their errors.
+    pub fn errors(&self) -> ResourceBatchErrors {
+        self.resources
+            .iter()
+            .filter_map(|resource| match *resource.0.lock() {
+                ResourceState::LoadError {
+                    ref path,
+                    ref error,
+                    ..
+                } => Some((path.clone(), error.clone())),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Returns total amount of resources in the batch.
+    pub fn len(&self) -> usize {
+        self.resources.len()
+    }
+
+    /// Returns `true` if the batch has no resources.
+    pub fn is_empty(&self) -> bool {
+        self.resources.is_empty()
+    }
+
+    /// Returns every resource of the batch.
+    pub fn resources(&self) -> &[UntypedResource] {
+        &self.resources
+    }
+}
+
+impl Future for ResourceBatchHandle {
+    type Output = ResourceBatchErrors;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut is_done = true;
+        for resource in self.resources.iter() {
+            // Polling of a pending resource registers the waker, so the task will be woken up
+            // when the resource is loaded.
+            if Pin::new(&mut resource.clone()).poll(cx).is_pending() {
+                is_done = false;
+            }
+        }
+
+        if is_done {
+            Poll::Ready(self.errors())
+        } else {
+            Poll::Pending
+        }
+    }
+}
+
+/// Estimated memory usage of a single resource.
+#[derive(Clone, Debug)]
+pub struct ResourceMemoryUsageEntry {
+    /// A path of the resource.
+    pub path: PathBuf,
+    /// Estimated memory usage of the resource.
+    pub usage: ResourceMemoryUsage,
+}
+
+/// Estimated memory usage of every resource of a particular type.
+#[derive(Clone, Debug)]
+pub struct ResourceTypeMemoryUsage {
+    /// Unique type id of the resources.
+    pub type_uuid: Uuid,
+    /// Name of the type of the resources.
+    pub type_name: &'static str,
+    /// Total memory usage of the resources of the type.
+    pub total: ResourceMemoryUsage,
+    /// Memory usage of every resource of the type.
+    pub resources: Vec<ResourceMemoryUsageEntry>,
+}
+
+/// Estimated memory usage of loaded resources, grouped by resource type. See
+/// [`ResourceManagerState::memory_usage_report`] for more info.
+#[derive(Clone, Debug, Default)]
+pub struct ResourceMemoryReport {
+    /// Memory usage of each resource type, sorted by type name.
+    pub types: Vec<ResourceTypeMemoryUsage>,
+}
+
+impl ResourceMemoryReport {
+    /// Returns total memory usage of every resource in the report.
+    pub fn total(&self) -> ResourceMemoryUsage {
+        let mut total = ResourceMemoryUsage::default();
+        for group in self.types.iter() {
+            total += group.total;
+        }
+        total
+    }
+}
+
 /// See module docs.
 pub struct ResourceManagerState {
     /// A set of resource loaders. Use this field to register your own resource loader.
@@ -59,9 +286,17 @@ pub struct ResourceManagerState {
     pub constructors_container: ResourceConstructorContainer,
     /// A set of built-in resources, that will be used to resolve references on deserialization.
     pub built_in_resources: FxHashMap<PathBuf, UntypedResource>,
+    /// A queue of loaded resources that are waiting for GPU upload. It is filled and consumed by
+    /// the renderer, see [`crate::upload::UploadQueue`] docs for more info.
+    pub upload_queue: SharedUploadQueue,
     resources: Vec<TimedEntry<UntypedResource>>,
-    task_pool: Arc<TaskPool>,
+    // `None` when the manager is shut down, see [`ResourceManager::shutdown`].
+    task_pool: Option<Arc<TaskPool>>,
     watcher: Option<FileSystemWatcher>,
+    unused_resource_lifetime: Option<f32>,
+    load_callbacks: Vec<(UntypedResource, ResourceLoadCallback)>,
+    // Every loading task of the manager uses this mount table, see [`vfs::with_vfs`].
+    vfs: SharedVfs,
 }
 
 /// See module docs.
@@ -166,6 +401,31 @@ impl ResourceManager {
         self.state().request(path, type_uuid)
     }
 
+    /// Begins a new batch of resource requests, see [`ResourceBatch`] docs for more info.
+    ///
+    /// ```rust,no_run
+    /// # use fyrox_resource::{core::uuid::Uuid, manager::ResourceManager};
+    /// # async fn load(resource_manager: ResourceManager) {
+    /// let mut batch = resource_manager.begin_batch();
+    /// batch.request_untyped("data/level.rgs", Uuid::default());
+    /// batch.request_untyped("data/music.ogg", Uuid::default());
+    /// let handle = batch.finish();
+    ///
+    /// // Progress can be polled every frame to show a loading screen.
+    /// println!("Loading: {}%", handle.progress() * 100.0);
+    ///
+    /// for (path, error) in handle.await {
+    ///     println!("Unable to load {}: {:?}", path.display(), error);
+    /// }
+    /// # }
+    /// ```
+    pub fn begin_batch(&self) -> ResourceBatch {
+        ResourceBatch {
+            manager: self.clone(),
+            resources: Default::default(),
+        }
+    }
+
     /// Saves given resources in the specified path and registers it in resource manager, so
     /// it will be accessible through it later.
     pub fn register<P, F>(
@@ -199,6 +459,109 @@ impl ResourceManager {
         }
     }
 
+    /// Immediately destroys all resources that are not used anywhere else. See
+    /// [`ResourceManagerState::purge_unused`] for more info.
+    pub fn purge_unused(&self) -> usize {
+        self.state().purge_unused()
+    }
+
+    /// Immediately destroys a resource at the given path, if it is not used anywhere else. See
+    /// [`ResourceManagerState::try_unload`] for more info.
+    pub fn try_unload<P: AsRef<Path>>(&self, path: P) -> bool {
+        self.state().try_unload(path)
+    }
+
+    /// Returns amount of external references to a resource at the given path. See
+    /// [`ResourceManagerState::ref_count`] for more info.
+    pub fn ref_count<P: AsRef<Path>>(&self, path: P) -> Option<usize> {
+        self.state().ref_count(path)
+    }
+
+    /// Registers a callback, that will be called once the given resource is loaded or failed to
+    /// load. Check the state of the resource passed to the callback to find out whether the loading
+    /// was successful. Callbacks are called by [`Self::process_load_callbacks`], the engine does
+    /// this on the main thread at the beginning of each update. If the resource is already loaded,
+    /// the callback will be called on the next processing.
+    ///
+    /// It is an alternative to polling the state of many resources every frame, for example to swap
+    /// a placeholder with a high-resolution texture once it is loaded.
+    pub fn on_loaded<R, F>(&self, resource: R, callback: F)
+    where
+        R: Into<UntypedResource>,
+        F: FnOnce(UntypedResource) + Send + 'static,
+    {
+        self.state()
+            .load_callbacks
+            .push((resource.into(), Box::new(callback)));
+    }
+
+    /// Calls the callbacks (see [`Self::on_loaded`]) of every resource that has finished loading.
+    /// Returns the amount of called callbacks. Callbacks are called without holding the lock of the
+    /// manager, so they can request other resources.
+    pub fn process_load_callbacks(&self) -> usize {
+        let ready = {
+            let mut state = self.state();
+            let (ready, pending): (Vec<_>, Vec<_>) = std::mem::take(&mut state.load_callbacks)
+                .into_iter()
+                .partition(|(resource, _)| !resource.is_loading());
+            state.load_callbacks = pending;
+            ready
+        };
+
+        let count = ready.len();
+        for (resource, callback) in ready {
+            callback(resource);
+        }
+        count
+    }
+
+    /// Returns estimated memory usage of every loaded resource. See
+    /// [`ResourceManagerState::memory_usage_report`] for more info.
+    pub fn memory_usage_report(&self) -> ResourceMemoryReport {
+        self.state().memory_usage_report()
+    }
+
+    /// Sets amount of threads that will be used to load resources. See
+    /// [`ResourceManagerState::set_loader_thread_count`] for more info.
+    pub fn set_loader_thread_count(&self, thread_count: usize) {
+        self.state().set_loader_thread_count(thread_count)
+    }
+
+    /// Returns amount of threads that is used to load resources. Zero means one thread per each
+    /// CPU core.
+    pub fn loader_thread_count(&self) -> usize {
+        self.state().loader_thread_count()
+    }
+
+    /// Stops loading of resources and waits (no longer than `timeout`) until all in-flight loading
+    /// tasks are finished and the loader threads are stopped. Every resource requested after the
+    /// shutdown will fail to load. Returns `false` if the threads did not stop in time, such threads
+    /// are detached and will stop on their own when their tasks are finished.
+    ///
+    /// The method is called by the engine when it is shut down, there's no need to call it manually.
+    pub fn shutdown(&self, timeout: Duration) -> bool {
+        // Do not hold the lock while waiting, loaders may request other resources.
+        let task_pool = self.state().task_pool.take();
+        match task_pool.map(Arc::try_unwrap) {
+            Some(Ok(task_pool)) => task_pool.join(timeout),
+            // The pool is shared, it will be destroyed when the last user drops it.
+            Some(Err(_)) => false,
+            None => true,
+        }
+    }
+
+    /// Returns total size (in bytes) of loaded resources that are waiting for GPU upload. It could
+    /// be used by a loading screen to wait until everything is ready to be shown, for example
+    /// `resource_manager.pending_upload_bytes() == 0`.
+    pub fn pending_upload_bytes(&self) -> usize {
+        self.state().upload_queue.lock().pending_bytes()
+    }
+
+    /// Returns amount of loaded resources that are waiting for GPU upload.
+    pub fn pending_upload_count(&self) -> usize {
+        self.state().upload_queue.lock().len()
+    }
+
     /// Reloads all loaded resources. Normally it should never be called, because it is **very** heavy
     /// method! This method is asynchronous, it uses all available CPU power to reload resources as
     /// fast as possible.
@@ -206,21 +569,94 @@ impl ResourceManager {
         let resources = self.state().reload_resources();
         join_all(resources).await;
     }
+
+    /// Mounts an archive (see [`vfs::ArchiveIoProvider`]) with the given priority, so all resources
+    /// will be loaded from the archive if it contains them. Archives with higher priority override
+    /// archives with lower priority, loose files have [`vfs::LOOSE_FILES_PRIORITY`]. Already loaded
+    /// resources are not affected, use [`Self::reload_resources`] to reload them.
+    ///
+    /// Every resource manager has its own mount table (its clones share the same table), it does
+    /// not affect other resource managers. See [`vfs`] docs for more info.
+    #[cfg(all(not(target_os = "android"), not(target_arch = "wasm32")))]
+    pub fn mount_archive<P: AsRef<Path>>(
+        &self,
+        path: P,
+        priority: i32,
+    ) -> Result<MountId, FileLoadError> {
+        Ok(self.mount(vfs::ArchiveIoProvider::from_file(path)?, priority))
+    }
+
+    /// Mounts the given IO provider with the given priority. It could be used to mount archives on
+    /// platforms without file system (see [`vfs::ArchiveIoProvider::from_bytes`]) or custom
+    /// providers. See [`Self::mount_archive`] for more info.
+    pub fn mount<P: IoProvider>(&self, provider: P, priority: i32) -> MountId {
+        self.state().vfs.write().mount(provider, priority)
+    }
+
+    /// Unmounts the provider, that was mounted by [`Self::mount_archive`] or [`Self::mount`]. Returns
+    /// `false` if there's no such provider.
+    pub fn unmount(&self, id: MountId) -> bool {
+        self.state().vfs.write().unmount(id)
+    }
 }
 
 impl ResourceManagerState {
     pub(crate) fn new() -> Self {
         Self {
             resources: Default::default(),
-            task_pool: Arc::new(Default::default()),
+            task_pool: Some(Arc::new(Default::default())),
             loaders: Default::default(),
             event_broadcaster: Default::default(),
             constructors_container: Default::default(),
             watcher: None,
             built_in_resources: Default::default(),
+            upload_queue: Default::default(),
+            unused_resource_lifetime: Some(DEFAULT_RESOURCE_LIFETIME),
+            load_callbacks: Default::default(),
+            vfs: Arc::new(RwLock::new(Vfs::default())),
+        }
+    }
+
+    /// Sets amount of time (in seconds) for which a resource is kept alive when it is not used
+    /// anywhere else. When the time runs out, the resource is destroyed automatically in
+    /// [`Self::update`]. `None` disables automatic destruction, in this case unused resources can
+    /// be destroyed only by [`Self::purge_unused`]. Default is [`DEFAULT_RESOURCE_LIFETIME`].
+    pub fn set_unused_resource_lifetime(&mut self, lifetime: Option<f32>) {
+        self.unused_resource_lifetime = lifetime;
+    }
+
+    /// Returns amount of time (in seconds) for which a resource is kept alive when it is not used
+    /// anywhere else. See [`Self::set_unused_resource_lifetime`] for more info.
+    pub fn unused_resource_lifetime(&self) -> Option<f32> {
+        self.unused_resource_lifetime
+    }
+
+    /// Sets amount of threads that will be used to load resources. Zero means one thread per each
+    /// CPU core, which is the default. Limiting the amount of threads could be useful for dedicated
+    /// servers that run many instances on the same machine. Resources that are already being loaded
+    /// will finish loading on the old threads, all new requests will use the new threads.
+    pub fn set_loader_thread_count(&mut self, thread_count: usize) {
+        if let Some(task_pool) = self.task_pool.as_mut() {
+            if task_pool.thread_count() != thread_count {
+                *task_pool = Arc::new(TaskPool::with_thread_count(thread_count));
+            }
         }
     }
 
+    /// Returns amount of threads that is used to load resources. Zero means one thread per each
+    /// CPU core.
+    pub fn loader_thread_count(&self) -> usize {
+        self.task_pool
+            .as_ref()
+            .map_or(0, |task_pool| task_pool.thread_count())
+    }
+
+    /// Returns `true` if the manager was shut down and does not load resources anymore, see
+    /// [`ResourceManager::shutdown`].
+    pub fn is_shut_down(&self) -> bool {
+        self.task_pool.is_none()
+    }
+
     /// Sets resource watcher which will track any modifications in file system and forcing
     /// the manager to reload changed resources. By default there is no watcher, since it
     /// may be an undesired effect to reload resources at runtime. This is very useful thing
@@ -255,42 +691,55 @@ impl ResourceManagerState {
     /// Normally, this is called from `Engine::update()`.
     /// You should only call this manually if you don't use that method.
     pub fn update(&mut self, dt: f32) {
-        self.resources.retain_mut(|resource| {
-            // One usage means that the resource has single owner, and that owner
-            // is this container. Such resources have limited life time, if the time
-            // runs out before it gets shared again, the resource will be deleted.
-            if resource.value.use_count() <= 1 {
-                resource.time_to_live -= dt;
-                if resource.time_to_live <= 0.0 {
-                    let path = resource.0.lock().path().to_path_buf();
-
-                    Log::info(format!(
-                        "Resource {} destroyed because it is not used anymore!",
-                        path.display()
-                    ));
-
-                    self.event_broadcaster
-                        .broadcast(ResourceEvent::Removed(path));
-
-                    false
+        if let Some(lifetime) = self.unused_resource_lifetime {
+            for resource in self.resources.iter_mut() {
+                // One usage means that the resource has single owner, and that owner
+                // is this container. Such resources have limited life time, if the time
+                // runs out before it gets shared again, the resource will be deleted.
+                if resource.value.use_count() <= 1 {
+                    resource.time_to_live = resource.time_to_live.min(lifetime) - dt;
                 } else {
-                    // Keep resource alive for short period of time.
-                    true
+                    // Make sure to reset timer if a resource is used by more than one owner.
+                    resource.time_to_live = lifetime;
                 }
-            } else {
-                // Make sure to reset timer if a resource is used by more than one owner.
-                resource.time_to_live = DEFAULT_RESOURCE_LIFETIME;
-
-                // Keep resource alive while it has more than one owner.
-                true
             }
-        });
+
+            let event_broadcaster = &self.event_broadcaster;
+            self.resources.retain(|resource| {
+                // Keep resource alive while it has more than one owner or for short period of
+                // time after it became unused.
+                if resource.value.use_count() > 1 || resource.time_to_live > 0.0 {
+                    return true;
+                }
+
+                let path = resource.0.lock().path().to_path_buf();
+
+                Log::info(format!(
+                    "Resource {} destroyed because it is not used anymore!",
+                    path.display()
+                ));
+
+                event_broadcaster.broadcast(ResourceEvent::Removed(path));
+                event_broadcaster.broadcast(ResourceEvent::Unloaded(resource.value.key()));
+
+                false
+            });
+        }
 
         if let Some(watcher) = self.watcher.as_ref() {
             if let Some(evt) = watcher.try_get_event() {
                 if let notify::EventKind::Modify(_) = evt.kind {
                     for path in evt.paths {
-                        if let Ok(relative_path) = make_relative_path(path) {
+                        if let Ok(mut relative_path) = make_relative_path(path) {
+                            // Changed import options (`foo.fbx.options`) must reload the resource
+                            // they belong to.
+                            if relative_path
+                                .extension()
+                                .map_or(false, |ext| ext == "options")
+                            {
+                                relative_path = relative_path.with_extension("");
+                            }
+
                             if self.try_reload_resource_from_path(&relative_path) {
                                 Log::info(format!(
                                         "File {} was changed, trying to reload a respective resource...",
@@ -313,7 +762,9 @@ impl ResourceManagerState {
 
         self.resources.push(TimedEntry {
             value: resource,
-            time_to_live: DEFAULT_RESOURCE_LIFETIME,
+            time_to_live: self
+                .unused_resource_lifetime
+                .unwrap_or(DEFAULT_RESOURCE_LIFETIME),
         });
     }
 
@@ -348,8 +799,110 @@ impl ResourceManagerState {
 
     /// Immediately destroys all resources in the manager that are not used anywhere else.
     pub fn destroy_unused_resources(&mut self) {
-        self.resources
-            .retain(|resource| resource.value.use_count() > 1);
+        self.purge_unused();
+    }
+
+    /// Immediately destroys all resources in the manager that are not used anywhere else, ignoring
+    /// their lifetime. Destruction is repeated until there's nothing left to destroy, because a
+    /// destroyed resource could be the last user of some other resources (for example, a model
+    /// keeps its textures alive). Returns the amount of destroyed resources.
+    pub fn purge_unused(&mut self) -> usize {
+        let mut count = 0;
+        loop {
+            let (unused, used): (Vec<_>, Vec<_>) = std::mem::take(&mut self.resources)
+                .into_iter()
+                .partition(|resource| resource.value.use_count() <= 1);
+            self.resources = used;
+
+            if unused.is_empty() {
+                return count;
+            }
+
+            count += unused.len();
+
+            // Dropping the entries releases resources they depend on, so these may become unused
+            // on the next iteration.
+            for resource in unused {
+                let path = resource.value.path();
+
+                Log::info(format!("Resource {} was purged!", path.display()));
+
+                self.event_broadcaster
+                    .broadcast(ResourceEvent::Removed(path));
+                self.event_broadcaster
+                    .broadcast(ResourceEvent::Unloaded(resource.value.key()));
+            }
+        }
+    }
+
+    /// Immediately destroys a resource at the given path, ignoring its lifetime. The resource is
+    /// destroyed only if it is not used anywhere else (its [`Self::ref_count`] is zero). Returns
+    /// `true` if the resource was destroyed, `false` - if there's no such resource or it is still
+    /// in use.
+    pub fn try_unload<P: AsRef<Path>>(&mut self, path: P) -> bool {
+        let path = path.as_ref();
+        let index = if let Some(index) = self
+            .resources
+            .iter()
+            .position(|resource| resource.value.path() == path)
+        {
+            index
+        } else {
+            return false;
+        };
+
+        if self.resources[index].value.use_count() > 1 {
+            return false;
+        }
+
+        let resource = self.resources.remove(index);
+
+        Log::info(format!("Resource {} was unloaded!", path.display()));
+
+        self.event_broadcaster
+            .broadcast(ResourceEvent::Removed(path.to_path_buf()));
+        self.event_broadcaster
+            .broadcast(ResourceEvent::Unloaded(resource.value.key()));
+
+        true
+    }
+
+    /// Returns amount of references to a resource at the given path, excluding the one that is
+    /// held by the manager itself. Returns `None` if there's no such resource.
+    pub fn ref_count<P: AsRef<Path>>(&self, path: P) -> Option<usize> {
+        self.find(path)
+            .map(|resource| resource.use_count().saturating_sub(1))
+    }
+
+    /// Returns estimated memory usage of every loaded resource in the manager, grouped by type of
+    /// the resources. The estimation is provided by [`ResourceData::memory_usage`], resources that
+    /// are still loading or failed to load are not included in the report.
+    pub fn memory_usage_report(&self) -> ResourceMemoryReport {
+        let mut groups = FxHashMap::<Uuid, ResourceTypeMemoryUsage>::default();
+        for resource in self.resources.iter() {
+            if let ResourceState::Ok(ref data) = *resource.0.lock() {
+                let usage = data.memory_usage();
+                let group =
+                    groups
+                        .entry(data.type_uuid())
+                        .or_insert_with(|| ResourceTypeMemoryUsage {
+                            type_uuid: data.type_uuid(),
+                            type_name: data.type_name(),
+                            total: Default::default(),
+                            resources: Default::default(),
+                        });
+                group.total += usage;
+                group.resources.push(ResourceMemoryUsageEntry {
+                    path: data.path().to_path_buf(),
+                    usage,
+                });
+            }
+        }
+
+        let mut types = groups.into_values().collect::<Vec<_>>();
+        types.sort_by(|a, b| a.type_name.cmp(b.type_name));
+
+        ResourceMemoryReport { types }
     }
 
     /// Returns total amount of resources that still loading.
@@ -379,7 +932,9 @@ impl ResourceManagerState {
         self.resources.iter().map(|t| t.value.clone()).collect()
     }
 
-    /// Tries to load a resources at a given path.
+    /// Tries to load a resources at a given path. Lookup and registration of a new resource happen
+    /// while the state is borrowed mutably, so concurrent requests for the same path always share
+    /// a single resource instance and a single loading task.
     pub fn request<P>(&mut self, path: P, type_uuid: Uuid) -> UntypedResource
     where
         P: AsRef<Path>,
@@ -407,11 +962,17 @@ impl ResourceManagerState {
                     .iter()
                     .any(|ext| OsStr::new(ext) == ext_lowercase.as_os_str())
             }) {
-                self.task_pool.spawn_task(loader.load(
-                    resource,
-                    self.event_broadcaster.clone(),
-                    reload,
-                ));
+                if let Some(task_pool) = self.task_pool.as_ref() {
+                    task_pool.spawn_task(vfs::with_vfs(
+                        self.vfs.clone(),
+                        loader.load(resource, self.event_broadcaster.clone(), reload),
+                    ));
+                } else {
+                    resource.commit_error(
+                        path.to_path_buf(),
+                        "Resource manager is shut down!".to_string(),
+                    );
+                }
 
                 return;
             }
@@ -470,3 +1031,460 @@ impl ResourceManagerState {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        event::ResourceEventBroadcaster, loader::BoxedLoaderFuture, loader::ResourceLoader,
+    };
+    use fyrox_core::{futures::executor::block_on, reflect::prelude::*, visitor::prelude::*};
+    use std::{
+        any::Any,
+        borrow::Cow,
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Barrier,
+        },
+        thread,
+    };
+
+    #[derive(Debug, Default, Reflect, Visit)]
+    struct Blob {
+        path: PathBuf,
+        size: u32,
+        #[visit(skip)]
+        #[reflect(hidden)]
+        // Keeps the dependency alive while the blob is alive.
+        _dependency: Option<UntypedResource>,
+    }
+
+    impl ResourceData for Blob {
+        fn path(&self) -> Cow<Path> {
+            Cow::Borrowed(&self.path)
+        }
+
+        fn set_path(&mut self, path: PathBuf) {
+            self.path = path;
+        }
+
+        fn as_any(&self) -> &dyn Any {
+            self
+        }
+
+        fn as_any_mut(&mut self) -> &mut dyn Any {
+            self
+        }
+
+        fn type_uuid(&self) -> Uuid {
+            Uuid::default()
+        }
+
+        fn memory_usage(&self) -> ResourceMemoryUsage {
+            ResourceMemoryUsage {
+                cpu_bytes: self.size as usize,
+                gpu_bytes: 0,
+            }
+        }
+    }
+
+    struct CountingLoader {
+        reads: Arc<AtomicUsize>,
+    }
+
+    impl ResourceLoader for CountingLoader {
+        fn extensions(&self) -> &[&str] {
+            &["counted"]
+        }
+
+        fn into_any(self: Box<Self>) -> Box<dyn Any> {
+            self
+        }
+
+        fn as_any(&self) -> &dyn Any {
+            self
+        }
+
+        fn as_any_mut(&mut self) -> &mut dyn Any {
+            self
+        }
+
+        fn load(
+            &self,
+            resource: UntypedResource,
+            _event_broadcaster: ResourceEventBroadcaster,
+            _reload: bool,
+        ) -> BoxedLoaderFuture {
+            let reads = self.reads.clone();
+            Box::pin(async move {
+                let path = resource.path();
+                let result = std::fs::read(&path);
+                reads.fetch_add(1, Ordering::SeqCst);
+                match result {
+                    Ok(bytes) => resource.commit_ok(Blob {
+                        path,
+                        size: bytes.len() as u32,
+                        _dependency: None,
+                    }),
+                    Err(error) => resource.commit_error(path, format!("{:?}", error)),
+                }
+            })
+        }
+    }
+
+    // Each call returns a new path, so concurrent test runs never share their files.
+    fn unique_temp_path(name: &str) -> PathBuf {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        std::env::temp_dir().join(format!(
+            "fyrox_{}_{}_{}.counted",
+            name,
+            std::process::id(),
+            COUNTER.fetch_add(1, Ordering::SeqCst)
+        ))
+    }
+
+    #[test]
+    fn concurrent_requests_are_coalesced() {
+        let path = unique_temp_path("resource_coalescing_test");
+        std::fs::write(&path, b"data").unwrap();
+
+        let reads = Arc::new(AtomicUsize::new(0));
+        let manager = ResourceManager::new();
+        manager.state().loaders.set(CountingLoader {
+            reads: reads.clone(),
+        });
+
+        let barrier = Arc::new(Barrier::new(100));
+        let resources = (0..100)
+            .map(|_| {
+                let manager = manager.clone();
+                let barrier = barrier.clone();
+                let path = path.clone();
+                thread::spawn(move || {
+                    barrier.wait();
+                    manager.request_untyped(path, Uuid::default())
+                })
+            })
+            .collect::<Vec<_>>()
+            .into_iter()
+            .map(|handle| handle.join().unwrap())
+            .collect::<Vec<_>>();
+
+        assert!(block_on(resources[0].clone()).is_ok());
+        assert!(resources.iter().all(|resource| *resource == resources[0]));
+        assert!(resources.iter().all(|resource| !resource.is_loading()));
+        assert_eq!(manager.state().len(), 1);
+        assert_eq!(reads.load(Ordering::SeqCst), 1);
+
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn shutdown_finishes_loading() {
+        let path = unique_temp_path("resource_shutdown_test");
+        std::fs::write(&path, b"data").unwrap();
+
+        let reads = Arc::new(AtomicUsize::new(0));
+        let manager = ResourceManager::new();
+        manager.set_loader_thread_count(2);
+        manager.state().loaders.set(CountingLoader {
+            reads: reads.clone(),
+        });
+
+        let resource = manager.request_untyped(&path, Uuid::default());
+        assert!(manager.shutdown(Duration::from_secs(10)));
+        assert!(manager.state().is_shut_down());
+        // In-flight task is finished before the shutdown returns.
+        assert_eq!(reads.load(Ordering::SeqCst), 1);
+        assert!(!resource.is_loading());
+
+        // New requests are rejected immediately.
+        let other = unique_temp_path("resource_shutdown_test_other");
+        assert!(block_on(manager.request_untyped(other, Uuid::default())).is_err());
+        assert_eq!(reads.load(Ordering::SeqCst), 1);
+        assert!(manager.shutdown(Duration::from_secs(10)));
+
+        let _ = std::fs::remove_file(path);
+    }
+
+    struct VfsLoader;
+
+    impl ResourceLoader for VfsLoader {
+        fn extensions(&self) -> &[&str] {
+            &["vfsblob"]
+        }
+
+        fn into_any(self: Box<Self>) -> Box<dyn Any> {
+            self
+        }
+
+        fn as_any(&self) -> &dyn Any {
+            self
+        }
+
+        fn as_any_mut(&mut self) -> &mut dyn Any {
+            self
+        }
+
+        fn load(
+            &self,
+            resource: UntypedResource,
+            _event_broadcaster: ResourceEventBroadcaster,
+            _reload: bool,
+        ) -> BoxedLoaderFuture {
+            Box::pin(async move {
+                let path = resource.path();
+                match fyrox_core::io::load_file(&path).await {
+                    Ok(data) => resource.commit_ok(Blob {
+                        path,
+                        size: data.len() as u32,
+                        _dependency: None,
+                    }),
+                    Err(error) => resource.commit_error(path, format!("{:?}", error)),
+                }
+            })
+        }
+    }
+
+    #[test]
+    fn mounted_archives_override_loose_files() {
+        let dir = std::env::temp_dir();
+        let path = dir.join("fyrox_vfs_override_test.vfsblob");
+        let base = dir.join("fyrox_vfs_override_test_base.pak");
+        let patch = dir.join("fyrox_vfs_override_test_patch.pak");
+
+        std::fs::write(&path, b"abc").unwrap();
+        vfs::ArchiveBuilder::new()
+            .with_file(&path, b"abcde".to_vec())
+            .save(&base)
+            .unwrap();
+        vfs::ArchiveBuilder::new()
+            .with_file(&path, b"abcdefg".to_vec())
+            .save(&patch)
+            .unwrap();
+
+        let manager = ResourceManager::new();
+        manager.state().loaders.set(VfsLoader);
+
+        let patch_id = manager.mount_archive(&patch, 20).unwrap();
+        let base_id = manager.mount_archive(&base, 10).unwrap();
+
+        let resource = manager.request_untyped(&path, Uuid::default());
+        assert!(block_on(resource.clone()).is_ok());
+        assert_eq!(resource.path(), path);
+        assert_eq!(manager.memory_usage_report().total().cpu_bytes, 7);
+
+        assert!(manager.unmount(patch_id));
+        block_on(manager.reload_resources());
+        assert_eq!(manager.memory_usage_report().total().cpu_bytes, 5);
+
+        assert!(manager.unmount(base_id));
+        block_on(manager.reload_resources());
+        assert_eq!(manager.memory_usage_report().total().cpu_bytes, 3);
+
+        for file in [path, base, patch] {
+            let _ = std::fs::remove_file(file);
+        }
+    }
+
+    #[test]
+    fn mount_table_is_not_shared_between_managers() {
+        let path = std::env::temp_dir().join("fyrox_vfs_per_manager_test.vfsblob");
+
+        let first = ResourceManager::new();
+        first.state().loaders.set(VfsLoader);
+        first.mount(
+            vfs::ArchiveIoProvider::from_bytes(
+                vfs::ArchiveBuilder::new()
+                    .with_file(&path, b"abc".to_vec())
+                    .build(),
+            )
+            .unwrap(),
+            10,
+        );
+
+        let second = ResourceManager::new();
+        second.state().loaders.set(VfsLoader);
+
+        assert!(block_on(first.request_untyped(&path, Uuid::default())).is_ok());
+        assert!(block_on(second.request_untyped(&path, Uuid::default())).is_err());
+    }
+
+    #[test]
+    fn batch_collects_errors_and_progress() {
+        let dir = std::env::temp_dir();
+        let small = dir.join("fyrox_batch_test_small.vfsblob");
+        let big = dir.join("fyrox_batch_test_big.vfsblob");
+        let missing = dir.join("fyrox_batch_test_missing.vfsblob");
+        let outside = dir.join("fyrox_batch_test_outside.vfsblob");
+        std::fs::write(&small, b"abc").unwrap();
+        std::fs::write(&big, b"abcdefghij").unwrap();
+        std::fs::write(&outside, b"a").unwrap();
+        let _ = std::fs::remove_file(&missing);
+
+        let manager = ResourceManager::new();
+        manager.state().loaders.set(VfsLoader);
+
+        let mut batch = manager.begin_batch();
+        batch.request_untyped(&small, Uuid::default());
+        batch.request_untyped(&missing, Uuid::default());
+        batch.request_untyped(&big, Uuid::default());
+        assert_eq!(batch.len(), 3);
+        let handle = batch.finish();
+
+        // Requests made outside of the batch are not tracked by it.
+        let outside_resource = manager.request_untyped(&outside, Uuid::default());
+
+        let errors = block_on(handle.clone());
+        assert!(handle.is_done());
+        assert_eq!(handle.len(), 3);
+        assert_eq!(handle.progress(), 1.0);
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].0, missing);
+        assert!(errors[0].1.is_some());
+        assert_eq!(handle.errors().len(), 1);
+        assert!(!handle.resources().contains(&outside_resource));
+
+        assert_eq!(manager.begin_batch().finish().progress(), 1.0);
+
+        for file in [small, big, outside] {
+            let _ = std::fs::remove_file(file);
+        }
+    }
+
+    #[test]
+    fn purge_unused_releases_dependencies() {
+        let manager = ResourceManager::new();
+
+        let texture = UntypedResource::new_ok(Blob {
+            path: "texture.blob".into(),
+            size: 100,
+            _dependency: None,
+        });
+        let model = UntypedResource::new_ok(Blob {
+            path: "model.blob".into(),
+            size: 20,
+            _dependency: Some(texture.clone()),
+        });
+        let used = UntypedResource::new_ok(Blob {
+            path: "used.blob".into(),
+            size: 3,
+            _dependency: None,
+        });
+
+        {
+            let mut state = manager.state();
+            state.push(texture);
+            state.push(model.clone());
+            state.push(used.clone());
+        }
+
+        let report = manager.memory_usage_report();
+        assert_eq!(report.types.len(), 1);
+        assert!(report.types[0].type_name.ends_with("Blob"));
+        assert_eq!(report.types[0].resources.len(), 3);
+        assert_eq!(report.total().cpu_bytes, 123);
+
+        // The model is still in use, and it keeps the texture alive.
+        assert_eq!(manager.purge_unused(), 0);
+        assert_eq!(manager.state().len(), 3);
+
+        drop(model);
+
+        // The texture becomes unused only after the model is destroyed.
+        assert_eq!(manager.purge_unused(), 2);
+        assert_eq!(manager.state().len(), 1);
+        assert!(manager.state().find("used.blob").is_some());
+        assert_eq!(manager.memory_usage_report().total().cpu_bytes, 3);
+    }
+
+    #[test]
+    fn try_unload_and_ref_count() {
+        let manager = ResourceManager::new();
+
+        let resource = UntypedResource::new_ok(Blob {
+            path: "level.blob".into(),
+            size: 1,
+            _dependency: None,
+        });
+        manager.state().push(resource.clone());
+
+        assert_eq!(manager.ref_count("level.blob"), Some(1));
+        assert_eq!(manager.ref_count("missing.blob"), None);
+
+        // The resource is still in use.
+        assert!(!manager.try_unload("level.blob"));
+        assert_eq!(manager.state().len(), 1);
+
+        drop(resource);
+
+        assert_eq!(manager.ref_count("level.blob"), Some(0));
+        assert!(manager.try_unload("level.blob"));
+        assert!(!manager.try_unload("level.blob"));
+        assert_eq!(manager.state().len(), 0);
+    }
+
+    #[test]
+    fn load_callbacks() {
+        let manager = ResourceManager::new();
+        let calls = Arc::new(AtomicUsize::new(0));
+
+        let loaded = UntypedResource::new_ok(Blob::default());
+        let pending = UntypedResource::new_pending("pending.blob".into(), Uuid::default());
+
+        for resource in [loaded, pending.clone()] {
+            let calls = calls.clone();
+            manager.on_loaded(resource, move |_| {
+                calls.fetch_add(1, Ordering::SeqCst);
+            });
+        }
+
+        assert_eq!(manager.process_load_callbacks(), 1);
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        // Failed resources are reported as well.
+        pending.commit_error("pending.blob".into(), "error".to_string());
+        assert_eq!(manager.process_load_callbacks(), 1);
+        assert_eq!(manager.process_load_callbacks(), 0);
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn unused_resource_lifetime() {
+        let mut state = ResourceManagerState::new();
+        state.set_unused_resource_lifetime(Some(1.0));
+        state.push(UntypedResource::new_ok(Blob::default()));
+
+        state.update(0.5);
+        assert_eq!(state.len(), 1);
+        state.update(0.6);
+        assert_eq!(state.len(), 0);
+
+        state.set_unused_resource_lifetime(None);
+        state.push(UntypedResource::new_ok(Blob::default()));
+        state.update(100.0);
+        assert_eq!(state.len(), 1);
+    }
+
+    #[test]
+    fn loader_thread_count() {
+        let path = unique_temp_path("resource_thread_count_test");
+        std::fs::write(&path, b"data").unwrap();
+
+        let reads = Arc::new(AtomicUsize::new(0));
+        let manager = ResourceManager::new();
+        manager.state().loaders.set(CountingLoader {
+            reads: reads.clone(),
+        });
+
+        assert_eq!(manager.loader_thread_count(), 0);
+        manager.set_loader_thread_count(1);
+        assert_eq!(manager.loader_thread_count(), 1);
+
+        let resource = manager.request_untyped(&path, Uuid::default());
+        assert!(block_on(resource).is_ok());
+        assert_eq!(reads.load(Ordering::SeqCst), 1);
+
+        let _ = std::fs::remove_file(path);
+    }
+}
@@ -1,10 +1,21 @@
 #[cfg(not(target_arch = "wasm32"))]
 use crate::core::futures::executor::ThreadPool;
-use std::future::Future;
+use std::{future::Future, time::Duration};
+#[cfg(not(target_arch = "wasm32"))]
+use std::{
+    sync::{Arc, Condvar, Mutex},
+    time::Instant,
+};
 
 pub struct TaskPool {
     #[cfg(not(target_arch = "wasm32"))]
     thread_pool: ThreadPool,
+    // Amount of worker threads of the pool and amount of worker threads that have stopped.
+    #[cfg(not(target_arch = "wasm32"))]
+    pool_size: usize,
+    #[cfg(not(target_arch = "wasm32"))]
+    stopped: Arc<(Mutex<usize>, Condvar)>,
+    thread_count: usize,
 }
 
 impl Default for TaskPool {
@@ -15,10 +26,53 @@ impl Default for TaskPool {
 
 impl TaskPool {
     pub fn new() -> Self {
-        Self {
-            #[cfg(not(target_arch = "wasm32"))]
-            thread_pool: ThreadPool::new().unwrap(),
+        Self::with_thread_count(0)
+    }
+
+    /// Creates new task pool with the given amount of worker threads. Zero means that the pool will
+    /// use one thread per each CPU core. The value is ignored on WebAssembly, where all tasks are
+    /// executed on the main thread.
+    pub fn with_thread_count(thread_count: usize) -> Self {
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            // The same default as in `futures`, which already requires a newer Rust version.
+            #[allow(clippy::incompatible_msrv)]
+            let pool_size = if thread_count > 0 {
+                thread_count
+            } else {
+                std::thread::available_parallelism().map_or(1, |p| p.get())
+            };
+            let stopped = Arc::new((Mutex::new(0), Condvar::new()));
+            let thread_pool = {
+                let stopped = stopped.clone();
+                ThreadPool::builder()
+                    .pool_size(pool_size)
+                    .before_stop(move |_| {
+                        let (count, condvar) = &*stopped;
+                        *count.lock().unwrap() += 1;
+                        condvar.notify_all();
+                    })
+                    .create()
+                    .unwrap()
+            };
+            Self {
+                thread_pool,
+                pool_size,
+                stopped,
+                thread_count,
+            }
         }
+
+        #[cfg(target_arch = "wasm32")]
+        {
+            Self { thread_count }
+        }
+    }
+
+    /// Returns amount of worker threads that was requested when the pool was created. Zero means
+    /// one thread per each CPU core.
+    pub fn thread_count(&self) -> usize {
+        self.thread_count
     }
 
     #[cfg(target_arch = "wasm32")]
@@ -36,4 +90,37 @@ impl TaskPool {
     {
         self.thread_pool.spawn_ok(future);
     }
+
+    /// Destroys the pool and waits until every worker thread finishes its tasks and stops. Returns
+    /// `false` if the threads did not stop in the given time, such threads are detached and will stop
+    /// on their own when their tasks are finished.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn join(self, timeout: Duration) -> bool {
+        let Self {
+            thread_pool,
+            pool_size,
+            stopped,
+            ..
+        } = self;
+
+        // Dropping the pool asks each worker to stop after all previously spawned tasks.
+        drop(thread_pool);
+
+        let deadline = Instant::now() + timeout;
+        let (count, condvar) = &*stopped;
+        let mut count = count.lock().unwrap();
+        while *count < pool_size {
+            let now = Instant::now();
+            if now >= deadline {
+                return false;
+            }
+            count = condvar.wait_timeout(count, deadline - now).unwrap().0;
+        }
+        true
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    pub fn join(self, _timeout: Duration) -> bool {
+        true
+    }
 }
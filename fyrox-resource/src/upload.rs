@@ -0,0 +1,239 @@
+//! GPU upload queue. Resources that must be uploaded to GPU (for example textures) are put into
+//! the queue by a renderer when they are loaded and taken out of it in small portions every frame,
+//! so streaming of many large resources does not cause long stalls. See [`UploadQueue`] docs for
+//! more info.
+
+use crate::{
+    core::{parking_lot::Mutex, reflect::prelude::*, visitor::prelude::*},
+    UntypedResource,
+};
+use serde::{Deserialize, Serialize};
+use std::{cmp::Ordering, collections::BinaryHeap, sync::Arc};
+use strum_macros::{AsRefStr, EnumString, EnumVariantNames};
+
+/// Priority of GPU upload of a resource. Resources with higher priority are uploaded first,
+/// resources with the same priority are uploaded in the order they were loaded.
+#[derive(
+    Copy,
+    Clone,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize,
+    Visit,
+    Reflect,
+    AsRefStr,
+    EnumString,
+    EnumVariantNames,
+)]
+pub enum UploadPriority {
+    /// Resources that could be shown late without any harm, for example distant details.
+    Low,
+    /// Default priority.
+    #[default]
+    Normal,
+    /// Resources that should be shown as soon as possible.
+    High,
+    /// Resources that must be shown immediately, for example UI textures.
+    Critical,
+}
+
+struct UploadRequest {
+    resource: UntypedResource,
+    priority: UploadPriority,
+    size: usize,
+    // Monotonic counter that keeps loading order for requests with the same priority.
+    order: u64,
+}
+
+impl PartialEq for UploadRequest {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for UploadRequest {}
+
+impl PartialOrd for UploadRequest {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for UploadRequest {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // Binary heap is a max-heap, so earlier requests must be "greater".
+        self.priority
+            .cmp(&other.priority)
+            .then_with(|| other.order.cmp(&self.order))
+    }
+}
+
+/// A queue of resources waiting for GPU upload, ordered by [`UploadPriority`]. The queue does not
+/// upload anything by itself, it only tracks pending requests and hands them out respecting a
+/// per-frame byte budget (see [`Self::pop_within_budget`]).
+#[derive(Default)]
+pub struct UploadQueue {
+    requests: BinaryHeap<UploadRequest>,
+    pending_bytes: usize,
+    order: u64,
+}
+
+/// Upload queue that is shared between a resource manager and a renderer.
+pub type SharedUploadQueue = Arc<Mutex<UploadQueue>>;
+
+impl UploadQueue {
+    /// Puts the resource of the given size (in bytes) into the queue. If the resource is already in
+    /// the queue, its request is replaced.
+    pub fn push(&mut self, resource: UntypedResource, priority: UploadPriority, size: usize) {
+        self.remove(resource.key());
+        self.requests.push(UploadRequest {
+            resource,
+            priority,
+            size,
+            order: self.order,
+        });
+        self.order += 1;
+        self.pending_bytes += size;
+    }
+
+    /// Takes resources out of the queue, until total size of the taken resources reaches the given
+    /// budget (in bytes). At least one resource is always taken (if the queue is not empty), so a
+    /// resource larger than the budget won't block the queue forever.
+    pub fn pop_within_budget(&mut self, budget: usize) -> Vec<UntypedResource> {
+        let mut resources = Vec::new();
+        let mut spent = 0;
+        while let Some(resource) = self.pop_if_fits(&mut spent, budget) {
+            resources.push(resource);
+        }
+        resources
+    }
+
+    /// Takes the next resource out of the queue, if its size fits into the rest of the given budget
+    /// (in bytes) and adds the size to `spent`. When nothing is spent yet, the resource is taken
+    /// regardless of its size. This allows to upload resources one-by-one and to stop at any moment,
+    /// for example when a time limit is exceeded.
+    pub fn pop_if_fits(&mut self, spent: &mut usize, budget: usize) -> Option<UntypedResource> {
+        let request = self.requests.peek()?;
+        if *spent != 0 && *spent + request.size > budget {
+            return None;
+        }
+        let request = self.requests.pop().unwrap();
+        *spent += request.size;
+        self.pending_bytes -= request.size;
+        Some(request.resource)
+    }
+
+    /// Removes the resource with the given key (see [`UntypedResource::key`]) from the queue.
+    /// Returns `true` if the resource was in the queue.
+    pub fn remove(&mut self, key: usize) -> bool {
+        if !self.contains(key) {
+            return false;
+        }
+
+        let (removed, requests): (Vec<_>, Vec<_>) = std::mem::take(&mut self.requests)
+            .into_vec()
+            .into_iter()
+            .partition(|request| request.resource.key() == key);
+        self.pending_bytes -= removed.iter().map(|r| r.size).sum::<usize>();
+        self.requests = BinaryHeap::from(requests);
+        true
+    }
+
+    /// Returns `true` if the resource with the given key (see [`UntypedResource::key`]) is waiting
+    /// for upload.
+    pub fn contains(&self, key: usize) -> bool {
+        self.requests.iter().any(|r| r.resource.key() == key)
+    }
+
+    /// Returns total size (in bytes) of the resources waiting for upload.
+    pub fn pending_bytes(&self) -> usize {
+        self.pending_bytes
+    }
+
+    /// Returns amount of the resources waiting for upload.
+    pub fn len(&self) -> usize {
+        self.requests.len()
+    }
+
+    /// Returns `true` if there are no resources waiting for upload.
+    pub fn is_empty(&self) -> bool {
+        self.requests.is_empty()
+    }
+
+    /// Removes every request from the queue.
+    pub fn clear(&mut self) {
+        self.requests.clear();
+        self.pending_bytes = 0;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn resource(name: &str) -> UntypedResource {
+        UntypedResource::new_pending(PathBuf::from(name), Default::default())
+    }
+
+    #[test]
+    fn upload_queue_order_and_budget() {
+        const MB: usize = 1024 * 1024;
+
+        let mut queue = UploadQueue::default();
+        let level_a = resource("level_a.png");
+        let level_b = resource("level_b.png");
+        let ui = resource("ui.png");
+        let detail = resource("detail.png");
+        let huge = resource("huge.png");
+
+        queue.push(level_a.clone(), UploadPriority::Normal, 4 * MB);
+        queue.push(detail.clone(), UploadPriority::Low, MB);
+        queue.push(level_b.clone(), UploadPriority::Normal, 4 * MB);
+        queue.push(ui.clone(), UploadPriority::Critical, 2 * MB);
+        assert_eq!(queue.len(), 4);
+        assert_eq!(queue.pending_bytes(), 11 * MB);
+        assert!(queue.contains(detail.key()));
+
+        // Critical first, then normal ones in loading order, until the budget is exhausted.
+        assert_eq!(queue.pop_within_budget(8 * MB), vec![ui, level_a.clone()]);
+        assert_eq!(queue.pending_bytes(), 5 * MB);
+
+        // A resource larger than the budget is still taken alone.
+        queue.push(huge.clone(), UploadPriority::High, 16 * MB);
+        assert_eq!(queue.pop_within_budget(8 * MB), vec![huge.clone()]);
+        assert_eq!(
+            queue.pop_within_budget(8 * MB),
+            vec![level_b, detail.clone()]
+        );
+        assert!(queue.is_empty());
+        assert_eq!(queue.pending_bytes(), 0);
+        assert!(queue.pop_within_budget(8 * MB).is_empty());
+
+        // Step-by-step popping respects the budget the same way.
+        queue.push(level_a.clone(), UploadPriority::Normal, 4 * MB);
+        queue.push(huge.clone(), UploadPriority::Normal, 16 * MB);
+        let mut spent = 0;
+        assert_eq!(queue.pop_if_fits(&mut spent, 8 * MB), Some(level_a));
+        assert_eq!(queue.pop_if_fits(&mut spent, 8 * MB), None);
+        assert_eq!(spent, 4 * MB);
+        spent = 0;
+        assert_eq!(queue.pop_if_fits(&mut spent, 8 * MB), Some(huge));
+        assert!(queue.is_empty());
+
+        // Re-queued resources replace their previous request.
+        queue.push(detail.clone(), UploadPriority::Low, MB);
+        queue.push(detail.clone(), UploadPriority::High, 2 * MB);
+        assert_eq!(queue.len(), 1);
+        assert_eq!(queue.pending_bytes(), 2 * MB);
+        assert!(queue.remove(detail.key()));
+        assert!(!queue.remove(detail.key()));
+        assert_eq!(queue.pending_bytes(), 0);
+    }
+}
@@ -15,7 +15,7 @@ use crate::{
 use fyrox_core::{
     io::FileLoadError, reflect::prelude::*, uuid::Uuid, visitor::prelude::*, TypeUuidProvider,
 };
-use fyrox_resource::{Resource, ResourceData, SOUND_BUFFER_RESOURCE_UUID};
+use fyrox_resource::{Resource, ResourceData, ResourceMemoryUsage, SOUND_BUFFER_RESOURCE_UUID};
 use std::{
     any::Any,
     borrow::Cow,
@@ -275,4 +275,11 @@ impl ResourceData for SoundBuffer {
     fn type_uuid(&self) -> Uuid {
         SOUND_BUFFER_RESOURCE_UUID
     }
+
+    fn memory_usage(&self) -> ResourceMemoryUsage {
+        ResourceMemoryUsage {
+            cpu_bytes: std::mem::size_of_val(self.samples()),
+            gpu_bytes: 0,
+        }
+    }
 }
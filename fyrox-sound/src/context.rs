@@ -32,6 +32,9 @@ use strum_macros::{AsRefStr, EnumString, EnumVariantNames};
 /// TODO: Make this configurable, for now its set to most commonly used sample rate of 44100 Hz.
 pub const SAMPLE_RATE: u32 = 44100;
 
+/// Default speed of sound in units per second, it matches the speed of sound in air (m/s).
+pub const DEFAULT_SPEED_OF_SOUND: f32 = 343.3;
+
 /// Distance model defines how volume of sound will decay when distance to listener changes.
 #[derive(
     Copy, Clone, Debug, Eq, PartialEq, Reflect, Visit, AsRefStr, EnumString, EnumVariantNames,
@@ -107,6 +110,11 @@ pub struct State {
     bus_graph: AudioBusGraph,
     distance_model: DistanceModel,
     paused: bool,
+    doppler_enabled: bool,
+    #[reflect(min_value = 0.0, step = 1.0)]
+    speed_of_sound: f32,
+    #[reflect(min_value = 0.0, step = 0.05)]
+    doppler_factor: f32,
 }
 
 impl State {
@@ -153,6 +161,41 @@ impl State {
         self.distance_model
     }
 
+    /// Enables or disables Doppler effect for every sound source in the context. When enabled, the
+    /// pitch of a source is shifted according to relative radial velocity of the source and the
+    /// listener (see [`SoundSource::set_velocity`] and [`Listener::set_velocity`]). Sources could
+    /// opt out using [`SoundSource::set_doppler_enabled`]. Default is `true`.
+    pub fn set_doppler_enabled(&mut self, enabled: bool) {
+        self.doppler_enabled = enabled;
+    }
+
+    /// Returns `true` if Doppler effect is enabled in the context.
+    pub fn is_doppler_enabled(&self) -> bool {
+        self.doppler_enabled
+    }
+
+    /// Sets speed of sound in units per second. It is used to calculate Doppler effect, default
+    /// is [`DEFAULT_SPEED_OF_SOUND`].
+    pub fn set_speed_of_sound(&mut self, speed_of_sound: f32) {
+        self.speed_of_sound = speed_of_sound.max(0.0);
+    }
+
+    /// Returns speed of sound in units per second.
+    pub fn speed_of_sound(&self) -> f32 {
+        self.speed_of_sound
+    }
+
+    /// Sets Doppler factor, that exaggerates (values > 1.0) or weakens (values < 1.0) Doppler
+    /// effect. Default is `1.0`, that gives physically correct results.
+    pub fn set_doppler_factor(&mut self, doppler_factor: f32) {
+        self.doppler_factor = doppler_factor.max(0.0);
+    }
+
+    /// Returns Doppler factor.
+    pub fn doppler_factor(&self) -> f32 {
+        self.doppler_factor
+    }
+
     /// Normalizes given frequency using context's sampling rate. Normalized frequency then can be used
     /// to create filters.
     pub fn normalize_frequency(&self, f: f32) -> f32 {
@@ -258,6 +301,17 @@ impl State {
             {
                 if let Some(bus_input_buffer) = self.bus_graph.try_get_bus_input_buffer(&source.bus)
                 {
+                    let doppler_pitch = if self.doppler_enabled {
+                        source.calculate_doppler_pitch(
+                            &self.listener,
+                            self.speed_of_sound,
+                            self.doppler_factor,
+                        )
+                    } else {
+                        1.0
+                    };
+                    source.update_doppler_pitch(doppler_pitch);
+
                     source.render(output_device_buffer.len());
 
                     match self.renderer {
@@ -312,6 +366,9 @@ impl SoundContext {
                 bus_graph: AudioBusGraph::new(),
                 distance_model: DistanceModel::InverseDistance,
                 paused: false,
+                doppler_enabled: true,
+                speed_of_sound: DEFAULT_SPEED_OF_SOUND,
+                doppler_factor: 1.0,
             }))),
         }
     }
@@ -365,7 +422,67 @@ impl Visit for State {
         self.renderer.visit("Renderer", &mut region)?;
         self.paused.visit("Paused", &mut region)?;
         self.distance_model.visit("DistanceModel", &mut region)?;
+        let _ = self.doppler_enabled.visit("DopplerEnabled", &mut region);
+        let _ = self.speed_of_sound.visit("SpeedOfSound", &mut region);
+        let _ = self.doppler_factor.visit("DopplerFactor", &mut region);
 
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        context::{SoundContext, DEFAULT_SPEED_OF_SOUND},
+        source::{SoundSourceBuilder, Status},
+    };
+    use fyrox_core::algebra::Vector3;
+
+    fn render_doppler_pitch(velocity: Vector3<f32>, doppler_enabled: bool) -> f64 {
+        let context = SoundContext::new();
+        let mut state = context.state();
+
+        let source = state.add_source(
+            SoundSourceBuilder::new()
+                .with_position(Vector3::new(0.0, 0.0, 10.0))
+                .with_velocity(velocity)
+                .with_doppler_enabled(doppler_enabled)
+                .with_status(Status::Playing)
+                .build()
+                .unwrap(),
+        );
+
+        let mut output_buffer = vec![(0.0, 0.0); SoundContext::SAMPLES_PER_CHANNEL];
+        // Pitch changes smoothly, so render a few frames to reach the desired value.
+        for _ in 0..64 {
+            state.render(&mut output_buffer);
+        }
+
+        state.source(source).doppler_pitch()
+    }
+
+    #[test]
+    fn test_doppler_effect() {
+        let speed = DEFAULT_SPEED_OF_SOUND * 0.1;
+
+        // Moving towards the listener at the origin.
+        let approaching = render_doppler_pitch(Vector3::new(0.0, 0.0, -speed), true);
+        let expected = DEFAULT_SPEED_OF_SOUND as f64 / (DEFAULT_SPEED_OF_SOUND - speed) as f64;
+        assert!((approaching - expected).abs() < 1.0e-3);
+        assert!(approaching > 1.0);
+
+        // Moving away from the listener.
+        let receding = render_doppler_pitch(Vector3::new(0.0, 0.0, speed), true);
+        let expected = DEFAULT_SPEED_OF_SOUND as f64 / (DEFAULT_SPEED_OF_SOUND + speed) as f64;
+        assert!((receding - expected).abs() < 1.0e-3);
+        assert!(receding < 1.0);
+
+        // Moving perpendicular to the direction to the listener gives no shift.
+        let passing = render_doppler_pitch(Vector3::new(speed, 0.0, 0.0), true);
+        assert_eq!(passing, 1.0);
+
+        // Sources with disabled Doppler effect must keep their pitch.
+        let disabled = render_doppler_pitch(Vector3::new(0.0, 0.0, -speed), false);
+        assert_eq!(disabled, 1.0);
+    }
+}
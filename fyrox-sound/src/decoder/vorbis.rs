@@ -1,4 +1,5 @@
 use crate::{buffer::DataSource, error::SoundError};
+use fyrox_core::log::Log;
 use lewton::{inside_ogg::read_headers, inside_ogg::OggStreamReader, samples::InterleavedSamples};
 use ogg::PacketReader;
 use std::{
@@ -134,7 +135,9 @@ impl OggDecoder {
             .seek_absgp_pg(sample_index as u64)
             .is_err()
         {
-            println!("Failed to seek vorbis/ogg, see https://github.com/RustAudio/lewton/issues/73")
+            Log::warn(
+                "Failed to seek vorbis/ogg, see https://github.com/RustAudio/lewton/issues/73",
+            )
         }
     }
 
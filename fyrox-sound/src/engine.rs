@@ -76,9 +76,12 @@ impl SoundEngine {
         Ok(())
     }
 
-    /// Destroys current audio output device (if any).
+    /// Destroys current audio output device (if any) and waits until its mixer thread is stopped.
     pub fn destroy_audio_output_device(&self) {
-        self.state().output_device = None;
+        // The device must be dropped without holding the lock, otherwise the mixer thread could
+        // wait for the lock forever while the device waits for the thread.
+        let device = self.state().output_device.take();
+        drop(device);
     }
 
     /// Provides direct access to actual engine data.
@@ -32,17 +32,23 @@ use crate::{
     buffer::{streaming::StreamingBuffer, SoundBuffer, SoundBufferResource},
     bus::AudioBusGraph,
     context::DistanceModel,
+    dsp::filters::OnePole,
     error::SoundError,
     listener::Listener,
 };
 use fyrox_core::{
     algebra::Vector3,
+    math,
     reflect::prelude::*,
     visitor::{Visit, VisitResult, Visitor},
 };
 use fyrox_resource::ResourceStateRefMut;
 use std::time::Duration;
 
+// A fraction of the difference between current and desired Doppler pitch, that is applied
+// each render frame. Prevents audible pitch jumps on sudden velocity changes.
+const DOPPLER_PITCH_SMOOTHING: f64 = 0.25;
+
 /// Status (state) of sound source.
 #[derive(Eq, PartialEq, Copy, Clone, Debug, Reflect, Visit)]
 #[repr(u32)]
@@ -123,6 +129,14 @@ pub struct SoundSource {
     max_distance: f32,
     #[reflect(min_value = 0.0, step = 0.05)]
     rolloff_factor: f32,
+    #[visit(optional)]
+    velocity: Vector3<f32>,
+    #[visit(optional)]
+    doppler_enabled: bool,
+    // Current pitch multiplier caused by Doppler effect, it smoothly follows the desired value.
+    #[reflect(hidden)]
+    #[visit(skip)]
+    doppler_pitch: f64,
     // Some data that needed for iterative overlap-save convolution.
     #[reflect(hidden)]
     #[visit(skip)]
@@ -136,6 +150,19 @@ pub struct SoundSource {
     #[reflect(hidden)]
     #[visit(skip)]
     pub(crate) prev_distance_gain: Option<f32>,
+    // Additional gain and lowpass filter, that are used to simulate occlusion of the source.
+    #[reflect(hidden)]
+    #[visit(skip)]
+    occlusion_gain: f32,
+    #[reflect(hidden)]
+    #[visit(skip)]
+    last_occlusion_gain: f32,
+    #[reflect(hidden)]
+    #[visit(skip)]
+    lowpass_cutoff: Option<f32>,
+    #[reflect(hidden)]
+    #[visit(skip)]
+    lowpass: Option<(OnePole, OnePole)>,
 }
 
 impl Default for SoundSource {
@@ -162,10 +189,17 @@ impl Default for SoundSource {
             position: Vector3::new(0.0, 0.0, 0.0),
             max_distance: f32::MAX,
             rolloff_factor: 1.0,
+            velocity: Vector3::new(0.0, 0.0, 0.0),
+            doppler_enabled: true,
+            doppler_pitch: 1.0,
             prev_left_samples: Default::default(),
             prev_right_samples: Default::default(),
             prev_sampling_vector: Vector3::new(0.0, 0.0, 1.0),
             prev_distance_gain: None,
+            occlusion_gain: 1.0,
+            last_occlusion_gain: 1.0,
+            lowpass_cutoff: None,
+            lowpass: None,
         }
     }
 }
@@ -358,6 +392,35 @@ impl SoundSource {
         self.position
     }
 
+    /// Sets velocity of source in world space (units per second). It is used to calculate Doppler
+    /// effect, see [`crate::context::State::set_doppler_enabled`]. Default is zero.
+    pub fn set_velocity(&mut self, velocity: Vector3<f32>) -> &mut Self {
+        self.velocity = velocity;
+        self
+    }
+
+    /// Returns velocity of source.
+    pub fn velocity(&self) -> Vector3<f32> {
+        self.velocity
+    }
+
+    /// Enables or disables Doppler effect for the source. It should be disabled for sounds that
+    /// must never change their pitch, such as UI sounds. Default is `true`.
+    pub fn set_doppler_enabled(&mut self, enabled: bool) -> &mut Self {
+        self.doppler_enabled = enabled;
+        self
+    }
+
+    /// Returns `true` if Doppler effect is enabled for the source.
+    pub fn is_doppler_enabled(&self) -> bool {
+        self.doppler_enabled
+    }
+
+    /// Returns current pitch multiplier caused by Doppler effect, `1.0` means there is no shift.
+    pub fn doppler_pitch(&self) -> f64 {
+        self.doppler_pitch
+    }
+
     /// Sets radius of imaginable sphere around source in which no distance attenuation is applied.
     pub fn set_radius(&mut self, radius: f32) -> &mut Self {
         self.radius = radius;
@@ -450,6 +513,80 @@ impl SoundSource {
             .unwrap_or_else(|| Vector3::new(0.0, 0.0, 1.0))
     }
 
+    // Calculates desired pitch multiplier using relative radial velocity of the source and the
+    // listener. The formula is similar to the one in OpenAL Specification, but velocities are
+    // positive when the source and the listener are approaching each other.
+    pub(crate) fn calculate_doppler_pitch(
+        &self,
+        listener: &Listener,
+        speed_of_sound: f32,
+        doppler_factor: f32,
+    ) -> f64 {
+        if !self.doppler_enabled || speed_of_sound <= 0.0 || doppler_factor <= 0.0 {
+            return 1.0;
+        }
+
+        let to_source = match (self.position - listener.position()).try_normalize(f32::EPSILON) {
+            Some(to_source) => to_source,
+            // Listener is exactly at the source, radial velocity is undefined.
+            None => return 1.0,
+        };
+
+        // Velocities are clamped to prevent division by zero and negative pitch.
+        let limit = speed_of_sound * 0.99;
+        let listener_speed =
+            (doppler_factor * listener.velocity().dot(&to_source)).clamp(-limit, limit);
+        let source_speed = (-doppler_factor * self.velocity.dot(&to_source)).clamp(-limit, limit);
+
+        let pitch = (speed_of_sound + listener_speed) / (speed_of_sound - source_speed);
+
+        // 2D sounds are not affected by Doppler effect.
+        math::lerpf(1.0, pitch, self.spatial_blend) as f64
+    }
+
+    pub(crate) fn update_doppler_pitch(&mut self, desired: f64) {
+        self.doppler_pitch += (desired - self.doppler_pitch) * DOPPLER_PITCH_SMOOTHING;
+        // Snap to the desired value to allow fast-path rendering without resampling.
+        if (desired - self.doppler_pitch).abs() < 1.0e-4 {
+            self.doppler_pitch = desired;
+        }
+    }
+
+    /// Sets additional gain multiplier, that is used to simulate occlusion of the source by
+    /// obstacles. It is applied on top of the gain of the source and changes smoothly during one
+    /// render frame, so it could be changed every frame without clicks. Default is `1.0`.
+    pub fn set_occlusion_gain(&mut self, gain: f32) -> &mut Self {
+        self.occlusion_gain = gain.max(0.0);
+        self
+    }
+
+    /// Returns additional gain multiplier, that is used to simulate occlusion.
+    pub fn occlusion_gain(&self) -> f32 {
+        self.occlusion_gain
+    }
+
+    /// Sets normalized (divided by the sample rate, see [`crate::context::SAMPLE_RATE`]) cutoff
+    /// frequency of a lowpass filter, that is applied to the samples of the source. `None` disables
+    /// the filter (default). Muffled sound of an occluded source could be simulated by the filter.
+    pub fn set_lowpass_cutoff(&mut self, fc: Option<f32>) -> &mut Self {
+        match (fc, self.lowpass.as_mut()) {
+            (Some(fc), Some((left, right))) => {
+                // Keep the state of the filters, so the change won't cause clicks.
+                left.set_fc(fc);
+                right.set_fc(fc);
+            }
+            (Some(fc), None) => self.lowpass = Some((OnePole::new(fc), OnePole::new(fc))),
+            (None, _) => self.lowpass = None,
+        }
+        self.lowpass_cutoff = fc;
+        self
+    }
+
+    /// Returns normalized cutoff frequency of the lowpass filter of the source, if any.
+    pub fn lowpass_cutoff(&self) -> Option<f32> {
+        self.lowpass_cutoff
+    }
+
     /// Returns playback duration.
     pub fn playback_time(&self) -> Duration {
         if let Some(buffer) = self.buffer.as_ref() {
@@ -505,6 +642,29 @@ impl SoundSource {
         }
         // Fill the remaining part of frame_samples.
         self.frame_samples.resize(amount, (0.0, 0.0));
+
+        self.apply_occlusion();
+    }
+
+    fn apply_occlusion(&mut self) {
+        if let Some((left, right)) = self.lowpass.as_mut() {
+            for (l, r) in self.frame_samples.iter_mut() {
+                *l = left.feed(*l);
+                *r = right.feed(*r);
+            }
+        }
+
+        let (from, to) = (self.last_occlusion_gain, self.occlusion_gain);
+        if from != 1.0 || to != 1.0 {
+            // Interpolate the gain across the frame to prevent clicks.
+            let step = 1.0 / self.frame_samples.len().max(1) as f32;
+            for (i, (l, r)) in self.frame_samples.iter_mut().enumerate() {
+                let gain = math::lerpf(from, to, i as f32 * step);
+                *l *= gain;
+                *r *= gain;
+            }
+        }
+        self.last_occlusion_gain = to;
     }
 
     fn render_playing(&mut self, buffer: &mut SoundBuffer, amount: usize) {
@@ -544,7 +704,7 @@ impl SoundSource {
     // Renders until the end of the block or until amount samples is written and returns
     // the number of written samples.
     fn render_until_block_end(&mut self, buffer: &mut SoundBuffer, mut amount: usize) -> usize {
-        let step = self.pitch * self.resampling_multiplier;
+        let step = self.pitch * self.resampling_multiplier * self.doppler_pitch;
         if step == 1.0 {
             if self.buf_read_pos < 0.0 {
                 // This can theoretically happen if we change pitch on the fly.
@@ -716,6 +876,8 @@ pub struct SoundSourceBuilder {
     rolloff_factor: f32,
     spatial_blend: f32,
     bus: String,
+    velocity: Vector3<f32>,
+    doppler_enabled: bool,
 }
 
 impl Default for SoundSourceBuilder {
@@ -743,6 +905,8 @@ impl SoundSourceBuilder {
             rolloff_factor: 1.0,
             spatial_blend: 1.0,
             bus: AudioBusGraph::PRIMARY_BUS.to_string(),
+            velocity: Vector3::new(0.0, 0.0, 0.0),
+            doppler_enabled: true,
         }
     }
 
@@ -836,6 +1000,18 @@ impl SoundSourceBuilder {
         self
     }
 
+    /// See [`SoundSource::set_velocity`]
+    pub fn with_velocity(mut self, velocity: Vector3<f32>) -> Self {
+        self.velocity = velocity;
+        self
+    }
+
+    /// See [`SoundSource::set_doppler_enabled`]
+    pub fn with_doppler_enabled(mut self, enabled: bool) -> Self {
+        self.doppler_enabled = enabled;
+        self
+    }
+
     /// Sets desired output bus for the sound source.
     pub fn with_bus<S: AsRef<str>>(mut self, bus: S) -> Self {
         self.bus = bus.as_ref().to_string();
@@ -859,9 +1035,14 @@ impl SoundSourceBuilder {
             max_distance: self.max_distance,
             rolloff_factor: self.rolloff_factor,
             spatial_blend: self.spatial_blend,
+            velocity: self.velocity,
+            doppler_enabled: self.doppler_enabled,
             prev_left_samples: Default::default(),
             prev_right_samples: Default::default(),
             bus: self.bus,
+            // Filters aren't `Copy` and cannot be moved out of the default instance, because
+            // the source implements `Drop`.
+            lowpass: None,
             ..Default::default()
         };
 
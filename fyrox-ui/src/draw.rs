@@ -5,8 +5,8 @@ use crate::{
         color::Color,
         math::{self, Rect, TriangleDefinition},
     },
-    formatted_text::FormattedText,
-    ttf::SharedFont,
+    formatted_text::{FormattedText, TextGlyph},
+    ttf::{Font, SharedFont},
     Thickness,
 };
 use std::{any::Any, ops::Range, sync::Arc};
@@ -513,6 +513,16 @@ pub struct DrawingContext {
     pub transform_stack: TransformStack,
     opacity_stack: Vec<f32>,
     triangles_to_commit: usize,
+    scale_factor: f32,
+}
+
+// Texture coordinates are taken from the font, because its glyphs could be rasterized again after
+// the text was formatted.
+fn glyph_tex_coords(font: &Font, glyph: &TextGlyph) -> [Vector2<f32>; 4] {
+    glyph
+        .glyph_index()
+        .and_then(|index| font.glyphs().get(index))
+        .map_or(*glyph.get_tex_coords(), |font_glyph| font_glyph.tex_coords)
 }
 
 fn get_line_thickness_vector(a: Vector2<f32>, b: Vector2<f32>, thickness: f32) -> Vector2<f32> {
@@ -562,9 +572,24 @@ impl DrawingContext {
             triangles_to_commit: 0,
             opacity_stack: vec![1.0],
             transform_stack: Default::default(),
+            scale_factor: 1.0,
         }
     }
 
+    /// Sets the ratio between physical pixels and logical units of the drawing context. Fonts are
+    /// rasterized using this scale, so the text stays sharp when it is scaled. See
+    /// [`crate::UserInterface::set_scale_factor`] for more info.
+    #[inline]
+    pub fn set_scale_factor(&mut self, scale_factor: f32) {
+        self.scale_factor = scale_factor;
+    }
+
+    /// Returns the ratio between physical pixels and logical units of the drawing context.
+    #[inline]
+    pub fn scale_factor(&self) -> f32 {
+        self.scale_factor
+    }
+
     #[inline]
     pub fn clear(&mut self) {
         self.vertex_buffer.clear();
@@ -672,6 +697,8 @@ impl DrawingContext {
         formatted_text: &FormattedText,
     ) {
         let font = formatted_text.get_font();
+        let mut font_ref = font.0.lock();
+        font_ref.set_rasterization_scale(self.scale_factor);
 
         // Draw shadow, if any.
         if formatted_text.shadow {
@@ -690,7 +717,7 @@ impl DrawingContext {
                 )
                 .translate(formatted_text.shadow_offset);
 
-                self.push_rect_filled(&final_bounds, Some(element.get_tex_coords()));
+                self.push_rect_filled(&final_bounds, Some(&glyph_tex_coords(&font_ref, element)));
             }
 
             self.commit(
@@ -711,9 +738,11 @@ impl DrawingContext {
                 bounds.h(),
             );
 
-            self.push_rect_filled(&final_bounds, Some(element.get_tex_coords()));
+            self.push_rect_filled(&final_bounds, Some(&glyph_tex_coords(&font_ref, element)));
         }
 
+        drop(font_ref);
+
         self.commit(
             clip_bounds,
             formatted_text.brush(),
@@ -10,6 +10,7 @@ use std::ops::Range;
 pub struct TextGlyph {
     bounds: Rect<f32>,
     tex_coords: [Vector2<f32>; 4],
+    glyph_index: Option<usize>,
 }
 
 impl TextGlyph {
@@ -20,6 +21,12 @@ impl TextGlyph {
     pub fn get_tex_coords(&self) -> &[Vector2<f32>; 4] {
         &self.tex_coords
     }
+
+    /// Returns index of the glyph in the font, `None` means that the font does not have the
+    /// glyph for the character.
+    pub fn glyph_index(&self) -> Option<usize> {
+        self.glyph_index
+    }
 }
 
 #[derive(Copy, Clone, Debug)]
@@ -466,15 +473,14 @@ impl FormattedText {
                         // Insert glyph
                         let rect = Rect::new(
                             cursor.x + glyph.left.floor(),
-                            cursor.y + font.ascender().floor()
-                                - glyph.top.floor()
-                                - glyph.bitmap_height as f32,
-                            glyph.bitmap_width as f32,
-                            glyph.bitmap_height as f32,
+                            cursor.y + font.ascender().floor() - glyph.top.floor() - glyph.height,
+                            glyph.width,
+                            glyph.height,
                         );
                         let text_glyph = TextGlyph {
                             bounds: rect,
                             tex_coords: glyph.tex_coords,
+                            glyph_index: Some(character.glyph_index as usize),
                         };
                         self.glyphs.push(text_glyph);
 
@@ -491,6 +497,7 @@ impl FormattedText {
                         self.glyphs.push(TextGlyph {
                             bounds: rect,
                             tex_coords: [Vector2::default(); 4],
+                            glyph_index: None,
                         });
                         cursor.x += rect.w();
                     }
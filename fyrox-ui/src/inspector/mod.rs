@@ -23,7 +23,6 @@ use crate::{
     widget::{Widget, WidgetBuilder, WidgetMessage},
     BuildContext, Control, RcUiNodeHandle, Thickness, UiNode, UserInterface, VerticalAlignment,
 };
-use copypasta::ClipboardProvider;
 use std::sync::Arc;
 use std::{
     any::{Any, TypeId},
@@ -252,7 +252,7 @@ use crate::{
     ttf::{Font, FontBuilder, SharedFont},
     widget::{Widget, WidgetBuilder, WidgetMessage},
 };
-use copypasta::ClipboardContext;
+use copypasta::{ClipboardContext, ClipboardProvider};
 use fxhash::{FxHashMap, FxHashSet};
 use serde::{Deserialize, Serialize};
 use std::{
@@ -545,6 +545,7 @@ struct DoubleClickEntry {
 
 pub struct UserInterface {
     screen_size: Vector2<f32>,
+    scale_factor: f32,
     nodes: Pool<UiNode>,
     drawing_context: DrawingContext,
     visual_debug: bool,
@@ -565,7 +566,7 @@ pub struct UserInterface {
     cursor_icon: CursorIcon,
     active_tooltip: Option<TooltipEntry>,
     preview_set: FxHashSet<Handle<UiNode>>,
-    clipboard: Option<RefCell<ClipboardContext>>,
+    clipboard: Option<RefCell<Box<dyn ClipboardProvider>>>,
     layout_events_receiver: Receiver<LayoutEvent>,
     layout_events_sender: Sender<LayoutEvent>,
     need_update_global_transform: bool,
@@ -665,6 +666,7 @@ impl UserInterface {
         let default_font = SharedFont::new(FontBuilder::new().build_builtin().unwrap());
         let mut ui = UserInterface {
             screen_size,
+            scale_factor: 1.0,
             sender,
             receiver,
             visual_debug: false,
@@ -685,7 +687,9 @@ impl UserInterface {
             cursor_icon: Default::default(),
             active_tooltip: Default::default(),
             preview_set: Default::default(),
-            clipboard: ClipboardContext::new().ok().map(RefCell::new),
+            clipboard: ClipboardContext::new()
+                .ok()
+                .map(|clipboard| RefCell::new(Box::new(clipboard) as Box<dyn ClipboardProvider>)),
             layout_events_receiver,
             layout_events_sender,
             need_update_global_transform: Default::default(),
@@ -809,6 +813,42 @@ impl UserInterface {
         self.screen_size = screen_size;
     }
 
+    /// Sets scale factor of the user interface (usually it is the scale factor of the window, see
+    /// HiDPI docs of `winit` for more info). Widgets are laid out in logical units (physical screen
+    /// size divided by the scale factor) and then scaled to physical pixels, so the interface has
+    /// the same apparent size on displays with any pixel density. Fonts are rasterized at the
+    /// scaled size when the text is drawn, so the text stays sharp.
+    pub fn set_scale_factor(&mut self, scale_factor: f32) {
+        if scale_factor <= 0.0 || self.scale_factor == scale_factor {
+            return;
+        }
+
+        self.scale_factor = scale_factor;
+        self.drawing_context.set_scale_factor(scale_factor);
+
+        let root_canvas = &mut self.nodes[self.root_canvas];
+        root_canvas.render_transform = Matrix3::new_scaling(scale_factor);
+        root_canvas.invalidate_layout();
+        self.need_update_global_transform = true;
+    }
+
+    /// Returns scale factor of the user interface, see [`Self::set_scale_factor`] for more info.
+    pub fn scale_factor(&self) -> f32 {
+        self.scale_factor
+    }
+
+    /// Returns size of the screen in logical units (physical size divided by the scale factor). Widgets
+    /// that are attached to the root canvas are laid out in this space.
+    pub fn logical_screen_size(&self) -> Vector2<f32> {
+        self.screen_size.scale(1.0 / self.scale_factor)
+    }
+
+    // Visual transform of every widget includes the scale of the root canvas, so the initial size
+    // must be scaled back to be in the same (logical) units as the layout.
+    fn logical_initial_size(&self, node: &UiNode) -> Vector2<f32> {
+        node.actual_initial_size().scale(self.scale_factor)
+    }
+
     fn handle_layout_events(&mut self) {
         fn invalidate_recursive_up(
             nodes: &Pool<UiNode>,
@@ -854,10 +894,11 @@ impl UserInterface {
 
         self.handle_layout_events();
 
-        self.measure_node(self.root_canvas, screen_size);
+        let logical_screen_size = self.logical_screen_size();
+        self.measure_node(self.root_canvas, logical_screen_size);
         let arrangement_changed = self.arrange_node(
             self.root_canvas,
-            &Rect::new(0.0, 0.0, screen_size.x, screen_size.y),
+            &Rect::new(0.0, 0.0, logical_screen_size.x, logical_screen_size.y),
         );
 
         if self.need_update_global_transform {
@@ -958,14 +999,26 @@ impl UserInterface {
         &self.drawing_context
     }
 
-    pub fn clipboard(&self) -> Option<Ref<ClipboardContext>> {
+    pub fn clipboard(&self) -> Option<Ref<'_, Box<dyn ClipboardProvider>>> {
         self.clipboard.as_ref().map(|v| v.borrow())
     }
 
-    pub fn clipboard_mut(&self) -> Option<RefMut<ClipboardContext>> {
+    pub fn clipboard_mut(&self) -> Option<RefMut<'_, Box<dyn ClipboardProvider>>> {
         self.clipboard.as_ref().map(|v| v.borrow_mut())
     }
 
+    /// Replaces the clipboard that is used by the widgets (for example, by [`text_box::TextBox`] for
+    /// copy and paste). By default, the system clipboard is used (if it is available), this method
+    /// allows you to provide a platform-specific implementation or an application-local clipboard.
+    pub fn set_clipboard(&mut self, clipboard: Box<dyn ClipboardProvider>) {
+        self.clipboard = Some(RefCell::new(clipboard));
+    }
+
+    /// Returns a handle of the widget that has keyboard focus.
+    pub fn keyboard_focus_node(&self) -> Handle<UiNode> {
+        self.keyboard_focus_node
+    }
+
     pub fn arrange_node(&self, handle: Handle<UiNode>, final_rect: &Rect<f32>) -> bool {
         scope_profile!();
 
@@ -1578,12 +1631,12 @@ impl UserInterface {
                         WidgetMessage::Center => {
                             if self.nodes.is_valid_handle(message.destination()) {
                                 let node = self.node(message.destination());
-                                let size = node.actual_initial_size();
+                                let size = self.logical_initial_size(node);
                                 let parent = node.parent();
                                 let parent_size = if parent.is_some() {
-                                    self.node(parent).actual_initial_size()
+                                    self.logical_initial_size(self.node(parent))
                                 } else {
-                                    self.screen_size
+                                    self.logical_screen_size()
                                 };
 
                                 self.send_message(WidgetMessage::desired_position(
@@ -1597,12 +1650,12 @@ impl UserInterface {
                             if self.nodes.is_valid_handle(message.destination()) {
                                 let node = self.node(message.destination());
                                 let mut position = node.actual_local_position();
-                                let size = node.actual_initial_size();
+                                let size = self.logical_initial_size(node);
                                 let parent = node.parent();
                                 let parent_size = if parent.is_some() {
-                                    self.node(parent).actual_initial_size()
+                                    self.logical_initial_size(self.node(parent))
                                 } else {
-                                    self.screen_size
+                                    self.logical_screen_size()
                                 };
 
                                 if position.x < 0.0 {
@@ -1768,6 +1821,12 @@ impl UserInterface {
         self.captured_node
     }
 
+    /// Returns a handle of the widget that is currently under the cursor (or the captured widget, if
+    /// any).
+    pub fn picked_node(&self) -> Handle<UiNode> {
+        self.picked_node
+    }
+
     // Tries to set new picked node (a node under the cursor) and returns `true` if the node was
     // changed.
     fn try_set_picked_node(&mut self, node: Handle<UiNode>) -> bool {
@@ -2500,9 +2559,10 @@ fn transform_size(transform_space_bounds: Vector2<f32>, matrix: &Matrix3<f32>) -
 
 #[cfg(test)]
 mod test {
-    use crate::message::{ButtonState, KeyCode};
+    use crate::message::{ButtonState, KeyCode, MouseButton};
     use crate::{
         border::BorderBuilder,
+        button::{ButtonBuilder, ButtonMessage},
         core::algebra::{Rotation2, UnitComplex, Vector2},
         message::MessageDirection,
         text_box::TextBoxBuilder,
@@ -2540,6 +2600,75 @@ mod test {
         assert_eq!(actual_position, expected_position);
     }
 
+    #[test]
+    fn test_scale_factor() {
+        let screen_size = Vector2::new(1000.0, 1000.0);
+        let mut ui = UserInterface::new(screen_size);
+        let widget = BorderBuilder::new(WidgetBuilder::new().with_width(100.0).with_height(100.0))
+            .build(&mut ui.build_ctx());
+        ui.set_scale_factor(2.0);
+        ui.update(screen_size, 0.0);
+        assert_eq!(ui.logical_screen_size(), Vector2::new(500.0, 500.0));
+
+        // The widget is laid out in logical units, but covers twice more physical pixels.
+        ui.send_message(WidgetMessage::center(widget, MessageDirection::ToWidget));
+        while ui.poll_message().is_some() {}
+        ui.update(screen_size, 0.0);
+        assert_eq!(
+            ui.node(widget).actual_local_position(),
+            Vector2::new(200.0, 200.0)
+        );
+        let bounds = ui.node(widget).screen_bounds();
+        assert_eq!(bounds.position, Vector2::new(400.0, 400.0));
+        assert_eq!(bounds.size, Vector2::new(200.0, 200.0));
+        // Hit testing uses drawn geometry, which is in physical pixels.
+        ui.draw();
+        assert_eq!(ui.hit_test(Vector2::new(590.0, 590.0)), widget);
+    }
+
+    #[test]
+    fn test_scaled_button_click() {
+        let screen_size = Vector2::new(1000.0, 1000.0);
+        let mut ui = UserInterface::new(screen_size);
+        let button = ButtonBuilder::new(WidgetBuilder::new().with_width(100.0).with_height(100.0))
+            .with_text("Click")
+            .build(&mut ui.build_ctx());
+        ui.set_scale_factor(2.0);
+        ui.update(screen_size, 0.0);
+        while ui.poll_message().is_some() {}
+
+        // A 100-unit button occupies 200 physical pixels.
+        let bounds = ui.node(button).screen_bounds();
+        assert_eq!(bounds.position, Vector2::new(0.0, 0.0));
+        assert_eq!(bounds.size, Vector2::new(200.0, 200.0));
+
+        ui.draw();
+        // Cursor position is in physical pixels, the point is outside of unscaled button.
+        ui.process_os_event(&OsEvent::CursorMoved {
+            position: Vector2::new(150.0, 150.0),
+        });
+        for state in [ButtonState::Pressed, ButtonState::Released] {
+            ui.process_os_event(&OsEvent::MouseInput {
+                button: MouseButton::Left,
+                state,
+            });
+        }
+        let mut clicked = false;
+        while let Some(message) = ui.poll_message() {
+            if message.destination() == button
+                && message.direction() == MessageDirection::FromWidget
+                && message.data() == Some(&ButtonMessage::Click)
+            {
+                clicked = true;
+            }
+        }
+        assert!(clicked);
+
+        // The text is rasterized at the scaled size.
+        ui.draw();
+        assert_eq!(ui.default_font.0.lock().rasterization_scale(), 2.0);
+    }
+
     #[test]
     fn test_keyboard_focus() {
         let screen_size = Vector2::new(1000.0, 1000.0);
@@ -414,6 +414,7 @@ pub enum MouseButton {
 }
 
 /// An event that an OS sends to a window, that is then can be used to "feed" the user interface so it can do some actions.
+#[derive(Debug, Clone, PartialEq)]
 pub enum OsEvent {
     /// Mouse input event.
     MouseInput {
@@ -308,28 +308,36 @@ impl Popup {
         ui.try_get_node(target)
             .map(|n| n.screen_position() + Vector2::new(n.actual_global_size().x, 0.0))
             .unwrap_or_else(|| {
-                Vector2::new(ui.screen_size().x - self.widget.actual_global_size().x, 0.0)
+                Vector2::new(
+                    ui.logical_screen_size().x - self.widget.actual_global_size().x,
+                    0.0,
+                )
             })
     }
 
     fn center_placement(&self, ui: &UserInterface, target: Handle<UiNode>) -> Vector2<f32> {
         ui.try_get_node(target)
             .map(|n| n.screen_position() + n.actual_global_size().scale(0.5))
-            .unwrap_or_else(|| (ui.screen_size - self.widget.actual_global_size()).scale(0.5))
+            .unwrap_or_else(|| {
+                (ui.logical_screen_size() - self.widget.actual_global_size()).scale(0.5)
+            })
     }
 
     fn left_bottom_placement(&self, ui: &UserInterface, target: Handle<UiNode>) -> Vector2<f32> {
         ui.try_get_node(target)
             .map(|n| n.screen_position() + Vector2::new(0.0, n.actual_global_size().y))
             .unwrap_or_else(|| {
-                Vector2::new(0.0, ui.screen_size().y - self.widget.actual_global_size().y)
+                Vector2::new(
+                    0.0,
+                    ui.logical_screen_size().y - self.widget.actual_global_size().y,
+                )
             })
     }
 
     fn right_bottom_placement(&self, ui: &UserInterface, target: Handle<UiNode>) -> Vector2<f32> {
         ui.try_get_node(target)
             .map(|n| n.screen_position() + n.actual_global_size())
-            .unwrap_or_else(|| ui.screen_size - self.widget.actual_global_size())
+            .unwrap_or_else(|| ui.logical_screen_size() - self.widget.actual_global_size())
     }
 }
 
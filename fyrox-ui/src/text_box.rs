@@ -21,7 +21,6 @@ use crate::{
     BuildContext, Control, HorizontalAlignment, UiNode, UserInterface, VerticalAlignment,
     BRUSH_DARKER, BRUSH_TEXT,
 };
-use copypasta::ClipboardProvider;
 use std::{
     any::{Any, TypeId},
     cell::RefCell,
@@ -49,6 +48,10 @@ pub enum TextBoxMessage {
     Multiline(bool),
     /// Used to enable or disable an ability to edit text box content. Use [TextBoxMessage::editable`] to create the message.
     Editable(bool),
+    /// Used to paste text at the caret position (replacing selected text), just like `Ctrl+V` does. It is useful
+    /// when the clipboard content is delivered asynchronously (for example, on WebAssembly). Does nothing if the
+    /// text box is not editable. Use [TextBoxMessage::paste`] to create the message.
+    Paste(String),
 }
 
 impl TextBoxMessage {
@@ -72,6 +75,10 @@ impl TextBoxMessage {
         /// Creates [`TextBoxMessage::Editable`].
         TextBoxMessage:Editable => fn editable(bool), layout: false
     );
+    define_constructor!(
+        /// Creates [`TextBoxMessage::Paste`].
+        TextBoxMessage:Paste => fn paste(String), layout: false
+    );
 }
 
 /// Specifies a direction on horizontal axis.
@@ -288,6 +295,7 @@ pub type FilterCallback = dyn FnMut(char) -> bool;
 /// - [`TextBoxMessage::TextCommitMode`] - changes the [text commit mode](TextBox#text-commit-mode).
 /// - [`TextBoxMessage::Multiline`] - makes the TextBox either multiline (`true`) or single line (`false`)
 /// - [`TextBoxMessage::Editable`] - enables or disables editing of the text.
+/// - [`TextBoxMessage::Paste`] - pastes the text at the caret position.
 ///
 /// **Important:** Please keep in mind, that TextBox widget also accepts [`TextMessage`]s. An example of changing text at
 /// runtime could be something like this:
@@ -318,6 +326,7 @@ pub type FilterCallback = dyn FnMut(char) -> bool;
 ///
 /// - `Ctrl+A` - select all
 /// - `Ctrl+C` - copy selected text
+/// - `Ctrl+X` - cut selected text
 /// - `Ctrl+V` - paste text from clipboard
 /// - `Ctrl+Home` - move caret to the beginning of the text
 /// - `Ctrl+End` - move caret to the beginning of the text
@@ -684,6 +693,35 @@ impl TextBox {
         }
     }
 
+    fn copy_selection(&self, ui: &UserInterface) {
+        if let Some(selection_range) = self.selection_range.as_ref() {
+            if let (Some(begin), Some(end)) = (
+                self.position_to_char_index_unclamped(selection_range.begin),
+                self.position_to_char_index_unclamped(selection_range.end),
+            ) {
+                if let Some(mut clipboard) = ui.clipboard_mut() {
+                    // Positions are char indices, so the text must not be sliced by bytes.
+                    let selected = self
+                        .text()
+                        .chars()
+                        .skip(begin.min(end))
+                        .take(begin.max(end) - begin.min(end))
+                        .collect::<String>();
+                    let _ = clipboard.set_contents(selected);
+                }
+            }
+        }
+    }
+
+    fn paste(&mut self, content: &str, ui: &UserInterface) {
+        if let Some(selection_range) = self.selection_range {
+            self.remove_range(ui, selection_range);
+            self.selection_range = None;
+        }
+
+        self.insert_str(content, ui);
+    }
+
     /// Returns current text length in characters.
     pub fn get_text_len(&self) -> usize {
         self.formatted_text.borrow_mut().get_raw_text().len()
@@ -854,11 +892,7 @@ impl TextBox {
                     let character = self.formatted_text.borrow().get_raw_text()[index];
                     let (width, height, advance) =
                         if let Some(glyph) = font.glyphs().get(character.glyph_index as usize) {
-                            (
-                                glyph.bitmap_width as f32,
-                                glyph.bitmap_height as f32,
-                                glyph.advance,
-                            )
+                            (glyph.width, glyph.height, glyph.advance)
                         } else {
                             // Stub
                             let h = font.height();
@@ -1315,37 +1349,21 @@ impl Control for TextBox {
                                 }
                             }
                             KeyCode::KeyC if ui.keyboard_modifiers().control => {
-                                if let Some(mut clipboard) = ui.clipboard_mut() {
-                                    if let Some(selection_range) = self.selection_range.as_ref() {
-                                        if let (Some(begin), Some(end)) = (
-                                            self.position_to_char_index_unclamped(
-                                                selection_range.begin,
-                                            ),
-                                            self.position_to_char_index_unclamped(
-                                                selection_range.end,
-                                            ),
-                                        ) {
-                                            let _ = clipboard.set_contents(String::from(
-                                                &self.text()[if begin < end {
-                                                    begin..end
-                                                } else {
-                                                    end..begin
-                                                }],
-                                            ));
-                                        }
-                                    }
+                                self.copy_selection(ui);
+                            }
+                            KeyCode::KeyX if ui.keyboard_modifiers().control && self.editable => {
+                                if let Some(selection_range) = self.selection_range {
+                                    self.copy_selection(ui);
+                                    self.remove_range(ui, selection_range);
+                                    self.selection_range = None;
                                 }
                             }
-                            KeyCode::KeyV if ui.keyboard_modifiers().control => {
-                                if let Some(mut clipboard) = ui.clipboard_mut() {
-                                    if let Ok(content) = clipboard.get_contents() {
-                                        if let Some(selection_range) = self.selection_range {
-                                            self.remove_range(ui, selection_range);
-                                            self.selection_range = None;
-                                        }
-
-                                        self.insert_str(&content, ui);
-                                    }
+                            KeyCode::KeyV if ui.keyboard_modifiers().control && self.editable => {
+                                let content = ui
+                                    .clipboard_mut()
+                                    .and_then(|mut clipboard| clipboard.get_contents().ok());
+                                if let Some(content) = content {
+                                    self.paste(&content, ui);
                                 }
                             }
                             _ => (),
@@ -1556,6 +1574,11 @@ impl Control for TextBox {
                                 ui.send_message(message.reverse());
                             }
                         }
+                        TextBoxMessage::Paste(content) => {
+                            if self.editable {
+                                self.paste(content, ui);
+                            }
+                        }
                     }
                 }
             }
@@ -18,6 +18,10 @@ pub struct FontGlyph {
     pub left: f32,
     pub advance: f32,
     pub tex_coords: [Vector2<f32>; 4],
+    /// Width of the glyph in logical units (width of the bitmap divided by rasterization scale).
+    pub width: f32,
+    /// Height of the glyph in logical units (height of the bitmap divided by rasterization scale).
+    pub height: f32,
     pub bitmap_width: usize,
     pub bitmap_height: usize,
     pub pixels: Vec<u8>,
@@ -25,6 +29,8 @@ pub struct FontGlyph {
 
 pub struct Font {
     height: f32,
+    rasterization_scale: f32,
+    face: fontdue::Font,
     glyphs: Vec<FontGlyph>,
     ascender: f32,
     descender: f32,
@@ -150,11 +156,13 @@ impl Font {
         height: f32,
         char_set: &[Range<u32>],
     ) -> Result<Self, &'static str> {
-        let fontdue_font = fontdue::Font::from_bytes(data, fontdue::FontSettings::default())?;
-        let font_metrics = fontdue_font.horizontal_line_metrics(height).unwrap();
+        let face = fontdue::Font::from_bytes(data, fontdue::FontSettings::default())?;
+        let font_metrics = face.horizontal_line_metrics(height).unwrap();
 
         let mut font = Font {
             height,
+            rasterization_scale: 1.0,
+            face,
             glyphs: Vec::new(),
             ascender: font_metrics.ascent,
             descender: font_metrics.descent,
@@ -164,24 +172,12 @@ impl Font {
             texture: None,
         };
 
-        let mut index = 0;
         for range in char_set {
             for unicode in range.start..range.end {
                 if let Some(character) = std::char::from_u32(unicode) {
-                    let (metrics, bitmap) = fontdue_font.rasterize(character, height);
-
-                    font.glyphs.push(FontGlyph {
-                        left: metrics.xmin as f32,
-                        top: metrics.ymin as f32,
-                        pixels: bitmap,
-                        advance: metrics.advance_width,
-                        tex_coords: Default::default(),
-                        bitmap_width: metrics.width,
-                        bitmap_height: metrics.height,
-                    });
-
-                    font.char_map.insert(unicode, index);
-                    index += 1;
+                    let glyph = font.rasterize(character);
+                    font.char_map.insert(unicode, font.glyphs.len());
+                    font.glyphs.push(glyph);
                 }
             }
         }
@@ -191,6 +187,62 @@ impl Font {
         Ok(font)
     }
 
+    fn rasterize(&self, character: char) -> FontGlyph {
+        let scale = self.rasterization_scale;
+        let (metrics, bitmap) = self.face.rasterize(character, self.height * scale);
+        FontGlyph {
+            left: metrics.xmin as f32 / scale,
+            top: metrics.ymin as f32 / scale,
+            pixels: bitmap,
+            advance: metrics.advance_width / scale,
+            tex_coords: Default::default(),
+            width: metrics.width as f32 / scale,
+            height: metrics.height as f32 / scale,
+            bitmap_width: metrics.width,
+            bitmap_height: metrics.height,
+        }
+    }
+
+    /// Sets the ratio between the size of glyph bitmaps in pixels and the size of glyphs in logical
+    /// units. Every glyph is rasterized again at `height * scale` size, so the text stays sharp
+    /// when it is drawn with scaling (for example, on HiDPI displays), all the metrics of the font
+    /// stay the same. Does nothing if the scale is the same as the current one. The texture of
+    /// the atlas is re-created on the next draw.
+    ///
+    /// # Performance
+    ///
+    /// Rasterization of a large character set is slow, so avoid sharing a font between user
+    /// interfaces with different scale factors - the font will be rasterized every frame.
+    pub fn set_rasterization_scale(&mut self, scale: f32) {
+        if scale <= 0.0 || self.rasterization_scale == scale {
+            return;
+        }
+
+        self.rasterization_scale = scale;
+
+        let mut characters = self
+            .char_map
+            .iter()
+            .map(|(unicode, index)| (*index, *unicode))
+            .collect::<Vec<_>>();
+        characters.sort_unstable();
+        for (index, unicode) in characters {
+            if let Some(character) = std::char::from_u32(unicode) {
+                self.glyphs[index] = self.rasterize(character);
+            }
+        }
+
+        self.pack();
+        self.texture = None;
+    }
+
+    /// Returns the ratio between the size of glyph bitmaps in pixels and the size of glyphs in
+    /// logical units. See [`Self::set_rasterization_scale`] for more info.
+    #[inline]
+    pub fn rasterization_scale(&self) -> f32 {
+        self.rasterization_scale
+    }
+
     pub async fn from_file<P: AsRef<Path>>(
         path: P,
         height: f32,
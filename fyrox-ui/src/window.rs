@@ -610,7 +610,8 @@ impl Control for Window {
                                 current_size.y,
                             ))
                             .unwrap_or_else(|| {
-                                Rect::new(0.0, 0.0, ui.screen_size.x, ui.screen_size.y)
+                                let screen_size = ui.logical_screen_size();
+                                Rect::new(0.0, 0.0, screen_size.x, screen_size.y)
                             });
 
                         ui.send_message(WidgetMessage::desired_position(
@@ -665,11 +666,11 @@ impl Control for Window {
                             // application window, thus leaving an opportunity to drag window to some other place.
                             new_pos.x = new_pos.x.clamp(
                                 -(self.actual_local_size().x - safe_border.x).abs(),
-                                (ui.screen_size().x - safe_border.x).abs(),
+                                (ui.logical_screen_size().x - safe_border.x).abs(),
                             );
                             new_pos.y = new_pos
                                 .y
-                                .clamp(0.0, (ui.screen_size().y - safe_border.y).abs());
+                                .clamp(0.0, (ui.logical_screen_size().y - safe_border.y).abs());
                         }
 
                         if self.is_dragging && self.desired_local_position() != new_pos {
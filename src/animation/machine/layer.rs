@@ -571,7 +571,7 @@ impl MachineLayer {
     pub(super) fn evaluate_pose(
         &mut self,
         animations: &mut AnimationContainer,
-        parameters: &ParameterContainer,
+        parameters: &mut ParameterContainer,
         dt: f32,
     ) -> &AnimationPose {
         self.final_pose.reset();
@@ -592,6 +592,8 @@ impl MachineLayer {
                     }
 
                     if transition.condition.calculate_value(parameters, animations) {
+                        transition.condition.consume_triggers(parameters);
+
                         if let Some(active_state) = self.states.try_borrow(self.active_state) {
                             for action in active_state.on_leave_actions.iter() {
                                 action.apply(animations);
@@ -686,3 +688,96 @@ impl MachineLayer {
         &self.final_pose
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        animation::{
+            machine::{
+                transition::{CompareNode, ComparisonOperator, LogicNode},
+                Event, Machine, MachineLayer, Parameter, PlayAnimation, PoseNode, State,
+                Transition,
+            },
+            AnimationContainer,
+        },
+        core::pool::Handle,
+    };
+
+    fn compare(operator: ComparisonOperator, value: f32) -> LogicNode {
+        LogicNode::Compare(CompareNode {
+            parameter: "Speed".to_string(),
+            operator,
+            value,
+        })
+    }
+
+    #[test]
+    fn test_float_and_trigger_conditions() {
+        let mut layer = MachineLayer::new();
+
+        let mut add_state = |name: &str| {
+            let node = layer.add_node(PoseNode::PlayAnimation(PlayAnimation::new(Handle::NONE)));
+            layer.add_state(State::new(name, node))
+        };
+        let walk = add_state("Walk");
+        let run = add_state("Run");
+        let jump = add_state("Jump");
+        layer.set_entry_state(walk);
+
+        let mut walk_to_run = Transition::new("Walk->Run", walk, run, 0.1, "");
+        walk_to_run.set_condition(compare(ComparisonOperator::Greater, 0.5));
+        layer.add_transition(walk_to_run);
+
+        let mut run_to_walk = Transition::new("Run->Walk", run, walk, 0.1, "");
+        run_to_walk.set_condition(compare(ComparisonOperator::LessOrEqual, 0.5));
+        layer.add_transition(run_to_walk);
+
+        layer.add_transition(Transition::new("Walk->Jump", walk, jump, 0.1, "Jump"));
+
+        // Jump animation does not exist, so it is considered ended immediately.
+        let mut jump_to_walk = Transition::new("Jump->Walk", jump, walk, 0.1, "");
+        jump_to_walk.set_condition(LogicNode::IsAnimationEnded(Handle::NONE));
+        layer.add_transition(jump_to_walk);
+
+        let mut machine = Machine::new();
+        // The machine is created with an empty base layer, replace it with ours.
+        machine.layers_mut()[0] = layer;
+        machine
+            .set_parameter("Speed", Parameter::Weight(0.0))
+            .set_parameter("Jump", Parameter::Trigger(false));
+
+        let mut animations = AnimationContainer::new();
+
+        let mut evaluate = |machine: &mut Machine| {
+            machine.evaluate_pose(&mut animations, 0.1);
+            machine.layers()[0].active_state()
+        };
+
+        assert_eq!(evaluate(&mut machine), walk);
+
+        machine.set_parameter("Speed", Parameter::Weight(0.75));
+        assert_eq!(evaluate(&mut machine), run);
+        assert_eq!(evaluate(&mut machine), run);
+
+        machine.set_parameter("Speed", Parameter::Weight(0.25));
+        assert_eq!(evaluate(&mut machine), walk);
+
+        machine.set_parameter("Jump", Parameter::Trigger(true));
+        for _ in 0..10 {
+            evaluate(&mut machine);
+        }
+        assert_eq!(
+            machine.parameters().get("Jump"),
+            Some(&Parameter::Trigger(false))
+        );
+
+        let mut jump_count = 0;
+        while let Some(event) = machine.layers_mut()[0].pop_event() {
+            if event == Event::StateEnter(jump) {
+                jump_count += 1;
+            }
+        }
+        assert_eq!(jump_count, 1);
+        assert_eq!(machine.layers()[0].active_state(), walk);
+    }
+}
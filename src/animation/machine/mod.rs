@@ -59,11 +59,13 @@ pub mod transition;
 /// # Parameters
 ///
 /// Parameter is a named variable of a fixed type. Parameters are used as a data source in various places in the animation
-/// blending state machines. There are three main types of parameters:
+/// blending state machines. There are four main types of parameters:
 ///
 /// `Rule` - boolean value that used as a trigger for transitions. When transition is using some rule, it checks the value
 /// of the parameter and if it is `true` transition starts.
-/// `Weight` - real number (`f32`) that is used a weight when you blending multiple animations into one.
+/// `Trigger` - same as `Rule`, but it is automatically reset to `false` when a transition that uses it starts.
+/// `Weight` - real number (`f32`) that is used a weight when you blending multiple animations into one. It can also
+/// be compared with a constant in a transition condition (see [`transition::CompareNode`]).
 /// `Index` - natural number (`i32`) that is used as an animation selector.
 ///
 /// Each parameter has a name, it could be pretty much any string.
@@ -295,7 +297,7 @@ impl Machine {
 
         for layer in self.layers.iter_mut() {
             let weight = layer.weight();
-            let pose = layer.evaluate_pose(animations, &self.parameters, dt);
+            let pose = layer.evaluate_pose(animations, &mut self.parameters, dt);
 
             self.final_pose.blend_with(pose, weight);
         }
@@ -24,6 +24,11 @@ pub enum Parameter {
 
     /// A sampling point. Usually it is used together with BlendSpace nodes.
     SamplingPoint(Vector2<f32>),
+
+    /// Trigger parameter is a one-shot rule. It is used to check whether a transition from a state to state is
+    /// possible, just like [`Parameter::Rule`], but it is automatically reset to `false` when a transition that
+    /// uses it is activated. It is useful for one-shot actions, such as jumps.
+    Trigger(bool),
 }
 
 impl Default for Parameter {
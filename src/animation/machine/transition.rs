@@ -260,6 +260,56 @@ impl Reflect for NotNode {
     }
 }
 
+/// A comparison operator that is used in [`CompareNode`].
+#[derive(
+    Copy,
+    Clone,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    Visit,
+    Reflect,
+    AsRefStr,
+    EnumString,
+    EnumVariantNames,
+)]
+pub enum ComparisonOperator {
+    /// `parameter < value`
+    #[default]
+    Less,
+    /// `parameter <= value`
+    LessOrEqual,
+    /// `parameter > value`
+    Greater,
+    /// `parameter >= value`
+    GreaterOrEqual,
+}
+
+impl ComparisonOperator {
+    /// Compares two values using the operator.
+    pub fn compare(self, lhs: f32, rhs: f32) -> bool {
+        match self {
+            ComparisonOperator::Less => lhs < rhs,
+            ComparisonOperator::LessOrEqual => lhs <= rhs,
+            ComparisonOperator::Greater => lhs > rhs,
+            ComparisonOperator::GreaterOrEqual => lhs >= rhs,
+        }
+    }
+}
+
+/// Compares a value of `Weight` parameter with a constant. Output value will be `false` if the parameter is not found
+/// or it is not a `Weight` parameter.
+#[derive(Debug, Default, Clone, PartialEq, Visit, Reflect)]
+pub struct CompareNode {
+    /// A name of `Weight` parameter.
+    pub parameter: String,
+    /// An operator that will be used to compare the parameter with the value.
+    pub operator: ComparisonOperator,
+    /// A constant to compare the parameter with.
+    pub value: f32,
+}
+
 /// A node responsible for logical operations evaluation. It can have any number of descendant nodes.
 ///
 /// # Examples
@@ -287,7 +337,7 @@ impl Reflect for NotNode {
 /// ```
 #[derive(Debug, Visit, Clone, Reflect, PartialEq, AsRefStr, EnumString, EnumVariantNames)]
 pub enum LogicNode {
-    /// Fetches a value of `Rule` parameter and returns its value. `false` if the parameter is not found.
+    /// Fetches a value of `Rule` or `Trigger` parameter and returns its value. `false` if the parameter is not found.
     Parameter(String),
     /// Calculates logical AND between two arguments. Output value will be `true` iff both of the arguments is `true`.
     And(AndNode),
@@ -299,6 +349,8 @@ pub enum LogicNode {
     Not(NotNode),
     /// Returns `true` if the animation has ended, `false` - otherwise.
     IsAnimationEnded(Handle<Animation>),
+    /// Compares a value of `Weight` parameter with a constant.
+    Compare(CompareNode),
 }
 
 impl Default for LogicNode {
@@ -315,13 +367,12 @@ impl LogicNode {
         animations: &AnimationContainer,
     ) -> bool {
         match self {
-            LogicNode::Parameter(rule_name) => parameters.get(rule_name).map_or(false, |p| {
-                if let Parameter::Rule(rule_value) = p {
-                    *rule_value
-                } else {
-                    false
-                }
-            }),
+            LogicNode::Parameter(rule_name) => {
+                parameters.get(rule_name).map_or(false, |p| match p {
+                    Parameter::Rule(rule_value) | Parameter::Trigger(rule_value) => *rule_value,
+                    _ => false,
+                })
+            }
             LogicNode::And(and) => {
                 let lhs_value = and.lhs.calculate_value(parameters, animations);
                 let rhs_value = and.rhs.calculate_value(parameters, animations);
@@ -341,6 +392,32 @@ impl LogicNode {
             LogicNode::IsAnimationEnded(animation) => animations
                 .try_get(*animation)
                 .map_or(true, |a| a.has_ended()),
+            LogicNode::Compare(compare) => parameters.get(&compare.parameter).map_or(false, |p| {
+                if let Parameter::Weight(weight) = p {
+                    compare.operator.compare(*weight, compare.value)
+                } else {
+                    false
+                }
+            }),
+        }
+    }
+
+    /// Resets every `Trigger` parameter used by the logic node (and its descendants) to `false`.
+    pub fn consume_triggers(&self, parameters: &mut ParameterContainer) {
+        match self {
+            LogicNode::Parameter(name) => {
+                if let Some(Parameter::Trigger(value)) = parameters.get_mut(name) {
+                    *value = false;
+                }
+            }
+            LogicNode::And(AndNode { lhs, rhs })
+            | LogicNode::Or(OrNode { lhs, rhs })
+            | LogicNode::Xor(XorNode { lhs, rhs }) => {
+                lhs.consume_triggers(parameters);
+                rhs.consume_triggers(parameters);
+            }
+            LogicNode::Not(node) => node.lhs.consume_triggers(parameters),
+            LogicNode::IsAnimationEnded(_) | LogicNode::Compare(_) => (),
         }
     }
 }
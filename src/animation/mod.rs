@@ -6,7 +6,7 @@
 use crate::{
     animation::track::Track,
     core::{
-        algebra::{UnitQuaternion, Vector3},
+        algebra::{Quaternion, UnitQuaternion, Vector3},
         math::wrapf,
         pool::{Handle, Pool, Ticket},
         reflect::prelude::*,
@@ -221,6 +221,9 @@ pub struct RootMotionSettings {
     pub ignore_z_movement: bool,
     /// Keeps rotational part of the motion.
     pub ignore_rotations: bool,
+    /// Extracts only rotation around Y axis (yaw), pitch and roll are kept in the pose.
+    #[visit(optional)]
+    pub yaw_only: bool,
 }
 
 /// Motion of a root node of an hierarchy of nodes. It contains relative rotation and translation in local
@@ -232,9 +235,6 @@ pub struct RootMotion {
     pub delta_position: Vector3<f32>,
     /// Relative rotation between current and a previous frame of an animation.
     pub delta_rotation: UnitQuaternion<f32>,
-
-    prev_position: Vector3<f32>,
-    prev_rotation: UnitQuaternion<f32>,
 }
 
 impl RootMotion {
@@ -245,6 +245,18 @@ impl RootMotion {
     }
 }
 
+// Returns rotation around Y axis of the given rotation (twist part of swing-twist decomposition).
+fn yaw_part(rotation: &UnitQuaternion<f32>) -> UnitQuaternion<f32> {
+    let q = rotation.quaternion();
+    let length = (q.w * q.w + q.j * q.j).sqrt();
+    if length <= f32::EPSILON {
+        // Rotation by 180 degrees around some horizontal axis, the yaw is undefined.
+        UnitQuaternion::identity()
+    } else {
+        UnitQuaternion::new_unchecked(Quaternion::new(q.w / length, 0.0, q.j / length, 0.0))
+    }
+}
+
 impl NameProvider for Animation {
     fn name(&self) -> &str {
         &self.name
@@ -398,10 +410,10 @@ impl Animation {
     }
 
     fn update_root_motion(&mut self, prev_time_position: f32) {
-        fn fetch_position_at_time(tracks: &[Track], time: f32) -> Vector3<f32> {
+        fn fetch_position_at_time(tracks: &[Track], node: Handle<Node>, time: f32) -> Vector3<f32> {
             tracks
                 .iter()
-                .find(|track| track.binding() == &ValueBinding::Position)
+                .find(|track| track.target() == node && track.binding() == &ValueBinding::Position)
                 .and_then(|track| track.fetch(time))
                 .and_then(|value| {
                     if let TrackValue::Vector3(position) = value.value {
@@ -413,10 +425,14 @@ impl Animation {
                 .unwrap_or_default()
         }
 
-        fn fetch_rotation_at_time(tracks: &[Track], time: f32) -> UnitQuaternion<f32> {
+        fn fetch_rotation_at_time(
+            tracks: &[Track],
+            node: Handle<Node>,
+            time: f32,
+        ) -> UnitQuaternion<f32> {
             tracks
                 .iter()
-                .find(|track| track.binding() == &ValueBinding::Rotation)
+                .find(|track| track.target() == node && track.binding() == &ValueBinding::Rotation)
                 .and_then(|track| track.fetch(time))
                 .and_then(|value| {
                     if let TrackValue::UnitQuaternion(rotation) = value.value {
@@ -431,7 +447,7 @@ impl Animation {
         // If we have root motion enabled, try to extract the actual motion values. We'll take only relative motion
         // here, relative to the previous values.
         if let Some(root_motion_settings) = self.root_motion_settings.as_ref() {
-            let prev_root_motion = self.root_motion.clone().unwrap_or_default();
+            let node = root_motion_settings.node;
 
             // Check if we've started another loop cycle.
             let new_loop_cycle_started = self.looped
@@ -450,48 +466,80 @@ impl Animation {
                 self.time_slice.start
             };
 
-            let mut root_motion = RootMotion::default();
-            if let Some(root_pose) = self.pose.poses_mut().get_mut(&root_motion_settings.node) {
-                for bound_value in root_pose.values.values.iter_mut() {
-                    match bound_value.binding {
-                        ValueBinding::Position => {
-                            if let TrackValue::Vector3(pose_position) = bound_value.value {
-                                let delta = if new_loop_cycle_started {
-                                    root_motion.prev_position =
-                                        fetch_position_at_time(&self.tracks, cycle_start_time);
-
-                                    let end_value =
-                                        fetch_position_at_time(&self.tracks, cycle_end_time);
+            // The motion is sampled directly from the tracks, so the part of the motion after the
+            // loop seam is not lost.
+            let prev_position = fetch_position_at_time(&self.tracks, node, prev_time_position);
+            let current_position = fetch_position_at_time(&self.tracks, node, self.time_position);
+            let delta = if new_loop_cycle_started {
+                (fetch_position_at_time(&self.tracks, node, cycle_end_time) - prev_position)
+                    + (current_position
+                        - fetch_position_at_time(&self.tracks, node, cycle_start_time))
+            } else {
+                current_position - prev_position
+            };
 
-                                    end_value - prev_root_motion.prev_position
-                                } else {
-                                    root_motion.prev_position = pose_position;
-                                    pose_position - prev_root_motion.prev_position
-                                };
+            let mut root_motion = RootMotion {
+                delta_position: Vector3::new(
+                    if root_motion_settings.ignore_x_movement {
+                        0.0
+                    } else {
+                        delta.x
+                    },
+                    if root_motion_settings.ignore_y_movement {
+                        0.0
+                    } else {
+                        delta.y
+                    },
+                    if root_motion_settings.ignore_z_movement {
+                        0.0
+                    } else {
+                        delta.z
+                    },
+                ),
+                delta_rotation: Default::default(),
+            };
 
-                                root_motion.delta_position.x =
-                                    if root_motion_settings.ignore_x_movement {
-                                        0.0
-                                    } else {
-                                        delta.x
-                                    };
-                                root_motion.delta_position.y =
-                                    if root_motion_settings.ignore_y_movement {
-                                        0.0
-                                    } else {
-                                        delta.y
-                                    };
-                                root_motion.delta_position.z =
-                                    if root_motion_settings.ignore_z_movement {
-                                        0.0
-                                    } else {
-                                        delta.z
-                                    };
+            if !root_motion_settings.ignore_rotations {
+                let extract = |rotation: UnitQuaternion<f32>| {
+                    if root_motion_settings.yaw_only {
+                        yaw_part(&rotation)
+                    } else {
+                        rotation
+                    }
+                };
+
+                let prev_rotation = extract(fetch_rotation_at_time(
+                    &self.tracks,
+                    node,
+                    prev_time_position,
+                ));
+                let current_rotation = extract(fetch_rotation_at_time(
+                    &self.tracks,
+                    node,
+                    self.time_position,
+                ));
+                root_motion.delta_rotation = if new_loop_cycle_started {
+                    prev_rotation.inverse()
+                        * extract(fetch_rotation_at_time(&self.tracks, node, cycle_end_time))
+                        * extract(fetch_rotation_at_time(&self.tracks, node, cycle_start_time))
+                            .inverse()
+                        * current_rotation
+                } else {
+                    prev_rotation.inverse() * current_rotation
+                };
+            }
 
-                                // Reset position so the root won't move.
-                                let start_position =
-                                    fetch_position_at_time(&self.tracks, self.time_slice.start);
+            // Reset the extracted part of the pose, so the root won't move.
+            if let Some(root_pose) = self.pose.poses_mut().get_mut(&node) {
+                let start_position =
+                    fetch_position_at_time(&self.tracks, node, self.time_slice.start);
+                let start_rotation =
+                    fetch_rotation_at_time(&self.tracks, node, self.time_slice.start);
 
+                for bound_value in root_pose.values.values.iter_mut() {
+                    match bound_value.binding {
+                        ValueBinding::Position => {
+                            if let TrackValue::Vector3(pose_position) = bound_value.value {
                                 bound_value.value = TrackValue::Vector3(Vector3::new(
                                     if root_motion_settings.ignore_x_movement {
                                         pose_position.x
@@ -514,26 +562,15 @@ impl Animation {
                         ValueBinding::Rotation => {
                             if let TrackValue::UnitQuaternion(pose_rotation) = bound_value.value {
                                 if !root_motion_settings.ignore_rotations {
-                                    if new_loop_cycle_started {
-                                        root_motion.prev_rotation =
-                                            fetch_rotation_at_time(&self.tracks, cycle_start_time);
-
-                                        let end_value =
-                                            fetch_rotation_at_time(&self.tracks, cycle_end_time);
-
-                                        root_motion.delta_rotation =
-                                            prev_root_motion.prev_rotation.inverse() * end_value;
-                                    } else {
-                                        // Compute relative rotation that can be used to "turn" a node later on.
-                                        root_motion.delta_rotation =
-                                            prev_root_motion.prev_rotation.inverse()
-                                                * pose_rotation;
-                                        root_motion.prev_rotation = pose_rotation;
-                                    }
-
-                                    // Reset rotation so the root won't rotate.
                                     bound_value.value = TrackValue::UnitQuaternion(
-                                        fetch_rotation_at_time(&self.tracks, self.time_slice.start),
+                                        if root_motion_settings.yaw_only {
+                                            // Keep pitch and roll, replace the yaw with the initial one.
+                                            yaw_part(&start_rotation)
+                                                * yaw_part(&pose_rotation).inverse()
+                                                * pose_rotation
+                                        } else {
+                                            start_rotation
+                                        },
                                     );
                                 }
                             }
@@ -542,6 +579,7 @@ impl Animation {
                     }
                 }
             }
+
             self.root_motion = Some(root_motion);
         }
     }
@@ -939,6 +977,30 @@ impl AnimationContainer {
         self.pool.retain(pred)
     }
 
+    /// Removes every animation from the container that does not satisfy the given predicate, that
+    /// receives both a handle and a mutable reference to each animation. Handles of the retained
+    /// animations stay valid.
+    #[inline]
+    pub fn retain_pairs<P>(&mut self, pred: P)
+    where
+        P: FnMut(Handle<Animation>, &mut Animation) -> bool,
+    {
+        self.pool.retain_pairs(pred)
+    }
+
+    /// Creates an iterator, that removes every animation that satisfies the given predicate from the
+    /// container and yields it together with its handle. Handles of the remaining animations stay valid.
+    #[inline]
+    pub fn drain_filter<'a, P>(
+        &'a mut self,
+        pred: P,
+    ) -> impl Iterator<Item = (Handle<Animation>, Animation)> + 'a
+    where
+        P: FnMut(Handle<Animation>, &mut Animation) -> bool + 'a,
+    {
+        self.pool.drain_filter(pred)
+    }
+
     /// Updates all animations in the container and applies their poses to respective nodes. This method is intended to
     /// be used only by the internals of the engine!
     pub fn update_animations(&mut self, nodes: &mut NodePool, apply: bool, dt: f32) {
@@ -992,3 +1054,88 @@ impl IndexMut<Handle<Animation>> for AnimationContainer {
         &mut self.pool[index]
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        animation::{
+            container::{TrackDataContainer, TrackValueKind},
+            track::Track,
+            value::{TrackValue, ValueBinding},
+            Animation, RootMotionSettings,
+        },
+        core::{
+            algebra::{UnitQuaternion, Vector3},
+            curve::{Curve, CurveKey, CurveKeyKind},
+            pool::Handle,
+        },
+    };
+
+    #[test]
+    fn test_root_motion_loop() {
+        let root = Handle::new(1, 1);
+
+        // The root moves 1 unit along X axis and turns by 0.5 radians per loop, while keeping
+        // its pitch.
+        let mut position = TrackDataContainer::new(TrackValueKind::Vector3);
+        position.curves_mut()[0] = Curve::from(vec![
+            CurveKey::new(0.0, 0.0, CurveKeyKind::Linear),
+            CurveKey::new(1.0, 1.0, CurveKeyKind::Linear),
+        ]);
+        let mut position_track = Track::new(position, ValueBinding::Position);
+        position_track.set_target(root);
+
+        let mut rotation = TrackDataContainer::new(TrackValueKind::UnitQuaternion);
+        rotation.curves_mut()[0] = Curve::from(vec![
+            CurveKey::new(0.0, 0.3, CurveKeyKind::Linear),
+            CurveKey::new(1.0, 0.3, CurveKeyKind::Linear),
+        ]);
+        rotation.curves_mut()[1] = Curve::from(vec![
+            CurveKey::new(0.0, 0.0, CurveKeyKind::Linear),
+            CurveKey::new(1.0, 0.5, CurveKeyKind::Linear),
+        ]);
+        let mut rotation_track = Track::new(rotation, ValueBinding::Rotation);
+        rotation_track.set_target(root);
+
+        let mut animation = Animation::default();
+        animation.add_track(position_track);
+        animation.add_track(rotation_track);
+        animation.set_time_slice(0.0..1.0);
+        animation.set_root_motion_settings(Some(RootMotionSettings {
+            node: root,
+            yaw_only: true,
+            ..Default::default()
+        }));
+
+        let mut total_position = Vector3::default();
+        let mut total_rotation = UnitQuaternion::identity();
+        for _ in 0..35 {
+            animation.tick(0.1);
+
+            let root_motion = animation.root_motion().unwrap();
+            // No pop at the loop seam.
+            assert!((root_motion.delta_position.x - 0.1).abs() < 1.0e-4);
+            total_position += root_motion.delta_position;
+            total_rotation *= root_motion.delta_rotation;
+
+            // The root stays at its initial location, only pitch is kept in the pose.
+            for value in animation.pose().poses()[&root].values.values.iter() {
+                match value.value {
+                    TrackValue::Vector3(position) => assert!(position.norm() < 1.0e-5),
+                    TrackValue::UnitQuaternion(rotation) => assert!(
+                        rotation
+                            .angle_to(&UnitQuaternion::from_axis_angle(&Vector3::x_axis(), 0.3))
+                            < 1.0e-4
+                    ),
+                    _ => unreachable!(),
+                }
+            }
+        }
+
+        assert!((total_position - Vector3::new(3.5, 0.0, 0.0)).norm() < 1.0e-3);
+        assert!(
+            total_rotation.angle_to(&UnitQuaternion::from_axis_angle(&Vector3::y_axis(), 1.75))
+                < 1.0e-3
+        );
+    }
+}
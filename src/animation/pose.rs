@@ -78,9 +78,12 @@ impl AnimationPose {
             }
         }
 
-        self.root_motion
-            .get_or_insert_with(Default::default)
-            .blend_with(&other.root_motion.clone().unwrap_or_default(), weight);
+        // Root motion is blended the same way as node poses - missing motion becomes a copy.
+        if let Some(current_root_motion) = self.root_motion.as_mut() {
+            current_root_motion.blend_with(&other.root_motion.clone().unwrap_or_default(), weight);
+        } else {
+            self.root_motion = other.root_motion.clone();
+        }
     }
 
     fn add_node_pose(&mut self, local_pose: NodePose) {
@@ -106,6 +109,7 @@ impl AnimationPose {
     /// Clears the pose.
     pub fn reset(&mut self) {
         self.poses.clear();
+        self.root_motion = None;
     }
 
     /// Returns a reference to inner node pose map.
@@ -123,18 +127,23 @@ impl AnimationPose {
             if node.is_none() {
                 Log::writeln(MessageKind::Error, "Invalid node handle found for animation pose, most likely it means that animation retargeting failed!");
             } else if let Some(node) = nodes.try_borrow_mut(*node) {
-                local_pose.values.apply(node);
+                if node.is_globally_enabled() {
+                    local_pose.values.apply(node);
+                }
             }
         }
     }
 
     /// Tries to set each value to the each property from the animation pose to respective scene nodes.
+    /// Disabled nodes (see [`crate::scene::base::Base::set_enabled`]) are skipped.
     pub fn apply(&self, graph: &mut Graph) {
         for (node, local_pose) in self.poses.iter() {
             if node.is_none() {
                 Log::writeln(MessageKind::Error, "Invalid node handle found for animation pose, most likely it means that animation retargeting failed!");
             } else if let Some(node) = graph.try_get_mut(*node) {
-                local_pose.values.apply(node);
+                if node.is_globally_enabled() {
+                    local_pose.values.apply(node);
+                }
             }
         }
     }
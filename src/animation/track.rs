@@ -3,7 +3,7 @@
 use crate::{
     animation::{
         container::{TrackDataContainer, TrackValueKind},
-        value::{BoundValue, ValueBinding},
+        value::{BoundValue, ValueBinding, ValueType},
     },
     core::{pool::Handle, reflect::prelude::*, uuid::Uuid, visitor::prelude::*},
     scene::node::Node,
@@ -87,6 +87,20 @@ impl Track {
         }
     }
 
+    /// Creates a new track that is responsible in animating a weight of a blend shape with the given
+    /// index of a mesh (see [`crate::scene::mesh::Mesh::blend_shapes`]). Weights are in `0..100`
+    /// range.
+    pub fn new_blend_shape_weight(index: usize) -> Self {
+        Self {
+            frames: TrackDataContainer::new(TrackValueKind::Real),
+            binding: ValueBinding::Property {
+                name: format!("blend_shapes[{}].weight", index),
+                value_type: ValueType::F32,
+            },
+            ..Default::default()
+        }
+    }
+
     /// Sets target of the track.
     pub fn with_target(mut self, target: Handle<Node>) -> Self {
         self.target = target;
@@ -0,0 +1,98 @@
+//! Clipboard integration for WebAssembly. On other platforms the user interface uses the system
+//! clipboard directly, see [`crate::gui::UserInterface::clipboard_mut`].
+//!
+//! Browsers allow to access the clipboard only asynchronously, so the clipboard cannot be read at
+//! the moment when `Ctrl+V` is pressed. Instead, the engine listens to `paste` events of the canvas
+//! (the browser fires them right after `Ctrl+V`, without any permission prompts) and passes the
+//! pasted text to the widget with keyboard focus on the next update using
+//! [`crate::gui::text_box::TextBoxMessage::Paste`] message.
+
+use crate::{
+    core::{
+        js_sys,
+        log::Log,
+        wasm_bindgen::{closure::Closure, JsCast, JsValue},
+        wasm_bindgen_futures::{spawn_local, JsFuture},
+        web_sys,
+    },
+    gui::copypasta::ClipboardProvider,
+};
+use std::{cell::RefCell, error::Error, rc::Rc};
+
+type ClipboardResult<T> = Result<T, Box<dyn Error + Send + Sync + 'static>>;
+
+/// Clipboard of a web page. Writing is performed using asynchronous Clipboard API, which is available
+/// only in secure contexts (HTTPS or localhost), some browsers also require the page to be focused
+/// or a recent user interaction (such as the key press of `Ctrl+C`). Reading is not supported, the
+/// content of the clipboard is delivered by `paste` events, see module docs for more info.
+#[derive(Default)]
+pub struct WebClipboard;
+
+fn js_error(context: &str, value: JsValue) -> Box<dyn Error + Send + Sync + 'static> {
+    format!("{context}: {value:?}").into()
+}
+
+impl ClipboardProvider for WebClipboard {
+    fn get_contents(&mut self) -> ClipboardResult<String> {
+        Err("The clipboard can be read only by paste events on the web!".into())
+    }
+
+    fn set_contents(&mut self, text: String) -> ClipboardResult<()> {
+        let navigator = web_sys::window().ok_or("There is no window!")?.navigator();
+        let clipboard = js_sys::Reflect::get(&navigator, &JsValue::from_str("clipboard"))
+            .map_err(|e| js_error("Unable to get the clipboard", e))?;
+        if clipboard.is_undefined() {
+            return Err("Clipboard API is not available, it requires a secure context!".into());
+        }
+        let write_text = js_sys::Reflect::get(&clipboard, &JsValue::from_str("writeText"))
+            .map_err(|e| js_error("Unable to get writeText function", e))?
+            .dyn_into::<js_sys::Function>()
+            .map_err(|e| js_error("writeText is not a function", e))?;
+        let promise = write_text
+            .call1(&clipboard, &JsValue::from_str(&text))
+            .map_err(|e| js_error("Unable to write the clipboard", e))?
+            .dyn_into::<js_sys::Promise>()
+            .map_err(|e| js_error("writeText did not return a promise", e))?;
+
+        // The permission could be denied by the browser, the only thing that can be done is to
+        // report it.
+        spawn_local(async move {
+            if let Err(err) = JsFuture::from(promise).await {
+                Log::warn(format!("Unable to write the clipboard: {err:?}"));
+            }
+        });
+
+        Ok(())
+    }
+}
+
+/// A queue of texts, that were pasted in the canvas, but not yet passed to the user interface.
+#[derive(Clone, Default)]
+pub(crate) struct PasteQueue(Rc<RefCell<Vec<String>>>);
+
+impl PasteQueue {
+    /// Subscribes to `paste` events of the given canvas.
+    pub(crate) fn listen(&self, canvas: &web_sys::HtmlCanvasElement) {
+        let queue = self.0.clone();
+        let on_paste = Closure::wrap(Box::new(move |event: web_sys::ClipboardEvent| {
+            if let Some(text) = event
+                .clipboard_data()
+                .and_then(|data| data.get_data("text").ok())
+            {
+                if !text.is_empty() {
+                    queue.borrow_mut().push(text);
+                }
+            }
+            event.prevent_default();
+        }) as Box<dyn FnMut(_)>);
+        Log::verify(
+            canvas.add_event_listener_with_callback("paste", on_paste.as_ref().unchecked_ref()),
+        );
+        on_paste.forget();
+    }
+
+    /// Takes every pasted text from the queue.
+    pub(crate) fn take(&self) -> Vec<String> {
+        std::mem::take(&mut *self.0.borrow_mut())
+    }
+}
@@ -0,0 +1,93 @@
+//! Game clock tracks real and scaled (game) time. See [`GameClock`] docs for more info.
+
+/// Game clock accumulates real time and scaled time. Scaled time advances `time_scale` times faster
+/// than real time, so it could be used for slow motion (`0.0 < time_scale < 1.0`), fast forward
+/// (`time_scale > 1.0`) or pause (`time_scale == 0.0`). The engine owns an instance of the clock,
+/// see [`super::Engine::update_real`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct GameClock {
+    elapsed_real: f64,
+    elapsed_scaled: f64,
+    time_scale: f32,
+}
+
+impl Default for GameClock {
+    fn default() -> Self {
+        Self {
+            elapsed_real: 0.0,
+            elapsed_scaled: 0.0,
+            time_scale: 1.0,
+        }
+    }
+}
+
+impl GameClock {
+    /// Creates new clock with zero elapsed time and time scale of `1.0`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Advances the clock by the given amount of real time (in seconds) and returns scaled time
+    /// delta.
+    pub fn advance(&mut self, real_dt: f32) -> f32 {
+        let scaled_dt = real_dt * self.time_scale;
+        self.elapsed_real += real_dt as f64;
+        self.elapsed_scaled += scaled_dt as f64;
+        scaled_dt
+    }
+
+    /// Sets new time scale. Negative values are clamped to zero.
+    pub fn set_time_scale(&mut self, time_scale: f32) {
+        self.time_scale = time_scale.max(0.0);
+    }
+
+    /// Returns current time scale.
+    pub fn time_scale(&self) -> f32 {
+        self.time_scale
+    }
+
+    /// Returns amount of real time (in seconds) that was passed to the clock.
+    pub fn elapsed_real(&self) -> f64 {
+        self.elapsed_real
+    }
+
+    /// Returns amount of scaled time (in seconds), that passed since creation of the clock.
+    pub fn elapsed_scaled(&self) -> f64 {
+        self.elapsed_scaled
+    }
+
+    /// Resets elapsed time, time scale is left unchanged.
+    pub fn reset(&mut self) {
+        self.elapsed_real = 0.0;
+        self.elapsed_scaled = 0.0;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::engine::clock::GameClock;
+
+    #[test]
+    fn test_time_scale() {
+        let mut clock = GameClock::new();
+
+        assert_eq!(clock.advance(1.0), 1.0);
+
+        clock.set_time_scale(0.5);
+        assert_eq!(clock.advance(1.0), 0.5);
+
+        // Pause.
+        clock.set_time_scale(0.0);
+        assert_eq!(clock.advance(1.0), 0.0);
+
+        clock.set_time_scale(-1.0);
+        assert_eq!(clock.time_scale(), 0.0);
+
+        assert_eq!(clock.elapsed_real(), 3.0);
+        assert_eq!(clock.elapsed_scaled(), 1.5);
+
+        clock.reset();
+        assert_eq!(clock.elapsed_real(), 0.0);
+        assert_eq!(clock.elapsed_scaled(), 0.0);
+    }
+}
@@ -11,6 +11,8 @@ pub enum EngineError {
     Sound(SoundError),
     /// Rendering system error.
     Renderer(FrameworkError),
+    /// Requested feature is not supported on the current platform.
+    Unsupported(String),
     /// Internal error.
     Custom(String),
 }
@@ -20,6 +22,9 @@ impl Display for EngineError {
         match self {
             EngineError::Sound(v) => Display::fmt(v, f),
             EngineError::Renderer(v) => Display::fmt(v, f),
+            EngineError::Unsupported(v) => {
+                write!(f, "Unsupported: {v}")
+            }
             EngineError::Custom(v) => {
                 write!(f, "Custom error: {v}")
             }
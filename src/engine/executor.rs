@@ -13,7 +13,6 @@ use crate::{
     event_loop::{ControlFlow, EventLoop},
     plugin::PluginConstructor,
     scene::loader::AsyncSceneLoader,
-    utils::translate_event,
 };
 use clap::Parser;
 use std::{
@@ -217,7 +216,12 @@ impl Executor {
                     lag += elapsed.as_secs_f32();
 
                     while lag >= fixed_time_step {
-                        engine.update(fixed_time_step, control_flow, &mut lag, Default::default());
+                        engine.update_real(
+                            fixed_time_step,
+                            control_flow,
+                            &mut lag,
+                            Default::default(),
+                        );
                         lag -= fixed_time_step;
                     }
 
@@ -234,7 +238,12 @@ impl Executor {
 
                     engine.render().unwrap();
                 }
-                Event::WindowEvent { event, .. } => {
+                Event::WindowEvent { event, window_id } => {
+                    // Events of secondary windows are handled separately.
+                    if engine.handle_secondary_window_event(window_id, &event) {
+                        return;
+                    }
+
                     match event {
                         WindowEvent::CloseRequested => *control_flow = ControlFlow::Exit,
                         WindowEvent::Resized(size) => {
@@ -248,9 +257,7 @@ impl Executor {
                         _ => (),
                     }
 
-                    if let Some(os_event) = translate_event(&event) {
-                        engine.user_interface.process_os_event(&os_event);
-                    }
+                    engine.process_window_event(&event);
                 }
                 _ => *control_flow = ControlFlow::Poll,
             }
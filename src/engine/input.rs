@@ -0,0 +1,178 @@
+//! Input snapshot is a simple polled state of the mouse, that is maintained by the engine. See
+//! [`InputSnapshot`] docs for more info.
+
+use crate::{
+    core::algebra::Vector2,
+    event::{ElementState, MouseButton, MouseScrollDelta, WindowEvent},
+};
+use fxhash::FxHashSet;
+
+/// Input snapshot holds the state of the mouse of the main window: cursor position, pressed buttons
+/// and wheel rotation. It allows to poll the state without handling OS events manually. The engine
+/// updates the snapshot in [`super::Engine::process_window_event`], the snapshot could be accessed
+/// by [`super::Engine::input`] or by [`crate::plugin::PluginContext::input`].
+///
+/// Per-frame values (wheel rotation) are accumulated between frames and become visible at the
+/// beginning of the next [`super::Engine::update`], so every update sees the values of the last
+/// frame.
+#[derive(Clone, Debug)]
+pub struct InputSnapshot {
+    cursor_position: Vector2<f32>,
+    scale_factor: f64,
+    cursor_inside: bool,
+    mouse_buttons: FxHashSet<MouseButton>,
+    wheel_delta: Vector2<f32>,
+    pending_wheel_delta: Vector2<f32>,
+}
+
+impl Default for InputSnapshot {
+    fn default() -> Self {
+        Self {
+            cursor_position: Default::default(),
+            scale_factor: 1.0,
+            cursor_inside: false,
+            mouse_buttons: Default::default(),
+            wheel_delta: Default::default(),
+            pending_wheel_delta: Default::default(),
+        }
+    }
+}
+
+impl InputSnapshot {
+    /// Creates new empty snapshot.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Updates the snapshot using the given window event.
+    pub fn process_window_event(&mut self, event: &WindowEvent) {
+        match event {
+            WindowEvent::CursorMoved { position, .. } => {
+                self.cursor_position = Vector2::new(position.x as f32, position.y as f32);
+                self.cursor_inside = true;
+            }
+            WindowEvent::CursorEntered { .. } => self.cursor_inside = true,
+            WindowEvent::CursorLeft { .. } => self.cursor_inside = false,
+            WindowEvent::MouseInput { state, button, .. } => {
+                if *state == ElementState::Pressed {
+                    self.mouse_buttons.insert(*button);
+                } else {
+                    self.mouse_buttons.remove(button);
+                }
+            }
+            WindowEvent::MouseWheel { delta, .. } => match delta {
+                MouseScrollDelta::LineDelta(x, y) => {
+                    self.pending_wheel_delta += Vector2::new(*x, *y)
+                }
+                MouseScrollDelta::PixelDelta(pos) => {
+                    self.pending_wheel_delta += Vector2::new(pos.x as f32, pos.y as f32)
+                }
+            },
+            WindowEvent::ScaleFactorChanged { scale_factor, .. } => {
+                self.scale_factor = *scale_factor;
+            }
+            // Release buttons, otherwise buttons that were released while the window was out of
+            // focus will be stuck.
+            WindowEvent::Focused(false) => self.mouse_buttons.clear(),
+            _ => (),
+        }
+    }
+
+    /// Makes values accumulated since the previous call visible and resets the accumulators. The
+    /// engine calls this method at the beginning of [`super::Engine::update`], there's no need to
+    /// call it manually.
+    pub fn begin_frame(&mut self) {
+        self.wheel_delta = std::mem::take(&mut self.pending_wheel_delta);
+    }
+
+    /// Sets scale factor of the window, it is used to calculate logical cursor position.
+    pub fn set_scale_factor(&mut self, scale_factor: f64) {
+        self.scale_factor = scale_factor;
+    }
+
+    /// Returns scale factor of the window.
+    pub fn scale_factor(&self) -> f64 {
+        self.scale_factor
+    }
+
+    /// Returns cursor position in physical pixels relative to the top-left corner of the window.
+    /// The user interface works in physical pixels too, so this position could be compared with
+    /// screen positions of widgets.
+    pub fn cursor_position(&self) -> Vector2<f32> {
+        self.cursor_position
+    }
+
+    /// Returns cursor position in logical pixels (physical position divided by the scale factor of
+    /// the window).
+    pub fn cursor_position_logical(&self) -> Vector2<f32> {
+        self.cursor_position.scale(1.0 / self.scale_factor as f32)
+    }
+
+    /// Returns `true` if the cursor is inside the window.
+    pub fn is_cursor_inside(&self) -> bool {
+        self.cursor_inside
+    }
+
+    /// Returns `true` if the mouse button is pressed.
+    pub fn is_mouse_button_pressed(&self, button: MouseButton) -> bool {
+        self.mouse_buttons.contains(&button)
+    }
+
+    /// Returns wheel rotation of the last frame. `y` is vertical rotation, `x` is horizontal
+    /// rotation (supported only by some devices).
+    pub fn wheel_delta(&self) -> Vector2<f32> {
+        self.wheel_delta
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::algebra::Vector2,
+        dpi::PhysicalPosition,
+        engine::input::InputSnapshot,
+        event::{DeviceId, ElementState, MouseButton, MouseScrollDelta, TouchPhase, WindowEvent},
+    };
+
+    #[test]
+    fn test_input_snapshot() {
+        let device_id = unsafe { DeviceId::dummy() };
+        let mut input = InputSnapshot::new();
+        input.set_scale_factor(2.0);
+
+        input.process_window_event(&WindowEvent::CursorMoved {
+            device_id,
+            position: PhysicalPosition::new(100.0, 50.0),
+        });
+        assert!(input.is_cursor_inside());
+        assert_eq!(input.cursor_position(), Vector2::new(100.0, 50.0));
+        assert_eq!(input.cursor_position_logical(), Vector2::new(50.0, 25.0));
+
+        input.process_window_event(&WindowEvent::MouseInput {
+            device_id,
+            state: ElementState::Pressed,
+            button: MouseButton::Left,
+        });
+        assert!(input.is_mouse_button_pressed(MouseButton::Left));
+
+        for _ in 0..2 {
+            input.process_window_event(&WindowEvent::MouseWheel {
+                device_id,
+                delta: MouseScrollDelta::LineDelta(0.0, 1.0),
+                phase: TouchPhase::Moved,
+            });
+        }
+        // Accumulated values become visible at the beginning of the next frame.
+        assert_eq!(input.wheel_delta(), Vector2::default());
+        input.begin_frame();
+        assert_eq!(input.wheel_delta(), Vector2::new(0.0, 2.0));
+        input.begin_frame();
+        assert_eq!(input.wheel_delta(), Vector2::default());
+
+        input.process_window_event(&WindowEvent::Focused(false));
+        assert!(!input.is_mouse_button_pressed(MouseButton::Left));
+
+        input.process_window_event(&WindowEvent::CursorLeft { device_id });
+        assert!(!input.is_cursor_inside());
+    }
+}
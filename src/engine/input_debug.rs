@@ -0,0 +1,262 @@
+//! Input debugger records OS events, that were passed to the main user interface, together with
+//! the widget that was picked at the moment of the event. It is useful to find out why some widget
+//! does not receive input. See [`InputDebugger`] docs for more info.
+
+use crate::{
+    core::{algebra::Vector2, color::Color, math::Rect, pool::Handle},
+    gui::{
+        brush::Brush,
+        draw::{CommandTexture, Draw},
+        formatted_text::FormattedTextBuilder,
+        message::{ButtonState, KeyCode, OsEvent},
+        UiNode, UserInterface,
+    },
+};
+use std::collections::VecDeque;
+
+/// Default amount of entries that the input debugger keeps.
+pub const DEFAULT_INPUT_DEBUG_CAPACITY: usize = 64;
+
+/// A single recorded OS event.
+#[derive(Debug, Clone, PartialEq)]
+pub struct InputDebugEntry {
+    /// Index of the frame at which the event was received.
+    pub frame: u64,
+    /// Translated OS event.
+    pub event: OsEvent,
+    /// A widget that was picked (under the cursor or captured) after the event was processed.
+    pub picked: Handle<UiNode>,
+}
+
+/// Input debugger keeps last N OS events (see [`InputDebugger::set_capacity`]) in a ring buffer and
+/// optionally draws an overlay on top of the user interface: a crosshair at the cursor position, a
+/// rectangle around the picked widget and the handle and the name of the widget. The debugger is
+/// disabled by default and does nothing in this state, it could be enabled either from code or by
+/// pressing the toggle key (see [`InputDebugger::set_toggle_key`]).
+pub struct InputDebugger {
+    enabled: bool,
+    overlay: bool,
+    toggle_key: Option<KeyCode>,
+    capacity: usize,
+    frame: u64,
+    entries: VecDeque<InputDebugEntry>,
+}
+
+impl Default for InputDebugger {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            overlay: true,
+            toggle_key: None,
+            capacity: DEFAULT_INPUT_DEBUG_CAPACITY,
+            frame: 0,
+            entries: Default::default(),
+        }
+    }
+}
+
+impl InputDebugger {
+    /// Returns `true` if the debugger records events, `false` - otherwise.
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Enables or disables the debugger. Recorded entries are removed when the debugger is disabled.
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+        if !enabled {
+            self.entries = Default::default();
+        }
+    }
+
+    /// Switches the debugger on or off.
+    pub fn toggle(&mut self) {
+        self.set_enabled(!self.enabled);
+    }
+
+    /// Returns `true` if the overlay is drawn when the debugger is enabled.
+    pub fn is_overlay_enabled(&self) -> bool {
+        self.overlay
+    }
+
+    /// Defines whether the overlay should be drawn when the debugger is enabled. Default is `true`.
+    pub fn set_overlay_enabled(&mut self, overlay: bool) {
+        self.overlay = overlay;
+    }
+
+    /// Returns current toggle key.
+    pub fn toggle_key(&self) -> Option<KeyCode> {
+        self.toggle_key
+    }
+
+    /// Sets a key, that switches the debugger on or off when pressed. `None` disables the toggle
+    /// key, which is default.
+    pub fn set_toggle_key(&mut self, key: Option<KeyCode>) {
+        self.toggle_key = key;
+    }
+
+    /// Returns maximum amount of entries that the debugger keeps.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Sets maximum amount of entries that the debugger keeps, the oldest entries are removed
+    /// when the limit is exceeded.
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity;
+        while self.entries.len() > capacity {
+            self.entries.pop_front();
+        }
+    }
+
+    /// Returns recorded entries, the oldest ones go first.
+    pub fn entries(&self) -> &VecDeque<InputDebugEntry> {
+        &self.entries
+    }
+
+    /// Removes every recorded entry.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /// Returns index of current frame.
+    pub fn frame(&self) -> u64 {
+        self.frame
+    }
+
+    pub(crate) fn next_frame(&mut self) {
+        self.frame += 1;
+    }
+
+    pub(crate) fn record(&mut self, event: &OsEvent, ui: &UserInterface) {
+        if let (
+            Some(toggle_key),
+            OsEvent::KeyboardInput {
+                button,
+                state: ButtonState::Pressed,
+                ..
+            },
+        ) = (self.toggle_key, event)
+        {
+            if *button == toggle_key {
+                self.toggle();
+                return;
+            }
+        }
+
+        if !self.enabled || self.capacity == 0 {
+            return;
+        }
+
+        if self.entries.len() >= self.capacity {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(InputDebugEntry {
+            frame: self.frame,
+            event: event.clone(),
+            picked: ui.picked_node(),
+        });
+    }
+
+    pub(crate) fn draw_overlay(&self, ui: &mut UserInterface) {
+        if !self.enabled || !self.overlay {
+            return;
+        }
+
+        let screen_size = ui.screen_size();
+        let clip_bounds = Rect::new(0.0, 0.0, screen_size.x, screen_size.y);
+        let cursor = ui.cursor_position();
+        let picked = ui.picked_node();
+        let picked_info = ui
+            .try_get_node(picked)
+            .map(|node| (node.screen_bounds(), format!("{} {}", picked, node.name())));
+        let font = ui.default_font.clone();
+
+        let ctx = ui.get_drawing_context_mut();
+
+        let half_size = 8.0;
+        ctx.push_line(
+            Vector2::new(cursor.x - half_size, cursor.y),
+            Vector2::new(cursor.x + half_size, cursor.y),
+            1.0,
+        );
+        ctx.push_line(
+            Vector2::new(cursor.x, cursor.y - half_size),
+            Vector2::new(cursor.x, cursor.y + half_size),
+            1.0,
+        );
+        ctx.commit(
+            clip_bounds,
+            Brush::Solid(Color::RED),
+            CommandTexture::None,
+            None,
+        );
+
+        if let Some((bounds, text)) = picked_info {
+            ctx.push_rect(&bounds, 1.0);
+            ctx.commit(
+                clip_bounds,
+                Brush::Solid(Color::opaque(255, 255, 0)),
+                CommandTexture::None,
+                None,
+            );
+
+            let mut formatted_text = FormattedTextBuilder::new(font)
+                .with_text(text)
+                .with_constraint(screen_size)
+                .with_shadow(true)
+                .build();
+            formatted_text.build();
+            ctx.draw_text(
+                clip_bounds,
+                cursor + Vector2::new(half_size, half_size),
+                &formatted_text,
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::algebra::Vector2,
+        engine::input_debug::InputDebugger,
+        gui::{
+            message::{ButtonState, KeyCode, OsEvent},
+            UserInterface,
+        },
+    };
+
+    #[test]
+    fn test_input_debugger_ring_buffer() {
+        let ui = UserInterface::new(Vector2::new(100.0, 100.0));
+        let mut debugger = InputDebugger::default();
+        let event = OsEvent::MouseWheel(0.0, 1.0);
+
+        // Disabled debugger records nothing.
+        debugger.record(&event, &ui);
+        assert!(debugger.entries().is_empty());
+
+        debugger.set_enabled(true);
+        debugger.set_capacity(2);
+        for _ in 0..3 {
+            debugger.record(&event, &ui);
+            debugger.next_frame();
+        }
+        assert_eq!(debugger.entries().len(), 2);
+        assert_eq!(debugger.entries()[0].frame, 1);
+        assert_eq!(debugger.entries()[1].frame, 2);
+
+        debugger.set_toggle_key(Some(KeyCode::F12));
+        debugger.record(
+            &OsEvent::KeyboardInput {
+                button: KeyCode::F12,
+                state: ButtonState::Pressed,
+                text: Default::default(),
+            },
+            &ui,
+        );
+        assert!(!debugger.is_enabled());
+        assert!(debugger.entries().is_empty());
+    }
+}
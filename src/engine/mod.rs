@@ -3,8 +3,13 @@
 
 #![warn(missing_docs)]
 
+#[cfg(target_arch = "wasm32")]
+pub mod clipboard;
+pub mod clock;
 pub mod error;
 pub mod executor;
+pub mod input;
+pub mod input_debug;
 
 use crate::scene::camera::SkyBoxKind;
 use crate::{
@@ -13,24 +18,39 @@ use crate::{
         manager::{ResourceManager, ResourceWaitContext},
         ResourceStateRef,
     },
-    core::{algebra::Vector2, futures::executor::block_on, instant, log::Log, pool::Handle},
-    engine::error::EngineError,
-    event::Event,
+    core::{
+        algebra::{Matrix4, UnitQuaternion, Vector2},
+        color::Color,
+        futures::executor::block_on,
+        instant,
+        log::Log,
+        math::Matrix4Ext,
+        pool::Handle,
+    },
+    engine::{
+        clock::GameClock, error::EngineError, input::InputSnapshot, input_debug::InputDebugger,
+    },
+    event::{Event, WindowEvent},
     event_loop::ControlFlow,
-    gui::UserInterface,
+    gui::{copypasta::ClipboardProvider, message::OsEvent, UserInterface},
     material::shader::{loader::ShaderLoader, Shader, ShaderResource, ShaderResourceExtension},
     plugin::{Plugin, PluginConstructor, PluginContext, PluginRegistrationContext},
-    renderer::{framework::error::FrameworkError, framework::state::GlKind, Renderer},
+    renderer::{framework::error::FrameworkError, framework::state::GlKind, Renderer, SceneView},
     resource::{
         curve::{loader::CurveLoader, CurveResourceState},
         model::{loader::ModelLoader, Model, ModelResource},
-        texture::{loader::TextureLoader, Texture, TextureKind},
+        texture::{
+            loader::TextureLoader, Texture, TextureKind, TexturePixelKind, TextureResource,
+            TextureResourceExtension,
+        },
     },
     scene::{
-        base::NodeScriptMessage,
+        base::{BaseBuilder, NodeScriptMessage},
+        camera::{Camera, CameraBuilder},
         graph::GraphUpdateSwitches,
         node::{constructor::NodeConstructorContainer, Node},
         sound::SoundEngine,
+        transform::TransformBuilder,
         Scene, SceneContainer,
     },
     script::{
@@ -38,26 +58,29 @@ use crate::{
         ScriptDeinitContext, ScriptMessage, ScriptMessageContext, ScriptMessageKind,
         ScriptMessageSender,
     },
-    window::{Window, WindowBuilder},
+    utils::{component::ComponentConstructorContainer, translate_event},
+    window::{CursorGrabMode, Window, WindowBuilder, WindowId},
 };
 use fxhash::{FxHashMap, FxHashSet};
 use fyrox_sound::buffer::{loader::SoundBufferLoader, SoundBuffer};
 #[cfg(not(target_arch = "wasm32"))]
 use glutin::{
-    config::ConfigTemplateBuilder,
+    config::{Config, ConfigTemplateBuilder},
     context::{
-        ContextApi, ContextAttributesBuilder, GlProfile, NotCurrentGlContextSurfaceAccessor,
-        PossiblyCurrentContext, Version,
+        ContextApi, ContextAttributesBuilder, GlProfile, NotCurrentContext,
+        NotCurrentGlContextSurfaceAccessor, PossiblyCurrentContext,
+        PossiblyCurrentContextGlSurfaceAccessor, Robustness, Version,
     },
     display::{GetGlDisplay, GlDisplay},
     surface::{GlSurface, Surface, SwapInterval, WindowSurface},
 };
 #[cfg(not(target_arch = "wasm32"))]
-use glutin_winit::{DisplayBuilder, GlWindow};
+use glutin_winit::{finalize_window, DisplayBuilder, GlWindow};
 #[cfg(not(target_arch = "wasm32"))]
-use raw_window_handle::HasRawWindowHandle;
+use raw_window_handle::{HasRawWindowHandle, RawWindowHandle};
 use std::{
     any::TypeId,
+    cell::RefMut,
     collections::{HashSet, VecDeque},
     fmt::{Display, Formatter},
     ops::Deref,
@@ -71,6 +94,7 @@ use std::{
 use std::{ffi::CString, num::NonZeroU32};
 use winit::{
     dpi::{Position, Size},
+    error::ExternalError,
     event_loop::EventLoopWindowTarget,
     window::WindowAttributes,
 };
@@ -82,6 +106,9 @@ pub struct SerializationContext {
     pub node_constructors: NodeConstructorContainer,
     /// A script constructor container.
     pub script_constructors: ScriptConstructorContainer,
+    /// A component constructor container. See
+    /// [`crate::utils::component::ComponentContainer`] docs for more info.
+    pub component_constructors: ComponentConstructorContainer,
 }
 
 impl Default for SerializationContext {
@@ -96,6 +123,7 @@ impl SerializationContext {
         Self {
             node_constructors: NodeConstructorContainer::new(),
             script_constructors: ScriptConstructorContainer::new(),
+            component_constructors: ComponentConstructorContainer::new(),
         }
     }
 }
@@ -111,31 +139,293 @@ pub struct PerformanceStatistics {
 
     /// Amount of time spent in plugins updating.
     pub plugins_time: Duration,
+
+    /// Actual amount of time between the ends of two last rendered frames, including the time spent
+    /// waiting by the frame rate limiter (see [`Engine::set_frame_rate_limit`]).
+    pub frame_time: Duration,
 }
 
 impl Display for PerformanceStatistics {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         writeln!(
             f,
-            "Performance Statistics:\n\tUI: {:?}\n\tScripts: {:?}\n\tPlugins: {:?}",
-            self.ui_time, self.scripts_time, self.plugins_time
+            "Performance Statistics:\n\tUI: {:?}\n\tScripts: {:?}\n\tPlugins: {:?}\n\tFrame: {:?}",
+            self.ui_time, self.scripts_time, self.plugins_time, self.frame_time
         )
     }
 }
 
-/// An initialized graphics context. It contains the main application window and the renderer instance.
-pub struct InitializedGraphicsContext {
-    /// Main application window.
+/// Limits frame rate by waiting at the end of each frame until the desired frame time is reached.
+#[derive(Default)]
+struct FrameRateLimiter {
+    limit: Option<u32>,
+    last_frame_end: Option<instant::Instant>,
+}
+
+impl FrameRateLimiter {
+    // Sleep is not precise (its granularity could be a few milliseconds, depending on OS), so the
+    // last part of the wait is done by spinning.
+    #[cfg(not(target_arch = "wasm32"))]
+    const SPIN_TIME: Duration = Duration::from_millis(2);
+
+    /// Waits until the end of the current frame (if there is a limit) and returns actual frame time.
+    fn end_frame(&mut self) -> Duration {
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            if let (Some(limit), Some(last_frame_end)) = (self.limit, self.last_frame_end) {
+                let deadline = last_frame_end + Duration::from_secs_f64(1.0 / limit as f64);
+                let now = instant::Instant::now();
+                if deadline > now {
+                    let remaining = deadline - now;
+                    if remaining > Self::SPIN_TIME {
+                        std::thread::sleep(remaining - Self::SPIN_TIME);
+                    }
+                    while instant::Instant::now() < deadline {
+                        std::hint::spin_loop();
+                    }
+                }
+            }
+        }
+
+        let now = instant::Instant::now();
+        let frame_time = self
+            .last_frame_end
+            .map(|last_frame_end| now - last_frame_end)
+            .unwrap_or_default();
+        self.last_frame_end = Some(now);
+        frame_time
+    }
+}
+
+/// A secondary window of the engine, created by [`Engine::create_window`]. Secondary windows are
+/// rendered using the same OpenGL context as the main window, so every GPU resource (textures,
+/// geometry buffers, shaders, etc.) is shared between all the windows. Only per-scene render data
+/// (G-Buffer, HDR frame buffers, etc.) is created separately for each window, because it depends
+/// on the frame size.
+///
+/// Each window could show a scene through a specific camera (see [`SecondaryWindow::scene_view`])
+/// and/or its own user interface (see [`SecondaryWindow::user_interface`]), which is drawn on top
+/// of the scene view.
+pub struct SecondaryWindow {
+    /// The window itself.
     pub window: Window,
 
+    /// A scene and a camera, that will be rendered in the window. Use `None` to show the user
+    /// interface only.
+    pub scene_view: Option<SceneView>,
+
+    /// An optional user interface of the window. It receives input events of the window only. Messages
+    /// of the user interface are passed to [`Plugin::on_secondary_window_ui_message`].
+    pub user_interface: Option<UserInterface>,
+
+    #[cfg(not(target_arch = "wasm32"))]
+    gl_surface: Surface<WindowSurface>,
+}
+
+impl SecondaryWindow {
+    fn frame_size(&self) -> Vector2<f32> {
+        let inner_size = self.window.inner_size();
+        Vector2::new(inner_size.width as f32, inner_size.height as f32)
+    }
+}
+
+/// An initialized graphics context. It contains the main application window and the renderer instance.
+pub struct InitializedGraphicsContext {
     /// Current renderer.
     pub renderer: Renderer,
 
     params: GraphicsContextParams,
+    secondary_windows: FxHashMap<WindowId, SecondaryWindow>,
+    #[cfg(not(target_arch = "wasm32"))]
+    gl_config: Config,
     #[cfg(not(target_arch = "wasm32"))]
     gl_context: PossiblyCurrentContext,
     #[cfg(not(target_arch = "wasm32"))]
     gl_surface: Surface<WindowSurface>,
+    #[cfg(target_arch = "wasm32")]
+    webgl2_context: crate::core::web_sys::WebGl2RenderingContext,
+    // Whether the loss of the context was already reported and the context must be restored.
+    context_lost: bool,
+
+    /// Main application window. Fields are dropped in declaration order, the window must outlive
+    /// the renderer and the graphics context.
+    pub window: Window,
+}
+
+impl Drop for InitializedGraphicsContext {
+    fn drop(&mut self) {
+        // GPU objects must be destroyed while the context is current, otherwise some drivers crash.
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Err(err) = self.gl_context.make_current(&self.gl_surface) {
+            Log::warn(format!(
+                "Unable to make graphics context current before destruction: {:?}",
+                err
+            ));
+        }
+
+        self.renderer.flush();
+    }
+}
+
+impl InitializedGraphicsContext {
+    /// Returns a reference to a secondary window with the given id.
+    pub fn secondary_window(&self, id: WindowId) -> Option<&SecondaryWindow> {
+        self.secondary_windows.get(&id)
+    }
+
+    /// Returns a mutable reference to a secondary window with the given id.
+    pub fn secondary_window_mut(&mut self, id: WindowId) -> Option<&mut SecondaryWindow> {
+        self.secondary_windows.get_mut(&id)
+    }
+
+    /// Returns an iterator over every secondary window.
+    pub fn secondary_windows(&self) -> impl Iterator<Item = (WindowId, &SecondaryWindow)> {
+        self.secondary_windows
+            .iter()
+            .map(|(id, window)| (*id, window))
+    }
+
+    /// Tries to restore lost context, returns `false` if the context cannot be restored yet.
+    fn restore_context(&mut self, resource_manager: &ResourceManager) -> Result<bool, EngineError> {
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            // Lost context cannot be used anymore, so a new one is created for the same surfaces.
+            let (non_current_gl_context, gl_kind) =
+                create_gl_context(&self.gl_config, self.window.raw_window_handle())?;
+
+            let gl_context = non_current_gl_context.make_current(&self.gl_surface)?;
+
+            for window in self.secondary_windows.values() {
+                gl_context.make_current(&window.gl_surface)?;
+                Log::verify(
+                    window
+                        .gl_surface
+                        .set_swap_interval(&gl_context, SwapInterval::DontWait),
+                );
+            }
+            gl_context.make_current(&self.gl_surface)?;
+
+            if self.params.vsync {
+                Log::verify(self.gl_surface.set_swap_interval(
+                    &gl_context,
+                    SwapInterval::Wait(NonZeroU32::new(1).unwrap()),
+                ));
+            }
+
+            let glow_context = unsafe { create_glow_context(&self.gl_config) };
+            self.renderer
+                .restore_context(glow_context, resource_manager, gl_kind)?;
+
+            // Previous context must be destroyed only after the renderer has released every object
+            // of it.
+            self.gl_context = gl_context;
+
+            Ok(true)
+        }
+
+        #[cfg(target_arch = "wasm32")]
+        {
+            // The browser restores the same context and then every object must be created again.
+            if self.webgl2_context.is_context_lost() {
+                return Ok(false);
+            }
+
+            self.renderer.restore_context(
+                glow::Context::from_webgl2_context(self.webgl2_context.clone()),
+                resource_manager,
+                GlKind::OpenGLES,
+            )?;
+
+            Ok(true)
+        }
+    }
+}
+
+/// Creates OpenGL 3.3 Core context, or OpenGL ES 3.0 context if the former is not supported. Robust
+/// context is preferred, because it reports driver resets as context loss.
+#[cfg(not(target_arch = "wasm32"))]
+fn create_gl_context(
+    gl_config: &Config,
+    raw_window_handle: RawWindowHandle,
+) -> Result<(NotCurrentContext, GlKind), EngineError> {
+    let gl_display = gl_config.display();
+
+    let mut last_error = None;
+    for (context_api, gl_kind) in [
+        (ContextApi::OpenGl(Some(Version::new(3, 3))), GlKind::OpenGL),
+        (ContextApi::Gles(Some(Version::new(3, 0))), GlKind::OpenGLES),
+    ] {
+        for robustness in [Robustness::RobustLoseContextOnReset, Robustness::NotRobust] {
+            let context_attributes = ContextAttributesBuilder::new()
+                .with_profile(GlProfile::Core)
+                .with_context_api(context_api)
+                .with_robustness(robustness)
+                .build(Some(raw_window_handle));
+
+            match unsafe { gl_display.create_context(gl_config, &context_attributes) } {
+                Ok(context) => return Ok((context, gl_kind)),
+                Err(error) => last_error = Some(error),
+            }
+        }
+    }
+
+    Err(last_error.map_or_else(
+        || EngineError::Custom("Unable to create graphics context!".to_string()),
+        EngineError::from,
+    ))
+}
+
+/// Loads GL functions for a context, that is current at the moment of the call.
+#[cfg(not(target_arch = "wasm32"))]
+unsafe fn create_glow_context(gl_config: &Config) -> glow::Context {
+    let gl_display = gl_config.display();
+    glow::Context::from_loader_function(|s| gl_display.get_proc_address(&CString::new(s).unwrap()))
+}
+
+/// Maximum amount of time that [`Engine::shutdown`] waits for in-flight resource loading tasks.
+pub const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// An event produced by the engine itself. See [`Plugin::on_engine_event`] and
+/// [`Engine::poll_event`] to find out how to handle such events.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum EngineEvent {
+    /// Graphics context was lost, for example because of a GPU driver reset or because a browser
+    /// released WebGL context of a background tab. Nothing is rendered until the context is restored,
+    /// scenes and user interface are kept intact.
+    GraphicsContextLost,
+
+    /// Graphics context was restored and the renderer has re-created its GPU objects. Custom render
+    /// passes (see [`Renderer::add_render_pass`]) must re-create their GPU objects as well.
+    GraphicsContextRestored,
+}
+
+/// Defines how the user interfaces of the engine are scaled, see [`Engine::set_ui_scaling_mode`].
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+pub enum UiScaling {
+    /// No scaling, one logical unit of the user interface is one physical pixel. The user interface
+    /// looks smaller on displays with high pixel density.
+    Physical,
+
+    /// The user interface is scaled using the scale factor of its window (see HiDPI docs of `winit`
+    /// for more info), so it has the same apparent size on displays with any pixel density. This
+    /// is the default mode.
+    #[default]
+    ScaleFactor,
+
+    /// The user interface is scaled using the given scale factor, regardless of the scale factor of
+    /// its window.
+    Custom(f32),
+}
+
+impl UiScaling {
+    /// Returns scale factor of a user interface for a window with the given scale factor.
+    pub fn scale_factor(self, window_scale_factor: f64) -> f32 {
+        match self {
+            UiScaling::Physical => 1.0,
+            UiScaling::ScaleFactor => window_scale_factor as f32,
+            UiScaling::Custom(scale_factor) => scale_factor,
+        }
+    }
 }
 
 /// Graphics context of the engine, it could be in two main states:
@@ -196,7 +486,8 @@ pub struct Engine {
     /// Current resource manager. Resource manager can be cloned (it does clone only ref) to be able to
     /// use resource manager from any thread, this is useful to load resources from multiple
     /// threads to decrease loading times of your game by utilizing all available power of
-    /// your CPU.
+    /// your CPU. Amount of loader threads can be limited by
+    /// [`ResourceManager::set_loader_thread_count`].
     pub resource_manager: ResourceManager,
 
     /// All available scenes in the engine.
@@ -204,6 +495,8 @@ pub struct Engine {
 
     performance_statistics: PerformanceStatistics,
 
+    frame_rate_limiter: FrameRateLimiter,
+
     model_events_receiver: Receiver<ResourceEvent>,
 
     #[allow(dead_code)] // Keep engine instance alive.
@@ -220,6 +513,36 @@ pub struct Engine {
     // Amount of time (in seconds) that passed from creation of the engine.
     elapsed_time: f32,
 
+    // Whether scene updates are paused, see [`Engine::pause`].
+    paused: bool,
+
+    // Time step of a single update requested by [`Engine::step_once`].
+    pending_step: Option<f32>,
+
+    // Events that will be passed to plugins on the next update.
+    engine_events: Vec<EngineEvent>,
+
+    // Events that are waiting to be read by [`Engine::poll_event`].
+    polled_events: VecDeque<EngineEvent>,
+
+    // Real and scaled time, see [`Engine::update_real`].
+    clock: GameClock,
+
+    input_debugger: InputDebugger,
+
+    input: InputSnapshot,
+
+    // Color of the main window where nothing is rendered, it is kept here to survive re-creation
+    // of the graphics context.
+    clear_color: Color,
+
+    // Defines how the user interfaces are scaled, it is applied to every window.
+    ui_scaling: UiScaling,
+
+    // Texts pasted in the canvas, that will be passed to the user interface on the next update.
+    #[cfg(target_arch = "wasm32")]
+    paste_queue: clipboard::PasteQueue,
+
     /// A special container that is able to create nodes by their type UUID. Use a copy of this
     /// value whenever you need it as a parameter in other parts of the engine.
     pub serialization_context: Arc<SerializationContext>,
@@ -440,10 +763,12 @@ impl ScriptProcessor {
             let scene = &mut scenes[scripted_scene.handle];
 
             // Disabled scenes should not update their scripts.
-            if !scene.enabled {
+            if !scene.is_update_enabled() {
                 continue 'scene_loop;
             }
 
+            let dt = scene.scaled_dt(dt);
+
             // Fill in initial handles to nodes to update.
             let mut update_queue = VecDeque::new();
             for (handle, node) in scene.graph.pair_iter() {
@@ -717,7 +1042,8 @@ pub struct EngineInitParams {
     pub graphics_context_params: GraphicsContextParams,
     /// A special container that is able to create nodes by their type UUID.
     pub serialization_context: Arc<SerializationContext>,
-    /// A resource manager.
+    /// A resource manager. Use [`ResourceManager::set_loader_thread_count`] before passing it here, if
+    /// you need to limit amount of threads that are used to load resources.
     pub resource_manager: ResourceManager,
 }
 
@@ -886,20 +1212,37 @@ impl Engine {
 
         let sound_engine = SoundEngine::without_device();
 
+        #[allow(unused_mut)]
+        let mut user_interface = UserInterface::new(Vector2::new(100.0, 100.0));
+        #[cfg(target_arch = "wasm32")]
+        user_interface.set_clipboard(Box::new(clipboard::WebClipboard));
+
         Ok(Self {
             graphics_context: GraphicsContext::Uninitialized(graphics_context_params),
             model_events_receiver: tx,
             resource_manager,
             scenes: SceneContainer::new(sound_engine.clone()),
             sound_engine,
-            user_interface: UserInterface::new(Vector2::new(100.0, 100.0)),
+            user_interface,
             performance_statistics: Default::default(),
+            frame_rate_limiter: Default::default(),
             plugins: Default::default(),
             serialization_context,
             script_processor: Default::default(),
             plugins_enabled: false,
             plugin_constructors: Default::default(),
             elapsed_time: 0.0,
+            paused: false,
+            pending_step: None,
+            engine_events: Default::default(),
+            polled_events: Default::default(),
+            clock: Default::default(),
+            input_debugger: Default::default(),
+            input: Default::default(),
+            clear_color: Color::BLACK,
+            ui_scaling: Default::default(),
+            #[cfg(target_arch = "wasm32")]
+            paste_queue: Default::default(),
         })
     }
 
@@ -951,7 +1294,7 @@ impl Engine {
                 .with_active(params.window_attributes.active);
 
             #[cfg(not(target_arch = "wasm32"))]
-            let (window, gl_context, gl_surface, glow_context, gl_kind) = {
+            let (window, gl_config, gl_context, gl_surface, glow_context, gl_kind) = {
                 let template = ConfigTemplateBuilder::new()
                     .prefer_hardware_accelerated(Some(true))
                     .with_stencil_size(8)
@@ -965,20 +1308,6 @@ impl Engine {
 
                 let window = opt_window.unwrap();
 
-                let raw_window_handle = window.raw_window_handle();
-
-                let gl_display = gl_config.display();
-
-                let gl3_3_core_context_attributes = ContextAttributesBuilder::new()
-                    .with_profile(GlProfile::Core)
-                    .with_context_api(ContextApi::OpenGl(Some(Version::new(3, 3))))
-                    .build(Some(raw_window_handle));
-
-                let gles3_context_attributes = ContextAttributesBuilder::new()
-                    .with_profile(GlProfile::Core)
-                    .with_context_api(ContextApi::Gles(Some(Version::new(3, 0))))
-                    .build(Some(raw_window_handle));
-
                 unsafe {
                     let attrs = window.build_surface_attributes(Default::default());
 
@@ -986,16 +1315,8 @@ impl Engine {
                         .display()
                         .create_window_surface(&gl_config, &attrs)?;
 
-                    let (non_current_gl_context, gl_kind) = if let Ok(gl3_3_core_context) =
-                        gl_display.create_context(&gl_config, &gl3_3_core_context_attributes)
-                    {
-                        (gl3_3_core_context, GlKind::OpenGL)
-                    } else {
-                        (
-                            gl_display.create_context(&gl_config, &gles3_context_attributes)?,
-                            GlKind::OpenGLES,
-                        )
-                    };
+                    let (non_current_gl_context, gl_kind) =
+                        create_gl_context(&gl_config, window.raw_window_handle())?;
 
                     let gl_context = non_current_gl_context.make_current(&gl_surface)?;
 
@@ -1006,20 +1327,21 @@ impl Engine {
                         ));
                     }
 
+                    let glow_context = create_glow_context(&gl_config);
+
                     (
                         window,
+                        gl_config,
                         gl_context,
                         gl_surface,
-                        glow::Context::from_loader_function(|s| {
-                            gl_display.get_proc_address(&CString::new(s).unwrap())
-                        }),
+                        glow_context,
                         gl_kind,
                     )
                 }
             };
 
             #[cfg(target_arch = "wasm32")]
-            let (window, glow_context, gl_kind) = {
+            let (window, webgl2_context, glow_context, gl_kind) = {
                 let window = window_builder.build(window_target).unwrap();
 
                 use crate::core::wasm_bindgen::JsCast;
@@ -1033,14 +1355,32 @@ impl Engine {
                 body.append_child(&canvas)
                     .expect("Append canvas to HTML body");
 
+                self.paste_queue.listen(&canvas);
+
                 let webgl2_context = canvas
                     .get_context("webgl2")
                     .unwrap()
                     .unwrap()
                     .dyn_into::<crate::core::web_sys::WebGl2RenderingContext>()
                     .unwrap();
+
+                // The browser restores a lost context only if the default behavior of the event
+                // is prevented.
+                let on_context_lost = crate::core::wasm_bindgen::closure::Closure::wrap(Box::new(
+                    |event: crate::core::web_sys::Event| event.prevent_default(),
+                )
+                    as Box<dyn FnMut(_)>);
+                canvas
+                    .add_event_listener_with_callback(
+                        "webglcontextlost",
+                        on_context_lost.as_ref().unchecked_ref(),
+                    )
+                    .expect("Subscribe to context loss events");
+                on_context_lost.forget();
+
                 (
                     window,
+                    webgl2_context.clone(),
                     glow::Context::from_webgl2_context(webgl2_context),
                     GlKind::OpenGLES,
                 )
@@ -1050,6 +1390,9 @@ impl Engine {
                 window.inner_size().width as f32,
                 window.inner_size().height as f32,
             ));
+            self.input.set_scale_factor(window.scale_factor());
+            self.user_interface
+                .set_scale_factor(self.ui_scaling.scale_factor(window.scale_factor()));
 
             #[cfg(not(target_arch = "wasm32"))]
             gl_surface.resize(
@@ -1060,19 +1403,28 @@ impl Engine {
                     .unwrap_or_else(|| NonZeroU32::new(1).unwrap()),
             );
 
+            let mut renderer = Renderer::new(
+                glow_context,
+                (window.inner_size().width, window.inner_size().height),
+                &self.resource_manager,
+                gl_kind,
+            )?;
+            renderer.set_backbuffer_clear_color(self.clear_color);
+
             self.graphics_context = GraphicsContext::Initialized(InitializedGraphicsContext {
+                #[cfg(not(target_arch = "wasm32"))]
+                gl_config,
                 #[cfg(not(target_arch = "wasm32"))]
                 gl_context,
                 #[cfg(not(target_arch = "wasm32"))]
                 gl_surface,
-                renderer: Renderer::new(
-                    glow_context,
-                    (window.inner_size().width, window.inner_size().height),
-                    &self.resource_manager,
-                    gl_kind,
-                )?,
+                #[cfg(target_arch = "wasm32")]
+                webgl2_context,
+                context_lost: false,
+                renderer,
                 window,
                 params: params.clone(),
+                secondary_windows: Default::default(),
             });
 
             self.sound_engine.initialize_audio_output_device()?;
@@ -1130,6 +1482,68 @@ impl Engine {
         }
     }
 
+    /// Shuts the engine down and destroys its subsystems in a strict order:
+    ///
+    /// 1. All scenes are destroyed and the plugins are deinitialized.
+    /// 2. The resource manager stops accepting new loading tasks and waits until in-flight tasks are
+    ///    finished, see [`ResourceManager::shutdown`].
+    /// 3. The audio output device is destroyed and its mixer thread is stopped, then the sound
+    ///    engine releases every remaining sound context (including the ones, that were added manually).
+    /// 4. The renderer releases its GPU resources while the graphics context is still current, and
+    ///    only then the context and the main window are destroyed.
+    ///
+    /// The method consumes the engine, so nothing could use the destroyed subsystems afterwards.
+    /// Dropping the engine does the same, except the second step: the resource manager could be
+    /// shared with other users (for example, with another engine instance), so its loaders are left
+    /// intact. Errors are only logged on drop and the order of destruction of the values, that were
+    /// taken out of the engine (such as sound engine clones), is up to the user. The method returns
+    /// an error if resource loading tasks did not finish in [`SHUTDOWN_TIMEOUT`], such tasks are
+    /// detached.
+    pub fn shutdown(mut self) -> Result<(), EngineError> {
+        self.destroy_subsystems(true)
+    }
+
+    fn destroy_subsystems(&mut self, stop_resource_loaders: bool) -> Result<(), EngineError> {
+        // Destroy all scenes first and correctly destroy all script instances.
+        // This will ensure that any `on_destroy` logic will be executed before
+        // engine destroyed.
+        let scenes = self
+            .scenes
+            .pair_iter()
+            .map(|(h, _)| h)
+            .collect::<Vec<Handle<Scene>>>();
+
+        for handle in scenes {
+            self.scenes.remove(handle);
+        }
+
+        self.enable_plugins(Default::default(), false);
+
+        let loaders_stopped = if stop_resource_loaders {
+            self.resource_manager.shutdown(SHUTDOWN_TIMEOUT)
+        } else {
+            true
+        };
+
+        // The mixer thread must be stopped first, so it won't access the contexts while they're
+        // being destroyed.
+        self.sound_engine.destroy_audio_output_device();
+        self.sound_engine.state().remove_all_contexts();
+
+        // The renderer is flushed and destroyed before the window, see `InitializedGraphicsContext`.
+        if let GraphicsContext::Initialized(ref ctx) = self.graphics_context {
+            self.graphics_context = GraphicsContext::Uninitialized(ctx.params.clone());
+        }
+
+        if loaders_stopped {
+            Ok(())
+        } else {
+            Err(EngineError::Custom(
+                "Resource loading tasks did not finish in time!".to_string(),
+            ))
+        }
+    }
+
     /// Adjust size of the frame to be rendered. Must be called after the window size changes.
     /// Will update the renderer and GL context frame size.
     pub fn set_frame_size(&mut self, new_size: (u32, u32)) -> Result<(), FrameworkError> {
@@ -1147,6 +1561,144 @@ impl Engine {
         Ok(())
     }
 
+    /// Creates a new secondary window using the given window builder and returns its id. The window
+    /// shares the OpenGL context with the main window, which means that every GPU resource is shared
+    /// as well. Use [`InitializedGraphicsContext::secondary_window_mut`] to assign a scene view or a
+    /// user interface to the window. Secondary windows do not wait for vertical synchronization, so
+    /// vsync setting of the main window defines the frame rate of every window.
+    ///
+    /// The method will fail if the graphics context is not initialized. Secondary windows are destroyed
+    /// together with the graphics context (see [`Engine::destroy_graphics_context`]) and must be
+    /// re-created when a new context is initialized. Secondary windows are not supported on WebAssembly.
+    pub fn create_window(
+        &mut self,
+        window_target: &EventLoopWindowTarget<()>,
+        window_builder: WindowBuilder,
+    ) -> Result<WindowId, EngineError> {
+        let ctx = if let GraphicsContext::Initialized(ctx) = &mut self.graphics_context {
+            ctx
+        } else {
+            return Err(EngineError::Custom(
+                "Graphics context is not initialized!".to_string(),
+            ));
+        };
+
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            let window = finalize_window(window_target, window_builder, &ctx.gl_config)
+                .map_err(|e| EngineError::Custom(e.to_string()))?;
+
+            let attrs = window.build_surface_attributes(Default::default());
+            let gl_surface = unsafe {
+                ctx.gl_config
+                    .display()
+                    .create_window_surface(&ctx.gl_config, &attrs)?
+            };
+
+            ctx.gl_context.make_current(&gl_surface)?;
+            Log::verify(gl_surface.set_swap_interval(&ctx.gl_context, SwapInterval::DontWait));
+            ctx.gl_context.make_current(&ctx.gl_surface)?;
+
+            let id = window.id();
+
+            ctx.secondary_windows.insert(
+                id,
+                SecondaryWindow {
+                    window,
+                    scene_view: None,
+                    user_interface: None,
+                    gl_surface,
+                },
+            );
+
+            Ok(id)
+        }
+
+        #[cfg(target_arch = "wasm32")]
+        {
+            let _ = (ctx, window_target, window_builder);
+
+            Err(EngineError::Custom(
+                "Secondary windows are not supported on WebAssembly!".to_string(),
+            ))
+        }
+    }
+
+    /// Destroys a secondary window with the given id and frees every GPU resource associated with it.
+    /// The main window cannot be destroyed using this method, use [`Engine::destroy_graphics_context`]
+    /// instead.
+    pub fn destroy_window(&mut self, id: WindowId) -> Result<(), EngineError> {
+        let ctx = if let GraphicsContext::Initialized(ctx) = &mut self.graphics_context {
+            ctx
+        } else {
+            return Err(EngineError::Custom(
+                "Graphics context is not initialized!".to_string(),
+            ));
+        };
+
+        if ctx.secondary_windows.remove(&id).is_none() {
+            return Err(EngineError::Custom(format!(
+                "There is no secondary window with {id:?} id!"
+            )));
+        }
+
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            ctx.renderer.on_secondary_window_destroyed(id);
+            ctx.gl_context.make_current(&ctx.gl_surface)?;
+        }
+
+        Ok(())
+    }
+
+    /// Handles an event of a secondary window: resizes the window surface, destroys the window when it
+    /// was requested to close and passes input events to the user interface of the window. Returns
+    /// `false` if there's no secondary window with the given id (for example, if the event belongs to
+    /// the main window), `true` - otherwise.
+    pub fn handle_secondary_window_event(&mut self, id: WindowId, event: &WindowEvent) -> bool {
+        let ui_scaling = self.ui_scaling;
+        let ctx = if let GraphicsContext::Initialized(ctx) = &mut self.graphics_context {
+            ctx
+        } else {
+            return false;
+        };
+
+        let window = if let Some(window) = ctx.secondary_windows.get_mut(&id) {
+            window
+        } else {
+            return false;
+        };
+
+        match event {
+            WindowEvent::CloseRequested => {
+                Log::verify(self.destroy_window(id));
+                return true;
+            }
+            WindowEvent::ScaleFactorChanged { scale_factor, .. } => {
+                if let Some(user_interface) = window.user_interface.as_mut() {
+                    user_interface.set_scale_factor(ui_scaling.scale_factor(*scale_factor));
+                }
+            }
+            #[cfg(not(target_arch = "wasm32"))]
+            WindowEvent::Resized(size) => {
+                window.gl_surface.resize(
+                    &ctx.gl_context,
+                    NonZeroU32::new(size.width).unwrap_or_else(|| NonZeroU32::new(1).unwrap()),
+                    NonZeroU32::new(size.height).unwrap_or_else(|| NonZeroU32::new(1).unwrap()),
+                );
+            }
+            _ => (),
+        }
+
+        if let Some(user_interface) = window.user_interface.as_mut() {
+            if let Some(os_event) = translate_event(event) {
+                user_interface.process_os_event(&os_event);
+            }
+        }
+
+        true
+    }
+
     /// Amount of time (in seconds) that passed from creation of the engine. Keep in mind, that
     /// this value is **not** guaranteed to match real time. A user can change delta time with
     /// which the engine "ticks" and this delta time affects elapsed time.
@@ -1154,6 +1706,225 @@ impl Engine {
         self.elapsed_time
     }
 
+    /// Returns a reference to the engine clock, see [`GameClock`] docs for more info.
+    pub fn clock(&self) -> &GameClock {
+        &self.clock
+    }
+
+    /// Returns a reference to the engine clock, see [`GameClock`] docs for more info.
+    pub fn clock_mut(&mut self) -> &mut GameClock {
+        &mut self.clock
+    }
+
+    /// Sets new time scale of the engine clock. It affects time delta, that is passed to scenes,
+    /// scripts and plugins by [`Self::update_real`]. Use `0.0` to pause the game, values in `0.0..1.0`
+    /// range for slow motion.
+    pub fn set_time_scale(&mut self, time_scale: f32) {
+        self.clock.set_time_scale(time_scale);
+    }
+
+    /// Returns current time scale of the engine clock.
+    pub fn time_scale(&self) -> f32 {
+        self.clock.time_scale()
+    }
+
+    /// Amount of real time (in seconds) that was passed to [`Self::update_real`].
+    pub fn elapsed_real(&self) -> f64 {
+        self.clock.elapsed_real()
+    }
+
+    /// Amount of scaled time (in seconds) that was passed to scenes by [`Self::update_real`].
+    pub fn elapsed_scaled(&self) -> f64 {
+        self.clock.elapsed_scaled()
+    }
+
+    /// Pauses the simulation: [`Self::update`] will skip scene updates (including physics, animations,
+    /// etc.) and scripts, but user interface, plugins and rendering keep working, so the last frame
+    /// stays on the screen. Use [`Self::step_once`] to advance the simulation by a single tick while
+    /// paused.
+    pub fn pause(&mut self) {
+        self.paused = true;
+    }
+
+    /// Resumes the simulation, that was paused by [`Self::pause`].
+    pub fn resume(&mut self) {
+        self.paused = false;
+        self.pending_step = None;
+    }
+
+    /// Returns `true` if the simulation is paused, see [`Self::pause`].
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    /// Requests a single simulation tick with the given time delta. The tick is performed on the
+    /// next call of [`Self::update`] (or [`Self::pre_update`]), even if the simulation is paused.
+    /// Has no effect if the simulation is not paused.
+    pub fn step_once(&mut self, dt: f32) {
+        if self.paused {
+            self.pending_step = Some(dt);
+        }
+    }
+
+    /// Returns a reference to the input debugger, see [`InputDebugger`] docs for more info.
+    pub fn input_debugger(&self) -> &InputDebugger {
+        &self.input_debugger
+    }
+
+    /// Returns a reference to the input debugger, see [`InputDebugger`] docs for more info.
+    pub fn input_debugger_mut(&mut self) -> &mut InputDebugger {
+        &mut self.input_debugger
+    }
+
+    /// Returns a reference to the input snapshot of the main window, see [`InputSnapshot`] docs for
+    /// more info.
+    pub fn input(&self) -> &InputSnapshot {
+        &self.input
+    }
+
+    /// Grabs the cursor, so it can't leave the main window, or releases it if `mode` is
+    /// [`CursorGrabMode::None`]. Platforms support different grab modes: for example, macOS supports
+    /// only [`CursorGrabMode::Locked`], while Windows supports only [`CursorGrabMode::Confined`]. If
+    /// the requested mode is not supported, the other one is used instead, an error is returned only
+    /// if the platform supports none of them or if the graphics context is not initialized.
+    ///
+    /// For mouselook, grab the cursor, hide it (see [`Self::set_cursor_visible`]) and use raw mouse
+    /// motion events (`DeviceEvent::MouseMotion`) to rotate the camera.
+    pub fn set_cursor_grab(&mut self, mode: CursorGrabMode) -> Result<(), EngineError> {
+        let window = self.main_window()?;
+        let result = match window.set_cursor_grab(mode) {
+            Err(ExternalError::NotSupported(_)) if mode != CursorGrabMode::None => {
+                let fallback = if mode == CursorGrabMode::Locked {
+                    CursorGrabMode::Confined
+                } else {
+                    CursorGrabMode::Locked
+                };
+                window.set_cursor_grab(fallback)
+            }
+            result => result,
+        };
+        result.map_err(|err| match err {
+            ExternalError::NotSupported(_) => EngineError::Unsupported(format!(
+                "Cursor grab mode {mode:?} is not supported on this platform!"
+            )),
+            err => EngineError::Custom(format!("Unable to set cursor grab mode {mode:?}: {err}")),
+        })
+    }
+
+    /// Shows or hides the cursor when it is over the main window. Returns an error if the graphics
+    /// context is not initialized.
+    pub fn set_cursor_visible(&mut self, visible: bool) -> Result<(), EngineError> {
+        self.main_window()?.set_cursor_visible(visible);
+        Ok(())
+    }
+
+    /// Returns scale factor of the main window (the ratio between physical pixels and logical units,
+    /// see HiDPI docs of `winit` for more info) or `1.0` if the graphics context is not initialized.
+    /// The main user interface is scaled using this value by default (see [`Self::set_ui_scaling_mode`]),
+    /// use it to scale your own screen-space content or to convert cursor position to logical units.
+    pub fn scale_factor(&self) -> f64 {
+        self.main_window()
+            .map(|window| window.scale_factor())
+            .unwrap_or(1.0)
+    }
+
+    /// Sets the way of scaling of the main user interface and the user interfaces of secondary
+    /// windows. Widgets are laid out in logical units and then scaled to physical pixels, cursor
+    /// position stays in physical pixels, so hit testing works with any scale. Fonts are rasterized
+    /// at the scaled size, so the text stays sharp. Default is [`UiScaling::ScaleFactor`].
+    pub fn set_ui_scaling_mode(&mut self, ui_scaling: UiScaling) {
+        self.ui_scaling = ui_scaling;
+        if let GraphicsContext::Initialized(ctx) = &mut self.graphics_context {
+            self.user_interface
+                .set_scale_factor(ui_scaling.scale_factor(ctx.window.scale_factor()));
+            for window in ctx.secondary_windows.values_mut() {
+                let scale_factor = ui_scaling.scale_factor(window.window.scale_factor());
+                if let Some(user_interface) = window.user_interface.as_mut() {
+                    user_interface.set_scale_factor(scale_factor);
+                }
+            }
+        } else {
+            self.user_interface
+                .set_scale_factor(ui_scaling.scale_factor(1.0));
+        }
+    }
+
+    /// Returns current way of scaling of the user interfaces, see [`Self::set_ui_scaling_mode`]
+    /// for more info.
+    pub fn ui_scaling_mode(&self) -> UiScaling {
+        self.ui_scaling
+    }
+
+    /// Sets the color, that is used to fill the main window before rendering of scenes, it is visible
+    /// where no scene covers the screen. Scenes fill pixels, that are not covered by any geometry or
+    /// skybox, by the same color, unless they override it using
+    /// [`crate::scene::environment::EnvironmentSettings::clear_color`]. Default is black. The color
+    /// is kept when the graphics context is re-created.
+    pub fn set_clear_color(&mut self, color: Color) {
+        self.clear_color = color;
+        if let GraphicsContext::Initialized(ctx) = &mut self.graphics_context {
+            ctx.renderer.set_backbuffer_clear_color(color);
+        }
+    }
+
+    /// Returns the color, that is used to fill the main window before rendering of scenes. See
+    /// [`Self::set_clear_color`] for more info.
+    pub fn clear_color(&self) -> Color {
+        self.clear_color
+    }
+
+    /// Returns the clipboard that is used by the main user interface, or `None` if the clipboard is not
+    /// available on the current platform. Use it to copy text to the clipboard (or to read it) from
+    /// game code:
+    ///
+    /// ```rust,no_run
+    /// # use fyrox::{engine::Engine, gui::copypasta::ClipboardProvider};
+    /// fn copy_score(engine: &Engine, score: u32) {
+    ///     if let Some(mut clipboard) = engine.clipboard() {
+    ///         let _ = clipboard.set_contents(format!("My score is {score}!"));
+    ///     }
+    /// }
+    /// ```
+    ///
+    /// On WebAssembly the clipboard cannot be read, pasted text is passed directly to the widget with
+    /// keyboard focus instead, see `clipboard` module docs for more info.
+    pub fn clipboard(&self) -> Option<RefMut<'_, Box<dyn ClipboardProvider>>> {
+        self.user_interface.clipboard_mut()
+    }
+
+    fn main_window(&self) -> Result<&Window, EngineError> {
+        if let GraphicsContext::Initialized(ref graphics_context) = self.graphics_context {
+            Ok(&graphics_context.window)
+        } else {
+            Err(EngineError::Custom(
+                "Graphics context is not initialized!".to_string(),
+            ))
+        }
+    }
+
+    /// Passes the given event of the main window to the input snapshot (see [`Self::input`]) and to
+    /// the main user interface. Returns `true` if the event was processed by the user interface. This
+    /// method should be used in custom game loops instead of manual translation of the events.
+    pub fn process_window_event(&mut self, event: &WindowEvent) -> bool {
+        self.input.process_window_event(event);
+        if let WindowEvent::ScaleFactorChanged { scale_factor, .. } = event {
+            self.user_interface
+                .set_scale_factor(self.ui_scaling.scale_factor(*scale_factor));
+        }
+        match translate_event(event) {
+            Some(os_event) => self.process_os_event(&os_event),
+            None => false,
+        }
+    }
+
+    /// Passes the given OS event to the main user interface and records it in the input debugger
+    /// (if it is enabled). Returns `true` if the event was processed by the user interface.
+    pub fn process_os_event(&mut self, event: &OsEvent) -> bool {
+        let processed = self.user_interface.process_os_event(event);
+        self.input_debugger.record(event, &self.user_interface);
+        processed
+    }
+
     /// Performs single update tick with given time delta. Engine internally will perform update
     /// of all scenes, sub-systems, user interface, etc. Must be called in order to get engine
     /// functioning.
@@ -1178,6 +1949,23 @@ impl Engine {
         self.post_update(dt);
     }
 
+    /// Performs single update tick with given real time delta. The delta is scaled using the time
+    /// scale of the engine clock (see [`Self::set_time_scale`]) and the scaled delta is passed to
+    /// scenes, scripts and plugins, while user interface is updated with the real delta so it stays
+    /// responsive in slow motion or pause. Use [`Self::update`] if you want to control time delta
+    /// manually. See [`Self::update`] docs for the meaning of other parameters.
+    pub fn update_real(
+        &mut self,
+        real_dt: f32,
+        control_flow: &mut ControlFlow,
+        lag: &mut f32,
+        switches: FxHashMap<Handle<Scene>, GraphUpdateSwitches>,
+    ) {
+        let scaled_dt = self.clock.advance(real_dt);
+        self.pre_update(scaled_dt, control_flow, lag, switches);
+        self.post_update(real_dt);
+    }
+
     /// Performs pre update for the engine.
     ///
     /// Normally, this is called from `Engine::update()`.
@@ -1203,28 +1991,50 @@ impl Engine {
             let inner_size = ctx.window.inner_size();
             let window_size = Vector2::new(inner_size.width as f32, inner_size.height as f32);
 
+            self.input.begin_frame();
             self.resource_manager.state().update(dt);
+            self.resource_manager.process_load_callbacks();
             ctx.renderer.update_caches(dt);
             self.handle_model_events();
 
-            for (handle, scene) in self.scenes.pair_iter_mut().filter(|(_, s)| s.enabled) {
-                let frame_size = scene.render_target.as_ref().map_or(window_size, |rt| {
-                    if let TextureKind::Rectangle { width, height } = rt.data_ref().kind() {
-                        Vector2::new(width as f32, height as f32)
-                    } else {
-                        panic!("only rectangle textures can be used as render target!");
+            // Paused simulation advances only when a single step was requested.
+            let simulation_dt = if self.paused {
+                self.pending_step.take()
+            } else {
+                Some(dt)
+            };
+
+            if let Some(simulation_dt) = simulation_dt {
+                for handle in self.scenes.handles_by_priority() {
+                    let scene = &mut self.scenes[handle];
+                    if !scene.is_update_enabled() {
+                        continue;
                     }
-                });
 
-                scene.update(
-                    frame_size,
-                    dt,
-                    switches.get(&handle).cloned().unwrap_or_default(),
-                );
+                    let frame_size = scene.render_target.as_ref().map_or(window_size, |rt| {
+                        if let TextureKind::Rectangle { width, height } = rt.data_ref().kind() {
+                            Vector2::new(width as f32, height as f32)
+                        } else {
+                            panic!("only rectangle textures can be used as render target!");
+                        }
+                    });
+
+                    let scene_dt = scene.scaled_dt(simulation_dt);
+                    let mut scene_switches = switches.get(&handle).cloned().unwrap_or_default();
+                    // Zero time scale pauses the scene, so the physics won't be stepped with zero
+                    // time delta and the sounds will be paused (not pitch shifted).
+                    scene_switches.paused |= scene_dt <= 0.0;
+
+                    scene.update(frame_size, scene_dt, scene_switches);
+                }
             }
 
+            self.handle_engine_events_by_plugins(dt, control_flow, lag);
             self.update_plugins(dt, control_flow, lag);
-            self.handle_scripts(dt);
+
+            if let Some(simulation_dt) = simulation_dt {
+                self.handle_scripts(simulation_dt);
+            }
         }
     }
 
@@ -1233,12 +2043,34 @@ impl Engine {
     /// Normally, this is called from `Engine::update()`.
     /// You should only call this manually if you don't use that method.
     pub fn post_update(&mut self, dt: f32) {
-        if let GraphicsContext::Initialized(ref ctx) = self.graphics_context {
+        if let GraphicsContext::Initialized(ref mut ctx) = self.graphics_context {
             let inner_size = ctx.window.inner_size();
             let window_size = Vector2::new(inner_size.width as f32, inner_size.height as f32);
 
+            #[cfg(target_arch = "wasm32")]
+            for text in self.paste_queue.take() {
+                self.user_interface
+                    .send_message(crate::gui::text_box::TextBoxMessage::paste(
+                        self.user_interface.keyboard_focus_node(),
+                        crate::gui::message::MessageDirection::ToWidget,
+                        text,
+                    ));
+            }
+
             let time = instant::Instant::now();
+            // Scale factor could change without an event (for example, when a custom game loop
+            // does not pass window events to the engine), so keep it in sync every frame.
+            self.user_interface
+                .set_scale_factor(self.ui_scaling.scale_factor(ctx.window.scale_factor()));
             self.user_interface.update(window_size, dt);
+            for window in ctx.secondary_windows.values_mut() {
+                let frame_size = window.frame_size();
+                let scale_factor = self.ui_scaling.scale_factor(window.window.scale_factor());
+                if let Some(user_interface) = window.user_interface.as_mut() {
+                    user_interface.set_scale_factor(scale_factor);
+                    user_interface.update(frame_size, dt);
+                }
+            }
             self.performance_statistics.ui_time = instant::Instant::now() - time;
             self.elapsed_time += dt;
         }
@@ -1285,6 +2117,7 @@ impl Engine {
                 performance_statistics: &self.performance_statistics,
                 elapsed_time: self.elapsed_time,
                 script_processor: &self.script_processor,
+                input: &self.input,
             };
 
             for plugin in self.plugins.iter_mut() {
@@ -1303,12 +2136,44 @@ impl Engine {
                     performance_statistics: &self.performance_statistics,
                     elapsed_time: self.elapsed_time,
                     script_processor: &self.script_processor,
+                    input: &self.input,
                 };
 
                 for plugin in self.plugins.iter_mut() {
                     plugin.on_ui_message(&mut context, &message, control_flow);
                 }
             }
+
+            let mut secondary_messages = Vec::new();
+            if let GraphicsContext::Initialized(ctx) = &mut self.graphics_context {
+                for (id, window) in ctx.secondary_windows.iter_mut() {
+                    if let Some(user_interface) = window.user_interface.as_mut() {
+                        while let Some(message) = user_interface.poll_message() {
+                            secondary_messages.push((*id, message));
+                        }
+                    }
+                }
+            }
+
+            for (id, message) in secondary_messages {
+                let mut context = PluginContext {
+                    scenes: &mut self.scenes,
+                    resource_manager: &self.resource_manager,
+                    graphics_context: &mut self.graphics_context,
+                    dt,
+                    lag,
+                    user_interface: &mut self.user_interface,
+                    serialization_context: &self.serialization_context,
+                    performance_statistics: &self.performance_statistics,
+                    elapsed_time: self.elapsed_time,
+                    script_processor: &self.script_processor,
+                    input: &self.input,
+                };
+
+                for plugin in self.plugins.iter_mut() {
+                    plugin.on_secondary_window_ui_message(&mut context, id, &message, control_flow);
+                }
+            }
         }
 
         self.performance_statistics.plugins_time = instant::Instant::now() - time;
@@ -1336,6 +2201,7 @@ impl Engine {
                         performance_statistics: &self.performance_statistics,
                         elapsed_time: self.elapsed_time,
                         script_processor: &self.script_processor,
+                        input: &self.input,
                     },
                     control_flow,
                 );
@@ -1343,6 +2209,80 @@ impl Engine {
         }
     }
 
+    fn handle_engine_events_by_plugins(
+        &mut self,
+        dt: f32,
+        control_flow: &mut ControlFlow,
+        lag: &mut f32,
+    ) {
+        let events = std::mem::take(&mut self.engine_events);
+
+        if self.plugins_enabled {
+            for event in events.iter() {
+                for plugin in self.plugins.iter_mut() {
+                    plugin.on_engine_event(
+                        event,
+                        PluginContext {
+                            scenes: &mut self.scenes,
+                            resource_manager: &self.resource_manager,
+                            graphics_context: &mut self.graphics_context,
+                            dt,
+                            lag,
+                            user_interface: &mut self.user_interface,
+                            serialization_context: &self.serialization_context,
+                            performance_statistics: &self.performance_statistics,
+                            elapsed_time: self.elapsed_time,
+                            script_processor: &self.script_processor,
+                            input: &self.input,
+                        },
+                        control_flow,
+                    );
+                }
+            }
+        }
+    }
+
+    /// Returns the oldest engine event (see [`EngineEvent`]), that was not read yet, or `None` if
+    /// there are no such events. Plugins receive the same events in [`Plugin::on_engine_event`],
+    /// polling is useful when the engine is used without plugins. Events are kept until they're
+    /// read, so call the method until it returns `None` every frame.
+    pub fn poll_event(&mut self) -> Option<EngineEvent> {
+        self.polled_events.pop_front()
+    }
+
+    /// Detects loss of the graphics context and tries to restore it. Every transition is reported
+    /// to plugins and to [`Self::poll_event`] as [`EngineEvent`].
+    fn handle_graphics_context_loss(&mut self) {
+        if let GraphicsContext::Initialized(ref mut ctx) = self.graphics_context {
+            #[cfg(target_arch = "wasm32")]
+            if ctx.webgl2_context.is_context_lost() {
+                ctx.renderer.pipeline_state().set_context_lost();
+            }
+
+            if !ctx.context_lost && ctx.renderer.is_context_lost() {
+                ctx.context_lost = true;
+                self.engine_events.push(EngineEvent::GraphicsContextLost);
+                self.polled_events
+                    .push_back(EngineEvent::GraphicsContextLost);
+            }
+
+            if ctx.context_lost {
+                match ctx.restore_context(&self.resource_manager) {
+                    Ok(true) => {
+                        Log::info("Graphics context was restored.");
+                        ctx.context_lost = false;
+                        self.engine_events
+                            .push(EngineEvent::GraphicsContextRestored);
+                        self.polled_events
+                            .push_back(EngineEvent::GraphicsContextRestored);
+                    }
+                    Ok(false) => (),
+                    Err(e) => Log::err(format!("Unable to restore graphics context: {:?}", e)),
+                }
+            }
+        }
+    }
+
     pub(crate) fn handle_graphics_context_created_by_plugins(
         &mut self,
         dt: f32,
@@ -1363,6 +2303,7 @@ impl Engine {
                         performance_statistics: &self.performance_statistics,
                         elapsed_time: self.elapsed_time,
                         script_processor: &self.script_processor,
+                        input: &self.input,
                     },
                     control_flow,
                 );
@@ -1390,6 +2331,7 @@ impl Engine {
                         performance_statistics: &self.performance_statistics,
                         elapsed_time: self.elapsed_time,
                         script_processor: &self.script_processor,
+                        input: &self.input,
                     },
                     control_flow,
                 );
@@ -1417,6 +2359,7 @@ impl Engine {
                         performance_statistics: &self.performance_statistics,
                         elapsed_time: self.elapsed_time,
                         script_processor: &self.script_processor,
+                        input: &self.input,
                     },
                     control_flow,
                 );
@@ -1444,7 +2387,7 @@ impl Engine {
             .find(|s| s.handle == scene)
         {
             let scene = &mut self.scenes[scene];
-            if scene.enabled {
+            if scene.is_update_enabled() {
                 process_scripts(
                     scene,
                     &mut self.plugins,
@@ -1492,11 +2435,162 @@ impl Engine {
         }
     }
 
+    /// Immediately renders the given scene into the given texture, outside of the main loop. The
+    /// texture could be used in materials of other scenes right after the call, which is useful for
+    /// minimaps, portals and similar effects. Cameras of the scene are re-calculated to match the
+    /// size of the texture. See [`Renderer::render_scene_to_texture`] for more info.
+    pub fn render_scene_to_texture(
+        &mut self,
+        scene: Handle<Scene>,
+        target: &TextureResource,
+    ) -> Result<(), EngineError> {
+        let ctx = if let GraphicsContext::Initialized(ctx) = &mut self.graphics_context {
+            ctx
+        } else {
+            return Err(EngineError::Custom(
+                "Graphics context is not initialized!".to_string(),
+            ));
+        };
+
+        let scene_ref = self
+            .scenes
+            .try_get_mut(scene)
+            .ok_or_else(|| EngineError::Custom(format!("There is no scene {scene}!")))?;
+
+        let frame_size = if target.is_ok() {
+            target.data_ref().kind().rectangle_size()
+        } else {
+            None
+        }
+        .ok_or_else(|| {
+            EngineError::Custom("Scene can be rendered only into rectangle texture!".to_string())
+        })?;
+        let frame_size = Vector2::new(frame_size.x as f32, frame_size.y as f32);
+        for node in scene_ref.graph.linear_iter_mut() {
+            if let Some(camera) = node.cast_mut::<Camera>() {
+                camera.calculate_matrices(frame_size);
+            }
+        }
+
+        ctx.renderer
+            .render_scene_to_texture(scene, scene_ref, target)?;
+
+        Ok(())
+    }
+
+    /// Renders a thumbnail of the given scene from a temporary camera with the given global
+    /// transform (the camera looks along its local Z axis) and returns it as a rectangle texture
+    /// of the given size with RGBA8 pixels. The method is self-contained: it does not touch cameras
+    /// of the scene, the frame buffer of the main window or render targets of the scene, so it
+    /// could be used at any time, for example to generate previews for an asset browser. Every
+    /// temporary resource (the camera and GPU objects) is freed before the method returns.
+    pub fn render_thumbnail(
+        &mut self,
+        scene: Handle<Scene>,
+        camera_transform: Matrix4<f32>,
+        size: Vector2<u32>,
+    ) -> Result<TextureResource, EngineError> {
+        let ctx = if let GraphicsContext::Initialized(ctx) = &mut self.graphics_context {
+            ctx
+        } else {
+            return Err(EngineError::Custom(
+                "Graphics context is not initialized!".to_string(),
+            ));
+        };
+
+        if size.x == 0 || size.y == 0 {
+            return Err(EngineError::Custom(format!(
+                "Invalid thumbnail size {}x{}!",
+                size.x, size.y
+            )));
+        }
+
+        let scene_ref = self
+            .scenes
+            .try_get_mut(scene)
+            .ok_or_else(|| EngineError::Custom(format!("There is no scene {scene}!")))?;
+
+        // The camera is disabled, so it won't be rendered in the main window if something goes
+        // wrong and it stays in the scene.
+        let rotation = UnitQuaternion::from_matrix_eps(
+            &camera_transform.basis(),
+            f32::EPSILON,
+            16,
+            UnitQuaternion::identity(),
+        );
+        let camera = CameraBuilder::new(
+            BaseBuilder::new().with_local_transform(
+                TransformBuilder::new()
+                    .with_local_position(camera_transform.position())
+                    .with_local_rotation(rotation)
+                    .build(),
+            ),
+        )
+        .enabled(false)
+        .build(&mut scene_ref.graph);
+        scene_ref
+            .graph
+            .update_hierarchical_data_for_descendants(camera);
+        scene_ref.graph[camera]
+            .as_camera_mut()
+            .calculate_matrices(Vector2::new(size.x as f32, size.y as f32));
+
+        let target = TextureResource::new_render_target(size.x, size.y);
+        let result = ctx.renderer.render_scene_view_to_texture(
+            SceneView { scene, camera },
+            scene_ref,
+            &target,
+        );
+        let pixels = ctx.renderer.read_render_target_pixels(&target);
+
+        ctx.renderer.release_render_target(&target);
+        scene_ref.graph.remove_node(camera);
+
+        result?;
+
+        let mut pixels = pixels.ok_or_else(|| {
+            EngineError::Custom("Unable to read pixels of the thumbnail!".to_string())
+        })?;
+        // Rows are read from the bottom of the image, flip them to match the texture layout.
+        let row_size = size.x as usize * 4;
+        let row_count = size.y as usize;
+        for y in 0..row_count / 2 {
+            let (top, bottom) = pixels.split_at_mut((row_count - y - 1) * row_size);
+            top[y * row_size..(y + 1) * row_size].swap_with_slice(&mut bottom[..row_size]);
+        }
+
+        Texture::from_bytes(
+            TextureKind::Rectangle {
+                width: size.x,
+                height: size.y,
+            },
+            TexturePixelKind::RGBA8,
+            pixels,
+            false,
+        )
+        .map(TextureResource::new_ok)
+        .ok_or_else(|| EngineError::Custom("Unable to create thumbnail texture!".to_string()))
+    }
+
+    /// Reads pixels of the image, that was rendered into the given texture by
+    /// [`Self::render_scene_to_texture`]. See [`Renderer::read_render_target_pixels`] for more info.
+    pub fn read_render_target_pixels(&mut self, target: &TextureResource) -> Option<Vec<u8>> {
+        if let GraphicsContext::Initialized(ctx) = &mut self.graphics_context {
+            ctx.renderer.read_render_target_pixels(target)
+        } else {
+            None
+        }
+    }
+
     /// Performs rendering of single frame, must be called from your game loop, otherwise you won't
     /// see anything.
     #[inline]
     pub fn render(&mut self) -> Result<(), FrameworkError> {
+        self.handle_graphics_context_loss();
+
         self.user_interface.draw();
+        self.input_debugger.draw_overlay(&mut self.user_interface);
+        self.input_debugger.next_frame();
 
         if let GraphicsContext::Initialized(ref mut ctx) = self.graphics_context {
             #[cfg(not(target_arch = "wasm32"))]
@@ -1507,6 +2601,43 @@ impl Engine {
                     &ctx.gl_surface,
                     &ctx.gl_context,
                 )?;
+
+                if !ctx.secondary_windows.is_empty() {
+                    for (id, window) in ctx.secondary_windows.iter_mut() {
+                        let frame_size = window.frame_size();
+
+                        // Cameras are updated using the size of the main window, so the matrices
+                        // must be re-calculated to match the size of the secondary window.
+                        if let Some(view) = window.scene_view {
+                            if let Some(camera) = self
+                                .scenes
+                                .try_get_mut(view.scene)
+                                .and_then(|scene| scene.graph.try_get_mut(view.camera))
+                                .and_then(|node| node.cast_mut::<crate::scene::camera::Camera>())
+                            {
+                                camera.calculate_matrices(frame_size);
+                            }
+                        }
+
+                        let drawing_context = window
+                            .user_interface
+                            .as_mut()
+                            .map(|user_interface| user_interface.draw());
+
+                        ctx.gl_context.make_current(&window.gl_surface)?;
+                        ctx.renderer.render_secondary_window_and_swap_buffers(
+                            *id,
+                            &self.scenes,
+                            window.scene_view,
+                            (frame_size.x as u32, frame_size.y as u32),
+                            drawing_context,
+                            &window.gl_surface,
+                            &ctx.gl_context,
+                        )?;
+                    }
+
+                    ctx.gl_context.make_current(&ctx.gl_surface)?;
+                }
             }
             #[cfg(target_arch = "wasm32")]
             {
@@ -1517,9 +2648,32 @@ impl Engine {
             }
         }
 
+        self.performance_statistics.frame_time = self.frame_rate_limiter.end_frame();
+
         Ok(())
     }
 
+    /// Sets maximum amount of frames per second. When set, [`Self::render`] waits at the end of each
+    /// frame until the desired frame time is reached. It sleeps most of the time and spins for the
+    /// last couple of milliseconds, which keeps the frame rate accurate without loading a CPU core.
+    /// `None` (or zero) disables the limit, which is default. It is useful when vertical
+    /// synchronization is off, to prevent rendering thousands of frames per second. Has no effect on
+    /// WebAssembly, where frame rate is controlled by the browser.
+    pub fn set_frame_rate_limit(&mut self, limit: Option<u32>) {
+        self.frame_rate_limiter.limit = limit.filter(|limit| *limit > 0);
+    }
+
+    /// Returns current frame rate limit. See [`Self::set_frame_rate_limit`] for more info.
+    pub fn frame_rate_limit(&self) -> Option<u32> {
+        self.frame_rate_limiter.limit
+    }
+
+    /// Returns actual amount of time between the ends of two last rendered frames, including the time
+    /// spent waiting by the frame rate limiter. Use it to show real frame rate.
+    pub fn frame_time(&self) -> Duration {
+        self.performance_statistics.frame_time
+    }
+
     /// Enables or disables registered plugins.
     pub(crate) fn enable_plugins(&mut self, override_scene: Handle<Scene>, enabled: bool) {
         if self.plugins_enabled != enabled {
@@ -1541,6 +2695,7 @@ impl Engine {
                             performance_statistics: &self.performance_statistics,
                             elapsed_time: self.elapsed_time,
                             script_processor: &self.script_processor,
+                            input: &self.input,
                         },
                     ));
                 }
@@ -1560,6 +2715,7 @@ impl Engine {
                         performance_statistics: &self.performance_statistics,
                         elapsed_time: self.elapsed_time,
                         script_processor: &self.script_processor,
+                        input: &self.input,
                     });
                 }
             }
@@ -1581,21 +2737,11 @@ impl Engine {
 
 impl Drop for Engine {
     fn drop(&mut self) {
-        // Destroy all scenes first and correctly destroy all script instances.
-        // This will ensure that any `on_destroy` logic will be executed before
-        // engine destroyed.
-        let scenes = self
-            .scenes
-            .pair_iter()
-            .map(|(h, _)| h)
-            .collect::<Vec<Handle<Scene>>>();
-
-        for handle in scenes {
-            self.scenes.remove(handle);
+        // Does nothing if the engine was already shut down. The resource manager is not shut down
+        // here, because it could still be used by its other owners.
+        if let Err(err) = self.destroy_subsystems(false) {
+            Log::warn(err.to_string());
         }
-
-        // Finally disable plugins.
-        self.enable_plugins(Default::default(), false);
     }
 }
 
@@ -1604,7 +2750,9 @@ mod test {
     use crate::{
         asset::manager::ResourceManager,
         core::{pool::Handle, reflect::prelude::*, uuid::Uuid, visitor::prelude::*},
-        engine::ScriptProcessor,
+        engine::{
+            Engine, EngineInitParams, FrameRateLimiter, ScriptProcessor, SerializationContext,
+        },
         impl_component_provider,
         scene::{base::BaseBuilder, node::Node, pivot::PivotBuilder, Scene, SceneContainer},
         script::{
@@ -1613,7 +2761,14 @@ mod test {
         },
     };
 
-    use std::sync::mpsc::{self, Sender, TryRecvError};
+    use fyrox_sound::context::SoundContext;
+    use std::{
+        sync::{
+            mpsc::{self, Sender, TryRecvError},
+            Arc,
+        },
+        time::Duration,
+    };
 
     #[derive(PartialEq, Eq, Clone, Debug)]
     enum Event {
@@ -1933,4 +3088,72 @@ mod test {
             }
         }
     }
+
+    #[test]
+    fn test_frame_rate_limiter() {
+        let mut limiter = FrameRateLimiter {
+            limit: Some(100),
+            last_frame_end: None,
+        };
+
+        assert_eq!(limiter.end_frame(), Duration::default());
+        for _ in 0..5 {
+            assert!(limiter.end_frame() >= Duration::from_millis(10));
+        }
+
+        limiter.limit = None;
+        limiter.end_frame();
+        assert!(limiter.end_frame() < Duration::from_millis(10));
+    }
+
+    #[test]
+    fn test_shutdown_releases_sound_contexts() {
+        let engine = Engine::new(EngineInitParams {
+            graphics_context_params: Default::default(),
+            serialization_context: Arc::new(SerializationContext::new()),
+            resource_manager: ResourceManager::new(),
+        })
+        .unwrap();
+
+        let sound_engine = engine.sound_engine.clone();
+        let context = SoundContext::new();
+        sound_engine.state().add_context(context.clone());
+
+        engine.shutdown().unwrap();
+
+        assert!(!sound_engine.state().has_context(&context));
+        assert!(sound_engine.state().contexts().is_empty());
+    }
+
+    #[test]
+    fn test_drop_keeps_shared_resource_manager_alive() {
+        let resource_manager = ResourceManager::new();
+
+        let engine = Engine::new(EngineInitParams {
+            graphics_context_params: Default::default(),
+            serialization_context: Arc::new(SerializationContext::new()),
+            resource_manager: resource_manager.clone(),
+        })
+        .unwrap();
+
+        drop(engine);
+
+        assert!(!resource_manager.state().is_shut_down());
+    }
+
+    #[test]
+    fn test_shutdown_stops_resource_manager() {
+        let resource_manager = ResourceManager::new();
+
+        let engine = Engine::new(EngineInitParams {
+            graphics_context_params: Default::default(),
+            serialization_context: Arc::new(SerializationContext::new()),
+            resource_manager: resource_manager.clone(),
+        })
+        .unwrap();
+
+        engine.shutdown().unwrap();
+
+        assert!(resource_manager.state().is_shut_down());
+    }
 }
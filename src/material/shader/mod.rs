@@ -140,6 +140,13 @@
 //! limiting, it does not support lighting, shadows, etc. It should be only used to render
 //! translucent objects.
 //!
+//! - ForwardOIT - An optional pass that is used instead of Forward when order-independent
+//!   transparency is enabled (see [`crate::renderer::QualitySettings::use_oit`]). It must write
+//!   `vec4(color.rgb * w, color.a)` to the first output and `w` to the second one, where
+//!   `w = S_OITWeight(gl_FragCoord.z, color.a)`. Blending and depth writing are always overridden
+//!   by the renderer. Objects without this pass use Forward pass and are sorted as usual. See the
+//!   standard shader for example.
+//!
 //! - SpotShadow - A pass that emits depth values for an object, later this depth map will be
 //! used to render shadows.
 //!
@@ -159,6 +166,14 @@
 //! | fyrox_cameraPosition       | `Vector3`       | Position of the camera.
 //! | fyrox_usePOM               | `bool`          | Whether to use parallax mapping or not.
 //! | fyrox_lightPosition        | `Vector3`       | Light position.
+//! | fyrox_viewProjectionMatrix | `Matrix4`       | World-to-clip-space transform.
+//! | fyrox_useInstancing        | `bool`          | Whether instanced rendering is used or not.
+//! | fyrox_instanceMatrices     | `sampler2D`     | World matrices of instances, use `S_FetchMatrix(fyrox_instanceMatrices, gl_InstanceID)`.
+//!
+//! Instanced rendering is used in GBuffer and shadow passes for batches with multiple instances, but
+//! only if the pass uses `fyrox_instanceMatrices` and `fyrox_useInstancing`. In this case, world matrix
+//! of an instance must be fetched from the matrix storage instead of `fyrox_worldMatrix` (see the
+//! standard shader for example).
 //!
 //! To use any of the variables, just define a uniform with appropriate name:
 //!
@@ -89,6 +89,9 @@
                 // required data to these uniforms.
                 uniform mat4 fyrox_worldMatrix;
                 uniform mat4 fyrox_worldViewProjection;
+                uniform mat4 fyrox_viewProjectionMatrix;
+                uniform bool fyrox_useInstancing;
+                uniform sampler2D fyrox_instanceMatrices;
                 uniform bool fyrox_useSkeletalAnimation;
                 uniform sampler2D fyrox_boneMatrices;
                 uniform sampler3D fyrox_blendShapesStorage;
@@ -104,6 +107,14 @@
 
                 void main()
                 {
+                    mat4 worldMatrix = fyrox_worldMatrix;
+                    mat4 worldViewProjection = fyrox_worldViewProjection;
+                    if (fyrox_useInstancing)
+                    {
+                        worldMatrix = S_FetchMatrix(fyrox_instanceMatrices, gl_InstanceID);
+                        worldViewProjection = fyrox_viewProjectionMatrix * worldMatrix;
+                    }
+
                     vec4 localPosition = vec4(0);
                     vec3 localNormal = vec3(0);
                     vec3 localTangent = vec3(0);
@@ -154,15 +165,15 @@
                         localTangent = inputTangent;
                     }
 
-                    mat3 nm = mat3(fyrox_worldMatrix);
+                    mat3 nm = mat3(worldMatrix);
                     normal = normalize(nm * localNormal);
                     tangent = normalize(nm * localTangent);
                     binormal = normalize(vertexTangent.w * cross(tangent, normal));
                     texCoord = vertexTexCoord;
-                    position = vec3(fyrox_worldMatrix * localPosition);
+                    position = vec3(worldMatrix * localPosition);
                     secondTexCoord = vertexSecondTexCoord;
 
-                    gl_Position = fyrox_worldViewProjection * localPosition;
+                    gl_Position = worldViewProjection * localPosition;
                 }
                 "#,
             fragment_shader:
@@ -339,6 +350,114 @@
                 }
                "#,
         ),
+        (
+            name: "ForwardOIT",
+            draw_parameters: DrawParameters(
+                cull_face: Some(Back),
+                color_write: ColorMask(
+                    red: true,
+                    green: true,
+                    blue: true,
+                    alpha: true,
+                ),
+                depth_write: false,
+                stencil_test: None,
+                depth_test: true,
+                blend: Some(BlendParameters(
+                    func: BlendFunc(
+                        sfactor: One,
+                        dfactor: One,
+                        alpha_sfactor: Zero,
+                        alpha_dfactor: OneMinusSrcAlpha,
+                    ),
+                    equation: BlendEquation(
+                        rgb: Add,
+                        alpha: Add
+                    )
+                )),
+                stencil_op: StencilOp(
+                    fail: Keep,
+                    zfail: Keep,
+                    zpass: Keep,
+                    write_mask: 0xFFFF_FFFF,
+                ),
+            ),
+            vertex_shader:
+               r#"
+                layout(location = 0) in vec3 vertexPosition;
+                layout(location = 1) in vec2 vertexTexCoord;
+                layout(location = 5) in vec4 boneWeights;
+                layout(location = 6) in vec4 boneIndices;
+
+                uniform mat4 fyrox_worldViewProjection;
+                uniform bool fyrox_useSkeletalAnimation;
+                uniform sampler2D fyrox_boneMatrices;
+                uniform sampler3D fyrox_blendShapesStorage;
+                uniform float fyrox_blendShapesWeights[128];
+                uniform int fyrox_blendShapesCount;
+
+                out vec3 position;
+                out vec2 texCoord;
+
+                void main()
+                {
+                    vec4 localPosition = vec4(0);
+
+                    vec4 inputPosition = vec4(vertexPosition, 1.0);
+
+                    for (int i = 0; i < fyrox_blendShapesCount; ++i) {
+                        TBlendShapeOffsets offsets = S_FetchBlendShapeOffsets(fyrox_blendShapesStorage, gl_VertexID, i);
+                        float weight = fyrox_blendShapesWeights[i];
+                        inputPosition.xyz += offsets.position * weight;
+                    }
+
+                    if (fyrox_useSkeletalAnimation)
+                    {
+                        vec4 vertex = vec4(vertexPosition, 1.0);
+
+                        int i0 = int(boneIndices.x);
+                        int i1 = int(boneIndices.y);
+                        int i2 = int(boneIndices.z);
+                        int i3 = int(boneIndices.w);
+
+                        mat4 m0 = S_FetchMatrix(fyrox_boneMatrices, i0);
+                        mat4 m1 = S_FetchMatrix(fyrox_boneMatrices, i1);
+                        mat4 m2 = S_FetchMatrix(fyrox_boneMatrices, i2);
+                        mat4 m3 = S_FetchMatrix(fyrox_boneMatrices, i3);
+
+                        localPosition += m0 * inputPosition * boneWeights.x;
+                        localPosition += m1 * inputPosition * boneWeights.y;
+                        localPosition += m2 * inputPosition * boneWeights.z;
+                        localPosition += m3 * inputPosition * boneWeights.w;
+                    }
+                    else
+                    {
+                        localPosition = inputPosition;
+                    }
+                    gl_Position = fyrox_worldViewProjection * localPosition;
+                    texCoord = vertexTexCoord;
+                }
+               "#,
+
+           fragment_shader:
+               r#"
+                uniform sampler2D diffuseTexture;
+                uniform vec4 diffuseColor;
+
+                layout(location = 0) out vec4 outAccum;
+                layout(location = 1) out vec4 outWeight;
+
+                in vec2 texCoord;
+
+                void main()
+                {
+                    vec4 color = diffuseColor * texture(diffuseTexture, texCoord);
+                    float weight = S_OITWeight(gl_FragCoord.z, color.a);
+                    outAccum = vec4(color.rgb * weight, color.a);
+                    outWeight = vec4(weight);
+                }
+               "#,
+        ),
         (
             name: "DirectionalShadow",
 
@@ -370,6 +489,9 @@
                 layout(location = 5) in vec4 boneIndices;
 
                 uniform mat4 fyrox_worldViewProjection;
+                uniform mat4 fyrox_viewProjectionMatrix;
+                uniform bool fyrox_useInstancing;
+                uniform sampler2D fyrox_instanceMatrices;
                 uniform bool fyrox_useSkeletalAnimation;
                 uniform sampler2D fyrox_boneMatrices;
                 uniform sampler3D fyrox_blendShapesStorage;
@@ -380,6 +502,12 @@
 
                 void main()
                 {
+                    mat4 worldViewProjection = fyrox_worldViewProjection;
+                    if (fyrox_useInstancing)
+                    {
+                        worldViewProjection = fyrox_viewProjectionMatrix * S_FetchMatrix(fyrox_instanceMatrices, gl_InstanceID);
+                    }
+
                     vec4 localPosition = vec4(0);
 
                     vec4 inputPosition = vec4(vertexPosition, 1.0);
@@ -409,7 +537,7 @@
                         localPosition = inputPosition;
                     }
 
-                    gl_Position = fyrox_worldViewProjection * localPosition;
+                    gl_Position = worldViewProjection * localPosition;
                     texCoord = vertexTexCoord;
                 }
                 "#,
@@ -457,6 +585,9 @@
                 layout(location = 5) in vec4 boneIndices;
 
                 uniform mat4 fyrox_worldViewProjection;
+                uniform mat4 fyrox_viewProjectionMatrix;
+                uniform bool fyrox_useInstancing;
+                uniform sampler2D fyrox_instanceMatrices;
                 uniform bool fyrox_useSkeletalAnimation;
                 uniform sampler2D fyrox_boneMatrices;
                 uniform sampler3D fyrox_blendShapesStorage;
@@ -467,6 +598,12 @@
 
                 void main()
                 {
+                    mat4 worldViewProjection = fyrox_worldViewProjection;
+                    if (fyrox_useInstancing)
+                    {
+                        worldViewProjection = fyrox_viewProjectionMatrix * S_FetchMatrix(fyrox_instanceMatrices, gl_InstanceID);
+                    }
+
                     vec4 localPosition = vec4(0);
 
                     vec4 inputPosition = vec4(vertexPosition, 1.0);
@@ -496,7 +633,7 @@
                         localPosition = inputPosition;
                     }
 
-                    gl_Position = fyrox_worldViewProjection * localPosition;
+                    gl_Position = worldViewProjection * localPosition;
                     texCoord = vertexTexCoord;
                 }
                 "#,
@@ -545,6 +682,9 @@
 
                 uniform mat4 fyrox_worldMatrix;
                 uniform mat4 fyrox_worldViewProjection;
+                uniform mat4 fyrox_viewProjectionMatrix;
+                uniform bool fyrox_useInstancing;
+                uniform sampler2D fyrox_instanceMatrices;
                 uniform bool fyrox_useSkeletalAnimation;
                 uniform sampler2D fyrox_boneMatrices;
                 uniform sampler3D fyrox_blendShapesStorage;
@@ -556,6 +696,14 @@
 
                 void main()
                 {
+                    mat4 worldMatrix = fyrox_worldMatrix;
+                    mat4 worldViewProjection = fyrox_worldViewProjection;
+                    if (fyrox_useInstancing)
+                    {
+                        worldMatrix = S_FetchMatrix(fyrox_instanceMatrices, gl_InstanceID);
+                        worldViewProjection = fyrox_viewProjectionMatrix * worldMatrix;
+                    }
+
                     vec4 localPosition = vec4(0);
 
                     vec4 inputPosition = vec4(vertexPosition, 1.0);
@@ -585,8 +733,8 @@
                         localPosition = inputPosition;
                     }
 
-                    gl_Position = fyrox_worldViewProjection * localPosition;
-                    worldPosition = (fyrox_worldMatrix * localPosition).xyz;
+                    gl_Position = worldViewProjection * localPosition;
+                    worldPosition = (worldMatrix * localPosition).xyz;
                     texCoord = vertexTexCoord;
                 }
                 "#,
@@ -6,11 +6,15 @@ use crate::engine::ScriptProcessor;
 use crate::{
     asset::manager::ResourceManager,
     core::pool::Handle,
-    engine::{GraphicsContext, PerformanceStatistics, SerializationContext},
+    engine::{
+        input::InputSnapshot, EngineEvent, GraphicsContext, PerformanceStatistics,
+        SerializationContext,
+    },
     event::Event,
     event_loop::ControlFlow,
     gui::{message::UiMessage, UserInterface},
     scene::{Scene, SceneContainer},
+    window::WindowId,
 };
 use std::{any::Any, sync::Arc};
 
@@ -92,6 +96,9 @@ pub struct PluginContext<'a, 'b> {
 
     /// Script processor is used to run script methods in a strict order.
     pub script_processor: &'a ScriptProcessor,
+
+    /// Polled state of the mouse of the main window. See [`InputSnapshot`] docs for more info.
+    pub input: &'a InputSnapshot,
 }
 
 /// Base plugin automatically implements type casting for plugins.
@@ -226,6 +233,16 @@ pub trait Plugin: BasePlugin {
     ) {
     }
 
+    /// The method is called when the engine produces an event, for example when the graphics
+    /// context was lost or restored. See [`EngineEvent`] docs for more info.
+    fn on_engine_event(
+        &mut self,
+        #[allow(unused_variables)] event: &EngineEvent,
+        #[allow(unused_variables)] context: PluginContext,
+        #[allow(unused_variables)] control_flow: &mut ControlFlow,
+    ) {
+    }
+
     /// The method will be called when there is any message from main user interface instance
     /// of the engine.
     fn on_ui_message(
@@ -235,4 +252,15 @@ pub trait Plugin: BasePlugin {
         #[allow(unused_variables)] control_flow: &mut ControlFlow,
     ) {
     }
+
+    /// The method will be called when there is any message from a user interface of a secondary
+    /// window. See [`crate::engine::Engine::create_window`] for more info.
+    fn on_secondary_window_ui_message(
+        &mut self,
+        #[allow(unused_variables)] context: &mut PluginContext,
+        #[allow(unused_variables)] window_id: WindowId,
+        #[allow(unused_variables)] message: &UiMessage,
+        #[allow(unused_variables)] control_flow: &mut ControlFlow,
+    ) {
+    }
 }
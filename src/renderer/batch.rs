@@ -121,6 +121,29 @@ pub struct RenderDataBatch {
     sort_index: u64,
 }
 
+impl RenderDataBatch {
+    /// Checks whether all instances of the batch could be rendered using a single instanced draw
+    /// call. It is possible only for non-skinned batches with more than one instance, when every
+    /// instance draws the full range of elements and does not use depth offset or blend shapes.
+    pub fn is_instancing_possible(&self) -> bool {
+        !self.is_skinned
+            && self.instances.len() > 1
+            && self.instances.iter().all(|instance| {
+                instance.depth_offset == 0.0
+                    && instance.blend_shapes_weights.is_empty()
+                    && matches!(instance.element_range, ElementRange::Full)
+            })
+    }
+
+    /// Collects world matrices of every instance of the batch.
+    pub fn instance_matrices(&self) -> Vec<Matrix4<f32>> {
+        self.instances
+            .iter()
+            .map(|instance| instance.world_transform)
+            .collect()
+    }
+}
+
 impl Debug for RenderDataBatch {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         write!(
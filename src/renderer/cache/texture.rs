@@ -14,12 +14,15 @@ use crate::{
     },
     resource::texture::TextureResource,
 };
-use fxhash::FxHashMap;
+use fxhash::{FxHashMap, FxHashSet};
 use std::{cell::RefCell, collections::hash_map::Entry, rc::Rc};
 
 #[derive(Default)]
 pub struct TextureCache {
     pub(crate) map: FxHashMap<usize, CacheEntry<Rc<RefCell<GpuTexture>>>>,
+    // Keys of the textures that are waiting in the upload queue. Such textures are uploaded by the
+    // renderer respecting the upload budget, so they must not be uploaded on demand.
+    pub(crate) pending: FxHashSet<usize>,
 }
 
 impl TextureCache {
@@ -74,6 +77,15 @@ impl TextureCache {
 
         let key = texture_resource.key();
 
+        if self.pending.contains(&key) {
+            // Keep using previous version of the texture (if any) until the new one is uploaded,
+            // otherwise the caller will use a placeholder.
+            return self.map.get_mut(&key).map(|entry| {
+                entry.time_to_live = DEFAULT_RESOURCE_LIFETIME;
+                entry.value.clone()
+            });
+        }
+
         let texture_data_guard = texture_resource.state();
 
         if let ResourceStateRef::Ok(texture) = texture_data_guard.get() {
@@ -193,9 +205,11 @@ impl TextureCache {
 
     pub fn clear(&mut self) {
         self.map.clear();
+        self.pending.clear();
     }
 
     pub fn unload(&mut self, texture: TextureResource) {
+        self.pending.remove(&texture.key());
         self.map.remove(&texture.key());
     }
 }
@@ -6,7 +6,17 @@
 use crate::core::sstorage::ImmutableString;
 use crate::renderer::framework::geometry_buffer::ElementRange;
 use crate::{
-    core::{algebra::Vector3, math::Rect, scope_profile},
+    core::{
+        algebra::{Vector2, Vector3},
+        math::Rect,
+        scope_profile,
+    },
+    gui::{
+        brush::Brush,
+        draw::DrawingContext,
+        formatted_text::FormattedTextBuilder,
+        ttf::{FontBuilder, SharedFont},
+    },
     renderer::framework::{
         error::FrameworkError,
         framebuffer::{DrawParameters, FrameBuffer},
@@ -18,7 +28,10 @@ use crate::{
         state::PipelineState,
     },
     renderer::RenderPassStatistics,
-    scene::{camera::Camera, debug::SceneDrawingContext},
+    scene::{
+        camera::Camera,
+        debug::{Line, SceneDrawingContext},
+    },
 };
 
 #[repr(C)]
@@ -33,6 +46,8 @@ pub struct DebugRenderer {
     vertices: Vec<Vertex>,
     line_indices: Vec<[u32; 2]>,
     shader: DebugShader,
+    labels: DrawingContext,
+    font: Option<SharedFont>,
 }
 
 pub(crate) struct DebugShader {
@@ -79,9 +94,25 @@ impl DebugRenderer {
             shader: DebugShader::new(state)?,
             vertices: Default::default(),
             line_indices: Default::default(),
+            labels: DrawingContext::new(),
+            font: None,
         })
     }
 
+    fn push_line(&mut self, line: &Line) {
+        let color = line.color.into();
+        let i = self.vertices.len() as u32;
+        self.vertices.push(Vertex {
+            position: line.begin,
+            color,
+        });
+        self.vertices.push(Vertex {
+            position: line.end,
+            color,
+        });
+        self.line_indices.push([i, i + 1]);
+    }
+
     pub(crate) fn render(
         &mut self,
         state: &mut PipelineState,
@@ -97,46 +128,99 @@ impl DebugRenderer {
         self.vertices.clear();
         self.line_indices.clear();
 
-        let mut i = 0;
-        for line in drawing_context.lines.iter() {
-            let color = line.color.into();
-            self.vertices.push(Vertex {
-                position: line.begin,
-                color,
-            });
-            self.vertices.push(Vertex {
-                position: line.end,
-                color,
-            });
-            self.line_indices.push([i, i + 1]);
-            i += 2;
+        // Depth-tested lines go first, lines that are drawn on top of everything go after them.
+        for line in drawing_context.iter_lines(true) {
+            self.push_line(line);
+        }
+        let depth_tested_count = self.line_indices.len();
+        for line in drawing_context.iter_lines(false) {
+            self.push_line(line);
         }
+        let overlay_count = self.line_indices.len() - depth_tested_count;
+
         self.geometry.set_buffer_data(state, 0, &self.vertices);
         self.geometry.bind(state).set_lines(&self.line_indices);
 
-        statistics += framebuffer.draw(
-            &self.geometry,
-            state,
-            viewport,
-            &self.shader.program,
-            &DrawParameters {
-                cull_face: None,
-                color_write: Default::default(),
-                depth_write: false,
-                stencil_test: None,
-                depth_test: true,
-                blend: None,
-                stencil_op: Default::default(),
-            },
-            ElementRange::Full,
-            |mut program_binding| {
-                program_binding
-                    .set_matrix4(&self.shader.wvp_matrix, &camera.view_projection_matrix());
-            },
-        )?;
-
-        statistics.draw_calls += 1;
+        for (depth_test, offset, count) in [
+            (true, 0, depth_tested_count),
+            (false, depth_tested_count, overlay_count),
+        ] {
+            if count == 0 {
+                continue;
+            }
+
+            statistics += framebuffer.draw(
+                &self.geometry,
+                state,
+                viewport,
+                &self.shader.program,
+                &DrawParameters {
+                    cull_face: None,
+                    color_write: Default::default(),
+                    depth_write: false,
+                    stencil_test: None,
+                    depth_test,
+                    blend: None,
+                    stencil_op: Default::default(),
+                },
+                ElementRange::Specific { offset, count },
+                |mut program_binding| {
+                    program_binding
+                        .set_matrix4(&self.shader.wvp_matrix, &camera.view_projection_matrix());
+                },
+            )?;
+
+            statistics.draw_calls += 1;
+        }
 
         Ok(statistics)
     }
+
+    /// Projects labels of the drawing context on screen and puts them in the internal user interface
+    /// drawing context, which then could be rendered by the user interface renderer. Returns `None`
+    /// if there's nothing to draw.
+    pub(crate) fn prepare_labels(
+        &mut self,
+        drawing_context: &SceneDrawingContext,
+        camera: &Camera,
+        frame_size: Vector2<f32>,
+    ) -> Option<&DrawingContext> {
+        scope_profile!();
+
+        self.labels.clear();
+
+        let mut labels = drawing_context.iter_labels().peekable();
+        labels.peek()?;
+
+        // Built-in font is created on demand, most of the scenes do not have any labels.
+        let font = self
+            .font
+            .get_or_insert_with(|| SharedFont::new(FontBuilder::new().build_builtin().unwrap()))
+            .clone();
+
+        let viewport = camera.viewport_pixels(frame_size);
+        let clip_bounds = Rect::new(
+            viewport.x() as f32,
+            viewport.y() as f32,
+            viewport.w() as f32,
+            viewport.h() as f32,
+        );
+        for label in labels {
+            // Labels behind the camera are hidden.
+            if let Some(position) = camera.project(label.position, frame_size) {
+                let mut text = FormattedTextBuilder::new(font.clone())
+                    .with_text(label.text.clone())
+                    .with_brush(Brush::Solid(label.color))
+                    .with_constraint(frame_size)
+                    .with_shadow(true)
+                    .build();
+                let size = text.build();
+                // Center the text at the anchor point.
+                self.labels
+                    .draw_text(clip_bounds, position - size.scale(0.5), &text);
+            }
+        }
+
+        Some(&self.labels)
+    }
 }
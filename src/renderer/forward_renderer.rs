@@ -5,6 +5,9 @@
 //! This renderer eventually will replace deferred renderer, because deferred renderer is too restrictive.
 //! For now it is used **only** to render transparent meshes (or any other mesh that has Forward render
 //! path).
+//!
+//! When order-independent transparency is enabled, meshes which shaders have `ForwardOIT` render pass
+//! are drawn into the accumulation buffer using this pass, the rest use `Forward` pass as usual.
 
 use crate::{
     core::{math::Rect, scope_profile, sstorage::ImmutableString},
@@ -13,9 +16,12 @@ use crate::{
         batch::RenderDataBatchStorage,
         cache::{shader::ShaderCache, texture::TextureCache},
         framework::{
-            error::FrameworkError, framebuffer::FrameBuffer, gpu_texture::GpuTexture,
+            error::FrameworkError,
+            framebuffer::{DrawParameters, FrameBuffer},
+            gpu_texture::GpuTexture,
             state::PipelineState,
         },
+        oit,
         storage::MatrixStorageCache,
         GeometryCache, MaterialContext, QualitySettings, RenderPassStatistics,
     },
@@ -25,6 +31,19 @@ use std::{cell::RefCell, rc::Rc};
 
 pub(crate) struct ForwardRenderer {
     render_pass_name: ImmutableString,
+    oit_render_pass_name: ImmutableString,
+}
+
+/// Defines which batches are drawn and which render pass is used.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub(crate) enum ForwardPassKind {
+    /// Every batch is drawn using `Forward` render pass.
+    Sorted,
+    /// Only batches with `ForwardOIT` render pass are drawn using it, the frame buffer must be an
+    /// accumulation buffer of order-independent transparency.
+    OitAccumulation,
+    /// Only batches without `ForwardOIT` render pass are drawn using `Forward` render pass.
+    SortedFallback,
 }
 
 pub(crate) struct ForwardRenderContext<'a, 'b> {
@@ -42,12 +61,14 @@ pub(crate) struct ForwardRenderContext<'a, 'b> {
     pub black_dummy: Rc<RefCell<GpuTexture>>,
     pub volume_dummy: Rc<RefCell<GpuTexture>>,
     pub matrix_storage: &'a mut MatrixStorageCache,
+    pub pass_kind: ForwardPassKind,
 }
 
 impl ForwardRenderer {
     pub(crate) fn new() -> Self {
         Self {
             render_pass_name: ImmutableString::new("Forward"),
+            oit_render_pass_name: ImmutableString::new("ForwardOIT"),
         }
     }
 
@@ -74,8 +95,11 @@ impl ForwardRenderer {
             black_dummy,
             volume_dummy,
             matrix_storage,
+            pass_kind,
         } = args;
 
+        let oit_blend = oit::accumulation_blend_parameters();
+
         let initial_view_projection = camera.view_projection_matrix();
 
         for batch in batch_storage
@@ -92,10 +116,39 @@ impl ForwardRenderer {
                 .as_ref()
                 .and_then(|c| c.blend_shape_storage.clone());
 
-            if let Some(render_pass) = shader_cache
-                .get(state, material.shader())
-                .and_then(|shader_set| shader_set.render_passes.get(&self.render_pass_name))
-            {
+            let shader_set = if let Some(shader_set) = shader_cache.get(state, material.shader()) {
+                shader_set
+            } else {
+                continue;
+            };
+            let oit_render_pass = shader_set.render_passes.get(&self.oit_render_pass_name);
+            let render_pass = match pass_kind {
+                ForwardPassKind::Sorted => shader_set.render_passes.get(&self.render_pass_name),
+                ForwardPassKind::OitAccumulation => oit_render_pass,
+                ForwardPassKind::SortedFallback => {
+                    if oit_render_pass.is_some() {
+                        None
+                    } else {
+                        shader_set.render_passes.get(&self.render_pass_name)
+                    }
+                }
+            };
+
+            if let Some(render_pass) = render_pass {
+                // Accumulation is correct only with specific blending and without depth writes, so
+                // the draw parameters of the pass are adjusted.
+                let oit_draw_params;
+                let draw_params = if pass_kind == ForwardPassKind::OitAccumulation {
+                    oit_draw_params = DrawParameters {
+                        depth_write: false,
+                        blend: Some(oit_blend.clone()),
+                        ..render_pass.draw_params.clone()
+                    };
+                    &oit_draw_params
+                } else {
+                    &render_pass.draw_params
+                };
+
                 for instance in batch.instances.iter() {
                     let view_projection = if instance.depth_offset != 0.0 {
                         let mut projection = camera.projection_matrix();
@@ -110,7 +163,7 @@ impl ForwardRenderer {
                         state,
                         viewport,
                         &render_pass.program,
-                        &render_pass.draw_params,
+                        draw_params,
                         instance.element_range,
                         |mut program_binding| {
                             apply_material(MaterialContext {
@@ -126,6 +179,8 @@ impl ForwardRenderer {
                                 light_position: &Default::default(),
                                 blend_shapes_storage: blend_shapes_storage.as_ref(),
                                 blend_shapes_weights: &instance.blend_shapes_weights,
+                                view_projection_matrix: &view_projection,
+                                instance_matrices: &[],
                                 normal_dummy: normal_dummy.clone(),
                                 white_dummy: white_dummy.clone(),
                                 black_dummy: black_dummy.clone(),
@@ -214,6 +214,48 @@ impl FrameBuffer {
         self
     }
 
+    /// Reads pixels of the given rectangle of the first color attachment as RGBA32F values.
+    pub fn read_pixels_rgba32f(&self, state: &mut PipelineState, rect: Rect<i32>) -> Vec<u8> {
+        let mut bytes = vec![0u8; (rect.w() * rect.h()) as usize * 4 * std::mem::size_of::<f32>()];
+
+        unsafe {
+            state.set_framebuffer(self.fbo);
+            state.gl.read_buffer(glow::COLOR_ATTACHMENT0);
+            state.gl.read_pixels(
+                rect.x(),
+                rect.y(),
+                rect.w(),
+                rect.h(),
+                glow::RGBA,
+                glow::FLOAT,
+                glow::PixelPackData::Slice(&mut bytes),
+            );
+        }
+
+        bytes
+    }
+
+    /// Reads pixels of the given rectangle of the first color attachment as RGBA8 values.
+    pub fn read_pixels_rgba8(&self, state: &mut PipelineState, rect: Rect<i32>) -> Vec<u8> {
+        let mut bytes = vec![0u8; (rect.w() * rect.h()) as usize * 4];
+
+        unsafe {
+            state.set_framebuffer(self.fbo);
+            state.gl.read_buffer(glow::COLOR_ATTACHMENT0);
+            state.gl.read_pixels(
+                rect.x(),
+                rect.y(),
+                rect.w(),
+                rect.h(),
+                glow::RGBA,
+                glow::UNSIGNED_BYTE,
+                glow::PixelPackData::Slice(&mut bytes),
+            );
+        }
+
+        bytes
+    }
+
     /// None is possible only for back buffer.
     pub fn id(&self) -> Option<glow::Framebuffer> {
         self.fbo
@@ -390,7 +432,9 @@ impl Drop for FrameBuffer {
     fn drop(&mut self) {
         unsafe {
             if let Some(id) = self.fbo {
-                (*self.state).gl.delete_framebuffer(id);
+                if !(*self.state).is_context_lost() {
+                    (*self.state).gl.delete_framebuffer(id);
+                }
             }
         }
     }
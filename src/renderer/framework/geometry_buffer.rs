@@ -21,7 +21,9 @@ struct NativeBuffer {
 impl Drop for NativeBuffer {
     fn drop(&mut self) {
         unsafe {
-            (*self.state).gl.delete_buffer(self.id);
+            if !(*self.state).is_context_lost() {
+                (*self.state).gl.delete_buffer(self.id);
+            }
         }
     }
 }
@@ -363,10 +365,12 @@ impl Drop for GeometryBuffer {
         unsafe {
             self.buffers.clear();
 
-            (*self.state).gl.delete_buffer(self.element_buffer_object);
-            (*self.state)
-                .gl
-                .delete_vertex_array(self.vertex_array_object);
+            if !(*self.state).is_context_lost() {
+                (*self.state).gl.delete_buffer(self.element_buffer_object);
+                (*self.state)
+                    .gl
+                    .delete_vertex_array(self.vertex_array_object);
+            }
         }
     }
 }
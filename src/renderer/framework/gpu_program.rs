@@ -34,6 +34,9 @@ pub enum BuiltInUniform {
     BlendShapesStorage,
     BlendShapesWeights,
     BlendShapesCount,
+    ViewProjectionMatrix,
+    UseInstancing,
+    InstanceMatrices,
     // Must be last.
     Count,
 }
@@ -438,6 +441,12 @@ fn fetch_built_in_uniform_locations(
         fetch_uniform_location(state, program, "fyrox_blendShapesWeights");
     locations[BuiltInUniform::BlendShapesCount as usize] =
         fetch_uniform_location(state, program, "fyrox_blendShapesCount");
+    locations[BuiltInUniform::ViewProjectionMatrix as usize] =
+        fetch_uniform_location(state, program, "fyrox_viewProjectionMatrix");
+    locations[BuiltInUniform::UseInstancing as usize] =
+        fetch_uniform_location(state, program, "fyrox_useInstancing");
+    locations[BuiltInUniform::InstanceMatrices as usize] =
+        fetch_uniform_location(state, program, "fyrox_instanceMatrices");
 
     locations
 }
@@ -533,6 +542,13 @@ impl GpuProgram {
             .ok_or_else(|| FrameworkError::UnableToFindShaderUniform(name.deref().to_owned()))
     }
 
+    /// Returns `true` if the program is able to fetch world matrices of instances from a matrix
+    /// storage, which means that it can be used for instanced rendering.
+    pub fn supports_instancing(&self) -> bool {
+        self.built_in_uniform_locations[BuiltInUniform::InstanceMatrices as usize].is_some()
+            && self.built_in_uniform_locations[BuiltInUniform::UseInstancing as usize].is_some()
+    }
+
     pub fn bind<'a, 'b>(&'b self, state: &'a mut PipelineState) -> GpuProgramBinding<'a, 'b> {
         state.set_program(Some(self.id));
         GpuProgramBinding {
@@ -546,7 +562,9 @@ impl GpuProgram {
 impl Drop for GpuProgram {
     fn drop(&mut self) {
         unsafe {
-            (*self.state).gl.delete_program(self.id);
+            if !(*self.state).is_context_lost() {
+                (*self.state).gl.delete_program(self.id);
+            }
         }
     }
 }
@@ -576,6 +576,32 @@ impl<'a> TextureBinding<'a> {
         self
     }
 
+    /// Generates full mip chain from the first mip level of the texture.
+    pub fn generate_mip_maps(self) -> Self {
+        let size = match self.texture.kind {
+            GpuTextureKind::Line { length } => length,
+            GpuTextureKind::Rectangle { width, height }
+            | GpuTextureKind::Cube { width, height } => width.max(height),
+            GpuTextureKind::Volume {
+                width,
+                height,
+                depth,
+            } => width.max(height).max(depth),
+        };
+
+        unsafe {
+            let target = self.texture.kind.gl_texture_target();
+
+            self.state.gl.tex_parameter_i32(
+                target,
+                glow::TEXTURE_MAX_LEVEL,
+                (size.max(1) as f32).log2() as i32,
+            );
+            self.state.gl.generate_mipmap(target);
+        }
+        self
+    }
+
     pub fn set_border_color(self, #[allow(unused_variables)] color: Color) -> Self {
         #[cfg(not(target_arch = "wasm32"))]
         unsafe {
@@ -1119,7 +1145,9 @@ impl GpuTexture {
 impl Drop for GpuTexture {
     fn drop(&mut self) {
         unsafe {
-            (*self.state).gl.delete_texture(self.texture);
+            if !(*self.state).is_context_lost() {
+                (*self.state).gl.delete_texture(self.texture);
+            }
         }
     }
 }
@@ -35,6 +35,15 @@ float S_LightDistanceAttenuation(float distance, float radius)
     return attenuation;
 }
 
+// Returns attenuation from a lookup texture (N x 1) of a custom falloff curve.
+float S_LightFalloffCurve(sampler2D falloffTexture, float distance, float radius)
+{
+    float size = float(textureSize(falloffTexture, 0).x);
+    float t = clamp(distance / radius, 0.0, 1.0);
+    // Sample at texel centers, so the first and the last texels match the ends of the curve.
+    return texture(falloffTexture, vec2((t * (size - 1.0) + 0.5) / size, 0.5)).r;
+}
+
 // Projects world space position (typical use case) by given matrix.
 vec3 S_Project(vec3 worldPosition, mat4 matrix)
 {
@@ -365,4 +374,11 @@ TBlendShapeOffsets S_FetchBlendShapeOffsets(in sampler3D storage, int vertexInde
     vec3 normal = texelFetch(storage, ivec3(pos.x + 1, pos.y, pos.z), 0).xyz;
     vec3 tangent = texelFetch(storage, ivec3(pos.x + 2, pos.y, pos.z), 0).xyz;
     return TBlendShapeOffsets(position, normal, tangent);
-}
\ No newline at end of file
+}
+
+// Weight of a fragment for weighted blended order-independent transparency (McGuire and Bavoil,
+// 2013). Closer and more opaque fragments have higher weight. `depth` is a depth of the fragment
+// in [0; 1] range (gl_FragCoord.z).
+float S_OITWeight(float depth, float alpha) {
+    return alpha * max(0.01, 3000.0 * pow(1.0 - depth, 3.0));
+}
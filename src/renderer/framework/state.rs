@@ -10,7 +10,10 @@ use crate::{
 };
 use glow::{Framebuffer, HasContext};
 use serde::Deserialize;
-use std::fmt::{Display, Formatter};
+use std::{
+    cell::Cell,
+    fmt::{Display, Formatter},
+};
 use strum_macros::{AsRefStr, EnumString, EnumVariantNames};
 
 #[derive(Debug, Default, Copy, Clone)]
@@ -251,6 +254,7 @@ pub struct PipelineState {
 
     frame_statistics: PipelineStatistics,
     gl_kind: GlKind,
+    context_lost: Cell<bool>,
 }
 
 #[derive(Copy, Clone)]
@@ -479,6 +483,7 @@ impl PipelineState {
             frame_statistics: Default::default(),
             blend_equation: Default::default(),
             gl_kind,
+            context_lost: Cell::new(false),
         }
     }
 
@@ -486,6 +491,19 @@ impl PipelineState {
         self.gl_kind
     }
 
+    /// Returns `true` if the context was lost (for example because of a driver reset or when a
+    /// browser decided to release WebGL context of a background tab). Every object created using
+    /// a lost context is invalid, and it won't be deleted on drop.
+    pub fn is_context_lost(&self) -> bool {
+        self.context_lost.get()
+    }
+
+    /// Marks the context as lost. It is used when the context is replaced with a new one, so
+    /// objects of the previous context won't try to delete anything in the new one.
+    pub fn set_context_lost(&self) {
+        self.context_lost.set(true);
+    }
+
     pub fn set_polygon_fill_mode(
         &mut self,
         polygon_face: PolygonFace,
@@ -890,11 +908,22 @@ impl PipelineState {
         self.frame_statistics
     }
 
-    /// Checks for errors, returns true if any error has occurred.
+    /// Checks for errors, returns true if any error has occurred. Context loss is reported as an
+    /// error too, use [`Self::is_context_lost`] to distinguish it. Context loss is detected only if
+    /// the context was created with robustness (`GL_KHR_robustness`) or by WebGL.
     pub fn check_error(&self) -> bool {
+        // There are no constants for these codes in glow.
+        const CONTEXT_LOST: u32 = 0x0507;
+        const CONTEXT_LOST_WEBGL: u32 = 0x9242;
+
         unsafe {
             let error_code = self.gl.get_error();
-            if error_code != glow::NO_ERROR {
+            if error_code == CONTEXT_LOST || error_code == CONTEXT_LOST_WEBGL {
+                if !self.context_lost.replace(true) {
+                    Log::warn("Graphics context was lost!");
+                }
+                true
+            } else if error_code != glow::NO_ERROR {
                 let code = match error_code {
                     glow::INVALID_ENUM => "GL_INVALID_ENUM",
                     glow::INVALID_VALUE => "GL_INVALID_VALUE",
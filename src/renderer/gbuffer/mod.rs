@@ -315,16 +315,31 @@ impl GBuffer {
                 .get(state, material.shader())
                 .and_then(|shader_set| shader_set.render_passes.get(&self.render_pass_name))
             {
-                for instance in batch.instances.iter() {
-                    let apply_uniforms = |mut program_binding: GpuProgramBinding| {
-                        let view_projection = if instance.depth_offset != 0.0 {
-                            let mut projection = camera.projection_matrix();
-                            projection[14] -= instance.depth_offset;
-                            projection * camera.view_matrix()
-                        } else {
-                            initial_view_projection
-                        };
+                let instance_matrices = if batch.is_instancing_possible()
+                    && render_pass.program.supports_instancing()
+                {
+                    batch.instance_matrices()
+                } else {
+                    Default::default()
+                };
+
+                // Instanced batches are drawn at once using the data of the first instance.
+                let instances = if instance_matrices.is_empty() {
+                    &batch.instances[..]
+                } else {
+                    &batch.instances[..1]
+                };
+
+                for instance in instances.iter() {
+                    let view_projection = if instance.depth_offset != 0.0 {
+                        let mut projection = camera.projection_matrix();
+                        projection[14] -= instance.depth_offset;
+                        projection * camera.view_matrix()
+                    } else {
+                        initial_view_projection
+                    };
 
+                    let apply_uniforms = |mut program_binding: GpuProgramBinding| {
                         apply_material(MaterialContext {
                             material: &material,
                             program_binding: &mut program_binding,
@@ -339,6 +354,8 @@ impl GBuffer {
                             light_position: &Default::default(),
                             blend_shapes_storage: blend_shapes_storage.as_ref(),
                             blend_shapes_weights: &instance.blend_shapes_weights,
+                            view_projection_matrix: &view_projection,
+                            instance_matrices: &instance_matrices,
                             normal_dummy: normal_dummy.clone(),
                             white_dummy: white_dummy.clone(),
                             black_dummy: black_dummy.clone(),
@@ -347,15 +364,28 @@ impl GBuffer {
                         });
                     };
 
-                    statistics += self.framebuffer.draw(
-                        geometry,
-                        state,
-                        viewport,
-                        &render_pass.program,
-                        &render_pass.draw_params,
-                        instance.element_range,
-                        apply_uniforms,
-                    )?;
+                    if instance_matrices.is_empty() {
+                        statistics += self.framebuffer.draw(
+                            geometry,
+                            state,
+                            viewport,
+                            &render_pass.program,
+                            &render_pass.draw_params,
+                            instance.element_range,
+                            apply_uniforms,
+                        )?;
+                    } else {
+                        statistics += self.framebuffer.draw_instances(
+                            instance_matrices.len(),
+                            geometry,
+                            state,
+                            viewport,
+                            &render_pass.program,
+                            &render_pass.draw_params,
+                            apply_uniforms,
+                        );
+                        statistics.instanced_draw_calls += 1;
+                    }
                 }
             }
         }
@@ -369,7 +399,11 @@ impl GBuffer {
         // for rendering. We'll render in the G-Buffer, but depth will be used from final frame, since
         // decals do not modify depth (only diffuse and normal maps).
         let unit_cube = &self.cube;
-        for decal in graph.linear_iter().filter_map(|n| n.cast::<Decal>()) {
+        for decal in graph
+            .linear_iter()
+            .filter_map(|n| n.cast::<Decal>())
+            .filter(|d| d.is_globally_enabled())
+        {
             let shader = &self.decal_shader;
             let program = &self.decal_shader.program;
 
@@ -1,8 +1,11 @@
 use crate::core::sstorage::ImmutableString;
-use crate::renderer::framework::{
-    error::FrameworkError,
-    gpu_program::{GpuProgram, UniformLocation},
-    state::PipelineState,
+use crate::renderer::{
+    framework::{
+        error::FrameworkError,
+        gpu_program::{GpuProgram, UniformLocation},
+        state::PipelineState,
+    },
+    reflection_probe::MAX_VISIBLE_PROBES,
 };
 
 pub struct AmbientLightShader {
@@ -12,6 +15,22 @@ pub struct AmbientLightShader {
     pub ambient_color: UniformLocation,
     pub ao_sampler: UniformLocation,
     pub ambient_texture: UniformLocation,
+    pub depth_texture: UniformLocation,
+    pub normal_texture: UniformLocation,
+    pub material_texture: UniformLocation,
+    pub inv_view_proj_matrix: UniformLocation,
+    pub camera_position: UniformLocation,
+    pub environment_enabled: UniformLocation,
+    pub environment_texture: UniformLocation,
+    pub environment_linear: UniformLocation,
+    pub irradiance_enabled: UniformLocation,
+    pub irradiance_texture: UniformLocation,
+    pub probe_count: UniformLocation,
+    pub probe_textures: Vec<UniformLocation>,
+    pub probe_capture_positions: UniformLocation,
+    pub probe_bounds_min: UniformLocation,
+    pub probe_bounds_max: UniformLocation,
+    pub probe_spheres: UniformLocation,
 }
 
 impl AmbientLightShader {
@@ -30,6 +49,43 @@ impl AmbientLightShader {
             ao_sampler: program.uniform_location(state, &ImmutableString::new("aoSampler"))?,
             ambient_texture: program
                 .uniform_location(state, &ImmutableString::new("ambientTexture"))?,
+            depth_texture: program
+                .uniform_location(state, &ImmutableString::new("depthTexture"))?,
+            normal_texture: program
+                .uniform_location(state, &ImmutableString::new("normalTexture"))?,
+            material_texture: program
+                .uniform_location(state, &ImmutableString::new("materialTexture"))?,
+            inv_view_proj_matrix: program
+                .uniform_location(state, &ImmutableString::new("invViewProj"))?,
+            camera_position: program
+                .uniform_location(state, &ImmutableString::new("cameraPosition"))?,
+            environment_enabled: program
+                .uniform_location(state, &ImmutableString::new("environmentEnabled"))?,
+            environment_texture: program
+                .uniform_location(state, &ImmutableString::new("environmentTexture"))?,
+            environment_linear: program
+                .uniform_location(state, &ImmutableString::new("environmentLinear"))?,
+            irradiance_enabled: program
+                .uniform_location(state, &ImmutableString::new("irradianceEnabled"))?,
+            irradiance_texture: program
+                .uniform_location(state, &ImmutableString::new("irradianceTexture"))?,
+            probe_count: program.uniform_location(state, &ImmutableString::new("probeCount"))?,
+            probe_textures: (0..MAX_VISIBLE_PROBES)
+                .map(|i| {
+                    program.uniform_location(
+                        state,
+                        &ImmutableString::new(format!("probeTexture{}", i)),
+                    )
+                })
+                .collect::<Result<_, _>>()?,
+            probe_capture_positions: program
+                .uniform_location(state, &ImmutableString::new("probeCapturePositions"))?,
+            probe_bounds_min: program
+                .uniform_location(state, &ImmutableString::new("probeBoundsMin"))?,
+            probe_bounds_max: program
+                .uniform_location(state, &ImmutableString::new("probeBoundsMax"))?,
+            probe_spheres: program
+                .uniform_location(state, &ImmutableString::new("probeSpheres"))?,
             program,
         })
     }
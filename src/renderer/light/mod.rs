@@ -1,6 +1,6 @@
 use crate::{
     core::{
-        algebra::{Matrix4, Point3, Vector2, Vector3},
+        algebra::{Matrix3, Matrix4, Point3, Vector2, Vector3, Vector4},
         color::Color,
         math::{frustum::Frustum, Matrix4Ext, Rect, TriangleDefinition},
         scope_profile,
@@ -8,11 +8,12 @@ use crate::{
     renderer::{
         cache::shader::ShaderCache,
         flat_shader::FlatShader,
+        fog::FogShader,
         framework::{
             error::FrameworkError,
             framebuffer::{BlendParameters, CullFace, DrawParameters, FrameBuffer},
             geometry_buffer::{ElementRange, GeometryBuffer, GeometryBufferKind},
-            gpu_texture::GpuTexture,
+            gpu_texture::{GpuTexture, GpuTextureKind},
             state::{
                 BlendFactor, BlendFunc, ColorMask, CompareFunc, PipelineState, StencilAction,
                 StencilFunc, StencilOp,
@@ -24,6 +25,7 @@ use crate::{
             point::PointLightShader, spot::SpotLightShader,
         },
         light_volume::LightVolumeRenderer,
+        reflection_probe::{ReflectionProbeInfo, MAX_VISIBLE_PROBES},
         shadow::{
             csm::{CsmRenderContext, CsmRenderer},
             point::{PointShadowMapRenderContext, PointShadowMapRenderer},
@@ -36,7 +38,11 @@ use crate::{
     },
     scene::{
         camera::Camera,
-        light::{directional::DirectionalLight, point::PointLight, spot::SpotLight},
+        environment::FogKind,
+        light::{
+            directional::DirectionalLight, falloff::LightFalloffCurve, point::PointLight,
+            spot::SpotLight,
+        },
         mesh::{
             buffer::{TriangleBuffer, VertexBuffer},
             surface::SurfaceData,
@@ -65,6 +71,8 @@ pub struct LightingStatistics {
     pub spot_lights_rendered: usize,
     pub spot_shadow_maps_rendered: usize,
     pub directional_lights_rendered: usize,
+    pub cookies_rendered: usize,
+    pub falloff_curves_rendered: usize,
 }
 
 impl AddAssign for LightingStatistics {
@@ -75,6 +83,8 @@ impl AddAssign for LightingStatistics {
         self.spot_shadow_maps_rendered += rhs.spot_shadow_maps_rendered;
         self.directional_lights_rendered += rhs.directional_lights_rendered;
         self.csm_rendered += rhs.csm_rendered;
+        self.cookies_rendered += rhs.cookies_rendered;
+        self.falloff_curves_rendered += rhs.falloff_curves_rendered;
     }
 }
 
@@ -88,17 +98,35 @@ impl Display for LightingStatistics {
             \tDirectional Lights: {}\n\
             \tPoint Shadow Maps: {}\n\
             \tSpot Shadow Maps: {}\n\
-            \tSpot Shadow Maps: {}\n",
+            \tSpot Shadow Maps: {}\n\
+            \tCookies: {}\n\
+            \tFalloff Curves: {}\n",
             self.point_lights_rendered,
             self.spot_lights_rendered,
             self.directional_lights_rendered,
             self.point_shadow_maps_rendered,
             self.spot_shadow_maps_rendered,
-            self.csm_rendered
+            self.csm_rendered,
+            self.cookies_rendered,
+            self.falloff_curves_rendered
         )
     }
 }
 
+// Returns the lookup texture of the falloff curve, if the curve is set and not empty.
+fn falloff_lut(
+    state: &mut PipelineState,
+    textures: &mut TextureCache,
+    curve: Option<&LightFalloffCurve>,
+    white_dummy: &Rc<RefCell<GpuTexture>>,
+) -> (bool, Rc<RefCell<GpuTexture>>) {
+    curve
+        .filter(|curve| !curve.points().is_empty())
+        .and_then(|curve| textures.get(state, &curve.lut_texture()))
+        .map(|lut| (true, lut))
+        .unwrap_or_else(|| (false, white_dummy.clone()))
+}
+
 pub struct DeferredLightRenderer {
     pub ssao_renderer: ScreenSpaceAmbientOcclusionRenderer,
     spot_light_shader: SpotLightShader,
@@ -110,6 +138,7 @@ pub struct DeferredLightRenderer {
     skybox: GeometryBuffer,
     flat_shader: FlatShader,
     skybox_shader: SkyboxShader,
+    fog_shader: FogShader,
     spot_shadow_map_renderer: SpotShadowMapRenderer,
     point_shadow_map_renderer: PointShadowMapRenderer,
     csm_renderer: CsmRenderer,
@@ -131,6 +160,8 @@ pub(crate) struct DeferredRendererContext<'a> {
     pub white_dummy: Rc<RefCell<GpuTexture>>,
     pub black_dummy: Rc<RefCell<GpuTexture>>,
     pub volume_dummy: Rc<RefCell<GpuTexture>>,
+    pub environment_dummy: Rc<RefCell<GpuTexture>>,
+    pub reflection_probes: &'a [ReflectionProbeInfo],
     pub matrix_storage: &'a mut MatrixStorageCache,
 }
 
@@ -219,6 +250,7 @@ impl DeferredLightRenderer {
             ),
             flat_shader: FlatShader::new(state)?,
             skybox_shader: SkyboxShader::new(state)?,
+            fog_shader: FogShader::new(state)?,
             spot_shadow_map_renderer: SpotShadowMapRenderer::new(
                 state,
                 settings.spot_shadow_map_size,
@@ -311,6 +343,8 @@ impl DeferredLightRenderer {
             frame_buffer,
             black_dummy,
             volume_dummy,
+            environment_dummy,
+            reflection_probes,
             matrix_storage,
         } = args;
 
@@ -347,42 +381,46 @@ impl DeferredLightRenderer {
             )?;
         }
 
-        // Render skybox (if any).
-        if let Some(skybox) = camera.skybox_ref() {
+        // Render skybox (if any). Camera's own skybox has priority over the skybox of the scene.
+        let skybox = camera.skybox_ref().or(scene.environment.skybox.as_ref());
+        let environment = skybox
+            .and_then(|skybox| skybox.cubemap_ref())
+            .and_then(|cube_map| textures.get(state, cube_map));
+        let environment_linear = skybox.map_or(false, |skybox| skybox.is_hdr());
+        let irradiance = skybox
+            .and_then(|skybox| skybox.irradiance_ref())
+            .and_then(|irradiance| textures.get(state, irradiance));
+        if let Some(gpu_texture) = environment.as_ref() {
             let size = camera.projection().z_far() / 2.0f32.sqrt();
             let scale = Matrix4::new_scaling(size);
             let wvp = Matrix4::new_translation(&camera.global_position()) * scale;
 
-            if let Some(gpu_texture) = skybox
-                .cubemap_ref()
-                .and_then(|cube_map| textures.get(state, cube_map))
-            {
-                let shader = &self.skybox_shader;
-                pass_stats += frame_buffer.draw(
-                    &self.skybox,
-                    state,
-                    viewport,
-                    &shader.program,
-                    &DrawParameters {
-                        cull_face: None,
-                        color_write: Default::default(),
-                        depth_write: false,
-                        stencil_test: None,
-                        depth_test: false,
-                        blend: None,
-                        stencil_op: Default::default(),
-                    },
-                    ElementRange::Specific {
-                        offset: 0,
-                        count: 12,
-                    },
-                    |mut program_binding| {
-                        program_binding
-                            .set_texture(&shader.cubemap_texture, &gpu_texture)
-                            .set_matrix4(&shader.wvp_matrix, &(view_projection * wvp));
-                    },
-                )?;
-            }
+            let shader = &self.skybox_shader;
+            pass_stats += frame_buffer.draw(
+                &self.skybox,
+                state,
+                viewport,
+                &shader.program,
+                &DrawParameters {
+                    cull_face: None,
+                    color_write: Default::default(),
+                    depth_write: false,
+                    stencil_test: None,
+                    depth_test: false,
+                    blend: None,
+                    stencil_op: Default::default(),
+                },
+                ElementRange::Specific {
+                    offset: 0,
+                    count: 12,
+                },
+                |mut program_binding| {
+                    program_binding
+                        .set_texture(&shader.cubemap_texture, gpu_texture)
+                        .set_bool(&shader.linear_color_space, environment_linear)
+                        .set_matrix4(&shader.wvp_matrix, &(view_projection * wvp));
+                },
+            )?;
         }
 
         // Ambient light.
@@ -393,6 +431,31 @@ impl DeferredLightRenderer {
         let gbuffer_ambient_map = gbuffer.ambient_texture();
         let ao_map = self.ssao_renderer.ao_map();
 
+        // Every probe sampler must have a cube map bound, unused ones get a dummy.
+        let probe_textures = (0..MAX_VISIBLE_PROBES)
+            .map(|i| {
+                reflection_probes
+                    .get(i)
+                    .map_or_else(|| environment_dummy.clone(), |probe| probe.cube_map.clone())
+            })
+            .collect::<Vec<_>>();
+        let probe_capture_positions = reflection_probes
+            .iter()
+            .map(|probe| probe.capture_position)
+            .collect::<Vec<_>>();
+        let probe_bounds_min = reflection_probes
+            .iter()
+            .map(|probe| probe.bounds.min)
+            .collect::<Vec<_>>();
+        let probe_bounds_max = reflection_probes
+            .iter()
+            .map(|probe| probe.bounds.max)
+            .collect::<Vec<_>>();
+        let probe_spheres = reflection_probes
+            .iter()
+            .map(|probe| probe.sphere)
+            .collect::<Vec<Vector4<f32>>>();
+
         pass_stats += frame_buffer.draw(
             &self.quad,
             state,
@@ -430,7 +493,70 @@ impl DeferredLightRenderer {
                     .set_texture(
                         &self.ambient_light_shader.ambient_texture,
                         &gbuffer_ambient_map,
-                    );
+                    )
+                    .set_texture(&self.ambient_light_shader.depth_texture, &gbuffer_depth_map)
+                    .set_texture(
+                        &self.ambient_light_shader.normal_texture,
+                        &gbuffer_normal_map,
+                    )
+                    .set_texture(
+                        &self.ambient_light_shader.material_texture,
+                        &gbuffer_material_map,
+                    )
+                    .set_matrix4(
+                        &self.ambient_light_shader.inv_view_proj_matrix,
+                        &inv_view_projection,
+                    )
+                    .set_vector3(
+                        &self.ambient_light_shader.camera_position,
+                        &camera_global_position,
+                    )
+                    .set_bool(
+                        &self.ambient_light_shader.environment_enabled,
+                        environment.is_some(),
+                    )
+                    .set_texture(
+                        &self.ambient_light_shader.environment_texture,
+                        environment.as_ref().unwrap_or(&environment_dummy),
+                    )
+                    .set_bool(
+                        &self.ambient_light_shader.environment_linear,
+                        environment_linear,
+                    )
+                    .set_bool(
+                        &self.ambient_light_shader.irradiance_enabled,
+                        irradiance.is_some(),
+                    )
+                    .set_texture(
+                        &self.ambient_light_shader.irradiance_texture,
+                        irradiance.as_ref().unwrap_or(&environment_dummy),
+                    )
+                    .set_i32(
+                        &self.ambient_light_shader.probe_count,
+                        reflection_probes.len().min(MAX_VISIBLE_PROBES) as i32,
+                    )
+                    .set_vector3_slice(
+                        &self.ambient_light_shader.probe_capture_positions,
+                        &probe_capture_positions,
+                    )
+                    .set_vector3_slice(
+                        &self.ambient_light_shader.probe_bounds_min,
+                        &probe_bounds_min,
+                    )
+                    .set_vector3_slice(
+                        &self.ambient_light_shader.probe_bounds_max,
+                        &probe_bounds_max,
+                    )
+                    .set_vector4_slice(&self.ambient_light_shader.probe_spheres, &probe_spheres);
+
+                for (location, texture) in self
+                    .ambient_light_shader
+                    .probe_textures
+                    .iter()
+                    .zip(probe_textures.iter())
+                {
+                    program_binding.set_texture(location, texture);
+                }
             },
         )?;
 
@@ -680,8 +806,12 @@ impl DeferredLightRenderer {
                     } else {
                         (false, white_dummy.clone())
                     };
+                let (falloff_enabled, falloff_texture) =
+                    falloff_lut(state, textures, spot_light.falloff_curve(), &white_dummy);
 
                 light_stats.spot_lights_rendered += 1;
+                light_stats.cookies_rendered += cookie_enabled as usize;
+                light_stats.falloff_curves_rendered += falloff_enabled as usize;
 
                 frame_buffer.draw(
                     quad,
@@ -728,6 +858,8 @@ impl DeferredLightRenderer {
                             )
                             .set_texture(&shader.cookie_texture, &cookie_texture)
                             .set_bool(&shader.cookie_enabled, cookie_enabled)
+                            .set_texture(&shader.falloff_texture, &falloff_texture)
+                            .set_bool(&shader.falloff_enabled, falloff_enabled)
                             .set_f32(&shader.shadow_bias, spot_light.shadow_bias())
                             .set_f32(
                                 &shader.light_intensity,
@@ -738,7 +870,32 @@ impl DeferredLightRenderer {
             } else if let Some(point_light) = light.cast::<PointLight>() {
                 let shader = &self.point_light_shader;
 
+                let mut cookie_kind = 0;
+                let mut cookie_texture = white_dummy.clone();
+                let mut cookie_cube_texture = environment_dummy.clone();
+                if let Some(cookie) = point_light
+                    .cookie_texture_ref()
+                    .and_then(|texture| textures.get(state, texture))
+                {
+                    if let GpuTextureKind::Cube { .. } = cookie.borrow().kind() {
+                        cookie_kind = 2;
+                        cookie_cube_texture = cookie.clone();
+                    } else {
+                        cookie_kind = 1;
+                        cookie_texture = cookie.clone();
+                    }
+                }
+                let light_inv_rotation = Matrix3::from_rows(&[
+                    light.side_vector().normalize().transpose(),
+                    light.up_vector().normalize().transpose(),
+                    light.look_vector().normalize().transpose(),
+                ]);
+                let (falloff_enabled, falloff_texture) =
+                    falloff_lut(state, textures, point_light.falloff_curve(), &white_dummy);
+
                 light_stats.point_lights_rendered += 1;
+                light_stats.cookies_rendered += (cookie_kind != 0) as usize;
+                light_stats.falloff_curves_rendered += falloff_enabled as usize;
 
                 frame_buffer.draw(
                     quad,
@@ -774,7 +931,13 @@ impl DeferredLightRenderer {
                                 &self
                                     .point_shadow_map_renderer
                                     .cascade_texture(cascade_index),
-                            );
+                            )
+                            .set_i32(&shader.cookie_kind, cookie_kind)
+                            .set_texture(&shader.cookie_texture, &cookie_texture)
+                            .set_texture(&shader.cookie_cube_texture, &cookie_cube_texture)
+                            .set_matrix3(&shader.light_inv_rotation, &light_inv_rotation)
+                            .set_texture(&shader.falloff_texture, &falloff_texture)
+                            .set_bool(&shader.falloff_enabled, falloff_enabled);
                     },
                 )?
             } else if let Some(directional) = light.cast::<DirectionalLight>() {
@@ -855,7 +1018,7 @@ impl DeferredLightRenderer {
                 unreachable!()
             };
 
-            if settings.light_scatter_enabled {
+            if settings.light_scatter_enabled && scene.environment.light_scatter_enabled {
                 pass_stats += self.light_volume.render_volume(
                     state,
                     light,
@@ -872,6 +1035,52 @@ impl DeferredLightRenderer {
             }
         }
 
+        // Apply fog on top of lit opaque geometry.
+        let fog = &scene.environment.fog;
+        if fog.enabled {
+            let shader = &self.fog_shader;
+            let (kind, start, end, density) = match fog.kind {
+                FogKind::Linear { start, end } => (0, start, end, 0.0),
+                FogKind::Exponential { density } => (1, 0.0, 0.0, density),
+                FogKind::ExponentialSquared { density } => (2, 0.0, 0.0, density),
+            };
+
+            pass_stats += frame_buffer.draw(
+                &self.quad,
+                state,
+                viewport,
+                &shader.program,
+                &DrawParameters {
+                    cull_face: None,
+                    color_write: Default::default(),
+                    depth_write: false,
+                    stencil_test: None,
+                    depth_test: false,
+                    blend: Some(BlendParameters {
+                        func: BlendFunc::new(BlendFactor::SrcAlpha, BlendFactor::OneMinusSrcAlpha),
+                        ..Default::default()
+                    }),
+                    stencil_op: Default::default(),
+                },
+                ElementRange::Full,
+                |mut program_binding| {
+                    program_binding
+                        .set_matrix4(&shader.wvp_matrix, &frame_matrix)
+                        .set_texture(&shader.depth_sampler, &gbuffer_depth_map)
+                        .set_matrix4(&shader.inv_view_proj_matrix, &inv_view_projection)
+                        .set_vector3(&shader.camera_position, &camera_global_position)
+                        .set_linear_color(&shader.fog_color, &fog.color)
+                        .set_i32(&shader.fog_kind, kind)
+                        .set_f32(&shader.fog_start, start)
+                        .set_f32(&shader.fog_end, end)
+                        .set_f32(&shader.fog_density, density)
+                        .set_bool(&shader.height_fog_enabled, fog.height_fog.enabled)
+                        .set_f32(&shader.height_fog_base, fog.height_fog.base_height)
+                        .set_f32(&shader.height_fog_falloff, fog.height_fog.falloff);
+                },
+            )?;
+        }
+
         Ok((pass_stats, light_stats))
     }
 }
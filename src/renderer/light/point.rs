@@ -13,6 +13,12 @@ pub struct PointLightShader {
     pub normal_sampler: UniformLocation,
     pub material_sampler: UniformLocation,
     pub point_shadow_texture: UniformLocation,
+    pub cookie_kind: UniformLocation,
+    pub cookie_texture: UniformLocation,
+    pub cookie_cube_texture: UniformLocation,
+    pub light_inv_rotation: UniformLocation,
+    pub falloff_enabled: UniformLocation,
+    pub falloff_texture: UniformLocation,
     pub shadows_enabled: UniformLocation,
     pub soft_shadows: UniformLocation,
     pub light_position: UniformLocation,
@@ -43,6 +49,17 @@ impl PointLightShader {
                 .uniform_location(state, &ImmutableString::new("materialTexture"))?,
             point_shadow_texture: program
                 .uniform_location(state, &ImmutableString::new("pointShadowTexture"))?,
+            cookie_kind: program.uniform_location(state, &ImmutableString::new("cookieKind"))?,
+            cookie_texture: program
+                .uniform_location(state, &ImmutableString::new("cookieTexture"))?,
+            cookie_cube_texture: program
+                .uniform_location(state, &ImmutableString::new("cookieCubeTexture"))?,
+            light_inv_rotation: program
+                .uniform_location(state, &ImmutableString::new("lightInvRotation"))?,
+            falloff_enabled: program
+                .uniform_location(state, &ImmutableString::new("falloffEnabled"))?,
+            falloff_texture: program
+                .uniform_location(state, &ImmutableString::new("falloffTexture"))?,
             shadows_enabled: program
                 .uniform_location(state, &ImmutableString::new("shadowsEnabled"))?,
             soft_shadows: program.uniform_location(state, &ImmutableString::new("softShadows"))?,
@@ -15,6 +15,8 @@ pub struct SpotLightShader {
     pub spot_shadow_texture: UniformLocation,
     pub cookie_enabled: UniformLocation,
     pub cookie_texture: UniformLocation,
+    pub falloff_enabled: UniformLocation,
+    pub falloff_texture: UniformLocation,
     pub light_view_proj_matrix: UniformLocation,
     pub shadows_enabled: UniformLocation,
     pub soft_shadows: UniformLocation,
@@ -54,6 +56,10 @@ impl SpotLightShader {
                 .uniform_location(state, &ImmutableString::new("cookieEnabled"))?,
             cookie_texture: program
                 .uniform_location(state, &ImmutableString::new("cookieTexture"))?,
+            falloff_enabled: program
+                .uniform_location(state, &ImmutableString::new("falloffEnabled"))?,
+            falloff_texture: program
+                .uniform_location(state, &ImmutableString::new("falloffTexture"))?,
             light_view_proj_matrix: program
                 .uniform_location(state, &ImmutableString::new("lightViewProjMatrix"))?,
             shadows_enabled: program
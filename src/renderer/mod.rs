@@ -22,20 +22,27 @@ pub mod ui_renderer;
 
 mod bloom;
 mod flat_shader;
+mod fog;
 mod forward_renderer;
 mod fxaa;
 mod gbuffer;
 mod hdr;
 mod light;
 mod light_volume;
+mod oit;
 mod particle_system_renderer;
+mod reflection_probe;
 mod shadow;
 mod skybox_shader;
 mod sprite_renderer;
 mod ssao;
 
+#[cfg(not(target_arch = "wasm32"))]
+use crate::window::WindowId;
 use crate::{
-    asset::{event::ResourceEvent, manager::ResourceManager},
+    asset::{
+        event::ResourceEvent, manager::ResourceManager, upload::SharedUploadQueue, ResourceStateRef,
+    },
     core::{
         algebra::{Matrix4, Vector2, Vector3},
         color::Color,
@@ -58,7 +65,7 @@ use crate::{
         cache::{geometry::GeometryCache, shader::ShaderCache, texture::TextureCache, CacheEntry},
         debug_renderer::DebugRenderer,
         flat_shader::FlatShader,
-        forward_renderer::{ForwardRenderContext, ForwardRenderer},
+        forward_renderer::{ForwardPassKind, ForwardRenderContext, ForwardRenderer},
         framework::{
             error::FrameworkError,
             framebuffer::{Attachment, AttachmentKind, DrawParameters, FrameBuffer},
@@ -76,14 +83,19 @@ use crate::{
         gbuffer::{GBuffer, GBufferRenderContext},
         hdr::HighDynamicRangeRenderer,
         light::{DeferredLightRenderer, DeferredRendererContext, LightingStatistics},
+        oit::OitRenderer,
         particle_system_renderer::{ParticleSystemRenderContext, ParticleSystemRenderer},
+        reflection_probe::{ReflectionProbeInfo, ReflectionProbeRenderer},
         renderer2d::Renderer2d,
         sprite_renderer::{SpriteRenderContext, SpriteRenderer},
         storage::MatrixStorageCache,
         ui_renderer::{UiRenderContext, UiRenderer},
     },
     resource::texture::{Texture, TextureKind, TextureResource},
-    scene::{camera::Camera, mesh::surface::SurfaceData, Scene, SceneContainer},
+    scene::{
+        camera::Camera, mesh::surface::SurfaceData, node::Node, reflection_probe::ReflectionProbe,
+        Scene, SceneContainer,
+    },
 };
 use fxhash::FxHashMap;
 use glow::HasContext;
@@ -101,6 +113,7 @@ use std::{
     fmt::{Display, Formatter},
     rc::Rc,
     sync::mpsc::Receiver,
+    time::Duration,
 };
 use strum_macros::{AsRefStr, EnumString, EnumVariantNames};
 
@@ -112,6 +125,10 @@ lazy_static! {
     static ref POINT_SHADOW_PASS_NAME: ImmutableString = ImmutableString::new("PointShadow");
 }
 
+/// Default amount of bytes of loaded textures that could be uploaded to GPU per frame. See
+/// [`Renderer::set_texture_upload_budget`] for more info.
+pub const DEFAULT_TEXTURE_UPLOAD_BUDGET: usize = 8 * 1024 * 1024;
+
 /// Checks whether the provided render pass name is one of the names of built-in shadow render passes.
 pub fn is_shadow_pass(render_pass_name: &str) -> bool {
     render_pass_name == &**DIRECTIONAL_SHADOW_PASS_NAME
@@ -129,6 +146,10 @@ pub struct Statistics {
     pub lighting: LightingStatistics,
     /// Shows how many draw calls was made and how many triangles were rendered.
     pub geometry: RenderPassStatistics,
+    /// Shows how many draw calls was made by order-independent transparency passes (accumulation
+    /// of transparent objects and composition), these are not included in `geometry`. See
+    /// [`QualitySettings::use_oit`].
+    pub transparency: RenderPassStatistics,
     /// Real time consumed to render frame. Time given in **seconds**.
     pub pure_frame_time: f32,
     /// Total time renderer took to process single frame, usually includes
@@ -150,12 +171,14 @@ impl Display for Statistics {
             Pure Frame Time: {:.2} ms\n\
             Capped Frame Time: {:.2} ms\n\
             {}\n\
+            Order-Independent Transparency:\n{}\n\
             {}\n\
             {}\n",
             self.frames_per_second,
             self.pure_frame_time * 1000.0,
             self.capped_frame_time * 1000.0,
             self.geometry,
+            self.transparency,
             self.lighting,
             self.pipeline
         )
@@ -169,15 +192,18 @@ pub struct RenderPassStatistics {
     pub draw_calls: usize,
     /// Amount of triangles per frame.
     pub triangles_rendered: usize,
+    /// Amount of instanced draw calls per frame, every such draw call renders multiple instances
+    /// of the same surface at once. This value is included in `draw_calls`.
+    pub instanced_draw_calls: usize,
 }
 
 impl Display for RenderPassStatistics {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         write!(
             f,
-            "Draw Calls: {}\n\
+            "Draw Calls: {} ({} instanced)\n\
             Triangles Rendered: {}",
-            self.draw_calls, self.triangles_rendered
+            self.draw_calls, self.instanced_draw_calls, self.triangles_rendered
         )
     }
 }
@@ -186,6 +212,7 @@ impl std::ops::AddAssign for RenderPassStatistics {
     fn add_assign(&mut self, rhs: Self) {
         self.draw_calls += rhs.draw_calls;
         self.triangles_rendered += rhs.triangles_rendered;
+        self.instanced_draw_calls += rhs.instanced_draw_calls;
     }
 }
 
@@ -307,6 +334,16 @@ pub struct QualitySettings {
 
     /// Whether to use bloom effect.
     pub use_bloom: bool,
+
+    /// Whether to use weighted blended order-independent transparency for particle systems and
+    /// transparent meshes or not. It removes popping and flickering of intersecting transparent
+    /// objects, that happens because transparent objects are sorted per-object, at the cost of an
+    /// additional pass. Only meshes, which shaders have `ForwardOIT` render pass, could use it (the
+    /// standard shaders have it), the rest are sorted as usual. The setting is ignored if the GPU
+    /// does not support blending of floating-point render targets. Disabled in every preset, so it
+    /// must be enabled explicitly.
+    #[serde(default)]
+    pub use_oit: bool,
 }
 
 impl Default for QualitySettings {
@@ -341,6 +378,8 @@ impl QualitySettings {
 
             use_bloom: true,
 
+            use_oit: false,
+
             use_parallax_mapping: false, // TODO: Enable when it is fixed!
 
             csm_settings: Default::default(),
@@ -372,6 +411,8 @@ impl QualitySettings {
 
             use_bloom: true,
 
+            use_oit: false,
+
             use_parallax_mapping: false, // TODO: Enable when it is fixed!
 
             csm_settings: CsmSettings {
@@ -408,6 +449,8 @@ impl QualitySettings {
 
             use_bloom: true,
 
+            use_oit: false,
+
             use_parallax_mapping: false,
 
             csm_settings: CsmSettings {
@@ -444,6 +487,8 @@ impl QualitySettings {
 
             use_bloom: false,
 
+            use_oit: false,
+
             use_parallax_mapping: false,
 
             csm_settings: CsmSettings {
@@ -461,6 +506,7 @@ impl Statistics {
     fn begin_frame(&mut self) {
         self.frame_start_time = instant::Instant::now();
         self.geometry = Default::default();
+        self.transparency = Default::default();
         self.lighting = Default::default();
     }
 
@@ -498,6 +544,7 @@ impl Default for Statistics {
             pipeline: Default::default(),
             lighting: Default::default(),
             geometry: Default::default(),
+            transparency: Default::default(),
             pure_frame_time: 0.0,
             capped_frame_time: 0.0,
             frames_per_second: 0,
@@ -529,6 +576,9 @@ pub struct AssociatedSceneData {
     /// Bloom contains only overly bright pixels that creates light
     /// bleeding effect (glow effect).
     pub bloom_renderer: BloomRenderer,
+
+    // Accumulation targets for order-independent transparency, created on demand.
+    oit_framebuffer: Option<FrameBuffer>,
 }
 
 impl AssociatedSceneData {
@@ -630,9 +680,34 @@ impl AssociatedSceneData {
             hdr_scene_framebuffer,
             ldr_scene_framebuffer,
             ldr_temp_framebuffer,
+            oit_framebuffer: None,
         })
     }
 
+    fn oit_framebuffer(
+        &mut self,
+        state: &mut PipelineState,
+    ) -> Result<&mut FrameBuffer, FrameworkError> {
+        match self.oit_framebuffer {
+            Some(ref mut framebuffer) => Ok(framebuffer),
+            None => {
+                let depth = self
+                    .hdr_scene_framebuffer
+                    .depth_attachment()
+                    .map(|attachment| attachment.texture.clone())
+                    .ok_or(FrameworkError::FailedToConstructFBO)?;
+                Ok(self
+                    .oit_framebuffer
+                    .insert(oit::make_accumulation_framebuffer(
+                        state,
+                        self.gbuffer.width as usize,
+                        self.gbuffer.height as usize,
+                        depth,
+                    )?))
+            }
+        }
+    }
+
     fn copy_depth_stencil_to_scene_framebuffer(&mut self, state: &mut PipelineState) {
         state.blit_framebuffer(
             self.gbuffer.framebuffer().id(),
@@ -688,11 +763,23 @@ pub(crate) fn make_viewport_matrix(viewport: Rect<i32>) -> Matrix4<f32> {
     ))
 }
 
+/// A scene observed through a specific camera. It is used to show a single camera of a scene in
+/// a secondary window. The camera is rendered regardless of its enabled state, so it could be
+/// disabled to not show up in the main window.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct SceneView {
+    /// A handle of the scene.
+    pub scene: Handle<Scene>,
+    /// A handle of a camera in the scene.
+    pub camera: Handle<Node>,
+}
+
 /// See module docs.
 pub struct Renderer {
     backbuffer: FrameBuffer,
     scene_render_passes: Vec<Rc<RefCell<dyn SceneRenderPass>>>,
     deferred_light_renderer: DeferredLightRenderer,
+    reflection_probe_renderer: ReflectionProbeRenderer,
     flat_shader: FlatShader,
     sprite_renderer: SpriteRenderer,
     particle_system_renderer: ParticleSystemRenderer,
@@ -719,16 +806,30 @@ pub struct Renderer {
     pub debug_renderer: DebugRenderer,
     /// A set of associated data for each scene that was rendered.
     pub scene_data_map: FxHashMap<Handle<Scene>, AssociatedSceneData>,
+    /// A set of associated data for each scene that was rendered in a secondary window. Every window
+    /// has its own set, because frame size of each window could be different.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub secondary_scene_data_map:
+        FxHashMap<WindowId, FxHashMap<Handle<Scene>, AssociatedSceneData>>,
+    /// A set of associated data for each texture, that was used as a render target in
+    /// [`Self::render_scene_to_texture`].
+    pub render_target_data_map: FxHashMap<usize, AssociatedSceneData>,
     backbuffer_clear_color: Color,
     /// Texture cache with GPU textures.
     pub texture_cache: TextureCache,
     shader_cache: ShaderCache,
     geometry_cache: GeometryCache,
     forward_renderer: ForwardRenderer,
+    oit_renderer: OitRenderer,
+    // Whether order-independent transparency could be used or not.
+    oit_supported: bool,
     fxaa_renderer: FxaaRenderer,
     renderer2d: Renderer2d,
     texture_event_receiver: Receiver<ResourceEvent>,
     shader_event_receiver: Receiver<ResourceEvent>,
+    texture_upload_queue: SharedUploadQueue,
+    texture_upload_budget: usize,
+    texture_upload_time_budget: Option<Duration>,
     matrix_storage: MatrixStorageCache,
     // TextureId -> FrameBuffer mapping. This mapping is used for temporal frame buffers
     // like ones used to render UI instances.
@@ -946,6 +1047,9 @@ pub(crate) struct MaterialContext<'a, 'b, 'c> {
     pub light_position: &'a Vector3<f32>,
     pub blend_shapes_storage: Option<&'a TextureResource>,
     pub blend_shapes_weights: &'a [f32],
+    pub view_projection_matrix: &'a Matrix4<f32>,
+    /// World matrices of instances for instanced rendering, empty for non-instanced rendering.
+    pub instance_matrices: &'a [Matrix4<f32>],
 
     // Fallback samplers.
     pub normal_dummy: Rc<RefCell<GpuTexture>>,
@@ -1017,6 +1121,28 @@ pub(crate) fn apply_material(ctx: MaterialContext) {
         ctx.program_binding
             .set_i32(location, ctx.blend_shapes_weights.len() as i32);
     }
+    if let Some(location) = &built_in_uniforms[BuiltInUniform::ViewProjectionMatrix as usize] {
+        ctx.program_binding
+            .set_matrix4(location, ctx.view_projection_matrix);
+    }
+    if let Some(location) = &built_in_uniforms[BuiltInUniform::UseInstancing as usize] {
+        ctx.program_binding
+            .set_bool(location, !ctx.instance_matrices.is_empty());
+    }
+    if let Some(location) = &built_in_uniforms[BuiltInUniform::InstanceMatrices as usize] {
+        let active_sampler = ctx.program_binding.active_sampler();
+
+        let storage = ctx
+            .matrix_storage
+            .try_bind_and_upload_instances(
+                ctx.program_binding.state,
+                ctx.instance_matrices,
+                active_sampler,
+            )
+            .expect("Failed to upload instance matrices!");
+
+        ctx.program_binding.set_texture(location, storage.texture());
+    }
 
     // Apply material properties.
     for (name, value) in ctx.material.properties() {
@@ -1142,6 +1268,7 @@ impl Renderer {
             backbuffer: FrameBuffer::backbuffer(&mut state),
             frame_size,
             deferred_light_renderer: DeferredLightRenderer::new(&mut state, frame_size, &settings)?,
+            reflection_probe_renderer: ReflectionProbeRenderer::new(),
             flat_shader: FlatShader::new(&mut state)?,
             sprite_renderer: SpriteRenderer::new(&mut state)?,
             white_dummy: Rc::new(RefCell::new(GpuTexture::new(
@@ -1234,16 +1361,24 @@ impl Renderer {
             quality_settings: settings,
             debug_renderer: DebugRenderer::new(&mut state)?,
             scene_data_map: Default::default(),
+            #[cfg(not(target_arch = "wasm32"))]
+            secondary_scene_data_map: Default::default(),
+            render_target_data_map: Default::default(),
             backbuffer_clear_color: Color::BLACK,
             texture_cache: Default::default(),
             geometry_cache: Default::default(),
             forward_renderer: ForwardRenderer::new(),
+            oit_renderer: OitRenderer::new(&mut state)?,
+            oit_supported: oit::is_supported(&state),
             ui_frame_buffers: Default::default(),
             fxaa_renderer: FxaaRenderer::new(&mut state)?,
             statistics: Statistics::default(),
             renderer2d: Renderer2d::new(&mut state)?,
             shader_event_receiver,
             texture_event_receiver,
+            texture_upload_queue: resource_manager.state().upload_queue.clone(),
+            texture_upload_budget: DEFAULT_TEXTURE_UPLOAD_BUDGET,
+            texture_upload_time_budget: None,
             shader_cache,
             scene_render_passes: Default::default(),
             matrix_storage: MatrixStorageCache::new(&mut state)?,
@@ -1287,11 +1422,46 @@ impl Renderer {
         self.texture_cache.unload(texture)
     }
 
-    /// Sets color which will be used to fill screen when there is nothing to render.
+    /// Sets the maximum amount of bytes of loaded textures that could be uploaded to GPU per frame.
+    /// Textures that did not fit in the budget are left in the queue (see
+    /// [`ResourceManager::pending_upload_bytes`]) and rendered with a placeholder until they're
+    /// uploaded. Large budget means faster streaming, but longer stalls. At least one texture is
+    /// uploaded every frame, even if it is larger than the budget. Default is 8 Mb.
+    pub fn set_texture_upload_budget(&mut self, budget: usize) {
+        self.texture_upload_budget = budget;
+    }
+
+    /// Returns the maximum amount of bytes of loaded textures that could be uploaded to GPU per
+    /// frame.
+    pub fn texture_upload_budget(&self) -> usize {
+        self.texture_upload_budget
+    }
+
+    /// Sets the maximum amount of time that could be spent on texture uploads per frame, `None`
+    /// means no limit (default). The time limit is applied together with the byte budget (see
+    /// [`Self::set_texture_upload_budget`]), the uploading stops when any of them is exceeded. The
+    /// time is checked after each texture, so at least one texture is uploaded every frame.
+    pub fn set_texture_upload_time_budget(&mut self, budget: Option<Duration>) {
+        self.texture_upload_time_budget = budget;
+    }
+
+    /// Returns the maximum amount of time that could be spent on texture uploads per frame.
+    pub fn texture_upload_time_budget(&self) -> Option<Duration> {
+        self.texture_upload_time_budget
+    }
+
+    /// Sets color which will be used to fill screen when there is nothing to render. It is also
+    /// used as background of scenes, that do not override it by
+    /// [`crate::scene::environment::EnvironmentSettings::clear_color`].
     pub fn set_backbuffer_clear_color(&mut self, color: Color) {
         self.backbuffer_clear_color = color;
     }
 
+    /// Returns color which is used to fill screen when there is nothing to render.
+    pub fn backbuffer_clear_color(&self) -> Color {
+        self.backbuffer_clear_color
+    }
+
     /// Returns a reference to current pipeline state.
     pub fn pipeline_state(&mut self) -> &mut PipelineState {
         &mut self.state
@@ -1342,6 +1512,46 @@ impl Renderer {
         self.quality_settings
     }
 
+    /// Returns `true` if the graphics context was lost. The renderer does nothing until the context
+    /// is restored. The engine restores the context automatically, see
+    /// [`crate::engine::EngineEvent`] for more info.
+    pub fn is_context_lost(&self) -> bool {
+        self.state.is_context_lost()
+    }
+
+    /// Marks the graphics context as lost, the engine will re-create it on the next frame as if
+    /// the driver was reset. Could be used to test how a game handles context loss.
+    pub fn simulate_context_loss(&mut self) {
+        self.state.set_context_lost();
+    }
+
+    /// Re-creates every GPU object of the renderer using the given context, the previous context
+    /// must be marked as lost. Caches are filled again on demand: shaders are re-compiled, textures
+    /// and geometry are re-uploaded from their CPU-side data. Settings of the renderer and custom
+    /// render passes are kept, but render passes must re-create their own GPU objects.
+    pub(crate) fn restore_context(
+        &mut self,
+        context: glow::Context,
+        resource_manager: &ResourceManager,
+        gl_kind: GlKind,
+    ) -> Result<(), FrameworkError> {
+        // Objects of the lost context must not try to delete anything in the new one.
+        self.state.set_context_lost();
+
+        let mut renderer = Renderer::new(context, self.frame_size, resource_manager, gl_kind)?;
+        renderer.set_quality_settings(&self.quality_settings)?;
+        renderer.backbuffer_clear_color = self.backbuffer_clear_color;
+        renderer.texture_upload_budget = self.texture_upload_budget;
+        renderer.texture_upload_time_budget = self.texture_upload_time_budget;
+        renderer.scene_render_passes = std::mem::take(&mut self.scene_render_passes);
+        // Textures that are waiting in the upload queue will be uploaded by the new renderer.
+        renderer.texture_cache.pending = std::mem::take(&mut self.texture_cache.pending);
+
+        *self = renderer;
+
+        Ok(())
+    }
+
     /// Removes all cached GPU data, forces renderer to re-upload data to GPU.
     /// Do not call this method until you absolutely need! It may cause **significant**
     /// performance lag!
@@ -1353,13 +1563,29 @@ impl Renderer {
 
     /// Renders given UI into specified render target. This method is especially useful if you need
     /// to have off-screen UIs (like interactive touch-screen in Doom 3, Dead Space, etc).
+    ///
+    /// Frame buffer, viewport and projection are defined by the size of the render target, one UI
+    /// unit corresponds to one texel of the target. Usually, the UI should be updated with the size
+    /// of the target (see [`UserInterface::update`]) to fill it completely. The render target must
+    /// be a rectangle texture, for example created by
+    /// [`crate::resource::texture::TextureResourceExtension::new_render_target`].
     pub fn render_ui_to_texture(
         &mut self,
         render_target: TextureResource,
         ui: &mut UserInterface,
     ) -> Result<(), FrameworkError> {
-        let new_width = ui.screen_size().x as usize;
-        let new_height = ui.screen_size().y as usize;
+        let target_size = if render_target.is_ok() {
+            render_target.data_ref().kind().rectangle_size()
+        } else {
+            None
+        }
+        .ok_or_else(|| {
+            FrameworkError::Custom("UI can be rendered only into rectangle texture!".to_string())
+        })?;
+
+        let new_width = target_size.x as usize;
+        let new_height = target_size.y as usize;
+        let frame_size = Vector2::new(new_width as f32, new_height as f32);
 
         // Create or reuse existing frame buffer.
         let frame_buffer = match self.ui_frame_buffers.entry(render_target.key()) {
@@ -1369,7 +1595,7 @@ impl Renderer {
                 let color_texture_kind = frame.texture.borrow().kind();
                 if let GpuTextureKind::Rectangle { width, height } = color_texture_kind {
                     if width != new_width || height != new_height {
-                        *frame_buffer = make_ui_frame_buffer(ui.screen_size(), &mut self.state)?;
+                        *frame_buffer = make_ui_frame_buffer(frame_size, &mut self.state)?;
                     }
                 } else {
                     panic!("ui can be rendered only in rectangle texture!")
@@ -1377,7 +1603,7 @@ impl Renderer {
                 frame_buffer
             }
             Entry::Vacant(entry) => {
-                entry.insert(make_ui_frame_buffer(ui.screen_size(), &mut self.state)?)
+                entry.insert(make_ui_frame_buffer(frame_size, &mut self.state)?)
             }
         };
 
@@ -1395,8 +1621,8 @@ impl Renderer {
             state: &mut self.state,
             viewport,
             frame_buffer,
-            frame_width: ui.screen_size().x,
-            frame_height: ui.screen_size().y,
+            frame_width: frame_size.x,
+            frame_height: frame_size.y,
             drawing_context: ui.draw(),
             white_dummy: self.white_dummy.clone(),
             texture_cache: &mut self.texture_cache,
@@ -1421,34 +1647,178 @@ impl Renderer {
         Ok(())
     }
 
+    /// Immediately renders the given scene (from every enabled camera) into the given render target,
+    /// outside of the main rendering loop. It could be used for minimaps, portals and other
+    /// render-to-texture effects, the target could be used in materials of other scenes right after
+    /// the call. Render target of the scene itself (if any) is ignored, the back buffer is left
+    /// untouched. The render target must be a rectangle texture, for example created by
+    /// [`crate::resource::texture::TextureResourceExtension::new_render_target`].
+    ///
+    /// Use [`Self::read_render_target_pixels`] to read rendered image back.
+    pub fn render_scene_to_texture(
+        &mut self,
+        scene_handle: Handle<Scene>,
+        scene: &Scene,
+        render_target: &TextureResource,
+    ) -> Result<(), FrameworkError> {
+        self.render_scene_to_texture_internal(scene_handle, scene, None, render_target)
+    }
+
+    /// Same as [`Self::render_scene_to_texture`], but renders the scene only from the camera of the
+    /// given scene view. The camera is rendered regardless of its enabled state.
+    pub fn render_scene_view_to_texture(
+        &mut self,
+        scene_view: SceneView,
+        scene: &Scene,
+        render_target: &TextureResource,
+    ) -> Result<(), FrameworkError> {
+        self.render_scene_to_texture_internal(
+            scene_view.scene,
+            scene,
+            Some(scene_view),
+            render_target,
+        )
+    }
+
+    /// Frees every GPU resource, that was created to render into the given render target by
+    /// [`Self::render_scene_to_texture`] or [`Self::render_scene_view_to_texture`]. The target
+    /// can still be used after the call, the resources will be created again on demand.
+    pub fn release_render_target(&mut self, render_target: &TextureResource) {
+        self.render_target_data_map.remove(&render_target.key());
+        self.texture_cache.map.remove(&render_target.key());
+    }
+
+    fn render_scene_to_texture_internal(
+        &mut self,
+        scene_handle: Handle<Scene>,
+        scene: &Scene,
+        scene_view: Option<SceneView>,
+        render_target: &TextureResource,
+    ) -> Result<(), FrameworkError> {
+        scope_profile!();
+
+        if self.state.is_context_lost() {
+            return Ok(());
+        }
+
+        let target_size = if render_target.is_ok() {
+            render_target.data_ref().kind().rectangle_size()
+        } else {
+            None
+        }
+        .ok_or_else(|| {
+            FrameworkError::Custom("Scene can be rendered only into rectangle texture!".to_string())
+        })?;
+        let frame_size =
+            Vector2::new(target_size.x as f32, target_size.y as f32).sup(&Vector2::new(1.0, 1.0));
+
+        // The call could happen at any moment, so the bindings cache could be stale.
+        self.state.invalidate_resource_bindings_cache();
+
+        self.render_reflection_probes(scene)?;
+
+        let width = frame_size.x as usize;
+        let height = frame_size.y as usize;
+        let mut scene_associated_data =
+            match self.render_target_data_map.remove(&render_target.key()) {
+                Some(data)
+                    if data.gbuffer.width == width as i32
+                        && data.gbuffer.height == height as i32 =>
+                {
+                    data
+                }
+                _ => AssociatedSceneData::new(&mut self.state, width, height)?,
+            };
+
+        let dt = self.statistics.capped_frame_time;
+        let result = self.render_scene_cameras(
+            scene_handle,
+            scene,
+            scene_view,
+            Some(render_target),
+            frame_size,
+            &mut scene_associated_data,
+            dt,
+        );
+
+        self.render_target_data_map
+            .insert(render_target.key(), scene_associated_data);
+
+        self.state
+            .set_polygon_fill_mode(PolygonFace::FrontAndBack, PolygonFillMode::Fill);
+
+        result
+    }
+
+    /// Reads pixels of the image, that was rendered into the given render target by
+    /// [`Self::render_scene_to_texture`]. Pixels are stored as RGBA8 values in sRGB space, row by
+    /// row from the bottom of the image. Returns `None` if nothing was rendered into the target.
+    pub fn read_render_target_pixels(
+        &mut self,
+        render_target: &TextureResource,
+    ) -> Option<Vec<u8>> {
+        let data = self.render_target_data_map.get(&render_target.key())?;
+        let rect = Rect::new(0, 0, data.gbuffer.width, data.gbuffer.height);
+        Some(
+            data.ldr_scene_framebuffer
+                .read_pixels_rgba8(&mut self.state, rect),
+        )
+    }
+
     fn update_texture_cache(&mut self, dt: f32) {
-        // Maximum amount of textures uploaded to GPU per frame. This defines throughput **only** for
-        // requests from resource manager. This is needed to prevent huge lag when there are tons of
-        // requests, so this is some kind of work load balancer.
-        const THROUGHPUT: usize = 5;
+        let mut upload_queue = self.texture_upload_queue.lock();
 
-        let mut uploaded = 0;
         while let Ok(event) = self.texture_event_receiver.try_recv() {
-            if let ResourceEvent::Loaded(resource) | ResourceEvent::Reloaded(resource) = event {
-                if let Some(texture) = resource.try_cast::<Texture>() {
-                    match self.texture_cache.upload(&mut self.state, &texture) {
-                        Ok(_) => {
-                            uploaded += 1;
-                            if uploaded >= THROUGHPUT {
-                                break;
+            match event {
+                ResourceEvent::Loaded(resource) | ResourceEvent::Reloaded(resource) => {
+                    if let Some(texture) = resource.try_cast::<Texture>() {
+                        let (priority, size) = match texture.state().get() {
+                            ResourceStateRef::Ok(texture) => {
+                                (texture.upload_priority(), texture.gpu_size())
                             }
-                        }
-                        Err(e) => {
-                            Log::writeln(
-                                MessageKind::Error,
-                                format!("Failed to upload texture to GPU. Reason: {:?}", e),
-                            );
-                        }
+                            _ => continue,
+                        };
+                        self.texture_cache.pending.insert(resource.key());
+                        upload_queue.push(resource, priority, size);
                     }
                 }
+                ResourceEvent::Unloaded(key) => {
+                    // Release GPU texture of a resource that was destroyed by the resource manager.
+                    upload_queue.remove(key);
+                    self.texture_cache.pending.remove(&key);
+                    self.texture_cache.map.remove(&key);
+                }
+                _ => (),
+            }
+        }
+
+        // Upload only a portion of the queue per frame, this is needed to prevent huge lag when
+        // there are tons of requests (for example when a level is streamed in).
+        let upload_start_time = instant::Instant::now();
+        let mut spent_bytes = 0;
+        while let Some(resource) =
+            upload_queue.pop_if_fits(&mut spent_bytes, self.texture_upload_budget)
+        {
+            self.texture_cache.pending.remove(&resource.key());
+            if let Some(texture) = resource.try_cast::<Texture>() {
+                if let Err(e) = self.texture_cache.upload(&mut self.state, &texture) {
+                    Log::writeln(
+                        MessageKind::Error,
+                        format!("Failed to upload texture to GPU. Reason: {:?}", e),
+                    );
+                }
+            }
+
+            if self
+                .texture_upload_time_budget
+                .map_or(false, |budget| upload_start_time.elapsed() >= budget)
+            {
+                break;
             }
         }
 
+        drop(upload_queue);
+
         self.texture_cache.update(dt);
     }
 
@@ -1471,10 +1841,15 @@ impl Renderer {
     /// Normally, this is called from `Engine::update()`.
     /// You should only call this manually if you don't use that method.
     pub fn update_caches(&mut self, dt: f32) {
+        if self.state.is_context_lost() {
+            return;
+        }
+
         self.update_texture_cache(dt);
         self.update_shader_cache(dt);
         self.geometry_cache.update(dt);
         self.renderer2d.update_caches(dt);
+        self.reflection_probe_renderer.update(dt);
     }
 
     fn render_frame(
@@ -1498,7 +1873,543 @@ impl Renderer {
         let dt = self.statistics.capped_frame_time;
         self.statistics.begin_frame();
 
-        let window_viewport = Rect::new(0, 0, self.frame_size.0 as i32, self.frame_size.1 as i32);
+        let mut scene_data_map = std::mem::take(&mut self.scene_data_map);
+        let result = self.render_scenes(
+            scenes,
+            None,
+            self.frame_size,
+            &mut scene_data_map,
+            Some(drawing_context),
+            dt,
+        );
+        self.scene_data_map = scene_data_map;
+        result
+    }
+
+    /// Renders scene geometry (opaque and transparent objects, lighting, particles, sprites, 2D
+    /// objects) from the given camera into the high dynamic range frame buffer of the given scene
+    /// data. Returns render data batches, that were used to render the scene.
+    fn render_scene_geometry(
+        &mut self,
+        scene: &Scene,
+        camera: &Camera,
+        scene_associated_data: &mut AssociatedSceneData,
+        frame_size: Vector2<f32>,
+        settings: QualitySettings,
+        reflection_probes: &[ReflectionProbeInfo],
+    ) -> Result<RenderDataBatchStorage, FrameworkError> {
+        let graph = &scene.graph;
+        let state = &mut self.state;
+
+        let viewport = camera.viewport_pixels(frame_size);
+
+        let batch_storage = RenderDataBatchStorage::from_graph(
+            graph,
+            ObserverInfo {
+                observer_position: camera.global_position(),
+                z_near: camera.projection().z_near(),
+                z_far: camera.projection().z_far(),
+                view_matrix: camera.view_matrix(),
+                projection_matrix: camera.projection_matrix(),
+            },
+            GBUFFER_PASS_NAME.clone(),
+        );
+
+        state.set_polygon_fill_mode(PolygonFace::FrontAndBack, scene.polygon_rasterization_mode);
+
+        self.statistics += scene_associated_data.gbuffer.fill(GBufferRenderContext {
+            state,
+            camera,
+            geom_cache: &mut self.geometry_cache,
+            batch_storage: &batch_storage,
+            texture_cache: &mut self.texture_cache,
+            shader_cache: &mut self.shader_cache,
+            environment_dummy: self.environment_dummy.clone(),
+            use_parallax_mapping: settings.use_parallax_mapping,
+            normal_dummy: self.normal_dummy.clone(),
+            white_dummy: self.white_dummy.clone(),
+            black_dummy: self.black_dummy.clone(),
+            volume_dummy: self.volume_dummy.clone(),
+            graph,
+            matrix_storage: &mut self.matrix_storage,
+        })?;
+
+        state.set_polygon_fill_mode(PolygonFace::FrontAndBack, PolygonFillMode::Fill);
+
+        scene_associated_data.copy_depth_stencil_to_scene_framebuffer(state);
+
+        scene_associated_data.hdr_scene_framebuffer.clear(
+            state,
+            viewport,
+            Some(
+                scene
+                    .environment
+                    .clear_color
+                    .unwrap_or(self.backbuffer_clear_color),
+            ),
+            None, // Keep depth, we've just copied valid data in it.
+            Some(0),
+        );
+
+        let (pass_stats, light_stats) =
+            self.deferred_light_renderer
+                .render(DeferredRendererContext {
+                    state,
+                    scene,
+                    camera,
+                    gbuffer: &mut scene_associated_data.gbuffer,
+                    white_dummy: self.white_dummy.clone(),
+                    ambient_color: scene.environment.ambient_lighting_color,
+                    settings: &settings,
+                    textures: &mut self.texture_cache,
+                    geometry_cache: &mut self.geometry_cache,
+                    frame_buffer: &mut scene_associated_data.hdr_scene_framebuffer,
+                    shader_cache: &mut self.shader_cache,
+                    normal_dummy: self.normal_dummy.clone(),
+                    black_dummy: self.black_dummy.clone(),
+                    volume_dummy: self.volume_dummy.clone(),
+                    environment_dummy: self.environment_dummy.clone(),
+                    reflection_probes,
+                    matrix_storage: &mut self.matrix_storage,
+                })?;
+
+        self.statistics.lighting += light_stats;
+        self.statistics.geometry += pass_stats;
+
+        let depth = scene_associated_data.gbuffer.depth();
+
+        let use_oit = settings.use_oit && self.oit_supported;
+
+        if use_oit {
+            // Particles and transparent meshes are accumulated regardless of their order and then
+            // blended over the frame at once.
+            let oit_framebuffer = scene_associated_data.oit_framebuffer(state)?;
+            oit::clear_accumulation_framebuffer(state, oit_framebuffer, viewport);
+
+            self.statistics.transparency +=
+                self.particle_system_renderer
+                    .render(ParticleSystemRenderContext {
+                        state,
+                        framebuffer: oit_framebuffer,
+                        graph,
+                        camera,
+                        white_dummy: self.white_dummy.clone(),
+                        depth: depth.clone(),
+                        frame_width: frame_size.x,
+                        frame_height: frame_size.y,
+                        viewport,
+                        texture_cache: &mut self.texture_cache,
+                        oit: true,
+                    })?;
+
+            self.statistics.transparency += self.forward_renderer.render(ForwardRenderContext {
+                state,
+                camera,
+                geom_cache: &mut self.geometry_cache,
+                texture_cache: &mut self.texture_cache,
+                shader_cache: &mut self.shader_cache,
+                batch_storage: &batch_storage,
+                framebuffer: oit_framebuffer,
+                viewport,
+                quality_settings: &settings,
+                white_dummy: self.white_dummy.clone(),
+                normal_dummy: self.normal_dummy.clone(),
+                black_dummy: self.black_dummy.clone(),
+                volume_dummy: self.volume_dummy.clone(),
+                matrix_storage: &mut self.matrix_storage,
+                pass_kind: ForwardPassKind::OitAccumulation,
+            })?;
+
+            if let Some(oit_framebuffer) = scene_associated_data.oit_framebuffer.as_ref() {
+                self.statistics.transparency += self.oit_renderer.composite(
+                    state,
+                    viewport,
+                    oit_framebuffer,
+                    &mut scene_associated_data.hdr_scene_framebuffer,
+                )?;
+            }
+        } else {
+            self.statistics +=
+                self.particle_system_renderer
+                    .render(ParticleSystemRenderContext {
+                        state,
+                        framebuffer: &mut scene_associated_data.hdr_scene_framebuffer,
+                        graph,
+                        camera,
+                        white_dummy: self.white_dummy.clone(),
+                        depth,
+                        frame_width: frame_size.x,
+                        frame_height: frame_size.y,
+                        viewport,
+                        texture_cache: &mut self.texture_cache,
+                        oit: false,
+                    })?;
+        }
+
+        self.statistics += self.sprite_renderer.render(SpriteRenderContext {
+            state,
+            framebuffer: &mut scene_associated_data.hdr_scene_framebuffer,
+            graph,
+            camera,
+            white_dummy: self.white_dummy.clone(),
+            viewport,
+            textures: &mut self.texture_cache,
+        })?;
+
+        self.statistics += self.renderer2d.render(
+            state,
+            camera,
+            &mut scene_associated_data.hdr_scene_framebuffer,
+            viewport,
+            graph,
+            &mut self.texture_cache,
+            self.white_dummy.clone(),
+            scene.environment.ambient_lighting_color,
+        )?;
+
+        // Transparent meshes without order-independent transparency support are sorted as usual.
+        self.statistics += self.forward_renderer.render(ForwardRenderContext {
+            state,
+            camera,
+            geom_cache: &mut self.geometry_cache,
+            texture_cache: &mut self.texture_cache,
+            shader_cache: &mut self.shader_cache,
+            batch_storage: &batch_storage,
+            framebuffer: &mut scene_associated_data.hdr_scene_framebuffer,
+            viewport,
+            quality_settings: &settings,
+            white_dummy: self.white_dummy.clone(),
+            normal_dummy: self.normal_dummy.clone(),
+            black_dummy: self.black_dummy.clone(),
+            volume_dummy: self.volume_dummy.clone(),
+            matrix_storage: &mut self.matrix_storage,
+            pass_kind: if use_oit {
+                ForwardPassKind::SortedFallback
+            } else {
+                ForwardPassKind::Sorted
+            },
+        })?;
+
+        Ok(batch_storage)
+    }
+
+    /// Captures surroundings of every reflection probe of the scene, that requested capturing.
+    fn render_reflection_probes(&mut self, scene: &Scene) -> Result<(), FrameworkError> {
+        for probe in scene
+            .graph
+            .linear_iter()
+            .filter_map(|node| node.cast::<ReflectionProbe>())
+        {
+            if !probe.global_visibility() || !probe.is_globally_enabled() {
+                continue;
+            }
+
+            if self.reflection_probe_renderer.needs_capture(probe) {
+                self.capture_reflection_probe(scene, probe)?;
+            } else {
+                // Keep the cube map alive.
+                self.reflection_probe_renderer
+                    .get_or_create(&mut self.state, probe)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn capture_reflection_probe(
+        &mut self,
+        scene: &Scene,
+        probe: &ReflectionProbe,
+    ) -> Result<(), FrameworkError> {
+        scope_profile!();
+
+        let resolution = probe.resolution().max(1) as usize;
+        let mut capture_data = match self
+            .reflection_probe_renderer
+            .capture_data
+            .remove(&resolution)
+        {
+            Some(capture_data) => capture_data,
+            None => AssociatedSceneData::new(&mut self.state, resolution, resolution)?,
+        };
+
+        // Screen-space effects make little sense for cube map faces.
+        let settings = QualitySettings {
+            use_ssao: false,
+            ..self.quality_settings
+        };
+        let frame_size = Vector2::new(resolution as f32, resolution as f32);
+        let viewport = Rect::new(0, 0, resolution as i32, resolution as i32);
+
+        let face_cameras = self
+            .reflection_probe_renderer
+            .faces
+            .iter()
+            .map(|face| (face.face, ReflectionProbeRenderer::face_camera(probe, face)))
+            .collect::<Vec<_>>();
+
+        for (cube_map_face, camera) in face_cameras {
+            // Probes do not reflect each other, otherwise captured result would depend on the order
+            // of capturing.
+            self.render_scene_geometry(
+                scene,
+                &camera,
+                &mut capture_data,
+                frame_size,
+                settings,
+                &[],
+            )?;
+
+            let cube_map = self
+                .reflection_probe_renderer
+                .get_or_create(&mut self.state, probe)?;
+            cube_map
+                .framebuffer
+                .set_cubemap_face(&mut self.state, 0, cube_map_face);
+            self.statistics.geometry += blit_pixels(
+                &mut self.state,
+                &mut cube_map.framebuffer,
+                capture_data.hdr_scene_frame_texture(),
+                &self.flat_shader,
+                viewport,
+                &self.quad,
+            )?;
+        }
+
+        // Rough surfaces use lower mip levels of the cube map.
+        self.reflection_probe_renderer
+            .get_or_create(&mut self.state, probe)?
+            .texture()
+            .borrow_mut()
+            .bind_mut(&mut self.state, 0)
+            .generate_mip_maps();
+
+        if probe.is_serialize_cubemap() {
+            self.reflection_probe_renderer.bake(&mut self.state, probe);
+        }
+
+        probe.reset_render_request();
+
+        self.reflection_probe_renderer
+            .capture_data
+            .insert(resolution, capture_data);
+
+        Ok(())
+    }
+
+    /// Immediately captures surroundings of the given reflection probe of the scene, instead of
+    /// waiting for the next frame. It could be useful to capture probes while a level is loading.
+    /// Does nothing if the handle does not point to a reflection probe.
+    pub fn render_probe(
+        &mut self,
+        scene: &Scene,
+        probe: Handle<Node>,
+    ) -> Result<(), FrameworkError> {
+        if let Some(probe) = scene
+            .graph
+            .try_get(probe)
+            .and_then(|node| node.cast::<ReflectionProbe>())
+        {
+            self.capture_reflection_probe(scene, probe)?;
+        }
+        Ok(())
+    }
+
+    /// Renders the scene from every enabled camera (or from the camera of the given scene view) into
+    /// the LDR frame buffer of the given scene data.
+    #[allow(clippy::too_many_arguments)]
+    fn render_scene_cameras(
+        &mut self,
+        scene_handle: Handle<Scene>,
+        scene: &Scene,
+        scene_view: Option<SceneView>,
+        render_target: Option<&TextureResource>,
+        frame_size: Vector2<f32>,
+        scene_associated_data: &mut AssociatedSceneData,
+        dt: f32,
+    ) -> Result<(), FrameworkError> {
+        let graph = &scene.graph;
+
+        // If we specified a texture to draw to, we have to register it in texture cache
+        // so it can be used in later on as texture. This is useful in case if you need
+        // to draw something on offscreen and then draw it on some mesh.
+        // TODO: However it can be dangerous to use frame texture as it may be bound to
+        //  pipeline.
+        if let Some(rt) = render_target {
+            self.texture_cache.map.insert(
+                rt.key(),
+                CacheEntry {
+                    value: scene_associated_data.ldr_scene_frame_texture(),
+                    time_to_live: f32::INFINITY,
+                    value_hash: 0, // TODO
+                },
+            );
+        }
+
+        for camera in graph.pair_iter().filter_map(|(handle, node)| {
+            node.cast::<Camera>().filter(|&camera| match scene_view {
+                Some(view) => view.camera == handle,
+                None => camera.is_enabled(),
+            })
+        }) {
+            let viewport = camera.viewport_pixels(frame_size);
+
+            let reflection_probes =
+                self.reflection_probe_renderer
+                    .visible_probes(&mut self.state, graph, camera);
+
+            let batch_storage = self.render_scene_geometry(
+                scene,
+                camera,
+                scene_associated_data,
+                frame_size,
+                self.quality_settings,
+                &reflection_probes,
+            )?;
+
+            let state = &mut self.state;
+
+            for render_pass in self.scene_render_passes.iter() {
+                self.statistics +=
+                    render_pass
+                        .borrow_mut()
+                        .on_hdr_render(SceneRenderPassContext {
+                            pipeline_state: state,
+                            texture_cache: &mut self.texture_cache,
+                            geometry_cache: &mut self.geometry_cache,
+                            quality_settings: &self.quality_settings,
+                            batch_storage: &batch_storage,
+                            viewport,
+                            scene,
+                            camera,
+                            scene_handle,
+                            white_dummy: self.white_dummy.clone(),
+                            normal_dummy: self.normal_dummy.clone(),
+                            metallic_dummy: self.metallic_dummy.clone(),
+                            environment_dummy: self.environment_dummy.clone(),
+                            black_dummy: self.black_dummy.clone(),
+                            depth_texture: scene_associated_data.gbuffer.depth(),
+                            normal_texture: scene_associated_data.gbuffer.normal_texture(),
+                            ambient_texture: scene_associated_data.gbuffer.ambient_texture(),
+                            framebuffer: &mut scene_associated_data.hdr_scene_framebuffer,
+                            ui_renderer: &mut self.ui_renderer,
+                        })?;
+            }
+
+            let quad = &self.quad;
+
+            // Prepare glow map.
+            self.statistics.geometry += scene_associated_data.bloom_renderer.render(
+                state,
+                quad,
+                scene_associated_data.hdr_scene_frame_texture(),
+            )?;
+
+            // Convert high dynamic range frame to low dynamic range (sRGB) with tone mapping and gamma correction.
+            self.statistics.geometry += scene_associated_data.hdr_renderer.render(
+                state,
+                scene_associated_data.hdr_scene_frame_texture(),
+                scene_associated_data.bloom_renderer.result(),
+                &mut scene_associated_data.ldr_scene_framebuffer,
+                viewport,
+                quad,
+                dt,
+                camera.exposure(),
+                camera.color_grading_lut_ref(),
+                camera.color_grading_enabled(),
+                &mut self.texture_cache,
+            )?;
+
+            // Apply FXAA if needed.
+            if self.quality_settings.fxaa {
+                self.statistics.geometry += self.fxaa_renderer.render(
+                    state,
+                    viewport,
+                    scene_associated_data.ldr_scene_frame_texture(),
+                    &mut scene_associated_data.ldr_temp_framebuffer,
+                )?;
+
+                let quad = &self.quad;
+                let temp_frame_texture = scene_associated_data.ldr_temp_frame_texture();
+                self.statistics.geometry += blit_pixels(
+                    state,
+                    &mut scene_associated_data.ldr_scene_framebuffer,
+                    temp_frame_texture,
+                    &self.flat_shader,
+                    viewport,
+                    quad,
+                )?;
+            }
+
+            // Render debug geometry in the LDR frame buffer.
+            self.statistics += self.debug_renderer.render(
+                state,
+                viewport,
+                &mut scene_associated_data.ldr_scene_framebuffer,
+                &scene.drawing_context,
+                camera,
+            )?;
+
+            // Render debug labels on top of the debug geometry.
+            if let Some(labels) =
+                self.debug_renderer
+                    .prepare_labels(&scene.drawing_context, camera, frame_size)
+            {
+                self.statistics += self.ui_renderer.render(UiRenderContext {
+                    state,
+                    viewport: Rect::new(0, 0, frame_size.x as i32, frame_size.y as i32),
+                    frame_buffer: &mut scene_associated_data.ldr_scene_framebuffer,
+                    frame_width: frame_size.x,
+                    frame_height: frame_size.y,
+                    drawing_context: labels,
+                    white_dummy: self.white_dummy.clone(),
+                    texture_cache: &mut self.texture_cache,
+                })?;
+            }
+
+            for render_pass in self.scene_render_passes.iter() {
+                self.statistics +=
+                    render_pass
+                        .borrow_mut()
+                        .on_ldr_render(SceneRenderPassContext {
+                            pipeline_state: state,
+                            texture_cache: &mut self.texture_cache,
+                            geometry_cache: &mut self.geometry_cache,
+                            quality_settings: &self.quality_settings,
+                            batch_storage: &batch_storage,
+                            viewport,
+                            scene,
+                            camera,
+                            scene_handle,
+                            white_dummy: self.white_dummy.clone(),
+                            normal_dummy: self.normal_dummy.clone(),
+                            metallic_dummy: self.metallic_dummy.clone(),
+                            environment_dummy: self.environment_dummy.clone(),
+                            black_dummy: self.black_dummy.clone(),
+                            depth_texture: scene_associated_data.gbuffer.depth(),
+                            normal_texture: scene_associated_data.gbuffer.normal_texture(),
+                            ambient_texture: scene_associated_data.gbuffer.ambient_texture(),
+                            framebuffer: &mut scene_associated_data.ldr_scene_framebuffer,
+                            ui_renderer: &mut self.ui_renderer,
+                        })?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Renders either every enabled scene (with every enabled camera) or just a single scene
+    /// view into the back buffer of currently bound surface.
+    fn render_scenes(
+        &mut self,
+        scenes: &SceneContainer,
+        scene_view: Option<SceneView>,
+        backbuffer_size: (u32, u32),
+        scene_data_map: &mut FxHashMap<Handle<Scene>, AssociatedSceneData>,
+        drawing_context: Option<&DrawingContext>,
+        dt: f32,
+    ) -> Result<(), FrameworkError> {
+        let window_viewport = Rect::new(0, 0, backbuffer_size.0 as i32, backbuffer_size.1 as i32);
         self.backbuffer.clear(
             &mut self.state,
             window_viewport,
@@ -1507,15 +2418,26 @@ impl Renderer {
             Some(0),
         );
 
-        let backbuffer_width = self.frame_size.0 as f32;
-        let backbuffer_height = self.frame_size.1 as f32;
+        let backbuffer_width = backbuffer_size.0 as f32;
+        let backbuffer_height = backbuffer_size.1 as f32;
 
-        for (scene_handle, scene) in scenes.pair_iter().filter(|(_, s)| s.enabled) {
-            let graph = &scene.graph;
+        for (scene_handle, scene) in scenes
+            .handles_by_priority()
+            .into_iter()
+            .map(|handle| (handle, &scenes[handle]))
+            .filter(|(handle, scene)| {
+                scene.is_render_enabled() && scene_view.map_or(true, |view| view.scene == *handle)
+            })
+        {
+            self.render_reflection_probes(scene)?;
 
-            let frame_size = scene
+            // Scene views are always rendered into the back buffer.
+            let render_target = scene
                 .render_target
                 .as_ref()
+                .filter(|_| scene_view.is_none());
+
+            let frame_size = render_target
                 .map_or_else(
                     // Use either backbuffer size
                     || Vector2::new(backbuffer_width, backbuffer_height),
@@ -1533,8 +2455,7 @@ impl Renderer {
 
             let state = &mut self.state;
 
-            let scene_associated_data = self
-                .scene_data_map
+            let scene_associated_data = scene_data_map
                 .entry(scene_handle)
                 .and_modify(|data| {
                     if data.gbuffer.width != frame_size.x as i32
@@ -1564,266 +2485,21 @@ impl Renderer {
                     AssociatedSceneData::new(state, width, height).unwrap()
                 });
 
-            // If we specified a texture to draw to, we have to register it in texture cache
-            // so it can be used in later on as texture. This is useful in case if you need
-            // to draw something on offscreen and then draw it on some mesh.
-            // TODO: However it can be dangerous to use frame texture as it may be bound to
-            //  pipeline.
-            if let Some(rt) = scene.render_target.clone() {
-                self.texture_cache.map.insert(
-                    rt.key(),
-                    CacheEntry {
-                        value: scene_associated_data.ldr_scene_frame_texture(),
-                        time_to_live: f32::INFINITY,
-                        value_hash: 0, // TODO
-                    },
-                );
-            }
-
-            for camera in graph
-                .linear_iter()
-                .filter_map(|node| node.cast::<Camera>().filter(|&camera| camera.is_enabled()))
-            {
-                let viewport = camera.viewport_pixels(frame_size);
-
-                let batch_storage = RenderDataBatchStorage::from_graph(
-                    graph,
-                    ObserverInfo {
-                        observer_position: camera.global_position(),
-                        z_near: camera.projection().z_near(),
-                        z_far: camera.projection().z_far(),
-                        view_matrix: camera.view_matrix(),
-                        projection_matrix: camera.projection_matrix(),
-                    },
-                    GBUFFER_PASS_NAME.clone(),
-                );
-
-                state.set_polygon_fill_mode(
-                    PolygonFace::FrontAndBack,
-                    scene.polygon_rasterization_mode,
-                );
-
-                self.statistics += scene_associated_data.gbuffer.fill(GBufferRenderContext {
-                    state,
-                    camera,
-                    geom_cache: &mut self.geometry_cache,
-                    batch_storage: &batch_storage,
-                    texture_cache: &mut self.texture_cache,
-                    shader_cache: &mut self.shader_cache,
-                    environment_dummy: self.environment_dummy.clone(),
-                    use_parallax_mapping: self.quality_settings.use_parallax_mapping,
-                    normal_dummy: self.normal_dummy.clone(),
-                    white_dummy: self.white_dummy.clone(),
-                    black_dummy: self.black_dummy.clone(),
-                    volume_dummy: self.volume_dummy.clone(),
-                    graph,
-                    matrix_storage: &mut self.matrix_storage,
-                })?;
-
-                state.set_polygon_fill_mode(PolygonFace::FrontAndBack, PolygonFillMode::Fill);
-
-                scene_associated_data.copy_depth_stencil_to_scene_framebuffer(state);
-
-                scene_associated_data.hdr_scene_framebuffer.clear(
-                    state,
-                    viewport,
-                    Some(self.backbuffer_clear_color),
-                    None, // Keep depth, we've just copied valid data in it.
-                    Some(0),
-                );
-
-                let (pass_stats, light_stats) =
-                    self.deferred_light_renderer
-                        .render(DeferredRendererContext {
-                            state,
-                            scene,
-                            camera,
-                            gbuffer: &mut scene_associated_data.gbuffer,
-                            white_dummy: self.white_dummy.clone(),
-                            ambient_color: scene.ambient_lighting_color,
-                            settings: &self.quality_settings,
-                            textures: &mut self.texture_cache,
-                            geometry_cache: &mut self.geometry_cache,
-                            frame_buffer: &mut scene_associated_data.hdr_scene_framebuffer,
-                            shader_cache: &mut self.shader_cache,
-                            normal_dummy: self.normal_dummy.clone(),
-                            black_dummy: self.black_dummy.clone(),
-                            volume_dummy: self.volume_dummy.clone(),
-                            matrix_storage: &mut self.matrix_storage,
-                        })?;
-
-                self.statistics.lighting += light_stats;
-                self.statistics.geometry += pass_stats;
-
-                let depth = scene_associated_data.gbuffer.depth();
-
-                self.statistics +=
-                    self.particle_system_renderer
-                        .render(ParticleSystemRenderContext {
-                            state,
-                            framebuffer: &mut scene_associated_data.hdr_scene_framebuffer,
-                            graph,
-                            camera,
-                            white_dummy: self.white_dummy.clone(),
-                            depth,
-                            frame_width: frame_size.x,
-                            frame_height: frame_size.y,
-                            viewport,
-                            texture_cache: &mut self.texture_cache,
-                        })?;
-
-                self.statistics += self.sprite_renderer.render(SpriteRenderContext {
-                    state,
-                    framebuffer: &mut scene_associated_data.hdr_scene_framebuffer,
-                    graph,
-                    camera,
-                    white_dummy: self.white_dummy.clone(),
-                    viewport,
-                    textures: &mut self.texture_cache,
-                })?;
-
-                self.statistics += self.renderer2d.render(
-                    state,
-                    camera,
-                    &mut scene_associated_data.hdr_scene_framebuffer,
-                    viewport,
-                    graph,
-                    &mut self.texture_cache,
-                    self.white_dummy.clone(),
-                    scene.ambient_lighting_color,
-                )?;
-
-                self.statistics += self.forward_renderer.render(ForwardRenderContext {
-                    state,
-                    camera,
-                    geom_cache: &mut self.geometry_cache,
-                    texture_cache: &mut self.texture_cache,
-                    shader_cache: &mut self.shader_cache,
-                    batch_storage: &batch_storage,
-                    framebuffer: &mut scene_associated_data.hdr_scene_framebuffer,
-                    viewport,
-                    quality_settings: &self.quality_settings,
-                    white_dummy: self.white_dummy.clone(),
-                    normal_dummy: self.normal_dummy.clone(),
-                    black_dummy: self.black_dummy.clone(),
-                    volume_dummy: self.volume_dummy.clone(),
-                    matrix_storage: &mut self.matrix_storage,
-                })?;
-
-                for render_pass in self.scene_render_passes.iter() {
-                    self.statistics +=
-                        render_pass
-                            .borrow_mut()
-                            .on_hdr_render(SceneRenderPassContext {
-                                pipeline_state: state,
-                                texture_cache: &mut self.texture_cache,
-                                geometry_cache: &mut self.geometry_cache,
-                                quality_settings: &self.quality_settings,
-                                batch_storage: &batch_storage,
-                                viewport,
-                                scene,
-                                camera,
-                                scene_handle,
-                                white_dummy: self.white_dummy.clone(),
-                                normal_dummy: self.normal_dummy.clone(),
-                                metallic_dummy: self.metallic_dummy.clone(),
-                                environment_dummy: self.environment_dummy.clone(),
-                                black_dummy: self.black_dummy.clone(),
-                                depth_texture: scene_associated_data.gbuffer.depth(),
-                                normal_texture: scene_associated_data.gbuffer.normal_texture(),
-                                ambient_texture: scene_associated_data.gbuffer.ambient_texture(),
-                                framebuffer: &mut scene_associated_data.hdr_scene_framebuffer,
-                                ui_renderer: &mut self.ui_renderer,
-                            })?;
-                }
-
-                let quad = &self.quad;
-
-                // Prepare glow map.
-                self.statistics.geometry += scene_associated_data.bloom_renderer.render(
-                    state,
-                    quad,
-                    scene_associated_data.hdr_scene_frame_texture(),
-                )?;
-
-                // Convert high dynamic range frame to low dynamic range (sRGB) with tone mapping and gamma correction.
-                self.statistics.geometry += scene_associated_data.hdr_renderer.render(
-                    state,
-                    scene_associated_data.hdr_scene_frame_texture(),
-                    scene_associated_data.bloom_renderer.result(),
-                    &mut scene_associated_data.ldr_scene_framebuffer,
-                    viewport,
-                    quad,
-                    dt,
-                    camera.exposure(),
-                    camera.color_grading_lut_ref(),
-                    camera.color_grading_enabled(),
-                    &mut self.texture_cache,
-                )?;
-
-                // Apply FXAA if needed.
-                if self.quality_settings.fxaa {
-                    self.statistics.geometry += self.fxaa_renderer.render(
-                        state,
-                        viewport,
-                        scene_associated_data.ldr_scene_frame_texture(),
-                        &mut scene_associated_data.ldr_temp_framebuffer,
-                    )?;
-
-                    let quad = &self.quad;
-                    let temp_frame_texture = scene_associated_data.ldr_temp_frame_texture();
-                    self.statistics.geometry += blit_pixels(
-                        state,
-                        &mut scene_associated_data.ldr_scene_framebuffer,
-                        temp_frame_texture,
-                        &self.flat_shader,
-                        viewport,
-                        quad,
-                    )?;
-                }
-
-                // Render debug geometry in the LDR frame buffer.
-                self.statistics += self.debug_renderer.render(
-                    state,
-                    viewport,
-                    &mut scene_associated_data.ldr_scene_framebuffer,
-                    &scene.drawing_context,
-                    camera,
-                )?;
-
-                for render_pass in self.scene_render_passes.iter() {
-                    self.statistics +=
-                        render_pass
-                            .borrow_mut()
-                            .on_ldr_render(SceneRenderPassContext {
-                                pipeline_state: state,
-                                texture_cache: &mut self.texture_cache,
-                                geometry_cache: &mut self.geometry_cache,
-                                quality_settings: &self.quality_settings,
-                                batch_storage: &batch_storage,
-                                viewport,
-                                scene,
-                                camera,
-                                scene_handle,
-                                white_dummy: self.white_dummy.clone(),
-                                normal_dummy: self.normal_dummy.clone(),
-                                metallic_dummy: self.metallic_dummy.clone(),
-                                environment_dummy: self.environment_dummy.clone(),
-                                black_dummy: self.black_dummy.clone(),
-                                depth_texture: scene_associated_data.gbuffer.depth(),
-                                normal_texture: scene_associated_data.gbuffer.normal_texture(),
-                                ambient_texture: scene_associated_data.gbuffer.ambient_texture(),
-                                framebuffer: &mut scene_associated_data.ldr_scene_framebuffer,
-                                ui_renderer: &mut self.ui_renderer,
-                            })?;
-                }
-            }
+            self.render_scene_cameras(
+                scene_handle,
+                scene,
+                scene_view,
+                render_target,
+                frame_size,
+                scene_associated_data,
+                dt,
+            )?;
 
             // Optionally render everything into back buffer.
-            if scene.render_target.is_none() {
+            if render_target.is_none() {
                 let quad = &self.quad;
                 self.statistics.geometry += blit_pixels(
-                    state,
+                    &mut self.state,
                     &mut self.backbuffer,
                     scene_associated_data.ldr_scene_frame_texture(),
                     &self.flat_shader,
@@ -1837,16 +2513,18 @@ impl Renderer {
             .set_polygon_fill_mode(PolygonFace::FrontAndBack, PolygonFillMode::Fill);
 
         // Render UI on top of everything without gamma correction.
-        self.statistics += self.ui_renderer.render(UiRenderContext {
-            state: &mut self.state,
-            viewport: window_viewport,
-            frame_buffer: &mut self.backbuffer,
-            frame_width: backbuffer_width,
-            frame_height: backbuffer_height,
-            drawing_context,
-            white_dummy: self.white_dummy.clone(),
-            texture_cache: &mut self.texture_cache,
-        })?;
+        if let Some(drawing_context) = drawing_context {
+            self.statistics += self.ui_renderer.render(UiRenderContext {
+                state: &mut self.state,
+                viewport: window_viewport,
+                frame_buffer: &mut self.backbuffer,
+                frame_width: backbuffer_width,
+                frame_height: backbuffer_height,
+                drawing_context,
+                white_dummy: self.white_dummy.clone(),
+                texture_cache: &mut self.texture_cache,
+            })?;
+        }
 
         Ok(())
     }
@@ -1859,6 +2537,10 @@ impl Renderer {
         surface: &Surface<WindowSurface>,
         context: &PossiblyCurrentContext,
     ) -> Result<(), FrameworkError> {
+        if self.state.is_context_lost() {
+            return Ok(());
+        }
+
         self.render_frame(scenes, drawing_context)?;
         self.statistics.end_frame();
         surface.swap_buffers(context)?;
@@ -1868,12 +2550,69 @@ impl Renderer {
         Ok(())
     }
 
+    /// Renders a single frame into a secondary window. The surface of the window must be current
+    /// at the moment of the call. Scene view (if any) is rendered first and then the UI (if any) is
+    /// drawn on top of it.
+    #[cfg(not(target_arch = "wasm32"))]
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn render_secondary_window_and_swap_buffers(
+        &mut self,
+        window_id: WindowId,
+        scenes: &SceneContainer,
+        scene_view: Option<SceneView>,
+        frame_size: (u32, u32),
+        drawing_context: Option<&DrawingContext>,
+        surface: &Surface<WindowSurface>,
+        context: &PossiblyCurrentContext,
+    ) -> Result<(), FrameworkError> {
+        scope_profile!();
+
+        if self.state.is_context_lost() {
+            return Ok(());
+        }
+
+        let frame_size = (frame_size.0.max(1), frame_size.1.max(1));
+
+        let mut scene_data_map = self
+            .secondary_scene_data_map
+            .remove(&window_id)
+            .unwrap_or_default();
+        scene_data_map.retain(|h, _| scene_view.map_or(false, |view| view.scene == *h));
+
+        let dt = self.statistics.capped_frame_time;
+        let result = self.render_scenes(
+            scenes,
+            scene_view,
+            frame_size,
+            &mut scene_data_map,
+            drawing_context,
+            dt,
+        );
+        self.secondary_scene_data_map
+            .insert(window_id, scene_data_map);
+        result?;
+
+        surface.swap_buffers(context)?;
+        self.state.check_error();
+        Ok(())
+    }
+
+    /// Frees every GPU resource that was associated with the given secondary window.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(crate) fn on_secondary_window_destroyed(&mut self, window_id: WindowId) {
+        self.secondary_scene_data_map.remove(&window_id);
+    }
+
     #[cfg(target_arch = "wasm32")]
     pub(crate) fn render_and_swap_buffers(
         &mut self,
         scenes: &SceneContainer,
         drawing_context: &DrawingContext,
     ) -> Result<(), FrameworkError> {
+        if self.state.is_context_lost() {
+            return Ok(());
+        }
+
         self.render_frame(scenes, drawing_context)?;
         self.statistics.end_frame();
         self.state.check_error();
@@ -1882,3 +2621,98 @@ impl Renderer {
         Ok(())
     }
 }
+
+// Creating an off-screen OpenGL context requires FFI calls.
+#[cfg(all(test, target_os = "linux"))]
+#[allow(unsafe_code)]
+mod test {
+    use crate::{
+        asset::manager::ResourceManager,
+        renderer::{framework::state::GlKind, Renderer},
+        resource::texture::{Texture, TextureKind, TexturePixelKind, TextureResource},
+    };
+    use glutin::{
+        api::egl::{context::PossiblyCurrentContext, device::Device, display::Display},
+        config::{ConfigSurfaceTypes, ConfigTemplateBuilder},
+        context::{ContextApi, ContextAttributesBuilder, Version},
+        prelude::*,
+    };
+    use std::ffi::CString;
+
+    // Creates an off-screen OpenGL context, `None` if there's no EGL device (for example, on CI
+    // machines without GPU and software rasterizer).
+    fn headless_context() -> Option<(Display, PossiblyCurrentContext)> {
+        let device = Device::query_devices().ok()?.next()?;
+        let display = unsafe { Display::with_device(&device, None) }.ok()?;
+        let template = ConfigTemplateBuilder::new()
+            .with_surface_type(ConfigSurfaceTypes::empty())
+            .build();
+        let config = unsafe { display.find_configs(template) }.ok()?.next()?;
+        let attributes = ContextAttributesBuilder::new()
+            .with_context_api(ContextApi::OpenGl(Some(Version::new(3, 3))))
+            .build(None);
+        let context = unsafe { display.create_context(&config, &attributes) }
+            .ok()?
+            .make_current_surfaceless()
+            .ok()?;
+        Some((display, context))
+    }
+
+    fn glow_context(display: &Display) -> glow::Context {
+        unsafe {
+            glow::Context::from_loader_function(|s| {
+                display.get_proc_address(&CString::new(s).unwrap())
+            })
+        }
+    }
+
+    #[test]
+    fn test_context_loss_restores_resources() {
+        let (display, _context) = match headless_context() {
+            Some(context) => context,
+            None => return,
+        };
+
+        let resource_manager = ResourceManager::new();
+        let mut renderer = Renderer::new(
+            glow_context(&display),
+            (64, 64),
+            &resource_manager,
+            GlKind::OpenGL,
+        )
+        .unwrap();
+
+        let texture = TextureResource::new_ok(
+            Texture::from_bytes(
+                TextureKind::Rectangle {
+                    width: 1,
+                    height: 1,
+                },
+                TexturePixelKind::RGBA8,
+                vec![255; 4],
+                false,
+            )
+            .unwrap(),
+        );
+        assert!(renderer
+            .texture_cache
+            .get(&mut renderer.state, &texture)
+            .is_some());
+
+        renderer.simulate_context_loss();
+        assert!(renderer.is_context_lost());
+
+        renderer
+            .restore_context(glow_context(&display), &resource_manager, GlKind::OpenGL)
+            .unwrap();
+        assert!(!renderer.is_context_lost());
+        // GPU objects of the lost context must be released, and the texture must be uploaded again
+        // on demand.
+        assert!(!renderer.texture_cache.map.contains_key(&texture.key()));
+        assert!(renderer
+            .texture_cache
+            .get(&mut renderer.state, &texture)
+            .is_some());
+        assert!(renderer.texture_cache.map.contains_key(&texture.key()));
+    }
+}
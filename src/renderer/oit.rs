@@ -0,0 +1,198 @@
+//! Weighted blended order-independent transparency (McGuire and Bavoil, 2013).
+//!
+//! Transparent fragments are accumulated into two render targets regardless of their order: the
+//! first one stores sum of weighted premultiplied colors in RGB and product of `1 - alpha`
+//! (revealage) in alpha, the second one stores sum of weights. Then the composition pass blends
+//! weighted average color over the frame using revealage. Both targets are written with the same
+//! blending function, so the technique does not require per-target blending (OpenGL 4.0), only
+//! blending of floating-point render targets.
+
+use crate::{
+    core::{color::Color, math::Rect, scope_profile, sstorage::ImmutableString},
+    renderer::{
+        framework::{
+            error::FrameworkError,
+            framebuffer::{
+                Attachment, AttachmentKind, BlendParameters, DrawParameters, FrameBuffer,
+            },
+            geometry_buffer::{ElementRange, GeometryBuffer, GeometryBufferKind},
+            gpu_program::{GpuProgram, UniformLocation},
+            gpu_texture::{
+                GpuTexture, GpuTextureKind, MagnificationFilter, MinificationFilter, PixelKind,
+            },
+            state::{BlendFactor, BlendFunc, GlKind, PipelineState},
+        },
+        make_viewport_matrix, RenderPassStatistics,
+    },
+    scene::mesh::surface::SurfaceData,
+};
+use glow::HasContext;
+use std::{cell::RefCell, rc::Rc};
+
+/// Blending parameters that must be used by every draw call in the accumulation pass.
+pub(crate) fn accumulation_blend_parameters() -> BlendParameters {
+    BlendParameters {
+        func: BlendFunc::new_separate(
+            BlendFactor::One,
+            BlendFactor::One,
+            BlendFactor::Zero,
+            BlendFactor::OneMinusSrcAlpha,
+        ),
+        ..Default::default()
+    }
+}
+
+/// Checks whether floating-point render targets could be used with blending. This is always the
+/// case for desktop OpenGL 3.3, but OpenGL ES requires extensions for that.
+pub(crate) fn is_supported(state: &PipelineState) -> bool {
+    match state.gl_kind() {
+        GlKind::OpenGL => true,
+        GlKind::OpenGLES => {
+            let extensions = state.gl.supported_extensions();
+            let has = |name: &str| {
+                extensions.contains(name) || extensions.contains(&format!("GL_{}", name))
+            };
+            has("EXT_color_buffer_float") && has("EXT_float_blend")
+        }
+    }
+}
+
+/// Creates an accumulation frame buffer, that shares depth with the given frame buffer.
+pub(crate) fn make_accumulation_framebuffer(
+    state: &mut PipelineState,
+    width: usize,
+    height: usize,
+    depth: Rc<RefCell<GpuTexture>>,
+) -> Result<FrameBuffer, FrameworkError> {
+    let mut make_target = |pixel_kind| {
+        GpuTexture::new(
+            state,
+            GpuTextureKind::Rectangle { width, height },
+            pixel_kind,
+            MinificationFilter::Nearest,
+            MagnificationFilter::Nearest,
+            1,
+            None,
+        )
+        .map(|texture| Attachment {
+            kind: AttachmentKind::Color,
+            texture: Rc::new(RefCell::new(texture)),
+        })
+    };
+
+    let accum = make_target(PixelKind::RGBA16F)?;
+    let weight = make_target(PixelKind::R16F)?;
+
+    FrameBuffer::new(
+        state,
+        Some(Attachment {
+            kind: AttachmentKind::DepthStencil,
+            texture: depth,
+        }),
+        vec![accum, weight],
+    )
+}
+
+/// Prepares the accumulation frame buffer for a new frame: no color and full revealage.
+pub(crate) fn clear_accumulation_framebuffer(
+    state: &mut PipelineState,
+    framebuffer: &mut FrameBuffer,
+    viewport: Rect<i32>,
+) {
+    framebuffer.clear(
+        state,
+        viewport,
+        Some(Color::from_rgba(0, 0, 0, 255)),
+        None,
+        None,
+    );
+}
+
+struct CompositeShader {
+    program: GpuProgram,
+    wvp_matrix: UniformLocation,
+    accum_texture: UniformLocation,
+    weight_texture: UniformLocation,
+}
+
+impl CompositeShader {
+    fn new(state: &mut PipelineState) -> Result<Self, FrameworkError> {
+        let fragment_source = include_str!("shaders/oit_composite_fs.glsl");
+        let vertex_source = include_str!("shaders/flat_vs.glsl");
+
+        let program =
+            GpuProgram::from_source(state, "OitCompositeShader", vertex_source, fragment_source)?;
+        Ok(Self {
+            wvp_matrix: program
+                .uniform_location(state, &ImmutableString::new("worldViewProjection"))?,
+            accum_texture: program
+                .uniform_location(state, &ImmutableString::new("accumTexture"))?,
+            weight_texture: program
+                .uniform_location(state, &ImmutableString::new("weightTexture"))?,
+            program,
+        })
+    }
+}
+
+pub(crate) struct OitRenderer {
+    shader: CompositeShader,
+    quad: GeometryBuffer,
+}
+
+impl OitRenderer {
+    pub fn new(state: &mut PipelineState) -> Result<Self, FrameworkError> {
+        Ok(Self {
+            shader: CompositeShader::new(state)?,
+            quad: GeometryBuffer::from_surface_data(
+                &SurfaceData::make_unit_xy_quad(),
+                GeometryBufferKind::StaticDraw,
+                state,
+            ),
+        })
+    }
+
+    /// Blends accumulated transparent fragments over the frame.
+    pub fn composite(
+        &self,
+        state: &mut PipelineState,
+        viewport: Rect<i32>,
+        accumulation: &FrameBuffer,
+        frame_buffer: &mut FrameBuffer,
+    ) -> Result<RenderPassStatistics, FrameworkError> {
+        scope_profile!();
+
+        let mut statistics = RenderPassStatistics::default();
+
+        let accum = accumulation.color_attachments()[0].texture.clone();
+        let weight = accumulation.color_attachments()[1].texture.clone();
+        let frame_matrix = make_viewport_matrix(viewport);
+
+        statistics += frame_buffer.draw(
+            &self.quad,
+            state,
+            viewport,
+            &self.shader.program,
+            &DrawParameters {
+                cull_face: None,
+                color_write: Default::default(),
+                depth_write: false,
+                stencil_test: None,
+                depth_test: false,
+                blend: Some(BlendParameters {
+                    func: BlendFunc::new(BlendFactor::SrcAlpha, BlendFactor::OneMinusSrcAlpha),
+                    ..Default::default()
+                }),
+                stencil_op: Default::default(),
+            },
+            ElementRange::Full,
+            |mut program_binding| {
+                program_binding
+                    .set_matrix4(&self.shader.wvp_matrix, &frame_matrix)
+                    .set_texture(&self.shader.accum_texture, &accum)
+                    .set_texture(&self.shader.weight_texture, &weight);
+            },
+        )?;
+
+        Ok(statistics)
+    }
+}
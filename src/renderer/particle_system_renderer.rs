@@ -16,7 +16,7 @@ use crate::{
         gpu_texture::GpuTexture,
         state::PipelineState,
     },
-    renderer::{RenderPassStatistics, TextureCache},
+    renderer::{oit, RenderPassStatistics, TextureCache},
     scene::{camera::Camera, graph::Graph, particle_system},
 };
 use std::{cell::RefCell, rc::Rc};
@@ -35,15 +35,13 @@ struct ParticleSystemShader {
 }
 
 impl ParticleSystemShader {
-    fn new(state: &mut PipelineState) -> Result<Self, FrameworkError> {
+    fn new(
+        state: &mut PipelineState,
+        name: &str,
+        fragment_source: &str,
+    ) -> Result<Self, FrameworkError> {
         let vertex_source = include_str!("shaders/particle_system_vs.glsl");
-        let fragment_source = include_str!("shaders/particle_system_fs.glsl");
-        let program = GpuProgram::from_source(
-            state,
-            "ParticleSystemShader",
-            vertex_source,
-            fragment_source,
-        )?;
+        let program = GpuProgram::from_source(state, name, vertex_source, fragment_source)?;
         Ok(Self {
             view_projection_matrix: program
                 .uniform_location(state, &ImmutableString::new("viewProjectionMatrix"))?,
@@ -68,6 +66,7 @@ impl ParticleSystemShader {
 
 pub struct ParticleSystemRenderer {
     shader: ParticleSystemShader,
+    oit_shader: ParticleSystemShader,
     draw_data: particle_system::draw::DrawData,
     geometry_buffer: GeometryBuffer,
     sorted_particles: Vec<u32>,
@@ -84,6 +83,8 @@ pub(crate) struct ParticleSystemRenderContext<'a, 'b, 'c> {
     pub frame_height: f32,
     pub viewport: Rect<i32>,
     pub texture_cache: &'a mut TextureCache,
+    /// Whether the frame buffer is an accumulation buffer of order-independent transparency or not.
+    pub oit: bool,
 }
 
 impl ParticleSystemRenderer {
@@ -128,7 +129,16 @@ impl ParticleSystemRenderer {
             .build(state)?;
 
         Ok(Self {
-            shader: ParticleSystemShader::new(state)?,
+            shader: ParticleSystemShader::new(
+                state,
+                "ParticleSystemShader",
+                include_str!("shaders/particle_system_fs.glsl"),
+            )?,
+            oit_shader: ParticleSystemShader::new(
+                state,
+                "ParticleSystemOitShader",
+                include_str!("shaders/particle_system_oit_fs.glsl"),
+            )?,
             draw_data: Default::default(),
             geometry_buffer,
             sorted_particles: Vec::new(),
@@ -154,8 +164,21 @@ impl ParticleSystemRenderer {
             frame_height,
             viewport,
             texture_cache,
+            oit,
         } = args;
 
+        let (shader, blend) = if oit {
+            (&self.oit_shader, oit::accumulation_blend_parameters())
+        } else {
+            (
+                &self.shader,
+                BlendParameters {
+                    func: BlendFunc::new(BlendFactor::SrcAlpha, BlendFactor::OneMinusSrcAlpha),
+                    ..Default::default()
+                },
+            )
+        };
+
         let inv_view = camera.inv_view_matrix().unwrap();
         let view_proj = camera.view_projection_matrix();
 
@@ -168,6 +191,7 @@ impl ParticleSystemRenderer {
         for particle_system in graph
             .linear_iter()
             .filter_map(|n| n.cast::<ParticleSystem>())
+            .filter(|ps| ps.is_globally_enabled())
         {
             particle_system.generate_draw_data(
                 &mut self.sorted_particles,
@@ -189,10 +213,7 @@ impl ParticleSystemRenderer {
                 depth_write: false,
                 stencil_test: None,
                 depth_test: true,
-                blend: Some(BlendParameters {
-                    func: BlendFunc::new(BlendFactor::SrcAlpha, BlendFactor::OneMinusSrcAlpha),
-                    ..Default::default()
-                }),
+                blend: Some(blend.clone()),
                 stencil_op: Default::default(),
             };
 
@@ -205,21 +226,21 @@ impl ParticleSystemRenderer {
                 &self.geometry_buffer,
                 state,
                 viewport,
-                &self.shader.program,
+                &shader.program,
                 &draw_params,
                 ElementRange::Full,
                 |mut program_binding| {
                     program_binding
-                        .set_texture(&self.shader.depth_buffer_texture, &depth)
-                        .set_texture(&self.shader.diffuse_texture, &diffuse_texture)
-                        .set_vector3(&self.shader.camera_side_vector, &camera_side)
-                        .set_vector3(&self.shader.camera_up_vector, &camera_up)
-                        .set_matrix4(&self.shader.view_projection_matrix, &view_proj)
-                        .set_matrix4(&self.shader.world_matrix, &global_transform)
-                        .set_vector2(&self.shader.inv_screen_size, &inv_screen_size)
-                        .set_vector2(&self.shader.proj_params, &proj_params)
+                        .set_texture(&shader.depth_buffer_texture, &depth)
+                        .set_texture(&shader.diffuse_texture, &diffuse_texture)
+                        .set_vector3(&shader.camera_side_vector, &camera_side)
+                        .set_vector3(&shader.camera_up_vector, &camera_up)
+                        .set_matrix4(&shader.view_projection_matrix, &view_proj)
+                        .set_matrix4(&shader.world_matrix, &global_transform)
+                        .set_vector2(&shader.inv_screen_size, &inv_screen_size)
+                        .set_vector2(&shader.proj_params, &proj_params)
                         .set_f32(
-                            &self.shader.soft_boundary_sharpness_factor,
+                            &shader.soft_boundary_sharpness_factor,
                             particle_system.soft_boundary_sharpness_factor(),
                         );
                 },
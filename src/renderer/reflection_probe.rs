@@ -0,0 +1,377 @@
+//! Reflection probe renderer captures surroundings of reflection probes into cube maps and keeps
+//! the cube maps on GPU. See [`crate::scene::reflection_probe::ReflectionProbe`] docs for more info.
+
+use crate::{
+    asset::entry::DEFAULT_RESOURCE_LIFETIME,
+    core::{
+        algebra::{Matrix4, Vector2, Vector3, Vector4},
+        math::{aabb::AxisAlignedBoundingBox, frustum::Frustum, Rect},
+    },
+    renderer::{
+        framework::{
+            error::FrameworkError,
+            framebuffer::{Attachment, AttachmentKind, FrameBuffer},
+            gpu_texture::{
+                Coordinate, CubeMapFace, GpuTexture, GpuTextureKind, MagnificationFilter,
+                MinificationFilter, PixelKind, WrapMode,
+            },
+            state::PipelineState,
+        },
+        AssociatedSceneData,
+    },
+    resource::texture::{Texture, TextureKind, TexturePixelKind},
+    scene::{
+        base::BaseBuilder,
+        camera::{Camera, CameraBuilder, PerspectiveProjection, Projection},
+        graph::Graph,
+        reflection_probe::ReflectionProbe,
+    },
+};
+use fxhash::FxHashMap;
+use std::{cell::RefCell, cmp::Ordering, rc::Rc};
+
+/// Maximum amount of reflection probes that could be used at once by the lighting pass.
+pub(crate) const MAX_VISIBLE_PROBES: usize = 4;
+
+pub(crate) struct ProbeCubeFace {
+    pub face: CubeMapFace,
+    pub look: Vector3<f32>,
+    pub up: Vector3<f32>,
+}
+
+pub(crate) struct ProbeCubeMap {
+    pub framebuffer: FrameBuffer,
+    resolution: usize,
+    // Hash of baked data, that was uploaded to (or read back from) the cube map.
+    data_hash: u64,
+    time_to_live: f32,
+}
+
+impl ProbeCubeMap {
+    fn new(state: &mut PipelineState, resolution: usize) -> Result<Self, FrameworkError> {
+        let mut texture = GpuTexture::new(
+            state,
+            GpuTextureKind::Cube {
+                width: resolution,
+                height: resolution,
+            },
+            PixelKind::RGBA16F,
+            MinificationFilter::LinearMipMapLinear,
+            MagnificationFilter::Linear,
+            1,
+            None,
+        )?;
+        texture
+            .bind_mut(state, 0)
+            .set_wrap(Coordinate::S, WrapMode::ClampToEdge)
+            .set_wrap(Coordinate::T, WrapMode::ClampToEdge)
+            .set_wrap(Coordinate::R, WrapMode::ClampToEdge);
+
+        Ok(Self {
+            framebuffer: FrameBuffer::new(
+                state,
+                None,
+                vec![Attachment {
+                    kind: AttachmentKind::Color,
+                    texture: Rc::new(RefCell::new(texture)),
+                }],
+            )?,
+            resolution,
+            data_hash: 0,
+            time_to_live: DEFAULT_RESOURCE_LIFETIME,
+        })
+    }
+
+    pub fn texture(&self) -> Rc<RefCell<GpuTexture>> {
+        self.framebuffer.color_attachments()[0].texture.clone()
+    }
+
+    fn upload(&mut self, state: &mut PipelineState, baked: &Texture) -> Result<(), FrameworkError> {
+        self.texture()
+            .borrow_mut()
+            .bind_mut(state, 0)
+            .set_data(
+                GpuTextureKind::Cube {
+                    width: self.resolution,
+                    height: self.resolution,
+                },
+                PixelKind::RGBA32F,
+                1,
+                Some(baked.data()),
+            )?
+            .generate_mip_maps();
+        self.data_hash = baked.data_hash();
+        Ok(())
+    }
+}
+
+/// Data of a probe, that is needed by the lighting pass.
+pub(crate) struct ReflectionProbeInfo {
+    pub cube_map: Rc<RefCell<GpuTexture>>,
+    pub capture_position: Vector3<f32>,
+    pub bounds: AxisAlignedBoundingBox,
+    /// xyz - center, w - radius of a sphere volume, zero for box volumes.
+    pub sphere: Vector4<f32>,
+}
+
+pub(crate) struct ReflectionProbeRenderer {
+    cube_maps: FxHashMap<usize, ProbeCubeMap>,
+    /// Frame buffers for the probes of each resolution.
+    pub capture_data: FxHashMap<usize, AssociatedSceneData>,
+    pub faces: [ProbeCubeFace; 6],
+}
+
+impl ReflectionProbeRenderer {
+    pub fn new() -> Self {
+        Self {
+            cube_maps: Default::default(),
+            capture_data: Default::default(),
+            faces: [
+                ProbeCubeFace {
+                    face: CubeMapFace::PositiveX,
+                    look: Vector3::new(1.0, 0.0, 0.0),
+                    up: Vector3::new(0.0, -1.0, 0.0),
+                },
+                ProbeCubeFace {
+                    face: CubeMapFace::NegativeX,
+                    look: Vector3::new(-1.0, 0.0, 0.0),
+                    up: Vector3::new(0.0, -1.0, 0.0),
+                },
+                ProbeCubeFace {
+                    face: CubeMapFace::PositiveY,
+                    look: Vector3::new(0.0, 1.0, 0.0),
+                    up: Vector3::new(0.0, 0.0, 1.0),
+                },
+                ProbeCubeFace {
+                    face: CubeMapFace::NegativeY,
+                    look: Vector3::new(0.0, -1.0, 0.0),
+                    up: Vector3::new(0.0, 0.0, -1.0),
+                },
+                ProbeCubeFace {
+                    face: CubeMapFace::PositiveZ,
+                    look: Vector3::new(0.0, 0.0, 1.0),
+                    up: Vector3::new(0.0, -1.0, 0.0),
+                },
+                ProbeCubeFace {
+                    face: CubeMapFace::NegativeZ,
+                    look: Vector3::new(0.0, 0.0, -1.0),
+                    up: Vector3::new(0.0, -1.0, 0.0),
+                },
+            ],
+        }
+    }
+
+    /// Returns `true` if the probe must be captured, either because it was requested or because
+    /// the probe has neither a cube map on GPU nor baked data.
+    pub fn needs_capture(&self, probe: &ReflectionProbe) -> bool {
+        probe.is_render_requested()
+            || (!probe.is_baked() && !self.cube_maps.contains_key(&probe.cubemap().key()))
+    }
+
+    /// Returns a cube map of the probe, creating (and filling with baked data) it if needed.
+    pub fn get_or_create(
+        &mut self,
+        state: &mut PipelineState,
+        probe: &ReflectionProbe,
+    ) -> Result<&mut ProbeCubeMap, FrameworkError> {
+        let resolution = probe.resolution().max(1) as usize;
+
+        let cube_map = match self.cube_maps.entry(probe.cubemap().key()) {
+            std::collections::hash_map::Entry::Occupied(entry) => {
+                let cube_map = entry.into_mut();
+                if cube_map.resolution != resolution {
+                    *cube_map = ProbeCubeMap::new(state, resolution)?;
+                }
+                cube_map
+            }
+            std::collections::hash_map::Entry::Vacant(entry) => {
+                entry.insert(ProbeCubeMap::new(state, resolution)?)
+            }
+        };
+        cube_map.time_to_live = DEFAULT_RESOURCE_LIFETIME;
+
+        let baked = probe.cubemap().data_ref();
+        if baked.data_hash() != cube_map.data_hash
+            && matches!(baked.kind(), TextureKind::Cube { width, height }
+                if width == resolution as u32 && height == resolution as u32)
+            && baked.pixel_kind() == TexturePixelKind::RGBA32F
+        {
+            cube_map.upload(state, &baked)?;
+        }
+
+        Ok(cube_map)
+    }
+
+    /// Reads the first mip level of the cube map of the probe back from GPU and stores it in the
+    /// probe, so it will be saved with the scene.
+    pub fn bake(&mut self, state: &mut PipelineState, probe: &ReflectionProbe) {
+        let cube_map = if let Some(cube_map) = self.cube_maps.get_mut(&probe.cubemap().key()) {
+            cube_map
+        } else {
+            return;
+        };
+
+        let size = cube_map.resolution as i32;
+        let mut bytes = Vec::new();
+        for face in self.faces.iter() {
+            bytes.extend(
+                cube_map
+                    .framebuffer
+                    .set_cubemap_face(state, 0, face.face)
+                    .read_pixels_rgba32f(state, Rect::new(0, 0, size, size)),
+            );
+        }
+
+        if let Some(texture) = Texture::from_bytes(
+            TextureKind::Cube {
+                width: size as u32,
+                height: size as u32,
+            },
+            TexturePixelKind::RGBA32F,
+            bytes,
+            true,
+        ) {
+            cube_map.data_hash = texture.data_hash();
+            *probe.cubemap().data_ref() = texture;
+        }
+    }
+
+    /// Collects probes that could be used by the lighting pass for the given camera, the closest
+    /// probes to the camera are used first.
+    pub fn visible_probes(
+        &mut self,
+        state: &mut PipelineState,
+        graph: &Graph,
+        camera: &Camera,
+    ) -> Vec<ReflectionProbeInfo> {
+        let frustum = Frustum::from_view_projection_matrix(camera.view_projection_matrix())
+            .unwrap_or_default();
+        let camera_position = camera.global_position();
+
+        let mut probes = graph
+            .linear_iter()
+            .filter_map(|node| node.cast::<ReflectionProbe>())
+            .filter(|probe| {
+                probe.global_visibility()
+                    && probe.is_globally_enabled()
+                    && frustum.is_intersects_aabb(&probe.volume_bounds())
+            })
+            .collect::<Vec<_>>();
+
+        probes.sort_by(|a, b| {
+            let a = (a.capture_position() - camera_position).norm_squared();
+            let b = (b.capture_position() - camera_position).norm_squared();
+            a.partial_cmp(&b).unwrap_or(Ordering::Equal)
+        });
+
+        probes
+            .into_iter()
+            .filter_map(|probe| {
+                let cube_map = self.get_or_create(state, probe).ok()?;
+                let bounds = probe.volume_bounds();
+                Some(ReflectionProbeInfo {
+                    cube_map: cube_map.texture(),
+                    capture_position: probe.capture_position(),
+                    bounds,
+                    sphere: match probe.volume_radius() {
+                        Some(radius) => probe.global_position().push(radius),
+                        None => Vector4::default(),
+                    },
+                })
+            })
+            .take(MAX_VISIBLE_PROBES)
+            .collect()
+    }
+
+    /// Returns a camera, that captures the given face of the probe.
+    pub fn face_camera(probe: &ReflectionProbe, face: &ProbeCubeFace) -> Camera {
+        let mut camera = CameraBuilder::new(BaseBuilder::new())
+            .with_projection(Projection::Perspective(PerspectiveProjection {
+                fov: std::f32::consts::FRAC_PI_2,
+                z_near: probe.z_near(),
+                z_far: probe.z_far(),
+            }))
+            .build_camera();
+
+        let side = face.up.cross(&face.look);
+        camera.global_transform.set(Matrix4::from_columns(&[
+            side.push(0.0),
+            face.up.push(0.0),
+            face.look.push(0.0),
+            probe.capture_position().push(1.0),
+        ]));
+
+        let size = probe.resolution().max(1) as f32;
+        camera.calculate_matrices(Vector2::new(size, size));
+        camera
+    }
+
+    pub fn update(&mut self, dt: f32) {
+        self.cube_maps.retain(|_, cube_map| {
+            cube_map.time_to_live -= dt;
+            cube_map.time_to_live > 0.0
+        });
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::algebra::Vector3,
+        renderer::reflection_probe::ReflectionProbeRenderer,
+        resource::texture::{Texture, TextureKind, TexturePixelKind},
+        scene::{
+            base::BaseBuilder, reflection_probe::ReflectionProbeBuilder,
+            transform::TransformBuilder,
+        },
+    };
+
+    #[test]
+    fn test_needs_capture() {
+        let renderer = ReflectionProbeRenderer::new();
+        let probe = ReflectionProbeBuilder::new(BaseBuilder::new()).build_reflection_probe();
+
+        assert!(renderer.needs_capture(&probe));
+
+        // There's neither a cube map on GPU nor baked data.
+        probe.reset_render_request();
+        assert!(renderer.needs_capture(&probe));
+
+        *probe.cubemap().data_ref() = Texture::from_bytes(
+            TextureKind::Cube {
+                width: 1,
+                height: 1,
+            },
+            TexturePixelKind::RGBA32F,
+            vec![0; 6 * 16],
+            true,
+        )
+        .unwrap();
+        assert!(!renderer.needs_capture(&probe));
+
+        probe.request_render();
+        assert!(renderer.needs_capture(&probe));
+    }
+
+    #[test]
+    fn test_face_camera() {
+        let probe = ReflectionProbeBuilder::new(
+            BaseBuilder::new().with_local_transform(
+                TransformBuilder::new()
+                    .with_local_position(Vector3::new(1.0, 2.0, 3.0))
+                    .build(),
+            ),
+        )
+        .with_capture_offset(Vector3::new(0.0, 1.0, 0.0))
+        .build_reflection_probe();
+        probe.global_transform.set(probe.local_transform().matrix());
+
+        let renderer = ReflectionProbeRenderer::new();
+        for face in renderer.faces.iter() {
+            let camera = ReflectionProbeRenderer::face_camera(&probe, face);
+            assert_eq!(camera.global_position(), Vector3::new(1.0, 3.0, 3.0));
+            assert_eq!(camera.look_vector(), face.look);
+            assert_eq!(camera.up_vector(), face.up);
+        }
+    }
+}
@@ -101,7 +101,7 @@ impl SpriteBatchStorage {
         let mut batch_index = 0;
         for node in graph.linear_iter() {
             if let Some(rectangle) = node.cast::<Rectangle>() {
-                if !rectangle.global_visibility() {
+                if !rectangle.global_visibility() || !rectangle.is_globally_enabled() {
                     continue;
                 }
 
@@ -226,7 +226,10 @@ impl Renderer2d {
         let mut light_parameters = [Vector2::default(); MAX_LIGHTS];
 
         for light in graph.linear_iter() {
-            if !light.global_visibility() || light_count == MAX_LIGHTS {
+            if !light.global_visibility()
+                || !light.is_globally_enabled()
+                || light_count == MAX_LIGHTS
+            {
                 continue;
             }
 
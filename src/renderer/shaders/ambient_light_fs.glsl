@@ -1,18 +1,135 @@
 uniform sampler2D diffuseTexture;
 uniform sampler2D aoSampler;
 uniform sampler2D ambientTexture;
+uniform sampler2D depthTexture;
+uniform sampler2D normalTexture;
+uniform sampler2D materialTexture;
 uniform vec4 ambientColor;
+uniform mat4 invViewProj;
+uniform vec3 cameraPosition;
+
+// Sky box of the scene, used for pixels outside of every reflection probe.
+uniform bool environmentEnabled;
+uniform samplerCube environmentTexture;
+// HDR sky boxes are already in linear color space.
+uniform bool environmentLinear;
+// Irradiance of the sky box (divided by PI), used for diffuse lighting. Only HDR sky boxes have it.
+uniform bool irradianceEnabled;
+uniform samplerCube irradianceTexture;
+
+// Reflection probes. Sampler arrays can't be indexed dynamically, so probes are bound to
+// separate samplers.
+uniform int probeCount;
+uniform samplerCube probeTexture0;
+uniform samplerCube probeTexture1;
+uniform samplerCube probeTexture2;
+uniform samplerCube probeTexture3;
+uniform vec3 probeCapturePositions[4];
+uniform vec3 probeBoundsMin[4];
+uniform vec3 probeBoundsMax[4];
+// xyz - center, w - radius of a sphere probe, w is zero for box probes.
+uniform vec4 probeSpheres[4];
 
 out vec4 FragColor;
 in vec2 texCoord;
 
+const float MAX_REFLECTION_LOD = 6.0;
+
+vec3 SampleProbe(int index, vec3 direction, float lod)
+{
+    if (index == 0) {
+        return textureLod(probeTexture0, direction, lod).rgb;
+    } else if (index == 1) {
+        return textureLod(probeTexture1, direction, lod).rgb;
+    } else if (index == 2) {
+        return textureLod(probeTexture2, direction, lod).rgb;
+    } else {
+        return textureLod(probeTexture3, direction, lod).rgb;
+    }
+}
+
+bool IsInsideProbe(int index, vec3 position)
+{
+    vec4 sphere = probeSpheres[index];
+    if (sphere.w > 0.0) {
+        return distance(position, sphere.xyz) <= sphere.w;
+    }
+    return all(greaterThanEqual(position, probeBoundsMin[index])) &&
+        all(lessThanEqual(position, probeBoundsMax[index]));
+}
+
+// Finds a point where the reflection ray leaves the volume of the probe and returns the direction
+// from the capture position to this point, so reflections will match the geometry around the probe.
+vec3 ProjectReflection(int index, vec3 position, vec3 reflection)
+{
+    vec4 sphere = probeSpheres[index];
+    float t;
+    if (sphere.w > 0.0) {
+        vec3 oc = position - sphere.xyz;
+        float b = dot(oc, reflection);
+        float c = dot(oc, oc) - sphere.w * sphere.w;
+        t = -b + sqrt(max(b * b - c, 0.0));
+    } else {
+        vec3 t1 = (probeBoundsMax[index] - position) / reflection;
+        vec3 t2 = (probeBoundsMin[index] - position) / reflection;
+        vec3 tFar = max(t1, t2);
+        t = min(min(tFar.x, tFar.y), tFar.z);
+    }
+    return position + reflection * t - probeCapturePositions[index];
+}
+
 void main()
 {
     float ambientOcclusion = texture(aoSampler, texCoord).r;
     vec4 ambientPixel = texture(ambientTexture, texCoord);
-    FragColor = (ambientColor + ambientPixel) * S_SRGBToLinear(texture(diffuseTexture, texCoord));
+    vec4 albedo = S_SRGBToLinear(texture(diffuseTexture, texCoord));
+    FragColor = (ambientColor + ambientPixel) * albedo;
     FragColor.rgb *= ambientOcclusion;
     FragColor.a = ambientPixel.a;
 
-    // TODO: Implement IBL.
-}
\ No newline at end of file
+    // Specular reflections of the nearest enclosing probe or the sky box.
+    vec3 fragmentPosition = S_UnProject(vec3(texCoord, texture(depthTexture, texCoord).r), invViewProj);
+
+    int probe = -1;
+    float closestDistance = 3.402823466e+38;
+    for (int i = 0; i < probeCount; ++i) {
+        float probeDistance = distance(fragmentPosition, probeCapturePositions[i]);
+        if (probeDistance < closestDistance && IsInsideProbe(i, fragmentPosition)) {
+            closestDistance = probeDistance;
+            probe = i;
+        }
+    }
+
+    if (probe < 0 && !environmentEnabled) {
+        return;
+    }
+
+    vec3 material = texture(materialTexture, texCoord).rgb;
+    float metallic = material.x;
+    float roughness = material.y;
+    vec3 normal = normalize(texture(normalTexture, texCoord).xyz * 2.0 - 1.0);
+    vec3 viewVector = normalize(cameraPosition - fragmentPosition);
+    vec3 reflection = reflect(-viewVector, normal);
+    float lod = roughness * MAX_REFLECTION_LOD;
+
+    vec3 environment;
+    if (probe >= 0) {
+        environment = SampleProbe(probe, ProjectReflection(probe, fragmentPosition, reflection), lod);
+    } else {
+        vec4 sky = textureLod(environmentTexture, reflection, lod);
+        environment = environmentLinear ? sky.rgb : S_SRGBToLinear(sky).rgb;
+    }
+
+    // Fresnel-Schlick with roughness.
+    vec3 f0 = mix(vec3(0.04), albedo.rgb, metallic);
+    float cosTheta = max(dot(normal, viewVector), 0.0);
+    vec3 fresnel = f0 + (max(vec3(1.0 - roughness), f0) - f0) * pow(1.0 - cosTheta, 5.0);
+
+    FragColor.rgb += environment * fresnel * ambientOcclusion * material.z;
+
+    if (irradianceEnabled) {
+        vec3 irradiance = texture(irradianceTexture, normal).rgb;
+        vec3 diffuse = (1.0 - fresnel) * (1.0 - metallic) * albedo.rgb;
+        FragColor.rgb += irradiance * diffuse * ambientOcclusion * material.z;
+    }
+}
@@ -3,6 +3,9 @@ uniform sampler2D colorTexture;
 uniform sampler2D normalTexture;
 uniform sampler2D materialTexture;
 uniform samplerCube pointShadowTexture;
+uniform sampler2D cookieTexture;
+uniform samplerCube cookieCubeTexture;
+uniform sampler2D falloffTexture;
 
 uniform vec3 lightPos;
 uniform float lightRadius;
@@ -13,6 +16,11 @@ uniform bool softShadows;
 uniform bool shadowsEnabled;
 uniform float shadowBias;
 uniform float lightIntensity;
+// 0 - no cookie, 1 - latitude-longitude cookie texture, 2 - cube cookie texture.
+uniform int cookieKind;
+// Transforms directions from world space to local space of the light.
+uniform mat3 lightInvRotation;
+uniform bool falloffEnabled;
 
 in vec2 texCoord;
 out vec4 FragColor;
@@ -36,10 +44,25 @@ void main()
 
     vec3 lighting = S_PBR_CalculateLight(ctx);
 
-    float distanceAttenuation = S_LightDistanceAttenuation(distance, lightRadius);
+    float distanceAttenuation = falloffEnabled
+        ? S_LightFalloffCurve(falloffTexture, distance, lightRadius)
+        : S_LightDistanceAttenuation(distance, lightRadius);
 
     float shadow = S_PointShadow(
         shadowsEnabled, softShadows, distance, shadowBias, ctx.fragmentToLight, pointShadowTexture);
 
-    FragColor = vec4(lightIntensity * distanceAttenuation * shadow * lighting, 1.0);
+    vec4 cookieAttenuation = vec4(1.0);
+    if (cookieKind != 0) {
+        vec3 direction = lightInvRotation * -ctx.fragmentToLight;
+        if (cookieKind == 1) {
+            vec2 texCoords = vec2(
+                atan(direction.z, direction.x) / (2.0 * PI) + 0.5,
+                acos(clamp(direction.y, -1.0, 1.0)) / PI);
+            cookieAttenuation = texture(cookieTexture, texCoords);
+        } else {
+            cookieAttenuation = texture(cookieCubeTexture, direction);
+        }
+    }
+
+    FragColor = cookieAttenuation * vec4(lightIntensity * distanceAttenuation * shadow * lighting, 1.0);
 }
@@ -4,6 +4,7 @@ uniform sampler2D normalTexture;
 uniform sampler2D materialTexture;
 uniform sampler2D spotShadowTexture;
 uniform sampler2D cookieTexture;
+uniform sampler2D falloffTexture;
 
 uniform mat4 lightViewProjMatrix;
 uniform vec3 lightPos;
@@ -19,6 +20,7 @@ uniform bool softShadows;
 uniform float shadowMapInvSize;
 uniform float shadowBias;
 uniform bool cookieEnabled;
+uniform bool falloffEnabled;
 uniform float lightIntensity;
 
 in vec2 texCoord;
@@ -43,7 +45,9 @@ void main()
 
     vec3 lighting = S_PBR_CalculateLight(ctx);
 
-    float distanceAttenuation = S_LightDistanceAttenuation(distance, lightRadius);
+    float distanceAttenuation = falloffEnabled
+        ? S_LightFalloffCurve(falloffTexture, distance, lightRadius)
+        : S_LightDistanceAttenuation(distance, lightRadius);
 
     float spotAngleCos = dot(lightDirection, ctx.fragmentToLight);
     float coneFactor = smoothstep(halfConeAngleCos, halfHotspotConeAngleCos, spotAngleCos);
@@ -0,0 +1,46 @@
+uniform sampler2D depthTexture;
+uniform mat4 invViewProj;
+uniform vec3 cameraPosition;
+uniform vec4 fogColor;
+// 0 - Linear, 1 - Exponential, 2 - Exponential squared.
+uniform int fogKind;
+uniform float fogStart;
+uniform float fogEnd;
+uniform float fogDensity;
+uniform bool heightFogEnabled;
+uniform float heightFogBase;
+uniform float heightFogFalloff;
+
+in vec2 texCoord;
+out vec4 FragColor;
+
+void main()
+{
+    float depth = texture(depthTexture, texCoord).r;
+
+    // Sky is not affected by fog.
+    if (depth >= 1.0) {
+        discard;
+    }
+
+    vec3 fragmentPosition = S_UnProject(vec3(texCoord, depth), invViewProj);
+    float distance = length(fragmentPosition - cameraPosition);
+
+    float factor;
+    if (fogKind == 0) {
+        factor = (distance - fogStart) / max(fogEnd - fogStart, 0.0001);
+    } else if (fogKind == 1) {
+        factor = 1.0 - exp(-fogDensity * distance);
+    } else {
+        float scaledDistance = fogDensity * distance;
+        factor = 1.0 - exp(-scaledDistance * scaledDistance);
+    }
+
+    if (heightFogEnabled) {
+        // Fog becomes thinner exponentially above its base height.
+        float height = max(fragmentPosition.y - heightFogBase, 0.0);
+        factor *= exp(-heightFogFalloff * height);
+    }
+
+    FragColor = vec4(fogColor.rgb, clamp(factor, 0.0, 1.0) * fogColor.a);
+}
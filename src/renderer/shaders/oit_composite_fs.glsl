@@ -0,0 +1,24 @@
+uniform sampler2D accumTexture;
+uniform sampler2D weightTexture;
+
+out vec4 FragColor;
+
+void main()
+{
+    // Accumulation targets have the same size as the frame, so the fragment position could be used
+    // directly regardless of the viewport.
+    ivec2 position = ivec2(gl_FragCoord.xy);
+    vec4 accum = texelFetch(accumTexture, position, 0);
+    float revealage = accum.a;
+
+    // Nothing transparent was drawn at this pixel.
+    if (revealage >= 1.0) {
+        discard;
+    }
+
+    float weight = texelFetch(weightTexture, position, 0).r;
+    vec3 averageColor = accum.rgb / max(weight, 0.00001);
+
+    // Blended with the frame using 1 - revealage as opacity.
+    FragColor = vec4(averageColor, 1.0 - revealage);
+}
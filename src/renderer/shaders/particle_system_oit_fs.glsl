@@ -0,0 +1,30 @@
+uniform sampler2D diffuseTexture;
+uniform sampler2D depthBufferTexture;
+uniform vec2 invScreenSize;
+uniform vec2 projParams;
+uniform float softBoundarySharpnessFactor;
+
+layout(location = 0) out vec4 outAccum;
+layout(location = 1) out vec4 outWeight;
+in vec2 texCoord;
+in vec4 color;
+
+float toProjSpace(float z)
+{
+    float far = projParams.x;
+    float near = projParams.y;
+    return (far * near) / (far - z * (far - near));
+}
+
+void main()
+{
+    float sceneDepth = toProjSpace(texture(depthBufferTexture, gl_FragCoord.xy * invScreenSize).r);
+    float fragmentDepth = toProjSpace(gl_FragCoord.z);
+    float depthOpacity = smoothstep((sceneDepth - fragmentDepth) * softBoundarySharpnessFactor, 0.0, 1.0);
+    vec4 fragColor = color * S_SRGBToLinear(texture(diffuseTexture, texCoord)).r;
+    fragColor.a *= depthOpacity;
+
+    float weight = S_OITWeight(gl_FragCoord.z, fragColor.a);
+    outAccum = vec4(fragColor.rgb * weight, fragColor.a);
+    outWeight = vec4(weight);
+}
@@ -1,4 +1,6 @@
 uniform samplerCube cubemapTexture;
+// HDR sky boxes are already in linear color space.
+uniform bool linearColorSpace;
 
 out vec4 FragColor;
 
@@ -6,5 +8,6 @@ in vec3 texCoord;
 
 void main()
 {
-    FragColor = S_SRGBToLinear(texture(cubemapTexture, texCoord));
+    vec4 color = texture(cubemapTexture, texCoord);
+    FragColor = linearColorSpace ? color : S_SRGBToLinear(color);
 }
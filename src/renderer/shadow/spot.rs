@@ -12,6 +12,7 @@ use crate::{
         framework::{
             error::FrameworkError,
             framebuffer::{Attachment, AttachmentKind, CullFace, DrawParameters, FrameBuffer},
+            gpu_program::GpuProgramBinding,
             gpu_texture::{
                 Coordinate, GpuTexture, GpuTextureKind, MagnificationFilter, MinificationFilter,
                 PixelKind, WrapMode,
@@ -172,45 +173,79 @@ impl SpotShadowMapRenderer {
                 .get(state, material.shader())
                 .and_then(|shader_set| shader_set.render_passes.get(&SPOT_SHADOW_PASS_NAME))
             {
-                for instance in batch.instances.iter() {
-                    statistics += framebuffer.draw(
-                        geometry,
-                        state,
-                        viewport,
-                        &render_pass.program,
-                        &DrawParameters {
-                            cull_face: Some(CullFace::Back),
-                            color_write: ColorMask::all(false),
-                            depth_write: true,
-                            stencil_test: None,
-                            depth_test: true,
-                            blend: None,
-                            stencil_op: Default::default(),
-                        },
-                        instance.element_range,
-                        |mut program_binding| {
-                            apply_material(MaterialContext {
-                                material: &material,
-                                program_binding: &mut program_binding,
-                                texture_cache,
-                                matrix_storage,
-                                world_matrix: &instance.world_transform,
-                                wvp_matrix: &(light_view_projection * instance.world_transform),
-                                bone_matrices: &instance.bone_matrices,
-                                use_skeletal_animation: batch.is_skinned,
-                                camera_position: &Default::default(),
-                                use_pom: false,
-                                light_position: &Default::default(),
-                                blend_shapes_storage: blend_shapes_storage.as_ref(),
-                                blend_shapes_weights: &instance.blend_shapes_weights,
-                                normal_dummy: normal_dummy.clone(),
-                                white_dummy: white_dummy.clone(),
-                                black_dummy: black_dummy.clone(),
-                                volume_dummy: volume_dummy.clone(),
-                                persistent_identifier: instance.persistent_identifier,
-                            });
-                        },
-                    )?;
+                let draw_params = DrawParameters {
+                    cull_face: Some(CullFace::Back),
+                    color_write: ColorMask::all(false),
+                    depth_write: true,
+                    stencil_test: None,
+                    depth_test: true,
+                    blend: None,
+                    stencil_op: Default::default(),
+                };
+
+                let instance_matrices = if batch.is_instancing_possible()
+                    && render_pass.program.supports_instancing()
+                {
+                    batch.instance_matrices()
+                } else {
+                    Default::default()
+                };
+
+                // Instanced batches are drawn at once using the data of the first instance.
+                let instances = if instance_matrices.is_empty() {
+                    &batch.instances[..]
+                } else {
+                    &batch.instances[..1]
+                };
+
+                for instance in instances.iter() {
+                    let apply_uniforms = |mut program_binding: GpuProgramBinding| {
+                        apply_material(MaterialContext {
+                            material: &material,
+                            program_binding: &mut program_binding,
+                            texture_cache,
+                            matrix_storage,
+                            world_matrix: &instance.world_transform,
+                            wvp_matrix: &(light_view_projection * instance.world_transform),
+                            bone_matrices: &instance.bone_matrices,
+                            use_skeletal_animation: batch.is_skinned,
+                            camera_position: &Default::default(),
+                            use_pom: false,
+                            light_position: &Default::default(),
+                            blend_shapes_storage: blend_shapes_storage.as_ref(),
+                            blend_shapes_weights: &instance.blend_shapes_weights,
+                            view_projection_matrix: &light_view_projection,
+                            instance_matrices: &instance_matrices,
+                            normal_dummy: normal_dummy.clone(),
+                            white_dummy: white_dummy.clone(),
+                            black_dummy: black_dummy.clone(),
+                            volume_dummy: volume_dummy.clone(),
+                            persistent_identifier: instance.persistent_identifier,
+                        });
+                    };
+
+                    if instance_matrices.is_empty() {
+                        statistics += framebuffer.draw(
+                            geometry,
+                            state,
+                            viewport,
+                            &render_pass.program,
+                            &draw_params,
+                            instance.element_range,
+                            apply_uniforms,
+                        )?;
+                    } else {
+                        statistics += framebuffer.draw_instances(
+                            instance_matrices.len(),
+                            geometry,
+                            state,
+                            viewport,
+                            &render_pass.program,
+                            &draw_params,
+                            apply_uniforms,
+                        );
+                        statistics.instanced_draw_calls += 1;
+                    }
                 }
             }
         }
@@ -9,6 +9,7 @@ pub struct SkyboxShader {
     pub program: GpuProgram,
     pub wvp_matrix: UniformLocation,
     pub cubemap_texture: UniformLocation,
+    pub linear_color_space: UniformLocation,
 }
 
 impl SkyboxShader {
@@ -23,6 +24,8 @@ impl SkyboxShader {
                 .uniform_location(state, &ImmutableString::new("worldViewProjection"))?,
             cubemap_texture: program
                 .uniform_location(state, &ImmutableString::new("cubemapTexture"))?,
+            linear_color_space: program
+                .uniform_location(state, &ImmutableString::new("linearColorSpace"))?,
             program,
         })
     }
@@ -102,6 +102,7 @@ impl MatrixStorage {
 pub struct MatrixStorageCache {
     empty: MatrixStorage,
     active_set: FxHashMap<PersistentIdentifier, MatrixStorage>,
+    instance_set: Vec<MatrixStorage>,
     cache: Vec<MatrixStorage>,
 }
 
@@ -111,6 +112,7 @@ impl MatrixStorageCache {
         Ok(Self {
             empty: MatrixStorage::new(state)?,
             active_set: Default::default(),
+            instance_set: Default::default(),
             cache: Default::default(),
         })
     }
@@ -120,6 +122,7 @@ impl MatrixStorageCache {
         for (_, storage) in self.active_set.drain() {
             self.cache.push(storage);
         }
+        self.cache.append(&mut self.instance_set);
     }
 
     /// Tries to upload the given set of matrices to a GPU matrix storage associated with some persistent
@@ -160,4 +163,32 @@ impl MatrixStorageCache {
             }
         }
     }
+
+    /// Uploads the given set of instance world matrices to a vacant GPU matrix storage. Unlike
+    /// [`Self::try_bind_and_upload`], every call gets its own storage, because the same set of
+    /// instances could be rendered multiple times per frame with different culling results (for
+    /// example for each shadow cascade).
+    pub fn try_bind_and_upload_instances(
+        &mut self,
+        state: &mut PipelineState,
+        matrices: &[Matrix4<f32>],
+        sampler: u32,
+    ) -> Result<&MatrixStorage, FrameworkError> {
+        if matrices.is_empty() {
+            self.empty.texture().borrow().bind(state, sampler);
+            Ok(&self.empty)
+        } else {
+            let mut storage = if let Some(cached) = self.cache.pop() {
+                cached
+            } else {
+                MatrixStorage::new(state)?
+            };
+
+            storage.upload(state, matrices, sampler)?;
+
+            self.instance_set.push(storage);
+
+            Ok(self.instance_set.last().unwrap())
+        }
+    }
 }
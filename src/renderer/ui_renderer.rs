@@ -91,6 +91,42 @@ pub struct UiRenderer {
     clipping_geometry_buffer: GeometryBuffer,
 }
 
+/// Calculates scissor box (in frame buffer pixels) for the given clip bounds (in UI units). The UI is
+/// stretched over the viewport, so the clip bounds are scaled if the frame size differs from the
+/// viewport size. The result is clamped to the viewport, so nothing is drawn outside of it even if
+/// the clip bounds are larger than the viewport.
+fn calculate_scissor_box(
+    viewport: Rect<i32>,
+    frame_size: Vector2<f32>,
+    clip_bounds: Rect<f32>,
+) -> Rect<i32> {
+    let scale_x = if frame_size.x > 0.0 {
+        viewport.size.x as f32 / frame_size.x
+    } else {
+        1.0
+    };
+    let scale_y = if frame_size.y > 0.0 {
+        viewport.size.y as f32 / frame_size.y
+    } else {
+        1.0
+    };
+
+    let left = ((clip_bounds.position.x * scale_x).floor() as i32).clamp(0, viewport.size.x);
+    let top = ((clip_bounds.position.y * scale_y).floor() as i32).clamp(0, viewport.size.y);
+    let right = (((clip_bounds.position.x + clip_bounds.size.x) * scale_x).ceil() as i32)
+        .clamp(left, viewport.size.x);
+    let bottom = (((clip_bounds.position.y + clip_bounds.size.y) * scale_y).ceil() as i32)
+        .clamp(top, viewport.size.y);
+
+    Rect::new(
+        viewport.position.x + left,
+        // Because OpenGL is was designed for mathematicians, it has origin at lower left corner.
+        viewport.position.y + viewport.size.y - bottom,
+        right - left,
+        bottom - top,
+    )
+}
+
 /// A set of parameters to render a specified user interface drawing context.
 pub struct UiRenderContext<'a, 'b, 'c> {
     /// Render pipeline state.
@@ -198,18 +234,12 @@ impl UiRenderer {
             let mut diffuse_texture = white_dummy.clone();
             let mut is_font_texture = false;
 
-            let mut clip_bounds = cmd.clip_bounds;
-            clip_bounds.position.x = clip_bounds.position.x.floor();
-            clip_bounds.position.y = clip_bounds.position.y.floor();
-            clip_bounds.size.x = clip_bounds.size.x.ceil();
-            clip_bounds.size.y = clip_bounds.size.y.ceil();
-
+            let scissor_box = calculate_scissor_box(viewport, resolution, cmd.clip_bounds);
             state.set_scissor_box(
-                clip_bounds.position.x as i32,
-                // Because OpenGL is was designed for mathematicians, it has origin at lower left corner.
-                viewport.size.y - (clip_bounds.position.y + clip_bounds.size.y) as i32,
-                clip_bounds.size.x as i32,
-                clip_bounds.size.y as i32,
+                scissor_box.position.x,
+                scissor_box.position.y,
+                scissor_box.size.x,
+                scissor_box.size.y,
             );
 
             let mut stencil_test = None;
@@ -408,3 +438,46 @@ impl UiRenderer {
         Ok(statistics)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::{algebra::Vector2, math::Rect},
+        renderer::ui_renderer::calculate_scissor_box,
+    };
+
+    #[test]
+    fn test_scissor_box_in_small_render_target() {
+        // A 64x64 render target, while the window is much larger.
+        let viewport = Rect::new(0, 0, 64, 64);
+        let frame_size = Vector2::new(64.0, 64.0);
+
+        // Scroll viewer's content area inside the target.
+        assert_eq!(
+            calculate_scissor_box(viewport, frame_size, Rect::new(8.0, 4.0, 40.0, 20.0)),
+            Rect::new(8, 40, 40, 20)
+        );
+
+        // Content of the scroll viewer is much larger than the target, it must not leak out.
+        assert_eq!(
+            calculate_scissor_box(viewport, frame_size, Rect::new(-20.0, 40.0, 500.0, 300.0)),
+            Rect::new(0, 0, 64, 24)
+        );
+
+        // Completely outside of the target.
+        let outside =
+            calculate_scissor_box(viewport, frame_size, Rect::new(100.0, 0.0, 10.0, 10.0));
+        assert_eq!(outside.size.x, 0);
+    }
+
+    #[test]
+    fn test_scissor_box_with_offset_and_scaled_viewport() {
+        let viewport = Rect::new(100, 50, 128, 64);
+        let frame_size = Vector2::new(64.0, 32.0);
+
+        assert_eq!(
+            calculate_scissor_box(viewport, frame_size, Rect::new(8.0, 8.0, 16.0, 8.0)),
+            Rect::new(116, 82, 32, 16)
+        );
+    }
+}
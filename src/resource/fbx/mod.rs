@@ -14,8 +14,8 @@ use crate::{
     animation::{track::Track, Animation, AnimationContainer},
     asset::manager::ResourceManager,
     core::{
-        algebra::{Matrix4, Point3, UnitQuaternion, Vector2, Vector3, Vector4},
-        curve::{CurveKey, CurveKeyKind},
+        algebra::{Matrix3, Matrix4, Point3, UnitQuaternion, Vector2, Vector3, Vector4},
+        curve::{Curve, CurveKey, CurveKeyKind},
         instant::Instant,
         io,
         log::{Log, MessageKind},
@@ -53,7 +53,7 @@ use crate::{
             Mesh, MeshBuilder,
         },
         node::Node,
-        pivot::PivotBuilder,
+        pivot::{Pivot, PivotBuilder},
         transform::TransformBuilder,
         Scene,
     },
@@ -80,6 +80,143 @@ fn quat_from_euler(euler: Vector3<f32>) -> UnitQuaternion<f32> {
     )
 }
 
+/// Converts data from the coordinate system of a model to the coordinate system of the engine using
+/// import options of the model (see [`ModelImportOptions::axis_conversion`]). Every transform `T` of
+/// the model becomes `C * T * C^-1`, where `C` is the conversion matrix, so the whole hierarchy is
+/// converted, not just its root.
+struct AxisConversion {
+    basis: Matrix3<f32>,
+    rotation: UnitQuaternion<f32>,
+    scale: f32,
+}
+
+impl AxisConversion {
+    fn new(model_import_options: &ModelImportOptions) -> Self {
+        let basis = model_import_options.axis_conversion();
+        let scale = if model_import_options.scale > 0.0 {
+            model_import_options.scale
+        } else {
+            Log::warn(format!(
+                "Scale of a model must be positive, got {}! Scale is ignored.",
+                model_import_options.scale
+            ));
+            1.0
+        };
+        Self {
+            basis,
+            rotation: UnitQuaternion::from_matrix(&basis),
+            scale,
+        }
+    }
+
+    fn is_identity(&self) -> bool {
+        self.basis == Matrix3::identity() && self.scale == 1.0
+    }
+
+    fn point(&self, point: Vector3<f32>) -> Vector3<f32> {
+        (self.basis * point).scale(self.scale)
+    }
+
+    fn direction(&self, direction: Vector3<f32>) -> Vector3<f32> {
+        self.basis * direction
+    }
+
+    fn rotation(&self, rotation: UnitQuaternion<f32>) -> UnitQuaternion<f32> {
+        self.rotation * rotation * self.rotation.inverse()
+    }
+
+    // Scale is applied along local axes, so it is just permuted.
+    fn scaling(&self, scale: Vector3<f32>) -> Vector3<f32> {
+        self.basis.abs() * scale
+    }
+
+    fn matrix(&self, matrix: &Matrix4<f32>) -> Matrix4<f32> {
+        let conversion = self.basis.scale(self.scale).to_homogeneous();
+        let inv_conversion = self
+            .basis
+            .transpose()
+            .scale(1.0 / self.scale)
+            .to_homogeneous();
+        conversion * matrix * inv_conversion
+    }
+
+    // Converts X, Y, Z curves of a position (`is_position = true`) or a scale track.
+    fn convert_vector_curves(&self, curves: &mut [Curve], is_position: bool) {
+        let keys = curves
+            .iter()
+            .map(|curve| curve.keys().to_vec())
+            .collect::<Vec<_>>();
+        for curve in curves.iter_mut() {
+            curve.clear();
+        }
+        for (axis, keys) in keys.into_iter().enumerate() {
+            let column = self.basis.column(axis);
+            let target_axis = column.iamax();
+            let factor = if is_position {
+                column[target_axis] * self.scale
+            } else {
+                1.0
+            };
+            for key in keys {
+                curves[target_axis].add_key(CurveKey::new(
+                    key.location(),
+                    key.value * factor,
+                    key.kind,
+                ));
+            }
+        }
+    }
+
+    // Converts Euler angles (XYZ order) curves of a rotation track. The order of rotations changes after
+    // conversion, so the curves are resampled at every key.
+    fn convert_rotation_curves(&self, curves: &mut [Curve]) {
+        let mut locations = curves
+            .iter()
+            .flat_map(|curve| curve.keys().iter().map(|key| key.location()))
+            .collect::<Vec<_>>();
+        locations.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+        locations.dedup();
+
+        let angles = locations
+            .iter()
+            .map(|&location| {
+                let euler = Vector3::new(
+                    curves[0].value_at(location),
+                    curves[1].value_at(location),
+                    curves[2].value_at(location),
+                );
+                let (x, y, z) = self
+                    .rotation(math::quat_from_euler(euler, RotationOrder::XYZ))
+                    .euler_angles();
+                Vector3::new(x, y, z)
+            })
+            .collect::<Vec<_>>();
+
+        for curve in curves.iter_mut() {
+            curve.clear();
+        }
+
+        let mut previous: Option<Vector3<f32>> = None;
+        for (location, mut euler) in locations.into_iter().zip(angles) {
+            // Keep angles continuous, otherwise interpolation between keys will take the long way.
+            if let Some(previous) = previous {
+                for i in 0..3 {
+                    while euler[i] - previous[i] > std::f32::consts::PI {
+                        euler[i] -= std::f32::consts::TAU;
+                    }
+                    while euler[i] - previous[i] < -std::f32::consts::PI {
+                        euler[i] += std::f32::consts::TAU;
+                    }
+                }
+            }
+            for (curve, value) in curves.iter_mut().zip(euler.iter()) {
+                curve.add_key(CurveKey::new(location, *value, CurveKeyKind::Linear));
+            }
+            previous = Some(euler);
+        }
+    }
+}
+
 /// Fixes index that is used as indicator of end of a polygon
 /// FBX stores array of indices like so 0,1,-3,... where -3
 /// is actually index 2 but it xor'ed using -1.
@@ -194,6 +331,8 @@ fn convert_vertex(
     index: usize,
     index_in_polygon: usize,
     skin_data: &[VertexWeightSet],
+    conversion: &AxisConversion,
+    weld_distance: f32,
 ) -> Result<UnpackedVertex, FbxError> {
     let position = *geom.vertices.get(index).ok_or(FbxError::IndexOutOfBounds)?;
 
@@ -217,12 +356,20 @@ fn convert_vertex(
         None => 0,
     };
 
-    Ok(UnpackedVertex {
-        position: geometric_transform
+    let mut position = conversion.point(
+        geometric_transform
             .transform_point(&Point3::from(position))
             .coords,
-        normal: geometric_transform.transform_vector(&normal),
-        tangent: geometric_transform.transform_vector(&tangent),
+    );
+    if weld_distance > 0.0 {
+        // Snap the position, so close vertices become equal and welded by the mesh builder.
+        position = position.map(|c| (c / weld_distance).round() * weld_distance);
+    }
+
+    Ok(UnpackedVertex {
+        position,
+        normal: conversion.direction(geometric_transform.transform_vector(&normal)),
+        tangent: conversion.direction(geometric_transform.transform_vector(&tangent)),
         uv: Vector2::new(uv.x, 1.0 - uv.y), // Invert Y because OpenGL has origin at left *bottom* corner.
         surface_index: material as usize,
         weights: if geom.deformers.is_empty() {
@@ -428,6 +575,7 @@ async fn convert_mesh(
     graph: &mut Graph,
     model_path: &Path,
     model_import_options: &ModelImportOptions,
+    conversion: &AxisConversion,
 ) -> Result<Handle<Node>, FbxError> {
     let geometric_transform = Matrix4::new_translation(&model.geometric_translation)
         * quat_from_euler(model.geometric_rotation).to_homogeneous()
@@ -440,9 +588,15 @@ async fn convert_mesh(
     // triangulated polygon.
     let mut face_triangles = Vec::new();
 
-    let mut mesh_surfaces = Vec::new();
     let mut mesh_blend_shapes = Vec::new();
 
+    let merge_surfaces =
+        model_import_options.merge_surfaces_by_material && can_merge_geoms(fbx_scene, model)?;
+
+    // Every item is a set of surfaces per material and a flag, that tells whether tangents must be
+    // calculated or not. Merged geoms share the same set.
+    let mut data_sets: Vec<(Vec<FbxSurfaceData>, bool)> = Vec::new();
+
     for &geom_handle in &model.geoms {
         let geom = fbx_scene.get(geom_handle).as_mesh_geometry()?;
         let skin_data = geom.get_skin_data(fbx_scene)?;
@@ -459,29 +613,38 @@ async fn convert_mesh(
             })
             .collect();
 
-        let mut data_set = vec![
-            FbxSurfaceData {
-                base_mesh_builder: if geom.deformers.is_empty() {
-                    FbxMeshBuilder::Static(RawMeshBuilder::new(1024, 1024))
-                } else {
-                    FbxMeshBuilder::Animated(RawMeshBuilder::new(1024, 1024))
-                },
-                blend_shapes: blend_shapes
-                    .iter()
-                    .map(|bs_channel| {
-                        InputBlendShapeData {
-                            name: bs_channel.name.clone(),
-                            default_weight: bs_channel.deform_percent,
-                            positions: Default::default(),
-                            normals: Default::default(),
-                            tangents: Default::default(),
-                        }
-                    })
-                    .collect(),
-                skin_data: Default::default(),
-            };
-            model.materials.len().max(1)
-        ];
+        let (mut data_set, mut calculate_tangents) = match data_sets.pop() {
+            Some(last) if merge_surfaces => last,
+            last => {
+                data_sets.extend(last);
+                (
+                    vec![
+                        FbxSurfaceData {
+                            base_mesh_builder: if geom.deformers.is_empty() {
+                                FbxMeshBuilder::Static(RawMeshBuilder::new(1024, 1024))
+                            } else {
+                                FbxMeshBuilder::Animated(RawMeshBuilder::new(1024, 1024))
+                            },
+                            blend_shapes: blend_shapes
+                                .iter()
+                                .map(|bs_channel| {
+                                    InputBlendShapeData {
+                                        name: bs_channel.name.clone(),
+                                        default_weight: bs_channel.deform_percent,
+                                        positions: Default::default(),
+                                        normals: Default::default(),
+                                        tangents: Default::default(),
+                                    }
+                                })
+                                .collect(),
+                            skin_data: Default::default(),
+                        };
+                        model.materials.len().max(1)
+                    ],
+                    false,
+                )
+            }
+        };
 
         let mut material_index = 0;
         let mut n = 0;
@@ -504,6 +667,8 @@ async fn convert_mesh(
                         index,
                         polygon_vertex_index,
                         &skin_data,
+                        conversion,
+                        model_import_options.weld_distance,
                     )?;
                     let data = data_set.get_mut(vertex.surface_index).unwrap();
                     let weights = vertex.weights;
@@ -540,20 +705,24 @@ async fn convert_mesh(
                         {
                             blend_shape.positions.insert(
                                 final_index as u32,
-                                utils::vec3_f16_from_f32(
+                                utils::vec3_f16_from_f32(conversion.point(
                                     blend_shape_geometry.vertices[*relative_index as usize],
-                                ),
+                                )),
                             );
                             if let Some(normals) = blend_shape_geometry.normals.as_ref() {
                                 blend_shape.normals.insert(
                                     final_index as u32,
-                                    utils::vec3_f16_from_f32(normals[*relative_index as usize]),
+                                    utils::vec3_f16_from_f32(
+                                        conversion.direction(normals[*relative_index as usize]),
+                                    ),
                                 );
                             }
                             if let Some(tangents) = blend_shape_geometry.tangents.as_ref() {
-                                blend_shape.normals.insert(
+                                blend_shape.tangents.insert(
                                     final_index as u32,
-                                    utils::vec3_f16_from_f32(tangents[*relative_index as usize]),
+                                    utils::vec3_f16_from_f32(
+                                        conversion.direction(tangents[*relative_index as usize]),
+                                    ),
                                 );
                             }
                         }
@@ -567,6 +736,12 @@ async fn convert_mesh(
             }
         }
 
+        calculate_tangents |= geom.tangents.is_none();
+        data_sets.push((data_set, calculate_tangents));
+    }
+
+    let mut mesh_surfaces = Vec::new();
+    for (data_set, calculate_tangents) in data_sets {
         let mut surfaces = create_surfaces(
             fbx_scene,
             data_set,
@@ -577,13 +752,17 @@ async fn convert_mesh(
         )
         .await?;
 
-        if geom.tangents.is_none() {
+        if calculate_tangents {
             for surface in surfaces.iter_mut() {
                 surface.data().lock().calculate_tangents().unwrap();
             }
         }
 
         for surface in surfaces {
+            // Merged geoms could leave some materials without triangles.
+            if merge_surfaces && surface.data().lock().vertex_buffer.vertex_count() == 0 {
+                continue;
+            }
             mesh_surfaces.push(surface);
         }
     }
@@ -594,7 +773,29 @@ async fn convert_mesh(
         .build(graph))
 }
 
-fn convert_model_to_base(model: &FbxModel) -> BaseBuilder {
+/// Geoms of a model could be merged into the same set of surfaces only if they have the same vertex
+/// format and the same blend shapes.
+fn can_merge_geoms(fbx_scene: &FbxScene, model: &FbxModel) -> Result<bool, FbxError> {
+    let mut first_layout = None;
+    for &geom_handle in &model.geoms {
+        let geom = fbx_scene.get(geom_handle).as_mesh_geometry()?;
+        let layout = (
+            geom.deformers.is_empty(),
+            geom.collect_blend_shapes_refs(fbx_scene)?
+                .iter()
+                .map(|bs| bs.name.clone())
+                .collect::<Vec<_>>(),
+        );
+        match first_layout {
+            None => first_layout = Some(layout),
+            Some(ref first_layout) if *first_layout != layout => return Ok(false),
+            _ => (),
+        }
+    }
+    Ok(model.geoms.len() > 1)
+}
+
+fn convert_model_to_base(model: &FbxModel, conversion: &AxisConversion) -> BaseBuilder {
     // Dirty hack: use name of the model to generate instance id. This is necessary evil, because FBX does
     // not provide any stable unique id per model. We need stable unique id, to not break parent-child
     // relations between prefabs. Using the name for this purpose is unideal, because one could easily change
@@ -607,20 +808,20 @@ fn convert_model_to_base(model: &FbxModel) -> BaseBuilder {
     let instance_id = InstanceId(Uuid::from_u64_pair(hash, hash));
 
     BaseBuilder::new()
-        .with_inv_bind_pose_transform(model.inv_bind_transform)
+        .with_inv_bind_pose_transform(conversion.matrix(&model.inv_bind_transform))
         .with_name(model.name.as_str())
         .with_instance_id(instance_id)
         .with_local_transform(
             TransformBuilder::new()
-                .with_local_rotation(quat_from_euler(model.rotation))
-                .with_local_scale(model.scale)
-                .with_local_position(model.translation)
-                .with_post_rotation(quat_from_euler(model.post_rotation))
-                .with_pre_rotation(quat_from_euler(model.pre_rotation))
-                .with_rotation_offset(model.rotation_offset)
-                .with_rotation_pivot(model.rotation_pivot)
-                .with_scaling_offset(model.scaling_offset)
-                .with_scaling_pivot(model.scaling_pivot)
+                .with_local_rotation(conversion.rotation(quat_from_euler(model.rotation)))
+                .with_local_scale(conversion.scaling(model.scale))
+                .with_local_position(conversion.point(model.translation))
+                .with_post_rotation(conversion.rotation(quat_from_euler(model.post_rotation)))
+                .with_pre_rotation(conversion.rotation(quat_from_euler(model.pre_rotation)))
+                .with_rotation_offset(conversion.point(model.rotation_offset))
+                .with_rotation_pivot(conversion.point(model.rotation_pivot))
+                .with_scaling_offset(conversion.point(model.scaling_offset))
+                .with_scaling_pivot(conversion.point(model.scaling_pivot))
                 .build(),
         )
 }
@@ -633,8 +834,9 @@ async fn convert_model(
     animation: &mut Animation,
     model_path: &Path,
     model_import_options: &ModelImportOptions,
+    conversion: &AxisConversion,
 ) -> Result<Handle<Node>, FbxError> {
-    let base = convert_model_to_base(model);
+    let base = convert_model_to_base(model, conversion);
 
     // Create node with correct kind.
     let node_handle = if !model.geoms.is_empty() {
@@ -646,6 +848,7 @@ async fn convert_model(
             graph,
             model_path,
             model_import_options,
+            conversion,
         )
         .await?
     } else if model.light.is_some() {
@@ -683,13 +886,25 @@ async fn convert_model(
             let curves = track.data_container_mut().curves_mut();
 
             if !fbx_track.curves.contains_key("d|X") {
-                curves[0].add_key(CurveKey::new(0.0, default.x, CurveKeyKind::Constant));
+                curves[0].add_key(CurveKey::new(
+                    0.0,
+                    transform_value(default.x),
+                    CurveKeyKind::Constant,
+                ));
             }
             if !fbx_track.curves.contains_key("d|Y") {
-                curves[1].add_key(CurveKey::new(0.0, default.y, CurveKeyKind::Constant));
+                curves[1].add_key(CurveKey::new(
+                    0.0,
+                    transform_value(default.y),
+                    CurveKeyKind::Constant,
+                ));
             }
             if !fbx_track.curves.contains_key("d|Z") {
-                curves[2].add_key(CurveKey::new(0.0, default.z, CurveKeyKind::Constant));
+                curves[2].add_key(CurveKey::new(
+                    0.0,
+                    transform_value(default.z),
+                    CurveKeyKind::Constant,
+                ));
             }
 
             for (id, curve_handle) in fbx_track.curves.iter() {
@@ -705,7 +920,7 @@ async fn convert_model(
                         if fbx_curve.keys.is_empty() {
                             curves[index].add_key(CurveKey::new(
                                 0.0,
-                                default[index],
+                                transform_value(default[index]),
                                 CurveKeyKind::Constant,
                             ));
                         } else {
@@ -755,7 +970,10 @@ async fn convert_model(
                 |v| v.to_radians(),
             );
         } else {
-            add_vec3_key(&mut rotation_track, model.rotation);
+            add_vec3_key(
+                &mut rotation_track,
+                model.rotation.map(|angle| angle.to_radians()),
+            );
         }
 
         let mut scale_track = Track::new_scale();
@@ -766,11 +984,65 @@ async fn convert_model(
             add_vec3_key(&mut scale_track, model.scale);
         }
 
+        if !conversion.is_identity() {
+            conversion
+                .convert_vector_curves(translation_track.data_container_mut().curves_mut(), true);
+            conversion.convert_rotation_curves(rotation_track.data_container_mut().curves_mut());
+            conversion.convert_vector_curves(scale_track.data_container_mut().curves_mut(), false);
+        }
+
         animation.add_track(translation_track);
         animation.add_track(rotation_track);
         animation.add_track(scale_track);
     }
 
+    // Convert animations of blend shape weights. Mesh takes blend shapes from its last geometry,
+    // so their indices match.
+    if let Some(&geom_handle) = model.geoms.last() {
+        let geom = fbx_scene.get(geom_handle).as_mesh_geometry()?;
+        for (index, channel) in geom
+            .collect_blend_shapes_refs(fbx_scene)?
+            .iter()
+            .enumerate()
+        {
+            if channel.animation_curve_node.is_none() {
+                continue;
+            }
+
+            if let FbxComponent::AnimationCurveNode(curve_node) =
+                fbx_scene.get(channel.animation_curve_node)
+            {
+                if let Some(FbxComponent::AnimationCurve(fbx_curve)) = curve_node
+                    .curves
+                    .get("d|DeformPercent")
+                    .map(|curve_handle| fbx_scene.get(*curve_handle))
+                {
+                    let mut track = Track::new_blend_shape_weight(index);
+                    track.set_target(node_handle);
+
+                    let curve = &mut track.data_container_mut().curves_mut()[0];
+                    if fbx_curve.keys.is_empty() {
+                        curve.add_key(CurveKey::new(
+                            0.0,
+                            channel.deform_percent,
+                            CurveKeyKind::Constant,
+                        ));
+                    } else {
+                        for pair in fbx_curve.keys.iter() {
+                            curve.add_key(CurveKey::new(
+                                pair.time,
+                                pair.value,
+                                CurveKeyKind::Linear,
+                            ));
+                        }
+                    }
+
+                    animation.add_track(track);
+                }
+            }
+        }
+    }
+
     animation.fit_length_to_content();
 
     Ok(node_handle)
@@ -787,6 +1059,7 @@ async fn convert(
     model_import_options: &ModelImportOptions,
 ) -> Result<(), FbxError> {
     let root = scene.graph.get_root();
+    let conversion = AxisConversion::new(model_import_options);
 
     let mut animation = Animation::default();
     animation.set_name("Animation");
@@ -802,6 +1075,7 @@ async fn convert(
                 &mut animation,
                 model_path,
                 model_import_options,
+                &conversion,
             )
             .await?;
             scene.graph.link_nodes(node, root);
@@ -809,15 +1083,6 @@ async fn convert(
         }
     }
 
-    // Do not create animation player if there's no animation content.
-    if !animation.tracks().is_empty() {
-        let mut animations_container = AnimationContainer::new();
-        animations_container.add(animation);
-        AnimationPlayerBuilder::new(BaseBuilder::new().with_name("AnimationPlayer"))
-            .with_animations(animations_container)
-            .build(&mut scene.graph);
-    }
-
     // Link according to hierarchy
     for (&fbx_model_handle, node_handle) in fbx_model_to_node_map.iter() {
         if let FbxComponent::Model(fbx_model) = fbx_scene.get(fbx_model_handle) {
@@ -880,9 +1145,71 @@ async fn convert(
         }
     }
 
+    if model_import_options.strip_unused_bones {
+        strip_unused_bones(&mut scene.graph, fbx_model_to_node_map.values().copied());
+        animation.retain_tracks(|track| scene.graph.is_valid_handle(track.target()));
+    }
+
+    // Do not create animation player if there's no animation content.
+    if !animation.tracks().is_empty() {
+        let mut animations_container = AnimationContainer::new();
+        animations_container.add(animation);
+        AnimationPlayerBuilder::new(BaseBuilder::new().with_name("AnimationPlayer"))
+            .with_animations(animations_container)
+            .build(&mut scene.graph);
+    }
+
     Ok(())
 }
 
+/// Removes leaf pivots, that are descendants of bones and are not used by any mesh, for example bone
+/// tips that are added by modelling software.
+fn strip_unused_bones(graph: &mut Graph, nodes: impl Iterator<Item = Handle<Node>> + Clone) {
+    let mut used_bones = FxHashSet::default();
+    for handle in nodes.clone() {
+        if let Some(mesh) = graph[handle].cast::<Mesh>() {
+            for surface in mesh.surfaces() {
+                used_bones.extend(surface.bones.iter().copied());
+            }
+        }
+    }
+
+    let has_used_ancestor = |graph: &Graph, handle: Handle<Node>| {
+        let mut parent = graph[handle].parent();
+        while parent.is_some() {
+            if used_bones.contains(&parent) {
+                return true;
+            }
+            parent = graph[parent].parent();
+        }
+        false
+    };
+
+    // Removal of a leaf could make its parent a leaf too, so repeat until there's nothing to remove.
+    loop {
+        let unused = nodes
+            .clone()
+            .filter(|&handle| {
+                graph.is_valid_handle(handle) && {
+                    let node = &graph[handle];
+                    node.cast::<Pivot>().is_some()
+                        && node.children().is_empty()
+                        && !used_bones.contains(&handle)
+                        && has_used_ancestor(graph, handle)
+                }
+            })
+            .collect::<Vec<_>>();
+
+        if unused.is_empty() {
+            break;
+        }
+
+        for handle in unused {
+            graph.remove_node(handle);
+        }
+    }
+}
+
 /// Tries to load and convert FBX from given path.
 ///
 /// Normally you should never use this method, use resource manager to load models.
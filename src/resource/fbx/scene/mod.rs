@@ -202,11 +202,11 @@ fn link_child_with_parent_component(
                 model.inv_bind_transform = sub_deformer.transform;
             }
         }
-        FbxComponent::BlendShapeChannel(channel) => {
-            if let FbxComponent::ShapeGeometry(_) = child {
-                channel.geometry = child_handle;
-            }
-        }
+        FbxComponent::BlendShapeChannel(channel) => match child {
+            FbxComponent::ShapeGeometry(_) => channel.geometry = child_handle,
+            FbxComponent::AnimationCurveNode(_) => channel.animation_curve_node = child_handle,
+            _ => (),
+        },
         // Ignore rest
         _ => (),
     }
@@ -254,6 +254,8 @@ pub struct FbxBlendShapeChannel {
     pub geometry: Handle<FbxComponent>,
     pub deform_percent: f32,
     pub name: String,
+    /// Animation of the `DeformPercent` property, if any.
+    pub animation_curve_node: Handle<FbxComponent>,
 }
 
 impl FbxBlendShapeChannel {
@@ -273,6 +275,7 @@ impl FbxBlendShapeChannel {
             geometry: Default::default(),
             deform_percent,
             name,
+            animation_curve_node: Default::default(),
         })
     }
 }
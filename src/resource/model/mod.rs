@@ -25,7 +25,7 @@ use crate::{
         MODEL_RESOURCE_UUID,
     },
     core::{
-        algebra::{UnitQuaternion, Vector3},
+        algebra::{Matrix3, UnitQuaternion, Vector3},
         log::{Log, MessageKind},
         pool::Handle,
         reflect::prelude::*,
@@ -408,6 +408,63 @@ impl MaterialSearchOptions {
     }
 }
 
+/// An axis of the coordinate system of a source model. See [`ModelImportOptions::up_axis`] and
+/// [`ModelImportOptions::forward_axis`] docs for more info.
+#[derive(
+    Copy,
+    Clone,
+    Debug,
+    PartialEq,
+    Eq,
+    Hash,
+    Deserialize,
+    Serialize,
+    Reflect,
+    AsRefStr,
+    EnumString,
+    EnumVariantNames,
+)]
+pub enum ModelAxis {
+    /// Positive X axis.
+    X,
+    /// Negative X axis.
+    NegativeX,
+    /// Positive Y axis.
+    Y,
+    /// Negative Y axis.
+    NegativeY,
+    /// Positive Z axis.
+    Z,
+    /// Negative Z axis.
+    NegativeZ,
+}
+
+impl ModelAxis {
+    /// Returns a unit vector, that corresponds to the axis.
+    pub fn vector(self) -> Vector3<f32> {
+        match self {
+            ModelAxis::X => Vector3::x(),
+            ModelAxis::NegativeX => -Vector3::x(),
+            ModelAxis::Y => Vector3::y(),
+            ModelAxis::NegativeY => -Vector3::y(),
+            ModelAxis::Z => Vector3::z(),
+            ModelAxis::NegativeZ => -Vector3::z(),
+        }
+    }
+}
+
+fn default_up_axis() -> ModelAxis {
+    ModelAxis::Y
+}
+
+fn default_forward_axis() -> ModelAxis {
+    ModelAxis::Z
+}
+
+fn default_scale() -> f32 {
+    1.0
+}
+
 /// A set of options that will be applied to a model resource when loading it from external source.
 ///
 /// # Details
@@ -418,16 +475,88 @@ impl MaterialSearchOptions {
 ///
 /// ```text
 /// (
-///     material_search_options: RecursiveUp
+///     material_search_options: RecursiveUp,
+///     // A model from Blender: Z-up, -Y forward.
+///     up_axis: Z,
+///     forward_axis: NegativeY,
 /// )
 /// ```
 ///
+/// The options are stored next to the model, so every scene that references the model gets the same
+/// imported data. If the resource manager has a file system watcher, changing the options file reimports
+/// the model.
+///
 /// Check documentation of the field of the structure for more info about each parameter.
-#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Default, Reflect, Eq)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Reflect)]
 pub struct ModelImportOptions {
     /// See [`MaterialSearchOptions`] docs for more info.
     #[serde(default)]
     pub material_search_options: MaterialSearchOptions,
+    /// Up axis of the source model, it is converted to +Y axis of the engine. Most of 3D modelling
+    /// software (Blender, 3ds Max) use +Z as up axis. The conversion is applied to node transforms,
+    /// mesh data and animations, so the imported geometry is correct for physics and navmesh
+    /// generation. Default is [`ModelAxis::Y`].
+    #[serde(default = "default_up_axis")]
+    pub up_axis: ModelAxis,
+    /// Forward axis of the source model, it is converted to +Z axis of the engine. It must be
+    /// perpendicular to the up axis, otherwise axis conversion is not performed. Default is
+    /// [`ModelAxis::Z`].
+    #[serde(default = "default_forward_axis")]
+    pub forward_axis: ModelAxis,
+    /// Uniform scale factor, that is applied to the model. For example, use `0.01` for models made in
+    /// centimeters. Default is `1.0`.
+    #[serde(default = "default_scale")]
+    pub scale: f32,
+    /// If `true`, all geometries of a mesh are merged, so there will be only one surface per material.
+    /// Empty surfaces are removed as well. It reduces the number of draw calls. Default is `false`.
+    #[serde(default)]
+    pub merge_surfaces_by_material: bool,
+    /// If `true`, leaf bones, that do not affect any vertex (for example, "end" bones that Blender
+    /// adds to every bone chain), are removed together with their animation tracks. Default is `false`.
+    #[serde(default)]
+    pub strip_unused_bones: bool,
+    /// Vertex positions are snapped to a grid with the given cell size, so close vertices with the
+    /// same attributes are welded. Default is `0.0` - only exactly equal vertices are welded.
+    #[serde(default)]
+    pub weld_distance: f32,
+}
+
+impl Default for ModelImportOptions {
+    fn default() -> Self {
+        Self {
+            material_search_options: Default::default(),
+            up_axis: default_up_axis(),
+            forward_axis: default_forward_axis(),
+            scale: default_scale(),
+            merge_surfaces_by_material: false,
+            strip_unused_bones: false,
+            weld_distance: 0.0,
+        }
+    }
+}
+
+impl ModelImportOptions {
+    /// Returns a rotation matrix, that converts directions from the coordinate system of a source
+    /// model to the coordinate system of the engine (+Y up, +Z forward). Returns identity matrix
+    /// if the up and forward axes are not perpendicular.
+    pub fn axis_conversion(&self) -> Matrix3<f32> {
+        let up = self.up_axis.vector();
+        let forward = self.forward_axis.vector();
+        if up.dot(&forward) != 0.0 {
+            Log::warn(format!(
+                "Up axis {:?} and forward axis {:?} of a model must be perpendicular! \
+                Axis conversion is ignored.",
+                self.up_axis, self.forward_axis
+            ));
+            return Matrix3::identity();
+        }
+        // Rows of the matrix are source axes, that become X, Y and Z axes of the engine.
+        Matrix3::from_rows(&[
+            up.cross(&forward).transpose(),
+            up.transpose(),
+            forward.transpose(),
+        ])
+    }
 }
 
 impl ImportOptions for ModelImportOptions {}
@@ -548,3 +677,51 @@ impl Model {
         &mut self.scene
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::algebra::Vector3,
+        resource::model::{ModelAxis, ModelImportOptions},
+    };
+
+    #[test]
+    fn test_axis_conversion() {
+        let convert = |up, forward, v: Vector3<f32>| {
+            ModelImportOptions {
+                up_axis: up,
+                forward_axis: forward,
+                ..Default::default()
+            }
+            .axis_conversion()
+                * v
+        };
+
+        // Default options do nothing.
+        let v = Vector3::new(1.0, 2.0, 3.0);
+        assert_eq!(convert(ModelAxis::Y, ModelAxis::Z, v), v);
+
+        // Blender: Z-up, -Y forward, right-handed.
+        assert_eq!(
+            convert(ModelAxis::Z, ModelAxis::NegativeY, Vector3::z()),
+            Vector3::y()
+        );
+        assert_eq!(
+            convert(ModelAxis::Z, ModelAxis::NegativeY, -Vector3::y()),
+            Vector3::z()
+        );
+        assert_eq!(
+            convert(ModelAxis::Z, ModelAxis::NegativeY, Vector3::x()),
+            Vector3::x()
+        );
+
+        // 3ds Max: Z-up, Y forward.
+        assert_eq!(
+            convert(ModelAxis::Z, ModelAxis::Y, Vector3::new(1.0, 2.0, 3.0)),
+            Vector3::new(-1.0, 3.0, 2.0)
+        );
+
+        // Invalid combination is ignored.
+        assert_eq!(convert(ModelAxis::Z, ModelAxis::NegativeZ, v), v);
+    }
+}
@@ -20,7 +20,9 @@ pub struct TextureLoader {
 
 impl ResourceLoader for TextureLoader {
     fn extensions(&self) -> &[&str] {
-        &["jpg", "jpeg", "tga", "gif", "bmp", "png", "tiff", "dds"]
+        &[
+            "jpg", "jpeg", "tga", "gif", "bmp", "png", "tiff", "dds", "hdr",
+        ]
     }
 
     fn into_any(self: Box<Self>) -> Box<dyn Any> {
@@ -73,6 +75,7 @@ impl ResourceLoader for TextureLoader {
                     raw_texture.set_anisotropy_level(import_options.anisotropy);
                     raw_texture.set_s_wrap_mode(import_options.s_wrap_mode);
                     raw_texture.set_t_wrap_mode(import_options.t_wrap_mode);
+                    raw_texture.set_upload_priority(import_options.upload_priority);
 
                     texture.commit_ok(raw_texture);
 
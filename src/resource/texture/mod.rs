@@ -7,7 +7,8 @@
 //! ## Supported formats
 //!
 //! To load images and decode them, Fyrox uses image and ddsfile crates. Here is the list of
-//! supported formats: png, tga, bmp, dds, jpg, gif, tiff, dds.
+//! supported formats: png, tga, bmp, dds, jpg, gif, tiff, dds, hdr. HDR images are loaded as
+//! RGB32F textures in linear color space.
 //!
 //! ## Compressed textures
 //!
@@ -21,7 +22,10 @@
 //! access to pixels of render target.
 
 use crate::{
-    asset::{options::ImportOptions, Resource, ResourceData, TEXTURE_RESOURCE_UUID},
+    asset::{
+        options::ImportOptions, upload::UploadPriority, Resource, ResourceData,
+        ResourceMemoryUsage, TEXTURE_RESOURCE_UUID,
+    },
     core::{
         algebra::{Vector2, Vector3},
         futures::io::Error,
@@ -253,6 +257,7 @@ pub struct Texture {
     serialize_content: bool,
     data_hash: u64,
     is_render_target: bool,
+    upload_priority: UploadPriority,
 }
 
 impl TypeUuidProvider for Texture {
@@ -281,6 +286,13 @@ impl ResourceData for Texture {
     fn type_uuid(&self) -> Uuid {
         <Self as TypeUuidProvider>::type_uuid()
     }
+
+    fn memory_usage(&self) -> ResourceMemoryUsage {
+        ResourceMemoryUsage {
+            cpu_bytes: self.bytes.len(),
+            gpu_bytes: self.gpu_size(),
+        }
+    }
 }
 
 impl Visit for Texture {
@@ -307,6 +319,7 @@ impl Visit for Texture {
         let _ = self
             .serialize_content
             .visit("SerializeContent", &mut region);
+        let _ = self.upload_priority.visit("UploadPriority", &mut region);
 
         if self.serialize_content {
             let mut bytes_view = PodVecView::from_pod_vec(&mut self.bytes);
@@ -339,6 +352,7 @@ impl Default for Texture {
             serialize_content: false,
             data_hash: 0,
             is_render_target: false,
+            upload_priority: UploadPriority::Normal,
         }
     }
 }
@@ -419,6 +433,8 @@ pub struct TextureImportOptions {
     pub(crate) compression: CompressionOptions,
     #[serde(default)]
     pub(crate) mip_filter: MipFilter,
+    #[serde(default)]
+    pub(crate) upload_priority: UploadPriority,
 }
 
 impl Default for TextureImportOptions {
@@ -431,6 +447,7 @@ impl Default for TextureImportOptions {
             anisotropy: 16.0,
             compression: CompressionOptions::default(),
             mip_filter: Default::default(),
+            upload_priority: Default::default(),
         }
     }
 }
@@ -519,6 +536,17 @@ impl TextureImportOptions {
     pub fn set_compression(&mut self, compression: CompressionOptions) {
         self.compression = compression;
     }
+
+    /// Sets priority of GPU upload of imported textures. See [`UploadPriority`] docs for more info.
+    pub fn with_upload_priority(mut self, upload_priority: UploadPriority) -> Self {
+        self.upload_priority = upload_priority;
+        self
+    }
+
+    /// Sets priority of GPU upload of imported textures. See [`UploadPriority`] docs for more info.
+    pub fn set_upload_priority(&mut self, upload_priority: UploadPriority) {
+        self.upload_priority = upload_priority;
+    }
 }
 
 /// Type alias for texture resources.
@@ -586,6 +614,7 @@ impl TextureResourceExtension for TextureResource {
             serialize_content: false,
             data_hash: 0,
             is_render_target: true,
+            upload_priority: UploadPriority::Normal,
         })
     }
 
@@ -1361,7 +1390,14 @@ impl Texture {
                 width as usize * height as usize * pixel_kind.size_in_bytes().unwrap_or(4),
             );
 
-            if gen_mip_maps {
+            // Mip maps can't be generated for floating-point images (HDR), these are usually used as
+            // a source for environment maps, that have their own mip levels.
+            if gen_mip_maps
+                && !matches!(
+                    pixel_kind,
+                    TexturePixelKind::RGB32F | TexturePixelKind::RGBA32F
+                )
+            {
                 let pixel_type = convert_pixel_type_enum(pixel_kind);
                 let mut level_width = width;
                 let mut level_height = height;
@@ -1471,7 +1507,24 @@ impl Texture {
         bytes: Vec<u8>,
         serialize_content: bool,
     ) -> Option<Self> {
-        if bytes_in_mip_level(kind, pixel_kind, 0) != bytes.len() as u32 {
+        Self::from_bytes_with_mip_count(kind, pixel_kind, 1, bytes, serialize_content)
+    }
+
+    /// Creates new texture instance from given parameters. `bytes` must contain data of every mip
+    /// level, starting from the largest one. Data of cube maps is stored per level, each level
+    /// contains all six faces.
+    pub fn from_bytes_with_mip_count(
+        kind: TextureKind,
+        pixel_kind: TexturePixelKind,
+        mip_count: u32,
+        bytes: Vec<u8>,
+        serialize_content: bool,
+    ) -> Option<Self> {
+        let mip_count = mip_count.max(1);
+        let expected_size = (0..mip_count as usize)
+            .map(|mip| bytes_in_mip_level(kind, pixel_kind, mip) as usize)
+            .sum::<usize>();
+        if expected_size != bytes.len() {
             None
         } else {
             Some(Self {
@@ -1480,6 +1533,7 @@ impl Texture {
                 data_hash: data_hash(&bytes),
                 bytes: bytes.into(),
                 pixel_kind,
+                mip_count,
                 serialize_content,
                 ..Default::default()
             })
@@ -1618,6 +1672,14 @@ impl Texture {
         self.is_render_target
     }
 
+    /// Changes size of the render target. The renderer picks up the new size on the next frame.
+    /// Does nothing if the texture is not a render target.
+    pub fn set_render_target_size(&mut self, width: u32, height: u32) {
+        if self.is_render_target {
+            self.kind = TextureKind::Rectangle { width, height };
+        }
+    }
+
     /// Max samples for anisotropic filtering. Default value is 16.0 (max).
     /// However real value passed to GPU will be clamped to maximum supported
     /// by current GPU. To disable anisotropic filtering set this to 1.0.
@@ -1631,6 +1693,25 @@ impl Texture {
         self.anisotropy
     }
 
+    /// Sets priority of GPU upload of the texture. Loaded textures are uploaded to GPU gradually,
+    /// in small portions every frame, textures with higher priority are uploaded first. See
+    /// [`UploadPriority`] docs for more info.
+    pub fn set_upload_priority(&mut self, upload_priority: UploadPriority) {
+        self.upload_priority = upload_priority;
+    }
+
+    /// Returns priority of GPU upload of the texture.
+    pub fn upload_priority(&self) -> UploadPriority {
+        self.upload_priority
+    }
+
+    /// Returns amount of bytes the texture occupies in GPU memory (including every mip level).
+    pub fn gpu_size(&self) -> usize {
+        (0..self.mip_count.max(1) as usize)
+            .map(|mip| bytes_in_mip_level(self.kind, self.pixel_kind, mip) as usize)
+            .sum()
+    }
+
     /// Sets new path to source file.
     pub fn set_path<P: AsRef<Path>>(&mut self, path: P) {
         self.path = path.as_ref().to_owned();
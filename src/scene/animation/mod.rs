@@ -2,9 +2,10 @@
 //! See [`AnimationPlayer`] docs for more info.
 
 use crate::{
-    animation::AnimationContainer,
+    animation::{AnimationContainer, RootMotion},
     core::{
-        math::aabb::AxisAlignedBoundingBox,
+        algebra::{Matrix4, UnitQuaternion},
+        math::{aabb::AxisAlignedBoundingBox, Matrix4Ext},
         pool::Handle,
         reflect::prelude::*,
         uuid::{uuid, Uuid},
@@ -14,8 +15,9 @@ use crate::{
     },
     scene::{
         base::{Base, BaseBuilder},
-        graph::Graph,
+        graph::{Graph, NodePool},
         node::{Node, NodeTrait, UpdateContext},
+        rigidbody::RigidBody,
     },
 };
 use std::ops::{Deref, DerefMut};
@@ -91,11 +93,23 @@ pub mod absm;
 /// The example creates a bounce animation first - it is a simple animation that animates position of a given node
 /// (`animated_node`). Only then it creates an animation player node with an animation container with a single animation.
 /// To understand why this is so complicated, see the docs of [`crate::animation::Animation`].
+///
+/// # Root motion
+///
+/// Root motion of every enabled animation (see [`crate::animation::RootMotionSettings`]) is accumulated
+/// on each update and could be fetched using [`AnimationPlayer::root_motion`]. The motion could also be
+/// applied automatically to a node set by [`AnimationPlayer::set_root_motion_target`]. If the node is a
+/// rigid body, the motion is applied as its velocity, otherwise the local transform of the node is changed.
 #[derive(Visit, Reflect, Clone, Debug)]
 pub struct AnimationPlayer {
     base: Base,
     animations: InheritableVariable<AnimationContainer>,
     auto_apply: bool,
+    #[visit(optional)]
+    root_motion_target: InheritableVariable<Handle<Node>>,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    root_motion: Option<RootMotion>,
 }
 
 impl Default for AnimationPlayer {
@@ -104,6 +118,8 @@ impl Default for AnimationPlayer {
             base: Default::default(),
             animations: Default::default(),
             auto_apply: true,
+            root_motion_target: Default::default(),
+            root_motion: None,
         }
     }
 }
@@ -138,6 +154,68 @@ impl AnimationPlayer {
     pub fn set_animations(&mut self, animations: AnimationContainer) {
         self.animations.set_value_and_mark_modified(animations);
     }
+
+    /// Sets a node that will be moved by the root motion of the animations. Rigid bodies are moved by
+    /// setting their velocities, any other node is moved by changing its local transform. Pass
+    /// [`Handle::NONE`] to disable automatic root motion applying.
+    pub fn set_root_motion_target(&mut self, target: Handle<Node>) {
+        self.root_motion_target.set_value_and_mark_modified(target);
+    }
+
+    /// Returns a handle of a node that is moved by the root motion of the animations.
+    pub fn root_motion_target(&self) -> Handle<Node> {
+        *self.root_motion_target
+    }
+
+    /// Returns root motion of all enabled animations accumulated on the last update (if any animation has
+    /// root motion). The motion is in local space of the root nodes of the animations, see
+    /// [`RootMotion`] docs for more info.
+    pub fn root_motion(&self) -> Option<&RootMotion> {
+        self.root_motion.as_ref()
+    }
+}
+
+// Returns global transform of the parent of the given node, root motion is defined in this space.
+fn root_motion_space(nodes: &NodePool, node: Handle<Node>) -> Matrix4<f32> {
+    nodes
+        .try_borrow(node)
+        .and_then(|node| nodes.try_borrow(node.parent()))
+        .map(|parent| parent.global_transform())
+        .unwrap_or_else(Matrix4::identity)
+}
+
+/// Applies root motion in world space to the given node. Rigid bodies are moved by setting their
+/// linear and angular velocities, vertical velocity of a body is kept, if there's no vertical motion (so
+/// the gravity still works). Any other node is moved by changing its local transform.
+pub fn apply_root_motion(nodes: &mut NodePool, target: Handle<Node>, motion: &RootMotion, dt: f32) {
+    if dt <= 0.0 {
+        return;
+    }
+
+    let parent_transform = root_motion_space(nodes, target);
+
+    if let Some(target) = nodes.try_borrow_mut(target) {
+        if let Some(rigid_body) = target.cast_mut::<RigidBody>() {
+            let mut lin_vel = motion.delta_position.scale(1.0 / dt);
+            if motion.delta_position.y == 0.0 {
+                lin_vel.y = rigid_body.lin_vel().y;
+            }
+            rigid_body.set_lin_vel(lin_vel);
+            rigid_body.set_ang_vel(motion.delta_rotation.scaled_axis().scale(1.0 / dt));
+        } else {
+            let parent_rotation = UnitQuaternion::from_matrix(&parent_transform.basis());
+            let local_delta = parent_transform
+                .try_inverse()
+                .unwrap_or_else(Matrix4::identity)
+                .transform_vector(&motion.delta_position);
+            let transform = target.local_transform_mut();
+            let rotation = parent_rotation.inverse()
+                * motion.delta_rotation
+                * parent_rotation
+                * **transform.rotation();
+            transform.offset(local_delta).set_rotation(rotation);
+        }
+    }
 }
 
 impl TypeUuidProvider for AnimationPlayer {
@@ -181,6 +259,36 @@ impl NodeTrait for AnimationPlayer {
             self.auto_apply,
             context.dt,
         );
+
+        self.root_motion = None;
+        let mut world_motion = RootMotion::default();
+        for animation in self.animations.iter().filter(|a| a.is_enabled()) {
+            if let (Some(settings), Some(motion)) = (
+                animation.root_motion_settings_ref(),
+                animation.root_motion(),
+            ) {
+                let accumulated = self.root_motion.get_or_insert_with(Default::default);
+                accumulated.delta_position += motion.delta_position;
+                accumulated.delta_rotation *= motion.delta_rotation;
+
+                let space = root_motion_space(context.nodes, settings.node);
+                let space_rotation = UnitQuaternion::from_matrix(&space.basis());
+                world_motion.delta_position += space.transform_vector(&motion.delta_position);
+                world_motion.delta_rotation = space_rotation
+                    * motion.delta_rotation
+                    * space_rotation.inverse()
+                    * world_motion.delta_rotation;
+            }
+        }
+
+        if self.root_motion.is_some() && self.root_motion_target.is_some() {
+            apply_root_motion(
+                context.nodes,
+                *self.root_motion_target,
+                &world_motion,
+                context.dt,
+            );
+        }
     }
 }
 
@@ -189,6 +297,7 @@ pub struct AnimationPlayerBuilder {
     base_builder: BaseBuilder,
     animations: AnimationContainer,
     auto_apply: bool,
+    root_motion_target: Handle<Node>,
 }
 
 impl AnimationPlayerBuilder {
@@ -198,6 +307,7 @@ impl AnimationPlayerBuilder {
             base_builder,
             animations: AnimationContainer::new(),
             auto_apply: true,
+            root_motion_target: Handle::NONE,
         }
     }
 
@@ -213,12 +323,21 @@ impl AnimationPlayerBuilder {
         self
     }
 
+    /// Sets a node that will be moved by the root motion of the animations. See
+    /// [`AnimationPlayer::set_root_motion_target`] docs for more info.
+    pub fn with_root_motion_target(mut self, target: Handle<Node>) -> Self {
+        self.root_motion_target = target;
+        self
+    }
+
     /// Creates an instance of [`AnimationPlayer`] node.
     pub fn build_node(self) -> Node {
         Node::new(AnimationPlayer {
             base: self.base_builder.build_base(),
             animations: self.animations.into(),
             auto_apply: self.auto_apply,
+            root_motion_target: self.root_motion_target.into(),
+            root_motion: None,
         })
     }
 
@@ -229,6 +229,20 @@ pub struct Property {
     pub value: PropertyValue,
 }
 
+/// A record of attachment of a node to a bone of a model instance, see
+/// [`crate::scene::graph::Graph::attach_to_bone`] for more info. The bone is stored by its name, so
+/// the attachment could be re-established when the graph is resolved, even if handle of the bone was
+/// changed.
+#[derive(Debug, Visit, Reflect, Default, Clone)]
+pub struct BoneAttachment {
+    /// A handle of the root node of a model instance, that contains the bone.
+    pub model_root: Handle<Node>,
+    /// A name of the bone.
+    pub bone_name: String,
+    /// Local transform of the attached node relative to the bone.
+    pub offset: Transform,
+}
+
 /// A script message from scene node. It is used for deferred initialization/deinitialization.
 pub enum NodeScriptMessage {
     /// A script was set to a node and needs to be initialized.
@@ -382,6 +396,9 @@ pub struct Base {
 
     #[reflect(hidden)]
     pub(crate) global_enabled: Cell<bool>,
+
+    #[reflect(hidden)]
+    pub(crate) bone_attachment: Option<BoneAttachment>,
 }
 
 impl Drop for Base {
@@ -580,6 +597,13 @@ impl Base {
         self.original_handle_in_resource
     }
 
+    /// Returns attachment of the node to a bone (if any), see
+    /// [`crate::scene::graph::Graph::attach_to_bone`] for more info.
+    #[inline]
+    pub fn bone_attachment(&self) -> Option<&BoneAttachment> {
+        self.bone_attachment.as_ref()
+    }
+
     /// Returns position of the node in absolute coordinates.
     #[inline]
     pub fn global_position(&self) -> Vector3<f32> {
@@ -817,6 +841,15 @@ impl Base {
     }
 
     /// Enables or disables scene node. Disabled scene nodes won't be updated (including scripts) or rendered.
+    /// Unlike visibility, that only hides a node, disabling stops everything related to the node:
+    ///
+    /// - animation players and particle systems are not updated, they continue from the same state when enabled
+    ///   again (time spent in disabled state is not accumulated, so there's no jumps);
+    /// - animation tracks that target disabled nodes are skipped, animations keep playing, but their values are
+    ///   not applied to disabled nodes;
+    /// - sound sources are removed from the sound context, so they're silent;
+    /// - rigid bodies, colliders and joints are removed from the physics world, so they're frozen and do not
+    ///   interact with other bodies. Their state (position, velocity) is restored when enabled again.
     ///
     /// # Important notes
     ///
@@ -944,6 +977,7 @@ impl Visit for Base {
         let _ = self.cast_shadows.visit("CastShadows", &mut region);
         let _ = self.instance_id.visit("InstanceId", &mut region);
         let _ = self.enabled.visit("Enabled", &mut region);
+        let _ = self.bone_attachment.visit("BoneAttachment", &mut region);
 
         // Script visiting may fail for various reasons:
         //
@@ -1153,6 +1187,7 @@ impl BaseBuilder {
             instance_id: InstanceId(Uuid::new_v4()),
             enabled: self.enabled.into(),
             global_enabled: Cell::new(true),
+            bone_attachment: None,
         }
     }
 }
@@ -24,6 +24,7 @@ use crate::{
         graph::Graph,
         node::{Node, NodeTrait, UpdateContext},
     },
+    utils::environment_map::{equirect_face_size, make_environment_maps, EnvironmentMapError},
 };
 use lazy_static::lazy_static;
 use std::{
@@ -103,6 +104,22 @@ pub struct OrthographicProjection {
     /// some minimal value to prevent singularities from occuring.
     #[reflect(step = 0.1)]
     pub vertical_size: f32,
+    /// Uniform scale of the view about the camera center. Values larger than `1.0` zoom in, values
+    /// less than `1.0` zoom out. Any values very close to zero will be clamped to some minimal value.
+    #[reflect(min_value = 0.0, step = 0.1)]
+    #[visit(optional)] // Backward compatibility
+    pub zoom: f32,
+    /// Rotation of the view (in radians) around the viewing axis. It is applied on top of the
+    /// rotation of the camera node, which is handy for 2D games where the node usually stays
+    /// unrotated.
+    #[reflect(step = 0.1)]
+    #[visit(optional)] // Backward compatibility
+    pub rotation: f32,
+    /// If set, the translation of the view is rounded to whole pixels of the render target. It
+    /// prevents texture shimmering in pixel art games, when the camera moves by fractions of a
+    /// pixel.
+    #[visit(optional)] // Backward compatibility
+    pub pixel_snapping: bool,
 }
 
 impl Default for OrthographicProjection {
@@ -111,6 +128,9 @@ impl Default for OrthographicProjection {
             z_near: 0.0,
             z_far: 2048.0,
             vertical_size: 5.0,
+            zoom: 1.0,
+            rotation: 0.0,
+            pixel_snapping: false,
         }
     }
 }
@@ -134,7 +154,7 @@ impl OrthographicProjection {
         let aspect = (frame_size.x / frame_size.y).max(limit);
 
         // Prevent collapsing projection "box" into a point, which could cause panic.
-        let vertical_size = clamp_to_limit_signed(self.vertical_size, limit);
+        let vertical_size = clamp_to_limit_signed(self.zoomed_vertical_size(), limit);
         let horizontal_size = clamp_to_limit_signed(aspect * vertical_size, limit);
 
         let z_near = self.z_far.min(self.z_near);
@@ -151,6 +171,41 @@ impl OrthographicProjection {
         let bottom = -vertical_size;
         Matrix4::new_orthographic(left, right, bottom, top, z_near, z_far)
     }
+
+    /// Returns vertical size of the "view box" with zoom applied.
+    #[inline]
+    pub fn zoomed_vertical_size(&self) -> f32 {
+        self.vertical_size / self.zoom.max(10.0 * f32::EPSILON)
+    }
+
+    /// Returns view matrix adjusted by the rotation and pixel snapping of the projection. The
+    /// viewport size is used to calculate the size of a pixel in world units.
+    #[inline]
+    pub fn adjust_view_matrix(
+        &self,
+        mut view_matrix: Matrix4<f32>,
+        viewport_size: Vector2<f32>,
+    ) -> Matrix4<f32> {
+        if self.rotation != 0.0 {
+            view_matrix =
+                Matrix4::from_axis_angle(&Vector3::z_axis(), -self.rotation) * view_matrix;
+        }
+
+        if self.pixel_snapping && viewport_size.y > 0.0 {
+            let pixel_size = 2.0 * self.zoomed_vertical_size().abs() / viewport_size.y;
+            if pixel_size > f32::EPSILON {
+                // Snap in pixel space, with origin at the corner of the viewport, otherwise the
+                // view will be off by half of a pixel for odd viewport sizes.
+                for (row, size) in [(0, viewport_size.x), (1, viewport_size.y)] {
+                    let half_size = size * 0.5;
+                    let pixels = view_matrix[(row, 3)] / pixel_size + half_size;
+                    view_matrix[(row, 3)] = (pixels.round() - half_size) * pixel_size;
+                }
+            }
+        }
+
+        view_matrix
+    }
 }
 
 /// A method of projection. Different projection types suitable for different purposes:
@@ -394,6 +449,13 @@ impl Camera {
         let up = self.base.up_vector();
 
         self.view_matrix = Matrix4::look_at_rh(&Point3::from(pos), &Point3::from(pos + look), &up);
+        if let Projection::Orthographic(ref orthographic) = *self.projection {
+            let viewport = self.viewport_pixels(frame_size);
+            self.view_matrix = orthographic.adjust_view_matrix(
+                self.view_matrix,
+                Vector2::new(viewport.w() as f32, viewport.h() as f32),
+            );
+        }
         self.projection_matrix = self.projection.matrix(frame_size);
     }
 
@@ -559,6 +621,39 @@ impl Camera {
         Ray::from_two_points(begin, end)
     }
 
+    /// Converts the given screen coordinates to a point on XY plane in world space. It is intended
+    /// to be used with 2D cameras (orthographic cameras looking along Z axis), for example for mouse
+    /// picking. Zoom and rotation of the camera are taken into account.
+    pub fn screen_to_world_2d(
+        &self,
+        screen_coord: Vector2<f32>,
+        screen_size: Vector2<f32>,
+    ) -> Vector2<f32> {
+        let ray = self.make_ray(screen_coord, screen_size);
+        if ray.dir.z.abs() > f32::EPSILON {
+            let point = ray.get_point(-ray.origin.z / ray.dir.z);
+            Vector2::new(point.x, point.y)
+        } else {
+            Vector2::new(ray.origin.x, ray.origin.y)
+        }
+    }
+
+    /// Converts the given point on XY plane in world space to screen coordinates. It is the inverse
+    /// of [`Self::screen_to_world_2d`].
+    pub fn world_to_screen_2d(
+        &self,
+        world_pos: Vector2<f32>,
+        screen_size: Vector2<f32>,
+    ) -> Vector2<f32> {
+        let viewport = self.viewport_pixels(screen_size);
+        let proj = self.view_projection_matrix() * Vector4::new(world_pos.x, world_pos.y, 0.0, 1.0);
+        let k = if proj.w != 0.0 { 0.5 / proj.w } else { 0.5 };
+        Vector2::new(
+            viewport.w() as f32 * (proj.x * k + 0.5),
+            viewport.h() as f32 * (0.5 - proj.y * k),
+        )
+    }
+
     /// Calculates new fitting parameters for the given axis-aligned bounding box using current camera's
     /// global transform and provided aspect ratio. See [`FitParameters`] docs for more info.
     ///
@@ -583,7 +678,7 @@ impl Camera {
                     position: aabb.center() - look_vector.scale(distance),
                 }
             }
-            Projection::Orthographic(_) => {
+            Projection::Orthographic(orthographic) => {
                 let mut min_x = f32::MAX;
                 let mut min_y = f32::MAX;
                 let mut max_x = -f32::MAX;
@@ -607,7 +702,8 @@ impl Camera {
 
                 FitParameters::Orthographic {
                     position: aabb.center() - look_vector.scale((aabb.max - aabb.min).norm()),
-                    vertical_size: (max_y - min_y).max((max_x - min_x) * aspect_ratio),
+                    vertical_size: (max_y - min_y).max((max_x - min_x) * aspect_ratio)
+                        * orthographic.zoom,
                 }
             }
         }
@@ -701,6 +797,10 @@ impl NodeTrait for Camera {
 
     fn update(&mut self, context: &mut UpdateContext) {
         self.calculate_matrices(context.frame_size);
+
+        if let Some(skybox) = self.sky_box.get_value_mut_silent() {
+            skybox.update();
+        }
     }
 
     fn debug_draw(&self, ctx: &mut SceneDrawingContext) {
@@ -1190,7 +1290,7 @@ impl SkyBoxBuilder {
             bottom: self.bottom,
             front: self.front,
             back: self.back,
-            cubemap: None,
+            ..Default::default()
         };
 
         skybox.create_cubemap()?;
@@ -1204,6 +1304,20 @@ impl SkyBoxBuilder {
 /// skies and/or some other objects (mountains, buildings, etc.). Usually skyboxes used
 /// in outdoor scenes, however real use of it limited only by your imagination. Skybox
 /// will be drawn first, none of objects could be drawn before skybox.
+///
+/// ## HDR sky boxes
+///
+/// Instead of six faces, a skybox could be made of a single equirectangular (latitude-longitude)
+/// texture, usually an HDR (`.hdr`) image, see [`SkyBox::from_equirect`]. Such skybox also provides
+/// prefiltered reflections for rough surfaces and irradiance for diffuse lighting. Only the path of
+/// the equirectangular texture is saved, the cube maps are generated again when a scene is loaded.
+///
+/// HDR sky is rendered in linear color space without any clamping, so a bright sky makes the frame
+/// bright as well. The final image is tonemapped by the HDR pass of the renderer using the exposure
+/// of the camera (see [`Exposure`]). Automatic exposure (default) adapts to the average luminance of
+/// the frame, so an HDR sky does not need to be scaled down manually, while manual exposure must be
+/// lowered for bright skies. LDR sky boxes are in sRGB color space and converted to linear color
+/// space when rendered.
 #[derive(Debug, Clone, Default, PartialEq, Reflect, Visit, Eq)]
 pub struct SkyBox {
     /// Texture for front face.
@@ -1230,10 +1344,25 @@ pub struct SkyBox {
     #[reflect(setter = "set_bottom")]
     pub(crate) bottom: Option<TextureResource>,
 
+    /// Equirectangular texture, that is used instead of the faces if set.
+    #[reflect(setter = "set_equirect")]
+    #[visit(optional)]
+    pub(crate) equirect: Option<TextureResource>,
+
     /// Cubemap texture
     #[reflect(hidden)]
     #[visit(skip)]
     pub(crate) cubemap: Option<TextureResource>,
+
+    /// Irradiance cube map, only sky boxes made of an equirectangular texture have it.
+    #[reflect(hidden)]
+    #[visit(skip)]
+    pub(crate) irradiance: Option<TextureResource>,
+
+    /// Whether the cube map must be created once the equirectangular texture is loaded.
+    #[reflect(hidden)]
+    #[visit(skip)]
+    pub(crate) pending: bool,
 }
 
 /// An error that may occur during skybox creation.
@@ -1274,14 +1403,105 @@ pub enum SkyBoxError {
         /// Index of the faulty input texture.
         index: usize,
     },
+    /// Unable to convert an equirectangular texture to a cube map.
+    EnvironmentMap(EnvironmentMapError),
 }
 
 impl SkyBox {
+    /// Creates a skybox from an equirectangular texture, for example an HDR (`.hdr`) image, see
+    /// [`SkyBox`] docs for more info. The texture could still be loading, in this case the cube maps
+    /// will be created as soon as it is loaded and the skybox won't be drawn until then.
+    pub fn from_equirect(texture: TextureResource) -> Self {
+        let mut skybox = Self {
+            equirect: Some(texture),
+            ..Default::default()
+        };
+        skybox.update_equirect();
+        skybox
+    }
+
     /// Returns cubemap texture
     pub fn cubemap(&self) -> Option<TextureResource> {
         self.cubemap.clone()
     }
 
+    /// Returns irradiance cube map, only sky boxes made of an equirectangular texture have it.
+    pub fn irradiance_ref(&self) -> Option<&TextureResource> {
+        self.irradiance.as_ref()
+    }
+
+    /// Returns `true` if the cube map has floating-point pixels in linear color space.
+    pub fn is_hdr(&self) -> bool {
+        self.cubemap.as_ref().map_or(false, |cubemap| {
+            matches!(
+                cubemap.data_ref().pixel_kind(),
+                TexturePixelKind::R16F
+                    | TexturePixelKind::R32F
+                    | TexturePixelKind::RGB16F
+                    | TexturePixelKind::RGB32F
+                    | TexturePixelKind::RGBA32F
+            )
+        })
+    }
+
+    /// Sets new equirectangular texture, that will be used instead of the faces. `None` switches the
+    /// skybox back to the faces.
+    pub fn set_equirect(&mut self, texture: Option<TextureResource>) -> Option<TextureResource> {
+        let prev = std::mem::replace(&mut self.equirect, texture);
+        if self.equirect.is_some() {
+            self.update_equirect();
+        } else {
+            Log::verify(self.create_cubemap());
+        }
+        prev
+    }
+
+    /// Returns equirectangular texture of the skybox, if any.
+    pub fn equirect(&self) -> Option<TextureResource> {
+        self.equirect.clone()
+    }
+
+    // Creates the cube map right away if the equirectangular texture is loaded, or postpones it.
+    fn update_equirect(&mut self) {
+        self.cubemap = None;
+        self.irradiance = None;
+        self.pending = true;
+        self.update();
+    }
+
+    /// Creates the cube maps if it was postponed until the equirectangular texture is loaded. It is
+    /// called automatically by the engine for sky boxes of cameras and scenes.
+    pub(crate) fn update(&mut self) {
+        if self.pending
+            && !self
+                .equirect
+                .as_ref()
+                .map_or(false, |equirect| equirect.is_loading())
+        {
+            Log::verify(self.create_cubemap());
+        }
+    }
+
+    fn create_cubemap_from_equirect(
+        &mut self,
+        equirect: &TextureResource,
+    ) -> Result<(), SkyBoxError> {
+        let maps = if let ResourceStateRef::Ok(texture) = equirect.state().get() {
+            let face_size = match texture.kind() {
+                TextureKind::Rectangle { height, .. } => equirect_face_size(height),
+                kind => return Err(SkyBoxError::UnsupportedTextureKind(kind)),
+            };
+            make_environment_maps(texture, face_size).map_err(SkyBoxError::EnvironmentMap)?
+        } else {
+            return Err(SkyBoxError::TextureIsNotReady { index: 0 });
+        };
+
+        self.cubemap = Some(TextureResource::new_ok(maps.specular));
+        self.irradiance = Some(TextureResource::new_ok(maps.irradiance));
+
+        Ok(())
+    }
+
     /// Returns cubemap texture
     pub fn cubemap_ref(&self) -> Option<&TextureResource> {
         self.cubemap.as_ref()
@@ -1345,11 +1565,24 @@ impl SkyBox {
     }
 
     /// Creates a cubemap using provided faces. If some face has not been provided corresponding side will be black.
+    /// If the skybox has an equirectangular texture, it is used instead of the faces.
     ///
     /// # Important notes.
     ///
     /// It will fail if provided face's kind is not TextureKind::Rectangle.
     pub fn create_cubemap(&mut self) -> Result<(), SkyBoxError> {
+        self.pending = false;
+
+        if let Some(equirect) = self.equirect.clone() {
+            if equirect.is_loading() {
+                self.pending = true;
+                return Err(SkyBoxError::TextureIsNotReady { index: 0 });
+            }
+            return self.create_cubemap_from_equirect(&equirect);
+        }
+
+        self.irradiance = None;
+
         self.validate()?;
 
         let (kind, pixel_kind, bytes_per_face) =
@@ -2,13 +2,58 @@
 //!
 //! For more info see [`SceneDrawingContext`]
 
-use crate::core::{
-    algebra::{Matrix4, Point3, UnitQuaternion, Vector2, Vector3},
-    color::{Color, Hsl},
-    math::{aabb::AxisAlignedBoundingBox, frustum::Frustum, Matrix4Ext},
+use crate::{
+    core::{
+        algebra::{Matrix4, Point3, UnitQuaternion, Vector2, Vector3},
+        color::{Color, Hsl},
+        math::{aabb::AxisAlignedBoundingBox, frustum::Frustum, Matrix4Ext},
+        reflect::prelude::*,
+    },
+    scene::{
+        camera::Camera,
+        graph::Graph,
+        light::{directional::DirectionalLight, point::PointLight, spot::SpotLight},
+        mesh::Mesh,
+        sound::Sound,
+    },
+    utils::raw_mesh::{PrimitiveTopology, RawMesh},
 };
+use bitflags::bitflags;
+use fxhash::FxHashSet;
 use std::ops::Range;
 
+/// A set of flags that defines which entities of a scene will be visualized by
+/// [`SceneDrawingContext::draw_graph`]. Every scene has its own set of flags (see
+/// [`crate::scene::Scene::debug_draw_flags`]), the scene draws enabled categories on every update.
+#[derive(Reflect, Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+#[repr(transparent)]
+pub struct DebugDrawFlags(u32);
+
+bitflags! {
+    impl DebugDrawFlags: u32 {
+        /// Nothing is drawn.
+        const NONE = 0;
+        /// Frustums of cameras.
+        const CAMERA_FRUSTUMS = 0b0000_0001;
+        /// Shapes and ranges of light sources - spheres for point lights, cones for spot lights and
+        /// arrows for directional lights.
+        const LIGHTS = 0b0000_0010;
+        /// Radii and maximum distances of sound sources.
+        const SOUND_SOURCES = 0b0000_0100;
+        /// Bone hierarchies of skinned meshes.
+        const SKELETONS = 0b0000_1000;
+        /// Pivots and local axes of every scene node.
+        const PIVOTS = 0b0001_0000;
+        /// Colliders, joints and contacts of 2D and 3D physics.
+        const PHYSICS = 0b0010_0000;
+    }
+}
+
+const LIGHT_COLOR: Color = Color::opaque(255, 255, 0);
+const SOUND_RADIUS_COLOR: Color = Color::opaque(0, 200, 255);
+const SOUND_MAX_DISTANCE_COLOR: Color = Color::opaque(0, 100, 160);
+const SKELETON_COLOR: Color = Color::opaque(255, 0, 255);
+
 /// Colored line between two points.
 #[derive(Clone, Debug)]
 pub struct Line {
@@ -60,14 +105,86 @@ pub struct Line {
 /// moving objects. In this case call `clear_lines` once in a few seconds, and you'll see the "track" of
 /// moving objects.
 ///
+/// # Persistent shapes
+///
+/// Shapes could also be added with a lifetime (see [`Self::add_line_timed`], [`Self::add_sphere_timed`],
+/// [`Self::add_aabb_timed`] and [`Self::add_label_timed`]), such shapes are not removed by `clear_lines`,
+/// instead the scene removes them automatically when their lifetime is over. It is useful to visualize
+/// one-off events, for example the place where a ray hit something a few seconds ago.
+///
+/// # Depth test
+///
+/// By default, lines are hidden behind scene geometry. Use [`Self::set_depth_test`] to draw the shapes
+/// added after the call on top of everything.
+///
+/// # Labels
+///
+/// Text labels (see [`Self::add_label`]) are anchored at world positions, the renderer projects them on
+/// screen every frame and hides the labels that are behind the camera.
+///
 /// # Rendering performance
 ///
-/// The engine renders the entire set of lines in a single draw call, so it very fast - you should be able to draw
-/// up to few millions of lines without any significant performance issues.
-#[derive(Default, Clone, Debug)]
+/// The engine renders the entire set of lines in two draw calls (with and without depth test), so it very
+/// fast - you should be able to draw up to few millions of lines without any significant performance issues.
+/// Persistent lines are kept in a retained buffer and expired lines are removed from it with swap-remove, so
+/// they do not cause reallocations every frame.
+#[derive(Clone, Debug)]
 pub struct SceneDrawingContext {
-    /// List of lines to draw.
+    /// List of lines to draw with depth test.
     pub lines: Vec<Line>,
+    /// List of lines to draw on top of everything (without depth test).
+    pub overlay_lines: Vec<Line>,
+    /// List of text labels to draw.
+    pub labels: Vec<DebugLabel>,
+    timed_lines: Vec<Timed<Line>>,
+    timed_overlay_lines: Vec<Timed<Line>>,
+    timed_labels: Vec<Timed<DebugLabel>>,
+    depth_test: bool,
+}
+
+impl Default for SceneDrawingContext {
+    fn default() -> Self {
+        Self {
+            lines: Default::default(),
+            overlay_lines: Default::default(),
+            labels: Default::default(),
+            timed_lines: Default::default(),
+            timed_overlay_lines: Default::default(),
+            timed_labels: Default::default(),
+            depth_test: true,
+        }
+    }
+}
+
+/// Colored text label anchored at a point in world space.
+#[derive(Clone, Debug)]
+pub struct DebugLabel {
+    /// Position of the label in world space.
+    pub position: Vector3<f32>,
+    /// Text of the label.
+    pub text: String,
+    /// Color of the text.
+    pub color: Color,
+}
+
+#[derive(Clone, Debug)]
+struct Timed<T> {
+    value: T,
+    time_left: f32,
+}
+
+// Decreases lifetimes of the items and removes expired ones. Items are removed only on the next update
+// after their lifetime is over, so every item is drawn at least once.
+fn update_timed<T>(items: &mut Vec<Timed<T>>, dt: f32) {
+    let mut i = 0;
+    while i < items.len() {
+        if items[i].time_left <= 0.0 {
+            items.swap_remove(i);
+        } else {
+            items[i].time_left -= dt;
+            i += 1;
+        }
+    }
 }
 
 impl rapier2d::pipeline::DebugRenderBackend for SceneDrawingContext {
@@ -409,6 +526,57 @@ impl SceneDrawingContext {
         });
     }
 
+    /// Draws a raw mesh using its topology: triangles are drawn as wireframe, line segments are
+    /// drawn as is and points are drawn as small crosses of the given size. The `position` closure
+    /// must return a position of the given vertex, the position is then transformed using the given
+    /// matrix. See [`crate::utils::raw_mesh`] docs for more info.
+    pub fn draw_raw_mesh<T, F>(
+        &mut self,
+        mesh: &RawMesh<T>,
+        transform: Matrix4<f32>,
+        point_size: f32,
+        color: Color,
+        mut position: F,
+    ) where
+        F: FnMut(&T) -> Vector3<f32>,
+    {
+        let mut world_position = |vertex: &T| {
+            transform
+                .transform_point(&Point3::from(position(vertex)))
+                .coords
+        };
+        match mesh.topology {
+            PrimitiveTopology::Triangles => {
+                for [a, b, c] in mesh.triangles() {
+                    let (a, b, c) = (world_position(a), world_position(b), world_position(c));
+                    self.draw_triangle(a, b, c, color);
+                }
+            }
+            PrimitiveTopology::Lines => {
+                for [a, b] in mesh.lines() {
+                    self.add_line(Line {
+                        begin: world_position(a),
+                        end: world_position(b),
+                        color,
+                    });
+                }
+            }
+            PrimitiveTopology::Points => {
+                let half_size = point_size * 0.5;
+                for point in mesh.points() {
+                    let point = world_position(point);
+                    for axis in [Vector3::x(), Vector3::y(), Vector3::z()] {
+                        self.add_line(Line {
+                            begin: point - axis.scale(half_size),
+                            end: point + axis.scale(half_size),
+                            color,
+                        });
+                    }
+                }
+            }
+        }
+    }
+
     /// Draws a sphere as a set of three circles around each axes.
     pub fn draw_wire_sphere(
         &mut self,
@@ -1063,14 +1231,300 @@ impl SceneDrawingContext {
         );
     }
 
+    /// Draws entities of the graph that are enabled by the given set of flags. See [`DebugDrawFlags`]
+    /// for more info. Does nothing if the flags are empty.
+    pub fn draw_graph(&mut self, graph: &Graph, flags: DebugDrawFlags) {
+        if flags.contains(DebugDrawFlags::PHYSICS) {
+            graph.physics.draw(self);
+            graph.physics2d.draw(self);
+        }
+
+        if (flags - DebugDrawFlags::PHYSICS).is_empty() {
+            return;
+        }
+
+        for (handle, node) in graph.pair_iter() {
+            if flags.contains(DebugDrawFlags::PIVOTS) {
+                self.draw_transform(node.global_transform());
+            }
+
+            if flags.contains(DebugDrawFlags::CAMERA_FRUSTUMS) {
+                if let Some(camera) = node.query_component_ref::<Camera>() {
+                    self.draw_frustum(&camera.frustum(), Color::ORANGE);
+                }
+            }
+
+            if flags.contains(DebugDrawFlags::LIGHTS) {
+                let position = node.global_position();
+                if let Some(point_light) = node.query_component_ref::<PointLight>() {
+                    self.draw_wire_sphere(position, point_light.radius(), 24, LIGHT_COLOR);
+                } else if let Some(spot_light) = node.query_component_ref::<SpotLight>() {
+                    // Spot light shines along -Y axis, the cone tip is at the light position.
+                    let distance = spot_light.distance();
+                    let radius = (spot_light.full_cone_angle() * 0.5).tan() * distance;
+                    self.draw_cone(
+                        16,
+                        radius,
+                        distance,
+                        Matrix4::new_translation(&position)
+                            * graph.global_rotation(handle).to_homogeneous()
+                            * Matrix4::new_translation(&Vector3::new(0.0, -distance * 0.5, 0.0)),
+                        LIGHT_COLOR,
+                        false,
+                    );
+                } else if node.query_component_ref::<DirectionalLight>().is_some() {
+                    // Directional light shines along -Y axis.
+                    let length = 1.0;
+                    self.draw_arrow(
+                        8,
+                        LIGHT_COLOR,
+                        length,
+                        0.05,
+                        Matrix4::new_translation(&position)
+                            * graph.global_rotation(handle).to_homogeneous()
+                            * Matrix4::new_rotation(Vector3::new(std::f32::consts::PI, 0.0, 0.0))
+                            * Matrix4::new_translation(&Vector3::new(0.0, length * 0.5, 0.0)),
+                    );
+                }
+            }
+
+            if flags.contains(DebugDrawFlags::SOUND_SOURCES) {
+                if let Some(sound) = node.query_component_ref::<Sound>() {
+                    let position = node.global_position();
+                    self.draw_wire_sphere(position, sound.radius(), 24, SOUND_RADIUS_COLOR);
+                    // Max distance is practically infinite by default.
+                    if sound.max_distance() < f32::MAX {
+                        self.draw_wire_sphere(
+                            position,
+                            sound.max_distance(),
+                            24,
+                            SOUND_MAX_DISTANCE_COLOR,
+                        );
+                    }
+                }
+            }
+
+            if flags.contains(DebugDrawFlags::SKELETONS) {
+                if let Some(mesh) = node.query_component_ref::<Mesh>() {
+                    let bones = mesh
+                        .surfaces()
+                        .iter()
+                        .flat_map(|surface| surface.bones().iter().cloned())
+                        .collect::<FxHashSet<_>>();
+
+                    for &bone in bones.iter() {
+                        if let Some(bone_ref) = graph.try_get(bone) {
+                            let parent = bone_ref.parent();
+                            if bones.contains(&parent) {
+                                self.add_line(Line {
+                                    begin: graph[parent].global_position(),
+                                    end: bone_ref.global_position(),
+                                    color: SKELETON_COLOR,
+                                });
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+
     /// Adds single line into internal buffer.
     pub fn add_line(&mut self, line: Line) {
-        self.lines.push(line);
+        if self.depth_test {
+            self.lines.push(line);
+        } else {
+            self.overlay_lines.push(line);
+        }
     }
 
-    /// Removes all lines from internal buffer. For dynamic drawing you should call it
-    /// every update tick of your application.
+    /// Removes all lines and labels from internal buffer, except persistent ones. For dynamic drawing
+    /// you should call it every update tick of your application.
     pub fn clear_lines(&mut self) {
-        self.lines.clear()
+        self.lines.clear();
+        self.overlay_lines.clear();
+        self.labels.clear();
+    }
+
+    /// Enables or disables depth test for the shapes, that will be added after this call. Shapes without
+    /// depth test are drawn on top of everything. Depth test is enabled by default.
+    pub fn set_depth_test(&mut self, enabled: bool) {
+        self.depth_test = enabled;
+    }
+
+    /// Returns `true` if the shapes, that will be added, are depth-tested.
+    pub fn is_depth_test_enabled(&self) -> bool {
+        self.depth_test
+    }
+
+    /// Adds single line, that will be drawn for the given amount of seconds.
+    pub fn add_line_timed(&mut self, line: Line, seconds: f32) {
+        let timed = Timed {
+            value: line,
+            time_left: seconds,
+        };
+        if self.depth_test {
+            self.timed_lines.push(timed);
+        } else {
+            self.timed_overlay_lines.push(timed);
+        }
+    }
+
+    /// Draws a wire sphere, that will be visible for the given amount of seconds. See
+    /// [`Self::draw_wire_sphere`] for more info.
+    pub fn add_sphere_timed(
+        &mut self,
+        position: Vector3<f32>,
+        radius: f32,
+        segments: usize,
+        color: Color,
+        seconds: f32,
+    ) {
+        self.add_timed_with(seconds, |ctx| {
+            ctx.draw_wire_sphere(position, radius, segments, color)
+        });
+    }
+
+    /// Draws an axis-aligned bounding box, that will be visible for the given amount of seconds.
+    pub fn add_aabb_timed(&mut self, aabb: &AxisAlignedBoundingBox, color: Color, seconds: f32) {
+        self.add_timed_with(seconds, |ctx| ctx.draw_aabb(aabb, color));
+    }
+
+    // Draws a shape using immediate mode methods and moves its lines to the persistent buffer.
+    fn add_timed_with<F>(&mut self, seconds: f32, draw: F)
+    where
+        F: FnOnce(&mut Self),
+    {
+        let first = if self.depth_test {
+            self.lines.len()
+        } else {
+            self.overlay_lines.len()
+        };
+
+        draw(self);
+
+        let (lines, timed_lines) = if self.depth_test {
+            (&mut self.lines, &mut self.timed_lines)
+        } else {
+            (&mut self.overlay_lines, &mut self.timed_overlay_lines)
+        };
+        timed_lines.extend(lines.drain(first..).map(|line| Timed {
+            value: line,
+            time_left: seconds,
+        }));
+    }
+
+    /// Adds a text label at the given position in world space. The label will be removed by
+    /// [`Self::clear_lines`].
+    pub fn add_label<S: AsRef<str>>(&mut self, position: Vector3<f32>, text: S, color: Color) {
+        self.labels.push(DebugLabel {
+            position,
+            text: text.as_ref().to_owned(),
+            color,
+        });
+    }
+
+    /// Adds a text label at the given position in world space, that will be visible for the given
+    /// amount of seconds.
+    pub fn add_label_timed<S: AsRef<str>>(
+        &mut self,
+        position: Vector3<f32>,
+        text: S,
+        color: Color,
+        seconds: f32,
+    ) {
+        self.timed_labels.push(Timed {
+            value: DebugLabel {
+                position,
+                text: text.as_ref().to_owned(),
+                color,
+            },
+            time_left: seconds,
+        });
+    }
+
+    /// Returns an iterator over all lines (including persistent ones) with the given depth test mode.
+    pub fn iter_lines(&self, depth_test: bool) -> impl Iterator<Item = &Line> {
+        let (lines, timed_lines) = if depth_test {
+            (&self.lines, &self.timed_lines)
+        } else {
+            (&self.overlay_lines, &self.timed_overlay_lines)
+        };
+        lines.iter().chain(timed_lines.iter().map(|l| &l.value))
+    }
+
+    /// Returns an iterator over all labels (including persistent ones).
+    pub fn iter_labels(&self) -> impl Iterator<Item = &DebugLabel> {
+        self.labels
+            .iter()
+            .chain(self.timed_labels.iter().map(|l| &l.value))
+    }
+
+    /// Removes all persistent shapes and labels.
+    pub fn clear_timed(&mut self) {
+        self.timed_lines.clear();
+        self.timed_overlay_lines.clear();
+        self.timed_labels.clear();
+    }
+
+    /// Updates lifetimes of persistent shapes and removes expired ones. The scene calls this method
+    /// automatically on every update, there's no need to call it manually.
+    pub fn update(&mut self, dt: f32) {
+        update_timed(&mut self.timed_lines, dt);
+        update_timed(&mut self.timed_overlay_lines, dt);
+        update_timed(&mut self.timed_labels, dt);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::{algebra::Vector3, color::Color, math::aabb::AxisAlignedBoundingBox},
+        scene::debug::{Line, SceneDrawingContext},
+    };
+
+    #[test]
+    fn test_timed_shapes() {
+        let mut ctx = SceneDrawingContext::default();
+        let line = Line {
+            begin: Vector3::default(),
+            end: Vector3::new(1.0, 0.0, 0.0),
+            color: Color::WHITE,
+        };
+
+        ctx.add_line_timed(line.clone(), 1.0);
+        ctx.set_depth_test(false);
+        ctx.add_aabb_timed(&AxisAlignedBoundingBox::from_radius(1.0), Color::RED, 2.0);
+        ctx.add_line(line);
+        ctx.set_depth_test(true);
+        ctx.add_label_timed(Vector3::default(), "Hit", Color::GREEN, 0.5);
+
+        assert_eq!(ctx.iter_lines(true).count(), 1);
+        assert_eq!(ctx.iter_lines(false).count(), 13);
+        assert_eq!(ctx.iter_labels().count(), 1);
+
+        // Persistent shapes are not affected by clearing.
+        ctx.clear_lines();
+        assert_eq!(ctx.iter_lines(true).count(), 1);
+        assert_eq!(ctx.iter_lines(false).count(), 12);
+
+        // Expired shapes are removed on the next update, so they're drawn at least once.
+        let counts = |ctx: &SceneDrawingContext| {
+            (
+                ctx.iter_lines(true).count(),
+                ctx.iter_lines(false).count(),
+                ctx.iter_labels().count(),
+            )
+        };
+        ctx.update(0.6);
+        assert_eq!(counts(&ctx), (1, 12, 1));
+        ctx.update(0.6);
+        assert_eq!(counts(&ctx), (1, 12, 0));
+        ctx.update(0.6);
+        assert_eq!(counts(&ctx), (0, 12, 0));
+        ctx.update(0.6);
+        assert_eq!(counts(&ctx), (0, 12, 0));
+        ctx.update(0.6);
+        assert_eq!(counts(&ctx), (0, 0, 0));
     }
 }
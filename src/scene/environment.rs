@@ -0,0 +1,129 @@
+//! Environment settings of a scene. See [`EnvironmentSettings`] docs for more info.
+
+use crate::{
+    core::{color::Color, reflect::prelude::*, visitor::prelude::*},
+    scene::camera::SkyBox,
+};
+use strum_macros::{AsRefStr, EnumString, EnumVariantNames};
+
+/// Defines how fog density grows with distance from the camera.
+#[derive(Visit, Copy, Clone, PartialEq, Debug, Reflect, AsRefStr, EnumString, EnumVariantNames)]
+pub enum FogKind {
+    /// Fog linearly grows from zero at `start` distance to full density at `end` distance.
+    Linear {
+        /// Distance at which the fog starts.
+        #[reflect(min_value = 0.0, step = 0.1)]
+        start: f32,
+        /// Distance at which the fog reaches full density.
+        #[reflect(min_value = 0.0, step = 0.1)]
+        end: f32,
+    },
+    /// Fog factor is calculated using `1 - exp(-density * distance)` formula.
+    Exponential {
+        /// Density of the fog.
+        #[reflect(min_value = 0.0, step = 0.001)]
+        density: f32,
+    },
+    /// Fog factor is calculated using `1 - exp(-(density * distance)^2)` formula.
+    ExponentialSquared {
+        /// Density of the fog.
+        #[reflect(min_value = 0.0, step = 0.001)]
+        density: f32,
+    },
+}
+
+impl Default for FogKind {
+    fn default() -> Self {
+        Self::Exponential { density: 0.02 }
+    }
+}
+
+/// Height fog makes the fog thinner with altitude, it could be used to create low-lying fog in
+/// valleys and so on.
+#[derive(Visit, Copy, Clone, PartialEq, Debug, Reflect)]
+pub struct HeightFog {
+    /// Whether the height fog is enabled or not. Default is `false`.
+    pub enabled: bool,
+    /// World-space height below which the fog has its full density. Default is `0.0`.
+    pub base_height: f32,
+    /// Defines how fast the fog density decreases above the base height. Default is `0.1`.
+    #[reflect(min_value = 0.0, step = 0.01)]
+    pub falloff: f32,
+}
+
+impl Default for HeightFog {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            base_height: 0.0,
+            falloff: 0.1,
+        }
+    }
+}
+
+/// Distance fog settings.
+#[derive(Visit, Clone, PartialEq, Debug, Reflect)]
+pub struct FogSettings {
+    /// Whether the fog is enabled or not. Default is `false`.
+    pub enabled: bool,
+    /// Color of the fog. Alpha channel defines max opacity of the fog.
+    pub color: Color,
+    /// Defines how fog density grows with distance. See [`FogKind`] docs for more info.
+    pub kind: FogKind,
+    /// Height fog settings. See [`HeightFog`] docs for more info.
+    pub height_fog: HeightFog,
+}
+
+impl Default for FogSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            color: Color::opaque(128, 128, 128),
+            kind: Default::default(),
+            height_fog: Default::default(),
+        }
+    }
+}
+
+/// Environment settings define the atmosphere of a scene - ambient lighting, fog, sky, etc. Every
+/// scene has its own settings, they're applied when rendering cameras of the scene. Any changes
+/// to the settings are applied on the next frame.
+///
+/// # Notes
+///
+/// Fog is applied to opaque (deferred) geometry only, transparent objects and particles are not
+/// affected by it.
+#[derive(Visit, Clone, PartialEq, Debug, Reflect)]
+pub struct EnvironmentSettings {
+    /// Color of ambient lighting.
+    pub ambient_lighting_color: Color,
+
+    /// Distance fog settings. See [`FogSettings`] docs for more info.
+    pub fog: FogSettings,
+
+    /// Skybox that will be used by every camera of the scene that does not have its own skybox.
+    /// Default is `None`.
+    pub skybox: Option<SkyBox>,
+
+    /// Whether light scattering is enabled in the scene or not. Default is `true`. Light scattering
+    /// also must be enabled in the renderer quality settings and on each light source.
+    pub light_scatter_enabled: bool,
+
+    /// Color of the pixels that are not covered by any geometry or skybox. `None` means that the
+    /// clear color of the renderer is used, see
+    /// [`crate::renderer::Renderer::set_backbuffer_clear_color`]. Default is `None`.
+    #[visit(optional)]
+    pub clear_color: Option<Color>,
+}
+
+impl Default for EnvironmentSettings {
+    fn default() -> Self {
+        Self {
+            ambient_lighting_color: Color::opaque(100, 100, 100),
+            fog: Default::default(),
+            skybox: None,
+            light_scatter_enabled: true,
+            clear_color: None,
+        }
+    }
+}
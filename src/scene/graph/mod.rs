@@ -25,7 +25,9 @@
 use crate::{
     asset::ResourceStateRef,
     core::{
-        algebra::{Matrix4, Rotation3, UnitQuaternion, Vector2, Vector3},
+        algebra::{
+            Isometry3, Matrix4, Point3, Rotation3, Translation3, UnitQuaternion, Vector2, Vector3,
+        },
         instant,
         log::{Log, MessageKind},
         math::Matrix4Ext,
@@ -38,28 +40,30 @@ use crate::{
     resource::model::{ModelResource, ModelResourceExtension, NodeMapping},
     scene::{
         self,
-        base::NodeScriptMessage,
+        base::{BaseBuilder, BoneAttachment, NodeScriptMessage},
         camera::Camera,
         dim2::{self},
         graph::{
             event::{GraphEvent, GraphEventBroadcaster},
             map::NodeHandleMap,
-            physics::{PhysicsPerformanceStatistics, PhysicsWorld},
+            physics::{PhysicsPerformanceStatistics, PhysicsWorld, RayCastOptions},
         },
+        joint::{JointBuilder, JointDesc, JointHandle},
         mesh::Mesh,
         node::{container::NodeContainer, Node, NodeTrait, SyncContext, UpdateContext},
         pivot::Pivot,
-        sound::context::SoundContext,
-        transform::TransformBuilder,
+        sound::{context::SoundContext, occlusion, Sound, Status},
+        transform::{Transform, TransformBuilder},
     },
     script::ScriptTrait,
+    utils::component::ComponentProvider,
 };
 use fxhash::FxHashSet;
 use fyrox_core::math::aabb::AxisAlignedBoundingBox;
 use rapier3d::geometry::ColliderHandle;
 use std::{
-    any::Any,
-    fmt::Debug,
+    any::{Any, TypeId},
+    fmt::{Debug, Display, Formatter},
     ops::{Index, IndexMut},
     sync::mpsc::{channel, Receiver, Sender},
     time::Duration,
@@ -243,6 +247,65 @@ impl Default for GraphUpdateSwitches {
     }
 }
 
+/// An error, that may occur during attachment of a node to a bone, see [`Graph::attach_to_bone`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AttachError {
+    /// A node, that should be attached, does not exist.
+    InvalidItem(Handle<Node>),
+    /// A root node of a model instance does not exist.
+    InvalidModelRoot(Handle<Node>),
+    /// There is no bone with the given name in the model instance.
+    BoneNotFound {
+        /// A name of the bone.
+        bone_name: String,
+        /// Names of the nodes of the model instance, that are similar to the name of the bone.
+        candidates: Vec<String>,
+    },
+}
+
+impl Display for AttachError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AttachError::InvalidItem(handle) => {
+                write!(f, "Node {handle} to attach does not exist.")
+            }
+            AttachError::InvalidModelRoot(handle) => {
+                write!(f, "Model root {handle} does not exist.")
+            }
+            AttachError::BoneNotFound {
+                bone_name,
+                candidates,
+            } => {
+                if candidates.is_empty() {
+                    write!(f, "Bone \"{bone_name}\" was not found.")
+                } else {
+                    write!(
+                        f,
+                        "Bone \"{bone_name}\" was not found. Similar names: {}.",
+                        candidates.join(", ")
+                    )
+                }
+            }
+        }
+    }
+}
+
+// Calculates the amount of single-character edits that is needed to transform one string to another.
+fn levenshtein_distance(a: &str, b: &str) -> usize {
+    let b = b.chars().collect::<Vec<_>>();
+    let mut prev = (0..=b.len()).collect::<Vec<_>>();
+    let mut cur = vec![0; b.len() + 1];
+    for (i, ca) in a.chars().enumerate() {
+        cur[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = prev[j] + if ca == *cb { 0 } else { 1 };
+            cur[j + 1] = substitution.min(prev[j + 1] + 1).min(cur[j] + 1);
+        }
+        std::mem::swap(&mut prev, &mut cur);
+    }
+    prev[b.len()]
+}
+
 impl Graph {
     /// Creates new graph instance with single root node.
     #[inline]
@@ -343,6 +406,40 @@ impl Graph {
         handle
     }
 
+    /// Creates a joint between two rigid bodies using the given typed parameters (see [`JointDesc`])
+    /// and adds it to the graph. Anchors and axes of the descriptor are defined in local coordinates
+    /// of the bodies, they're converted to explicit local frames of the joint (see
+    /// [`JointLocalFrames`]), so the joint configuration is saved and loaded exactly. Current global
+    /// transforms of the bodies are used to match the axes of the frames, make sure that they're
+    /// up-to-date (see [`Self::update_hierarchical_data`]).
+    ///
+    /// [`JointLocalFrames`]: crate::scene::joint::JointLocalFrames
+    pub fn add_joint<D: Into<JointDesc>>(
+        &mut self,
+        body1: Handle<Node>,
+        body2: Handle<Node>,
+        desc: D,
+    ) -> JointHandle {
+        let pose = |node: Handle<Node>| {
+            let (rotation, position) = self.global_rotation_position_no_scale(node);
+            Isometry3::from_parts(Translation3::from(position), rotation)
+        };
+        let (params, local_frames) = desc.into().into_params_and_frames(pose(body1), pose(body2));
+
+        JointBuilder::new(
+            BaseBuilder::new().with_local_transform(
+                TransformBuilder::new()
+                    .with_local_position(self[body1].global_position())
+                    .build(),
+            ),
+        )
+        .with_params(params)
+        .with_body1(body1)
+        .with_body2(body2)
+        .with_local_frames(local_frames)
+        .build(self)
+    }
+
     /// Tries to find references of the given node in other scene nodes. It could be used to check if the node is
     /// used by some other scene node or not. Returns an array of nodes, that references the given node. This method
     /// is reflection-based, so it is quite slow and should not be used every frame.
@@ -550,6 +647,167 @@ impl Graph {
         self.link_nodes(child, parent);
     }
 
+    /// Attaches the `item` to a bone with the given name, that is searched in the hierarchy of the
+    /// `model_root` (usually a root of an instance of a rigged model). The item becomes a child of
+    /// the bone and its local transform is replaced with the `offset`. The attachment is stored in
+    /// the item (see [`Base::bone_attachment`](crate::scene::base::Base::bone_attachment)) and it is
+    /// re-established when the graph is resolved (for example, after loading a scene or reloading the
+    /// model resource), even if handle of the bone has changed. Use [`Self::detach_from_bone`] to
+    /// remove the attachment.
+    ///
+    /// The error contains names of nodes with similar names if the bone cannot be found.
+    ///
+    /// ```no_run
+    /// # use fyrox::{
+    /// #     core::pool::Handle,
+    /// #     scene::{graph::{AttachError, Graph}, node::Node, transform::Transform},
+    /// # };
+    /// fn attach_sword(
+    ///     graph: &mut Graph,
+    ///     sword: Handle<Node>,
+    ///     character: Handle<Node>,
+    /// ) -> Result<(), AttachError> {
+    ///     graph.attach_to_bone(sword, character, "mixamorig:RightHand", Transform::identity())
+    /// }
+    /// ```
+    pub fn attach_to_bone(
+        &mut self,
+        item: Handle<Node>,
+        model_root: Handle<Node>,
+        bone_name: &str,
+        offset: Transform,
+    ) -> Result<(), AttachError> {
+        let bone = self.find_bone(item, model_root, bone_name)?;
+        self.link_nodes(item, bone);
+        let node = &mut self.pool[item];
+        *node.local_transform_mut() = offset.clone();
+        node.bone_attachment = Some(BoneAttachment {
+            model_root,
+            bone_name: bone_name.to_owned(),
+            offset,
+        });
+        Ok(())
+    }
+
+    /// Does the same as [`Self::attach_to_bone`], but keeps global position and rotation of the
+    /// `item`. Resulting local transform of the item is used as the offset of the attachment.
+    pub fn attach_to_bone_keep_global_position_rotation(
+        &mut self,
+        item: Handle<Node>,
+        model_root: Handle<Node>,
+        bone_name: &str,
+    ) -> Result<(), AttachError> {
+        let bone = self.find_bone(item, model_root, bone_name)?;
+        self.link_nodes_keep_global_position_rotation(item, bone);
+        let node = &mut self.pool[item];
+        node.bone_attachment = Some(BoneAttachment {
+            model_root,
+            bone_name: bone_name.to_owned(),
+            offset: node.local_transform.clone(),
+        });
+        Ok(())
+    }
+
+    /// Removes attachment of the `item` to a bone (if any) and links the item to the root of the graph
+    /// while keeping its global position and rotation. Returns the removed attachment.
+    pub fn detach_from_bone(&mut self, item: Handle<Node>) -> Option<BoneAttachment> {
+        let attachment = self.pool.try_borrow_mut(item)?.bone_attachment.take();
+        if attachment.is_some() {
+            self.link_nodes_keep_global_position_rotation(item, self.root);
+        }
+        attachment
+    }
+
+    fn find_bone(
+        &self,
+        item: Handle<Node>,
+        model_root: Handle<Node>,
+        bone_name: &str,
+    ) -> Result<Handle<Node>, AttachError> {
+        if !self.is_valid_handle(item) {
+            return Err(AttachError::InvalidItem(item));
+        }
+        if !self.is_valid_handle(model_root) {
+            return Err(AttachError::InvalidModelRoot(model_root));
+        }
+
+        // Descendants of the item are skipped, the item cannot be attached to its own descendant.
+        let mut stack = vec![model_root];
+        let mut names = Vec::new();
+        while let Some(handle) = stack.pop() {
+            if handle == item {
+                continue;
+            }
+            let node = &self.pool[handle];
+            if node.name() == bone_name {
+                return Ok(handle);
+            }
+            names.push(node.name());
+            stack.extend(node.children().iter().rev());
+        }
+
+        let lowercase_bone_name = bone_name.to_lowercase();
+        let max_distance = (bone_name.chars().count() / 3).max(2);
+        let mut candidates = names
+            .into_iter()
+            .filter_map(|name| {
+                let lowercase_name = name.to_lowercase();
+                let distance = levenshtein_distance(&lowercase_name, &lowercase_bone_name);
+                if distance <= max_distance
+                    || (!lowercase_name.is_empty()
+                        && (lowercase_name.contains(&lowercase_bone_name)
+                            || lowercase_bone_name.contains(&lowercase_name)))
+                {
+                    Some((distance, name))
+                } else {
+                    None
+                }
+            })
+            .collect::<Vec<_>>();
+        candidates.sort();
+        candidates.dedup();
+
+        Err(AttachError::BoneNotFound {
+            bone_name: bone_name.to_owned(),
+            candidates: candidates
+                .into_iter()
+                .take(5)
+                .map(|(_, name)| name.to_owned())
+                .collect(),
+        })
+    }
+
+    // Links attached nodes with their bones again, handles of the bones could change after resolve.
+    fn restore_bone_attachments(&mut self) {
+        let attachments = self
+            .pool
+            .pair_iter()
+            .filter_map(|(handle, node)| node.bone_attachment.clone().map(|a| (handle, a)))
+            .collect::<Vec<_>>();
+
+        for (item, attachment) in attachments {
+            match self.find_bone(item, attachment.model_root, &attachment.bone_name) {
+                Ok(bone) => {
+                    if self.pool[item].parent() != bone {
+                        self.link_nodes(item, bone);
+                        *self.pool[item].local_transform_mut() = attachment.offset;
+
+                        Log::info(format!(
+                            "Node {} was attached to bone {} again.",
+                            self.pool[item].name(),
+                            attachment.bone_name
+                        ));
+                    }
+                }
+                Err(err) => Log::warn(format!(
+                    "Unable to restore bone attachment of node {}. Reason: {}",
+                    self.pool[item].name(),
+                    err
+                )),
+            }
+        }
+    }
+
     /// Unlinks specified node from its parent and attaches it to root graph node.
     #[inline]
     pub fn unlink_node(&mut self, node_handle: Handle<Node>) {
@@ -1084,6 +1342,7 @@ impl Graph {
         self.update_hierarchical_data();
         let instances = self.restore_integrity();
         self.remap_handles(&instances);
+        self.restore_bone_attachments();
 
         // Update cube maps for sky boxes.
         for node in self.linear_iter_mut() {
@@ -1218,8 +1477,16 @@ impl Graph {
             switches: Some(switches),
         };
 
-        for (handle, node) in self.pool.pair_iter() {
-            node.sync_native(handle, &mut sync_context);
+        // Native objects of some nodes depend on native objects of their parents (for example,
+        // a collider can be created only when its parent rigid body is created), so parents must be
+        // synced first. Otherwise such nodes would be created one frame later, and the first physics
+        // step would be performed without them.
+        let mut stack = vec![self.root];
+        while let Some(handle) = stack.pop() {
+            if let Some(node) = self.pool.try_borrow(handle) {
+                node.sync_native(handle, &mut sync_context);
+                stack.extend_from_slice(node.children());
+            }
         }
     }
 
@@ -1297,6 +1564,8 @@ impl Graph {
             self.performance_statistics.physics2d = self.physics2d.performance_statistics.clone();
         }
 
+        self.update_sound_occlusion(dt);
+
         self.performance_statistics.sound_update_time =
             self.sound_context.state().full_render_duration();
 
@@ -1316,6 +1585,77 @@ impl Graph {
         }
     }
 
+    fn update_sound_occlusion(&mut self, dt: f32) {
+        // Sounds that must be checked for occlusion and sounds that are fading back to the open
+        // state after their occlusion was disabled.
+        let mut occluded = Vec::new();
+        let mut fading = Vec::new();
+        for (handle, node) in self.pool.pair_iter() {
+            if let Some(sound) = node.cast::<Sound>() {
+                if sound.occlusion().enabled && sound.status() == Status::Playing {
+                    occluded.push(handle);
+                } else if sound.occlusion_level > 0.0 {
+                    fading.push(handle);
+                }
+            }
+        }
+
+        if occluded.is_empty() && fading.is_empty() {
+            return;
+        }
+
+        let listener_position = self.sound_context.native.state().listener().position();
+        let rays_per_frame = self.sound_context.occlusion_rays_per_frame();
+        let mut intersections = Vec::new();
+        for index in self
+            .sound_context
+            .occlusion_scheduler
+            .select(occluded.len(), rays_per_frame)
+        {
+            let sound = self.pool[occluded[index]].cast::<Sound>().unwrap();
+            let direction = sound.global_position() - listener_position;
+            let distance = direction.norm();
+            let mut blocked = false;
+            if distance > f32::EPSILON {
+                self.physics.cast_ray(
+                    RayCastOptions {
+                        ray_origin: Point3::from(listener_position),
+                        ray_direction: direction,
+                        max_len: distance,
+                        groups: sound.occlusion().blockers,
+                        sort_results: false,
+                    },
+                    &mut intersections,
+                );
+                // Colliders that contain the listener are reported at zero distance, ignore them.
+                blocked = intersections.iter().any(|i| i.toi > f32::EPSILON);
+            }
+            self.pool[occluded[index]]
+                .cast_mut::<Sound>()
+                .unwrap()
+                .occlusion_target = if blocked { 1.0 } else { 0.0 };
+        }
+
+        let mut state = self.sound_context.native.state();
+        for handle in occluded.into_iter().chain(fading) {
+            let sound = self.pool[handle].cast_mut::<Sound>().unwrap();
+            if !sound.occlusion().enabled {
+                sound.occlusion_target = 0.0;
+            }
+            sound.occlusion_level = occlusion::smooth_occlusion(
+                sound.occlusion_level,
+                sound.occlusion_target,
+                dt,
+                sound.occlusion().smoothing_time,
+            );
+            if let Some(source) = state.try_get_source_mut(sound.native.get()) {
+                source
+                    .set_occlusion_gain(sound.occlusion().gain(sound.occlusion_level))
+                    .set_lowpass_cutoff(sound.occlusion().normalized_cutoff(sound.occlusion_level));
+            }
+        }
+    }
+
     /// Returns capacity of internal pool. Can be used to iterate over all **potentially**
     /// available indices and try to convert them to handles.
     ///
@@ -1388,6 +1728,48 @@ impl Graph {
         self.pool.pair_iter_mut()
     }
 
+    /// Creates new iterator over all nodes that have a component of the given type, giving
+    /// (handle; component) pairs. Components are fetched using [`ComponentProvider`] implementation
+    /// of [`Node`], so the iterator yields components of nodes (for example - [`Camera`], or
+    /// [`crate::scene::light::BaseLight`] of every light source) as well as components of their
+    /// scripts. It does *not* perform any tree traversal!
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use fyrox::scene::{graph::Graph, light::BaseLight};
+    /// fn enable_shadows(graph: &mut Graph) {
+    ///     for (_, light) in graph.typed_iter_mut::<BaseLight>() {
+    ///         light.set_cast_shadows(true);
+    ///     }
+    /// }
+    /// ```
+    #[inline]
+    pub fn typed_iter<T>(&self) -> impl Iterator<Item = (Handle<Node>, &T)>
+    where
+        T: 'static,
+    {
+        self.pool.pair_iter().filter_map(|(handle, node)| {
+            ComponentProvider::query_component_ref(node, TypeId::of::<T>())
+                .and_then(|c| c.downcast_ref::<T>())
+                .map(|c| (handle, c))
+        })
+    }
+
+    /// Creates new iterator over all nodes that have a component of the given type, giving
+    /// (handle; component) pairs. See [`Self::typed_iter`] for more info.
+    #[inline]
+    pub fn typed_iter_mut<T>(&mut self) -> impl Iterator<Item = (Handle<Node>, &mut T)>
+    where
+        T: 'static,
+    {
+        self.pool.pair_iter_mut().filter_map(|(handle, node)| {
+            ComponentProvider::query_component_mut(node, TypeId::of::<T>())
+                .and_then(|c| c.downcast_mut::<T>())
+                .map(|c| (handle, c))
+        })
+    }
+
     /// Extracts node from graph and reserves its handle. It is used to temporarily take
     /// ownership over node, and then put node back using given ticket. Extracted node is
     /// detached from its parent!
@@ -1619,6 +2001,90 @@ impl Graph {
         Vector3::new(m[0], m[5], m[10])
     }
 
+    /// Calculates world transformation matrix of a node using local transforms of the node and its
+    /// ancestors. Unlike [`crate::scene::base::Base::global_transform`], the result is always up to date, it does not
+    /// require the graph to be updated.
+    fn calculate_global_transform(&self, node: Handle<Node>) -> Matrix4<f32> {
+        let mut transform = Matrix4::identity();
+        let mut current = node;
+        while let Some(node) = self.pool.try_borrow(current) {
+            transform = node.local_transform().matrix() * transform;
+            current = node.parent();
+        }
+        transform
+    }
+
+    /// Sets position of a node in world coordinates. Local position of the node is calculated using
+    /// current global transform of its parent, so the node will be at the given position even if
+    /// its ancestors are rotated or have non-uniform scale. Nothing happens if the parent has
+    /// degenerate transform (zero scale). Just like
+    /// [`crate::scene::transform::Transform::set_position`], the method does not change global
+    /// transform of the node immediately, it will be updated on the next update of the graph.
+    #[inline]
+    pub fn set_global_position(&mut self, node: Handle<Node>, position: Vector3<f32>) {
+        let parent_inv = match self
+            .calculate_global_transform(self[node].parent())
+            .try_inverse()
+        {
+            Some(parent_inv) => parent_inv,
+            None => return,
+        };
+        let local_position = parent_inv.transform_point(&Point3::from(position)).coords;
+
+        // Position of the origin of the node in the parent space also depends on offsets and
+        // pivots of its transform, but it is linear to the local position.
+        let transform = self[node].local_transform_mut();
+        let offset = local_position - transform.matrix().position();
+        let new_position = **transform.position() + offset;
+        transform.set_position(new_position);
+    }
+
+    /// Sets rotation of a node in world coordinates, so [`Self::global_rotation`] of the node will
+    /// return the given rotation. Just like [`Self::global_rotation`], scale of the ancestors is
+    /// ignored: the rotation is exact if the ancestors have uniform scale, but if some of them
+    /// have non-uniform scale, the axes of the node will be skewed by the scale anyway and they
+    /// will only approximately match the rotation. Just like
+    /// [`crate::scene::transform::Transform::set_rotation`], the method does not change global
+    /// transform of the node immediately, it will be updated on the next update of the graph.
+    #[inline]
+    pub fn set_global_rotation(&mut self, node: Handle<Node>, rotation: UnitQuaternion<f32>) {
+        let parent = self[node].parent();
+        let parent_rotation = if self.is_valid_handle(parent) {
+            self.global_rotation(parent)
+        } else {
+            UnitQuaternion::identity()
+        };
+
+        let transform = self[node].local_transform_mut();
+        let local_rotation = transform.pre_rotation().inverse()
+            * parent_rotation.inverse()
+            * rotation
+            * transform.post_rotation().inverse();
+        transform.set_rotation(local_rotation);
+    }
+
+    /// Rotates a node, so its look vector (local Z axis) points at the given target in world
+    /// coordinates and its up vector (local Y axis) is as close to the given up direction as
+    /// possible. If the up direction is parallel to the direction to the target, the world X or Z
+    /// axis is used as the up direction instead. Nothing happens if the target matches the
+    /// position of the node. See [`Self::set_global_rotation`] for more info about ancestors with
+    /// non-uniform scale.
+    #[inline]
+    pub fn look_at(&mut self, node: Handle<Node>, target: Vector3<f32>, up: Vector3<f32>) {
+        let position = self.calculate_global_transform(node).position();
+        let direction = match (target - position).try_normalize(f32::EPSILON) {
+            Some(direction) => direction,
+            None => return,
+        };
+
+        let up = [up, Vector3::x(), Vector3::z()]
+            .into_iter()
+            .find(|up| up.cross(&direction).norm() > 1.0e-4 * up.norm())
+            .unwrap_or_else(Vector3::y);
+
+        self.set_global_rotation(node, UnitQuaternion::face_towards(&direction, &up));
+    }
+
     /// Tries to borrow a node using the given handle, fetch its script and cast it to the specified type.
     #[inline]
     pub fn try_get_script_of<T>(&self, node: Handle<Node>) -> Option<&T>
@@ -1788,9 +2254,51 @@ mod test {
     use crate::scene::base::BaseBuilder;
     use crate::scene::pivot::PivotBuilder;
     use crate::{
-        core::pool::Handle,
-        scene::{graph::Graph, node::Node, pivot::Pivot},
+        animation::{
+            container::{TrackDataContainer, TrackValueKind},
+            track::Track,
+            value::ValueBinding,
+            Animation, AnimationContainer,
+        },
+        asset::manager::ResourceManager,
+        core::{
+            algebra::{Matrix4, UnitQuaternion, Vector2, Vector3},
+            curve::{Curve, CurveKey, CurveKeyKind},
+            futures::executor::block_on,
+            pool::Handle,
+            reflect::prelude::*,
+            uuid::Uuid,
+            variable::InheritableVariable,
+            visitor::prelude::*,
+        },
+        engine::{self, SerializationContext},
+        impl_component_provider,
+        resource::model::{Model, ModelResourceExtension},
+        scene::{
+            animation::AnimationPlayerBuilder,
+            camera::{Camera, CameraBuilder},
+            collider::{Collider, ColliderBuilder},
+            graph::{AttachError, Graph},
+            light::{
+                directional::DirectionalLightBuilder,
+                point::PointLightBuilder,
+                spot::{SpotLight, SpotLightBuilder},
+                BaseLight, BaseLightBuilder,
+            },
+            mesh::{
+                surface::{Surface, SurfaceBuilder, SurfaceData, SurfaceSharedData},
+                Mesh, MeshBuilder,
+            },
+            node::Node,
+            pivot::Pivot,
+            rigidbody::{RigidBody, RigidBodyBuilder},
+            transform::TransformBuilder,
+            Scene, SceneLoader,
+        },
+        script::{Script, ScriptTrait},
     };
+    use rapier3d::{dynamics::RigidBodyHandle, geometry::ColliderHandle};
+    use std::{fs, path::Path, sync::Arc};
 
     #[test]
     fn graph_init_test() {
@@ -1918,4 +2426,393 @@ mod test {
 
         assert!(graph[b].children.is_empty());
     }
+
+    #[test]
+    fn test_disabled_subtree() {
+        let mut graph = Graph::new();
+
+        let child = PivotBuilder::new(BaseBuilder::new()).build(&mut graph);
+        let parent =
+            PivotBuilder::new(BaseBuilder::new().with_children(&[child])).build(&mut graph);
+
+        let mut container = TrackDataContainer::new(TrackValueKind::Vector3);
+        container.curves_mut()[0] = Curve::from(vec![
+            CurveKey::new(0.0, 0.0, CurveKeyKind::Linear),
+            CurveKey::new(10.0, 10.0, CurveKeyKind::Linear),
+        ]);
+        let mut track = Track::new(container, ValueBinding::Position);
+        track.set_target(child);
+        let mut animation = Animation::default();
+        animation.add_track(track);
+        animation.set_time_slice(0.0..10.0);
+        let mut animations = AnimationContainer::new();
+        animations.add(animation);
+        AnimationPlayerBuilder::new(BaseBuilder::new())
+            .with_animations(animations)
+            .build(&mut graph);
+
+        graph[parent].set_enabled(false);
+        graph.update(Vector2::new(100.0, 100.0), 1.0, Default::default());
+        assert!(!graph[child].is_globally_enabled());
+        // The animation keeps playing, but its values are not applied to disabled nodes.
+        assert_eq!(
+            **graph[child].local_transform().position(),
+            Vector3::default()
+        );
+
+        graph[parent].set_enabled(true);
+        graph.update(Vector2::new(100.0, 100.0), 1.0, Default::default());
+        assert!(graph[child].is_globally_enabled());
+        // An animation samples its pose before advancing its time, so the pose applied on the
+        // second frame is the one at 1.0s. It is not 0.0, because the animation kept playing
+        // while the node was disabled.
+        assert_eq!(
+            **graph[child].local_transform().position(),
+            Vector3::new(1.0, 0.0, 0.0)
+        );
+    }
+
+    #[test]
+    fn test_native_objects_are_synced_parents_first() {
+        let mut graph = Graph::new();
+
+        // The collider is added to the pool before its rigid body, so it must not be synced
+        // before the body.
+        let collider = ColliderBuilder::new(BaseBuilder::new()).build(&mut graph);
+        let body =
+            RigidBodyBuilder::new(BaseBuilder::new().with_children(&[collider])).build(&mut graph);
+        assert!(collider.index() < body.index());
+
+        graph.update(Vector2::new(100.0, 100.0), 1.0 / 60.0, Default::default());
+
+        // Both native objects must exist right after the first update, so the first physics step
+        // takes the collider into account.
+        let collider = graph[collider].query_component_ref::<Collider>().unwrap();
+        assert_ne!(collider.native.get(), ColliderHandle::invalid());
+        let body = graph[body].query_component_ref::<RigidBody>().unwrap();
+        assert_ne!(body.native.get(), RigidBodyHandle::invalid());
+    }
+
+    #[derive(Reflect, Visit, Debug, Clone, Default, PartialEq)]
+    struct Health {
+        value: f32,
+    }
+
+    #[derive(Reflect, Visit, Debug, Clone, Default)]
+    struct HealthScript {
+        health: Health,
+    }
+
+    impl_component_provider!(HealthScript, health: Health);
+
+    impl ScriptTrait for HealthScript {
+        fn id(&self) -> Uuid {
+            Uuid::default()
+        }
+    }
+
+    #[test]
+    fn test_typed_iter() {
+        let mut graph = Graph::new();
+
+        for i in 0..3 {
+            CameraBuilder::new(BaseBuilder::new()).build(&mut graph);
+            PointLightBuilder::new(BaseLightBuilder::new(BaseBuilder::new())).build(&mut graph);
+            PivotBuilder::new(BaseBuilder::new()).build(&mut graph);
+            if i < 2 {
+                SpotLightBuilder::new(BaseLightBuilder::new(BaseBuilder::new())).build(&mut graph);
+                DirectionalLightBuilder::new(BaseLightBuilder::new(BaseBuilder::new()))
+                    .build(&mut graph);
+            }
+        }
+        MeshBuilder::new(BaseBuilder::new()).build(&mut graph);
+        let scripted =
+            PivotBuilder::new(BaseBuilder::new().with_script(Script::new(HealthScript {
+                health: Health { value: 10.0 },
+            })))
+            .build(&mut graph);
+
+        let cameras = graph.linear_iter().filter(|n| n.is_camera()).count();
+        let lights = graph
+            .linear_iter()
+            .filter(|n| n.is_point_light() || n.is_spot_light() || n.is_directional_light())
+            .count();
+        assert_eq!(cameras, 3);
+        assert_eq!(lights, 7);
+
+        assert_eq!(graph.typed_iter::<Camera>().count(), cameras);
+        assert_eq!(graph.typed_iter::<BaseLight>().count(), lights);
+        assert_eq!(graph.typed_iter::<SpotLight>().count(), 2);
+        assert_eq!(graph.typed_iter::<Mesh>().count(), 1);
+        assert_eq!(
+            graph
+                .typed_iter::<InheritableVariable<Vec<Surface>>>()
+                .count(),
+            1
+        );
+        assert!(graph
+            .typed_iter::<BaseLight>()
+            .all(|(handle, _)| graph[handle].query_component_ref::<BaseLight>().is_some()));
+
+        for (_, light) in graph.typed_iter_mut::<BaseLight>() {
+            light.set_cast_shadows(false);
+        }
+        assert!(graph
+            .typed_iter::<BaseLight>()
+            .all(|(_, light)| !light.is_cast_shadows()));
+
+        // Components of scripts are visible too.
+        for (_, health) in graph.typed_iter_mut::<Health>() {
+            health.value += 5.0;
+        }
+        assert_eq!(
+            graph.typed_iter::<Health>().collect::<Vec<_>>(),
+            vec![(scripted, &Health { value: 15.0 })]
+        );
+    }
+
+    #[test]
+    fn test_world_space_setters() {
+        let mut graph = Graph::new();
+
+        // Rotated, translated and non-uniformly scaled parent of a rotated and uniformly scaled
+        // parent.
+        let grand_parent = PivotBuilder::new(
+            BaseBuilder::new().with_local_transform(
+                TransformBuilder::new()
+                    .with_local_position(Vector3::new(1.0, 2.0, 3.0))
+                    .with_local_rotation(UnitQuaternion::from_euler_angles(0.3, 0.7, -0.2))
+                    .with_local_scale(Vector3::new(2.0, 0.5, 1.5))
+                    .build(),
+            ),
+        )
+        .build(&mut graph);
+        let parent = PivotBuilder::new(
+            BaseBuilder::new().with_local_transform(
+                TransformBuilder::new()
+                    .with_local_position(Vector3::new(-1.0, 0.5, 2.0))
+                    .with_local_rotation(UnitQuaternion::from_euler_angles(-0.4, 0.1, 1.1))
+                    .with_local_scale(Vector3::repeat(3.0))
+                    .build(),
+            ),
+        )
+        .build(&mut graph);
+        let child = PivotBuilder::new(
+            BaseBuilder::new().with_local_transform(
+                TransformBuilder::new()
+                    .with_local_rotation(UnitQuaternion::from_euler_angles(0.5, 0.5, 0.5))
+                    .with_rotation_offset(Vector3::new(0.1, 0.2, 0.3))
+                    .build(),
+            ),
+        )
+        .build(&mut graph);
+        graph.link_nodes(parent, grand_parent);
+        graph.link_nodes(child, parent);
+        graph.update_hierarchical_data();
+
+        let position = Vector3::new(4.0, -5.0, 6.0);
+        graph.set_global_position(child, position);
+        // Global transform is updated only on the next update.
+        assert!(graph[child].global_position().metric_distance(&position) > 1.0e-3);
+        graph.update_hierarchical_data();
+        assert!(graph[child].global_position().metric_distance(&position) < 1.0e-4);
+
+        // Scale of the ancestors is ignored by global rotation.
+        let rotation = UnitQuaternion::from_euler_angles(1.0, -0.3, 0.2);
+        graph.set_global_rotation(parent, rotation);
+        graph.update_hierarchical_data();
+        assert!(graph.global_rotation(parent).angle_to(&rotation) < 1.0e-3);
+
+        // Uniform scale of the ancestors only, the axes must match the rotation exactly.
+        graph.unlink_node(parent);
+        graph.update_hierarchical_data();
+        let position = graph[child].global_position();
+        graph.set_global_rotation(child, rotation);
+        graph.update_hierarchical_data();
+        assert!(graph.global_rotation(child).angle_to(&rotation) < 1.0e-3);
+        let look = graph[child].look_vector().normalize();
+        assert!(look.metric_distance(&(rotation * Vector3::z())) < 1.0e-4);
+        // Rotation does not move the node.
+        assert!(graph[child].global_position().metric_distance(&position) < 1.0e-4);
+
+        let target = Vector3::new(-3.0, 1.0, 2.0);
+        graph.look_at(child, target, Vector3::y());
+        graph.update_hierarchical_data();
+        let expected_look = (target - graph[child].global_position()).normalize();
+        let look = graph[child].look_vector().normalize();
+        assert!(look.metric_distance(&expected_look) < 1.0e-4);
+        assert!(graph[child].side_vector().dot(&Vector3::y()).abs() < 1.0e-4);
+        assert!(graph[child].up_vector().dot(&Vector3::y()) > 0.0);
+
+        // Up direction is parallel to the direction to the target.
+        let target = graph[child].global_position() + Vector3::new(0.0, 2.0, 0.0);
+        graph.look_at(child, target, Vector3::y());
+        graph.update_hierarchical_data();
+        let look = graph[child].look_vector().normalize();
+        assert!(look.metric_distance(&Vector3::y()) < 1.0e-4);
+    }
+
+    fn create_rig() -> Scene {
+        let mut scene = Scene::new();
+
+        let position = |x, y, z| {
+            TransformBuilder::new()
+                .with_local_position(Vector3::new(x, y, z))
+                .build()
+        };
+
+        let hand = PivotBuilder::new(
+            BaseBuilder::new()
+                .with_name("RightHand")
+                .with_local_transform(position(0.5, 0.0, 0.0)),
+        )
+        .build(&mut scene.graph);
+        let arm = PivotBuilder::new(
+            BaseBuilder::new()
+                .with_name("RightArm")
+                .with_local_transform(position(0.5, 0.0, 0.0))
+                .with_children(&[hand]),
+        )
+        .build(&mut scene.graph);
+        let spine = PivotBuilder::new(
+            BaseBuilder::new()
+                .with_name("Spine")
+                .with_local_transform(position(0.0, 1.0, 0.0))
+                .with_children(&[arm]),
+        )
+        .build(&mut scene.graph);
+        let body = MeshBuilder::new(BaseBuilder::new().with_name("Body"))
+            .with_surfaces(vec![SurfaceBuilder::new(SurfaceSharedData::new(
+                SurfaceData::make_cube(Matrix4::identity()),
+            ))
+            .with_bones(vec![spine, arm, hand])
+            .build()])
+            .build(&mut scene.graph);
+        PivotBuilder::new(
+            BaseBuilder::new()
+                .with_name("Character")
+                .with_children(&[spine, body]),
+        )
+        .build(&mut scene.graph);
+
+        scene
+    }
+
+    fn save_scene(scene: &mut Scene, path: &Path) {
+        let mut visitor = Visitor::new();
+        scene.save("Scene", &mut visitor).unwrap();
+        visitor.save_binary(path).unwrap();
+    }
+
+    #[test]
+    fn test_attach_to_bone() {
+        if !Path::new("test_output").exists() {
+            fs::create_dir_all("test_output").unwrap();
+        }
+
+        let rig_path = Path::new("test_output/rig.rgs");
+        let scene_path = Path::new("test_output/rig_instance.rgs");
+
+        save_scene(&mut create_rig(), rig_path);
+
+        let resource_manager = ResourceManager::new();
+        let serialization_context = Arc::new(SerializationContext::new());
+        engine::initialize_resource_manager_loaders(
+            &resource_manager,
+            serialization_context.clone(),
+        );
+
+        let rig = block_on(resource_manager.request::<Model, _>(rig_path)).unwrap();
+
+        let offset = TransformBuilder::new()
+            .with_local_position(Vector3::new(0.0, 0.25, 0.0))
+            .build();
+
+        {
+            let mut scene = Scene::new();
+            let character = rig.instantiate(&mut scene);
+            let cube = MeshBuilder::new(BaseBuilder::new().with_name("Sword"))
+                .with_surfaces(vec![SurfaceBuilder::new(SurfaceSharedData::new(
+                    SurfaceData::make_cube(Matrix4::identity()),
+                ))
+                .build()])
+                .build(&mut scene.graph);
+
+            // Misspelled name of the bone gives the list of similar names.
+            match scene
+                .graph
+                .attach_to_bone(cube, character, "RightHnad", offset.clone())
+            {
+                Err(AttachError::BoneNotFound {
+                    bone_name,
+                    candidates,
+                }) => {
+                    assert_eq!(bone_name, "RightHnad");
+                    assert_eq!(candidates, vec!["RightHand".to_string()]);
+                }
+                _ => panic!("the bone must not be found"),
+            }
+
+            scene
+                .graph
+                .attach_to_bone(cube, character, "RightHand", offset.clone())
+                .unwrap();
+            scene.graph.update_hierarchical_data();
+
+            let hand = scene.graph.find_by_name(character, "RightHand").unwrap().0;
+            assert_eq!(scene.graph[cube].parent(), hand);
+            assert!(
+                scene.graph[cube]
+                    .global_position()
+                    .metric_distance(&Vector3::new(1.0, 1.25, 0.0))
+                    < 1.0e-5
+            );
+
+            save_scene(&mut scene, scene_path);
+        }
+
+        let mut scene = block_on(
+            block_on(SceneLoader::from_file(
+                scene_path,
+                serialization_context,
+                resource_manager,
+            ))
+            .unwrap()
+            .finish(),
+        );
+
+        let cube = scene.graph.find_by_name_from_root("Sword").unwrap().0;
+        let hand = scene.graph.find_by_name_from_root("RightHand").unwrap().0;
+        let attachment = scene.graph[cube].bone_attachment().unwrap().clone();
+        assert_eq!(attachment.bone_name, "RightHand");
+        assert_eq!(scene.graph[cube].parent(), hand);
+        assert_eq!(
+            **scene.graph[cube].local_transform().position(),
+            Vector3::new(0.0, 0.25, 0.0)
+        );
+
+        // Simulate a change of the bone handle, the attachment must follow the name of the bone.
+        let arm = scene.graph[hand].parent();
+        scene.graph[hand].set_name("RightHand.001");
+        scene.graph[cube]
+            .local_transform_mut()
+            .set_position(Vector3::default());
+        let new_hand =
+            PivotBuilder::new(BaseBuilder::new().with_name("RightHand")).build(&mut scene.graph);
+        scene.graph.link_nodes(new_hand, arm);
+        scene.graph.resolve();
+
+        assert_eq!(scene.graph[cube].parent(), new_hand);
+        assert_eq!(
+            **scene.graph[cube].local_transform().position(),
+            Vector3::new(0.0, 0.25, 0.0)
+        );
+
+        assert_eq!(
+            scene.graph.detach_from_bone(cube).unwrap().bone_name,
+            "RightHand"
+        );
+        assert_eq!(scene.graph[cube].parent(), scene.graph.get_root());
+        assert!(scene.graph[cube].bone_attachment().is_none());
+    }
 }
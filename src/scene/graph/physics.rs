@@ -22,7 +22,7 @@ use crate::{
         collider::{self, ColliderShape, GeometrySource},
         debug::SceneDrawingContext,
         graph::{isometric_global_transform, NodePool},
-        joint::JointParams,
+        joint::{JointLocalFrames, JointParams},
         mesh::{
             buffer::{VertexAttributeUsage, VertexReadTrait},
             Mesh,
@@ -31,8 +31,12 @@ use crate::{
         rigidbody::ApplyAction,
         terrain::Terrain,
     },
-    utils::raw_mesh::{RawMeshBuilder, RawVertex},
+    utils::{
+        hash_as_bytes,
+        raw_mesh::{RawMeshBuilder, RawVertex},
+    },
 };
+use fxhash::FxHasher;
 use rapier3d::{
     dynamics::{
         CCDSolver, GenericJoint, GenericJointBuilder, ImpulseJointHandle, ImpulseJointSet,
@@ -49,8 +53,8 @@ use rapier3d::{
 use std::{
     cell::{Cell, RefCell},
     cmp::Ordering,
-    fmt::{Debug, Formatter},
-    hash::Hash,
+    fmt::{Debug, Formatter, Write},
+    hash::{Hash, Hasher},
     sync::Arc,
     time::Duration,
 };
@@ -424,17 +428,33 @@ fn convert_joint_params(
             if v.limits_enabled {
                 joint.set_limits(JointAxis::X, [v.limits.start, v.limits.end]);
             }
+            set_joint_motor(&mut joint, JointAxis::X, &v.motor);
         }
         scene::joint::JointParams::RevoluteJoint(v) => {
             if v.limits_enabled {
                 joint.set_limits(JointAxis::AngX, [v.limits.start, v.limits.end]);
             }
+            set_joint_motor(&mut joint, JointAxis::AngX, &v.motor);
         }
     }
 
     joint
 }
 
+fn set_joint_motor(joint: &mut GenericJoint, axis: JointAxis, motor: &scene::joint::JointMotor) {
+    if motor.enabled {
+        joint
+            .set_motor(
+                axis,
+                motor.target_position,
+                motor.target_velocity,
+                motor.stiffness,
+                motor.damping,
+            )
+            .set_motor_max_force(axis, motor.max_force);
+    }
+}
+
 /// Creates new trimesh collider shape from given mesh node. It also bakes scale into
 /// vertices of trimesh because rapier does not support collider scaling yet.
 fn make_trimesh(
@@ -965,6 +985,44 @@ fn calculate_local_frames(
     )
 }
 
+// Uses explicit local frames of the joint (if any) to get exactly the same joint configuration,
+// otherwise calculates them from current positions. The frames are remembered in the joint.
+fn joint_local_frames(
+    joint: &scene::joint::Joint,
+    body1: &dyn NodeTrait,
+    body2: &dyn NodeTrait,
+) -> (Isometry3<f32>, Isometry3<f32>) {
+    let (local_frame1, local_frame2) = match *joint.local_frames {
+        Some(local_frames) => local_frames.to_isometries(),
+        None => calculate_local_frames(joint, body1, body2),
+    };
+    joint
+        .bound_local_frames
+        .set(Some(JointLocalFrames::from_isometries(
+            local_frame1,
+            local_frame2,
+        )));
+    (local_frame1, local_frame2)
+}
+
+/// Replaces every NaN by the same quiet NaN and negative zero by positive zero, so the values, that
+/// are equal from the simulation's point of view, produce the same bytes.
+fn canonical_f32(v: f32) -> f32 {
+    if v.is_nan() {
+        f32::NAN
+    } else if v == 0.0 {
+        0.0
+    } else {
+        v
+    }
+}
+
+fn hash_f32s<H: Hasher>(values: &[f32], hasher: &mut H) {
+    for &value in values {
+        hash_as_bytes(&canonical_f32(value), hasher);
+    }
+}
+
 fn u32_to_group(v: u32) -> rapier3d::geometry::Group {
     rapier3d::geometry::Group::from_bits(v).unwrap_or_else(rapier3d::geometry::Group::all)
 }
@@ -1554,7 +1612,10 @@ impl PhysicsWorld {
             joint.params.try_sync_model(|v| {
                 native.data =
                     // Preserve local frames.
-                    convert_joint_params(v, native.data.local_frame1, native.data.local_frame2)
+                    convert_joint_params(v, native.data.local_frame1, native.data.local_frame2);
+                native
+                    .data
+                    .set_contacts_enabled(joint.is_contacts_enabled());
             });
             joint.contacts_enabled.try_sync_model(|v| {
                 native.data.set_contacts_enabled(v);
@@ -1568,9 +1629,12 @@ impl PhysicsWorld {
                         .try_borrow(joint.body2())
                         .and_then(|n| n.cast::<scene::rigidbody::RigidBody>()),
                 ) {
-                    let (local_frame1, local_frame2) = calculate_local_frames(joint, body1, body2);
+                    let (local_frame1, local_frame2) = joint_local_frames(joint, body1, body2);
                     native.data =
                         convert_joint_params((*joint.params).clone(), local_frame1, local_frame2);
+                    native
+                        .data
+                        .set_contacts_enabled(joint.is_contacts_enabled());
                     joint.need_rebind.set(false);
                 }
             }
@@ -1591,8 +1655,7 @@ impl PhysicsWorld {
                         .filter(|b| self.bodies.get(b.native.get()).is_some())
                 }),
             ) {
-                // Calculate local frames first.
-                let (local_frame1, local_frame2) = calculate_local_frames(joint, body1, body2);
+                let (local_frame1, local_frame2) = joint_local_frames(joint, body1, body2);
 
                 let native_body1 = body1.native.get();
                 let native_body2 = body2.native.get();
@@ -1649,6 +1712,86 @@ impl PhysicsWorld {
             .contact_pairs()
             .filter_map(|c| ContactPair::from_native(c, self))
     }
+
+    /// Returns native rigid bodies paired with handles of their scene nodes, sorted by the handles.
+    /// Internal order of the rigid body set depends on the history of insertions and removals, while
+    /// the handles of scene nodes are stable for the same scene.
+    fn bodies_in_stable_order(&self) -> Vec<(Handle<Node>, &RigidBody)> {
+        let mut bodies = self
+            .bodies
+            .iter()
+            .map(|(_, body)| (Handle::<Node>::decode_from_u128(body.user_data), body))
+            .collect::<Vec<_>>();
+        bodies.sort_by_key(|(handle, _)| (handle.index(), handle.generation()));
+        bodies
+    }
+
+    /// Calculates a checksum of the simulation state: positions, rotations, linear and angular
+    /// velocities and sleep states of every rigid body. It could be used to detect desynchronization
+    /// of simulations, for example in lockstep multiplayer games - if two simulations have different
+    /// checksums after the same number of steps, then they've diverged.
+    ///
+    /// Rigid bodies are hashed in the order of handles of their scene nodes, so the checksum does
+    /// not depend on the internal order of the physics engine. Every NaN and negative zero are
+    /// hashed as a quiet NaN and positive zero respectively.
+    ///
+    /// # Notes
+    ///
+    /// The checksum does not make the simulation deterministic by itself, it only allows to verify
+    /// it. The hashing algorithm depends on pointer width, so the checksums should be compared
+    /// only between the builds for platforms with the same pointer width. Use
+    /// [`Self::dump_state_debug`] to find which bodies have diverged.
+    pub fn state_hash(&self) -> u64 {
+        let mut hasher = FxHasher::default();
+        for (handle, body) in self.bodies_in_stable_order() {
+            hash_as_bytes(&handle.index(), &mut hasher);
+            hash_as_bytes(&handle.generation(), &mut hasher);
+            let position = body.position();
+            hash_f32s(position.translation.vector.as_slice(), &mut hasher);
+            hash_f32s(position.rotation.coords.as_slice(), &mut hasher);
+            hash_f32s(body.linvel().as_slice(), &mut hasher);
+            hash_f32s(body.angvel().as_slice(), &mut hasher);
+            hash_as_bytes(&(body.is_sleeping() as u8), &mut hasher);
+        }
+        hasher.finish()
+    }
+
+    /// Returns a human-readable listing of the simulation state, that is used to calculate
+    /// [`Self::state_hash`]. Each line describes a single rigid body, lines are sorted by handles
+    /// of scene nodes. Numbers are printed with the precision that is enough to restore exact values,
+    /// so dumps of two simulations could be compared by any text diff tool to find the first
+    /// diverged body.
+    pub fn dump_state_debug(&self) -> String {
+        let mut dump = String::new();
+        for (handle, body) in self.bodies_in_stable_order() {
+            let position = body.position();
+            let t = position.translation.vector;
+            let r = position.rotation.coords;
+            let lin_vel = body.linvel();
+            let ang_vel = body.angvel();
+            // Writing to a string cannot fail.
+            let _ = writeln!(
+                dump,
+                "{handle}: position: [{:?}, {:?}, {:?}]; rotation: [{:?}, {:?}, {:?}, {:?}]; \
+                lin_vel: [{:?}, {:?}, {:?}]; ang_vel: [{:?}, {:?}, {:?}]; sleeping: {}",
+                t.x,
+                t.y,
+                t.z,
+                r.x,
+                r.y,
+                r.z,
+                r.w,
+                lin_vel.x,
+                lin_vel.y,
+                lin_vel.z,
+                ang_vel.x,
+                ang_vel.y,
+                ang_vel.z,
+                body.is_sleeping()
+            );
+        }
+        dump
+    }
 }
 
 impl Default for PhysicsWorld {
@@ -1662,3 +1805,89 @@ impl Debug for PhysicsWorld {
         write!(f, "PhysicsWorld")
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::{
+            algebra::{Vector2, Vector3},
+            pool::Handle,
+        },
+        scene::{
+            base::BaseBuilder,
+            collider::{ColliderBuilder, ColliderShape},
+            graph::{physics::canonical_f32, Graph},
+            node::Node,
+            rigidbody::{RigidBodyBuilder, RigidBodyType},
+            transform::TransformBuilder,
+        },
+    };
+
+    fn make_graph() -> (Graph, Handle<Node>) {
+        let mut graph = Graph::new();
+
+        let ground_collider = ColliderBuilder::new(BaseBuilder::new())
+            .with_shape(ColliderShape::cuboid(10.0, 0.1, 10.0))
+            .build(&mut graph);
+        RigidBodyBuilder::new(BaseBuilder::new().with_children(&[ground_collider]))
+            .with_body_type(RigidBodyType::Static)
+            .build(&mut graph);
+
+        let mut body = Handle::NONE;
+        for i in 0..3 {
+            let collider = ColliderBuilder::new(BaseBuilder::new())
+                .with_shape(ColliderShape::cuboid(0.5, 0.5, 0.5))
+                .build(&mut graph);
+            body = RigidBodyBuilder::new(
+                BaseBuilder::new()
+                    .with_local_transform(
+                        TransformBuilder::new()
+                            .with_local_position(Vector3::new(0.3 * i as f32, 1.0 + i as f32, 0.0))
+                            .build(),
+                    )
+                    .with_children(&[collider]),
+            )
+            .build(&mut graph);
+        }
+
+        (graph, body)
+    }
+
+    fn step(graph: &mut Graph, count: usize) {
+        for _ in 0..count {
+            graph.update(Vector2::new(800.0, 600.0), 1.0 / 60.0, Default::default());
+        }
+    }
+
+    #[test]
+    fn test_physics_state_hash() {
+        let (mut a, body) = make_graph();
+        let (mut b, _) = make_graph();
+        assert_eq!(a.physics.state_hash(), b.physics.state_hash());
+
+        step(&mut a, 30);
+        step(&mut b, 30);
+        assert_eq!(a.physics.state_hash(), b.physics.state_hash());
+        assert_eq!(a.physics.dump_state_debug(), b.physics.dump_state_debug());
+        assert_eq!(a.physics.dump_state_debug().lines().count(), 4);
+
+        // Any perturbation must change the hash.
+        a[body]
+            .as_rigid_body_mut()
+            .set_lin_vel(Vector3::new(0.0, 0.001, 0.0));
+        step(&mut a, 1);
+        step(&mut b, 1);
+        assert_ne!(a.physics.state_hash(), b.physics.state_hash());
+        assert_ne!(a.physics.dump_state_debug(), b.physics.dump_state_debug());
+    }
+
+    #[test]
+    fn test_float_canonicalization() {
+        assert_eq!(canonical_f32(-0.0).to_bits(), 0.0f32.to_bits());
+        assert_eq!(
+            canonical_f32(f32::from_bits(f32::NAN.to_bits() | 1)).to_bits(),
+            f32::NAN.to_bits()
+        );
+        assert_eq!(canonical_f32(1.5), 1.5);
+    }
+}
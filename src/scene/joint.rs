@@ -2,7 +2,7 @@
 
 use crate::{
     core::{
-        algebra::Matrix4,
+        algebra::{Isometry3, Matrix4, Translation3, UnitQuaternion, Vector3},
         log::Log,
         math::{aabb::AxisAlignedBoundingBox, m4x4_approx_eq},
         pool::Handle,
@@ -27,6 +27,56 @@ use std::{
 };
 use strum_macros::{AsRefStr, EnumString, EnumVariantNames};
 
+/// Joint motor drives a joint along its free axis towards the target position and/or velocity. The
+/// force applied by the motor is `stiffness * (target_position - position) + damping *
+/// (target_velocity - velocity)`, clamped to `max_force`. Use zero stiffness to get a velocity motor
+/// (wheels, fans, etc.) and non-zero stiffness to get a position motor (servos, doors, etc.).
+#[derive(Clone, Debug, Visit, PartialEq, Reflect)]
+pub struct JointMotor {
+    /// Whether the motor is enabled or not. Default is `false`.
+    #[reflect(description = "Whether the motor is enabled or not.")]
+    pub enabled: bool,
+
+    /// Target position of the motor (angle in radians for revolute joints, distance for prismatic
+    /// joints).
+    #[reflect(description = "Target position of the motor.")]
+    pub target_position: f32,
+
+    /// Target velocity of the motor (radians per second for revolute joints, units per second for
+    /// prismatic joints).
+    #[reflect(description = "Target velocity of the motor.")]
+    pub target_velocity: f32,
+
+    /// Defines how strong the motor pulls the joint towards the target position.
+    #[reflect(
+        description = "Defines how strong the motor pulls the joint towards the target position."
+    )]
+    pub stiffness: f32,
+
+    /// Defines how strong the motor pulls the joint towards the target velocity.
+    #[reflect(
+        description = "Defines how strong the motor pulls the joint towards the target velocity."
+    )]
+    pub damping: f32,
+
+    /// Maximum force (or torque) the motor can apply.
+    #[reflect(description = "Maximum force (or torque) the motor can apply.")]
+    pub max_force: f32,
+}
+
+impl Default for JointMotor {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            target_position: 0.0,
+            target_velocity: 0.0,
+            stiffness: 0.0,
+            damping: 1.0,
+            max_force: f32::MAX,
+        }
+    }
+}
+
 /// Ball joint locks any translational moves between two objects on the axis between objects, but
 /// allows rigid bodies to perform relative rotations. The real world example is a human shoulder,
 /// pendulum, etc.
@@ -96,6 +146,13 @@ pub struct PrismaticJoint {
     )]
     #[visit(optional)] // Backward compatibility
     pub limits: Range<f32>,
+
+    /// A motor that drives the attached bodies along local X axis of the joint.
+    #[reflect(
+        description = "A motor that drives the attached bodies along local X axis of the joint."
+    )]
+    #[visit(optional)] // Backward compatibility
+    pub motor: JointMotor,
 }
 
 impl Default for PrismaticJoint {
@@ -103,6 +160,7 @@ impl Default for PrismaticJoint {
         Self {
             limits_enabled: false,
             limits: -std::f32::consts::PI..std::f32::consts::PI,
+            motor: Default::default(),
         }
     }
 }
@@ -123,6 +181,13 @@ pub struct RevoluteJoint {
     #[reflect(description = "Allowed angle range around local X axis of the joint (in radians).")]
     #[visit(optional)] // Backward compatibility
     pub limits: Range<f32>,
+
+    /// A motor that rotates the attached bodies around local X axis of the joint.
+    #[reflect(
+        description = "A motor that rotates the attached bodies around local X axis of the joint."
+    )]
+    #[visit(optional)] // Backward compatibility
+    pub motor: JointMotor,
 }
 
 impl Default for RevoluteJoint {
@@ -130,6 +195,7 @@ impl Default for RevoluteJoint {
         Self {
             limits_enabled: false,
             limits: -std::f32::consts::PI..std::f32::consts::PI,
+            motor: Default::default(),
         }
     }
 }
@@ -153,6 +219,229 @@ impl Default for JointParams {
     }
 }
 
+macro_rules! impl_from_joint {
+    ($($variant:ident),*) => {
+        $(
+            impl From<$variant> for JointParams {
+                fn from(params: $variant) -> Self {
+                    Self::$variant(params)
+                }
+            }
+        )*
+    };
+}
+
+impl_from_joint!(BallJoint, FixedJoint, PrismaticJoint, RevoluteJoint);
+
+/// Position and orientation of a joint relative to the rigid bodies it is attached to. The local X
+/// axis of the frames is the free axis of prismatic and revolute joints. Local frames could be set
+/// explicitly (see [`Joint::set_local_frames`]), in this case they're saved together with the joint,
+/// so a scene saved in the middle of simulation will be loaded with exactly the same joint
+/// configuration. Otherwise they're calculated from the current positions of the joint and the
+/// bodies each time the joint binds the bodies.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Visit)]
+pub struct JointLocalFrames {
+    /// Position of the joint in local coordinates of the first body.
+    pub position1: Vector3<f32>,
+    /// Orientation of the joint in local coordinates of the first body.
+    pub rotation1: UnitQuaternion<f32>,
+    /// Position of the joint in local coordinates of the second body.
+    pub position2: Vector3<f32>,
+    /// Orientation of the joint in local coordinates of the second body.
+    pub rotation2: UnitQuaternion<f32>,
+}
+
+impl JointLocalFrames {
+    /// Creates local frames from the given isometries in local coordinates of the first and the
+    /// second body respectively.
+    pub fn from_isometries(frame1: Isometry3<f32>, frame2: Isometry3<f32>) -> Self {
+        Self {
+            position1: frame1.translation.vector,
+            rotation1: frame1.rotation,
+            position2: frame2.translation.vector,
+            rotation2: frame2.rotation,
+        }
+    }
+
+    /// Returns local frames as isometries in local coordinates of the first and the second body
+    /// respectively.
+    pub fn to_isometries(self) -> (Isometry3<f32>, Isometry3<f32>) {
+        (
+            Isometry3::from_parts(Translation3::from(self.position1), self.rotation1),
+            Isometry3::from_parts(Translation3::from(self.position2), self.rotation2),
+        )
+    }
+}
+
+/// Handle of a joint node in a graph.
+pub type JointHandle = Handle<Node>;
+
+/// Parameters of a ball joint for [`Graph::add_joint`]. See [`BallJoint`] for more info.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct BallJointDesc {
+    /// Position of the joint in local coordinates of the first body.
+    pub local_anchor1: Vector3<f32>,
+    /// Position of the joint in local coordinates of the second body.
+    pub local_anchor2: Vector3<f32>,
+}
+
+/// Parameters of a fixed joint for [`Graph::add_joint`]. The bodies are welded together in their
+/// current relative position. See [`FixedJoint`] for more info.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct FixedJointDesc;
+
+/// Parameters of a prismatic joint for [`Graph::add_joint`]. See [`PrismaticJoint`] for more info.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PrismaticJointDesc {
+    /// Position of the joint in local coordinates of the first body.
+    pub local_anchor1: Vector3<f32>,
+    /// Position of the joint in local coordinates of the second body.
+    pub local_anchor2: Vector3<f32>,
+    /// Axis of translation in local coordinates of the first body.
+    pub axis: Vector3<f32>,
+    /// The min and max relative position of the bodies along the axis, `None` - no limits.
+    pub limits: Option<Range<f32>>,
+}
+
+impl Default for PrismaticJointDesc {
+    fn default() -> Self {
+        Self {
+            local_anchor1: Default::default(),
+            local_anchor2: Default::default(),
+            axis: Vector3::x(),
+            limits: None,
+        }
+    }
+}
+
+/// Parameters of a revolute joint for [`Graph::add_joint`]. See [`RevoluteJoint`] for more info.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RevoluteJointDesc {
+    /// Position of the joint in local coordinates of the first body.
+    pub local_anchor1: Vector3<f32>,
+    /// Position of the joint in local coordinates of the second body.
+    pub local_anchor2: Vector3<f32>,
+    /// Axis of rotation in local coordinates of the first body.
+    pub axis: Vector3<f32>,
+    /// Allowed angle range around the axis (in radians), `None` - no limits.
+    pub limits: Option<Range<f32>>,
+    /// A motor that rotates the bodies around the axis.
+    pub motor: JointMotor,
+}
+
+impl Default for RevoluteJointDesc {
+    fn default() -> Self {
+        Self {
+            local_anchor1: Default::default(),
+            local_anchor2: Default::default(),
+            axis: Vector3::x(),
+            limits: None,
+            motor: Default::default(),
+        }
+    }
+}
+
+/// Typed parameters of a joint for [`Graph::add_joint`].
+#[derive(Clone, Debug, PartialEq)]
+pub enum JointDesc {
+    /// See [`BallJointDesc`] for more info.
+    Ball(BallJointDesc),
+    /// See [`FixedJointDesc`] for more info.
+    Fixed(FixedJointDesc),
+    /// See [`PrismaticJointDesc`] for more info.
+    Prismatic(PrismaticJointDesc),
+    /// See [`RevoluteJointDesc`] for more info.
+    Revolute(RevoluteJointDesc),
+}
+
+macro_rules! impl_from_joint_desc {
+    ($($desc:ident => $variant:ident),*) => {
+        $(
+            impl From<$desc> for JointDesc {
+                fn from(desc: $desc) -> Self {
+                    Self::$variant(desc)
+                }
+            }
+        )*
+    };
+}
+
+impl_from_joint_desc!(
+    BallJointDesc => Ball,
+    FixedJointDesc => Fixed,
+    PrismaticJointDesc => Prismatic,
+    RevoluteJointDesc => Revolute
+);
+
+// Rotates local X axis of a joint frame to the given axis.
+fn axis_rotation(axis: Vector3<f32>) -> UnitQuaternion<f32> {
+    UnitQuaternion::rotation_between(&Vector3::x(), &axis).unwrap_or_else(|| {
+        UnitQuaternion::from_axis_angle(&Vector3::y_axis(), std::f32::consts::PI)
+    })
+}
+
+impl JointDesc {
+    // Converts the description to joint parameters and local frames, `pose1` and `pose2` are the
+    // current global poses of the bodies. The frame of the second body is oriented so the axes of
+    // both frames match in world space.
+    pub(crate) fn into_params_and_frames(
+        self,
+        pose1: Isometry3<f32>,
+        pose2: Isometry3<f32>,
+    ) -> (JointParams, JointLocalFrames) {
+        let (params, anchor1, anchor2, rotation1) = match self {
+            JointDesc::Ball(desc) => (
+                JointParams::BallJoint(Default::default()),
+                desc.local_anchor1,
+                desc.local_anchor2,
+                UnitQuaternion::identity(),
+            ),
+            JointDesc::Fixed(_) => {
+                // Weld the bodies in their current relative position.
+                let frame2 = pose2.inverse() * pose1;
+                return (
+                    JointParams::FixedJoint(Default::default()),
+                    JointLocalFrames::from_isometries(Isometry3::identity(), frame2),
+                );
+            }
+            JointDesc::Prismatic(desc) => (
+                JointParams::PrismaticJoint(PrismaticJoint {
+                    limits_enabled: desc.limits.is_some(),
+                    limits: desc
+                        .limits
+                        .unwrap_or_else(|| PrismaticJoint::default().limits),
+                    motor: Default::default(),
+                }),
+                desc.local_anchor1,
+                desc.local_anchor2,
+                axis_rotation(desc.axis),
+            ),
+            JointDesc::Revolute(desc) => (
+                JointParams::RevoluteJoint(RevoluteJoint {
+                    limits_enabled: desc.limits.is_some(),
+                    limits: desc
+                        .limits
+                        .unwrap_or_else(|| RevoluteJoint::default().limits),
+                    motor: desc.motor,
+                }),
+                desc.local_anchor1,
+                desc.local_anchor2,
+                axis_rotation(desc.axis),
+            ),
+        };
+
+        let rotation2 = pose2.rotation.inverse() * pose1.rotation * rotation1;
+
+        (
+            params,
+            JointLocalFrames::from_isometries(
+                Isometry3::from_parts(Translation3::from(anchor1), rotation1),
+                Isometry3::from_parts(Translation3::from(anchor2), rotation2),
+            ),
+        )
+    }
+}
+
 /// Joint is used to restrict motion of two rigid bodies. There are numerous examples of joints in
 /// real life: door hinge, ball joints in human arms, etc.
 #[derive(Visit, Reflect, Debug)]
@@ -183,6 +472,15 @@ pub struct Joint {
     #[visit(skip)]
     #[reflect(hidden)]
     pub(crate) need_rebind: Cell<bool>,
+
+    #[visit(optional)] // Backward compatibility
+    #[reflect(hidden)]
+    pub(crate) local_frames: InheritableVariable<Option<JointLocalFrames>>,
+
+    // Local frames, that were used when the joint bound the bodies last time.
+    #[visit(skip)]
+    #[reflect(hidden)]
+    pub(crate) bound_local_frames: Cell<Option<JointLocalFrames>>,
 }
 
 impl Default for Joint {
@@ -196,6 +494,8 @@ impl Default for Joint {
             auto_rebind: true.into(),
             native: Cell::new(ImpulseJointHandle::invalid()),
             need_rebind: Cell::new(true),
+            local_frames: Default::default(),
+            bound_local_frames: Default::default(),
         }
     }
 }
@@ -227,6 +527,8 @@ impl Clone for Joint {
             native: Cell::new(ImpulseJointHandle::invalid()),
             // Rebind will happen automatically.
             need_rebind: Cell::new(true),
+            local_frames: self.local_frames.clone(),
+            bound_local_frames: Default::default(),
         }
     }
 }
@@ -257,6 +559,7 @@ impl Joint {
     /// Sets the first body of the joint. The handle should point to the RigidBody node, otherwise
     /// the joint will have no effect!
     pub fn set_body1(&mut self, handle: Handle<Node>) -> Handle<Node> {
+        self.need_rebind.set(true);
         self.body1.set_value_and_mark_modified(handle)
     }
 
@@ -268,6 +571,7 @@ impl Joint {
     /// Sets the second body of the joint. The handle should point to the RigidBody node, otherwise
     /// the joint will have no effect!
     pub fn set_body2(&mut self, handle: Handle<Node>) -> Handle<Node> {
+        self.need_rebind.set(true);
         self.body2.set_value_and_mark_modified(handle)
     }
 
@@ -296,6 +600,52 @@ impl Joint {
     pub fn is_auto_rebinding_enabled(&self) -> bool {
         *self.auto_rebind
     }
+
+    /// Sets explicit local frames of the joint, `None` - the frames will be calculated from the
+    /// current positions of the joint and the bodies. See [`JointLocalFrames`] docs for more info.
+    pub fn set_local_frames(
+        &mut self,
+        local_frames: Option<JointLocalFrames>,
+    ) -> Option<JointLocalFrames> {
+        self.need_rebind.set(true);
+        self.local_frames.set_value_and_mark_modified(local_frames)
+    }
+
+    /// Returns current local frames of the joint: either explicit ones, or the ones that were used
+    /// when the joint bound the bodies last time. `None` if there are no explicit local frames and
+    /// the joint has not bound the bodies yet.
+    pub fn local_frames(&self) -> Option<JointLocalFrames> {
+        (*self.local_frames).or_else(|| self.bound_local_frames.get())
+    }
+
+    /// Returns position of the joint in local coordinates of the first body. See
+    /// [`Self::local_frames`] for more info.
+    pub fn local_anchor1(&self) -> Option<Vector3<f32>> {
+        self.local_frames().map(|frames| frames.position1)
+    }
+
+    /// Returns position of the joint in local coordinates of the second body. See
+    /// [`Self::local_frames`] for more info.
+    pub fn local_anchor2(&self) -> Option<Vector3<f32>> {
+        self.local_frames().map(|frames| frames.position2)
+    }
+
+    /// Returns free axis of the joint (for prismatic and revolute joints) in local coordinates of
+    /// the first body. See [`Self::local_frames`] for more info.
+    pub fn local_axis1(&self) -> Option<Vector3<f32>> {
+        self.local_frames()
+            .map(|frames| frames.rotation1 * Vector3::x())
+    }
+
+    /// Forces the joint to recalculate its local frames from the current positions of the joint and
+    /// the bodies on next update. Explicit local frames are removed.
+    pub fn invalidate_local_frames(&mut self) {
+        if self.local_frames.is_some() {
+            self.local_frames.set_value_and_mark_modified(None);
+        }
+        self.bound_local_frames.set(None);
+        self.need_rebind.set(true);
+    }
 }
 
 impl NodeTrait for Joint {
@@ -372,6 +722,7 @@ pub struct JointBuilder {
     body2: Handle<Node>,
     contacts_enabled: bool,
     auto_rebind: bool,
+    local_frames: Option<JointLocalFrames>,
 }
 
 impl JointBuilder {
@@ -384,12 +735,14 @@ impl JointBuilder {
             body2: Default::default(),
             contacts_enabled: true,
             auto_rebind: true,
+            local_frames: None,
         }
     }
 
-    /// Sets desired joint parameters which defines exact type of the joint.
-    pub fn with_params(mut self, params: JointParams) -> Self {
-        self.params = params;
+    /// Sets desired joint parameters which defines exact type of the joint. Accepts either
+    /// [`JointParams`] or any of the specific joint types ([`BallJoint`], [`RevoluteJoint`], etc.).
+    pub fn with_params<P: Into<JointParams>>(mut self, params: P) -> Self {
+        self.params = params.into();
         self
     }
 
@@ -420,6 +773,12 @@ impl JointBuilder {
         self
     }
 
+    /// Sets explicit local frames of the joint. See [`JointLocalFrames`] docs for more info.
+    pub fn with_local_frames(mut self, local_frames: JointLocalFrames) -> Self {
+        self.local_frames = Some(local_frames);
+        self
+    }
+
     /// Creates new Joint node, but does not add it to the graph.
     pub fn build_joint(self) -> Joint {
         Joint {
@@ -431,6 +790,8 @@ impl JointBuilder {
             auto_rebind: self.auto_rebind.into(),
             native: Cell::new(ImpulseJointHandle::invalid()),
             need_rebind: Cell::new(true),
+            local_frames: self.local_frames.into(),
+            bound_local_frames: Default::default(),
         }
     }
 
@@ -444,3 +805,126 @@ impl JointBuilder {
         graph.add_node(self.build_node())
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::{
+            algebra::{Isometry3, Translation3, UnitQuaternion, Vector2, Vector3},
+            visitor::prelude::*,
+        },
+        scene::{
+            base::BaseBuilder,
+            graph::Graph,
+            joint::{
+                Joint, JointBuilder, JointLocalFrames, JointMotor, JointParams, RevoluteJoint,
+                RevoluteJointDesc,
+            },
+            rigidbody::RigidBodyBuilder,
+            transform::TransformBuilder,
+        },
+    };
+    use rapier3d::dynamics::ImpulseJointHandle;
+
+    #[test]
+    fn test_joint_visit_round_trip() {
+        let mut joint = JointBuilder::new(BaseBuilder::new())
+            .with_params(RevoluteJoint {
+                limits_enabled: true,
+                limits: -1.0..1.0,
+                motor: JointMotor {
+                    enabled: true,
+                    target_velocity: 2.0,
+                    ..Default::default()
+                },
+            })
+            .with_contacts_enabled(false)
+            .build_joint();
+
+        let local_frames = JointLocalFrames::from_isometries(
+            Isometry3::from_parts(
+                Translation3::new(1.0, 2.0, 3.0),
+                UnitQuaternion::from_axis_angle(&Vector3::y_axis(), 0.5),
+            ),
+            Isometry3::translation(-1.0, 0.0, 0.0),
+        );
+        joint.set_local_frames(Some(local_frames));
+        joint.bound_local_frames.set(Some(Default::default()));
+
+        let mut visitor = Visitor::new();
+        joint.visit("Joint", &mut visitor).unwrap();
+        let data = visitor.save_binary_to_vec().unwrap();
+
+        let mut visitor = Visitor::load_from_memory(data).unwrap();
+        let mut loaded = JointBuilder::new(BaseBuilder::new()).build_joint();
+        loaded.visit("Joint", &mut visitor).unwrap();
+
+        assert_eq!(loaded.params(), joint.params());
+        assert!(!loaded.is_contacts_enabled());
+        assert_eq!(loaded.local_frames(), Some(local_frames));
+        assert_eq!(loaded.bound_local_frames.get(), None);
+        assert!(loaded.need_rebind.get());
+    }
+
+    #[test]
+    fn test_add_joint() {
+        let mut graph = Graph::new();
+        let body1 = RigidBodyBuilder::new(BaseBuilder::new()).build(&mut graph);
+        let body2 = RigidBodyBuilder::new(
+            BaseBuilder::new().with_local_transform(
+                TransformBuilder::new()
+                    .with_local_position(Vector3::new(2.0, 0.0, 0.0))
+                    .with_local_rotation(UnitQuaternion::from_axis_angle(
+                        &Vector3::y_axis(),
+                        std::f32::consts::FRAC_PI_2,
+                    ))
+                    .build(),
+            ),
+        )
+        .build(&mut graph);
+        graph.update_hierarchical_data();
+
+        let joint = graph.add_joint(
+            body1,
+            body2,
+            RevoluteJointDesc {
+                local_anchor1: Vector3::new(1.0, 0.0, 0.0),
+                local_anchor2: Vector3::new(0.0, 0.0, 1.0),
+                axis: Vector3::z(),
+                limits: Some(-0.5..0.5),
+                ..Default::default()
+            },
+        );
+
+        let joint_ref = graph[joint].query_component_ref::<Joint>().unwrap();
+        assert_eq!(joint_ref.body1(), body1);
+        assert_eq!(joint_ref.body2(), body2);
+        assert_eq!(
+            joint_ref.params(),
+            &JointParams::RevoluteJoint(RevoluteJoint {
+                limits_enabled: true,
+                limits: -0.5..0.5,
+                motor: Default::default(),
+            })
+        );
+        assert_eq!(joint_ref.local_anchor1(), Some(Vector3::new(1.0, 0.0, 0.0)));
+        assert_eq!(joint_ref.local_anchor2(), Some(Vector3::new(0.0, 0.0, 1.0)));
+        assert!((joint_ref.local_axis1().unwrap() - Vector3::z()).norm() < 1.0e-5);
+
+        // Axes of both frames must match in world space.
+        let frames = joint_ref.local_frames().unwrap();
+        let axis1 = graph.global_rotation(body1) * frames.rotation1 * Vector3::x();
+        let axis2 = graph.global_rotation(body2) * frames.rotation2 * Vector3::x();
+        assert!((axis1 - axis2).norm() < 1.0e-5);
+
+        // Native bodies are created on the first update, the joint binds them on the next one.
+        for _ in 0..2 {
+            graph.update(Vector2::new(100.0, 100.0), 1.0 / 60.0, Default::default());
+        }
+
+        let joint_ref = graph[joint].query_component_ref::<Joint>().unwrap();
+        assert_ne!(joint_ref.native.get(), ImpulseJointHandle::invalid());
+        // Explicit frames must be used as is.
+        assert_eq!(joint_ref.bound_local_frames.get(), Some(frames));
+    }
+}
@@ -0,0 +1,210 @@
+//! Custom distance attenuation of point and spot lights. See [`LightFalloffCurve`] docs for more
+//! info.
+
+use crate::{
+    asset::Resource,
+    core::{
+        reflect::prelude::*,
+        visitor::{Visit, VisitResult, Visitor},
+    },
+    resource::texture::{Texture, TextureKind, TexturePixelKind, TextureResource, TextureWrapMode},
+};
+use fxhash::FxHasher;
+use std::{
+    cell::RefCell,
+    cmp::Ordering,
+    hash::{Hash, Hasher},
+};
+
+/// Amount of samples in a lookup texture of a falloff curve.
+pub const FALLOFF_LUT_SIZE: usize = 256;
+
+/// A control point of [`LightFalloffCurve`].
+#[derive(Copy, Clone, Debug, Default, PartialEq, Visit, Reflect)]
+pub struct LightFalloffPoint {
+    /// Distance to the light divided by its radius (point lights) or its distance (spot lights).
+    /// Clamped to `0..1` range.
+    #[reflect(min_value = 0.0, max_value = 1.0, step = 0.01)]
+    pub distance: f32,
+    /// Intensity multiplier of the light at the distance. Clamped to `0..1` range.
+    #[reflect(min_value = 0.0, max_value = 1.0, step = 0.01)]
+    pub attenuation: f32,
+}
+
+impl LightFalloffPoint {
+    /// Creates new control point.
+    pub fn new(distance: f32, attenuation: f32) -> Self {
+        Self {
+            distance,
+            attenuation,
+        }
+    }
+}
+
+/// Falloff curve replaces built-in distance attenuation of a light. It is a piecewise linear
+/// function defined by a small set of control points, the values before the first point and after
+/// the last point are equal to the values of the respective points. An empty curve is ignored.
+///
+/// The renderer samples the curve from a lookup texture (LUT), which is generated on first use and
+/// re-generated only when the points were changed.
+#[derive(Clone, Debug, Default, Visit, Reflect)]
+pub struct LightFalloffCurve {
+    points: Vec<LightFalloffPoint>,
+
+    #[visit(skip)]
+    #[reflect(hidden)]
+    lut: RefCell<Option<(u64, TextureResource)>>,
+}
+
+impl PartialEq for LightFalloffCurve {
+    fn eq(&self, other: &Self) -> bool {
+        self.points == other.points
+    }
+}
+
+impl LightFalloffCurve {
+    /// Creates new falloff curve from the given control points. The points could be in any order.
+    pub fn new(points: Vec<LightFalloffPoint>) -> Self {
+        Self {
+            points,
+            lut: Default::default(),
+        }
+    }
+
+    /// Returns a reference to the control points.
+    pub fn points(&self) -> &[LightFalloffPoint] {
+        &self.points
+    }
+
+    /// Returns a mutable reference to the control points. The lookup texture is re-generated
+    /// automatically.
+    pub fn points_mut(&mut self) -> &mut Vec<LightFalloffPoint> {
+        &mut self.points
+    }
+
+    /// Calculates attenuation at the given normalized distance.
+    pub fn evaluate(&self, distance: f32) -> f32 {
+        self.generate_samples(&[distance])[0]
+    }
+
+    /// Generates `size` evenly spaced samples of the curve in `0..=1` range of distances.
+    pub fn generate_lut(&self, size: usize) -> Vec<f32> {
+        let step = 1.0 / (size.max(2) - 1) as f32;
+        let distances = (0..size).map(|i| i as f32 * step).collect::<Vec<_>>();
+        self.generate_samples(&distances)
+    }
+
+    fn generate_samples(&self, distances: &[f32]) -> Vec<f32> {
+        let mut points = self
+            .points
+            .iter()
+            .map(|p| {
+                LightFalloffPoint::new(p.distance.clamp(0.0, 1.0), p.attenuation.clamp(0.0, 1.0))
+            })
+            .collect::<Vec<_>>();
+        points.sort_by(|a, b| {
+            a.distance
+                .partial_cmp(&b.distance)
+                .unwrap_or(Ordering::Equal)
+        });
+
+        distances
+            .iter()
+            .map(|&distance| {
+                let right = points.partition_point(|p| p.distance <= distance);
+                match (
+                    right.checked_sub(1).map(|i| points[i]),
+                    points.get(right).copied(),
+                ) {
+                    (Some(left), Some(right)) => {
+                        let t = (distance - left.distance) / (right.distance - left.distance);
+                        left.attenuation + (right.attenuation - left.attenuation) * t
+                    }
+                    (Some(point), None) | (None, Some(point)) => point.attenuation,
+                    (None, None) => 1.0,
+                }
+            })
+            .collect()
+    }
+
+    fn points_hash(&self) -> u64 {
+        let mut hasher = FxHasher::default();
+        for point in self.points.iter() {
+            point.distance.to_bits().hash(&mut hasher);
+            point.attenuation.to_bits().hash(&mut hasher);
+        }
+        hasher.finish()
+    }
+
+    /// Returns the lookup texture of the curve, the texture is generated on first call and when the
+    /// points were changed. The texture is [`FALLOFF_LUT_SIZE`] pixels wide and one pixel high.
+    pub fn lut_texture(&self) -> TextureResource {
+        let hash = self.points_hash();
+        let mut lut = self.lut.borrow_mut();
+        if let Some((lut_hash, texture)) = lut.as_ref() {
+            if *lut_hash == hash {
+                return texture.clone();
+            }
+        }
+
+        let bytes = self
+            .generate_lut(FALLOFF_LUT_SIZE)
+            .into_iter()
+            .flat_map(|v| ((v * u16::MAX as f32) as u16).to_ne_bytes())
+            .collect::<Vec<_>>();
+        let mut texture = Texture::from_bytes(
+            TextureKind::Rectangle {
+                width: FALLOFF_LUT_SIZE as u32,
+                height: 1,
+            },
+            TexturePixelKind::R16,
+            bytes,
+            false,
+        )
+        .unwrap();
+        texture.set_s_wrap_mode(TextureWrapMode::ClampToEdge);
+        texture.set_t_wrap_mode(TextureWrapMode::ClampToEdge);
+
+        let texture = Resource::new_ok(texture);
+        *lut = Some((hash, texture.clone()));
+        texture
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::scene::light::falloff::{LightFalloffCurve, LightFalloffPoint};
+
+    #[test]
+    fn test_falloff_lut() {
+        // Empty curve does not attenuate.
+        assert_eq!(LightFalloffCurve::default().generate_lut(3), vec![1.0; 3]);
+
+        let curve = LightFalloffCurve::new(vec![
+            LightFalloffPoint::new(0.75, 0.0),
+            LightFalloffPoint::new(0.25, 1.0),
+        ]);
+        assert_eq!(
+            curve.generate_lut(5),
+            vec![1.0, 1.0, 0.5, 0.0, 0.0],
+            "unordered points must be sorted, values outside of points must be clamped"
+        );
+
+        let curve = LightFalloffCurve::new(vec![
+            LightFalloffPoint::new(-1.0, 2.0),
+            LightFalloffPoint::new(0.5, 0.5),
+            LightFalloffPoint::new(2.0, -1.0),
+        ]);
+        assert_eq!(curve.generate_lut(3), vec![1.0, 0.5, 0.0]);
+        assert_eq!(curve.evaluate(0.25), 0.75);
+
+        // A step with two points at the same distance.
+        let curve = LightFalloffCurve::new(vec![
+            LightFalloffPoint::new(0.5, 1.0),
+            LightFalloffPoint::new(0.5, 0.0),
+        ]);
+        let lut = curve.generate_lut(64);
+        assert!(lut.windows(2).all(|w| w[1] <= w[0]));
+        assert!(lut.iter().all(|v| (0.0..=1.0).contains(v)));
+    }
+}
@@ -29,6 +29,7 @@ use crate::{
 use std::ops::{Deref, DerefMut};
 
 pub mod directional;
+pub mod falloff;
 pub mod point;
 pub mod spot;
 
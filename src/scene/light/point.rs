@@ -28,11 +28,12 @@ use crate::{
         visitor::{Visit, VisitResult, Visitor},
         TypeUuidProvider,
     },
+    resource::texture::TextureResource,
     scene::{
         base::Base,
         debug::SceneDrawingContext,
         graph::Graph,
-        light::{BaseLight, BaseLightBuilder},
+        light::{falloff::LightFalloffCurve, BaseLight, BaseLightBuilder},
         node::{Node, NodeTrait},
     },
 };
@@ -50,6 +51,14 @@ pub struct PointLight {
     #[reflect(min_value = 0.0, step = 0.1)]
     #[reflect(setter = "set_radius")]
     radius: InheritableVariable<f32>,
+
+    #[visit(optional)]
+    #[reflect(setter = "set_cookie_texture")]
+    cookie_texture: InheritableVariable<Option<TextureResource>>,
+
+    #[visit(optional)]
+    #[reflect(setter = "set_falloff_curve")]
+    falloff_curve: InheritableVariable<Option<LightFalloffCurve>>,
 }
 
 impl Deref for PointLight {
@@ -106,6 +115,45 @@ impl PointLight {
     pub fn shadow_bias(&self) -> f32 {
         *self.shadow_bias
     }
+
+    /// Sets cookie texture, that modulates the light in every direction. It could be either a cube
+    /// map, or a 2D texture with latitude-longitude (equirectangular) mapping. Directions are in
+    /// local coordinates of the light, so the pattern rotates with the light.
+    #[inline]
+    pub fn set_cookie_texture(
+        &mut self,
+        texture: Option<TextureResource>,
+    ) -> Option<TextureResource> {
+        self.cookie_texture.set_value_and_mark_modified(texture)
+    }
+
+    /// Returns cookie texture of the light.
+    #[inline]
+    pub fn cookie_texture(&self) -> Option<TextureResource> {
+        (*self.cookie_texture).clone()
+    }
+
+    /// Returns cookie texture of the light by ref.
+    #[inline]
+    pub fn cookie_texture_ref(&self) -> Option<&TextureResource> {
+        self.cookie_texture.as_ref()
+    }
+
+    /// Sets custom distance attenuation of the light, it replaces built-in attenuation. See
+    /// [`LightFalloffCurve`] docs for more info.
+    #[inline]
+    pub fn set_falloff_curve(
+        &mut self,
+        curve: Option<LightFalloffCurve>,
+    ) -> Option<LightFalloffCurve> {
+        self.falloff_curve.set_value_and_mark_modified(curve)
+    }
+
+    /// Returns custom distance attenuation of the light.
+    #[inline]
+    pub fn falloff_curve(&self) -> Option<&LightFalloffCurve> {
+        self.falloff_curve.as_ref()
+    }
 }
 
 impl NodeTrait for PointLight {
@@ -135,6 +183,8 @@ impl Default for PointLight {
             base_light: Default::default(),
             shadow_bias: InheritableVariable::new_modified(0.025),
             radius: InheritableVariable::new_modified(10.0),
+            cookie_texture: InheritableVariable::new_modified(None),
+            falloff_curve: InheritableVariable::new_modified(None),
         }
     }
 }
@@ -144,6 +194,8 @@ pub struct PointLightBuilder {
     base_light_builder: BaseLightBuilder,
     shadow_bias: f32,
     radius: f32,
+    cookie_texture: Option<TextureResource>,
+    falloff_curve: Option<LightFalloffCurve>,
 }
 
 impl PointLightBuilder {
@@ -153,6 +205,8 @@ impl PointLightBuilder {
             base_light_builder,
             shadow_bias: 0.025,
             radius: 10.0,
+            cookie_texture: None,
+            falloff_curve: None,
         }
     }
 
@@ -168,12 +222,26 @@ impl PointLightBuilder {
         self
     }
 
+    /// Sets desired cookie texture, see [`PointLight::set_cookie_texture`] for more info.
+    pub fn with_cookie_texture(mut self, texture: TextureResource) -> Self {
+        self.cookie_texture = Some(texture);
+        self
+    }
+
+    /// Sets custom distance attenuation of the light.
+    pub fn with_falloff_curve(mut self, curve: LightFalloffCurve) -> Self {
+        self.falloff_curve = Some(curve);
+        self
+    }
+
     /// Builds new instance of point light.
     pub fn build_point_light(self) -> PointLight {
         PointLight {
             base_light: self.base_light_builder.build(),
             radius: self.radius.into(),
             shadow_bias: self.shadow_bias.into(),
+            cookie_texture: self.cookie_texture.into(),
+            falloff_curve: self.falloff_curve.into(),
         }
     }
 
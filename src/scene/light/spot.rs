@@ -39,7 +39,7 @@ use crate::{
         base::Base,
         debug::SceneDrawingContext,
         graph::Graph,
-        light::{BaseLight, BaseLightBuilder},
+        light::{falloff::LightFalloffCurve, BaseLight, BaseLightBuilder},
         node::{Node, NodeTrait},
     },
 };
@@ -68,6 +68,10 @@ pub struct SpotLight {
 
     #[reflect(setter = "set_cookie_texture")]
     cookie_texture: InheritableVariable<Option<TextureResource>>,
+
+    #[visit(optional)]
+    #[reflect(setter = "set_falloff_curve")]
+    falloff_curve: InheritableVariable<Option<LightFalloffCurve>>,
 }
 
 impl Deref for SpotLight {
@@ -93,6 +97,7 @@ impl Default for SpotLight {
             shadow_bias: InheritableVariable::new_modified(0.00005),
             distance: InheritableVariable::new_modified(10.0),
             cookie_texture: InheritableVariable::new_modified(None),
+            falloff_curve: InheritableVariable::new_modified(None),
         }
     }
 }
@@ -192,6 +197,22 @@ impl SpotLight {
     pub fn cookie_texture_ref(&self) -> Option<&TextureResource> {
         self.cookie_texture.as_ref()
     }
+
+    /// Sets custom distance attenuation of the light, it replaces built-in attenuation. See
+    /// [`LightFalloffCurve`] docs for more info.
+    #[inline]
+    pub fn set_falloff_curve(
+        &mut self,
+        curve: Option<LightFalloffCurve>,
+    ) -> Option<LightFalloffCurve> {
+        self.falloff_curve.set_value_and_mark_modified(curve)
+    }
+
+    /// Returns custom distance attenuation of the light.
+    #[inline]
+    pub fn falloff_curve(&self) -> Option<&LightFalloffCurve> {
+        self.falloff_curve.as_ref()
+    }
 }
 
 impl NodeTrait for SpotLight {
@@ -238,6 +259,7 @@ pub struct SpotLightBuilder {
     shadow_bias: f32,
     distance: f32,
     cookie_texture: Option<TextureResource>,
+    falloff_curve: Option<LightFalloffCurve>,
 }
 
 impl SpotLightBuilder {
@@ -250,6 +272,7 @@ impl SpotLightBuilder {
             shadow_bias: 0.00005,
             distance: 10.0,
             cookie_texture: None,
+            falloff_curve: None,
         }
     }
 
@@ -283,6 +306,12 @@ impl SpotLightBuilder {
         self
     }
 
+    /// Sets custom distance attenuation of the light.
+    pub fn with_falloff_curve(mut self, curve: LightFalloffCurve) -> Self {
+        self.falloff_curve = Some(curve);
+        self
+    }
+
     /// Creates new spot light.
     pub fn build_spot_light(self) -> SpotLight {
         SpotLight {
@@ -292,6 +321,7 @@ impl SpotLightBuilder {
             shadow_bias: self.shadow_bias.into(),
             distance: self.distance.into(),
             cookie_texture: self.cookie_texture.into(),
+            falloff_curve: self.falloff_curve.into(),
         }
     }
 
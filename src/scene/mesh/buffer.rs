@@ -921,6 +921,8 @@ impl<'a> PartialEq for VertexViewMut<'a> {
 pub enum VertexFetchError {
     /// Trying to read/write non-existent attribute.
     NoSuchAttribute(VertexAttributeUsage),
+    /// Trying to read/write a vertex at out-of-bounds index.
+    InvalidIndex(usize),
     /// IO error.
     Io(std::io::Error),
 }
@@ -933,6 +935,9 @@ impl Display for VertexFetchError {
             VertexFetchError::NoSuchAttribute(v) => {
                 write!(f, "No attribute with such usage: {v:?}")
             }
+            VertexFetchError::InvalidIndex(v) => {
+                write!(f, "Invalid vertex index {v}")
+            }
             VertexFetchError::Io(v) => {
                 write!(f, "An i/o error has occurred {v:?}")
             }
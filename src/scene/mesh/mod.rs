@@ -5,6 +5,7 @@ use crate::{
     core::{
         algebra::{Matrix4, Point3, Vector3},
         color::Color,
+        hash_combine,
         math::aabb::AxisAlignedBoundingBox,
         pool::Handle,
         reflect::prelude::*,
@@ -120,6 +121,11 @@ impl RenderPath {
 ///
 /// This example creates a unit cube surface with default material and then creates a mesh with this surface. If you need to create
 /// custom surface, see [`crate::scene::mesh::surface::SurfaceData`] docs for more info.
+///
+/// ## Components
+///
+/// Mesh provides access to its surfaces as a component of `InheritableVariable<Vec<Surface>>` type,
+/// see [`crate::scene::node::Node::query_component_ref`] and [`crate::scene::graph::Graph::typed_iter`].
 #[derive(Debug, Reflect, Clone, Visit)]
 pub struct Mesh {
     #[visit(rename = "Common")]
@@ -145,6 +151,10 @@ pub struct Mesh {
     #[visit(skip)]
     local_bounding_box_dirty: Cell<bool>,
 
+    #[reflect(hidden)]
+    #[visit(skip)]
+    surfaces_hash: Cell<u64>,
+
     #[reflect(hidden)]
     #[visit(skip)]
     world_bounding_box: Cell<AxisAlignedBoundingBox>,
@@ -158,6 +168,7 @@ impl Default for Mesh {
             local_bounding_box: Default::default(),
             world_bounding_box: Default::default(),
             local_bounding_box_dirty: Cell::new(true),
+            surfaces_hash: Default::default(),
             render_path: InheritableVariable::new_modified(RenderPath::Deferred),
             decal_layer_index: InheritableVariable::new_modified(0),
             blend_shapes: Default::default(),
@@ -230,6 +241,31 @@ impl Mesh {
         self.blend_shapes.get_value_mut_and_mark_modified()
     }
 
+    /// Sets the weight (in `0..100` range) of a blend shape with the given name. Returns `false` if
+    /// there's no such blend shape. Use [`crate::animation::track::Track::new_blend_shape_weight`]
+    /// to animate weights.
+    pub fn set_blend_shape_weight(&mut self, name: &str, weight: f32) -> bool {
+        if let Some(blend_shape) = self
+            .blend_shapes_mut()
+            .iter_mut()
+            .find(|bs| bs.name == name)
+        {
+            blend_shape.weight = weight;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Returns the weight of a blend shape with the given name, or `None` if there's no such blend
+    /// shape.
+    pub fn blend_shape_weight(&self, name: &str) -> Option<f32> {
+        self.blend_shapes
+            .iter()
+            .find(|bs| bs.name == name)
+            .map(|bs| bs.weight)
+    }
+
     /// Sets new render path for the mesh.
     pub fn set_render_path(&mut self, render_path: RenderPath) -> RenderPath {
         self.render_path.set_value_and_mark_modified(render_path)
@@ -244,58 +280,92 @@ impl Mesh {
     /// This method is very heavy and not intended to use every frame!
     pub fn accurate_world_bounding_box(&self, graph: &Graph) -> AxisAlignedBoundingBox {
         let mut bounding_box = AxisAlignedBoundingBox::default();
-        for surface in self.surfaces.iter() {
-            let data = surface.data();
-            let data = data.lock();
-            if surface.bones().is_empty() {
-                for view in data.vertex_buffer.iter() {
-                    bounding_box.add_point(
-                        self.global_transform()
-                            .transform_point(&Point3::from(
-                                view.read_3_f32(VertexAttributeUsage::Position).unwrap(),
-                            ))
-                            .coords,
-                    );
-                }
-            } else {
-                // Special case for skinned surface. Its actual bounds defined only by bones
-                // influence.
-
-                // Precalculate bone matrices first to speed up calculations.
-                let bone_matrices = surface
-                    .bones()
-                    .iter()
-                    .map(|&b| {
-                        let bone_node = &graph[b];
-                        bone_node.global_transform() * bone_node.inv_bind_pose_transform()
-                    })
-                    .collect::<Vec<Matrix4<f32>>>();
+        for surface_index in 0..self.surfaces.len() {
+            for position in self.cpu_skin_surface(surface_index, graph) {
+                bounding_box.add_point(position);
+            }
+        }
+        bounding_box
+    }
 
-                for view in data.vertex_buffer.iter() {
-                    let mut position = Vector3::default();
-                    for (&bone_index, &weight) in view
-                        .read_4_u8(VertexAttributeUsage::BoneIndices)
-                        .unwrap()
+    /// Calculates positions of vertices of a surface with the given index in *world coordinates*,
+    /// applying current bone transforms on CPU. It could be used for precise hit tests or decal
+    /// projection on animated meshes. Non-skinned surfaces are just transformed by the global
+    /// transform of the mesh. Blend shapes are applied before skinning, the same way as the renderer
+    /// does. Returns an empty vector if there is no such surface.
+    ///
+    /// This method is heavy and not intended to use every frame for every mesh!
+    pub fn cpu_skin_surface(&self, surface_index: usize, graph: &Graph) -> Vec<Vector3<f32>> {
+        let surface = if let Some(surface) = self.surfaces.get(surface_index) {
+            surface
+        } else {
+            return Vec::new();
+        };
+
+        let data = surface.data();
+        let data = data.lock();
+
+        let blend_shape_offsets =
+            data.blend_shapes_container
+                .as_ref()
+                .map_or_else(Vec::new, |container| {
+                    let weights = self
+                        .blend_shapes()
                         .iter()
-                        .zip(
-                            view.read_4_f32(VertexAttributeUsage::BoneWeight)
-                                .unwrap()
-                                .iter(),
-                        )
-                    {
-                        position += bone_matrices[bone_index as usize]
-                            .transform_point(&Point3::from(
-                                view.read_3_f32(VertexAttributeUsage::Position).unwrap(),
-                            ))
-                            .coords
-                            .scale(weight);
+                        .map(|bs| bs.weight / 100.0)
+                        .collect::<Vec<_>>();
+                    container.position_offsets(&weights, data.vertex_buffer.vertex_count() as usize)
+                });
+        let blend_shape_offset =
+            |index: usize| blend_shape_offsets.get(index).cloned().unwrap_or_default();
+
+        if surface.bones().is_empty() {
+            let transform = self.global_transform();
+            data.vertex_buffer
+                .iter()
+                .enumerate()
+                .filter_map(|(index, view)| {
+                    let position = view.read_3_f32(VertexAttributeUsage::Position).ok()?;
+                    Some(position + blend_shape_offset(index))
+                })
+                .map(|position| transform.transform_point(&Point3::from(position)).coords)
+                .collect()
+        } else {
+            // Precalculate bone matrices first to speed up calculations.
+            let bone_matrices = surface
+                .bones()
+                .iter()
+                .map(|&b| {
+                    graph
+                        .try_get(b)
+                        .map_or_else(Matrix4::identity, |bone_node| {
+                            bone_node.global_transform() * bone_node.inv_bind_pose_transform()
+                        })
+                })
+                .collect::<Vec<Matrix4<f32>>>();
+
+            data.vertex_buffer
+                .iter()
+                .enumerate()
+                .filter_map(|(index, view)| {
+                    let position = Point3::from(
+                        view.read_3_f32(VertexAttributeUsage::Position).ok()?
+                            + blend_shape_offset(index),
+                    );
+                    let indices = view.read_4_u8(VertexAttributeUsage::BoneIndices).ok()?;
+                    let weights = view.read_4_f32(VertexAttributeUsage::BoneWeight).ok()?;
+
+                    let mut skinned_position = Vector3::default();
+                    for (&bone_index, &weight) in indices.iter().zip(weights.iter()) {
+                        if let Some(bone_matrix) = bone_matrices.get(bone_index as usize) {
+                            skinned_position +=
+                                bone_matrix.transform_point(&position).coords.scale(weight);
+                        }
                     }
-
-                    bounding_box.add_point(position);
-                }
-            }
+                    Some(skinned_position)
+                })
+                .collect()
         }
-        bounding_box
     }
 
     /// Sets new decal layer index. It defines which decals will be applies to the mesh,
@@ -312,12 +382,18 @@ impl Mesh {
 }
 
 impl NodeTrait for Mesh {
-    crate::impl_query_component!();
+    crate::impl_query_component!(surfaces: InheritableVariable<Vec<Surface>>);
 
     /// Returns current bounding box. Bounding box presented in *local coordinates*
     /// WARNING: This method does *not* includes bounds of bones!
     fn local_bounding_box(&self) -> AxisAlignedBoundingBox {
-        if self.local_bounding_box_dirty.get() {
+        // Surface data could be modified directly (see `SurfaceData::modify`), so the bounding box is
+        // also recalculated when content of any surface has changed.
+        let surfaces_hash = self.surfaces.iter().fold(0, |hash, surface| {
+            hash_combine(hash, surface.data_ref().lock().content_hash())
+        });
+
+        if self.local_bounding_box_dirty.get() || self.surfaces_hash.get() != surfaces_hash {
             let mut bounding_box = AxisAlignedBoundingBox::default();
             for surface in self.surfaces.iter() {
                 let data = surface.data();
@@ -329,6 +405,7 @@ impl NodeTrait for Mesh {
             }
             self.local_bounding_box.set(bounding_box);
             self.local_bounding_box_dirty.set(false);
+            self.surfaces_hash.set(surfaces_hash);
         }
 
         self.local_bounding_box.get()
@@ -344,27 +421,32 @@ impl NodeTrait for Mesh {
     }
 
     fn update(&mut self, context: &mut UpdateContext) {
-        if self.surfaces.iter().any(|s| !s.bones.is_empty()) {
-            let mut world_aabb = self
-                .local_bounding_box()
-                .transform(&self.global_transform());
+        let local_bounding_box = self.local_bounding_box();
 
-            // Special case for skinned meshes.
-            for surface in self.surfaces.iter() {
-                for &bone in surface.bones() {
-                    if let Some(node) = context.nodes.try_borrow(bone) {
-                        world_aabb.add_point(node.global_position())
-                    }
+        // Special case for skinned meshes. A skinned vertex is a weighted sum of the vertex transformed
+        // by each of its bones, so it always lies inside the union of the bind-pose bounds transformed
+        // by every bone. This gives conservative bounds that follow the animation.
+        let mut world_aabb = AxisAlignedBoundingBox::default();
+        let mut need_unskinned_bounds = false;
+        for surface in self.surfaces.iter() {
+            let mut has_bones = false;
+            for &bone in surface.bones() {
+                if let Some(node) = context.nodes.try_borrow(bone) {
+                    world_aabb
+                        .add_box(local_bounding_box.transform(
+                            &(node.global_transform() * node.inv_bind_pose_transform()),
+                        ));
+                    has_bones = true;
                 }
             }
+            need_unskinned_bounds |= !has_bones;
+        }
 
-            self.world_bounding_box.set(world_aabb)
-        } else {
-            self.world_bounding_box.set(
-                self.local_bounding_box()
-                    .transform(&self.global_transform()),
-            );
+        if need_unskinned_bounds {
+            world_aabb.add_box(local_bounding_box.transform(&self.global_transform()));
         }
+
+        self.world_bounding_box.set(world_aabb);
     }
 
     fn collect_render_data(&self, ctx: &mut RenderContext) {
@@ -505,6 +587,12 @@ impl MeshBuilder {
         self
     }
 
+    /// Adds a surface to the mesh.
+    pub fn with_surface(mut self, surface: Surface) -> Self {
+        self.surfaces.push(surface);
+        self
+    }
+
     /// Sets desired render path. Keep in mind that RenderPath::Forward is not fully
     /// implemented and only used to render transparent objects!
     pub fn with_render_path(mut self, render_path: RenderPath) -> Self {
@@ -533,6 +621,7 @@ impl MeshBuilder {
             surfaces: self.surfaces.into(),
             local_bounding_box: Default::default(),
             local_bounding_box_dirty: Cell::new(true),
+            surfaces_hash: Default::default(),
             render_path: self.render_path.into(),
             decal_layer_index: self.decal_layer_index.into(),
             world_bounding_box: Default::default(),
@@ -544,3 +633,204 @@ impl MeshBuilder {
         graph.add_node(self.build_node())
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::{
+            algebra::{Matrix4, Vector2, Vector3, Vector4},
+            math::TriangleDefinition,
+        },
+        fxhash::FxHashMap,
+        scene::{
+            base::BaseBuilder,
+            graph::Graph,
+            mesh::{
+                buffer::{TriangleBuffer, VertexBuffer},
+                surface::{
+                    BlendShape, BlendShapesContainer, InputBlendShapeData, SurfaceBuilder,
+                    SurfaceData, SurfaceSharedData,
+                },
+                vertex::AnimatedVertex,
+                Mesh, MeshBuilder,
+            },
+            node::NodeTrait,
+            pivot::PivotBuilder,
+            transform::TransformBuilder,
+        },
+    };
+    use half::f16;
+
+    fn vertex(position: Vector3<f32>, bone: u8) -> AnimatedVertex {
+        AnimatedVertex {
+            position,
+            tex_coord: Vector2::default(),
+            normal: Vector3::y(),
+            tangent: Vector4::default(),
+            bone_weights: [1.0, 0.0, 0.0, 0.0],
+            bone_indices: [bone, 0, 0, 0],
+        }
+    }
+
+    #[test]
+    fn test_two_bone_skinning() {
+        let mut graph = Graph::new();
+
+        // First bone stays at the origin, the second one is bound at (1, 0, 0).
+        let bone0 = PivotBuilder::new(BaseBuilder::new()).build(&mut graph);
+        let bone1 = PivotBuilder::new(
+            BaseBuilder::new()
+                .with_inv_bind_pose_transform(Matrix4::new_translation(&Vector3::new(
+                    -1.0, 0.0, 0.0,
+                )))
+                .with_local_transform(
+                    TransformBuilder::new()
+                        .with_local_position(Vector3::new(1.0, 0.0, 0.0))
+                        .build(),
+                ),
+        )
+        .build(&mut graph);
+
+        let vertices = vec![
+            vertex(Vector3::new(0.0, 0.0, 0.0), 0),
+            vertex(Vector3::new(1.0, 0.0, 0.0), 1),
+            vertex(Vector3::new(2.0, 0.0, 0.0), 1),
+        ];
+        let data = SurfaceData::new(
+            VertexBuffer::new(vertices.len(), vertices).unwrap(),
+            TriangleBuffer::new(vec![TriangleDefinition([0, 1, 2])]),
+            true,
+        );
+        let mesh = MeshBuilder::new(BaseBuilder::new())
+            .with_surfaces(vec![SurfaceBuilder::new(SurfaceSharedData::new(data))
+                .with_bones(vec![bone0, bone1])
+                .build()])
+            .build(&mut graph);
+
+        graph.update(Vector2::new(800.0, 600.0), 1.0, Default::default());
+
+        // Bind pose.
+        assert_eq!(
+            graph[mesh]
+                .cast::<Mesh>()
+                .unwrap()
+                .cpu_skin_surface(0, &graph),
+            vec![
+                Vector3::new(0.0, 0.0, 0.0),
+                Vector3::new(1.0, 0.0, 0.0),
+                Vector3::new(2.0, 0.0, 0.0)
+            ]
+        );
+
+        // Move the second bone far away from the bind-pose bounds.
+        graph[bone1]
+            .local_transform_mut()
+            .set_position(Vector3::new(1.0, 10.0, 0.0));
+        graph.update(Vector2::new(800.0, 600.0), 1.0, Default::default());
+
+        let mesh_ref = graph[mesh].cast::<Mesh>().unwrap();
+        let skinned = mesh_ref.cpu_skin_surface(0, &graph);
+        assert_eq!(
+            skinned,
+            vec![
+                Vector3::new(0.0, 0.0, 0.0),
+                Vector3::new(1.0, 10.0, 0.0),
+                Vector3::new(2.0, 10.0, 0.0)
+            ]
+        );
+        assert!(mesh_ref.cpu_skin_surface(1, &graph).is_empty());
+
+        // Culling bounds must contain every posed vertex.
+        let world_bounding_box = mesh_ref.world_bounding_box();
+        for position in skinned {
+            assert!(world_bounding_box.is_contains_point(position));
+        }
+        assert_eq!(
+            mesh_ref.accurate_world_bounding_box(&graph).max,
+            Vector3::new(2.0, 10.0, 0.0)
+        );
+    }
+
+    #[test]
+    fn test_blend_shapes() {
+        let mut graph = Graph::new();
+
+        let vertices = vec![
+            vertex(Vector3::new(0.0, 0.0, 0.0), 0),
+            vertex(Vector3::new(1.0, 0.0, 0.0), 0),
+            vertex(Vector3::new(0.0, 1.0, 0.0), 0),
+        ];
+        let vertex_buffer = VertexBuffer::new(vertices.len(), vertices).unwrap();
+
+        let shape = |name: &str, index: u32, offset: Vector3<f32>| InputBlendShapeData {
+            default_weight: 0.0,
+            name: name.to_string(),
+            positions: FxHashMap::from_iter([(index, offset.map(f16::from_f32))]),
+            normals: Default::default(),
+            tangents: Default::default(),
+        };
+        let container = BlendShapesContainer::from_lists(
+            &vertex_buffer,
+            &[
+                shape("Up", 0, Vector3::new(0.0, 2.0, 0.0)),
+                shape("Forward", 1, Vector3::new(0.0, 0.0, 4.0)),
+            ],
+        );
+
+        let mut data = SurfaceData::new(
+            vertex_buffer,
+            TriangleBuffer::new(vec![TriangleDefinition([0, 1, 2])]),
+            true,
+        );
+        data.blend_shapes_container = Some(container);
+
+        let mesh = MeshBuilder::new(BaseBuilder::new())
+            .with_surfaces(vec![
+                SurfaceBuilder::new(SurfaceSharedData::new(data)).build()
+            ])
+            .with_blend_shapes(vec![
+                BlendShape {
+                    weight: 0.0,
+                    name: "Up".to_string(),
+                },
+                BlendShape {
+                    weight: 0.0,
+                    name: "Forward".to_string(),
+                },
+            ])
+            .build(&mut graph);
+
+        graph.update(Vector2::new(800.0, 600.0), 1.0, Default::default());
+
+        let base = vec![
+            Vector3::new(0.0, 0.0, 0.0),
+            Vector3::new(1.0, 0.0, 0.0),
+            Vector3::new(0.0, 1.0, 0.0),
+        ];
+        assert_eq!(
+            graph[mesh]
+                .cast::<Mesh>()
+                .unwrap()
+                .cpu_skin_surface(0, &graph),
+            base
+        );
+
+        let mesh_mut = graph[mesh].cast_mut::<Mesh>().unwrap();
+        assert!(mesh_mut.set_blend_shape_weight("Up", 50.0));
+        assert!(mesh_mut.set_blend_shape_weight("Forward", 100.0));
+        assert!(!mesh_mut.set_blend_shape_weight("Unknown", 100.0));
+        assert_eq!(mesh_mut.blend_shape_weight("Up"), Some(50.0));
+
+        assert_eq!(
+            graph[mesh]
+                .cast::<Mesh>()
+                .unwrap()
+                .cpu_skin_surface(0, &graph),
+            vec![
+                Vector3::new(0.0, 1.0, 0.0),
+                Vector3::new(1.0, 0.0, 4.0),
+                Vector3::new(0.0, 1.0, 0.0),
+            ]
+        );
+    }
+}
@@ -3,10 +3,12 @@
 
 use crate::scene::mesh::buffer::VertexTrait;
 use crate::{
+    asset::ResourceStateRef,
     core::{
         algebra::{Matrix4, Point3, Vector2, Vector3, Vector4},
         hash_combine,
-        math::TriangleDefinition,
+        log::Log,
+        math::{aabb::AxisAlignedBoundingBox, TriangleDefinition},
         parking_lot::{Mutex, MutexGuard},
         pool::{ErasedHandle, Handle},
         reflect::prelude::*,
@@ -19,8 +21,8 @@ use crate::{
     scene::{
         mesh::{
             buffer::{
-                TriangleBuffer, VertexAttributeUsage, VertexBuffer, VertexFetchError,
-                VertexReadTrait, VertexWriteTrait,
+                TriangleBuffer, TriangleBufferRefMut, VertexAttributeUsage, VertexBuffer,
+                VertexBufferRefMut, VertexFetchError, VertexReadTrait, VertexWriteTrait,
             },
             vertex::StaticVertex,
         },
@@ -56,7 +58,7 @@ impl Default for BlendShape {
 }
 
 /// A container for multiple blend shapes/
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, Visit)]
 pub struct BlendShapesContainer {
     /// A list of blend shapes.
     pub blend_shapes: Vec<BlendShape>,
@@ -182,6 +184,48 @@ impl BlendShapesContainer {
             ),
         }
     }
+
+    /// Calculates total position offset of every vertex for the given weights of blend shapes (in
+    /// `0..1` range). It could be used to apply blend shapes on CPU, the renderer does the same in
+    /// vertex shader. Returns an empty vector if the storage is not loaded.
+    pub fn position_offsets(&self, weights: &[f32], vertex_count: usize) -> Vec<Vector3<f32>> {
+        let storage = if let Some(storage) = self.blend_shape_storage.as_ref() {
+            storage.state()
+        } else {
+            return Vec::new();
+        };
+        let storage = if let ResourceStateRef::Ok(storage) = storage.get() {
+            storage
+        } else {
+            return Vec::new();
+        };
+        let layer_size = if let TextureKind::Volume { width, height, .. } = storage.kind() {
+            // Every vertex takes three texels: position, normal and tangent offsets.
+            (width / 3 * height) as usize
+        } else {
+            return Vec::new();
+        };
+
+        const VERTEX_SIZE: usize = 9 * std::mem::size_of::<f16>();
+
+        let data = storage.data();
+        let mut offsets = vec![Vector3::default(); vertex_count];
+        for (layer, &weight) in weights.iter().enumerate() {
+            if weight == 0.0 {
+                continue;
+            }
+
+            for (vertex_index, offset) in offsets.iter_mut().enumerate().take(layer_size) {
+                let begin = (layer * layer_size + vertex_index) * VERTEX_SIZE;
+                if let Some(bytes) = data.get(begin..begin + 3 * std::mem::size_of::<f16>()) {
+                    let component =
+                        |i: usize| f16::from_ne_bytes([bytes[i * 2], bytes[i * 2 + 1]]).to_f32();
+                    *offset += Vector3::new(component(0), component(1), component(2)).scale(weight);
+                }
+            }
+        }
+        offsets
+    }
 }
 
 /// Data source of a surface. Each surface can share same data source, this is used
@@ -249,7 +293,8 @@ impl SurfaceData {
     }
 
     /// Converts raw mesh into "renderable" mesh. It is useful to build procedural meshes. See [`RawMesh`] docs for more
-    /// info.
+    /// info. Surfaces consist of triangles only, so line segments and points of the raw mesh are ignored, use
+    /// [`crate::scene::debug::SceneDrawingContext::draw_raw_mesh`] to visualize them.
     pub fn from_raw_mesh<T>(raw: RawMesh<T>, is_procedural: bool) -> Self
     where
         T: VertexTrait,
@@ -269,76 +314,52 @@ impl SurfaceData {
     /// procedurally, you have to use this method! This method uses "classic" method which is described in:
     /// "Computing Tangent Space Basis Vectors for an Arbitrary Mesh" article by Eric Lengyel.
     pub fn calculate_tangents(&mut self) -> Result<(), VertexFetchError> {
-        let mut tan1 = vec![Vector3::default(); self.vertex_buffer.vertex_count() as usize];
-        let mut tan2 = vec![Vector3::default(); self.vertex_buffer.vertex_count() as usize];
-
-        for triangle in self.geometry_buffer.iter() {
-            let i1 = triangle[0] as usize;
-            let i2 = triangle[1] as usize;
-            let i3 = triangle[2] as usize;
-
-            let view1 = &self.vertex_buffer.get(i1).unwrap();
-            let view2 = &self.vertex_buffer.get(i2).unwrap();
-            let view3 = &self.vertex_buffer.get(i3).unwrap();
-
-            let v1 = view1.read_3_f32(VertexAttributeUsage::Position)?;
-            let v2 = view2.read_3_f32(VertexAttributeUsage::Position)?;
-            let v3 = view3.read_3_f32(VertexAttributeUsage::Position)?;
-
-            let w1 = view1.read_3_f32(VertexAttributeUsage::TexCoord0)?;
-            let w2 = view2.read_3_f32(VertexAttributeUsage::TexCoord0)?;
-            let w3 = view3.read_3_f32(VertexAttributeUsage::TexCoord0)?;
-
-            let x1 = v2.x - v1.x;
-            let x2 = v3.x - v1.x;
-            let y1 = v2.y - v1.y;
-            let y2 = v3.y - v1.y;
-            let z1 = v2.z - v1.z;
-            let z2 = v3.z - v1.z;
-
-            let s1 = w2.x - w1.x;
-            let s2 = w3.x - w1.x;
-            let t1 = w2.y - w1.y;
-            let t2 = w3.y - w1.y;
-
-            let r = 1.0 / (s1 * t2 - s2 * t1);
-
-            let sdir = Vector3::new(
-                (t2 * x1 - t1 * x2) * r,
-                (t2 * y1 - t1 * y2) * r,
-                (t2 * z1 - t1 * z2) * r,
-            );
-
-            tan1[i1] += sdir;
-            tan1[i2] += sdir;
-            tan1[i3] += sdir;
-
-            let tdir = Vector3::new(
-                (s1 * x2 - s2 * x1) * r,
-                (s1 * y2 - s2 * y1) * r,
-                (s1 * z2 - s2 * z1) * r,
-            );
-            tan2[i1] += tdir;
-            tan2[i2] += tdir;
-            tan2[i3] += tdir;
-        }
+        calculate_tangents(&mut self.vertex_buffer, &self.geometry_buffer)
+    }
 
-        let mut vertex_buffer_mut = self.vertex_buffer.modify();
-        for (mut view, (t1, t2)) in vertex_buffer_mut.iter_mut().zip(tan1.into_iter().zip(tan2)) {
-            let normal = view.read_3_f32(VertexAttributeUsage::Normal)?;
+    /// Calculates smooth normals of surface. Normal of each vertex is an area-weighted average of normals of
+    /// every triangle that shares the vertex. Unlike [`Self::calculate_normals`], this method gives smooth
+    /// shading on shared vertices, while keeping hard edges on vertices that are not shared (for example on
+    /// the edges of a cube, which has separate vertices for each side).
+    pub fn calculate_smooth_normals(&mut self) -> Result<(), VertexFetchError> {
+        calculate_smooth_normals(&mut self.vertex_buffer, &self.geometry_buffer)
+    }
 
-            // Gram-Schmidt orthogonalize
-            let tangent = (t1 - normal.scale(normal.dot(&t1)))
-                .try_normalize(f32::EPSILON)
-                .unwrap_or_else(|| Vector3::new(0.0, 1.0, 0.0));
-            let handedness = normal.cross(&t1).dot(&t2).signum();
-            view.write_4_f32(
-                VertexAttributeUsage::Tangent,
-                Vector4::new(tangent.x, tangent.y, tangent.z, handedness),
-            )?;
+    /// Calculates local bounding box of the surface using positions of its vertices.
+    pub fn calculate_bounding_box(&self) -> AxisAlignedBoundingBox {
+        let mut bounding_box = AxisAlignedBoundingBox::default();
+        for view in self.vertex_buffer.iter() {
+            if let Ok(position) = view.read_3_f32(VertexAttributeUsage::Position) {
+                bounding_box.add_point(position);
+            }
         }
+        bounding_box
+    }
 
-        Ok(())
+    /// Returns a guard, that provides mutable access to the vertex and triangle buffers of the surface.
+    /// When the guard is dropped, it automatically recalculates normals (see [`Self::calculate_smooth_normals`])
+    /// and tangents (see [`Self::calculate_tangents`]) of the surface, if the vertex layout has respective
+    /// attributes. It also recalculates hashes of the buffers, so the renderer will re-upload the data to GPU
+    /// and meshes that use the surface will update their bounding boxes. It is the easiest way to generate or
+    /// modify meshes at runtime (voxel chunks, road strips, etc.).
+    ///
+    /// ```
+    /// use fyrox::scene::mesh::surface::SurfaceData;
+    ///
+    /// fn inflate(data: &mut SurfaceData) {
+    ///     let mut modifier = data.modify();
+    ///     for i in 0..modifier.vertex_count() {
+    ///         let position = modifier.position(i).unwrap();
+    ///         modifier.set_position(i, position.scale(2.0)).unwrap();
+    ///     }
+    ///     // Normals and tangents will be recalculated here.
+    /// }
+    /// ```
+    pub fn modify(&mut self) -> SurfaceDataRefMut<'_> {
+        SurfaceDataRefMut {
+            vertex_buffer: self.vertex_buffer.modify(),
+            geometry_buffer: self.geometry_buffer.modify(),
+        }
     }
 
     /// Creates a quad oriented on oXY plane with unit width and height.
@@ -642,7 +663,8 @@ impl SurfaceData {
         data
     }
 
-    /// Creates a torus in oXY plane with the given inner and outer radii. `num_rings` defines the amount of "slices" around the Z axis of the
+    /// Creates a torus in oXZ plane with the given inner and outer radii (`inner_radius` is the distance from the center of the torus to
+    /// the center of the tube, `outer_radius` is the radius of the tube). `num_rings` defines the amount of "slices" around the Y axis of the
     /// torus shape, `num_segments` defines the amount of segments in every slice. The larger the `num_rings` and `num_segments` are, the more
     /// smooth the torus will be, typical values are `16..32`. Torus will be transformed using the given transformation matrix, which could be
     /// [`Matrix4::identity`] to not modify the torus at all.
@@ -659,7 +681,7 @@ impl SurfaceData {
                 let u = i as f32 / num_segments as f32 * std::f32::consts::TAU;
                 let v = j as f32 / num_rings as f32 * std::f32::consts::TAU;
 
-                let center = Vector3::new(inner_radius * u.cos(), inner_radius * u.sin(), 0.0);
+                let center = Vector3::new(inner_radius * u.cos(), 0.0, inner_radius * u.sin());
 
                 let position = Vector3::new(
                     (inner_radius + outer_radius * v.cos()) * u.cos(),
@@ -685,9 +707,282 @@ impl SurfaceData {
                 let c = ((num_segments + 1) * (j - 1) + i) as u32;
                 let d = ((num_segments + 1) * j + i) as u32;
 
-                triangles.push(TriangleDefinition([a, b, d]));
-                triangles.push(TriangleDefinition([b, c, d]));
+                triangles.push(TriangleDefinition([a, d, b]));
+                triangles.push(TriangleDefinition([b, d, c]));
+            }
+        }
+
+        let mut data = Self::new(
+            VertexBuffer::new(vertices.len(), vertices).unwrap(),
+            TriangleBuffer::new(triangles),
+            true,
+        );
+        data.calculate_tangents().unwrap();
+        data.transform_geometry(transform).unwrap();
+        data
+    }
+
+    /// Creates vertical (along Y axis) capsule, that consists of a cylinder with the given `height` and two hemispheres with
+    /// the given `radius` at its ends, so the total height of the capsule is `height + 2 * radius`. The capsule is centered
+    /// at the origin. `segments` defines the amount of segments around the Y axis, `rings` defines the amount of rings of each
+    /// hemisphere, typical values are `16..32` and `4..16` respectively. The capsule is then transformed using the given
+    /// transformation matrix, which could be [`Matrix4::identity`] to not modify the capsule at all.
+    pub fn make_capsule(
+        radius: f32,
+        height: f32,
+        segments: usize,
+        rings: usize,
+        transform: &Matrix4<f32>,
+    ) -> Self {
+        let segments = segments.max(3);
+        let rings = rings.max(1);
+        let half_height = height.max(0.0) * 0.5;
+        let quarter_arc = radius * std::f32::consts::FRAC_PI_2;
+        let profile_length = 2.0 * quarter_arc + height.max(0.0);
+
+        // Rows of vertices from the top pole to the bottom pole: (polar angle, y offset, v).
+        let mut rows = Vec::with_capacity(2 * (rings + 1));
+        for k in 0..=rings {
+            let t = k as f32 / rings as f32;
+            rows.push((
+                t * std::f32::consts::FRAC_PI_2,
+                half_height,
+                t * quarter_arc,
+            ));
+        }
+        for k in 0..=rings {
+            let t = k as f32 / rings as f32;
+            rows.push((
+                (1.0 + t) * std::f32::consts::FRAC_PI_2,
+                -half_height,
+                quarter_arc + height.max(0.0) + t * quarter_arc,
+            ));
+        }
+
+        let mut vertices = Vec::with_capacity(rows.len() * (segments + 1));
+        for (row, &(theta, y_offset, v)) in rows.iter().enumerate() {
+            let is_pole = row == 0 || row == rows.len() - 1;
+            for s in 0..=segments {
+                // Pole vertices have texture coordinate in the middle of their segment to keep
+                // triangles non-degenerate in texture space.
+                let u = if is_pole {
+                    (s as f32 + 0.5) / segments as f32
+                } else {
+                    s as f32 / segments as f32
+                };
+                let phi = s as f32 / segments as f32 * std::f32::consts::TAU;
+                let normal = Vector3::new(
+                    theta.sin() * phi.cos(),
+                    theta.cos(),
+                    theta.sin() * phi.sin(),
+                );
+                let position = normal.scale(radius) + Vector3::new(0.0, y_offset, 0.0);
+                vertices.push(StaticVertex::from_pos_uv_normal(
+                    position,
+                    Vector2::new(u, v / profile_length),
+                    normal,
+                ));
+            }
+        }
+
+        let mut triangles = Vec::new();
+        for row in 0..rows.len() - 1 {
+            // Skip the cylindrical part if it has zero height.
+            if row == rings && height <= 0.0 {
+                continue;
+            }
+
+            for s in 0..segments {
+                let i00 = (row * (segments + 1) + s) as u32;
+                let i01 = i00 + 1;
+                let i10 = ((row + 1) * (segments + 1) + s) as u32;
+                let i11 = i10 + 1;
+
+                if row == 0 {
+                    triangles.push(TriangleDefinition([i00, i11, i10]));
+                } else if row == rows.len() - 2 {
+                    triangles.push(TriangleDefinition([i00, i01, i10]));
+                } else {
+                    triangles.push(TriangleDefinition([i00, i01, i10]));
+                    triangles.push(TriangleDefinition([i01, i11, i10]));
+                }
+            }
+        }
+
+        let mut data = Self::new(
+            VertexBuffer::new(vertices.len(), vertices).unwrap(),
+            TriangleBuffer::new(triangles),
+            true,
+        );
+        data.calculate_tangents().unwrap();
+        data.transform_geometry(transform).unwrap();
+        data
+    }
+
+    /// Creates a plane in oXZ plane with the given width (along X axis) and depth (along Z axis), centered at the origin and facing
+    /// up (+Y). `subdivisions_x` and `subdivisions_z` define the amount of cells along respective axes, it is useful to make
+    /// surfaces, that will be deformed later (water, terrain patches, etc.). The plane is then transformed using the given
+    /// transformation matrix, which could be [`Matrix4::identity`] to not modify the plane at all.
+    pub fn make_plane(
+        width: f32,
+        depth: f32,
+        subdivisions_x: usize,
+        subdivisions_z: usize,
+        transform: &Matrix4<f32>,
+    ) -> Self {
+        let subdivisions_x = subdivisions_x.max(1);
+        let subdivisions_z = subdivisions_z.max(1);
+
+        let mut vertices = Vec::with_capacity((subdivisions_x + 1) * (subdivisions_z + 1));
+        for k in 0..=subdivisions_z {
+            for i in 0..=subdivisions_x {
+                let uv = Vector2::new(
+                    i as f32 / subdivisions_x as f32,
+                    k as f32 / subdivisions_z as f32,
+                );
+                vertices.push(StaticVertex::from_pos_uv_normal(
+                    Vector3::new((uv.x - 0.5) * width, 0.0, (uv.y - 0.5) * depth),
+                    uv,
+                    Vector3::y(),
+                ));
+            }
+        }
+
+        let mut triangles = Vec::with_capacity(subdivisions_x * subdivisions_z * 2);
+        for k in 0..subdivisions_z {
+            for i in 0..subdivisions_x {
+                let i00 = (k * (subdivisions_x + 1) + i) as u32;
+                let i10 = i00 + 1;
+                let i01 = ((k + 1) * (subdivisions_x + 1) + i) as u32;
+                let i11 = i01 + 1;
+
+                triangles.push(TriangleDefinition([i00, i01, i10]));
+                triangles.push(TriangleDefinition([i10, i01, i11]));
+            }
+        }
+
+        let mut data = Self::new(
+            VertexBuffer::new(vertices.len(), vertices).unwrap(),
+            TriangleBuffer::new(triangles),
+            true,
+        );
+        data.calculate_tangents().unwrap();
+        data.transform_geometry(transform).unwrap();
+        data
+    }
+
+    /// Creates a sphere by subdividing an icosahedron, unlike [`Self::make_sphere`] it has evenly distributed triangles.
+    /// Every subdivision splits each triangle into four, so the sphere has `20 * 4^subdivisions` triangles, typical values
+    /// are `2..4`. Texture coordinates use spherical mapping, vertices at the seam are duplicated. The sphere is then
+    /// transformed using the given transformation matrix, which could be [`Matrix4::identity`] to not modify the sphere at
+    /// all.
+    pub fn make_icosphere(radius: f32, subdivisions: usize, transform: &Matrix4<f32>) -> Self {
+        let t = (1.0 + 5.0f32.sqrt()) * 0.5;
+        let mut positions = [
+            [-1.0, t, 0.0],
+            [1.0, t, 0.0],
+            [-1.0, -t, 0.0],
+            [1.0, -t, 0.0],
+            [0.0, -1.0, t],
+            [0.0, 1.0, t],
+            [0.0, -1.0, -t],
+            [0.0, 1.0, -t],
+            [t, 0.0, -1.0],
+            [t, 0.0, 1.0],
+            [-t, 0.0, -1.0],
+            [-t, 0.0, 1.0],
+        ]
+        .iter()
+        .map(|p| Vector3::new(p[0], p[1], p[2]).normalize())
+        .collect::<Vec<_>>();
+        let mut faces = vec![
+            [0, 11, 5],
+            [0, 5, 1],
+            [0, 1, 7],
+            [0, 7, 10],
+            [0, 10, 11],
+            [1, 5, 9],
+            [5, 11, 4],
+            [11, 10, 2],
+            [10, 7, 6],
+            [7, 1, 8],
+            [3, 9, 4],
+            [3, 4, 2],
+            [3, 2, 6],
+            [3, 6, 8],
+            [3, 8, 9],
+            [4, 9, 5],
+            [2, 4, 11],
+            [6, 2, 10],
+            [8, 6, 7],
+            [9, 8, 1],
+        ];
+
+        for _ in 0..subdivisions {
+            let mut midpoints = FxHashMap::default();
+            let mut midpoint = |a: u32, b: u32, positions: &mut Vec<Vector3<f32>>| {
+                *midpoints.entry((a.min(b), a.max(b))).or_insert_with(|| {
+                    positions.push(
+                        (positions[a as usize] + positions[b as usize])
+                            .scale(0.5)
+                            .normalize(),
+                    );
+                    (positions.len() - 1) as u32
+                })
+            };
+
+            let mut new_faces = Vec::with_capacity(faces.len() * 4);
+            for [a, b, c] in faces {
+                let ab = midpoint(a, b, &mut positions);
+                let bc = midpoint(b, c, &mut positions);
+                let ca = midpoint(c, a, &mut positions);
+                new_faces.push([a, ab, ca]);
+                new_faces.push([b, bc, ab]);
+                new_faces.push([c, ca, bc]);
+                new_faces.push([ab, bc, ca]);
+            }
+            faces = new_faces;
+        }
+
+        // Spherical mapping, triangles that cross the seam get duplicated vertices with wrapped
+        // texture coordinates.
+        let spherical_uv = |p: &Vector3<f32>| {
+            Vector2::new(
+                p.z.atan2(p.x) / std::f32::consts::TAU + 0.5,
+                p.y.clamp(-1.0, 1.0).acos() / std::f32::consts::PI,
+            )
+        };
+        let mut vertex_map = FxHashMap::default();
+        let mut vertices = Vec::new();
+        let mut triangles = Vec::with_capacity(faces.len());
+        for face in faces {
+            let mut uvs = face.map(|i| spherical_uv(&positions[i as usize]));
+            let (min_u, max_u) = uvs.iter().fold((f32::MAX, f32::MIN), |(min, max), uv| {
+                (min.min(uv.x), max.max(uv.x))
+            });
+            if max_u - min_u > 0.5 {
+                for uv in uvs.iter_mut() {
+                    if uv.x < 0.5 {
+                        uv.x += 1.0;
+                    }
+                }
+            }
+
+            let mut triangle = [0; 3];
+            for ((index, uv), out) in face.iter().zip(uvs.iter()).zip(triangle.iter_mut()) {
+                *out = *vertex_map
+                    .entry((*index, uv.x.to_bits()))
+                    .or_insert_with(|| {
+                        let normal = positions[*index as usize];
+                        vertices.push(StaticVertex::from_pos_uv_normal(
+                            normal.scale(radius),
+                            *uv,
+                            normal,
+                        ));
+                        (vertices.len() - 1) as u32
+                    });
             }
+            triangles.push(TriangleDefinition(triangle));
         }
 
         let mut data = Self::new(
@@ -700,6 +995,19 @@ impl SurfaceData {
         data
     }
 
+    /// Generates second texture coordinates (used by light maps) for the surface using [`utils::uvgen`]. Generated
+    /// primitives have only the first texture coordinates, so this method should be used if a primitive needs to be
+    /// light mapped. See [`utils::uvgen::generate_uvs`] for more info about `spacing`.
+    pub fn with_second_tex_coords(mut self, spacing: f32) -> Self {
+        if let Err(e) = utils::uvgen::generate_uvs(&mut self, spacing) {
+            Log::err(format!(
+                "Failed to generate second texture coordinates. Reason: {:?}",
+                e
+            ));
+        }
+        self
+    }
+
     /// Creates vertical cylinder with the given amount of sides, radius, height and optional caps. The larger the `sides`, the smoother the cylinder
     /// will be, typical values are [16..32]. `caps` defines whether the cylinder will have caps or not. The cylinder is transformed using the given
     /// transformation matrix, which could be [`Matrix4::identity`] to not modify the cylinder at all.
@@ -1028,13 +1336,216 @@ impl Visit for SurfaceData {
 
         if self.is_procedural {
             self.vertex_buffer.visit("VertexBuffer", &mut region)?;
-            self.geometry_buffer.visit("GeometryBuffer", &mut region)?
+            self.geometry_buffer.visit("GeometryBuffer", &mut region)?;
+            // Surfaces saved before blend shapes were serialized do not have them.
+            let _ = self
+                .blend_shapes_container
+                .visit("BlendShapesContainer", &mut region);
         }
 
         Ok(())
     }
 }
 
+fn calculate_tangents(
+    vertex_buffer: &mut VertexBuffer,
+    geometry_buffer: &TriangleBuffer,
+) -> Result<(), VertexFetchError> {
+    let mut tan1 = vec![Vector3::default(); vertex_buffer.vertex_count() as usize];
+    let mut tan2 = vec![Vector3::default(); vertex_buffer.vertex_count() as usize];
+
+    for triangle in geometry_buffer.iter() {
+        let i1 = triangle[0] as usize;
+        let i2 = triangle[1] as usize;
+        let i3 = triangle[2] as usize;
+
+        let view1 = &vertex_buffer.get(i1).unwrap();
+        let view2 = &vertex_buffer.get(i2).unwrap();
+        let view3 = &vertex_buffer.get(i3).unwrap();
+
+        let v1 = view1.read_3_f32(VertexAttributeUsage::Position)?;
+        let v2 = view2.read_3_f32(VertexAttributeUsage::Position)?;
+        let v3 = view3.read_3_f32(VertexAttributeUsage::Position)?;
+
+        let w1 = view1.read_3_f32(VertexAttributeUsage::TexCoord0)?;
+        let w2 = view2.read_3_f32(VertexAttributeUsage::TexCoord0)?;
+        let w3 = view3.read_3_f32(VertexAttributeUsage::TexCoord0)?;
+
+        let x1 = v2.x - v1.x;
+        let x2 = v3.x - v1.x;
+        let y1 = v2.y - v1.y;
+        let y2 = v3.y - v1.y;
+        let z1 = v2.z - v1.z;
+        let z2 = v3.z - v1.z;
+
+        let s1 = w2.x - w1.x;
+        let s2 = w3.x - w1.x;
+        let t1 = w2.y - w1.y;
+        let t2 = w3.y - w1.y;
+
+        let r = 1.0 / (s1 * t2 - s2 * t1);
+
+        let sdir = Vector3::new(
+            (t2 * x1 - t1 * x2) * r,
+            (t2 * y1 - t1 * y2) * r,
+            (t2 * z1 - t1 * z2) * r,
+        );
+
+        tan1[i1] += sdir;
+        tan1[i2] += sdir;
+        tan1[i3] += sdir;
+
+        let tdir = Vector3::new(
+            (s1 * x2 - s2 * x1) * r,
+            (s1 * y2 - s2 * y1) * r,
+            (s1 * z2 - s2 * z1) * r,
+        );
+        tan2[i1] += tdir;
+        tan2[i2] += tdir;
+        tan2[i3] += tdir;
+    }
+
+    let mut vertex_buffer_mut = vertex_buffer.modify();
+    for (mut view, (t1, t2)) in vertex_buffer_mut.iter_mut().zip(tan1.into_iter().zip(tan2)) {
+        let normal = view.read_3_f32(VertexAttributeUsage::Normal)?;
+
+        // Gram-Schmidt orthogonalize
+        let tangent = (t1 - normal.scale(normal.dot(&t1)))
+            .try_normalize(f32::EPSILON)
+            .unwrap_or_else(|| Vector3::new(0.0, 1.0, 0.0));
+        let handedness = normal.cross(&t1).dot(&t2).signum();
+        view.write_4_f32(
+            VertexAttributeUsage::Tangent,
+            Vector4::new(tangent.x, tangent.y, tangent.z, handedness),
+        )?;
+    }
+
+    Ok(())
+}
+
+/// See [`SurfaceData::modify`] for more info.
+pub struct SurfaceDataRefMut<'a> {
+    /// Vertex buffer of the surface.
+    pub vertex_buffer: VertexBufferRefMut<'a>,
+    /// Triangle buffer of the surface.
+    pub geometry_buffer: TriangleBufferRefMut<'a>,
+}
+
+impl<'a> SurfaceDataRefMut<'a> {
+    /// Returns total amount of vertices in the surface.
+    pub fn vertex_count(&self) -> usize {
+        self.vertex_buffer.vertex_count() as usize
+    }
+
+    /// Returns position of a vertex at the given index.
+    pub fn position(&self, index: usize) -> Result<Vector3<f32>, VertexFetchError> {
+        self.vertex_buffer
+            .get(index)
+            .ok_or(VertexFetchError::InvalidIndex(index))?
+            .read_3_f32(VertexAttributeUsage::Position)
+    }
+
+    /// Sets new position of a vertex at the given index.
+    pub fn set_position(
+        &mut self,
+        index: usize,
+        position: Vector3<f32>,
+    ) -> Result<(), VertexFetchError> {
+        self.vertex_buffer
+            .get_mut(index)
+            .ok_or(VertexFetchError::InvalidIndex(index))?
+            .write_3_f32(VertexAttributeUsage::Position, position)
+    }
+
+    /// Returns first texture coordinates of a vertex at the given index.
+    pub fn tex_coord(&self, index: usize) -> Result<Vector2<f32>, VertexFetchError> {
+        self.vertex_buffer
+            .get(index)
+            .ok_or(VertexFetchError::InvalidIndex(index))?
+            .read_2_f32(VertexAttributeUsage::TexCoord0)
+    }
+
+    /// Sets new first texture coordinates of a vertex at the given index.
+    pub fn set_tex_coord(
+        &mut self,
+        index: usize,
+        tex_coord: Vector2<f32>,
+    ) -> Result<(), VertexFetchError> {
+        self.vertex_buffer
+            .get_mut(index)
+            .ok_or(VertexFetchError::InvalidIndex(index))?
+            .write_2_f32(VertexAttributeUsage::TexCoord0, tex_coord)
+    }
+}
+
+impl<'a> Drop for SurfaceDataRefMut<'a> {
+    fn drop(&mut self) {
+        if self
+            .vertex_buffer
+            .has_attribute(VertexAttributeUsage::Normal)
+        {
+            Log::verify(calculate_smooth_normals(
+                &mut self.vertex_buffer,
+                &self.geometry_buffer,
+            ));
+
+            if self
+                .vertex_buffer
+                .has_attribute(VertexAttributeUsage::Tangent)
+                && self
+                    .vertex_buffer
+                    .has_attribute(VertexAttributeUsage::TexCoord0)
+            {
+                Log::verify(calculate_tangents(
+                    &mut self.vertex_buffer,
+                    &self.geometry_buffer,
+                ));
+            }
+        }
+
+        // Hashes of the buffers will be recalculated when inner guards are dropped.
+    }
+}
+
+fn calculate_smooth_normals(
+    vertex_buffer: &mut VertexBuffer,
+    geometry_buffer: &TriangleBuffer,
+) -> Result<(), VertexFetchError> {
+    let mut normals = vec![Vector3::<f32>::default(); vertex_buffer.vertex_count() as usize];
+
+    for triangle in geometry_buffer.iter() {
+        let fetch = |i: u32| {
+            vertex_buffer
+                .get(i as usize)
+                .ok_or(VertexFetchError::InvalidIndex(i as usize))?
+                .read_3_f32(VertexAttributeUsage::Position)
+        };
+
+        let a = fetch(triangle[0])?;
+        let b = fetch(triangle[1])?;
+        let c = fetch(triangle[2])?;
+
+        // Length of the cross product is a doubled area of the triangle, so there is no need to
+        // normalize it - larger triangles will have more influence on the resulting normal.
+        let weighted_normal = (b - a).cross(&(c - a));
+        for &index in triangle.indices() {
+            normals[index as usize] += weighted_normal;
+        }
+    }
+
+    let mut vertex_buffer_mut = vertex_buffer.modify();
+    for (mut view, normal) in vertex_buffer_mut.iter_mut().zip(normals) {
+        view.write_3_f32(
+            VertexAttributeUsage::Normal,
+            normal
+                .try_normalize(f32::EPSILON)
+                .unwrap_or_else(Vector3::y),
+        )?;
+    }
+
+    Ok(())
+}
+
 /// Vertex weight is a pair of (bone; weight) that affects vertex.
 #[derive(Copy, Clone, PartialEq, Debug)]
 pub struct VertexWeight {
@@ -1317,6 +1828,53 @@ impl Surface {
         }
     }
 
+    /// Creates new surface with a capsule with standard material. See [`SurfaceData::make_capsule`] for more info.
+    pub fn capsule(radius: f32, height: f32, segments: usize, rings: usize) -> Self {
+        Self::new(SurfaceSharedData::new(SurfaceData::make_capsule(
+            radius,
+            height,
+            segments,
+            rings,
+            &Matrix4::identity(),
+        )))
+    }
+
+    /// Creates new surface with a subdivided plane with standard material. See [`SurfaceData::make_plane`] for more info.
+    pub fn plane(width: f32, depth: f32, subdivisions_x: usize, subdivisions_z: usize) -> Self {
+        Self::new(SurfaceSharedData::new(SurfaceData::make_plane(
+            width,
+            depth,
+            subdivisions_x,
+            subdivisions_z,
+            &Matrix4::identity(),
+        )))
+    }
+
+    /// Creates new surface with an icosphere with standard material. See [`SurfaceData::make_icosphere`] for more info.
+    pub fn icosphere(radius: f32, subdivisions: usize) -> Self {
+        Self::new(SurfaceSharedData::new(SurfaceData::make_icosphere(
+            radius,
+            subdivisions,
+            &Matrix4::identity(),
+        )))
+    }
+
+    /// Creates new surface with a torus with standard material. See [`SurfaceData::make_torus`] for more info.
+    pub fn torus(
+        inner_radius: f32,
+        outer_radius: f32,
+        num_rings: usize,
+        num_segments: usize,
+    ) -> Self {
+        Self::new(SurfaceSharedData::new(SurfaceData::make_torus(
+            inner_radius,
+            outer_radius,
+            num_rings,
+            num_segments,
+            &Matrix4::identity(),
+        )))
+    }
+
     /// Calculates material id.
     pub fn material_id(&self) -> u64 {
         self.material.key()
@@ -1420,3 +1978,122 @@ impl SurfaceBuilder {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::algebra::{Matrix4, Vector3},
+        fxhash::{FxHashMap, FxHashSet},
+        scene::mesh::{
+            buffer::{VertexAttributeUsage, VertexReadTrait},
+            surface::SurfaceData,
+        },
+    };
+
+    // Checks that the surface is edge-manifold (closed if `closed` is set), triangles are wound
+    // counter-clockwise when looking at them from the side pointed by `outward`, vertex normals
+    // agree with face normals and texture coordinates are not degenerate.
+    fn check_primitive<F>(data: &SurfaceData, closed: bool, outward: F)
+    where
+        F: Fn(Vector3<f32>) -> Vector3<f32>,
+    {
+        let mut welded = FxHashMap::default();
+        let mut vertices = Vec::new();
+        for view in data.vertex_buffer.iter() {
+            let position = view.read_3_f32(VertexAttributeUsage::Position).unwrap();
+            let key = position.map(|c| (c * 10000.0).round() as i32);
+            let count = welded.len();
+            let id = *welded.entry((key.x, key.y, key.z)).or_insert(count);
+            vertices.push((
+                id,
+                position,
+                view.read_3_f32(VertexAttributeUsage::Normal).unwrap(),
+                view.read_2_f32(VertexAttributeUsage::TexCoord0).unwrap(),
+            ));
+        }
+
+        let mut edges = FxHashSet::default();
+        for triangle in data.geometry_buffer.iter() {
+            let [a, b, c] = triangle.0.map(|i| vertices[i as usize]);
+
+            assert!(a.0 != b.0 && b.0 != c.0 && c.0 != a.0);
+            for edge in [(a.0, b.0), (b.0, c.0), (c.0, a.0)] {
+                // Every directed edge must be used once, otherwise the surface is not manifold or
+                // has inconsistent winding.
+                assert!(edges.insert(edge));
+            }
+
+            let face_normal = (b.1 - a.1).cross(&(c.1 - a.1));
+            assert!(face_normal.norm() > 1.0e-6);
+            let center = (a.1 + b.1 + c.1).scale(1.0 / 3.0);
+            assert!(face_normal.dot(&outward(center)) > 0.0);
+            for vertex in [a, b, c] {
+                assert!(vertex.2.dot(&face_normal) > 0.0);
+            }
+
+            let uv_area = (b.3 - a.3).perp(&(c.3 - a.3));
+            assert!(uv_area.abs() > 1.0e-9);
+        }
+
+        if closed {
+            for (a, b) in edges.iter() {
+                assert!(edges.contains(&(*b, *a)));
+            }
+        }
+    }
+
+    #[test]
+    fn test_primitives() {
+        let sphere = SurfaceData::make_icosphere(2.0, 3, &Matrix4::identity());
+        assert_eq!(sphere.geometry_buffer.len(), 20 * 4 * 4 * 4);
+        check_primitive(&sphere, true, |p| p);
+
+        for height in [0.0, 1.0] {
+            let capsule = SurfaceData::make_capsule(0.5, height, 16, 6, &Matrix4::identity());
+            check_primitive(&capsule, true, |p| {
+                p - Vector3::new(0.0, p.y.clamp(-height * 0.5, height * 0.5), 0.0)
+            });
+        }
+
+        let torus = SurfaceData::make_torus(1.0, 0.25, 16, 12, &Matrix4::identity());
+        check_primitive(&torus, true, |p| {
+            p - Vector3::new(p.x, 0.0, p.z).normalize()
+        });
+
+        let plane = SurfaceData::make_plane(2.0, 3.0, 4, 5, &Matrix4::identity());
+        assert_eq!(plane.geometry_buffer.len(), 4 * 5 * 2);
+        check_primitive(&plane, false, |_| Vector3::y());
+    }
+
+    #[test]
+    fn test_modify_cube_into_sphere() {
+        let mut data = SurfaceData::make_cube(Matrix4::identity());
+        let hash = data.content_hash();
+
+        {
+            let mut modifier = data.modify();
+            for i in 0..modifier.vertex_count() {
+                let position = modifier.position(i).unwrap();
+                modifier.set_position(i, position.normalize()).unwrap();
+            }
+            assert!(modifier.position(modifier.vertex_count()).is_err());
+        }
+
+        assert_ne!(data.content_hash(), hash);
+
+        let bounding_box = data.calculate_bounding_box();
+        for view in data.vertex_buffer.iter() {
+            let position = view.read_3_f32(VertexAttributeUsage::Position).unwrap();
+            assert!((position.norm() - 1.0).abs() <= 0.0001);
+            assert!(bounding_box.is_contains_point(position));
+
+            let normal = view.read_3_f32(VertexAttributeUsage::Normal).unwrap();
+            assert!((normal.norm() - 1.0).abs() <= 0.0001);
+            // Normals must point outward.
+            assert!(normal.dot(&position) > 0.0);
+
+            let tangent = view.read_4_f32(VertexAttributeUsage::Tangent).unwrap();
+            assert!(tangent.xyz().dot(&normal).abs() <= 0.0001);
+        }
+    }
+}
@@ -12,6 +12,7 @@ pub mod collider;
 pub mod debug;
 pub mod decal;
 pub mod dim2;
+pub mod environment;
 pub mod graph;
 pub mod joint;
 pub mod light;
@@ -22,6 +23,8 @@ pub mod node;
 pub mod particle_system;
 pub mod pivot;
 pub mod ragdoll;
+pub mod reflection_probe;
+pub mod replication;
 pub mod rigidbody;
 pub mod sound;
 pub mod sprite;
@@ -47,7 +50,8 @@ use crate::{
     scene::{
         base::BaseBuilder,
         camera::Camera,
-        debug::SceneDrawingContext,
+        debug::{DebugDrawFlags, SceneDrawingContext},
+        environment::EnvironmentSettings,
         graph::{map::NodeHandleMap, Graph, GraphPerformanceStatistics, GraphUpdateSwitches},
         mesh::{
             buffer::{
@@ -58,9 +62,10 @@ use crate::{
         },
         navmesh::NavigationalMeshBuilder,
         node::Node,
+        replication::{Replication, SceneSnapshot},
         sound::SoundEngine,
     },
-    utils::{lightmap::Lightmap, navmesh::Navmesh},
+    utils::{self, lightmap::Lightmap, navmesh::Navmesh, NameProvider},
 };
 use fxhash::{FxHashMap, FxHashSet};
 use std::path::PathBuf;
@@ -151,6 +156,10 @@ impl IndexMut<Handle<Navmesh>> for NavMeshContainer {
 /// See module docs.
 #[derive(Debug, Reflect)]
 pub struct Scene {
+    /// Name of the scene. It is not required to be unique, but it is used to find scenes by their names in
+    /// [`SceneContainer`] (see [`SceneContainer::find_by_name`]).
+    pub name: String,
+
     /// Graph is main container for all scene nodes. It calculates global transforms for nodes,
     /// updates them and performs all other important work. See `graph` module docs for more
     /// info.
@@ -169,6 +178,12 @@ pub struct Scene {
     #[reflect(hidden)]
     pub drawing_context: SceneDrawingContext,
 
+    /// A set of entities (cameras, lights, sounds, etc.), that will be visualized using the drawing
+    /// context on every update. Default is [`DebugDrawFlags::NONE`]. The flags are not saved together
+    /// with the scene, because debug visualization is a development tool only.
+    #[reflect(hidden)]
+    pub debug_draw_flags: DebugDrawFlags,
+
     /// Current lightmap.
     lightmap: Option<Lightmap>,
 
@@ -176,8 +191,9 @@ pub struct Scene {
     #[reflect(hidden)]
     pub performance_statistics: PerformanceStatistics,
 
-    /// Color of ambient lighting.
-    pub ambient_lighting_color: Color,
+    /// Environment settings of the scene (ambient lighting, fog, sky, etc.). See [`EnvironmentSettings`]
+    /// docs for more info.
+    pub environment: EnvironmentSettings,
 
     /// Whether the scene will be updated and rendered or not. Default is true.
     /// This flag allowing you to build a scene manager for your game. For example,
@@ -186,24 +202,68 @@ pub struct Scene {
     /// loaded and playing a game. When you're start playing, just set `enabled` flag
     /// to false for menu's scene and when you need to open a menu - set it to true and
     /// set `enabled` flag to false for level's scene.
+    ///
+    /// This flag is a master switch, use [`Self::update_enabled`] and [`Self::render_enabled`]
+    /// to control updating and rendering separately.
     pub enabled: bool,
 
+    /// Whether the scene will be updated (graph, physics, scripts, etc.) or not. Default is true.
+    /// It could be used to pause the scene while keeping it visible (freeze-frame). Has no effect
+    /// if the scene is disabled (see [`Self::enabled`]).
+    pub update_enabled: bool,
+
+    /// Whether the scene will be rendered or not. Default is true. It could be used to keep
+    /// the scene simulating while it is hidden (for example, to prepare it in background). Has
+    /// no effect if the scene is disabled (see [`Self::enabled`]).
+    pub render_enabled: bool,
+
     /// Defines how polygons of the scene will be rasterized. By default it set to [`PolygonFillMode::Fill`],
     /// [`PolygonFillMode::Line`] could be used to render the scene in wireframe mode.
     pub polygon_rasterization_mode: PolygonFillMode,
+
+    /// Defines the order in which scenes are updated and rendered, scenes with higher priority go
+    /// first. Scenes with the same priority keep the order of the scene container. Default is 0. It
+    /// could be used to make cross-scene dependencies deterministic, for example a scene that is
+    /// rendered into a texture should have higher priority than a scene that displays the texture.
+    pub update_priority: i32,
+
+    /// Time scale of the scene, it is multiplied with the time delta passed by the engine to the
+    /// scene and its scripts, so each scene could have its own slow motion. Use `0.0` to pause the
+    /// scene: animations, particles and physics will hold their state and sounds will be paused.
+    /// Negative values are treated as zero. Default is `1.0`. See also [`crate::engine::Engine::set_time_scale`].
+    #[reflect(min_value = 0.0, step = 0.1)]
+    pub time_scale: f32,
+
+    /// A registry of nodes, which transforms are synchronized over network. See [`Replication`]
+    /// docs for more info.
+    #[reflect(hidden)]
+    pub replication: Replication,
+}
+
+impl NameProvider for Scene {
+    fn name(&self) -> &str {
+        &self.name
+    }
 }
 
 impl Default for Scene {
     fn default() -> Self {
         Self {
+            name: Default::default(),
             graph: Default::default(),
             render_target: None,
             lightmap: None,
             drawing_context: Default::default(),
+            debug_draw_flags: Default::default(),
             performance_statistics: Default::default(),
-            ambient_lighting_color: Color::opaque(100, 100, 100),
+            environment: Default::default(),
             enabled: true,
+            update_enabled: true,
+            render_enabled: true,
             polygon_rasterization_mode: Default::default(),
+            update_priority: 0,
+            time_scale: 1.0,
+            replication: Default::default(),
         }
     }
 }
@@ -357,17 +417,38 @@ impl Scene {
     pub fn new() -> Self {
         Self {
             // Graph must be created with `new` method because it differs from `default`
+            name: Default::default(),
             graph: Graph::new(),
             render_target: None,
             lightmap: None,
             drawing_context: Default::default(),
+            debug_draw_flags: Default::default(),
             performance_statistics: Default::default(),
-            ambient_lighting_color: Color::opaque(100, 100, 100),
+            environment: Default::default(),
             enabled: true,
+            update_enabled: true,
+            render_enabled: true,
             polygon_rasterization_mode: Default::default(),
+            update_priority: 0,
+            time_scale: 1.0,
+            replication: Default::default(),
         }
     }
 
+    /// Returns `true` if the scene should be updated, `false` - otherwise. See [`Self::enabled`]
+    /// and [`Self::update_enabled`] for more info.
+    #[inline]
+    pub fn is_update_enabled(&self) -> bool {
+        self.enabled && self.update_enabled
+    }
+
+    /// Returns `true` if the scene should be rendered, `false` - otherwise. See [`Self::enabled`]
+    /// and [`Self::render_enabled`] for more info.
+    #[inline]
+    pub fn is_render_enabled(&self) -> bool {
+        self.enabled && self.render_enabled
+    }
+
     /// Synchronizes the state of the scene with external resources.
     pub fn resolve(&mut self) {
         Log::writeln(MessageKind::Information, "Starting resolve...");
@@ -375,6 +456,10 @@ impl Scene {
         self.graph.resolve();
         self.graph.update_hierarchical_data();
 
+        if let Some(skybox) = self.environment.skybox.as_mut() {
+            Log::verify(skybox.create_cubemap());
+        }
+
         // Re-apply lightmap if any. This has to be done after resolve because we must patch surface
         // data at this stage, but if we'd do this before we wouldn't be able to do this because
         // meshes contains invalid surface data.
@@ -510,12 +595,59 @@ impl Scene {
         Ok(std::mem::replace(&mut self.lightmap, Some(lightmap)))
     }
 
+    /// Returns ambient lighting color of the scene.
+    #[deprecated(
+        since = "0.32.0",
+        note = "ambient lighting color was moved to the environment settings, use `environment.ambient_lighting_color` instead"
+    )]
+    pub fn ambient_lighting_color(&self) -> Color {
+        self.environment.ambient_lighting_color
+    }
+
+    /// Sets new ambient lighting color of the scene and returns the old one.
+    #[deprecated(
+        since = "0.32.0",
+        note = "ambient lighting color was moved to the environment settings, use `environment.ambient_lighting_color` instead"
+    )]
+    pub fn set_ambient_lighting_color(&mut self, color: Color) -> Color {
+        std::mem::replace(&mut self.environment.ambient_lighting_color, color)
+    }
+
+    /// Returns time delta, scaled by the time scale of the scene (see [`Self::time_scale`]).
+    pub fn scaled_dt(&self, dt: f32) -> f32 {
+        dt * self.time_scale.max(0.0)
+    }
+
     /// Performs single update tick with given delta time from last frame. Internally
     /// it updates physics, animations, and each graph node. In most cases there is
     /// no need to call it directly, engine automatically updates all available scenes.
     pub fn update(&mut self, frame_size: Vector2<f32>, dt: f32, switches: GraphUpdateSwitches) {
         self.graph.update(frame_size, dt, switches);
+
+        if let Some(skybox) = self.environment.skybox.as_mut() {
+            skybox.update();
+        }
         self.performance_statistics.graph = self.graph.performance_statistics.clone();
+
+        self.drawing_context.update(dt);
+
+        if !self.debug_draw_flags.is_empty() {
+            self.drawing_context
+                .draw_graph(&self.graph, self.debug_draw_flags);
+        }
+    }
+
+    /// Captures transforms of replicated nodes, that were changed since the previous capture. See
+    /// [`Replication::capture`] for more info.
+    pub fn capture_snapshot(&mut self) -> SceneSnapshot {
+        self.replication.capture(&self.graph)
+    }
+
+    /// Applies the received snapshot, replicated nodes are moved toward received transforms by the
+    /// given interpolation factor. See [`Replication::apply`] for more info.
+    pub fn apply_snapshot(&mut self, snapshot: &SceneSnapshot, interpolation: f32) {
+        self.replication
+            .apply(&mut self.graph, snapshot, interpolation)
     }
 
     /// Creates deep copy of a scene, filter predicate allows you to filter out nodes
@@ -543,18 +675,34 @@ impl Scene {
             lightmap.map = map;
         }
 
+        let mut replication = self.replication.clone();
+        replication.remap_handles(|mut handle| {
+            if old_new_map.try_map(&mut handle) {
+                Some(handle)
+            } else {
+                None
+            }
+        });
+
         (
             Self {
+                name: self.name.clone(),
                 graph,
                 // Render target is intentionally not copied, because it does not makes sense - a copy
                 // will redraw frame completely.
                 render_target: Default::default(),
                 lightmap,
                 drawing_context: self.drawing_context.clone(),
+                debug_draw_flags: self.debug_draw_flags,
                 performance_statistics: Default::default(),
-                ambient_lighting_color: self.ambient_lighting_color,
+                environment: self.environment.clone(),
                 enabled: self.enabled,
+                update_enabled: self.update_enabled,
+                render_enabled: self.render_enabled,
                 polygon_rasterization_mode: self.polygon_rasterization_mode,
+                update_priority: self.update_priority,
+                time_scale: self.time_scale,
+                replication,
             },
             old_new_map,
         )
@@ -563,14 +711,24 @@ impl Scene {
     fn visit(&mut self, region_name: &str, visitor: &mut Visitor) -> VisitResult {
         let mut region = visitor.enter_region(region_name)?;
 
+        let _ = self.name.visit("Name", &mut region);
         self.graph.visit("Graph", &mut region)?;
         self.lightmap.visit("Lightmap", &mut region)?;
-        self.ambient_lighting_color
-            .visit("AmbientLightingColor", &mut region)?;
+        if self.environment.visit("Environment", &mut region).is_err() {
+            // Backward compatibility.
+            self.environment
+                .ambient_lighting_color
+                .visit("AmbientLightingColor", &mut region)?;
+        }
         self.enabled.visit("Enabled", &mut region)?;
+        let _ = self.update_enabled.visit("UpdateEnabled", &mut region);
+        let _ = self.render_enabled.visit("RenderEnabled", &mut region);
         let _ = self
             .polygon_rasterization_mode
             .visit("PolygonRasterizationMode", &mut region);
+        let _ = self.update_priority.visit("UpdatePriority", &mut region);
+        let _ = self.time_scale.visit("TimeScale", &mut region);
+        let _ = self.replication.visit("Replication", &mut region);
 
         // Backward compatibility.\
         let mut navmeshes = NavMeshContainer::default();
@@ -594,7 +752,7 @@ impl Scene {
     ///
     /// ```rust,no_run
     /// # use fyrox::{
-    /// #     core::visitor::Visitor,
+    /// #     core::visitor::{Visitor, VisitorFormat},
     /// #     scene::{
     /// #         base::BaseBuilder,
     /// #         mesh::{
@@ -621,6 +779,12 @@ impl Scene {
     ///
     /// // Write the data to a file.
     /// visitor.save_binary("path/to/a/scene.rgs").unwrap();
+    ///
+    /// // Or in human-readable text format, which is useful to track changes in version control
+    /// // systems. Scene loader detects the format automatically.
+    /// visitor
+    ///     .save_to_file("path/to/a/scene.rgs", VisitorFormat::Text)
+    ///     .unwrap();
     /// ```
     pub fn save(&mut self, region_name: &str, visitor: &mut Visitor) -> VisitResult {
         if visitor.is_reading() {
@@ -701,6 +865,56 @@ impl SceneContainer {
         self.pool.clear()
     }
 
+    /// Removes every scene that does not satisfy the given predicate. Removed scenes will be destroyed
+    /// on a next update call (the same as [`Self::remove`]), handles of the retained scenes stay valid.
+    pub fn retain<F>(&mut self, mut pred: F)
+    where
+        F: FnMut(Handle<Scene>, &mut Scene) -> bool,
+    {
+        let removed = self
+            .pool
+            .drain_filter(|handle, scene| !pred(handle, scene))
+            .collect::<Vec<_>>();
+        for (handle, scene) in removed {
+            self.sound_engine
+                .state()
+                .remove_context(scene.graph.sound_context.native.clone());
+            self.destruction_list.push((handle, scene));
+        }
+    }
+
+    /// Creates an iterator, that removes every scene that satisfies the given predicate from the
+    /// container and transfers its ownership to the caller. Unlike [`Self::remove`], scripts of the
+    /// removed scenes won't be deinitialized. Handles of the remaining scenes stay valid.
+    pub fn drain_filter<'a, F>(
+        &'a mut self,
+        pred: F,
+    ) -> impl Iterator<Item = (Handle<Scene>, Scene)> + 'a
+    where
+        F: FnMut(Handle<Scene>, &mut Scene) -> bool + 'a,
+    {
+        let sound_engine = &self.sound_engine;
+        self.pool.drain_filter(pred).map(move |(handle, scene)| {
+            sound_engine
+                .state()
+                .remove_context(scene.graph.sound_context.native.clone());
+            (handle, scene)
+        })
+    }
+
+    /// Returns handles of the scenes in the order they should be updated and rendered. See
+    /// [`Scene::update_priority`] for more info.
+    pub fn handles_by_priority(&self) -> Vec<Handle<Scene>> {
+        let mut handles = self
+            .pool
+            .pair_iter()
+            .map(|(handle, scene)| (handle, scene.update_priority))
+            .collect::<Vec<_>>();
+        // Sort is stable, so scenes with the same priority keep their order.
+        handles.sort_by_key(|(_, priority)| std::cmp::Reverse(*priority));
+        handles.into_iter().map(|(handle, _)| handle).collect()
+    }
+
     /// Removes given scene from container. The scene will be destroyed on a next update call.
     #[inline]
     pub fn remove(&mut self, handle: Handle<Scene>) {
@@ -710,6 +924,19 @@ impl SceneContainer {
         self.destruction_list.push((handle, self.pool.free(handle)));
     }
 
+    /// Tries to find a scene by its name. Returns a handle of the first scene with the given name.
+    pub fn find_by_name<S: AsRef<str>>(&self, name: S) -> Option<Handle<Scene>> {
+        utils::find_by_name_ref(self.pool.pair_iter(), name).map(|(handle, _)| handle)
+    }
+
+    /// Removes a scene with the given name from the container and returns its handle. The scene will
+    /// be destroyed on a next update call. Returns `None` if there is no such scene.
+    pub fn remove_by_name<S: AsRef<str>>(&mut self, name: S) -> Option<Handle<Scene>> {
+        let handle = self.find_by_name(name)?;
+        self.remove(handle);
+        Some(handle)
+    }
+
     /// Takes scene from the container and transfers ownership to caller. You must either
     /// put scene back using ticket or call `forget_ticket` to make memory used by scene
     /// vacant again.
@@ -743,3 +970,81 @@ impl IndexMut<Handle<Scene>> for SceneContainer {
         &mut self.pool[index]
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::color::Color,
+        scene::{Scene, SceneContainer},
+    };
+
+    #[test]
+    #[allow(deprecated)]
+    fn test_deprecated_ambient_lighting_color() {
+        let mut scene = Scene::new();
+        let default = scene.environment.ambient_lighting_color;
+        assert_eq!(scene.ambient_lighting_color(), default);
+
+        assert_eq!(scene.set_ambient_lighting_color(Color::RED), default);
+        assert_eq!(scene.environment.ambient_lighting_color, Color::RED);
+        assert_eq!(scene.ambient_lighting_color(), Color::RED);
+    }
+
+    #[test]
+    fn test_find_and_remove_by_name() {
+        let mut scenes = SceneContainer::new(Default::default());
+
+        let menu = scenes.add(Scene {
+            name: "Menu".to_string(),
+            ..Default::default()
+        });
+        let level = scenes.add(Scene {
+            name: "Level".to_string(),
+            ..Default::default()
+        });
+
+        assert_eq!(scenes.find_by_name("Menu"), Some(menu));
+        assert_eq!(scenes.find_by_name("Level"), Some(level));
+        assert_eq!(scenes.find_by_name("Foo"), None);
+
+        assert_eq!(scenes.remove_by_name("Menu"), Some(menu));
+        assert_eq!(scenes.find_by_name("Menu"), None);
+        assert_eq!(scenes.remove_by_name("Menu"), None);
+        assert!(scenes.is_valid_handle(level));
+    }
+
+    #[test]
+    fn test_retain_drain_filter_and_priority() {
+        let mut scenes = SceneContainer::new(Default::default());
+
+        let mut add = |name: &str, update_priority| {
+            scenes.add(Scene {
+                name: name.to_string(),
+                update_priority,
+                ..Default::default()
+            })
+        };
+        let display = add("Display", 0);
+        let monitor = add("Monitor", 10);
+        let menu = add("Menu", 0);
+        let temp = add("Temp", -5);
+
+        assert_eq!(
+            scenes.handles_by_priority(),
+            vec![monitor, display, menu, temp]
+        );
+
+        scenes.retain(|handle, _| handle != temp);
+        assert!(!scenes.is_valid_handle(temp));
+        assert_eq!(scenes.destruction_list.len(), 1);
+
+        let drained = scenes
+            .drain_filter(|_, scene| scene.name == "Menu")
+            .map(|(handle, _)| handle)
+            .collect::<Vec<_>>();
+        assert_eq!(drained, vec![menu]);
+        assert!(scenes.is_valid_handle(display));
+        assert!(scenes.is_valid_handle(monitor));
+        assert_eq!(scenes.handles_by_priority(), vec![monitor, display]);
+    }
+}
@@ -15,6 +15,7 @@ use crate::{
         particle_system::ParticleSystem,
         pivot::Pivot,
         ragdoll::Ragdoll,
+        reflection_probe::ReflectionProbe,
         sound::{listener::Listener, Sound},
         sprite::Sprite,
         terrain::Terrain,
@@ -59,6 +60,7 @@ impl NodeConstructorContainer {
         container.add::<AnimationBlendingStateMachine>();
         container.add::<NavigationalMesh>();
         container.add::<Ragdoll>();
+        container.add::<ReflectionProbe>();
 
         container
     }
@@ -30,11 +30,13 @@ use crate::{
         particle_system::ParticleSystem,
         pivot::Pivot,
         ragdoll::Ragdoll,
+        reflection_probe::ReflectionProbe,
         sound::{context::SoundContext, listener::Listener, Sound},
         sprite::Sprite,
         terrain::Terrain,
         Scene,
     },
+    utils::{component::ComponentProvider, NameProvider},
 };
 use std::{
     any::{Any, TypeId},
@@ -313,6 +315,33 @@ impl DerefMut for Node {
     }
 }
 
+impl NameProvider for Node {
+    fn name(&self) -> &str {
+        self.0.name()
+    }
+}
+
+/// Gives access to the components of the node itself (see [`NodeTrait::query_component_ref`]) and,
+/// if the node does not have a component of the requested type, to the components of its script.
+/// This allows to query engine and user types in the same way, see [`Graph::typed_iter`].
+impl ComponentProvider for Node {
+    fn query_component_ref(&self, type_id: TypeId) -> Option<&dyn Any> {
+        self.0.query_component_ref(type_id).or_else(|| {
+            self.script()
+                .and_then(|s| ComponentProvider::query_component_ref(s, type_id))
+        })
+    }
+
+    fn query_component_mut(&mut self, type_id: TypeId) -> Option<&mut dyn Any> {
+        if self.0.query_component_ref(type_id).is_some() {
+            return self.0.query_component_mut(type_id);
+        }
+
+        self.script_mut()
+            .and_then(|s| ComponentProvider::query_component_mut(s, type_id))
+    }
+}
+
 /// Defines as_(variant), as_mut_(variant) and is_(variant) methods.
 #[macro_export]
 macro_rules! define_is_as {
@@ -465,6 +494,7 @@ impl Node {
     define_is_as!(AnimationBlendingStateMachine => fn is_absm, fn as_absm, fn as_absm_mut);
     define_is_as!(AnimationPlayer => fn is_animation_player, fn as_animation_player, fn as_animation_player_mut);
     define_is_as!(Ragdoll => fn is_ragdoll, fn as_ragdoll, fn as_ragdoll_mut);
+    define_is_as!(ReflectionProbe => fn is_reflection_probe, fn as_reflection_probe, fn as_reflection_probe_mut);
 }
 
 impl Visit for Node {
@@ -61,6 +61,12 @@ impl ParticleSystemRng {
         }
     }
 
+    /// Returns the seed of the PRNG.
+    #[inline]
+    pub fn seed(&self) -> u64 {
+        self.rng_seed
+    }
+
     /// Resets the state of PRNG.
     #[inline]
     pub fn reset(&mut self) {
@@ -105,6 +111,17 @@ impl Visit for ParticleSystemRng {
     }
 }
 
+/// Fixed time step that is used to simulate particle systems during prewarm.
+pub const PREWARM_TIME_STEP: f32 = 1.0 / 30.0;
+
+/// Maximum amount of simulation steps that could be done during prewarm. It prevents the engine from
+/// hanging when prewarm time is too large.
+pub const MAX_PREWARM_STEPS: usize = 10_000;
+
+/// Maximum amount of particles that could be spawned during prewarm. Prewarm stops when this limit is
+/// reached.
+pub const MAX_PREWARM_PARTICLES: u64 = 100_000;
+
 /// Particle system used to create visual effects that consists of many small parts,
 /// this can be smoke, fire, dust, sparks, etc. Particle system optimized to operate
 /// on many small parts, so it is much efficient to use particle system instead of
@@ -125,6 +142,22 @@ impl Visit for ParticleSystemRng {
 /// enough, alternatively amount of particles can be defined by some coefficient based on
 /// graphics quality settings.
 ///
+/// # Prewarm
+///
+/// Particle systems start empty and it could take few seconds until an effect (smoke column, waterfall,
+/// etc.) will "fill up". To prevent this, set prewarm time (see [`ParticleSystem::set_prewarm_time`]) -
+/// the particle system will simulate that many seconds in fixed time steps (see [`PREWARM_TIME_STEP`])
+/// right before its first visible frame. Prewarm simulation is bounded by [`MAX_PREWARM_STEPS`] and
+/// [`MAX_PREWARM_PARTICLES`]. Every particle that is due to be emitted within the prewarm window will
+/// be emitted during prewarm. Prewarm state is not saved and not copied, so loaded and cloned particle
+/// systems are prewarmed again.
+///
+/// # Determinism
+///
+/// Particle systems use their own pseudo-random numbers generator with a fixed seed, so two instances
+/// of the same effect will behave identically. Use [`ParticleSystem::set_rng_seed`] to make them
+/// deliberately different.
+///
 /// # Example
 ///
 /// Simple smoke effect can be create like so:
@@ -174,7 +207,7 @@ impl Visit for ParticleSystemRng {
 ///         .build(graph);
 /// }
 /// ```
-#[derive(Debug, Visit, Clone, Reflect)]
+#[derive(Debug, Visit, Reflect)]
 pub struct ParticleSystem {
     base: Base,
 
@@ -206,6 +239,34 @@ pub struct ParticleSystem {
 
     #[visit(optional)]
     rng: ParticleSystemRng,
+
+    #[visit(optional)]
+    #[reflect(setter = "set_prewarm_time", min_value = 0.0)]
+    prewarm_time: InheritableVariable<f32>,
+
+    #[visit(skip)]
+    #[reflect(hidden)]
+    prewarmed: bool,
+}
+
+impl Clone for ParticleSystem {
+    fn clone(&self) -> Self {
+        Self {
+            base: self.base.clone(),
+            emitters: self.emitters.clone(),
+            texture: self.texture.clone(),
+            acceleration: self.acceleration.clone(),
+            color_over_lifetime: self.color_over_lifetime.clone(),
+            soft_boundary_sharpness_factor: self.soft_boundary_sharpness_factor.clone(),
+            is_playing: self.is_playing.clone(),
+            particles: self.particles.clone(),
+            free_particles: self.free_particles.clone(),
+            rng: self.rng.clone(),
+            prewarm_time: self.prewarm_time.clone(),
+            // Do not copy. The copy must be prewarmed on its own.
+            prewarmed: false,
+        }
+    }
 }
 
 impl Deref for ParticleSystem {
@@ -271,6 +332,60 @@ impl ParticleSystem {
             .set_value_and_mark_modified(factor)
     }
 
+    /// Sets new prewarm time (in seconds). The particle system will simulate that many seconds right
+    /// before its first visible frame, so the effect will look like it was playing for a while. See
+    /// [`Self::prewarm`] for more info.
+    pub fn set_prewarm_time(&mut self, time: f32) -> f32 {
+        self.prewarm_time.set_value_and_mark_modified(time.max(0.0))
+    }
+
+    /// Returns current prewarm time (in seconds).
+    pub fn prewarm_time(&self) -> f32 {
+        *self.prewarm_time
+    }
+
+    /// Sets new seed for the pseudo-random numbers generator of the particle system. Two particle
+    /// systems with the same parameters and the same seed will behave identically. The PRNG is reset
+    /// to the initial state with the new seed. Returns previous seed.
+    pub fn set_rng_seed(&mut self, seed: u64) -> u64 {
+        std::mem::replace(&mut self.rng, ParticleSystemRng::new(seed)).seed()
+    }
+
+    /// Returns current seed of the pseudo-random numbers generator of the particle system.
+    pub fn rng_seed(&self) -> u64 {
+        self.rng.seed()
+    }
+
+    /// Simulates the particle system for its prewarm time in fixed time steps (see [`PREWARM_TIME_STEP`]).
+    /// The state of the particle system is reset before the simulation. The simulation stops earlier if
+    /// either [`MAX_PREWARM_STEPS`] or [`MAX_PREWARM_PARTICLES`] is reached. There is no need to call
+    /// this method manually, it is called automatically right before the first visible frame of the
+    /// particle system.
+    pub fn prewarm(&mut self) {
+        self.prewarmed = true;
+
+        self.rng.reset();
+        self.clear_particles();
+
+        let time = *self.prewarm_time;
+        let mut t = 0.0;
+        let mut steps = 0;
+        while t < time && steps < MAX_PREWARM_STEPS {
+            self.tick(PREWARM_TIME_STEP);
+            t += PREWARM_TIME_STEP;
+            steps += 1;
+
+            let spawned = self
+                .emitters
+                .iter()
+                .map(|e| e.spawned_particles)
+                .sum::<u64>();
+            if spawned >= MAX_PREWARM_PARTICLES {
+                break;
+            }
+        }
+    }
+
     /// Replaces the particles in the particle system with pre-generated set. It could be useful
     /// to create procedural particle effects; when particles cannot be pre-made.
     pub fn set_particles(&mut self, particles: Vec<Particle>) {
@@ -491,6 +606,14 @@ impl NodeTrait for ParticleSystem {
         let dt = context.dt;
 
         if *self.is_playing {
+            if !self.prewarmed && self.global_visibility() {
+                if *self.prewarm_time > 0.0 {
+                    self.prewarm();
+                } else {
+                    self.prewarmed = true;
+                }
+            }
+
             self.tick(dt);
         }
     }
@@ -508,6 +631,7 @@ pub struct ParticleSystemBuilder {
     soft_boundary_sharpness_factor: f32,
     is_playing: bool,
     rng: ParticleSystemRng,
+    prewarm_time: f32,
 }
 
 impl ParticleSystemBuilder {
@@ -523,6 +647,7 @@ impl ParticleSystemBuilder {
             soft_boundary_sharpness_factor: 2.5,
             is_playing: true,
             rng: ParticleSystemRng::default(),
+            prewarm_time: 0.0,
         }
     }
 
@@ -581,6 +706,19 @@ impl ParticleSystemBuilder {
         self
     }
 
+    /// Sets desired pseudo-random numbers generator seed. See [`ParticleSystem::set_rng_seed`] for
+    /// more info.
+    pub fn with_rng_seed(mut self, seed: u64) -> Self {
+        self.rng = ParticleSystemRng::new(seed);
+        self
+    }
+
+    /// Sets desired prewarm time (in seconds). See [`ParticleSystem::prewarm`] for more info.
+    pub fn with_prewarm_time(mut self, time: f32) -> Self {
+        self.prewarm_time = time;
+        self
+    }
+
     fn build_particle_system(self) -> ParticleSystem {
         ParticleSystem {
             base: self.base_builder.build_base(),
@@ -593,6 +731,8 @@ impl ParticleSystemBuilder {
             soft_boundary_sharpness_factor: self.soft_boundary_sharpness_factor.into(),
             is_playing: self.is_playing.into(),
             rng: self.rng,
+            prewarm_time: self.prewarm_time.max(0.0).into(),
+            prewarmed: false,
         }
     }
 
@@ -606,3 +746,118 @@ impl ParticleSystemBuilder {
         graph.add_node(self.build_node())
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::{algebra::Vector3, visitor::prelude::*},
+        scene::{
+            base::BaseBuilder,
+            particle_system::{
+                emitter::{base::BaseEmitterBuilder, sphere::SphereEmitterBuilder},
+                ParticleSystem, ParticleSystemBuilder, MAX_PREWARM_PARTICLES, PREWARM_TIME_STEP,
+            },
+        },
+    };
+
+    fn make_particle_system(seed: u64, spawn_rate: u32, prewarm_time: f32) -> ParticleSystem {
+        ParticleSystemBuilder::new(BaseBuilder::new())
+            .with_emitters(vec![SphereEmitterBuilder::new(
+                BaseEmitterBuilder::new()
+                    .with_spawn_rate(spawn_rate)
+                    .with_lifetime_range(100.0..100.0)
+                    .with_x_velocity_range(-1.0..1.0),
+            )
+            .with_radius(1.0)
+            .build()])
+            .with_rng_seed(seed)
+            .with_prewarm_time(prewarm_time)
+            .build_particle_system()
+    }
+
+    fn positions(particle_system: &ParticleSystem) -> Vec<Vector3<f32>> {
+        particle_system
+            .particles()
+            .iter()
+            .filter(|p| p.alive)
+            .map(|p| p.position)
+            .collect()
+    }
+
+    #[test]
+    fn test_prewarm() {
+        let mut particle_system = make_particle_system(1, 100, 1.0);
+        assert!(particle_system.particles().is_empty());
+
+        particle_system.prewarm();
+        assert!(particle_system.prewarmed);
+        let count = positions(&particle_system).len();
+        assert!((95..=105).contains(&count));
+
+        // Prewarm resets the state, so it always gives the same result.
+        let first = positions(&particle_system);
+        particle_system.prewarm();
+        assert_eq!(positions(&particle_system), first);
+    }
+
+    #[test]
+    fn test_seed() {
+        let mut a = make_particle_system(123, 100, 1.0);
+        let mut b = make_particle_system(123, 100, 1.0);
+        let mut c = make_particle_system(321, 100, 1.0);
+        a.prewarm();
+        b.prewarm();
+        c.prewarm();
+        assert_eq!(positions(&a), positions(&b));
+        assert_ne!(positions(&a), positions(&c));
+
+        assert_eq!(c.set_rng_seed(123), 321);
+        assert_eq!(c.rng_seed(), 123);
+        c.prewarm();
+        assert_eq!(positions(&a), positions(&c));
+    }
+
+    #[test]
+    fn test_max_prewarm_particles() {
+        let spawn_rate = 1_000_000;
+        let mut particle_system = make_particle_system(1, spawn_rate, 100.0);
+        particle_system.prewarm();
+
+        let spawned = particle_system
+            .emitters
+            .iter()
+            .map(|e| e.spawned_particles)
+            .sum::<u64>();
+        assert!(spawned >= MAX_PREWARM_PARTICLES);
+        // Prewarm stops right after the step at which the limit was reached.
+        let per_step = (spawn_rate as f32 * PREWARM_TIME_STEP).ceil() as u64;
+        assert!(spawned < MAX_PREWARM_PARTICLES + per_step);
+    }
+
+    #[test]
+    fn test_prewarm_state_is_runtime_only() {
+        let mut particle_system = make_particle_system(1, 100, 1.0);
+        particle_system.prewarm();
+
+        let mut visitor = Visitor::new();
+        particle_system
+            .visit("ParticleSystem", &mut visitor)
+            .unwrap();
+        let data = visitor.save_binary_to_vec().unwrap();
+
+        let mut visitor = Visitor::load_from_memory(data).unwrap();
+        let mut loaded = make_particle_system(1, 100, 1.0);
+        loaded.visit("ParticleSystem", &mut visitor).unwrap();
+        assert!(!loaded.prewarmed);
+        assert_eq!(positions(&loaded), positions(&particle_system));
+
+        // Prewarm is deterministic, so prewarming of the loaded particle system gives the same
+        // state.
+        loaded.prewarm();
+        assert_eq!(positions(&loaded), positions(&particle_system));
+
+        let copy = particle_system.clone();
+        assert!(!copy.prewarmed);
+        assert_eq!(positions(&copy), positions(&particle_system));
+    }
+}
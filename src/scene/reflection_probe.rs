@@ -0,0 +1,718 @@
+//! Reflection probe is a volume that captures its surroundings into a cube map, which is then used
+//! for specular reflections of the objects inside the volume. See [`ReflectionProbe`] docs for more
+//! info.
+
+use crate::{
+    asset::Resource,
+    core::{
+        algebra::Vector3,
+        color::Color,
+        math::{aabb::AxisAlignedBoundingBox, Matrix4Ext},
+        pool::Handle,
+        reflect::prelude::*,
+        uuid::{uuid, Uuid},
+        variable::InheritableVariable,
+        visitor::prelude::*,
+        TypeUuidProvider,
+    },
+    resource::texture::{Texture, TextureResource},
+    scene::{
+        base::{Base, BaseBuilder},
+        debug::SceneDrawingContext,
+        graph::Graph,
+        node::{Node, NodeTrait, UpdateContext},
+    },
+};
+use std::{
+    cell::Cell,
+    ops::{Deref, DerefMut},
+};
+use strum_macros::{AsRefStr, EnumString, EnumVariantNames};
+
+/// Shape of the volume of a reflection probe. Objects inside the volume will use the probe for their
+/// reflections.
+#[derive(Copy, Clone, Debug, PartialEq, Visit, Reflect, AsRefStr, EnumString, EnumVariantNames)]
+pub enum ReflectionProbeShape {
+    /// Box volume, it also enables box projection correction of reflections, which makes them match
+    /// the walls of rectangular rooms. Rotation of the probe is ignored, its world-space axis-aligned
+    /// bounds are used instead.
+    Box {
+        /// Half of the size of the box along each axis (in local coordinates).
+        half_extents: Vector3<f32>,
+    },
+    /// Sphere volume.
+    Sphere {
+        /// Radius of the sphere (in local coordinates).
+        #[reflect(min_value = 0.0, step = 0.1)]
+        radius: f32,
+    },
+}
+
+impl Default for ReflectionProbeShape {
+    fn default() -> Self {
+        Self::Box {
+            half_extents: Vector3::new(5.0, 5.0, 5.0),
+        }
+    }
+}
+
+/// Defines how often a reflection probe captures its surroundings.
+#[derive(Copy, Clone, Debug, PartialEq, Visit, Reflect, AsRefStr, EnumString, EnumVariantNames)]
+pub enum ReflectionProbeUpdateMode {
+    /// The probe is captured once (when it is created or loaded without baked data) and then only
+    /// when requested explicitly (see [`ReflectionProbe::request_render`]). Suitable for static
+    /// environments.
+    Once,
+    /// The probe is captured periodically. Capturing is expensive (the scene is rendered six
+    /// times), so keep the interval as large as possible.
+    Interval {
+        /// Time (in seconds) between two captures, zero means every frame.
+        #[reflect(min_value = 0.0, step = 0.1)]
+        interval: f32,
+    },
+}
+
+impl Default for ReflectionProbeUpdateMode {
+    fn default() -> Self {
+        Self::Once
+    }
+}
+
+/// Reflection probe is a volume that captures its surroundings into a cube map, which is then used
+/// for specular reflections of every opaque object inside the volume. Without probes, metallic
+/// objects reflect the sky box of the scene (if any), which looks wrong in interiors - for example
+/// metallic objects in a cave would reflect the sky.
+///
+/// # Capturing
+///
+/// The scene is rendered six times (once per cube map face) from the capture position of the probe
+/// (see [`ReflectionProbe::capture_position`]). It could be done once or periodically, see
+/// [`ReflectionProbeUpdateMode`] docs for more info. A capture could also be requested explicitly
+/// using [`ReflectionProbe::request_render`] (the probe will be captured on the next frame) or
+/// [`crate::renderer::Renderer::render_probe`] (the probe is captured immediately).
+///
+/// # Selection
+///
+/// Every pixel uses the nearest probe whose volume contains the pixel, pixels outside of every probe
+/// use the sky box of the scene. Up to four probes (closest to the camera) are used at once.
+///
+/// # Baking
+///
+/// Captured cube maps exist only on GPU by default. If [`ReflectionProbe::set_serialize_cubemap`]
+/// is set, captured data is read back from GPU and saved with the scene, so the probe won't be
+/// captured again after the scene is loaded. Keep in mind, that baked data is quite large (16 bytes
+/// per pixel, six faces), so keep resolution of baked probes small.
+///
+/// # Example
+///
+/// ```rust
+/// use fyrox::{
+///     core::{algebra::Vector3, pool::Handle},
+///     scene::{
+///         base::BaseBuilder,
+///         graph::Graph,
+///         node::Node,
+///         reflection_probe::{ReflectionProbeBuilder, ReflectionProbeShape},
+///         transform::TransformBuilder,
+///     },
+/// };
+///
+/// fn create_room_probe(graph: &mut Graph) -> Handle<Node> {
+///     ReflectionProbeBuilder::new(
+///         BaseBuilder::new().with_local_transform(
+///             TransformBuilder::new()
+///                 .with_local_position(Vector3::new(0.0, 2.0, 0.0))
+///                 .build(),
+///         ),
+///     )
+///     .with_shape(ReflectionProbeShape::Box {
+///         half_extents: Vector3::new(4.0, 2.0, 6.0),
+///     })
+///     .with_resolution(128)
+///     .build(graph)
+/// }
+/// ```
+#[derive(Debug, Reflect)]
+pub struct ReflectionProbe {
+    base: Base,
+
+    #[reflect(setter = "set_shape")]
+    shape: InheritableVariable<ReflectionProbeShape>,
+
+    #[reflect(setter = "set_capture_offset")]
+    capture_offset: InheritableVariable<Vector3<f32>>,
+
+    #[reflect(min_value = 1.0, setter = "set_resolution")]
+    resolution: InheritableVariable<u32>,
+
+    #[reflect(min_value = 0.0, step = 0.01, setter = "set_z_near")]
+    z_near: InheritableVariable<f32>,
+
+    #[reflect(min_value = 0.0, step = 0.1, setter = "set_z_far")]
+    z_far: InheritableVariable<f32>,
+
+    #[reflect(setter = "set_update_mode")]
+    update_mode: InheritableVariable<ReflectionProbeUpdateMode>,
+
+    #[reflect(setter = "set_serialize_cubemap")]
+    serialize_cubemap: InheritableVariable<bool>,
+
+    #[reflect(hidden)]
+    cubemap: TextureResource,
+
+    #[reflect(hidden)]
+    render_requested: Cell<bool>,
+
+    #[reflect(hidden)]
+    time_since_render: f32,
+}
+
+impl Default for ReflectionProbe {
+    fn default() -> Self {
+        ReflectionProbeBuilder::new(BaseBuilder::new()).build_reflection_probe()
+    }
+}
+
+impl Clone for ReflectionProbe {
+    fn clone(&self) -> Self {
+        Self {
+            base: self.base.clone(),
+            shape: self.shape.clone(),
+            capture_offset: self.capture_offset.clone(),
+            resolution: self.resolution.clone(),
+            z_near: self.z_near.clone(),
+            z_far: self.z_far.clone(),
+            update_mode: self.update_mode.clone(),
+            serialize_cubemap: self.serialize_cubemap.clone(),
+            // Every probe must have its own cube map (its key identifies the cube map on GPU), baked
+            // data is copied only if it is saved with the scene, otherwise the copy will be captured
+            // on its own.
+            cubemap: if *self.serialize_cubemap {
+                Resource::new_ok(self.cubemap.data_ref().clone())
+            } else {
+                Resource::new_ok(Texture::default())
+            },
+            render_requested: Cell::new(true),
+            time_since_render: 0.0,
+        }
+    }
+}
+
+impl Visit for ReflectionProbe {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        let mut region = visitor.enter_region(name)?;
+
+        self.base.visit("Base", &mut region)?;
+        self.shape.visit("Shape", &mut region)?;
+        self.capture_offset.visit("CaptureOffset", &mut region)?;
+        self.resolution.visit("Resolution", &mut region)?;
+        self.z_near.visit("ZNear", &mut region)?;
+        self.z_far.visit("ZFar", &mut region)?;
+        self.update_mode.visit("UpdateMode", &mut region)?;
+        self.serialize_cubemap
+            .visit("SerializeCubemap", &mut region)?;
+
+        if *self.serialize_cubemap {
+            let _ = self.cubemap.visit("Cubemap", &mut region);
+        }
+
+        if region.is_reading() {
+            self.render_requested.set(!self.is_baked());
+        }
+
+        Ok(())
+    }
+}
+
+impl Deref for ReflectionProbe {
+    type Target = Base;
+
+    fn deref(&self) -> &Self::Target {
+        &self.base
+    }
+}
+
+impl DerefMut for ReflectionProbe {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.base
+    }
+}
+
+impl TypeUuidProvider for ReflectionProbe {
+    fn type_uuid() -> Uuid {
+        uuid!("2d3b5a8e-5cb1-4c2f-9b0e-6f0a8d7c4e21")
+    }
+}
+
+impl ReflectionProbe {
+    /// Sets new shape of the volume of the probe.
+    pub fn set_shape(&mut self, shape: ReflectionProbeShape) -> ReflectionProbeShape {
+        self.shape.set_value_and_mark_modified(shape)
+    }
+
+    /// Returns current shape of the volume of the probe.
+    pub fn shape(&self) -> ReflectionProbeShape {
+        *self.shape
+    }
+
+    /// Sets new offset (in local coordinates) of the capture position relative to the position of
+    /// the probe. It is useful to move the capture position away from geometry inside the volume.
+    pub fn set_capture_offset(&mut self, offset: Vector3<f32>) -> Vector3<f32> {
+        self.request_render();
+        self.capture_offset.set_value_and_mark_modified(offset)
+    }
+
+    /// Returns current offset of the capture position.
+    pub fn capture_offset(&self) -> Vector3<f32> {
+        *self.capture_offset
+    }
+
+    /// Sets new size (in pixels) of each face of the cube map of the probe. Default is 128.
+    pub fn set_resolution(&mut self, resolution: u32) -> u32 {
+        self.request_render();
+        self.resolution
+            .set_value_and_mark_modified(resolution.max(1))
+    }
+
+    /// Returns current size (in pixels) of each face of the cube map of the probe.
+    pub fn resolution(&self) -> u32 {
+        *self.resolution
+    }
+
+    /// Sets new near clipping plane distance of the capture. Default is 0.025.
+    pub fn set_z_near(&mut self, z_near: f32) -> f32 {
+        self.request_render();
+        self.z_near.set_value_and_mark_modified(z_near)
+    }
+
+    /// Returns current near clipping plane distance of the capture.
+    pub fn z_near(&self) -> f32 {
+        *self.z_near
+    }
+
+    /// Sets new far clipping plane distance of the capture. Default is 128.0.
+    pub fn set_z_far(&mut self, z_far: f32) -> f32 {
+        self.request_render();
+        self.z_far.set_value_and_mark_modified(z_far)
+    }
+
+    /// Returns current far clipping plane distance of the capture.
+    pub fn z_far(&self) -> f32 {
+        *self.z_far
+    }
+
+    /// Sets new update mode of the probe. See [`ReflectionProbeUpdateMode`] docs for more info.
+    pub fn set_update_mode(
+        &mut self,
+        update_mode: ReflectionProbeUpdateMode,
+    ) -> ReflectionProbeUpdateMode {
+        self.update_mode.set_value_and_mark_modified(update_mode)
+    }
+
+    /// Returns current update mode of the probe.
+    pub fn update_mode(&self) -> ReflectionProbeUpdateMode {
+        *self.update_mode
+    }
+
+    /// Defines whether captured cube map should be read back from GPU and saved with the scene or
+    /// not. Default is `false`. See "Baking" section of [`ReflectionProbe`] docs for more info.
+    pub fn set_serialize_cubemap(&mut self, serialize: bool) -> bool {
+        if serialize && !self.is_baked() {
+            self.request_render();
+        }
+        self.serialize_cubemap
+            .set_value_and_mark_modified(serialize)
+    }
+
+    /// Returns `true` if captured cube map will be saved with the scene, `false` - otherwise.
+    pub fn is_serialize_cubemap(&self) -> bool {
+        *self.serialize_cubemap
+    }
+
+    /// Returns the texture with baked cube map data. The texture is empty until the probe is
+    /// captured with [`Self::is_serialize_cubemap`] set.
+    pub fn cubemap(&self) -> &TextureResource {
+        &self.cubemap
+    }
+
+    /// Returns `true` if the probe has baked cube map data.
+    pub fn is_baked(&self) -> bool {
+        !self.cubemap.data_ref().data().is_empty()
+    }
+
+    /// Requests the probe to be captured on the next frame.
+    pub fn request_render(&self) {
+        self.render_requested.set(true);
+    }
+
+    /// Returns `true` if the probe will be captured on the next frame.
+    pub fn is_render_requested(&self) -> bool {
+        self.render_requested.get()
+    }
+
+    pub(crate) fn reset_render_request(&self) {
+        self.render_requested.set(false);
+    }
+
+    /// Returns world-space position, from which the probe captures its surroundings.
+    pub fn capture_position(&self) -> Vector3<f32> {
+        self.global_transform()
+            .transform_point(&(*self.capture_offset).into())
+            .coords
+    }
+
+    /// Returns world-space axis-aligned bounds of the volume of the probe.
+    pub fn volume_bounds(&self) -> AxisAlignedBoundingBox {
+        let local_bounds = match *self.shape {
+            ReflectionProbeShape::Box { half_extents } => {
+                AxisAlignedBoundingBox::from_min_max(-half_extents, half_extents)
+            }
+            ReflectionProbeShape::Sphere { radius } => AxisAlignedBoundingBox::from_radius(radius),
+        };
+        local_bounds.transform(&self.global_transform())
+    }
+
+    /// Returns world-space radius of the sphere volume, or `None` if the probe has box volume.
+    pub fn volume_radius(&self) -> Option<f32> {
+        match *self.shape {
+            ReflectionProbeShape::Box { .. } => None,
+            ReflectionProbeShape::Sphere { radius } => {
+                let basis = self.global_transform().basis();
+                let scale = basis
+                    .column(0)
+                    .norm()
+                    .max(basis.column(1).norm())
+                    .max(basis.column(2).norm());
+                Some(radius * scale)
+            }
+        }
+    }
+
+    /// Checks whether the given world-space point is inside the volume of the probe.
+    pub fn contains_point(&self, point: Vector3<f32>) -> bool {
+        match self.volume_radius() {
+            Some(radius) => (point - self.global_position()).norm() <= radius,
+            None => self.volume_bounds().is_contains_point(point),
+        }
+    }
+}
+
+impl NodeTrait for ReflectionProbe {
+    crate::impl_query_component!();
+
+    fn local_bounding_box(&self) -> AxisAlignedBoundingBox {
+        match *self.shape {
+            ReflectionProbeShape::Box { half_extents } => {
+                AxisAlignedBoundingBox::from_min_max(-half_extents, half_extents)
+            }
+            ReflectionProbeShape::Sphere { radius } => AxisAlignedBoundingBox::from_radius(radius),
+        }
+    }
+
+    fn world_bounding_box(&self) -> AxisAlignedBoundingBox {
+        self.volume_bounds()
+    }
+
+    fn id(&self) -> Uuid {
+        Self::type_uuid()
+    }
+
+    fn update(&mut self, context: &mut UpdateContext) {
+        if let ReflectionProbeUpdateMode::Interval { interval } = *self.update_mode {
+            self.time_since_render += context.dt;
+            if self.time_since_render >= interval {
+                self.time_since_render = 0.0;
+                self.request_render();
+            }
+        }
+    }
+
+    fn debug_draw(&self, ctx: &mut SceneDrawingContext) {
+        match self.volume_radius() {
+            Some(radius) => ctx.draw_wire_sphere(self.global_position(), radius, 30, Color::BLUE),
+            None => ctx.draw_aabb(&self.volume_bounds(), Color::BLUE),
+        }
+        ctx.draw_wire_sphere(self.capture_position(), 0.1, 10, Color::BLUE);
+    }
+}
+
+/// Allows you to create a reflection probe in a declarative manner.
+pub struct ReflectionProbeBuilder {
+    base_builder: BaseBuilder,
+    shape: ReflectionProbeShape,
+    capture_offset: Vector3<f32>,
+    resolution: u32,
+    z_near: f32,
+    z_far: f32,
+    update_mode: ReflectionProbeUpdateMode,
+    serialize_cubemap: bool,
+}
+
+impl ReflectionProbeBuilder {
+    /// Creates new builder instance.
+    pub fn new(base_builder: BaseBuilder) -> Self {
+        Self {
+            base_builder,
+            shape: Default::default(),
+            capture_offset: Default::default(),
+            resolution: 128,
+            z_near: 0.025,
+            z_far: 128.0,
+            update_mode: Default::default(),
+            serialize_cubemap: false,
+        }
+    }
+
+    /// Sets desired shape of the volume of the probe.
+    pub fn with_shape(mut self, shape: ReflectionProbeShape) -> Self {
+        self.shape = shape;
+        self
+    }
+
+    /// Sets desired offset of the capture position.
+    pub fn with_capture_offset(mut self, offset: Vector3<f32>) -> Self {
+        self.capture_offset = offset;
+        self
+    }
+
+    /// Sets desired size (in pixels) of each face of the cube map.
+    pub fn with_resolution(mut self, resolution: u32) -> Self {
+        self.resolution = resolution.max(1);
+        self
+    }
+
+    /// Sets desired near clipping plane distance of the capture.
+    pub fn with_z_near(mut self, z_near: f32) -> Self {
+        self.z_near = z_near;
+        self
+    }
+
+    /// Sets desired far clipping plane distance of the capture.
+    pub fn with_z_far(mut self, z_far: f32) -> Self {
+        self.z_far = z_far;
+        self
+    }
+
+    /// Sets desired update mode.
+    pub fn with_update_mode(mut self, update_mode: ReflectionProbeUpdateMode) -> Self {
+        self.update_mode = update_mode;
+        self
+    }
+
+    /// Defines whether captured cube map should be saved with the scene or not.
+    pub fn with_serialize_cubemap(mut self, serialize: bool) -> Self {
+        self.serialize_cubemap = serialize;
+        self
+    }
+
+    /// Creates new reflection probe.
+    pub fn build_reflection_probe(self) -> ReflectionProbe {
+        ReflectionProbe {
+            base: self.base_builder.build_base(),
+            shape: self.shape.into(),
+            capture_offset: self.capture_offset.into(),
+            resolution: self.resolution.into(),
+            z_near: self.z_near.into(),
+            z_far: self.z_far.into(),
+            update_mode: self.update_mode.into(),
+            serialize_cubemap: self.serialize_cubemap.into(),
+            cubemap: Resource::new_ok(Texture::default()),
+            render_requested: Cell::new(true),
+            time_since_render: 0.0,
+        }
+    }
+
+    /// Creates new reflection probe node.
+    pub fn build_node(self) -> Node {
+        Node::new(self.build_reflection_probe())
+    }
+
+    /// Creates new reflection probe and adds it to the graph.
+    pub fn build(self, graph: &mut Graph) -> Handle<Node> {
+        graph.add_node(self.build_node())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        asset::manager::ResourceManager,
+        core::{
+            algebra::{Vector2, Vector3},
+            visitor::prelude::*,
+        },
+        resource::texture::{Texture, TextureKind, TexturePixelKind},
+        scene::{
+            base::BaseBuilder,
+            graph::Graph,
+            reflection_probe::{
+                ReflectionProbe, ReflectionProbeBuilder, ReflectionProbeShape,
+                ReflectionProbeUpdateMode,
+            },
+            transform::TransformBuilder,
+        },
+    };
+    use std::sync::Arc;
+
+    fn probe_at(position: Vector3<f32>, shape: ReflectionProbeShape) -> ReflectionProbe {
+        let probe = ReflectionProbeBuilder::new(
+            BaseBuilder::new().with_local_transform(
+                TransformBuilder::new()
+                    .with_local_position(position)
+                    .with_local_scale(Vector3::new(2.0, 2.0, 2.0))
+                    .build(),
+            ),
+        )
+        .with_shape(shape)
+        .build_reflection_probe();
+        probe.global_transform.set(probe.local_transform().matrix());
+        probe
+    }
+
+    fn bake(probe: &ReflectionProbe) {
+        *probe.cubemap().data_ref() = Texture::from_bytes(
+            TextureKind::Cube {
+                width: 1,
+                height: 1,
+            },
+            TexturePixelKind::RGBA32F,
+            vec![1; 6 * 16],
+            true,
+        )
+        .unwrap();
+    }
+
+    fn visit_round_trip(probe: &mut ReflectionProbe) -> ReflectionProbe {
+        let mut visitor = Visitor::new();
+        probe.visit("Probe", &mut visitor).unwrap();
+        let data = visitor.save_binary_to_vec().unwrap();
+
+        let resource_manager = ResourceManager::new();
+        resource_manager
+            .state()
+            .constructors_container
+            .add::<Texture>();
+
+        let mut visitor = Visitor::load_from_memory(data).unwrap();
+        visitor.blackboard.register(Arc::new(resource_manager));
+        let mut loaded = ReflectionProbe::default();
+        loaded.visit("Probe", &mut visitor).unwrap();
+        loaded
+    }
+
+    #[test]
+    fn test_box_volume() {
+        let probe = probe_at(
+            Vector3::new(10.0, 0.0, 0.0),
+            ReflectionProbeShape::Box {
+                half_extents: Vector3::new(1.0, 2.0, 3.0),
+            },
+        );
+
+        let bounds = probe.volume_bounds();
+        assert_eq!(bounds.min, Vector3::new(8.0, -4.0, -6.0));
+        assert_eq!(bounds.max, Vector3::new(12.0, 4.0, 6.0));
+        assert_eq!(probe.volume_radius(), None);
+
+        assert!(probe.contains_point(Vector3::new(10.0, 0.0, 0.0)));
+        assert!(probe.contains_point(Vector3::new(11.9, 3.9, -5.9)));
+        assert!(!probe.contains_point(Vector3::new(12.1, 0.0, 0.0)));
+        assert!(!probe.contains_point(Vector3::new(10.0, 0.0, 6.1)));
+    }
+
+    #[test]
+    fn test_sphere_volume() {
+        let probe = probe_at(
+            Vector3::new(0.0, 5.0, 0.0),
+            ReflectionProbeShape::Sphere { radius: 1.5 },
+        );
+
+        let bounds = probe.volume_bounds();
+        assert_eq!(bounds.min, Vector3::new(-3.0, 2.0, -3.0));
+        assert_eq!(bounds.max, Vector3::new(3.0, 8.0, 3.0));
+        assert_eq!(probe.volume_radius(), Some(3.0));
+
+        assert!(probe.contains_point(Vector3::new(0.0, 7.9, 0.0)));
+        // Corners of the bounds are outside of the sphere.
+        assert!(!probe.contains_point(Vector3::new(2.9, 7.9, 2.9)));
+        assert!(!probe.contains_point(Vector3::new(0.0, 5.0, 3.1)));
+    }
+
+    #[test]
+    fn test_interval_update() {
+        let mut graph = Graph::new();
+        let handle = ReflectionProbeBuilder::new(BaseBuilder::new())
+            .with_update_mode(ReflectionProbeUpdateMode::Interval { interval: 1.0 })
+            .build(&mut graph);
+
+        let probe = |graph: &Graph| graph[handle].cast::<ReflectionProbe>().unwrap().clone();
+        let update = |graph: &mut Graph| {
+            graph[handle]
+                .cast::<ReflectionProbe>()
+                .unwrap()
+                .reset_render_request();
+            graph.update(Vector2::new(100.0, 100.0), 0.4, Default::default());
+            graph[handle]
+                .cast::<ReflectionProbe>()
+                .unwrap()
+                .is_render_requested()
+        };
+
+        assert!(probe(&graph).is_render_requested());
+        assert!(!update(&mut graph));
+        assert!(!update(&mut graph));
+        assert!(update(&mut graph));
+        // The timer must be restarted after the request.
+        assert!(!update(&mut graph));
+        assert!(!update(&mut graph));
+        assert!(update(&mut graph));
+    }
+
+    #[test]
+    fn test_visit_without_serialized_cubemap() {
+        let mut probe = ReflectionProbeBuilder::new(BaseBuilder::new())
+            .with_resolution(1)
+            .build_reflection_probe();
+        bake(&probe);
+
+        let loaded = visit_round_trip(&mut probe);
+        assert_eq!(loaded.resolution(), 1);
+        assert!(!loaded.is_serialize_cubemap());
+        assert!(!loaded.is_baked());
+        assert!(loaded.is_render_requested());
+    }
+
+    #[test]
+    fn test_visit_with_serialized_cubemap() {
+        let mut probe = ReflectionProbeBuilder::new(BaseBuilder::new())
+            .with_resolution(1)
+            .with_serialize_cubemap(true)
+            .build_reflection_probe();
+        bake(&probe);
+
+        let loaded = visit_round_trip(&mut probe);
+        assert!(loaded.is_serialize_cubemap());
+        assert!(loaded.is_baked());
+        assert_eq!(
+            loaded.cubemap().data_ref().data(),
+            probe.cubemap().data_ref().data()
+        );
+        // Baked probes must not be captured again.
+        assert!(!loaded.is_render_requested());
+    }
+
+    #[test]
+    fn test_clone() {
+        let probe = ReflectionProbeBuilder::new(BaseBuilder::new()).build_reflection_probe();
+        bake(&probe);
+        let clone = probe.clone();
+        assert_ne!(clone.cubemap().key(), probe.cubemap().key());
+        assert!(!clone.is_baked());
+
+        let mut probe = probe;
+        probe.set_serialize_cubemap(true);
+        let clone = probe.clone();
+        assert_ne!(clone.cubemap().key(), probe.cubemap().key());
+        assert!(clone.is_baked());
+    }
+}
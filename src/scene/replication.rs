@@ -0,0 +1,650 @@
+//! Replication of node transforms over network. See [`Replication`] docs for more info.
+
+use crate::{
+    core::{
+        algebra::{Quaternion, UnitQuaternion, Vector3},
+        pool::Handle,
+        visitor::prelude::*,
+    },
+    scene::{graph::Graph, node::Node},
+};
+use half::f16;
+use std::fmt::{Display, Formatter};
+
+/// Identifier of a replicated node, it must be the same on every peer.
+pub type NetworkId = u32;
+
+/// Defines how transforms are stored in serialized snapshots (see [`SceneSnapshot::to_bytes`]).
+#[derive(Visit, Copy, Clone, PartialEq, Debug)]
+pub enum SnapshotPrecision {
+    /// Every component is stored as 32-bit float.
+    Full,
+    /// Positions are stored as 32-bit integers (multiples of `position_step`), rotations as four
+    /// 16-bit integers and scales as 16-bit floats.
+    Quantized {
+        /// Size of a quantization step for positions.
+        position_step: f32,
+    },
+}
+
+impl Default for SnapshotPrecision {
+    fn default() -> Self {
+        Self::Full
+    }
+}
+
+/// State of a single replicated node. Only components, that were changed since the previous capture
+/// (dirty components) are stored.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SnapshotEntry {
+    /// Network id of the node.
+    pub network_id: NetworkId,
+    /// New local position of the node, if changed.
+    pub position: Option<Vector3<f32>>,
+    /// New local rotation of the node, if changed.
+    pub rotation: Option<UnitQuaternion<f32>>,
+    /// New local scale of the node, if changed.
+    pub scale: Option<Vector3<f32>>,
+}
+
+/// A set of changed transforms of replicated nodes. See [`Replication`] docs for more info.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SceneSnapshot {
+    /// Precision of the serialized form of the snapshot.
+    pub precision: SnapshotPrecision,
+    /// States of nodes, that have at least one changed component.
+    pub entries: Vec<SnapshotEntry>,
+}
+
+/// An error, that may occur during deserialization of a snapshot.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SnapshotError {
+    /// Data ended unexpectedly.
+    UnexpectedEnd,
+    /// Version of the data is not supported.
+    UnsupportedVersion(u8),
+    /// Unknown precision kind.
+    InvalidPrecision(u8),
+}
+
+impl Display for SnapshotError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SnapshotError::UnexpectedEnd => write!(f, "Unexpected end of snapshot data."),
+            SnapshotError::UnsupportedVersion(v) => {
+                write!(f, "Unsupported snapshot version {v}.")
+            }
+            SnapshotError::InvalidPrecision(v) => {
+                write!(f, "Invalid snapshot precision kind {v}.")
+            }
+        }
+    }
+}
+
+const SNAPSHOT_VERSION: u8 = 1;
+
+const POSITION_FLAG: u8 = 1;
+const ROTATION_FLAG: u8 = 2;
+const SCALE_FLAG: u8 = 4;
+
+struct Reader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn bytes<const N: usize>(&mut self) -> Result<[u8; N], SnapshotError> {
+        if self.data.len() < N {
+            return Err(SnapshotError::UnexpectedEnd);
+        }
+        let (bytes, rest) = self.data.split_at(N);
+        self.data = rest;
+        Ok(bytes.try_into().unwrap())
+    }
+
+    fn u8(&mut self) -> Result<u8, SnapshotError> {
+        Ok(self.bytes::<1>()?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, SnapshotError> {
+        Ok(u16::from_le_bytes(self.bytes()?))
+    }
+
+    fn u32(&mut self) -> Result<u32, SnapshotError> {
+        Ok(u32::from_le_bytes(self.bytes()?))
+    }
+
+    fn f32(&mut self) -> Result<f32, SnapshotError> {
+        Ok(f32::from_le_bytes(self.bytes()?))
+    }
+
+    fn vec3(&mut self) -> Result<Vector3<f32>, SnapshotError> {
+        Ok(Vector3::new(self.f32()?, self.f32()?, self.f32()?))
+    }
+}
+
+impl SceneSnapshot {
+    /// Returns `true` if there's no changed nodes in the snapshot.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Serializes the snapshot into a compact binary form, that could be sent over network.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = vec![SNAPSHOT_VERSION];
+
+        match self.precision {
+            SnapshotPrecision::Full => bytes.push(0),
+            SnapshotPrecision::Quantized { position_step } => {
+                bytes.push(1);
+                bytes.extend_from_slice(&position_step.to_le_bytes());
+            }
+        }
+
+        bytes.extend_from_slice(&(self.entries.len() as u32).to_le_bytes());
+
+        for entry in self.entries.iter() {
+            bytes.extend_from_slice(&entry.network_id.to_le_bytes());
+
+            let mut flags = 0;
+            if entry.position.is_some() {
+                flags |= POSITION_FLAG;
+            }
+            if entry.rotation.is_some() {
+                flags |= ROTATION_FLAG;
+            }
+            if entry.scale.is_some() {
+                flags |= SCALE_FLAG;
+            }
+            bytes.push(flags);
+
+            match self.precision {
+                SnapshotPrecision::Full => {
+                    let components = entry
+                        .position
+                        .iter()
+                        .flat_map(|p| p.iter())
+                        .chain(entry.rotation.iter().flat_map(|r| r.coords.iter()))
+                        .chain(entry.scale.iter().flat_map(|s| s.iter()));
+                    for component in components {
+                        bytes.extend_from_slice(&component.to_le_bytes());
+                    }
+                }
+                SnapshotPrecision::Quantized { position_step } => {
+                    if let Some(position) = entry.position {
+                        for component in position.iter() {
+                            let quantized = (component / position_step).round() as i32;
+                            bytes.extend_from_slice(&quantized.to_le_bytes());
+                        }
+                    }
+                    if let Some(rotation) = entry.rotation {
+                        for component in rotation.coords.iter() {
+                            let quantized = (component * i16::MAX as f32).round() as i16;
+                            bytes.extend_from_slice(&quantized.to_le_bytes());
+                        }
+                    }
+                    if let Some(scale) = entry.scale {
+                        for component in scale.iter() {
+                            bytes.extend_from_slice(&f16::from_f32(*component).to_le_bytes());
+                        }
+                    }
+                }
+            }
+        }
+
+        bytes
+    }
+
+    /// Deserializes a snapshot from the data produced by [`Self::to_bytes`].
+    pub fn from_bytes(data: &[u8]) -> Result<Self, SnapshotError> {
+        let mut reader = Reader { data };
+
+        let version = reader.u8()?;
+        if version != SNAPSHOT_VERSION {
+            return Err(SnapshotError::UnsupportedVersion(version));
+        }
+
+        let precision = match reader.u8()? {
+            0 => SnapshotPrecision::Full,
+            1 => SnapshotPrecision::Quantized {
+                position_step: reader.f32()?,
+            },
+            kind => return Err(SnapshotError::InvalidPrecision(kind)),
+        };
+
+        let count = reader.u32()? as usize;
+        // Do not trust the count, each entry takes at least five bytes.
+        let mut entries = Vec::with_capacity(count.min(reader.data.len() / 5));
+        for _ in 0..count {
+            let network_id = reader.u32()?;
+            let flags = reader.u8()?;
+
+            let mut entry = SnapshotEntry {
+                network_id,
+                position: None,
+                rotation: None,
+                scale: None,
+            };
+
+            match precision {
+                SnapshotPrecision::Full => {
+                    if flags & POSITION_FLAG != 0 {
+                        entry.position = Some(reader.vec3()?);
+                    }
+                    if flags & ROTATION_FLAG != 0 {
+                        let (x, y, z, w) =
+                            (reader.f32()?, reader.f32()?, reader.f32()?, reader.f32()?);
+                        entry.rotation =
+                            Some(UnitQuaternion::new_normalize(Quaternion::new(w, x, y, z)));
+                    }
+                    if flags & SCALE_FLAG != 0 {
+                        entry.scale = Some(reader.vec3()?);
+                    }
+                }
+                SnapshotPrecision::Quantized { position_step } => {
+                    if flags & POSITION_FLAG != 0 {
+                        let mut position = Vector3::default();
+                        for component in position.iter_mut() {
+                            *component = reader.u32()? as i32 as f32 * position_step;
+                        }
+                        entry.position = Some(position);
+                    }
+                    if flags & ROTATION_FLAG != 0 {
+                        // Coordinates are stored in (x, y, z, w) order.
+                        let mut coords = [0.0; 4];
+                        for component in coords.iter_mut() {
+                            *component = reader.u16()? as i16 as f32 / i16::MAX as f32;
+                        }
+                        let [x, y, z, w] = coords;
+                        entry.rotation =
+                            Some(UnitQuaternion::new_normalize(Quaternion::new(w, x, y, z)));
+                    }
+                    if flags & SCALE_FLAG != 0 {
+                        let mut scale = Vector3::default();
+                        for component in scale.iter_mut() {
+                            *component = f16::from_le_bytes(reader.bytes()?).to_f32();
+                        }
+                        entry.scale = Some(scale);
+                    }
+                }
+            }
+
+            entries.push(entry);
+        }
+
+        Ok(Self { precision, entries })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct TransformState {
+    position: Vector3<f32>,
+    rotation: UnitQuaternion<f32>,
+    scale: Vector3<f32>,
+}
+
+impl TransformState {
+    fn from_node(node: &Node) -> Self {
+        let transform = node.local_transform();
+        Self {
+            position: **transform.position(),
+            rotation: **transform.rotation(),
+            scale: **transform.scale(),
+        }
+    }
+}
+
+#[derive(Default, Clone, Debug, Visit)]
+struct ReplicatedNode {
+    network_id: NetworkId,
+    node: Handle<Node>,
+    // Last captured state, used to find changed components.
+    #[visit(skip)]
+    last_captured: Option<TransformState>,
+    // Last received state, nodes are smoothly moved to it.
+    #[visit(skip)]
+    target: Option<TransformState>,
+}
+
+/// Replication is a registry of nodes, which transforms are synchronized over network. Every node
+/// has a network id, that must be the same on every peer. The sending side captures snapshots (see
+/// [`Self::capture`]) of the changed transforms of the nodes, serializes them and sends to other
+/// peers, the receiving side deserializes the snapshots and applies them (see [`Self::apply`]).
+/// Networking itself (sockets, reliability, etc.) is out of scope of the engine.
+///
+/// Registered nodes are stored with the scene, so the mapping survives saving, loading and
+/// resolving of the scene.
+#[derive(Default, Clone, Debug, Visit)]
+pub struct Replication {
+    // Sorted by network id.
+    nodes: Vec<ReplicatedNode>,
+    precision: SnapshotPrecision,
+}
+
+impl Replication {
+    /// Registers a node with the given network id. Returns a node, that was previously registered
+    /// with the id (if any).
+    pub fn register(&mut self, node: Handle<Node>, network_id: NetworkId) -> Option<Handle<Node>> {
+        match self
+            .nodes
+            .binary_search_by_key(&network_id, |n| n.network_id)
+        {
+            Ok(index) => {
+                let entry = &mut self.nodes[index];
+                entry.last_captured = None;
+                entry.target = None;
+                Some(std::mem::replace(&mut entry.node, node))
+            }
+            Err(index) => {
+                self.nodes.insert(
+                    index,
+                    ReplicatedNode {
+                        network_id,
+                        node,
+                        last_captured: None,
+                        target: None,
+                    },
+                );
+                None
+            }
+        }
+    }
+
+    /// Unregisters a node with the given network id and returns its handle.
+    pub fn unregister(&mut self, network_id: NetworkId) -> Option<Handle<Node>> {
+        let index = self
+            .nodes
+            .binary_search_by_key(&network_id, |n| n.network_id)
+            .ok()?;
+        Some(self.nodes.remove(index).node)
+    }
+
+    /// Returns a handle of a node with the given network id, or [`Handle::NONE`] if there's no
+    /// such node.
+    pub fn node(&self, network_id: NetworkId) -> Handle<Node> {
+        self.nodes
+            .binary_search_by_key(&network_id, |n| n.network_id)
+            .map_or(Handle::NONE, |index| self.nodes[index].node)
+    }
+
+    /// Returns network id of the given node (if it is registered).
+    pub fn network_id(&self, node: Handle<Node>) -> Option<NetworkId> {
+        self.nodes
+            .iter()
+            .find(|n| n.node == node)
+            .map(|n| n.network_id)
+    }
+
+    /// Returns an iterator over pairs of network ids and handles of registered nodes.
+    pub fn iter(&self) -> impl Iterator<Item = (NetworkId, Handle<Node>)> + '_ {
+        self.nodes.iter().map(|n| (n.network_id, n.node))
+    }
+
+    /// Sets precision of captured snapshots. Default is [`SnapshotPrecision::Full`].
+    pub fn set_precision(&mut self, precision: SnapshotPrecision) {
+        self.precision = precision;
+    }
+
+    /// Returns precision of captured snapshots.
+    pub fn precision(&self) -> SnapshotPrecision {
+        self.precision
+    }
+
+    /// Captures transforms of every registered node, that were changed since the previous capture.
+    /// The first capture of a node contains its full transform. Nodes with invalid handles are
+    /// ignored.
+    pub fn capture(&mut self, graph: &Graph) -> SceneSnapshot {
+        self.capture_internal(graph, false)
+    }
+
+    /// Captures full transforms of every registered node, regardless of changes. It could be used
+    /// to send initial state to a newly connected peer.
+    pub fn capture_full(&mut self, graph: &Graph) -> SceneSnapshot {
+        self.capture_internal(graph, true)
+    }
+
+    fn capture_internal(&mut self, graph: &Graph, full: bool) -> SceneSnapshot {
+        let mut entries = Vec::new();
+
+        for replicated in self.nodes.iter_mut() {
+            let state = if let Some(node) = graph.try_get(replicated.node) {
+                TransformState::from_node(node)
+            } else {
+                continue;
+            };
+
+            let last = if full {
+                None
+            } else {
+                replicated.last_captured.as_ref()
+            };
+            let entry = SnapshotEntry {
+                network_id: replicated.network_id,
+                position: Some(state.position)
+                    .filter(|p| last.map_or(true, |last| last.position != *p)),
+                rotation: Some(state.rotation)
+                    .filter(|r| last.map_or(true, |last| last.rotation != *r)),
+                scale: Some(state.scale).filter(|s| last.map_or(true, |last| last.scale != *s)),
+            };
+
+            if entry.position.is_some() || entry.rotation.is_some() || entry.scale.is_some() {
+                entries.push(entry);
+            }
+
+            replicated.last_captured = Some(state);
+        }
+
+        SceneSnapshot {
+            precision: self.precision,
+            entries,
+        }
+    }
+
+    /// Forgets previously captured states, so the next capture will contain full transforms of
+    /// every node.
+    pub fn reset_history(&mut self) {
+        for replicated in self.nodes.iter_mut() {
+            replicated.last_captured = None;
+        }
+    }
+
+    /// Applies the received snapshot. Nodes are not teleported to the received transforms, instead
+    /// they're moved toward them by the given interpolation factor (`0.0` - stay in place, `1.0` -
+    /// move to the received transform immediately). Use [`Self::interpolate`] to keep moving the
+    /// nodes on frames without snapshots. Entries with unknown network ids are ignored.
+    pub fn apply(&mut self, graph: &mut Graph, snapshot: &SceneSnapshot, interpolation: f32) {
+        for entry in snapshot.entries.iter() {
+            let replicated = match self
+                .nodes
+                .binary_search_by_key(&entry.network_id, |n| n.network_id)
+            {
+                Ok(index) => &mut self.nodes[index],
+                Err(_) => continue,
+            };
+            let node = if let Some(node) = graph.try_get(replicated.node) {
+                node
+            } else {
+                continue;
+            };
+
+            let target = replicated
+                .target
+                .get_or_insert_with(|| TransformState::from_node(node));
+            if let Some(position) = entry.position {
+                target.position = position;
+            }
+            if let Some(rotation) = entry.rotation {
+                target.rotation = rotation;
+            }
+            if let Some(scale) = entry.scale {
+                target.scale = scale;
+            }
+        }
+
+        self.interpolate(graph, interpolation);
+    }
+
+    /// Moves every node toward its last received transform by the given interpolation factor. See
+    /// [`Self::apply`] for more info.
+    pub fn interpolate(&mut self, graph: &mut Graph, interpolation: f32) {
+        let t = interpolation.clamp(0.0, 1.0);
+
+        for replicated in self.nodes.iter_mut() {
+            let (target, node) = match (
+                replicated.target.as_ref(),
+                graph.try_get_mut(replicated.node),
+            ) {
+                (Some(target), Some(node)) => (target, node),
+                _ => continue,
+            };
+
+            let current = TransformState::from_node(node);
+            let transform = node.local_transform_mut();
+            if t >= 1.0 {
+                transform
+                    .set_position(target.position)
+                    .set_rotation(target.rotation)
+                    .set_scale(target.scale);
+                replicated.target = None;
+            } else {
+                transform
+                    .set_position(current.position.lerp(&target.position, t))
+                    .set_rotation(
+                        current
+                            .rotation
+                            .try_slerp(&target.rotation, t, f32::EPSILON)
+                            .unwrap_or(target.rotation),
+                    )
+                    .set_scale(current.scale.lerp(&target.scale, t));
+            }
+        }
+    }
+
+    pub(crate) fn remap_handles(&mut self, map: impl Fn(Handle<Node>) -> Option<Handle<Node>>) {
+        for replicated in self.nodes.iter_mut() {
+            replicated.node = map(replicated.node).unwrap_or_default();
+        }
+        self.nodes.retain(|replicated| replicated.node.is_some());
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::algebra::{UnitQuaternion, Vector3},
+        scene::{
+            base::BaseBuilder,
+            pivot::PivotBuilder,
+            replication::{SceneSnapshot, SnapshotPrecision},
+            transform::TransformBuilder,
+            Scene,
+        },
+    };
+
+    fn make_scene() -> Scene {
+        let mut scene = Scene::new();
+        for i in 0..3 {
+            let node = PivotBuilder::new(BaseBuilder::new()).build(&mut scene.graph);
+            scene.replication.register(node, 100 + i);
+        }
+        scene
+    }
+
+    #[test]
+    fn test_snapshot_round_trip() {
+        let mut sender = make_scene();
+        let mut receiver = make_scene();
+
+        for precision in [
+            SnapshotPrecision::Full,
+            SnapshotPrecision::Quantized {
+                position_step: 0.001,
+            },
+        ] {
+            sender.replication.set_precision(precision);
+            sender.replication.reset_history();
+
+            for (i, (_, handle)) in sender.replication.iter().enumerate() {
+                sender.graph[handle].set_local_transform(
+                    TransformBuilder::new()
+                        .with_local_position(Vector3::new(i as f32 * 1.2345, -3.21, 10.5))
+                        .with_local_rotation(UnitQuaternion::from_euler_angles(
+                            0.1,
+                            0.2 * i as f32,
+                            -0.3,
+                        ))
+                        .with_local_scale(Vector3::new(1.0, 2.5, 0.75))
+                        .build(),
+                );
+            }
+
+            let snapshot = sender.capture_snapshot();
+            assert_eq!(snapshot.entries.len(), 3);
+            let bytes = snapshot.to_bytes();
+            let received = SceneSnapshot::from_bytes(&bytes).unwrap();
+            assert_eq!(received.entries.len(), 3);
+            receiver.apply_snapshot(&received, 1.0);
+
+            for (network_id, handle) in sender.replication.iter() {
+                let expected = sender.graph[handle].local_transform();
+                let actual =
+                    receiver.graph[receiver.replication.node(network_id)].local_transform();
+                assert!(expected.position().metric_distance(&**actual.position()) < 0.002);
+                assert!(expected.rotation().angle_to(&**actual.rotation()) < 0.001);
+                assert!(expected.scale().metric_distance(&**actual.scale()) < 0.002);
+            }
+        }
+
+        // Truncated data.
+        let bytes = sender.replication.capture_full(&sender.graph).to_bytes();
+        assert!(SceneSnapshot::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+    }
+
+    #[test]
+    fn test_dirty_flags_and_interpolation() {
+        let mut sender = make_scene();
+        let mut receiver = make_scene();
+
+        // The first capture contains everything.
+        let snapshot = sender.capture_snapshot();
+        assert_eq!(snapshot.entries.len(), 3);
+        receiver.apply_snapshot(&snapshot, 1.0);
+
+        // Nothing has changed.
+        assert!(sender.capture_snapshot().is_empty());
+
+        let handle = sender.replication.node(101);
+        sender.graph[handle]
+            .local_transform_mut()
+            .set_position(Vector3::new(2.0, 0.0, 0.0));
+        let snapshot = sender.capture_snapshot();
+        assert_eq!(snapshot.entries.len(), 1);
+        let entry = &snapshot.entries[0];
+        assert_eq!(entry.network_id, 101);
+        assert_eq!(entry.position, Some(Vector3::new(2.0, 0.0, 0.0)));
+        assert!(entry.rotation.is_none() && entry.scale.is_none());
+
+        // Full capture ignores dirty flags.
+        assert_eq!(
+            sender.replication.capture_full(&sender.graph).entries.len(),
+            3
+        );
+
+        let received = SceneSnapshot::from_bytes(&snapshot.to_bytes()).unwrap();
+        let receiver_handle = receiver.replication.node(101);
+        receiver.apply_snapshot(&received, 0.5);
+        assert_eq!(
+            **receiver.graph[receiver_handle].local_transform().position(),
+            Vector3::new(1.0, 0.0, 0.0)
+        );
+        receiver.replication.interpolate(&mut receiver.graph, 0.5);
+        assert_eq!(
+            **receiver.graph[receiver_handle].local_transform().position(),
+            Vector3::new(1.5, 0.0, 0.0)
+        );
+        receiver.replication.interpolate(&mut receiver.graph, 1.0);
+        assert_eq!(
+            **receiver.graph[receiver_handle].local_transform().position(),
+            Vector3::new(2.0, 0.0, 0.0)
+        );
+    }
+}
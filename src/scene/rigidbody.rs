@@ -740,3 +740,61 @@ impl RigidBodyBuilder {
         graph.add_node(self.build_node())
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::algebra::{Vector2, Vector3},
+        scene::{
+            base::BaseBuilder,
+            collider::{ColliderBuilder, ColliderShape},
+            graph::Graph,
+            rigidbody::{RigidBodyBuilder, RigidBodyType},
+            transform::TransformBuilder,
+        },
+    };
+
+    fn shoot_through_wall(ccd_enabled: bool) -> f32 {
+        let mut graph = Graph::new();
+        graph.physics.gravity = Vector3::default();
+
+        // Thin static wall at x = 5.
+        let wall_collider = ColliderBuilder::new(BaseBuilder::new())
+            .with_shape(ColliderShape::cuboid(0.05, 5.0, 5.0))
+            .build(&mut graph);
+        RigidBodyBuilder::new(
+            BaseBuilder::new()
+                .with_local_transform(
+                    TransformBuilder::new()
+                        .with_local_position(Vector3::new(5.0, 0.0, 0.0))
+                        .build(),
+                )
+                .with_children(&[wall_collider]),
+        )
+        .with_body_type(RigidBodyType::Static)
+        .build(&mut graph);
+
+        // Fast bullet, it moves ~8 meters per step which is way more than the thickness of the wall.
+        let bullet_collider = ColliderBuilder::new(BaseBuilder::new())
+            .with_shape(ColliderShape::ball(0.05))
+            .build(&mut graph);
+        let bullet = RigidBodyBuilder::new(BaseBuilder::new().with_children(&[bullet_collider]))
+            .with_lin_vel(Vector3::new(500.0, 0.0, 0.0))
+            .with_ccd_enabled(ccd_enabled)
+            .build(&mut graph);
+
+        for _ in 0..10 {
+            graph.update(Vector2::new(800.0, 600.0), 1.0 / 60.0, Default::default());
+        }
+
+        graph[bullet].global_position().x
+    }
+
+    #[test]
+    fn test_ccd_prevents_tunneling() {
+        // Without CCD the bullet simply jumps over the wall between two simulation steps.
+        assert!(shoot_through_wall(false) > 5.0);
+        // With CCD the bullet hits the wall and stays in front of it.
+        assert!(shoot_through_wall(true) < 5.0);
+    }
+}
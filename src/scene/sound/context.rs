@@ -2,11 +2,18 @@
 
 use crate::{
     core::{
+        algebra::Vector3,
         log::{Log, MessageKind},
         pool::Handle,
         visitor::prelude::*,
     },
-    scene::{node::Node, sound::Sound},
+    scene::{
+        node::Node,
+        sound::{
+            occlusion::{OcclusionScheduler, DEFAULT_OCCLUSION_RAYS_PER_FRAME},
+            Sound,
+        },
+    },
 };
 use fxhash::FxHashSet;
 use fyrox_sound::{
@@ -22,6 +29,10 @@ use std::{sync::MutexGuard, time::Duration};
 pub struct SoundContext {
     #[visit(optional)]
     pub(crate) native: fyrox_sound::context::SoundContext,
+    #[visit(optional)]
+    occlusion_rays_per_frame: u32,
+    #[visit(skip)]
+    pub(crate) occlusion_scheduler: OcclusionScheduler,
 }
 
 /// Proxy for guarded access to the sound context.
@@ -60,6 +71,38 @@ impl<'a> SoundContextGuard<'a> {
         self.guard.distance_model()
     }
 
+    /// Enables or disables Doppler effect for every sound in the context. Sounds could opt out
+    /// using [`Sound::set_doppler_enabled`]. Default is `true`.
+    pub fn set_doppler_enabled(&mut self, enabled: bool) {
+        self.guard.set_doppler_enabled(enabled);
+    }
+
+    /// Returns `true` if Doppler effect is enabled in the context.
+    pub fn is_doppler_enabled(&self) -> bool {
+        self.guard.is_doppler_enabled()
+    }
+
+    /// Sets speed of sound in units per second, default is 343.3 (speed of sound in air, m/s).
+    pub fn set_speed_of_sound(&mut self, speed_of_sound: f32) {
+        self.guard.set_speed_of_sound(speed_of_sound);
+    }
+
+    /// Returns speed of sound in units per second.
+    pub fn speed_of_sound(&self) -> f32 {
+        self.guard.speed_of_sound()
+    }
+
+    /// Sets Doppler factor, that exaggerates (values > 1.0) or weakens (values < 1.0) Doppler
+    /// effect. Default is `1.0`.
+    pub fn set_doppler_factor(&mut self, doppler_factor: f32) {
+        self.guard.set_doppler_factor(doppler_factor);
+    }
+
+    /// Returns Doppler factor.
+    pub fn doppler_factor(&self) -> f32 {
+        self.guard.doppler_factor()
+    }
+
     /// Normalizes given frequency using context's sampling rate. Normalized frequency then can be used
     /// to create filters.
     pub fn normalize_frequency(&self, f: f32) -> f32 {
@@ -101,6 +144,8 @@ impl Default for SoundContext {
     fn default() -> Self {
         Self {
             native: fyrox_sound::context::SoundContext::new(),
+            occlusion_rays_per_frame: DEFAULT_OCCLUSION_RAYS_PER_FRAME as u32,
+            occlusion_scheduler: Default::default(),
         }
     }
 }
@@ -114,9 +159,24 @@ impl SoundContext {
     pub fn deep_clone(&self) -> Self {
         Self {
             native: self.native.deep_clone(),
+            occlusion_rays_per_frame: self.occlusion_rays_per_frame,
+            occlusion_scheduler: Default::default(),
         }
     }
 
+    /// Sets the maximum amount of occlusion rays per frame. Occlusion of sound sources is checked
+    /// in round-robin fashion, so with `N` occluded sources each source is checked once in
+    /// `ceil(N / rays_per_frame)` frames. See [`crate::scene::sound::occlusion::SoundOcclusion`]
+    /// docs for more info.
+    pub fn set_occlusion_rays_per_frame(&mut self, rays_per_frame: usize) {
+        self.occlusion_rays_per_frame = rays_per_frame as u32;
+    }
+
+    /// Returns the maximum amount of occlusion rays per frame.
+    pub fn occlusion_rays_per_frame(&self) -> usize {
+        self.occlusion_rays_per_frame as usize
+    }
+
     /// Returns locked inner state of the sound context.
     pub fn state(&self) -> SoundContextGuard {
         SoundContextGuard {
@@ -142,8 +202,10 @@ impl SoundContext {
         }
     }
 
-    pub(crate) fn sync_with_sound(&self, sound: &mut Sound) {
+    pub(crate) fn sync_with_sound(&self, sound: &mut Sound, velocity: Vector3<f32>) {
         if let Some(source) = self.native.state().try_get_source_mut(sound.native.get()) {
+            source.set_velocity(velocity);
+
             // Sync back.
             sound.status.set_value_silent(source.status());
             sound.playback_time.set_value_silent(source.playback_time());
@@ -211,6 +273,9 @@ impl SoundContext {
             sound.audio_bus.try_sync_model(|audio_bus| {
                 source.set_bus(audio_bus);
             });
+            sound.doppler_enabled.try_sync_model(|v| {
+                source.set_doppler_enabled(v);
+            });
         } else {
             match SoundSourceBuilder::new()
                 .with_gain(sound.gain())
@@ -225,6 +290,7 @@ impl SoundContext {
                 .with_max_distance(sound.max_distance())
                 .with_bus(sound.audio_bus())
                 .with_rolloff_factor(sound.rolloff_factor())
+                .with_doppler_enabled(sound.is_doppler_enabled())
                 .build()
             {
                 Ok(source) => {
@@ -2,7 +2,7 @@
 
 use crate::{
     core::{
-        algebra::Matrix4,
+        algebra::{Matrix4, Vector3},
         math::{aabb::AxisAlignedBoundingBox, m4x4_approx_eq},
         pool::Handle,
         reflect::prelude::*,
@@ -36,7 +36,7 @@ pub use fyrox_sound::{
     source::Status,
 };
 
-use crate::scene::Scene;
+use crate::scene::{sound::occlusion::SoundOcclusion, Scene};
 use fyrox_resource::ResourceStateRef;
 use fyrox_sound::source::SoundSource;
 use std::{
@@ -47,6 +47,7 @@ use std::{
 
 pub mod context;
 pub mod listener;
+pub mod occlusion;
 
 /// Sound source.
 #[derive(Visit, Reflect, Debug)]
@@ -101,6 +102,28 @@ pub struct Sound {
     )]
     audio_bus: InheritableVariable<String>,
 
+    #[visit(optional)]
+    #[reflect(setter = "set_occlusion")]
+    occlusion: InheritableVariable<SoundOcclusion>,
+
+    #[visit(optional)]
+    #[reflect(setter = "set_doppler_enabled")]
+    doppler_enabled: InheritableVariable<bool>,
+
+    // Current and desired occlusion levels, see `SoundOcclusion` docs.
+    #[reflect(hidden)]
+    #[visit(skip)]
+    pub(crate) occlusion_level: f32,
+
+    #[reflect(hidden)]
+    #[visit(skip)]
+    pub(crate) occlusion_target: f32,
+
+    // Global position at previous update, it is used to calculate velocity of the sound.
+    #[reflect(hidden)]
+    #[visit(skip)]
+    prev_position: Option<Vector3<f32>>,
+
     #[reflect(hidden)]
     #[visit(skip)]
     pub(crate) native: Cell<Handle<SoundSource>>,
@@ -137,6 +160,11 @@ impl Default for Sound {
             playback_time: Default::default(),
             spatial_blend: InheritableVariable::new_modified(1.0),
             audio_bus: InheritableVariable::new_modified(AudioBusGraph::PRIMARY_BUS.to_string()),
+            occlusion: Default::default(),
+            doppler_enabled: InheritableVariable::new_modified(true),
+            occlusion_level: 0.0,
+            occlusion_target: 0.0,
+            prev_position: None,
             native: Default::default(),
         }
     }
@@ -159,6 +187,11 @@ impl Clone for Sound {
             playback_time: self.playback_time.clone(),
             spatial_blend: self.spatial_blend.clone(),
             audio_bus: self.audio_bus.clone(),
+            occlusion: self.occlusion.clone(),
+            doppler_enabled: self.doppler_enabled.clone(),
+            occlusion_level: self.occlusion_level,
+            occlusion_target: self.occlusion_target,
+            prev_position: None,
             // Do not copy. The copy will have its own native representation.
             native: Default::default(),
         }
@@ -350,6 +383,47 @@ impl Sound {
     pub fn audio_bus(&self) -> &str {
         &self.audio_bus
     }
+
+    /// Sets new occlusion settings of the sound. See [`SoundOcclusion`] docs for more info.
+    pub fn set_occlusion(&mut self, occlusion: SoundOcclusion) -> SoundOcclusion {
+        self.occlusion.set_value_and_mark_modified(occlusion)
+    }
+
+    /// Returns occlusion settings of the sound.
+    pub fn occlusion(&self) -> &SoundOcclusion {
+        &self.occlusion
+    }
+
+    /// Returns current (smoothed) occlusion level of the sound in `0..1` range, where `0.0` means
+    /// that the sound is not occluded and `1.0` - that the sound is fully occluded.
+    pub fn occlusion_level(&self) -> f32 {
+        self.occlusion_level
+    }
+
+    /// Enables or disables Doppler effect for the sound. It should be disabled for sounds that must
+    /// never change their pitch, such as UI sounds. Velocity of the sound is calculated automatically
+    /// from the movement of the node. See [`context::SoundContextGuard::set_doppler_enabled`] for
+    /// global settings of the effect.
+    pub fn set_doppler_enabled(&mut self, enabled: bool) -> bool {
+        self.doppler_enabled.set_value_and_mark_modified(enabled)
+    }
+
+    /// Returns `true` if Doppler effect is enabled for the sound.
+    pub fn is_doppler_enabled(&self) -> bool {
+        *self.doppler_enabled
+    }
+}
+
+// Calculates velocity from the position at previous update, returns zero velocity on first call.
+pub(crate) fn track_velocity(
+    prev_position: &mut Option<Vector3<f32>>,
+    position: Vector3<f32>,
+    dt: f32,
+) -> Vector3<f32> {
+    match prev_position.replace(position) {
+        Some(prev_position) if dt > 0.0 => (position - prev_position).scale(1.0 / dt),
+        _ => Vector3::default(),
+    }
 }
 
 impl NodeTrait for Sound {
@@ -398,7 +472,9 @@ impl NodeTrait for Sound {
     }
 
     fn update(&mut self, context: &mut UpdateContext) {
-        context.sound_context.sync_with_sound(self);
+        let position = self.global_position();
+        let velocity = track_velocity(&mut self.prev_position, position, context.dt);
+        context.sound_context.sync_with_sound(self, velocity);
     }
 
     fn validate(&self, _scene: &Scene) -> Result<(), String> {
@@ -440,6 +516,8 @@ pub struct SoundBuilder {
     playback_time: Duration,
     spatial_blend: f32,
     audio_bus: String,
+    occlusion: SoundOcclusion,
+    doppler_enabled: bool,
 }
 
 impl SoundBuilder {
@@ -460,6 +538,8 @@ impl SoundBuilder {
             spatial_blend: 1.0,
             playback_time: Default::default(),
             audio_bus: AudioBusGraph::PRIMARY_BUS.to_string(),
+            occlusion: Default::default(),
+            doppler_enabled: true,
         }
     }
 
@@ -528,6 +608,16 @@ impl SoundBuilder {
         fn with_audio_bus(audio_bus: String)
     );
 
+    define_with!(
+        /// Sets desired occlusion settings. See [`Sound::set_occlusion`] for more info.
+        fn with_occlusion(occlusion: SoundOcclusion)
+    );
+
+    define_with!(
+        /// Enables or disables Doppler effect. See [`Sound::set_doppler_enabled`] for more info.
+        fn with_doppler_enabled(doppler_enabled: bool)
+    );
+
     /// Creates a new [`Sound`] node.
     #[must_use]
     pub fn build_sound(self) -> Sound {
@@ -546,6 +636,11 @@ impl SoundBuilder {
             playback_time: self.playback_time.into(),
             spatial_blend: self.spatial_blend.into(),
             audio_bus: self.audio_bus.into(),
+            occlusion: self.occlusion.into(),
+            doppler_enabled: self.doppler_enabled.into(),
+            occlusion_level: 0.0,
+            occlusion_target: 0.0,
+            prev_position: None,
             native: Default::default(),
         }
     }
@@ -0,0 +1,177 @@
+//! Sound occlusion simulates obstacles between a sound source and the listener. See
+//! [`SoundOcclusion`] docs for more info.
+
+use crate::{
+    core::{reflect::prelude::*, visitor::prelude::*},
+    scene::{collider::InteractionGroups, sound::SAMPLE_RATE},
+};
+
+/// Cutoff frequency (in Hz) of the lowpass filter of a source that is not occluded at all.
+pub const OPEN_CUTOFF: f32 = 20000.0;
+
+/// Default amount of occlusion rays per frame, see [`super::context::SoundContext::set_occlusion_rays_per_frame`].
+pub const DEFAULT_OCCLUSION_RAYS_PER_FRAME: usize = 4;
+
+/// Occlusion settings of a sound source. When the occlusion is enabled, the engine periodically
+/// casts a ray from the listener to the source and if the ray is blocked by a collider, the source
+/// becomes quieter and muffled (its high frequencies are cut by a lowpass filter). The change is
+/// smoothly interpolated over [`Self::smoothing_time`] to prevent clicks.
+///
+/// Rays are cast for a few sources per frame in round-robin fashion (see
+/// [`super::context::SoundContext::set_occlusion_rays_per_frame`]), so the occlusion does not
+/// cost a ray per source per frame. Colliders that contain the listener are ignored, colliders of
+/// the source itself (if any) should be excluded using [`Self::blockers`] groups.
+#[derive(Clone, Debug, PartialEq, Visit, Reflect)]
+pub struct SoundOcclusion {
+    /// Whether the occlusion is enabled or not. Default is `false`.
+    pub enabled: bool,
+
+    /// Gain reduction of a fully occluded source in `0..1` range, `0.0` - no reduction, `1.0` -
+    /// fully occluded source is silent. Default is `0.6`.
+    #[reflect(min_value = 0.0, max_value = 1.0, step = 0.05)]
+    pub max_attenuation: f32,
+
+    /// Cutoff frequency (in Hz) of the lowpass filter of a fully occluded source. Lower values make
+    /// occluded sound more muffled. Default is `800` Hz.
+    #[reflect(min_value = 20.0, max_value = 20000.0, step = 10.0)]
+    pub lowpass_cutoff: f32,
+
+    /// Time (in seconds) of transition between the occluded and the open states. Default is `0.1`.
+    #[reflect(min_value = 0.0, step = 0.01)]
+    pub smoothing_time: f32,
+
+    /// Collision groups of occlusion rays. Colliders that do not interact with the groups do not
+    /// block the sound, it could be used to exclude thin props (like fences or grates).
+    pub blockers: InteractionGroups,
+}
+
+impl Default for SoundOcclusion {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_attenuation: 0.6,
+            lowpass_cutoff: 800.0,
+            smoothing_time: 0.1,
+            blockers: Default::default(),
+        }
+    }
+}
+
+impl SoundOcclusion {
+    /// Returns gain multiplier for the given occlusion level (`0.0` - open, `1.0` - fully occluded).
+    pub fn gain(&self, level: f32) -> f32 {
+        1.0 - self.max_attenuation.clamp(0.0, 1.0) * level.clamp(0.0, 1.0)
+    }
+
+    /// Returns cutoff frequency (in Hz) of the lowpass filter for the given occlusion level or `None`
+    /// if the filter is not needed. The frequency is interpolated in logarithmic scale, so the
+    /// change sounds uniform.
+    pub fn cutoff(&self, level: f32) -> Option<f32> {
+        let level = level.clamp(0.0, 1.0);
+        if level <= 0.0 {
+            return None;
+        }
+        let open = OPEN_CUTOFF.ln();
+        let closed = self.lowpass_cutoff.clamp(1.0, OPEN_CUTOFF).ln();
+        Some((open + (closed - open) * level).exp())
+    }
+
+    /// Returns cutoff frequency of the lowpass filter for the given occlusion level, normalized by
+    /// the sample rate of the sound engine, see [`Self::cutoff`].
+    pub fn normalized_cutoff(&self, level: f32) -> Option<f32> {
+        self.cutoff(level).map(|fc| fc / SAMPLE_RATE as f32)
+    }
+}
+
+/// Moves the occlusion level towards the target with constant speed, so the full transition takes
+/// `smoothing_time` seconds. Zero smoothing time means instant transition.
+pub fn smooth_occlusion(current: f32, target: f32, dt: f32, smoothing_time: f32) -> f32 {
+    if smoothing_time <= 0.0 {
+        return target;
+    }
+    let max_step = dt / smoothing_time;
+    current + (target - current).clamp(-max_step, max_step)
+}
+
+/// Picks sources that should be checked for occlusion on the current frame. Sources are picked in
+/// round-robin fashion, so every source is checked once in `ceil(count / rays_per_frame)` frames.
+#[derive(Default, Debug, Clone)]
+pub struct OcclusionScheduler {
+    next: usize,
+}
+
+impl OcclusionScheduler {
+    /// Returns indices of sources (in `0..count` range) that should be checked on the current frame.
+    /// No more than `rays_per_frame` indices are returned and every index is returned only once.
+    pub fn select(&mut self, count: usize, rays_per_frame: usize) -> Vec<usize> {
+        if count == 0 {
+            self.next = 0;
+            return Vec::new();
+        }
+        // The amount of sources could change between frames.
+        let start = self.next % count;
+        let selected = (0..rays_per_frame.min(count))
+            .map(|i| (start + i) % count)
+            .collect::<Vec<_>>();
+        self.next = (start + selected.len()) % count;
+        selected
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::scene::sound::occlusion::{smooth_occlusion, OcclusionScheduler, SoundOcclusion};
+
+    #[test]
+    fn test_occlusion_smoothing() {
+        // Full transition takes exactly the smoothing time, no overshoot.
+        let mut level = 0.0;
+        for _ in 0..5 {
+            level = smooth_occlusion(level, 1.0, 0.02, 0.1);
+        }
+        assert!((level - 1.0).abs() < 1.0e-5);
+        assert_eq!(smooth_occlusion(level, 1.0, 0.02, 0.1), 1.0);
+
+        // Half way back and then the target changes again.
+        level = smooth_occlusion(1.0, 0.0, 0.05, 0.1);
+        assert!((level - 0.5).abs() < 1.0e-5);
+        level = smooth_occlusion(level, 1.0, 0.025, 0.1);
+        assert!((level - 0.75).abs() < 1.0e-5);
+
+        // Large time step does not overshoot, zero smoothing time is instant.
+        assert_eq!(smooth_occlusion(0.2, 0.0, 10.0, 0.1), 0.0);
+        assert_eq!(smooth_occlusion(0.0, 1.0, 0.001, 0.0), 1.0);
+
+        let occlusion = SoundOcclusion::default();
+        assert_eq!(occlusion.gain(0.0), 1.0);
+        assert!((occlusion.gain(1.0) - 0.4).abs() < 1.0e-5);
+        assert_eq!(occlusion.cutoff(0.0), None);
+        assert!((occlusion.cutoff(1.0).unwrap() - 800.0).abs() < 0.1);
+        // Logarithmic interpolation - geometric mean in the middle.
+        assert!((occlusion.cutoff(0.5).unwrap() - (20000.0f32 * 800.0).sqrt()).abs() < 1.0);
+    }
+
+    #[test]
+    fn test_occlusion_scheduler_fairness() {
+        let mut scheduler = OcclusionScheduler::default();
+        let count = 7;
+        let mut checks = vec![0; count];
+        for _ in 0..21 {
+            let selected = scheduler.select(count, 3);
+            assert_eq!(selected.len(), 3);
+            for index in selected {
+                checks[index] += 1;
+            }
+        }
+        // 21 frames * 3 rays = 63 checks, evenly distributed between 7 sources.
+        assert!(checks.iter().all(|&c| c == 9));
+
+        // Budget larger than amount of sources checks every source once.
+        assert_eq!(scheduler.select(2, 10).len(), 2);
+
+        // Shrinking amount of sources keeps indices valid.
+        scheduler.select(count, 6);
+        assert!(scheduler.select(3, 2).iter().all(|&i| i < 3));
+        assert!(scheduler.select(0, 2).is_empty());
+    }
+}
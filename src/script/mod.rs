@@ -600,6 +600,18 @@ impl Script {
     }
 }
 
+impl ComponentProvider for Script {
+    #[inline]
+    fn query_component_ref(&self, type_id: TypeId) -> Option<&dyn Any> {
+        self.instance.query_component_ref(type_id)
+    }
+
+    #[inline]
+    fn query_component_mut(&mut self, type_id: TypeId) -> Option<&mut dyn Any> {
+        self.instance.query_component_mut(type_id)
+    }
+}
+
 #[cfg(test)]
 mod test {
     use crate::{
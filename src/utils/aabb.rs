@@ -0,0 +1,286 @@
+//! Bounding volumes. See [`AABB`] and [`OrientedBoundingBox`] docs for more info.
+
+use crate::core::{
+    algebra::{Matrix3, Matrix4, Vector3},
+    math::{
+        aabb::AxisAlignedBoundingBox,
+        ray::{IntersectionResult, Ray},
+        Matrix4Ext,
+    },
+    visitor::prelude::*,
+};
+
+/// Axis-aligned bounding box. It is an alias for [`AxisAlignedBoundingBox`] that is used across the
+/// engine, so every subsystem shares the same type. Commonly used methods are:
+///
+/// - [`AxisAlignedBoundingBox::from_points`] - creates a box that encloses all the given points.
+/// - [`AxisAlignedBoundingBox::is_contains_point`] - checks whether a point is inside the box.
+/// - [`AxisAlignedBoundingBox::is_intersects_aabb`] - checks whether two boxes intersect.
+/// - [`AxisAlignedBoundingBox::add_point`], [`AxisAlignedBoundingBox::add_box`],
+///   [`AxisAlignedBoundingBox::inflate`] - expands the box.
+/// - [`AxisAlignedBoundingBox::transform`] - transforms the box by an affine matrix.
+/// - [`Ray::aabb_intersection`] - finds an intersection of a ray with the box.
+pub type AABB = AxisAlignedBoundingBox;
+
+/// Oriented bounding box - a box with arbitrary orientation in space. It fits rotated objects much
+/// tighter than [`AABB`], but intersection tests are more expensive.
+///
+/// ## Example
+///
+/// ```rust
+/// use fyrox::{
+///     core::algebra::{Matrix4, Vector3},
+///     utils::aabb::{OrientedBoundingBox, AABB},
+/// };
+///
+/// let aabb = AABB::from_min_max(Vector3::new(-1.0, -1.0, -1.0), Vector3::new(1.0, 1.0, 1.0));
+/// let transform = Matrix4::new_rotation(Vector3::new(0.0, std::f32::consts::FRAC_PI_4, 0.0));
+/// let obb = OrientedBoundingBox::from_aabb(&aabb, &transform);
+///
+/// assert!(obb.contains(Vector3::new(1.3, 0.0, 0.0)));
+/// assert!(!aabb.is_contains_point(Vector3::new(1.3, 0.0, 0.0)));
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq, Visit)]
+pub struct OrientedBoundingBox {
+    /// Center of the box in world space.
+    pub center: Vector3<f32>,
+    /// Half of the size of the box along each of its local axes.
+    pub half_extents: Vector3<f32>,
+    /// Orthonormal basis of the box, columns are local X, Y, Z axes.
+    pub basis: Matrix3<f32>,
+}
+
+impl Default for OrientedBoundingBox {
+    fn default() -> Self {
+        Self {
+            center: Default::default(),
+            half_extents: Default::default(),
+            basis: Matrix3::identity(),
+        }
+    }
+}
+
+impl OrientedBoundingBox {
+    /// Creates new oriented bounding box from the given axis-aligned bounding box in local space
+    /// and its transformation matrix. The matrix could contain rotation, translation and scale, but
+    /// must not contain shear.
+    pub fn from_aabb(aabb: &AABB, transform: &Matrix4<f32>) -> Self {
+        Self {
+            center: aabb.center(),
+            half_extents: aabb.half_extents(),
+            basis: Matrix3::identity(),
+        }
+        .transform(transform)
+    }
+
+    /// Returns a copy of the box transformed by the given matrix. The matrix could contain
+    /// rotation, translation and scale, but must not contain shear.
+    #[must_use]
+    pub fn transform(&self, transform: &Matrix4<f32>) -> Self {
+        let mut basis = transform.basis() * self.basis;
+        let mut half_extents = self.half_extents;
+        for i in 0..3 {
+            let mut axis = basis.column(i).into_owned();
+            let length = axis.norm();
+            half_extents[i] *= length;
+            if length > f32::EPSILON {
+                axis /= length;
+            } else {
+                axis = self.basis.column(i).into_owned();
+            }
+            basis.set_column(i, &axis);
+        }
+
+        Self {
+            center: transform.transform_point(&self.center.into()).coords,
+            half_extents,
+            basis,
+        }
+    }
+
+    /// Returns the given world-space point in local space of the box, where the box is centered at
+    /// the origin and aligned with the coordinate axes.
+    pub fn to_local(&self, point: Vector3<f32>) -> Vector3<f32> {
+        self.basis.tr_mul(&(point - self.center))
+    }
+
+    /// Checks whether the point is inside the box (inclusive).
+    pub fn contains(&self, point: Vector3<f32>) -> bool {
+        let local = self.to_local(point);
+        (0..3).all(|i| local[i].abs() <= self.half_extents[i])
+    }
+
+    /// Returns corners of the box in world space.
+    pub fn corners(&self) -> [Vector3<f32>; 8] {
+        let x = self.basis.column(0) * self.half_extents.x;
+        let y = self.basis.column(1) * self.half_extents.y;
+        let z = self.basis.column(2) * self.half_extents.z;
+        let c = self.center;
+        [
+            c - x - y - z,
+            c + x - y - z,
+            c - x + y - z,
+            c + x + y - z,
+            c - x - y + z,
+            c + x - y + z,
+            c - x + y + z,
+            c + x + y + z,
+        ]
+    }
+
+    /// Returns the smallest axis-aligned bounding box that encloses this box.
+    pub fn bounds(&self) -> AABB {
+        let mut extents = Vector3::default();
+        for i in 0..3 {
+            for j in 0..3 {
+                extents[i] += (self.basis[(i, j)] * self.half_extents[j]).abs();
+            }
+        }
+        AABB::from_min_max(self.center - extents, self.center + extents)
+    }
+
+    /// Checks whether this box intersects with the other one (touching boxes are considered
+    /// intersecting). Uses separating axis theorem.
+    ///
+    /// # References
+    ///
+    /// Real-Time Collision Detection by Christer Ericson, Morgan Kaufmann, 2005, 4.4.1
+    pub fn intersects(&self, other: &Self) -> bool {
+        // Rotation and translation of the other box in local space of this box.
+        let r = self.basis.tr_mul(&other.basis);
+        let t = self.basis.tr_mul(&(other.center - self.center));
+
+        // Epsilon guards against arithmetic errors when edges are nearly parallel.
+        let abs_r = r.map(|v| v.abs() + f32::EPSILON);
+
+        let a = &self.half_extents;
+        let b = &other.half_extents;
+
+        for i in 0..3 {
+            let ra = a[i];
+            let rb = b.dot(&abs_r.row(i).transpose());
+            if t[i].abs() > ra + rb {
+                return false;
+            }
+        }
+
+        for i in 0..3 {
+            let ra = a.dot(&abs_r.column(i));
+            let rb = b[i];
+            if t.dot(&r.column(i)).abs() > ra + rb {
+                return false;
+            }
+        }
+
+        for i in 0..3 {
+            let (i1, i2) = ((i + 1) % 3, (i + 2) % 3);
+            for j in 0..3 {
+                let (j1, j2) = ((j + 1) % 3, (j + 2) % 3);
+                let ra = a[i1] * abs_r[(i2, j)] + a[i2] * abs_r[(i1, j)];
+                let rb = b[j1] * abs_r[(i, j2)] + b[j2] * abs_r[(i, j1)];
+                if (t[i2] * r[(i1, j)] - t[i1] * r[(i2, j)]).abs() > ra + rb {
+                    return false;
+                }
+            }
+        }
+
+        true
+    }
+
+    /// Checks whether this box intersects with the given axis-aligned bounding box.
+    pub fn intersects_aabb(&self, aabb: &AABB) -> bool {
+        self.intersects(&Self::from_aabb(aabb, &Matrix4::identity()))
+    }
+
+    /// Finds an intersection of the given ray with the box. Returned parameters could be used with
+    /// [`Ray::get_point`] to calculate intersection points.
+    pub fn ray_intersection(&self, ray: &Ray) -> Option<IntersectionResult> {
+        let local_ray = Ray {
+            origin: self.to_local(ray.origin),
+            dir: self.basis.tr_mul(&ray.dir),
+        };
+        local_ray.box_intersection(&-self.half_extents, &self.half_extents)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::{
+            algebra::{Matrix4, Vector3},
+            math::ray::Ray,
+        },
+        utils::aabb::{OrientedBoundingBox, AABB},
+    };
+    use std::f32::consts::FRAC_PI_4;
+
+    fn unit_cube() -> AABB {
+        AABB::from_min_max(Vector3::new(-1.0, -1.0, -1.0), Vector3::new(1.0, 1.0, 1.0))
+    }
+
+    #[test]
+    fn test_obb_transform() {
+        let transform = Matrix4::new_translation(&Vector3::new(10.0, 0.0, 0.0))
+            * Matrix4::new_rotation(Vector3::new(0.0, FRAC_PI_4, 0.0))
+            * Matrix4::new_nonuniform_scaling(&Vector3::new(2.0, 1.0, 1.0));
+        let obb = OrientedBoundingBox::from_aabb(&unit_cube(), &transform);
+
+        assert!((obb.center - Vector3::new(10.0, 0.0, 0.0)).norm() < 1.0e-5);
+        assert!((obb.half_extents - Vector3::new(2.0, 1.0, 1.0)).norm() < 1.0e-5);
+        assert!(obb.contains(Vector3::new(10.0, 0.0, 0.0)));
+        assert!(obb.contains(obb.center + obb.basis.column(0) * 1.9));
+        assert!(!obb.contains(obb.center + obb.basis.column(1) * 1.1));
+
+        let bounds = obb.bounds();
+        for corner in obb.corners() {
+            assert!(bounds.is_contains_point(corner));
+        }
+    }
+
+    #[test]
+    fn test_obb_intersection() {
+        let rotation = Matrix4::new_rotation(Vector3::new(0.0, FRAC_PI_4, 0.0));
+        let a = OrientedBoundingBox::from_aabb(&unit_cube(), &rotation);
+
+        // The rotated corner reaches sqrt(2) along X.
+        let near = OrientedBoundingBox::from_aabb(
+            &unit_cube(),
+            &Matrix4::new_translation(&Vector3::new(2.3, 0.0, 0.0)),
+        );
+        let far = OrientedBoundingBox::from_aabb(
+            &unit_cube(),
+            &Matrix4::new_translation(&Vector3::new(2.5, 0.0, 0.0)),
+        );
+        assert!(a.intersects(&near));
+        assert!(near.intersects(&a));
+        assert!(!a.intersects(&far));
+        assert!(!far.intersects(&a));
+
+        // Boxes whose AABBs overlap, but the boxes themselves do not.
+        let b = OrientedBoundingBox::from_aabb(
+            &unit_cube(),
+            &(Matrix4::new_translation(&Vector3::new(2.3, 0.0, 2.3)) * rotation),
+        );
+        assert!(a.bounds().is_intersects_aabb(&b.bounds()));
+        assert!(!a.intersects(&b));
+
+        assert!(a.intersects_aabb(&unit_cube()));
+    }
+
+    #[test]
+    fn test_obb_ray_intersection() {
+        let obb = OrientedBoundingBox::from_aabb(
+            &unit_cube(),
+            &(Matrix4::new_translation(&Vector3::new(0.0, 0.0, 5.0))
+                * Matrix4::new_rotation(Vector3::new(0.0, FRAC_PI_4, 0.0))),
+        );
+
+        let ray = Ray::new(Vector3::default(), Vector3::new(0.0, 0.0, 10.0));
+        let result = obb.ray_intersection(&ray).unwrap();
+        let distance = 5.0 - std::f32::consts::SQRT_2;
+        assert!((ray.get_point(result.min).z - distance).abs() < 1.0e-5);
+
+        let miss = Ray::new(Vector3::new(2.0, 0.0, 0.0), Vector3::new(0.0, 0.0, 10.0));
+        assert!(obb.ray_intersection(&miss).is_none());
+    }
+}
@@ -6,12 +6,22 @@
 
 #![warn(missing_docs)]
 
-use crate::core::{
-    algebra::Vector3,
-    math::{self, PositionProvider},
-    visitor::prelude::*,
+use crate::{
+    core::{
+        algebra::Vector3,
+        math::{self, PositionProvider},
+        visitor::{prelude::*, VisitorFormat},
+    },
+    fxhash::{FxHashMap, FxHashSet},
+    utils::small_vec::ResultBuffer,
+};
+use std::{
+    cmp::Ordering,
+    collections::BinaryHeap,
+    fmt::{Display, Formatter},
+    io::{Read, Write},
+    ops::ControlFlow,
 };
-use std::fmt::{Display, Formatter};
 
 #[derive(Copy, Clone, PartialEq, Eq, Debug)]
 enum PathVertexState {
@@ -27,27 +37,16 @@ pub struct PathVertex {
     /// Position in the world coordinates
     pub position: Vector3<f32>,
     pub(crate) neighbours: Vec<u32>,
-    #[visit(skip)]
-    state: PathVertexState,
-    #[visit(skip)]
+    // Optional for compatibility with old graphs, missing penalty is 1.0 (see `Default` impl).
+    #[visit(optional)]
     g_penalty: f32,
-    #[visit(skip)]
-    g_score: f32,
-    #[visit(skip)]
-    f_score: f32,
-    #[visit(skip)]
-    parent: Option<usize>,
 }
 
 impl Default for PathVertex {
     fn default() -> Self {
         Self {
             position: Default::default(),
-            parent: None,
             g_penalty: 1f32,
-            g_score: f32::MAX,
-            f_score: f32::MAX,
-            state: PathVertexState::NonVisited,
             neighbours: Default::default(),
         }
     }
@@ -58,11 +57,7 @@ impl PathVertex {
     pub fn new(position: Vector3<f32>) -> Self {
         Self {
             position,
-            parent: None,
             g_penalty: 1f32,
-            g_score: f32::MAX,
-            f_score: f32::MAX,
-            state: PathVertexState::NonVisited,
             neighbours: Default::default(),
         }
     }
@@ -78,14 +73,108 @@ impl PathVertex {
     pub fn set_penalty(&mut self, new_penalty: f32) {
         self.g_penalty = new_penalty;
     }
+}
+
+#[derive(Copy, Clone, Debug)]
+struct SearchState {
+    state: PathVertexState,
+    g_score: f32,
+    f_score: f32,
+    parent: Option<usize>,
+}
+
+impl Default for SearchState {
+    fn default() -> Self {
+        Self {
+            state: PathVertexState::NonVisited,
+            g_score: f32::MAX,
+            f_score: f32::MAX,
+            parent: None,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+struct OpenSetEntry {
+    f_score: f32,
+    index: usize,
+}
+
+impl PartialEq for OpenSetEntry {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for OpenSetEntry {}
+
+impl PartialOrd for OpenSetEntry {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for OpenSetEntry {
+    // Reversed, so the binary heap will give an entry with the lowest f-score (and the lowest index
+    // among equal f-scores) first.
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .f_score
+            .partial_cmp(&self.f_score)
+            .unwrap_or(Ordering::Equal)
+            .then_with(|| other.index.cmp(&self.index))
+    }
+}
+
+/// Pathfinding context holds intermediate state of a path search. The context could be re-used
+/// across multiple searches (even on different path finders) to avoid memory allocations on every
+/// search, it is especially useful when you need to build paths for lots of agents every frame.
+/// See [`PathFinder::build_in`] for more info.
+#[derive(Clone, Debug, Default)]
+pub struct PathfindingContext {
+    states: Vec<SearchState>,
+    open_set: BinaryHeap<OpenSetEntry>,
+    // Backward search of bidirectional search.
+    reverse_states: Vec<SearchState>,
+    reverse_open_set: BinaryHeap<OpenSetEntry>,
+    // Reverse links in compressed form, links to a vertex `i` are stored in
+    // `reverse_links[reverse_offsets[i]..reverse_offsets[i + 1]]`.
+    reverse_offsets: Vec<usize>,
+    reverse_links: Vec<u32>,
+}
+
+impl PathfindingContext {
+    /// Creates new empty pathfinding context.
+    pub fn new() -> Self {
+        Self::default()
+    }
 
-    fn clear(&mut self) {
-        self.g_penalty = 1f32;
-        self.g_score = f32::MAX;
-        self.f_score = f32::MAX;
-        self.state = PathVertexState::NonVisited;
-        self.parent = None;
+    fn reset(&mut self, vertex_count: usize) {
+        self.states.clear();
+        self.states.resize(vertex_count, Default::default());
+        self.open_set.clear();
     }
+
+    fn reset_reverse(&mut self, vertex_count: usize) {
+        self.reverse_states.clear();
+        self.reverse_states.resize(vertex_count, Default::default());
+        self.reverse_open_set.clear();
+    }
+}
+
+// Removes outdated entries from the top of the open set and returns the actual top entry.
+fn peek_open(
+    open_set: &mut BinaryHeap<OpenSetEntry>,
+    states: &[SearchState],
+) -> Option<OpenSetEntry> {
+    while let Some(entry) = open_set.peek().copied() {
+        let state = &states[entry.index];
+        if state.state == PathVertexState::Open && state.f_score == entry.f_score {
+            return Some(entry);
+        }
+        open_set.pop();
+    }
+    None
 }
 
 /// See module docs.
@@ -135,6 +224,10 @@ pub enum PathError {
     /// There is a vertex that has itself as neighbour.
     CyclicReferenceFound(usize),
 
+    /// There is no path between begin and end vertices, because they're not connected with each other
+    /// (for example, they're on different islands of a navmesh).
+    Unreachable(usize, usize),
+
     /// User-defined error.
     Custom(String),
 }
@@ -148,6 +241,9 @@ impl Display for PathError {
             PathError::CyclicReferenceFound(v) => {
                 write!(f, "Cyclical reference was found {v}.")
             }
+            PathError::Unreachable(from, to) => {
+                write!(f, "Vertex {to} is unreachable from vertex {from}.")
+            }
             PathError::Custom(v) => {
                 write!(f, "An error has occurred {v}")
             }
@@ -156,6 +252,9 @@ impl Display for PathError {
 }
 
 impl PathFinder {
+    /// Current version of the format used by [`Self::save`] and [`Self::load`].
+    pub const FORMAT_VERSION: u32 = 1;
+
     /// Creates new empty path finder.
     pub fn new() -> Self {
         Self {
@@ -197,6 +296,16 @@ impl PathFinder {
         }
     }
 
+    /// Moves a vertex at the given index to the new position, does nothing if the index is out of
+    /// bounds. Links of the vertex are left unchanged. Costs of the links and the heuristic are
+    /// calculated from current positions on every search, so the next search will use the new
+    /// position without rebuilding the graph.
+    pub fn set_vertex_position(&mut self, index: usize, position: Vector3<f32>) {
+        if let Some(vertex) = self.vertices.get_mut(index) {
+            vertex.position = position;
+        }
+    }
+
     /// Returns shared reference to a path vertex at the given index.
     pub fn vertex(&self, index: usize) -> Option<&PathVertex> {
         self.vertices.get(index)
@@ -286,12 +395,13 @@ impl PathFinder {
     /// # Notes
     ///
     /// This is more or less naive implementation, it most certainly will be slower than specialized solutions.
-    pub fn build(
-        &mut self,
-        from: usize,
-        to: usize,
-        path: &mut Vec<Vector3<f32>>,
-    ) -> Result<PathKind, PathError> {
+    /// The method allocates temporary memory on every call, use [`Self::build_in`] to avoid that. The path
+    /// could be stored in [`Vec`] or in [`crate::utils::small_vec::SmallVec`], the latter does not allocate
+    /// memory for short paths.
+    pub fn build<P>(&mut self, from: usize, to: usize, path: &mut P) -> Result<PathKind, PathError>
+    where
+        P: ResultBuffer<Vector3<f32>>,
+    {
         self.build_and_convert(from, to, path, |_, v| v.position)
     }
 
@@ -304,15 +414,121 @@ impl PathFinder {
     /// # Notes
     ///
     /// This is more or less naive implementation, it most certainly will be slower than specialized solutions.
-    pub fn build_and_convert<F, T>(
+    /// The method allocates temporary memory on every call, use [`Self::build_and_convert_in`] to avoid that.
+    pub fn build_and_convert<F, T, P>(
         &mut self,
         from: usize,
         to: usize,
-        path: &mut Vec<T>,
+        path: &mut P,
+        func: F,
+    ) -> Result<PathKind, PathError>
+    where
+        F: FnMut(usize, &PathVertex) -> T,
+        P: ResultBuffer<T>,
+    {
+        self.build_and_convert_in(&mut PathfindingContext::new(), from, to, path, func)
+    }
+
+    /// Same as [`Self::build`], but uses the given context to store intermediate search state. The
+    /// context is cleared and re-used, so repeated searches won't allocate any memory (except the
+    /// first one). Since the path finder itself is not modified, multiple searches could be done
+    /// on the same path finder in parallel, each with its own context.
+    pub fn build_in<P>(
+        &self,
+        ctx: &mut PathfindingContext,
+        from: usize,
+        to: usize,
+        path: &mut P,
+    ) -> Result<PathKind, PathError>
+    where
+        P: ResultBuffer<Vector3<f32>>,
+    {
+        self.build_and_convert_in(ctx, from, to, path, |_, v| v.position)
+    }
+
+    /// Same as [`Self::build_and_convert`], but uses the given context to store intermediate search
+    /// state. See [`Self::build_in`] for more info.
+    pub fn build_and_convert_in<F, T, P>(
+        &self,
+        ctx: &mut PathfindingContext,
+        from: usize,
+        to: usize,
+        path: &mut P,
+        func: F,
+    ) -> Result<PathKind, PathError>
+    where
+        F: FnMut(usize, &PathVertex) -> T,
+        P: ResultBuffer<T>,
+    {
+        self.build_and_convert_filtered_in(ctx, from, to, path, func, |_, _| Some(1.0))
+    }
+
+    /// Same as [`Self::build_and_convert_in`], but uses the given `edge_cost` function to filter and
+    /// weight links between vertices. The function takes indices of two linked vertices and returns
+    /// either `None` if the link cannot be traversed, or a cost multiplier of the link.
+    pub fn build_and_convert_filtered_in<F, T, C, P>(
+        &self,
+        ctx: &mut PathfindingContext,
+        from: usize,
+        to: usize,
+        path: &mut P,
+        func: F,
+        edge_cost: C,
+    ) -> Result<PathKind, PathError>
+    where
+        F: FnMut(usize, &PathVertex) -> T,
+        C: FnMut(usize, usize) -> Option<f32>,
+        P: ResultBuffer<T>,
+    {
+        self.search(ctx, from, to, path, func, edge_cost, |_, _| {
+            ControlFlow::Continue(())
+        })
+    }
+
+    /// Same as [`Self::build_and_convert_filtered_in`], but also calls `on_expand` for every vertex
+    /// expanded by the search (the destination vertex is not expanded). The function takes index of the
+    /// vertex and the vertex itself, and returns [`ControlFlow::Break`] to stop the search early - in
+    /// this case partial path to the closest vertex found so far is built. It could be used to limit
+    /// explored area, or to collect explored vertices for visualization. Neighbours could be vetoed
+    /// using `edge_cost` function.
+    #[allow(clippy::too_many_arguments)]
+    pub fn build_and_convert_observed_in<F, T, C, E, P>(
+        &self,
+        ctx: &mut PathfindingContext,
+        from: usize,
+        to: usize,
+        path: &mut P,
+        func: F,
+        edge_cost: C,
+        on_expand: E,
+    ) -> Result<PathKind, PathError>
+    where
+        F: FnMut(usize, &PathVertex) -> T,
+        C: FnMut(usize, usize) -> Option<f32>,
+        E: FnMut(usize, &PathVertex) -> ControlFlow<()>,
+        P: ResultBuffer<T>,
+    {
+        self.search(ctx, from, to, path, func, edge_cost, on_expand)
+    }
+
+    // Generic over `on_expand`, so the searches without it do not pay for it.
+    #[allow(clippy::too_many_arguments)]
+    #[inline(always)]
+    fn search<F, T, C, E, P>(
+        &self,
+        ctx: &mut PathfindingContext,
+        from: usize,
+        to: usize,
+        path: &mut P,
         func: F,
+        mut edge_cost: C,
+        mut on_expand: E,
     ) -> Result<PathKind, PathError>
     where
         F: FnMut(usize, &PathVertex) -> T,
+        C: FnMut(usize, usize) -> Option<f32>,
+        E: FnMut(usize, &PathVertex) -> ControlFlow<()>,
+        P: ResultBuffer<T>,
     {
         if self.vertices.is_empty() {
             return Ok(PathKind::Empty);
@@ -320,9 +536,7 @@ impl PathFinder {
 
         path.clear();
 
-        for vertex in self.vertices.iter_mut() {
-            vertex.clear();
-        }
+        ctx.reset(self.vertices.len());
 
         let end_pos = self
             .vertices
@@ -331,86 +545,96 @@ impl PathFinder {
             .position;
 
         // Put start vertex in open set.
-        let start = self
+        let start_position = self
             .vertices
-            .get_mut(from)
-            .ok_or(PathError::InvalidIndex(from))?;
+            .get(from)
+            .ok_or(PathError::InvalidIndex(from))?
+            .position;
+        let start = &mut ctx.states[from];
         start.state = PathVertexState::Open;
         start.g_score = 0.0;
-        start.f_score = heuristic(start.position, end_pos);
-
-        let mut open_set_size = 1;
-        while open_set_size > 0 {
-            let mut current_index = 0;
-            let mut lowest_f_score = f32::MAX;
-            for (i, vertex) in self.vertices.iter().enumerate() {
-                if vertex.state == PathVertexState::Open && vertex.f_score < lowest_f_score {
-                    current_index = i;
-                    lowest_f_score = vertex.f_score;
-                }
+        start.f_score = heuristic(start_position, end_pos);
+        ctx.open_set.push(OpenSetEntry {
+            f_score: start.f_score,
+            index: from,
+        });
+
+        while let Some(entry) = ctx.open_set.pop() {
+            let current_index = entry.index;
+            let current_state = &mut ctx.states[current_index];
+
+            // Skip outdated entries, the vertex was either closed or its f-score was improved.
+            if current_state.state != PathVertexState::Open
+                || current_state.f_score != entry.f_score
+            {
+                continue;
             }
 
             if current_index == to {
-                self.reconstruct_path(current_index, path, func);
+                self.reconstruct_path(ctx, current_index, path, func);
                 return Ok(PathKind::Full);
             }
 
-            open_set_size -= 1;
+            let current_vertex = &self.vertices[current_index];
 
-            // Take second mutable reference to vertices array, we'll enforce borrowing rules
-            // at runtime. It will *never* give two mutable references to same path vertex.
-            let unsafe_vertices: &mut Vec<PathVertex> =
-                unsafe { &mut *(&mut self.vertices as *mut _) };
-
-            let current_vertex = self
-                .vertices
-                .get_mut(current_index)
-                .ok_or(PathError::InvalidIndex(current_index))?;
+            if let ControlFlow::Break(()) = on_expand(current_index, current_vertex) {
+                break;
+            }
 
-            current_vertex.state = PathVertexState::Closed;
+            let current_state = &mut ctx.states[current_index];
+            current_state.state = PathVertexState::Closed;
+            let current_g_score = current_state.g_score;
 
             for neighbour_index in current_vertex.neighbours.iter() {
-                // Make sure that borrowing rules are not violated.
-                if *neighbour_index as usize == current_index {
+                let neighbour_index = *neighbour_index as usize;
+
+                if neighbour_index == current_index {
                     return Err(PathError::CyclicReferenceFound(current_index));
                 }
 
-                // Safely get mutable reference to neighbour
-                let neighbour = unsafe_vertices
-                    .get_mut(*neighbour_index as usize)
-                    .ok_or(PathError::InvalidIndex(*neighbour_index as usize))?;
+                let neighbour = self
+                    .vertices
+                    .get(neighbour_index)
+                    .ok_or(PathError::InvalidIndex(neighbour_index))?;
 
-                let g_score = current_vertex.g_score
+                let cost_multiplier =
+                    if let Some(cost_multiplier) = edge_cost(current_index, neighbour_index) {
+                        cost_multiplier
+                    } else {
+                        continue;
+                    };
+
+                let g_score = current_g_score
                     + ((current_vertex.position - neighbour.position).norm_squared()
-                        * neighbour.g_penalty);
-                if g_score < neighbour.g_score {
-                    neighbour.parent = Some(current_index);
-                    neighbour.g_score = g_score;
-                    neighbour.f_score = g_score + heuristic(neighbour.position, end_pos);
-
-                    if neighbour.state != PathVertexState::Open {
-                        neighbour.state = PathVertexState::Open;
-                        open_set_size += 1;
-                    }
+                        * neighbour.g_penalty
+                        * cost_multiplier);
+
+                let neighbour_state = &mut ctx.states[neighbour_index];
+                if g_score < neighbour_state.g_score {
+                    neighbour_state.parent = Some(current_index);
+                    neighbour_state.g_score = g_score;
+                    neighbour_state.f_score = g_score + heuristic(neighbour.position, end_pos);
+                    neighbour_state.state = PathVertexState::Open;
+
+                    ctx.open_set.push(OpenSetEntry {
+                        f_score: neighbour_state.f_score,
+                        index: neighbour_index,
+                    });
                 }
             }
         }
 
-        // No direct path found, then there is probably partial path exists.
+        // No direct path found (or the search was stopped), then there is probably partial path exists.
         // Look for vertex with least f_score and use it as starting point to
         // reconstruct partial path.
         let mut closest_index = 0;
-        for (i, vertex) in self.vertices.iter().enumerate() {
-            let closest_vertex = self
-                .vertices
-                .get(closest_index)
-                .ok_or(PathError::InvalidIndex(closest_index))?;
-            if vertex.f_score < closest_vertex.f_score {
+        for (i, state) in ctx.states.iter().enumerate() {
+            if state.f_score < ctx.states[closest_index].f_score {
                 closest_index = i;
             }
         }
 
-        self.reconstruct_path(closest_index, path, func);
+        self.reconstruct_path(ctx, closest_index, path, func);
 
         if path.is_empty() {
             Ok(PathKind::Empty)
@@ -419,13 +643,308 @@ impl PathFinder {
         }
     }
 
-    fn reconstruct_path<F, T>(&self, mut current: usize, path: &mut Vec<T>, mut func: F)
+    /// Same as [`Self::build`], but searches from both ends at once and stops when the searches meet in
+    /// the middle, which explores a lot less vertices on long paths across large graphs. Unlike
+    /// [`Self::build`], the path is always the cheapest one, because the search uses a heuristic which
+    /// never overestimates the cost of the rest of a path (the heuristic of [`Self::build`] does).
+    ///
+    /// # Notes
+    ///
+    /// Every call scans all links of the graph to collect reverse links and to calculate the heuristic,
+    /// so the method pays off only on long paths. The method allocates temporary memory on every call,
+    /// use [`Self::build_path_bidirectional_in`] to avoid that.
+    pub fn build_path_bidirectional<P>(
+        &self,
+        from: usize,
+        to: usize,
+        path: &mut P,
+    ) -> Result<PathKind, PathError>
+    where
+        P: ResultBuffer<Vector3<f32>>,
+    {
+        self.build_path_bidirectional_in(&mut PathfindingContext::new(), from, to, path)
+    }
+
+    /// Same as [`Self::build_path_bidirectional`], but uses the given context to store intermediate
+    /// search state. See [`Self::build_in`] for more info.
+    pub fn build_path_bidirectional_in<P>(
+        &self,
+        ctx: &mut PathfindingContext,
+        from: usize,
+        to: usize,
+        path: &mut P,
+    ) -> Result<PathKind, PathError>
     where
+        P: ResultBuffer<Vector3<f32>>,
+    {
+        if self.vertices.is_empty() {
+            return Ok(PathKind::Empty);
+        }
+
+        path.clear();
+
+        let start_position = self
+            .vertices
+            .get(from)
+            .ok_or(PathError::InvalidIndex(from))?
+            .position;
+        let end_position = self
+            .vertices
+            .get(to)
+            .ok_or(PathError::InvalidIndex(to))?
+            .position;
+
+        ctx.reset(self.vertices.len());
+        ctx.reset_reverse(self.vertices.len());
+
+        let PathfindingContext {
+            states,
+            open_set,
+            reverse_states,
+            reverse_open_set,
+            reverse_offsets,
+            reverse_links,
+        } = ctx;
+
+        // Collect reverse links and find the lowest ratio between cost and length among all the links.
+        // Cost of a path is never less than its length multiplied by the ratio, so the heuristic based
+        // on straight-line distances multiplied by the ratio is consistent.
+        let mut cost_ratio = f32::MAX;
+        reverse_offsets.clear();
+        reverse_offsets.resize(self.vertices.len() + 1, 0);
+        for (index, vertex) in self.vertices.iter().enumerate() {
+            for &neighbour_index in vertex.neighbours.iter() {
+                let neighbour_index = neighbour_index as usize;
+                if neighbour_index == index {
+                    return Err(PathError::CyclicReferenceFound(index));
+                }
+                let neighbour = self
+                    .vertices
+                    .get(neighbour_index)
+                    .ok_or(PathError::InvalidIndex(neighbour_index))?;
+                cost_ratio = cost_ratio
+                    .min((vertex.position - neighbour.position).norm() * neighbour.g_penalty);
+                reverse_offsets[neighbour_index + 1] += 1;
+            }
+        }
+        if !(cost_ratio > 0.0 && cost_ratio < f32::MAX) {
+            cost_ratio = 0.0;
+        }
+        for i in 1..reverse_offsets.len() {
+            reverse_offsets[i] += reverse_offsets[i - 1];
+        }
+        reverse_links.clear();
+        reverse_links.resize(reverse_offsets[self.vertices.len()], 0);
+        for (index, vertex) in self.vertices.iter().enumerate() {
+            for &neighbour_index in vertex.neighbours.iter() {
+                let offset = &mut reverse_offsets[neighbour_index as usize];
+                reverse_links[*offset] = index as u32;
+                *offset += 1;
+            }
+        }
+        // Every offset now points to the end of its range, shift them back.
+        for i in (1..reverse_offsets.len()).rev() {
+            reverse_offsets[i] = reverse_offsets[i - 1];
+        }
+        reverse_offsets[0] = 0;
+
+        // Average of the forward and the backward heuristics, the forward search uses it as is and the
+        // backward search uses it with the opposite sign. It makes both searches consistent with each
+        // other, so the search could be stopped as soon as the sum of the lowest f-scores of both sides
+        // reaches the cost of the best found path.
+        let potential = |position: Vector3<f32>| {
+            0.5 * cost_ratio
+                * ((position - end_position).norm() - (position - start_position).norm())
+        };
+
+        for (index, sign, states, open_set) in [
+            (from, 1.0, &mut *states, &mut *open_set),
+            (to, -1.0, &mut *reverse_states, &mut *reverse_open_set),
+        ] {
+            let state = &mut states[index];
+            state.state = PathVertexState::Open;
+            state.g_score = 0.0;
+            state.f_score = sign * potential(self.vertices[index].position);
+            open_set.push(OpenSetEntry {
+                f_score: state.f_score,
+                index,
+            });
+        }
+
+        let mut best_cost = if from == to { 0.0 } else { f32::MAX };
+        // A link between the vertex of the forward search and the vertex of the backward search.
+        let mut meeting = (from, to);
+
+        loop {
+            let forward_top = peek_open(open_set, states);
+            let reverse_top = peek_open(reverse_open_set, reverse_states);
+            let forward = match (forward_top, reverse_top) {
+                (Some(forward_top), Some(reverse_top)) => {
+                    if forward_top.f_score + reverse_top.f_score >= best_cost {
+                        break;
+                    }
+                    forward_top.f_score <= reverse_top.f_score
+                }
+                // There's no path, explore everything reachable to find the closest vertex for a
+                // partial path.
+                (Some(_), None) if best_cost == f32::MAX => true,
+                _ => break,
+            };
+
+            let (states, other_states, open_set, sign) = if forward {
+                (&mut *states, &*reverse_states, &mut *open_set, 1.0)
+            } else {
+                (&mut *reverse_states, &*states, &mut *reverse_open_set, -1.0)
+            };
+
+            let current_index = open_set.pop().unwrap().index;
+            let current_state = &mut states[current_index];
+            current_state.state = PathVertexState::Closed;
+            let current_g_score = current_state.g_score;
+            let current_vertex = &self.vertices[current_index];
+
+            let neighbours = if forward {
+                &current_vertex.neighbours
+            } else {
+                &reverse_links[reverse_offsets[current_index]..reverse_offsets[current_index + 1]]
+            };
+
+            for &neighbour_index in neighbours.iter() {
+                let neighbour_index = neighbour_index as usize;
+                let neighbour = &self.vertices[neighbour_index];
+
+                // Penalty of the destination vertex of a link.
+                let penalty = if forward {
+                    neighbour.g_penalty
+                } else {
+                    current_vertex.g_penalty
+                };
+                let g_score = current_g_score
+                    + (current_vertex.position - neighbour.position).norm_squared() * penalty;
+
+                let other_g_score = other_states[neighbour_index].g_score;
+                if other_g_score != f32::MAX && g_score + other_g_score < best_cost {
+                    best_cost = g_score + other_g_score;
+                    meeting = if forward {
+                        (current_index, neighbour_index)
+                    } else {
+                        (neighbour_index, current_index)
+                    };
+                }
+
+                let neighbour_state = &mut states[neighbour_index];
+                if g_score < neighbour_state.g_score {
+                    neighbour_state.parent = Some(current_index);
+                    neighbour_state.g_score = g_score;
+                    neighbour_state.f_score = g_score + sign * potential(neighbour.position);
+                    neighbour_state.state = PathVertexState::Open;
+
+                    open_set.push(OpenSetEntry {
+                        f_score: neighbour_state.f_score,
+                        index: neighbour_index,
+                    });
+                }
+            }
+        }
+
+        if best_cost == f32::MAX {
+            // No path, build partial path to the reached vertex closest to the destination.
+            let mut closest_index = from;
+            let mut closest_distance = f32::MAX;
+            for (i, state) in ctx.states.iter().enumerate() {
+                let distance = (self.vertices[i].position - end_position).norm_squared();
+                if state.g_score != f32::MAX && distance < closest_distance {
+                    closest_index = i;
+                    closest_distance = distance;
+                }
+            }
+
+            self.reconstruct_path(ctx, closest_index, path, |_, v| v.position);
+
+            return Ok(PathKind::Partial);
+        }
+
+        // Path goes from the end to the beginning, as the paths built by other methods.
+        let (forward_index, reverse_index) = meeting;
+        if forward_index != reverse_index {
+            let mut current = Some(reverse_index);
+            while let Some(index) = current {
+                path.push(self.vertices[index].position);
+                current = ctx.reverse_states[index].parent;
+            }
+            path.reverse();
+        }
+        self.reconstruct_path(ctx, forward_index, path, |_, v| v.position);
+
+        Ok(PathKind::Full)
+    }
+
+    /// Writes the graph (positions, penalties and links of every vertex) in the given format, so it
+    /// can be baked by an offline tool and loaded back using [`Self::load`]. The data is prefixed with
+    /// a version of the graph format, see [`Self::FORMAT_VERSION`].
+    pub fn save<W: Write>(&mut self, mut writer: W, format: VisitorFormat) -> VisitResult {
+        let mut visitor = Visitor::new();
+        let mut region = visitor.enter_region("PathFinder")?;
+        let mut version = Self::FORMAT_VERSION;
+        version.visit("Version", &mut region)?;
+        self.visit("Graph", &mut region)?;
+        drop(region);
+
+        match format {
+            VisitorFormat::Binary => visitor.save_binary_to_memory(writer),
+            VisitorFormat::Text => {
+                writer.write_all(visitor.save_text_format().as_bytes())?;
+                Ok(())
+            }
+        }
+    }
+
+    /// Reads the graph written by [`Self::save`], the format is detected automatically. Fails if the
+    /// data was written by a newer version of the format or if there is a link to a non-existent
+    /// vertex.
+    pub fn load<R: Read>(mut reader: R) -> Result<Self, VisitError> {
+        let mut data = Vec::new();
+        reader.read_to_end(&mut data)?;
+        let mut visitor = Visitor::load_from_memory(data)?;
+        let mut region = visitor.enter_region("PathFinder")?;
+
+        let mut version = 0u32;
+        version.visit("Version", &mut region)?;
+        if version > Self::FORMAT_VERSION {
+            return Err(VisitError::UnsupportedVersion(version));
+        }
+
+        let mut graph = Self::default();
+        graph.visit("Graph", &mut region)?;
+
+        for (index, vertex) in graph.vertices.iter().enumerate() {
+            if let Some(neighbour) = vertex
+                .neighbours
+                .iter()
+                .find(|n| **n as usize >= graph.vertices.len())
+            {
+                return Err(VisitError::User(format!(
+                    "Vertex {index} has a link to non-existent vertex {neighbour}!"
+                )));
+            }
+        }
+
+        Ok(graph)
+    }
+
+    fn reconstruct_path<F, T, P>(
+        &self,
+        ctx: &PathfindingContext,
+        mut current: usize,
+        path: &mut P,
+        mut func: F,
+    ) where
         F: FnMut(usize, &PathVertex) -> T,
+        P: ResultBuffer<T>,
     {
         while let Some(vertex) = self.vertices.get(current) {
             path.push(func(current, vertex));
-            if let Some(parent) = vertex.parent {
+            if let Some(parent) = ctx.states[current].parent {
                 current = parent;
             } else {
                 break;
@@ -434,13 +953,676 @@ impl PathFinder {
     }
 }
 
+/// A group of vertices of [`HierarchicalPathFinder`], that lie in the same cell of a uniform grid.
+#[derive(Clone, Debug, Default)]
+struct Cluster {
+    vertices: Vec<u32>,
+    /// Sorted list of vertices, that are linked with vertices of other clusters. Entrances are the
+    /// nodes of the abstract graph.
+    entrances: Vec<u32>,
+    /// Costs of paths between entrances inside the cluster (row-major, `entrances.len()` squared),
+    /// `f32::MAX` if there's no path.
+    distances: Vec<f32>,
+    adjacent: FxHashSet<u32>,
+}
+
+/// Entrances longer than this amount of links get two transitions (at both ends) instead of one in
+/// the middle.
+const LONG_ENTRANCE_LENGTH: usize = 6;
+
+/// Hierarchical path finder (HPA*) is a wrapper over [`PathFinder`], that is suitable for large
+/// graphs (for example, grids with millions of vertices). It splits the graph into clusters using
+/// uniform grid with the given cell size and precomputes costs of paths between the vertices at the
+/// borders of clusters (entrances). A search is done on the small abstract graph of entrances first
+/// and then the found path is refined inside every cluster along the abstract path.
+///
+/// Paths are usually a bit longer than the paths built by flat search, in exchange for much faster
+/// searches on large graphs. The graph must not be changed without notifying the path finder, see
+/// [`Self::invalidate`].
+#[derive(Clone, Debug)]
+pub struct HierarchicalPathFinder {
+    path_finder: PathFinder,
+    cluster_size: f32,
+    heuristic_weight: f32,
+    cluster_keys: FxHashMap<[i32; 3], u32>,
+    clusters: Vec<Cluster>,
+    vertex_cluster: Vec<u32>,
+    local_index: Vec<u32>,
+    reverse_neighbours: Vec<Vec<u32>>,
+    // Chosen links between adjacent clusters, the key is a pair of cluster indices (smaller first),
+    // each link is stored as a pair of vertices in the same order as clusters.
+    transitions: FxHashMap<(u32, u32), Vec<(u32, u32)>>,
+    // Entrance -> entrances of other clusters it is linked to.
+    links: FxHashMap<u32, Vec<u32>>,
+}
+
+impl HierarchicalPathFinder {
+    /// Creates new hierarchical path finder from the given graph. `cluster_size` defines the size of
+    /// a cluster in world units, it should be large enough to contain 10-30 vertices along each axis.
+    pub fn new(path_finder: PathFinder, cluster_size: f32) -> Self {
+        let mut hierarchical = Self {
+            path_finder,
+            cluster_size: cluster_size.max(f32::EPSILON),
+            heuristic_weight: 1.0,
+            cluster_keys: Default::default(),
+            clusters: Default::default(),
+            vertex_cluster: Default::default(),
+            local_index: Default::default(),
+            reverse_neighbours: Default::default(),
+            transitions: Default::default(),
+            links: Default::default(),
+        };
+        hierarchical.rebuild();
+        hierarchical
+    }
+
+    /// Returns shared reference to the inner graph.
+    pub fn path_finder(&self) -> &PathFinder {
+        &self.path_finder
+    }
+
+    /// Returns mutable reference to the inner graph. Changed vertices must be passed to
+    /// [`Self::invalidate`] (or [`Self::rebuild`] must be called) after modification.
+    pub fn path_finder_mut(&mut self) -> &mut PathFinder {
+        &mut self.path_finder
+    }
+
+    /// Moves a vertex at the given index to the new position, links of the vertex are left
+    /// unchanged. Cached costs of the clusters of the vertex (and the cluster itself, if the vertex
+    /// was moved to another one) are updated immediately, see [`Self::invalidate`].
+    pub fn set_vertex_position(&mut self, index: usize, position: Vector3<f32>) {
+        self.path_finder.set_vertex_position(index, position);
+        self.invalidate([index]);
+    }
+
+    /// Returns the inner graph.
+    pub fn into_inner(self) -> PathFinder {
+        self.path_finder
+    }
+
+    /// Returns size of a cluster in world units.
+    pub fn cluster_size(&self) -> f32 {
+        self.cluster_size
+    }
+
+    /// Sets weight of the heuristic of the abstract graph search. Values above `1.0` make searches
+    /// faster (especially on maze-like graphs), but paths become longer. Default is `1.0`.
+    pub fn set_heuristic_weight(&mut self, weight: f32) {
+        self.heuristic_weight = weight.max(0.0);
+    }
+
+    /// Returns weight of the heuristic of the abstract graph search.
+    pub fn heuristic_weight(&self) -> f32 {
+        self.heuristic_weight
+    }
+
+    /// Returns amount of clusters.
+    pub fn cluster_count(&self) -> usize {
+        self.clusters.len()
+    }
+
+    /// Returns amount of vertices of the abstract graph.
+    pub fn entrance_count(&self) -> usize {
+        self.clusters.iter().map(|c| c.entrances.len()).sum()
+    }
+
+    fn cluster_key(&self, position: Vector3<f32>) -> [i32; 3] {
+        [
+            (position.x / self.cluster_size).floor() as i32,
+            (position.y / self.cluster_size).floor() as i32,
+            (position.z / self.cluster_size).floor() as i32,
+        ]
+    }
+
+    fn cluster_for(&mut self, position: Vector3<f32>) -> u32 {
+        let key = self.cluster_key(position);
+        let next = self.clusters.len() as u32;
+        let cluster = *self.cluster_keys.entry(key).or_insert(next);
+        if cluster == next {
+            self.clusters.push(Default::default());
+        }
+        cluster
+    }
+
+    /// Rebuilds the whole hierarchy. Must be called if vertices were added to or removed from the
+    /// inner graph.
+    pub fn rebuild(&mut self) {
+        self.cluster_keys.clear();
+        self.clusters.clear();
+        self.vertex_cluster.clear();
+        self.local_index.clear();
+        self.transitions.clear();
+
+        for i in 0..self.path_finder.vertices.len() {
+            let cluster = self.cluster_for(self.path_finder.vertices[i].position);
+            let vertices = &mut self.clusters[cluster as usize].vertices;
+            self.local_index.push(vertices.len() as u32);
+            vertices.push(i as u32);
+            self.vertex_cluster.push(cluster);
+        }
+
+        self.rebuild_reverse_neighbours();
+
+        for cluster in 0..self.clusters.len() as u32 {
+            self.clusters[cluster as usize].adjacent = self.adjacent_clusters(cluster);
+        }
+        for cluster in 0..self.clusters.len() as u32 {
+            for other in self.clusters[cluster as usize].adjacent.clone() {
+                if cluster < other {
+                    self.update_transitions(cluster, other);
+                }
+            }
+        }
+        self.rebuild_links();
+        for cluster in 0..self.clusters.len() as u32 {
+            self.update_cluster(cluster);
+        }
+    }
+
+    /// Updates the hierarchy after modification of the given vertices (their positions, links or
+    /// penalties). Only the clusters of the vertices and their neighbours are updated. Falls back to
+    /// [`Self::rebuild`] if the amount of vertices has changed.
+    pub fn invalidate<I>(&mut self, vertices: I)
+    where
+        I: IntoIterator<Item = usize>,
+    {
+        let vertex_count = self.path_finder.vertices.len();
+        if vertex_count != self.vertex_cluster.len() {
+            self.rebuild();
+            return;
+        }
+
+        let mut dirty = FxHashSet::default();
+        for vertex in vertices {
+            if vertex >= vertex_count {
+                continue;
+            }
+
+            let old = self.vertex_cluster[vertex];
+            let new = self.cluster_for(self.path_finder.vertices[vertex].position);
+            if new != old {
+                let old_vertices = &mut self.clusters[old as usize].vertices;
+                old_vertices.retain(|v| *v != vertex as u32);
+                for (i, v) in old_vertices.iter().enumerate() {
+                    self.local_index[*v as usize] = i as u32;
+                }
+                let new_vertices = &mut self.clusters[new as usize].vertices;
+                self.local_index[vertex] = new_vertices.len() as u32;
+                new_vertices.push(vertex as u32);
+                self.vertex_cluster[vertex] = new;
+            }
+            dirty.insert(old);
+            dirty.insert(new);
+        }
+
+        self.rebuild_reverse_neighbours();
+
+        // Neighbour clusters must be updated as well, because their entrances may change.
+        let mut affected = dirty.clone();
+        for &cluster in dirty.iter() {
+            let old_adjacent = std::mem::take(&mut self.clusters[cluster as usize].adjacent);
+            let new_adjacent = self.adjacent_clusters(cluster);
+            for &other in old_adjacent.union(&new_adjacent) {
+                affected.insert(other);
+                let other_adjacent = &mut self.clusters[other as usize].adjacent;
+                if new_adjacent.contains(&other) {
+                    other_adjacent.insert(cluster);
+                } else {
+                    other_adjacent.remove(&cluster);
+                }
+                self.update_transitions(cluster.min(other), cluster.max(other));
+            }
+            self.clusters[cluster as usize].adjacent = new_adjacent;
+        }
+        self.rebuild_links();
+        for cluster in affected {
+            self.update_cluster(cluster);
+        }
+    }
+
+    fn rebuild_reverse_neighbours(&mut self) {
+        let vertices = &self.path_finder.vertices;
+        self.reverse_neighbours.clear();
+        self.reverse_neighbours.resize(vertices.len(), Vec::new());
+        for (i, vertex) in vertices.iter().enumerate() {
+            for &neighbour in vertex.neighbours.iter() {
+                if let Some(reverse) = self.reverse_neighbours.get_mut(neighbour as usize) {
+                    reverse.push(i as u32);
+                }
+            }
+        }
+    }
+
+    fn adjacent_clusters(&self, cluster: u32) -> FxHashSet<u32> {
+        let mut adjacent = FxHashSet::default();
+        for &vertex in self.clusters[cluster as usize].vertices.iter() {
+            let vertex = vertex as usize;
+            for &other in self.path_finder.vertices[vertex]
+                .neighbours
+                .iter()
+                .chain(self.reverse_neighbours[vertex].iter())
+            {
+                if let Some(&other_cluster) = self.vertex_cluster.get(other as usize) {
+                    if other_cluster != cluster {
+                        adjacent.insert(other_cluster);
+                    }
+                }
+            }
+        }
+        adjacent
+    }
+
+    fn is_linked(&self, a: u32, b: u32) -> bool {
+        self.path_finder.vertices[a as usize]
+            .neighbours
+            .contains(&b)
+            || self.path_finder.vertices[b as usize]
+                .neighbours
+                .contains(&a)
+    }
+
+    fn update_transitions(&mut self, a: u32, b: u32) {
+        // Collect all links between the clusters.
+        let mut edges = Vec::new();
+        for &vertex in self.clusters[a as usize].vertices.iter() {
+            for &other in self.path_finder.vertices[vertex as usize]
+                .neighbours
+                .iter()
+                .chain(self.reverse_neighbours[vertex as usize].iter())
+            {
+                if self.vertex_cluster.get(other as usize) == Some(&b) {
+                    edges.push((vertex, other));
+                }
+            }
+        }
+        edges.sort_unstable();
+        edges.dedup();
+
+        if edges.is_empty() {
+            self.transitions.remove(&(a, b));
+            return;
+        }
+
+        // Group the links into entrances - runs of links with linked vertices on each side.
+        fn find(parents: &mut [usize], mut i: usize) -> usize {
+            while parents[i] != i {
+                parents[i] = parents[parents[i]];
+                i = parents[i];
+            }
+            i
+        }
+        let mut parents = (0..edges.len()).collect::<Vec<_>>();
+        for i in 0..edges.len() {
+            for j in (i + 1)..edges.len() {
+                let (ea, eb) = (edges[i], edges[j]);
+                if ea.0 == eb.0
+                    || ea.1 == eb.1
+                    || self.is_linked(ea.0, eb.0)
+                    || self.is_linked(ea.1, eb.1)
+                {
+                    let (ri, rj) = (find(&mut parents, i), find(&mut parents, j));
+                    parents[ri] = rj;
+                }
+            }
+        }
+        let mut entrances = FxHashMap::<usize, Vec<(u32, u32)>>::default();
+        for (i, edge) in edges.iter().enumerate() {
+            entrances
+                .entry(find(&mut parents, i))
+                .or_default()
+                .push(*edge);
+        }
+
+        // Pick the middle link of short entrances and both ends of long ones.
+        let vertices = &self.path_finder.vertices;
+        let midpoint = |(u, v): (u32, u32)| {
+            (vertices[u as usize].position + vertices[v as usize].position).scale(0.5)
+        };
+        let pick_by_distance = |entrance: &[(u32, u32)], point: Vector3<f32>, closest: bool| {
+            entrance
+                .iter()
+                .copied()
+                .min_by(|x, y| {
+                    let dx = (midpoint(*x) - point).norm_squared();
+                    let dy = (midpoint(*y) - point).norm_squared();
+                    let ordering = dx.partial_cmp(&dy).unwrap_or(Ordering::Equal);
+                    if closest {
+                        ordering
+                    } else {
+                        ordering.reverse()
+                    }
+                })
+                .unwrap()
+        };
+        let mut transitions = Vec::new();
+        for entrance in entrances.values() {
+            let center = entrance
+                .iter()
+                .fold(Vector3::default(), |sum, edge| sum + midpoint(*edge))
+                .scale(1.0 / entrance.len() as f32);
+            if entrance.len() > LONG_ENTRANCE_LENGTH {
+                let first = pick_by_distance(entrance, center, false);
+                let second = pick_by_distance(entrance, midpoint(first), false);
+                transitions.push(first);
+                transitions.push(second);
+            } else {
+                transitions.push(pick_by_distance(entrance, center, true));
+            }
+        }
+        transitions.sort_unstable();
+        transitions.dedup();
+        self.transitions.insert((a, b), transitions);
+    }
+
+    fn rebuild_links(&mut self) {
+        self.links.clear();
+        for &(u, v) in self.transitions.values().flatten() {
+            let vertices = &self.path_finder.vertices;
+            if vertices[u as usize].neighbours.contains(&v) {
+                self.links.entry(u).or_default().push(v);
+            }
+            if vertices[v as usize].neighbours.contains(&u) {
+                self.links.entry(v).or_default().push(u);
+            }
+        }
+    }
+
+    fn update_cluster(&mut self, cluster: u32) {
+        let mut entrances = Vec::new();
+        for &other in self.clusters[cluster as usize].adjacent.iter() {
+            let key = (cluster.min(other), cluster.max(other));
+            if let Some(transitions) = self.transitions.get(&key) {
+                for &(u, v) in transitions {
+                    entrances.push(if cluster == key.0 { u } else { v });
+                }
+            }
+        }
+        entrances.sort_unstable();
+        entrances.dedup();
+
+        let mut distances = vec![f32::MAX; entrances.len() * entrances.len()];
+        for (i, &entrance) in entrances.iter().enumerate() {
+            let (costs, _) = self.cluster_search(cluster, entrance, None, false);
+            for (j, &other) in entrances.iter().enumerate() {
+                distances[i * entrances.len() + j] =
+                    costs[self.local_index[other as usize] as usize];
+            }
+        }
+
+        let cluster = &mut self.clusters[cluster as usize];
+        cluster.entrances = entrances;
+        cluster.distances = distances;
+    }
+
+    fn edge_cost(&self, from: u32, to: u32) -> f32 {
+        let from = &self.path_finder.vertices[from as usize];
+        let to = &self.path_finder.vertices[to as usize];
+        (from.position - to.position).norm_squared() * to.g_penalty
+    }
+
+    // Dijkstra search restricted to a single cluster, returns costs and parents of the vertices of
+    // the cluster (in local indices). Reverse search goes against the links, so it calculates costs
+    // of paths to the source vertex.
+    fn cluster_search(
+        &self,
+        cluster: u32,
+        source: u32,
+        target: Option<u32>,
+        reverse: bool,
+    ) -> (Vec<f32>, Vec<u32>) {
+        let vertices = &self.clusters[cluster as usize].vertices;
+        let mut costs = vec![f32::MAX; vertices.len()];
+        let mut parents = vec![u32::MAX; vertices.len()];
+        let mut open_set = BinaryHeap::new();
+
+        let source_local = self.local_index[source as usize] as usize;
+        costs[source_local] = 0.0;
+        open_set.push(OpenSetEntry {
+            f_score: 0.0,
+            index: source_local,
+        });
+
+        while let Some(entry) = open_set.pop() {
+            let local = entry.index;
+            if entry.f_score > costs[local] {
+                continue;
+            }
+
+            let vertex = vertices[local];
+            if Some(vertex) == target {
+                break;
+            }
+
+            let adjacency = if reverse {
+                &self.reverse_neighbours[vertex as usize]
+            } else {
+                &self.path_finder.vertices[vertex as usize].neighbours
+            };
+            for &next in adjacency.iter() {
+                if next == vertex || self.vertex_cluster.get(next as usize) != Some(&cluster) {
+                    continue;
+                }
+                let cost = if reverse {
+                    self.edge_cost(next, vertex)
+                } else {
+                    self.edge_cost(vertex, next)
+                };
+                let next_local = self.local_index[next as usize] as usize;
+                let g_score = costs[local] + cost;
+                if g_score < costs[next_local] {
+                    costs[next_local] = g_score;
+                    parents[next_local] = local as u32;
+                    open_set.push(OpenSetEntry {
+                        f_score: g_score,
+                        index: next_local,
+                    });
+                }
+            }
+        }
+
+        (costs, parents)
+    }
+
+    // Appends path (excluding `from`) inside the cluster to the given list of vertices.
+    fn refine(&self, cluster: u32, from: u32, to: u32, path: &mut Vec<u32>) -> bool {
+        let (costs, parents) = self.cluster_search(cluster, from, Some(to), false);
+        let vertices = &self.clusters[cluster as usize].vertices;
+        let mut current = self.local_index[to as usize];
+        if costs[current as usize] == f32::MAX {
+            return false;
+        }
+        let begin = path.len();
+        while vertices[current as usize] != from {
+            path.push(vertices[current as usize]);
+            current = parents[current as usize];
+        }
+        path[begin..].reverse();
+        true
+    }
+
+    // Builds full path (from `from` to `to`), returns `false` if there's no such path.
+    fn find_path(&self, from: u32, to: u32, path: &mut Vec<u32>) -> bool {
+        path.push(from);
+        if from == to {
+            return true;
+        }
+
+        let start_cluster = self.vertex_cluster[from as usize];
+        let goal_cluster = self.vertex_cluster[to as usize];
+        if start_cluster == goal_cluster && self.refine(start_cluster, from, to, path) {
+            return true;
+        }
+
+        // Start and goal vertices are temporarily connected to the entrances of their clusters.
+        let (start_costs, _) = self.cluster_search(start_cluster, from, None, false);
+        let (goal_costs, _) = self.cluster_search(goal_cluster, to, None, true);
+
+        // Euclidean distance is used as a heuristic, since the costs of long abstract edges are the
+        // sums of costs of short links.
+        let vertices = &self.path_finder.vertices;
+        let goal_position = vertices[to as usize].position;
+        let heuristic =
+            |v: u32| (vertices[v as usize].position - goal_position).norm() * self.heuristic_weight;
+
+        let mut states = FxHashMap::<u32, (f32, u32)>::default();
+        let mut closed = FxHashSet::default();
+        let mut open_set = BinaryHeap::new();
+        states.insert(from, (0.0, u32::MAX));
+        open_set.push(OpenSetEntry {
+            f_score: heuristic(from),
+            index: from as usize,
+        });
+
+        let mut found = false;
+        while let Some(entry) = open_set.pop() {
+            let current = entry.index as u32;
+            if !closed.insert(current) {
+                continue;
+            }
+            if current == to {
+                found = true;
+                break;
+            }
+
+            let current_g_score = states[&current].0;
+            let mut relax = |next: u32, cost: f32| {
+                if cost == f32::MAX {
+                    return;
+                }
+                let g_score = current_g_score + cost;
+                if !matches!(states.get(&next), Some((g, _)) if *g <= g_score) {
+                    states.insert(next, (g_score, current));
+                    open_set.push(OpenSetEntry {
+                        f_score: g_score + heuristic(next),
+                        index: next as usize,
+                    });
+                }
+            };
+
+            let current_cluster = self.vertex_cluster[current as usize];
+            if current == from {
+                for &entrance in self.clusters[start_cluster as usize].entrances.iter() {
+                    relax(
+                        entrance,
+                        start_costs[self.local_index[entrance as usize] as usize],
+                    );
+                }
+            }
+            let cluster = &self.clusters[current_cluster as usize];
+            if let Ok(i) = cluster.entrances.binary_search(&current) {
+                let count = cluster.entrances.len();
+                for (j, &entrance) in cluster.entrances.iter().enumerate() {
+                    if i != j {
+                        relax(entrance, cluster.distances[i * count + j]);
+                    }
+                }
+            }
+            if let Some(links) = self.links.get(&current) {
+                for &next in links {
+                    relax(next, self.edge_cost(current, next));
+                }
+            }
+            if current_cluster == goal_cluster {
+                relax(to, goal_costs[self.local_index[current as usize] as usize]);
+            }
+        }
+
+        if !found {
+            return false;
+        }
+
+        let mut abstract_path = Vec::new();
+        let mut current = to;
+        while current != u32::MAX {
+            abstract_path.push(current);
+            current = states[&current].1;
+        }
+        abstract_path.reverse();
+
+        // Refine only the clusters along the abstract path.
+        for pair in abstract_path.windows(2) {
+            let (a, b) = (pair[0], pair[1]);
+            let cluster = self.vertex_cluster[a as usize];
+            if cluster == self.vertex_cluster[b as usize] {
+                self.refine(cluster, a, b, path);
+            } else {
+                path.push(b);
+            }
+        }
+
+        true
+    }
+
+    /// Tries to build path from begin point to end point. Returns path kind, see
+    /// [`PathFinder::build`] for more info. The path is stored in the same order as in
+    /// [`PathFinder::build`] - from the end point to the begin point.
+    pub fn build<P>(&self, from: usize, to: usize, path: &mut P) -> Result<PathKind, PathError>
+    where
+        P: ResultBuffer<Vector3<f32>>,
+    {
+        self.build_and_convert(from, to, path, |_, v| v.position)
+    }
+
+    /// Same as [`Self::build`], but converts every vertex of the path using the given function.
+    ///
+    /// # Notes
+    ///
+    /// If there's no full path, the method falls back to the flat search to build partial path.
+    /// It could be slow on large graphs, since the flat search will explore every reachable vertex.
+    pub fn build_and_convert<F, T, P>(
+        &self,
+        from: usize,
+        to: usize,
+        path: &mut P,
+        mut func: F,
+    ) -> Result<PathKind, PathError>
+    where
+        F: FnMut(usize, &PathVertex) -> T,
+        P: ResultBuffer<T>,
+    {
+        let vertices = &self.path_finder.vertices;
+        if vertices.is_empty() {
+            return Ok(PathKind::Empty);
+        }
+
+        path.clear();
+
+        if to >= vertices.len() {
+            return Err(PathError::InvalidIndex(to));
+        }
+        if from >= vertices.len() {
+            return Err(PathError::InvalidIndex(from));
+        }
+
+        let mut vertices_path = Vec::new();
+        if self.find_path(from as u32, to as u32, &mut vertices_path) {
+            for &vertex in vertices_path.iter().rev() {
+                path.push(func(vertex as usize, &vertices[vertex as usize]));
+            }
+            Ok(PathKind::Full)
+        } else {
+            self.path_finder.build_and_convert_in(
+                &mut PathfindingContext::new(),
+                from,
+                to,
+                path,
+                func,
+            )
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
     use crate::rand::Rng;
     use crate::{
-        core::{algebra::Vector3, rand},
-        utils::astar::{PathFinder, PathVertex},
+        core::{algebra::Vector3, rand, visitor::VisitorFormat},
+        utils::{
+            astar::{HierarchicalPathFinder, PathFinder, PathKind, PathVertex, PathfindingContext},
+            small_vec::SmallVec,
+        },
     };
+    use std::ops::ControlFlow;
 
     #[test]
     fn astar_random_points() {
@@ -516,6 +1698,68 @@ mod test {
         assert!(paths_count > 0);
     }
 
+    fn make_grid(size: usize) -> PathFinder {
+        let mut pathfinder = PathFinder::new();
+        let mut vertices = Vec::new();
+        for y in 0..size {
+            for x in 0..size {
+                vertices.push(PathVertex::new(Vector3::new(x as f32, y as f32, 0.0)));
+            }
+        }
+        pathfinder.set_vertices(vertices);
+        for y in 0..(size - 1) {
+            for x in 0..(size - 1) {
+                pathfinder.link_bidirect(y * size + x, y * size + x + 1);
+                pathfinder.link_bidirect(y * size + x, (y + 1) * size + x);
+            }
+        }
+        pathfinder
+    }
+
+    #[test]
+    fn test_build_in_context() {
+        let size = 20;
+        let mut pathfinder = make_grid(size);
+        let mut ctx = PathfindingContext::new();
+
+        let mut expected = Vec::new();
+        let mut path = Vec::new();
+        for _ in 0..100 {
+            let from = rand::thread_rng().gen_range(0..(size * size));
+            let to = rand::thread_rng().gen_range(0..(size * size));
+
+            let kind = pathfinder.build(from, to, &mut expected).unwrap();
+            assert_eq!(
+                pathfinder.build_in(&mut ctx, from, to, &mut path).unwrap(),
+                kind
+            );
+            assert_eq!(path, expected);
+        }
+    }
+
+    #[test]
+    fn test_build_in_small_vec() {
+        let size = 20;
+        let pathfinder = make_grid(size);
+        let mut ctx = PathfindingContext::new();
+
+        let mut expected = Vec::new();
+        let mut path = SmallVec::<_, 8>::new();
+
+        // Short path fits inline.
+        pathfinder.build_in(&mut ctx, 0, 3, &mut expected).unwrap();
+        pathfinder.build_in(&mut ctx, 0, 3, &mut path).unwrap();
+        assert!(!path.is_spilled());
+        assert_eq!(path.as_slice(), expected.as_slice());
+
+        // Long path spills to the heap.
+        let to = size * size - 1;
+        pathfinder.build_in(&mut ctx, 0, to, &mut expected).unwrap();
+        pathfinder.build_in(&mut ctx, 0, to, &mut path).unwrap();
+        assert!(path.is_spilled());
+        assert_eq!(path.into_vec(), expected);
+    }
+
     #[test]
     fn test_remove_vertex() {
         let mut pathfinder = PathFinder::new();
@@ -564,4 +1808,423 @@ mod test {
         assert_eq!(pathfinder.vertex(2).unwrap().neighbours, vec![1, 3]);
         assert_eq!(pathfinder.vertex(3).unwrap().neighbours, vec![2, 1]);
     }
+
+    #[test]
+    fn test_observed_search() {
+        let size = 20;
+        let pathfinder = make_grid(size);
+        let mut ctx = PathfindingContext::new();
+        let mut path = Vec::new();
+
+        // Collect explored vertices without affecting the search.
+        let mut explored = Vec::new();
+        let kind = pathfinder
+            .build_and_convert_observed_in(
+                &mut ctx,
+                0,
+                size * 10 + 10,
+                &mut path,
+                |_, v| v.position,
+                |_, _| Some(1.0),
+                |index, _| {
+                    explored.push(index);
+                    ControlFlow::Continue(())
+                },
+            )
+            .unwrap();
+        assert_eq!(kind, PathKind::Full);
+        assert_eq!(explored[0], 0);
+        assert!(!explored.contains(&(size * 10 + 10)));
+
+        let mut expected = Vec::new();
+        pathfinder
+            .build_in(&mut ctx, 0, size * 10 + 10, &mut expected)
+            .unwrap();
+        assert_eq!(path, expected);
+
+        // Stop after a few expansions.
+        let mut expanded = 0;
+        let kind = pathfinder
+            .build_and_convert_observed_in(
+                &mut ctx,
+                0,
+                size * size - 2,
+                &mut path,
+                |_, v| v.position,
+                |_, _| Some(1.0),
+                |_, _| {
+                    expanded += 1;
+                    if expanded == 5 {
+                        ControlFlow::Break(())
+                    } else {
+                        ControlFlow::Continue(())
+                    }
+                },
+            )
+            .unwrap();
+        assert_eq!(kind, PathKind::Partial);
+        assert_eq!(expanded, 5);
+        assert_eq!(path.last(), Some(&Vector3::new(0.0, 0.0, 0.0)));
+    }
+
+    fn path_length(path: &[Vector3<f32>]) -> f32 {
+        path.windows(2).map(|p| p[0].metric_distance(&p[1])).sum()
+    }
+
+    // Grid with walls along every 10th row and column, every wall has a few gaps.
+    fn make_maze(size: usize, gaps: usize) -> PathFinder {
+        let mut pathfinder = PathFinder::new();
+        let mut vertices = Vec::new();
+        for y in 0..size {
+            for x in 0..size {
+                vertices.push(PathVertex::new(Vector3::new(x as f32, y as f32, 0.0)));
+            }
+        }
+        pathfinder.set_vertices(vertices);
+        let is_wall = |x: usize, y: usize| {
+            (x % 10 == 5 && (y * 7 + x) % 10 >= gaps) || (y % 10 == 5 && (x * 3 + y) % 10 >= gaps)
+        };
+        for y in 0..size {
+            for x in 0..size {
+                if is_wall(x, y) {
+                    continue;
+                }
+                if x + 1 < size && !is_wall(x + 1, y) {
+                    pathfinder.link_bidirect(y * size + x, y * size + x + 1);
+                }
+                if y + 1 < size && !is_wall(x, y + 1) {
+                    pathfinder.link_bidirect(y * size + x, (y + 1) * size + x);
+                }
+            }
+        }
+        pathfinder
+    }
+
+    #[test]
+    fn test_hierarchical_path_cost() {
+        let size = 60;
+        let hierarchical = HierarchicalPathFinder::new(make_maze(size, 3), 8.0);
+        assert!(hierarchical.cluster_count() > 1);
+        assert!(hierarchical.entrance_count() > 0);
+
+        let flat = hierarchical.path_finder();
+        let mut flat_path = Vec::new();
+        let mut path = Vec::new();
+        let mut full_paths = 0;
+        for _ in 0..200 {
+            let from = rand::thread_rng().gen_range(0..(size * size));
+            let to = rand::thread_rng().gen_range(0..(size * size));
+
+            let flat_kind = flat
+                .build_in(&mut PathfindingContext::new(), from, to, &mut flat_path)
+                .unwrap();
+            let kind = hierarchical.build(from, to, &mut path).unwrap();
+            assert_eq!(kind, flat_kind);
+            if kind != PathKind::Full {
+                continue;
+            }
+            full_paths += 1;
+
+            assert_eq!(path.first(), Some(&flat.vertex(to).unwrap().position));
+            assert_eq!(path.last(), Some(&flat.vertex(from).unwrap().position));
+            for pair in path.windows(2) {
+                assert_eq!(pair[0].metric_distance(&pair[1]), 1.0);
+            }
+            assert!(path_length(&path) <= path_length(&flat_path) * 1.5 + 4.0);
+        }
+        assert!(full_paths > 0);
+    }
+
+    #[test]
+    fn test_hierarchical_invalidate() {
+        let size = 30;
+        let mut hierarchical = HierarchicalPathFinder::new(make_grid(size), 8.0);
+        let mut path = Vec::new();
+        let from = 15 * size;
+        let to = 15 * size + size - 2;
+        assert_eq!(
+            hierarchical.build(from, to, &mut path).unwrap(),
+            PathKind::Full
+        );
+
+        // Block the column in the middle of the grid, except the vertex at the bottom.
+        let blocked = (1..size).map(|y| y * size + 12).collect::<Vec<_>>();
+        for &vertex in blocked.iter() {
+            for other in hierarchical.path_finder.vertices.iter_mut() {
+                other.neighbours.retain(|n| *n != vertex as u32);
+            }
+            hierarchical.path_finder_mut().vertices_mut()[vertex]
+                .neighbours
+                .clear();
+        }
+        hierarchical.invalidate(blocked.iter().copied());
+
+        assert_eq!(
+            hierarchical.build(from, to, &mut path).unwrap(),
+            PathKind::Full
+        );
+        assert!(path.iter().all(|p| p.x != 12.0 || p.y == 0.0));
+        assert!(path.iter().any(|p| p.x == 12.0 && p.y == 0.0));
+
+        // Fully blocked - partial path.
+        let vertex = 12;
+        for other in hierarchical.path_finder.vertices.iter_mut() {
+            other.neighbours.retain(|n| *n != vertex as u32);
+        }
+        hierarchical.invalidate([vertex]);
+        assert_eq!(
+            hierarchical.build(from, to, &mut path).unwrap(),
+            PathKind::Partial
+        );
+    }
+
+    // Randomized depth-first maze with some extra passages, every vertex is reachable.
+    fn make_random_maze(size: usize) -> PathFinder {
+        let mut pathfinder = PathFinder::new();
+        let mut vertices = Vec::new();
+        for y in 0..size {
+            for x in 0..size {
+                vertices.push(PathVertex::new(Vector3::new(x as f32, y as f32, 0.0)));
+            }
+        }
+        pathfinder.set_vertices(vertices);
+
+        let mut rng = rand::thread_rng();
+        let mut visited = vec![false; size * size];
+        let mut stack = vec![0];
+        visited[0] = true;
+        while let Some(&current) = stack.last() {
+            let (x, y) = (current % size, current / size);
+            let mut candidates = Vec::new();
+            if x > 0 {
+                candidates.push(current - 1);
+            }
+            if x + 1 < size {
+                candidates.push(current + 1);
+            }
+            if y > 0 {
+                candidates.push(current - size);
+            }
+            if y + 1 < size {
+                candidates.push(current + size);
+            }
+            candidates.retain(|c| !visited[*c]);
+            if candidates.is_empty() {
+                stack.pop();
+            } else {
+                let next = candidates[rng.gen_range(0..candidates.len())];
+                visited[next] = true;
+                pathfinder.link_bidirect(current, next);
+                stack.push(next);
+            }
+        }
+
+        for _ in 0..(size * size / 10) {
+            let x = rng.gen_range(0..(size - 1));
+            let y = rng.gen_range(0..(size - 1));
+            pathfinder.link_bidirect(y * size + x, y * size + x + 1);
+        }
+
+        pathfinder
+    }
+
+    #[test]
+    fn test_set_vertex_position() {
+        let mut pathfinder = PathFinder::new();
+        pathfinder.add_vertex(PathVertex::new(Vector3::new(0.0, 0.0, 0.0)));
+        pathfinder.add_vertex(PathVertex::new(Vector3::new(1.0, 0.0, 0.0)));
+        pathfinder.add_vertex(PathVertex::new(Vector3::new(5.0, 0.0, 0.0)));
+        pathfinder.add_vertex(PathVertex::new(Vector3::new(2.0, 0.0, 0.0)));
+        pathfinder.link_bidirect(0, 1);
+        pathfinder.link_bidirect(1, 3);
+        pathfinder.link_bidirect(0, 2);
+        pathfinder.link_bidirect(2, 3);
+
+        let mut path = Vec::new();
+        pathfinder
+            .build_and_convert(0, 3, &mut path, |i, _| i)
+            .unwrap();
+        assert_eq!(path, vec![3, 1, 0]);
+
+        // Move the vertex of the short route far away, links must be preserved.
+        pathfinder.set_vertex_position(1, Vector3::new(1.0, 10.0, 0.0));
+        pathfinder.set_vertex_position(2, Vector3::new(1.0, 0.0, 0.0));
+        assert_eq!(pathfinder.vertex(1).unwrap().neighbours(), &[0, 3]);
+        pathfinder
+            .build_and_convert(0, 3, &mut path, |i, _| i)
+            .unwrap();
+        assert_eq!(path, vec![3, 2, 0]);
+
+        // Out-of-bounds index is ignored.
+        pathfinder.set_vertex_position(10, Vector3::default());
+    }
+
+    // Cost of a path on a grid built by `make_grid` or `make_random_maze`, the path goes from the end to
+    // the beginning.
+    fn path_cost(pathfinder: &PathFinder, size: usize, path: &[Vector3<f32>]) -> f32 {
+        path.windows(2)
+            .map(|pair| {
+                let to = &pathfinder.vertices[pair[0].y as usize * size + pair[0].x as usize];
+                (pair[0] - pair[1]).norm_squared() * to.g_penalty
+            })
+            .sum()
+    }
+
+    // Reference search without heuristic.
+    fn dijkstra_cost(pathfinder: &PathFinder, from: usize, to: usize) -> Option<f32> {
+        let mut costs = vec![f32::MAX; pathfinder.vertices.len()];
+        let mut closed = vec![false; pathfinder.vertices.len()];
+        costs[from] = 0.0;
+        while let Some(current) = (0..costs.len())
+            .filter(|&i| !closed[i] && costs[i] != f32::MAX)
+            .min_by(|&a, &b| costs[a].partial_cmp(&costs[b]).unwrap())
+        {
+            closed[current] = true;
+            let vertex = &pathfinder.vertices[current];
+            for &neighbour_index in vertex.neighbours.iter() {
+                let neighbour = &pathfinder.vertices[neighbour_index as usize];
+                let cost = costs[current]
+                    + (vertex.position - neighbour.position).norm_squared() * neighbour.g_penalty;
+                if cost < costs[neighbour_index as usize] {
+                    costs[neighbour_index as usize] = cost;
+                }
+            }
+        }
+        Some(costs[to]).filter(|cost| *cost != f32::MAX)
+    }
+
+    #[test]
+    fn test_bidirectional_path_cost() {
+        let size = 30;
+        let mut ctx = PathfindingContext::new();
+        let mut path = Vec::new();
+        let mut expected = Vec::new();
+
+        // Unidirectional search builds the cheapest paths on open grids.
+        let mut grid = make_grid(size);
+        for _ in 0..100 {
+            // The last vertex of the grid is not linked.
+            let from = rand::thread_rng().gen_range(0..(size * size - 1));
+            let to = rand::thread_rng().gen_range(0..(size * size - 1));
+
+            assert_eq!(grid.build(from, to, &mut expected).unwrap(), PathKind::Full);
+            assert_eq!(
+                grid.build_path_bidirectional_in(&mut ctx, from, to, &mut path)
+                    .unwrap(),
+                PathKind::Full
+            );
+            assert_eq!(path.first(), Some(&grid.vertices[to].position));
+            assert_eq!(path.last(), Some(&grid.vertices[from].position));
+            assert_eq!(
+                path_cost(&grid, size, &path),
+                path_cost(&grid, size, &expected)
+            );
+        }
+
+        // Mazes with random penalties and one-way links, the heuristic of unidirectional search
+        // overestimates there, so compare with the search without heuristic.
+        for _ in 0..5 {
+            let mut maze = make_random_maze(size);
+            let mut rng = rand::thread_rng();
+            for vertex in maze.vertices.iter_mut() {
+                vertex.set_penalty(rng.gen_range(0.5..3.0));
+            }
+            for _ in 0..(size * size / 20) {
+                let x = rng.gen_range(0..size);
+                let y = rng.gen_range(0..(size - 1));
+                maze.link_unidirect(y * size + x, (y + 1) * size + x);
+            }
+
+            for _ in 0..20 {
+                let from = rng.gen_range(0..(size * size));
+                let to = rng.gen_range(0..(size * size));
+
+                let kind = maze
+                    .build_path_bidirectional_in(&mut ctx, from, to, &mut path)
+                    .unwrap();
+                let cost = path_cost(&maze, size, &path);
+                assert_eq!(kind, PathKind::Full);
+                assert_eq!(path.first(), Some(&maze.vertices[to].position));
+                assert_eq!(path.last(), Some(&maze.vertices[from].position));
+                let reference = dijkstra_cost(&maze, from, to).unwrap();
+                assert!((cost - reference).abs() <= reference * 1.0e-4);
+
+                maze.build(from, to, &mut expected).unwrap();
+                assert!(cost <= path_cost(&maze, size, &expected) + 1.0e-3);
+            }
+        }
+    }
+
+    #[test]
+    fn test_bidirectional_partial_path() {
+        let mut pathfinder = PathFinder::new();
+        for x in 0..4 {
+            pathfinder.add_vertex(PathVertex::new(Vector3::new(x as f32, 0.0, 0.0)));
+        }
+        pathfinder.add_vertex(PathVertex::new(Vector3::new(10.0, 0.0, 0.0)));
+        pathfinder.link_bidirect(0, 1);
+        pathfinder.link_bidirect(1, 2);
+        pathfinder.link_unidirect(3, 2);
+
+        let mut path = Vec::new();
+        assert_eq!(
+            pathfinder
+                .build_path_bidirectional(0, 4, &mut path)
+                .unwrap(),
+            PathKind::Partial
+        );
+        assert_eq!(
+            path,
+            vec![
+                Vector3::new(2.0, 0.0, 0.0),
+                Vector3::new(1.0, 0.0, 0.0),
+                Vector3::new(0.0, 0.0, 0.0)
+            ]
+        );
+
+        assert_eq!(
+            pathfinder
+                .build_path_bidirectional(3, 0, &mut path)
+                .unwrap(),
+            PathKind::Full
+        );
+        assert_eq!(path.len(), 4);
+
+        assert_eq!(
+            pathfinder
+                .build_path_bidirectional(2, 2, &mut path)
+                .unwrap(),
+            PathKind::Full
+        );
+        assert_eq!(path, vec![Vector3::new(2.0, 0.0, 0.0)]);
+
+        assert!(pathfinder
+            .build_path_bidirectional(0, 10, &mut path)
+            .is_err());
+    }
+
+    #[test]
+    fn test_save_load_round_trip() {
+        let mut pathfinder = PathFinder::new();
+        for i in 0..4 {
+            pathfinder.add_vertex(PathVertex::new(Vector3::new(i as f32, 0.0, 0.0)));
+        }
+        pathfinder.link_bidirect(0, 1);
+        pathfinder.link_bidirect(1, 2);
+        pathfinder.link_unidirect(2, 3);
+        pathfinder.vertex_mut(1).unwrap().set_penalty(5.0);
+
+        for format in [VisitorFormat::Binary, VisitorFormat::Text] {
+            let mut data = Vec::new();
+            pathfinder.save(&mut data, format).unwrap();
+            let loaded = PathFinder::load(data.as_slice()).unwrap();
+            assert_eq!(loaded, pathfinder);
+        }
+
+        // Links to non-existent vertices must be rejected.
+        pathfinder.vertices_mut()[3].neighbours.push(10);
+        let mut data = Vec::new();
+        pathfinder.save(&mut data, VisitorFormat::Binary).unwrap();
+        assert!(PathFinder::load(data.as_slice()).is_err());
+    }
 }
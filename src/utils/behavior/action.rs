@@ -0,0 +1,162 @@
+//! Action is a leaf node of a behavior tree, that contains logic defined by a closure. It is a quick
+//! way to add simple actions and conditions without defining a new type and implementing
+//! [`Behavior`] trait for it. Closures can't be saved, so use [`super::leaf::LeafNode`] for
+//! stateful leaves that need serialization.
+
+use crate::{
+    core::{pool::Handle, visitor::prelude::*},
+    utils::behavior::{Behavior, BehaviorNode, BehaviorTree, Status},
+};
+use std::{
+    cell::RefCell,
+    fmt::{Debug, Formatter},
+    rc::Rc,
+};
+
+/// A closure, that could be called with a context of a behavior.
+pub trait ActionFn<'a, B>
+where
+    B: Behavior<'a>,
+{
+    /// Calls the closure with the given context.
+    fn call(&mut self, context: &mut B::Context) -> Status;
+}
+
+struct ClosureAction<F>(F);
+
+impl<'a, B, F> ActionFn<'a, B> for ClosureAction<F>
+where
+    B: Behavior<'a>,
+    F: FnMut(&mut B::Context) -> Status,
+{
+    fn call(&mut self, context: &mut B::Context) -> Status {
+        (self.0)(context)
+    }
+}
+
+struct ClosureCondition<F>(F);
+
+impl<'a, B, F> ActionFn<'a, B> for ClosureCondition<F>
+where
+    B: Behavior<'a>,
+    F: FnMut(&B::Context) -> bool,
+{
+    fn call(&mut self, context: &mut B::Context) -> Status {
+        if (self.0)(context) {
+            Status::Success
+        } else {
+            Status::Failure
+        }
+    }
+}
+
+type SharedActionFn<B> = Rc<RefCell<dyn for<'a> ActionFn<'a, B>>>;
+
+/// See module docs. Clones of the node share the same closure. The closure is not saved, a loaded
+/// node is empty and always returns [`Status::Failure`] until a new closure is set using
+/// [`ActionNode::set`].
+pub struct ActionNode<B> {
+    func: Option<SharedActionFn<B>>,
+    pub(super) status: Option<Status>,
+}
+
+impl<B> ActionNode<B>
+where
+    B: for<'a> Behavior<'a> + 'static,
+{
+    /// Creates new action node, that calls the given closure on every tick.
+    pub fn new<F>(func: F) -> Self
+    where
+        F: for<'a> FnMut(&mut <B as Behavior<'a>>::Context) -> Status + 'static,
+    {
+        Self {
+            func: Some(Rc::new(RefCell::new(ClosureAction(func)))),
+            status: None,
+        }
+    }
+
+    /// Creates new condition node, that returns [`Status::Success`] if the given closure returns
+    /// `true` and [`Status::Failure`] otherwise.
+    pub fn new_condition<F>(func: F) -> Self
+    where
+        F: for<'a> FnMut(&<B as Behavior<'a>>::Context) -> bool + 'static,
+    {
+        Self {
+            func: Some(Rc::new(RefCell::new(ClosureCondition(func)))),
+            status: None,
+        }
+    }
+
+    /// Replaces the closure of the node.
+    pub fn set<F>(&mut self, func: F)
+    where
+        F: for<'a> FnMut(&mut <B as Behavior<'a>>::Context) -> Status + 'static,
+    {
+        self.func = Some(Rc::new(RefCell::new(ClosureAction(func))));
+    }
+}
+
+impl<B> ActionNode<B>
+where
+    B: Clone + 'static,
+{
+    /// Adds self to given behavior tree and returns handle to self.
+    pub fn add_to(self, tree: &mut BehaviorTree<B>) -> Handle<BehaviorNode<B>> {
+        tree.add_node(BehaviorNode::Action(self))
+    }
+
+    pub(super) fn tick<'a>(&self, context: &mut <B as Behavior<'a>>::Context) -> Status
+    where
+        B: Behavior<'a>,
+    {
+        match self.func {
+            Some(ref func) => func.borrow_mut().call(context),
+            None => Status::Failure,
+        }
+    }
+}
+
+impl<B> Default for ActionNode<B> {
+    fn default() -> Self {
+        Self {
+            func: None,
+            status: None,
+        }
+    }
+}
+
+impl<B> Clone for ActionNode<B> {
+    fn clone(&self) -> Self {
+        Self {
+            func: self.func.clone(),
+            status: self.status,
+        }
+    }
+}
+
+impl<B> Debug for ActionNode<B> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "ActionNode")
+    }
+}
+
+impl<B> PartialEq for ActionNode<B> {
+    fn eq(&self, other: &Self) -> bool {
+        let same_func = match (self.func.as_ref(), other.func.as_ref()) {
+            (Some(a), Some(b)) => Rc::ptr_eq(a, b),
+            (None, None) => true,
+            _ => false,
+        };
+        same_func && self.status == other.status
+    }
+}
+
+impl<B> Eq for ActionNode<B> {}
+
+impl<B> Visit for ActionNode<B> {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        // Closures can't be saved, keep an empty region to preserve the structure of the tree.
+        let _region = visitor.enter_region(name)?;
+        Ok(())
+    }
+}
@@ -7,7 +7,7 @@
 
 use crate::{
     core::{pool::Handle, visitor::prelude::*},
-    utils::behavior::{BehaviorNode, BehaviorTree},
+    utils::behavior::{BehaviorNode, BehaviorTree, Status},
 };
 
 /// Defines exact behavior of the composite node.
@@ -39,6 +39,8 @@ where
     pub children: Vec<Handle<BehaviorNode<B>>>,
     /// Current kind of the node.
     pub kind: CompositeNodeKind,
+    #[visit(skip)]
+    pub(super) status: Option<Status>,
 }
 
 impl<B> Default for CompositeNode<B>
@@ -49,6 +51,7 @@ where
         Self {
             children: Default::default(),
             kind: Default::default(),
+            status: None,
         }
     }
 }
@@ -59,7 +62,11 @@ where
 {
     /// Creates new composite node of given kind and set of children nodes.
     pub fn new(kind: CompositeNodeKind, children: Vec<Handle<BehaviorNode<B>>>) -> Self {
-        Self { children, kind }
+        Self {
+            children,
+            kind,
+            status: None,
+        }
     }
 
     /// Creates new sequence composite node with a set of children nodes.
@@ -67,6 +74,7 @@ where
         Self {
             children,
             kind: CompositeNodeKind::Sequence,
+            status: None,
         }
     }
 
@@ -75,6 +83,7 @@ where
         Self {
             children,
             kind: CompositeNodeKind::Selector,
+            status: None,
         }
     }
 
@@ -3,7 +3,7 @@
 
 use crate::{
     core::{pool::Handle, visitor::prelude::*},
-    utils::behavior::{BehaviorNode, BehaviorTree},
+    utils::behavior::{BehaviorNode, BehaviorTree, Status},
 };
 
 /// See module docs.
@@ -14,6 +14,8 @@ where
 {
     /// A handle of child node, the state of which will be inverted.
     pub child: Handle<BehaviorNode<B>>,
+    #[visit(skip)]
+    pub(super) status: Option<Status>,
 }
 
 impl<B> Default for Inverter<B>
@@ -23,6 +25,7 @@ where
     fn default() -> Self {
         Self {
             child: Default::default(),
+            status: None,
         }
     }
 }
@@ -33,7 +36,10 @@ where
 {
     /// Creates new inverter node with given action.
     pub fn new(child: Handle<BehaviorNode<B>>) -> Self {
-        Self { child }
+        Self {
+            child,
+            status: None,
+        }
     }
 
     /// Adds self to given behavior tree and returns handle to self.
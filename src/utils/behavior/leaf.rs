@@ -3,7 +3,7 @@
 
 use crate::{
     core::{pool::Handle, visitor::prelude::*},
-    utils::behavior::{BehaviorNode, BehaviorTree},
+    utils::behavior::{BehaviorNode, BehaviorTree, Status},
 };
 use std::cell::RefCell;
 
@@ -15,6 +15,8 @@ where
 {
     /// User-defined behavior.
     pub behavior: Option<RefCell<B>>,
+    #[visit(skip)]
+    pub(super) status: Option<Status>,
 }
 
 impl<B> Default for LeafNode<B>
@@ -22,7 +24,10 @@ where
     B: Clone,
 {
     fn default() -> Self {
-        Self { behavior: None }
+        Self {
+            behavior: None,
+            status: None,
+        }
     }
 }
 
@@ -34,6 +39,7 @@ where
     pub fn new(behavior: B) -> Self {
         Self {
             behavior: Some(RefCell::new(behavior)),
+            status: None,
         }
     }
 
@@ -9,6 +9,50 @@
 //! user-defined logic. Hard coded nodes are: Sequence, Selector, Leaf. Leaf is special - it has
 //! custom method `tick` that can contain any logic you want.
 //!
+//! Simple leaves could be defined by closures, without a new type that implements [`Behavior`]
+//! trait. See [`BehaviorNode::action`] and [`BehaviorNode::condition`]:
+//!
+//! ```rust
+//! use fyrox::{
+//!     core::visitor::prelude::*,
+//!     utils::behavior::{sequence, Behavior, BehaviorNode, BehaviorTree, Status},
+//! };
+//!
+//! #[derive(Default)]
+//! struct Bot {
+//!     health: f32,
+//!     fleeing: bool,
+//! }
+//!
+//! // Behavior of stateful leaves, closure-based leaves only use its context type.
+//! #[derive(Debug, PartialEq, Default, Visit, Clone)]
+//! struct BotBehavior;
+//!
+//! impl<'a> Behavior<'a> for BotBehavior {
+//!     type Context = Bot;
+//!
+//!     fn tick(&mut self, _context: &mut Self::Context) -> Status {
+//!         Status::Success
+//!     }
+//! }
+//!
+//! let mut tree = BehaviorTree::<BotBehavior>::new();
+//! let is_hurt = tree.add_node(BehaviorNode::condition(|bot: &Bot| bot.health < 10.0));
+//! let flee = tree.add_node(BehaviorNode::action(|bot: &mut Bot| {
+//!     bot.fleeing = true;
+//!     Status::Success
+//! }));
+//! let entry = sequence([is_hurt, flee], &mut tree);
+//! tree.set_entry_node(entry);
+//!
+//! let mut bot = Bot {
+//!     health: 5.0,
+//!     fleeing: false,
+//! };
+//! assert_eq!(tree.tick(&mut bot), Status::Success);
+//! assert!(bot.fleeing);
+//! ```
+//!
 //! For more info see:
 //! - [Wikipedia article](https://en.wikipedia.org/wiki/Behavior_tree_(artificial_intelligence,_robotics_and_control))
 //! - [Gamasutra](https://www.gamasutra.com/blogs/ChrisSimpson/20140717/221339/Behavior_trees_for_AI_How_they_work.php)
@@ -19,21 +63,26 @@ use crate::{
         visitor::prelude::*,
     },
     utils::behavior::{
+        action::ActionNode,
         composite::{CompositeNode, CompositeNodeKind},
         inverter::Inverter,
         leaf::LeafNode,
+        subtree::{SubtreeError, SubtreeLibrary, SubtreeRef},
     },
 };
 use std::{
-    fmt::Debug,
+    fmt::{Debug, Write},
     ops::{Index, IndexMut},
 };
 
+pub mod action;
 pub mod composite;
 pub mod inverter;
 pub mod leaf;
+pub mod subtree;
 
 /// Status of execution of behavior tree node.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum Status {
     /// Action was successful.
     Success,
@@ -61,6 +110,8 @@ where
     B: Clone,
 {
     child: Handle<BehaviorNode<B>>,
+    #[visit(skip)]
+    status: Option<Status>,
 }
 
 impl<B> Default for RootNode<B>
@@ -70,6 +121,7 @@ where
     fn default() -> Self {
         Self {
             child: Default::default(),
+            status: None,
         }
     }
 }
@@ -88,9 +140,13 @@ where
     Composite(CompositeNode<B>),
     /// A node with custom logic.
     Leaf(LeafNode<B>),
+    /// A node with custom logic defined by a closure.
+    Action(ActionNode<B>),
     /// A node, that inverts its child state ([`Status::Failure`] becomes [`Status::Success`] and vice versa, [`Status::Running`] remains
     /// unchanged)
     Inverter(Inverter<B>),
+    /// A reference to a named fragment, see [`subtree`] module docs for more info.
+    Subtree(SubtreeRef),
 }
 
 impl<B> Default for BehaviorNode<B>
@@ -102,6 +158,101 @@ where
     }
 }
 
+impl<B> BehaviorNode<B>
+where
+    B: for<'a> Behavior<'a> + 'static,
+{
+    /// Creates a leaf node, that calls the given closure on every tick. It is a shortcut for simple
+    /// actions, that do not need their own type. See [`ActionNode`] docs for more info.
+    pub fn action<F>(func: F) -> Self
+    where
+        F: for<'a> FnMut(&mut <B as Behavior<'a>>::Context) -> Status + 'static,
+    {
+        Self::Action(ActionNode::new(func))
+    }
+
+    /// Creates a leaf node, that returns [`Status::Success`] if the given closure returns `true`
+    /// and [`Status::Failure`] otherwise.
+    pub fn condition<F>(func: F) -> Self
+    where
+        F: for<'a> FnMut(&<B as Behavior<'a>>::Context) -> bool + 'static,
+    {
+        Self::Action(ActionNode::new_condition(func))
+    }
+}
+
+impl<B> BehaviorNode<B>
+where
+    B: Clone,
+{
+    /// Returns a human-readable name of the node kind.
+    pub fn kind_name(&self) -> &'static str {
+        match self {
+            BehaviorNode::Unknown => "Unknown",
+            BehaviorNode::Root(_) => "Root",
+            BehaviorNode::Composite(composite) => match composite.kind {
+                CompositeNodeKind::Sequence => "Sequence",
+                CompositeNodeKind::Selector => "Selector",
+            },
+            BehaviorNode::Leaf(_) => "Leaf",
+            BehaviorNode::Action(_) => "Action",
+            BehaviorNode::Inverter(_) => "Inverter",
+            BehaviorNode::Subtree(_) => "Subtree",
+        }
+    }
+
+    /// Returns a set of handles to children nodes of the node.
+    pub fn children(&self) -> &[Handle<BehaviorNode<B>>] {
+        match self {
+            BehaviorNode::Unknown
+            | BehaviorNode::Leaf(_)
+            | BehaviorNode::Action(_)
+            | BehaviorNode::Subtree(_) => &[],
+            BehaviorNode::Root(root) => std::slice::from_ref(&root.child),
+            BehaviorNode::Composite(composite) => &composite.children,
+            BehaviorNode::Inverter(inverter) => std::slice::from_ref(&inverter.child),
+        }
+    }
+
+    /// Returns a status that the node returned at the last tick of its tree, or `None` if the node
+    /// wasn't ticked (it wasn't on the execution path).
+    pub fn status(&self) -> Option<Status> {
+        match self {
+            BehaviorNode::Unknown => None,
+            BehaviorNode::Root(root) => root.status,
+            BehaviorNode::Composite(composite) => composite.status,
+            BehaviorNode::Leaf(leaf) => leaf.status,
+            BehaviorNode::Action(action) => action.status,
+            BehaviorNode::Inverter(inverter) => inverter.status,
+            BehaviorNode::Subtree(subtree) => subtree.status,
+        }
+    }
+
+    fn set_status(&mut self, status: Option<Status>) {
+        match self {
+            BehaviorNode::Unknown => (),
+            BehaviorNode::Root(root) => root.status = status,
+            BehaviorNode::Composite(composite) => composite.status = status,
+            BehaviorNode::Leaf(leaf) => leaf.status = status,
+            BehaviorNode::Action(action) => action.status = status,
+            BehaviorNode::Inverter(inverter) => inverter.status = status,
+            BehaviorNode::Subtree(subtree) => subtree.status = status,
+        }
+    }
+
+    fn children_mut(&mut self) -> &mut [Handle<BehaviorNode<B>>] {
+        match self {
+            BehaviorNode::Unknown
+            | BehaviorNode::Leaf(_)
+            | BehaviorNode::Action(_)
+            | BehaviorNode::Subtree(_) => &mut [],
+            BehaviorNode::Root(root) => std::slice::from_mut(&mut root.child),
+            BehaviorNode::Composite(composite) => &mut composite.children,
+            BehaviorNode::Inverter(inverter) => std::slice::from_mut(&mut inverter.child),
+        }
+    }
+}
+
 /// See module docs.
 #[derive(Debug, PartialEq, Visit, Clone)]
 pub struct BehaviorTree<B>
@@ -131,9 +282,7 @@ where
     /// Creates new behavior tree with single root node.
     pub fn new() -> Self {
         let mut nodes = Pool::new();
-        let root = nodes.spawn(BehaviorNode::Root(RootNode {
-            child: Default::default(),
-        }));
+        let root = nodes.spawn(BehaviorNode::Root(Default::default()));
         Self { nodes, root }
     }
 
@@ -151,63 +300,118 @@ where
         }
     }
 
-    fn tick_recursive<'a, Ctx>(&self, handle: Handle<BehaviorNode<B>>, context: &mut Ctx) -> Status
+    /// Returns a handle of the entry node of the tree.
+    pub fn entry_node(&self) -> Handle<BehaviorNode<B>> {
+        if let BehaviorNode::Root(root) = &self.nodes[self.root] {
+            root.child
+        } else {
+            unreachable!("must be root")
+        }
+    }
+
+    /// Replaces every [`SubtreeRef`] node of the tree with a copy of the respective fragment from
+    /// the given library. Fragments could refer to other fragments, such references are resolved
+    /// as well. The tree is left unchanged if a fragment is missing or the references form a
+    /// cycle.
+    pub fn resolve_subtrees(&mut self, library: &SubtreeLibrary<B>) -> Result<(), SubtreeError> {
+        let mut tree = self.clone();
+
+        for (handle, node) in self.nodes.pair_iter() {
+            if let BehaviorNode::Subtree(subtree) = node {
+                let instance = library.instantiate(&subtree.name, &mut tree, &mut Vec::new())?;
+                // Move the root of the copy in place of the reference, so its parent points to it.
+                // An empty fragment becomes an empty sequence, that always succeeds.
+                let instance = if instance.is_some() {
+                    tree.nodes.free(instance)
+                } else {
+                    BehaviorNode::Composite(CompositeNode::new_sequence(Vec::new()))
+                };
+                tree.nodes[handle] = instance;
+            }
+        }
+
+        *self = tree;
+
+        Ok(())
+    }
+
+    fn tick_recursive<'a, Ctx>(
+        &mut self,
+        handle: Handle<BehaviorNode<B>>,
+        context: &mut Ctx,
+    ) -> Status
     where
         B: Behavior<'a, Context = Ctx>,
     {
-        match self.nodes[handle] {
+        let status = match self.nodes[handle] {
             BehaviorNode::Root(ref root) => {
-                if root.child.is_some() {
-                    self.tick_recursive(root.child, context)
+                let child = root.child;
+                if child.is_some() {
+                    self.tick_recursive(child, context)
                 } else {
                     Status::Success
                 }
             }
             BehaviorNode::Composite(ref composite) => match composite.kind {
-                CompositeNodeKind::Sequence => {
-                    let mut all_succeeded = true;
-                    for child in composite.children.iter() {
-                        match self.tick_recursive(*child, context) {
-                            Status::Failure => {
-                                all_succeeded = false;
-                                break;
-                            }
-                            Status::Running => {
-                                return Status::Running;
-                            }
-                            _ => (),
-                        }
-                    }
-                    if all_succeeded {
-                        Status::Success
-                    } else {
-                        Status::Failure
-                    }
-                }
-                CompositeNodeKind::Selector => {
-                    for child in composite.children.iter() {
-                        match self.tick_recursive(*child, context) {
-                            Status::Success => return Status::Success,
-                            Status::Running => return Status::Running,
-                            _ => (),
-                        }
-                    }
-                    Status::Failure
-                }
+                CompositeNodeKind::Sequence => self.tick_children(handle, Status::Success, context),
+                CompositeNodeKind::Selector => self.tick_children(handle, Status::Failure, context),
             },
-            BehaviorNode::Leaf(ref leaf) => {
-                leaf.behavior.as_ref().unwrap().borrow_mut().tick(context)
+            BehaviorNode::Leaf(ref mut leaf) => {
+                leaf.behavior.as_mut().unwrap().get_mut().tick(context)
             }
+            BehaviorNode::Action(ref action) => action.tick(context),
             BehaviorNode::Inverter(ref inverter) => {
-                match self.tick_recursive(inverter.child, context) {
+                let child = inverter.child;
+                match self.tick_recursive(child, context) {
                     Status::Success => Status::Failure,
                     Status::Failure => Status::Success,
                     Status::Running => Status::Running,
                 }
             }
+            // Unresolved reference, see `resolve_subtrees`.
+            BehaviorNode::Subtree(_) => Status::Failure,
             BehaviorNode::Unknown => {
                 unreachable!()
             }
+        };
+
+        self.nodes[handle].set_status(Some(status));
+
+        status
+    }
+
+    /// Ticks children of a composite node one by one while they return `pass` status. Returns the
+    /// first status that differs from `pass`, or `pass` if every child returned it.
+    fn tick_children<'a, Ctx>(
+        &mut self,
+        handle: Handle<BehaviorNode<B>>,
+        pass: Status,
+        context: &mut Ctx,
+    ) -> Status
+    where
+        B: Behavior<'a, Context = Ctx>,
+    {
+        let mut index = 0;
+        while let Some(&child) = self.nodes[handle].children().get(index) {
+            let status = self.tick_recursive(child, context);
+            if status != pass {
+                return status;
+            }
+            index += 1;
+        }
+        pass
+    }
+
+    fn traverse_recursive<F>(&self, handle: Handle<BehaviorNode<B>>, depth: usize, func: &mut F)
+    where
+        F: FnMut(usize, Handle<BehaviorNode<B>>, &BehaviorNode<B>, Option<Status>),
+    {
+        if let Some(node) = self.nodes.try_borrow(handle) {
+            func(depth, handle, node, node.status());
+
+            for &child in node.children() {
+                self.traverse_recursive(child, depth + 1, func);
+            }
         }
     }
 
@@ -222,12 +426,74 @@ where
     }
 
     /// Performs a single update tick with given context.
-    pub fn tick<'a, Ctx>(&self, context: &mut Ctx) -> Status
+    pub fn tick<'a, Ctx>(&mut self, context: &mut Ctx) -> Status
     where
         B: Behavior<'a, Context = Ctx>,
     {
+        for node in self.nodes.iter_mut() {
+            node.set_status(None);
+        }
         self.tick_recursive(self.root, context)
     }
+
+    /// Returns a status that the node returned at the last tick, or `None` if the node wasn't
+    /// ticked (it wasn't on the execution path).
+    pub fn last_status(&self, handle: Handle<BehaviorNode<B>>) -> Option<Status> {
+        self.nodes.try_borrow(handle).and_then(|node| node.status())
+    }
+
+    /// Walks the tree in depth-first order starting from the root node. The given function will be
+    /// called for every node with its depth, handle, reference and the status that the node returned
+    /// at the last tick. It could be used to visualize the tree and its active branch.
+    pub fn traverse<F>(&self, mut func: F)
+    where
+        F: FnMut(usize, Handle<BehaviorNode<B>>, &BehaviorNode<B>, Option<Status>),
+    {
+        self.traverse_recursive(self.root, 0, &mut func)
+    }
+
+    /// Writes the structure of the tree and the statuses of the nodes at the last tick in
+    /// [Graphviz](https://graphviz.org/) DOT format. Nodes that succeeded are green, failed nodes
+    /// are red, running nodes are orange.
+    pub fn to_dot(&self) -> String
+    where
+        B: Debug,
+    {
+        let mut dot = "digraph BehaviorTree {\n    node [shape=box];\n".to_string();
+
+        self.traverse(|_, handle, node, status| {
+            let label = match node {
+                BehaviorNode::Leaf(LeafNode {
+                    behavior: Some(behavior),
+                    ..
+                }) => format!("{:?}", behavior.borrow()),
+                BehaviorNode::Subtree(subtree) => format!("Subtree({})", subtree.name),
+                _ => node.kind_name().to_string(),
+            };
+            let color = match status {
+                Some(Status::Success) => "green",
+                Some(Status::Failure) => "red",
+                Some(Status::Running) => "orange",
+                None => "black",
+            };
+
+            let _ = writeln!(
+                dot,
+                "    n{} [label=\"{}\", color={}];",
+                handle.index(),
+                label.replace('\\', "\\\\").replace('"', "\\\""),
+                color
+            );
+
+            for child in node.children().iter().filter(|c| c.is_some()) {
+                let _ = writeln!(dot, "    n{} -> n{};", handle.index(), child.index());
+            }
+        });
+
+        dot.push('}');
+
+        dot
+    }
 }
 
 impl<B: Clone + 'static> Index<Handle<BehaviorNode<B>>> for BehaviorTree<B> {
@@ -274,6 +540,24 @@ where
     LeafNode::new(behavior).add_to(tree)
 }
 
+/// Creates a new leaf with logic defined by the given closure.
+pub fn action<B, F>(func: F, tree: &mut BehaviorTree<B>) -> Handle<BehaviorNode<B>>
+where
+    B: for<'a> Behavior<'a> + 'static,
+    F: for<'a> FnMut(&mut <B as Behavior<'a>>::Context) -> Status + 'static,
+{
+    ActionNode::new(func).add_to(tree)
+}
+
+/// Creates a new leaf, that succeeds if the given closure returns `true` and fails otherwise.
+pub fn condition<B, F>(func: F, tree: &mut BehaviorTree<B>) -> Handle<BehaviorNode<B>>
+where
+    B: for<'a> Behavior<'a> + 'static,
+    F: for<'a> FnMut(&<B as Behavior<'a>>::Context) -> bool + 'static,
+{
+    ActionNode::new_condition(func).add_to(tree)
+}
+
 /// Creates a new inverter.
 pub fn inverter<B>(
     child: Handle<BehaviorNode<B>>,
@@ -285,13 +569,26 @@ where
     Inverter::new(child).add_to(tree)
 }
 
+/// Creates a new reference to a named fragment, see [`subtree`] module docs for more info.
+pub fn subtree<B, S>(name: S, tree: &mut BehaviorTree<B>) -> Handle<BehaviorNode<B>>
+where
+    B: Clone + 'static,
+    S: AsRef<str>,
+{
+    SubtreeRef::new(name).add_to(tree)
+}
+
 #[cfg(test)]
 mod test {
     use crate::{
         core::{futures::executor::block_on, visitor::prelude::*},
         utils::behavior::{
+            action,
             composite::{CompositeNode, CompositeNodeKind},
+            condition, inverter,
             leaf::LeafNode,
+            sequence, subtree,
+            subtree::{SubtreeError, SubtreeLibrary},
             Behavior, BehaviorTree, Status,
         },
     };
@@ -427,7 +724,7 @@ mod test {
 
     #[test]
     fn test_behavior() {
-        let tree = create_tree();
+        let mut tree = create_tree();
 
         let mut ctx = Environment {
             distance_to_door: 3.0,
@@ -440,6 +737,177 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_behavior_traverse() {
+        let mut tree = create_tree();
+
+        let mut ctx = Environment {
+            distance_to_door: 3.0,
+            door_opened: false,
+            done: false,
+        };
+
+        tree.tick(&mut ctx);
+        assert_eq!(tree[tree.entry_node()].status(), Some(Status::Running));
+
+        let mut nodes = Vec::new();
+        tree.traverse(|depth, _, node, status| nodes.push((depth, node.kind_name(), status)));
+        assert_eq!(
+            nodes,
+            vec![
+                (0, "Root", Some(Status::Running)),
+                (1, "Sequence", Some(Status::Running)),
+                (2, "Leaf", Some(Status::Running)),
+                (2, "Leaf", None),
+                (2, "Leaf", None),
+                (2, "Leaf", None),
+            ]
+        );
+
+        let dot = tree.to_dot();
+        assert!(dot.starts_with("digraph BehaviorTree {"));
+        assert!(dot.contains("[label=\"Walk(WalkAction)\", color=orange]"));
+        assert!(dot.contains("[label=\"CloseDoor(CloseDoorAction)\", color=black]"));
+    }
+
+    #[test]
+    fn test_closure_leaves() {
+        let mut tree = BehaviorTree::<BotBehavior>::new();
+
+        let is_door_closed = condition(|ctx: &Environment| !ctx.door_opened, &mut tree);
+        let walk = LeafNode::new(BotBehavior::Walk(WalkAction)).add_to(&mut tree);
+        let mut opened = 0;
+        let open_door = action(
+            move |ctx: &mut Environment| {
+                opened += 1;
+                ctx.door_opened = true;
+                ctx.done = opened > 1;
+                Status::Success
+            },
+            &mut tree,
+        );
+        let entry = sequence([is_door_closed, walk, open_door], &mut tree);
+        tree.set_entry_node(entry);
+
+        let mut ctx = Environment {
+            distance_to_door: 0.0,
+            door_opened: false,
+            done: false,
+        };
+
+        assert_eq!(tree.tick(&mut ctx), Status::Success);
+        assert!(ctx.door_opened);
+        assert!(!ctx.done);
+        assert_eq!(tree.last_status(open_door), Some(Status::Success));
+
+        // The condition fails now, so the action is not called.
+        assert_eq!(tree.tick(&mut ctx), Status::Failure);
+        assert_eq!(tree.last_status(open_door), None);
+        assert_eq!(tree[is_door_closed].kind_name(), "Action");
+
+        // Clones share the closure, so the counter is shared too.
+        ctx.door_opened = false;
+        let mut cloned = tree.clone();
+        assert_eq!(cloned, tree);
+        assert_eq!(cloned.tick(&mut ctx), Status::Success);
+        assert!(ctx.done);
+    }
+
+    #[test]
+    fn test_subtrees() {
+        let mut library = SubtreeLibrary::<BotBehavior>::new();
+
+        let mut approach = BehaviorTree::new();
+        let is_door_closed = condition(|ctx: &Environment| !ctx.door_opened, &mut approach);
+        let walk = LeafNode::new(BotBehavior::Walk(WalkAction)).add_to(&mut approach);
+        let entry = sequence([is_door_closed, walk], &mut approach);
+        approach.set_entry_node(entry);
+        library.add("approach", approach);
+
+        let mut open = BehaviorTree::new();
+        let approach = subtree("approach", &mut open);
+        let open_door = LeafNode::new(BotBehavior::OpenDoor(OpenDoorAction)).add_to(&mut open);
+        let entry = sequence([approach, open_door], &mut open);
+        open.set_entry_node(entry);
+        library.add("open", open);
+
+        // The same fragment is used twice, the second one fails because the door is opened.
+        let mut tree = BehaviorTree::new();
+        let first = subtree("open", &mut tree);
+        let second = subtree("approach", &mut tree);
+        let entry = sequence([first, second], &mut tree);
+        tree.set_entry_node(entry);
+        tree.resolve_subtrees(&library).unwrap();
+
+        let mut kinds = Vec::new();
+        tree.traverse(|depth, _, node, _| kinds.push((depth, node.kind_name())));
+        assert_eq!(
+            kinds,
+            vec![
+                (0, "Root"),
+                (1, "Sequence"),
+                (2, "Sequence"),
+                (3, "Sequence"),
+                (4, "Action"),
+                (4, "Leaf"),
+                (3, "Leaf"),
+                (2, "Sequence"),
+                (3, "Action"),
+                (3, "Leaf"),
+            ]
+        );
+
+        let mut ctx = Environment {
+            distance_to_door: 0.0,
+            door_opened: false,
+            done: false,
+        };
+        assert_eq!(tree.tick(&mut ctx), Status::Failure);
+        assert!(ctx.door_opened);
+        assert_eq!(tree.last_status(first), Some(Status::Success));
+        assert_eq!(tree.last_status(second), Some(Status::Failure));
+    }
+
+    #[test]
+    fn test_cyclic_subtrees() {
+        let mut library = SubtreeLibrary::<BotBehavior>::new();
+
+        let mut a = BehaviorTree::new();
+        let b = subtree("b", &mut a);
+        let entry = sequence([b], &mut a);
+        a.set_entry_node(entry);
+        library.add("a", a);
+
+        let mut b = BehaviorTree::new();
+        let a = subtree("a", &mut b);
+        let entry = inverter(a, &mut b);
+        b.set_entry_node(entry);
+        library.add("b", b);
+
+        let mut tree = BehaviorTree::new();
+        let entry = subtree("a", &mut tree);
+        tree.set_entry_node(entry);
+        let unresolved = tree.clone();
+
+        assert_eq!(
+            tree.resolve_subtrees(&library),
+            Err(SubtreeError::Cycle(vec![
+                "a".to_string(),
+                "b".to_string(),
+                "a".to_string()
+            ]))
+        );
+        assert_eq!(tree, unresolved);
+
+        let mut tree = BehaviorTree::<BotBehavior>::new();
+        let entry = subtree("c", &mut tree);
+        tree.set_entry_node(entry);
+        assert_eq!(
+            tree.resolve_subtrees(&library),
+            Err(SubtreeError::Unknown("c".to_string()))
+        );
+    }
+
     #[test]
     fn test_behavior_save_load() {
         let (bin, txt) = {
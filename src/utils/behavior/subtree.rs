@@ -0,0 +1,164 @@
+//! Subtree references allow to author reusable behavior fragments (for example "find and face
+//! target") once and use them in multiple trees. A fragment is a separate [`BehaviorTree`], stored
+//! in a [`SubtreeLibrary`] under a unique name. A [`SubtreeRef`] node refers to a fragment by its
+//! name and it is replaced by a copy of the fragment in [`BehaviorTree::resolve_subtrees`].
+//!
+//! Fragments could refer to other fragments as well, cyclic references are detected and rejected
+//! with [`SubtreeError::Cycle`].
+
+use crate::{
+    core::{pool::Handle, visitor::prelude::*},
+    utils::behavior::{BehaviorNode, BehaviorTree, Status},
+};
+use fxhash::FxHashMap;
+use std::fmt::{Display, Formatter};
+
+/// A node, that refers to a named fragment from a [`SubtreeLibrary`]. The node must be resolved
+/// by [`BehaviorTree::resolve_subtrees`] before the tree is ticked, unresolved references always
+/// fail.
+#[derive(Debug, Default, PartialEq, Visit, Eq, Clone)]
+pub struct SubtreeRef {
+    /// Name of the fragment in a subtree library.
+    pub name: String,
+    #[visit(skip)]
+    pub(super) status: Option<Status>,
+}
+
+impl SubtreeRef {
+    /// Creates new reference to a fragment with the given name.
+    pub fn new<S: AsRef<str>>(name: S) -> Self {
+        Self {
+            name: name.as_ref().to_owned(),
+            status: None,
+        }
+    }
+
+    /// Adds self to given behavior tree and returns handle to self.
+    pub fn add_to<B>(self, tree: &mut BehaviorTree<B>) -> Handle<BehaviorNode<B>>
+    where
+        B: Clone + 'static,
+    {
+        tree.add_node(BehaviorNode::Subtree(self))
+    }
+}
+
+/// An error that may occur during subtree resolution.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum SubtreeError {
+    /// There's no fragment with the given name in the library.
+    Unknown(String),
+    /// A fragment refers to itself, directly or through other fragments. Contains the names of the
+    /// fragments that form the cycle, the first and the last names are equal.
+    Cycle(Vec<String>),
+}
+
+impl Display for SubtreeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SubtreeError::Unknown(name) => write!(f, "There's no {} subtree!", name),
+            SubtreeError::Cycle(names) => {
+                write!(f, "Cyclic subtree reference: {}", names.join(" -> "))
+            }
+        }
+    }
+}
+
+impl std::error::Error for SubtreeError {}
+
+/// A set of named behavior fragments. Every fragment is a separate behavior tree, its entry node
+/// is the root of the fragment.
+#[derive(Debug, Clone)]
+pub struct SubtreeLibrary<B>
+where
+    B: Clone,
+{
+    subtrees: FxHashMap<String, BehaviorTree<B>>,
+}
+
+impl<B> Default for SubtreeLibrary<B>
+where
+    B: Clone,
+{
+    fn default() -> Self {
+        Self {
+            subtrees: Default::default(),
+        }
+    }
+}
+
+impl<B> SubtreeLibrary<B>
+where
+    B: Clone + 'static,
+{
+    /// Creates new empty library.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a fragment with the given name, returns previous fragment with the same name (if any).
+    pub fn add<S: AsRef<str>>(
+        &mut self,
+        name: S,
+        subtree: BehaviorTree<B>,
+    ) -> Option<BehaviorTree<B>> {
+        self.subtrees.insert(name.as_ref().to_owned(), subtree)
+    }
+
+    /// Removes a fragment with the given name.
+    pub fn remove<S: AsRef<str>>(&mut self, name: S) -> Option<BehaviorTree<B>> {
+        self.subtrees.remove(name.as_ref())
+    }
+
+    /// Returns a reference to a fragment with the given name.
+    pub fn get<S: AsRef<str>>(&self, name: S) -> Option<&BehaviorTree<B>> {
+        self.subtrees.get(name.as_ref())
+    }
+
+    /// Copies a fragment with the given name into the tree and returns a handle of its root node.
+    /// `stack` contains names of the fragments that are being copied at the moment.
+    pub(super) fn instantiate(
+        &self,
+        name: &str,
+        tree: &mut BehaviorTree<B>,
+        stack: &mut Vec<String>,
+    ) -> Result<Handle<BehaviorNode<B>>, SubtreeError> {
+        if let Some(position) = stack.iter().position(|n| n == name) {
+            let mut cycle = stack[position..].to_vec();
+            cycle.push(name.to_owned());
+            return Err(SubtreeError::Cycle(cycle));
+        }
+
+        let subtree = self
+            .get(name)
+            .ok_or_else(|| SubtreeError::Unknown(name.to_owned()))?;
+
+        stack.push(name.to_owned());
+        let result = self.copy_node(subtree, subtree.entry_node(), tree, stack);
+        stack.pop();
+
+        result
+    }
+
+    fn copy_node(
+        &self,
+        source: &BehaviorTree<B>,
+        handle: Handle<BehaviorNode<B>>,
+        tree: &mut BehaviorTree<B>,
+        stack: &mut Vec<String>,
+    ) -> Result<Handle<BehaviorNode<B>>, SubtreeError> {
+        let node = match source.node(handle) {
+            Some(BehaviorNode::Subtree(subtree)) => {
+                return self.instantiate(&subtree.name, tree, stack)
+            }
+            Some(node) => node,
+            None => return Ok(Handle::NONE),
+        };
+
+        let mut copy = node.clone();
+        for child in copy.children_mut() {
+            *child = self.copy_node(source, *child, tree, stack)?;
+        }
+
+        Ok(tree.add_node(copy))
+    }
+}
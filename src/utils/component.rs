@@ -1,6 +1,22 @@
 //! Component provider provides dynamic access to inner components of an object by their type id.
+//! Component container stores arbitrary set of components and could be serialized, see
+//! [`ComponentContainer`] docs for more info.
 
-use std::any::{Any, TypeId};
+use crate::{
+    core::{
+        parking_lot::{Mutex, MutexGuard},
+        uuid::Uuid,
+        visitor::prelude::*,
+        TypeUuidProvider,
+    },
+    engine::SerializationContext,
+};
+use fxhash::FxHashSet;
+use std::{
+    any::{Any, TypeId},
+    collections::BTreeMap,
+    fmt::Debug,
+};
 
 /// Component provider provides dynamic access to inner components of an object by their type id.
 pub trait ComponentProvider {
@@ -71,3 +87,626 @@ macro_rules! impl_component_provider {
         }
     };
 }
+
+/// A component that could be stored in [`ComponentContainer`]. It is implemented automatically for
+/// every type that implements [`Visit`], [`TypeUuidProvider`] and [`Debug`].
+pub trait Component: Visit + Any + Debug + Send {
+    /// Casts self as `Any`.
+    fn as_any(&self) -> &dyn Any;
+
+    /// Casts self as `Any`.
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+
+    /// Casts self as `Any`.
+    fn into_any(self: Box<Self>) -> Box<dyn Any>;
+
+    /// Returns unique id of the component type. It is used to create the component on deserialization.
+    fn id(&self) -> Uuid;
+}
+
+impl<T> Component for T
+where
+    T: Visit + Any + Debug + Send + TypeUuidProvider,
+{
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn into_any(self: Box<Self>) -> Box<dyn Any> {
+        self
+    }
+
+    fn id(&self) -> Uuid {
+        T::type_uuid()
+    }
+}
+
+/// Component constructor.
+pub struct ComponentConstructor {
+    /// A simple type alias for boxed component constructor.
+    pub constructor: Box<dyn FnMut() -> Box<dyn Component> + Send>,
+
+    /// Component name.
+    pub name: String,
+}
+
+/// A special container that is able to create components by their type UUID.
+#[derive(Default)]
+pub struct ComponentConstructorContainer {
+    // BTreeMap allows to have sorted list of constructors.
+    map: Mutex<BTreeMap<Uuid, ComponentConstructor>>,
+}
+
+impl ComponentConstructorContainer {
+    /// Creates new empty component constructor container.
+    pub fn new() -> Self {
+        ComponentConstructorContainer::default()
+    }
+
+    /// Adds new type constructor for a given type.
+    ///
+    /// # Panic
+    ///
+    /// The method will panic if there is already a constructor for given type uuid.
+    pub fn add<T>(&self, name: &str) -> &Self
+    where
+        T: TypeUuidProvider + Component + Default,
+    {
+        let old = self.map.lock().insert(
+            T::type_uuid(),
+            ComponentConstructor {
+                constructor: Box::new(|| Box::new(T::default())),
+                name: name.to_owned(),
+            },
+        );
+
+        assert!(old.is_none());
+
+        self
+    }
+
+    /// Adds custom type constructor.
+    ///
+    /// # Panic
+    ///
+    /// The method will panic if there is already a constructor for given type uuid.
+    pub fn add_custom(&self, type_uuid: Uuid, constructor: ComponentConstructor) {
+        let old = self.map.lock().insert(type_uuid, constructor);
+
+        assert!(old.is_none());
+    }
+
+    /// Unregisters type constructor.
+    pub fn remove(&self, type_uuid: Uuid) {
+        self.map.lock().remove(&type_uuid);
+    }
+
+    /// Makes an attempt to create a component using provided type UUID. It may fail if there is no
+    /// component constructor for specified type UUID.
+    pub fn try_create(&self, type_uuid: &Uuid) -> Option<Box<dyn Component>> {
+        self.map
+            .lock()
+            .get_mut(type_uuid)
+            .map(|c| (c.constructor)())
+    }
+
+    /// Returns inner map of component constructors.
+    pub fn map(&self) -> MutexGuard<'_, BTreeMap<Uuid, ComponentConstructor>> {
+        self.map.lock()
+    }
+}
+
+/// Component container stores a set of components of arbitrary types, but no more than one component
+/// of each type. Unlike plain `Box<dyn Any>` storage, it can be serialized using [`Visit`] trait.
+///
+/// ## Serialization
+///
+/// Every component is saved with its type UUID, which is used to create an instance of the component
+/// on deserialization. This means that every component type must be registered in
+/// [`SerializationContext::component_constructors`] and the serialization context must be put in the
+/// blackboard of the visitor before loading. Engine's serialization context is already put there when
+/// loading scenes.
+///
+/// ## Example
+///
+/// ```rust
+/// use fyrox::{
+///     core::{uuid::{uuid, Uuid}, visitor::prelude::*, TypeUuidProvider},
+///     engine::SerializationContext,
+///     utils::component::ComponentContainer,
+/// };
+/// use std::sync::Arc;
+///
+/// #[derive(Visit, Default, Debug)]
+/// struct Health(f32);
+///
+/// impl TypeUuidProvider for Health {
+///     fn type_uuid() -> Uuid {
+///         uuid!("5b3c5e4c-5f6b-4a41-8a3c-1b7d1f0b1f43")
+///     }
+/// }
+///
+/// let serialization_context = Arc::new(SerializationContext::new());
+/// serialization_context
+///     .component_constructors
+///     .add::<Health>("Health");
+///
+/// let mut components = ComponentContainer::default();
+/// components.insert(Health(100.0));
+///
+/// let mut visitor = Visitor::new();
+/// components.visit("Components", &mut visitor).unwrap();
+/// let data = visitor.save_binary_to_vec().unwrap();
+///
+/// let mut visitor = Visitor::load_from_memory(data).unwrap();
+/// visitor.blackboard.register(serialization_context);
+/// let mut loaded = ComponentContainer::default();
+/// loaded.visit("Components", &mut visitor).unwrap();
+/// assert_eq!(loaded.get::<Health>().unwrap().0, 100.0);
+/// ```
+///
+/// ## Change tracking
+///
+/// The container could track changes of its components, so systems could react on changes without
+/// comparing components every frame. Tracking is disabled by default, it could be enabled by
+/// [`Self::with_change_tracking`] or [`Self::set_change_tracking`]. When enabled, every access
+/// to a component that could modify it ([`Self::insert`], [`Self::get_mut`],
+/// [`ComponentProvider::query_component_mut`], etc.) marks the component as changed, even if it
+/// was not actually modified. [`Self::drain_changed`] fetches changed component and resets the
+/// mark.
+///
+/// ```rust
+/// # use fyrox::{
+/// #     core::{uuid::{uuid, Uuid}, visitor::prelude::*, TypeUuidProvider},
+/// #     utils::component::ComponentContainer,
+/// # };
+/// # #[derive(Visit, Default, Debug)]
+/// # struct Health(f32);
+/// # impl TypeUuidProvider for Health {
+/// #     fn type_uuid() -> Uuid {
+/// #         uuid!("5b3c5e4c-5f6b-4a41-8a3c-1b7d1f0b1f43")
+/// #     }
+/// # }
+/// let mut components = ComponentContainer::with_change_tracking();
+/// components.insert(Health(100.0));
+/// assert_eq!(components.drain_changed::<Health>().count(), 1);
+/// assert_eq!(components.drain_changed::<Health>().count(), 0);
+///
+/// components.get_mut::<Health>().unwrap().0 -= 10.0;
+/// for health in components.drain_changed::<Health>() {
+///     assert_eq!(health.0, 90.0);
+/// }
+/// ```
+#[derive(Default, Debug)]
+pub struct ComponentContainer {
+    components: Vec<Box<dyn Component>>,
+    /// Types of changed components, `None` if change tracking is disabled.
+    changed: Option<FxHashSet<TypeId>>,
+}
+
+impl ComponentContainer {
+    /// Creates new empty container with enabled change tracking.
+    pub fn with_change_tracking() -> Self {
+        Self {
+            components: Default::default(),
+            changed: Some(Default::default()),
+        }
+    }
+
+    /// Enables or disables change tracking. Disabling resets every change mark.
+    pub fn set_change_tracking(&mut self, enabled: bool) {
+        if enabled != self.changed.is_some() {
+            self.changed = if enabled {
+                Some(Default::default())
+            } else {
+                None
+            };
+        }
+    }
+
+    /// Checks whether change tracking is enabled or not.
+    pub fn is_change_tracking_enabled(&self) -> bool {
+        self.changed.is_some()
+    }
+
+    /// Checks whether a component of the given type was changed since the last call of
+    /// [`Self::drain_changed`]. Always returns `false` if change tracking is disabled.
+    pub fn is_changed<T: Component>(&self) -> bool {
+        self.changed
+            .as_ref()
+            .map_or(false, |changed| changed.contains(&TypeId::of::<T>()))
+    }
+
+    /// Returns an iterator, that yields a component of the given type if it was changed since the
+    /// last call of the method, and resets its change mark. The iterator is always empty if change
+    /// tracking is disabled.
+    pub fn drain_changed<T: Component>(&mut self) -> impl Iterator<Item = &T> {
+        let changed = self
+            .changed
+            .as_mut()
+            .map_or(false, |changed| changed.remove(&TypeId::of::<T>()));
+        changed.then(|| self.get::<T>()).flatten().into_iter()
+    }
+
+    fn mark_changed(&mut self, type_id: TypeId) {
+        if let Some(changed) = self.changed.as_mut() {
+            changed.insert(type_id);
+        }
+    }
+
+    fn position(&self, type_id: TypeId) -> Option<usize> {
+        self.components
+            .iter()
+            .position(|c| (**c).as_any().type_id() == type_id)
+    }
+
+    /// Adds new component to the container. If there is already a component of the same type, it
+    /// will be replaced and returned.
+    pub fn insert<T: Component>(&mut self, component: T) -> Option<T> {
+        self.mark_changed(TypeId::of::<T>());
+        match self.position(TypeId::of::<T>()) {
+            Some(index) => Some(std::mem::replace(
+                self.components[index]
+                    .as_any_mut()
+                    .downcast_mut::<T>()
+                    .unwrap(),
+                component,
+            )),
+            None => {
+                self.components.push(Box::new(component));
+                None
+            }
+        }
+    }
+
+    /// Removes a component of the given type from the container and returns it.
+    pub fn remove<T: Component>(&mut self) -> Option<T> {
+        if let Some(changed) = self.changed.as_mut() {
+            changed.remove(&TypeId::of::<T>());
+        }
+        let index = self.position(TypeId::of::<T>())?;
+        self.components
+            .remove(index)
+            .into_any()
+            .downcast::<T>()
+            .ok()
+            .map(|c| *c)
+    }
+
+    /// Returns a reference to a component of the given type.
+    pub fn get<T: Component>(&self) -> Option<&T> {
+        self.position(TypeId::of::<T>())
+            .and_then(|index| self.components[index].as_any().downcast_ref())
+    }
+
+    /// Returns a reference to a component of the given type. Marks the component as changed, if
+    /// change tracking is enabled.
+    pub fn get_mut<T: Component>(&mut self) -> Option<&mut T> {
+        let index = self.position(TypeId::of::<T>())?;
+        self.mark_changed(TypeId::of::<T>());
+        self.components[index].as_any_mut().downcast_mut()
+    }
+
+    /// Returns references to two components of the given types at once. Unlike [`Self::get_two_mut`]
+    /// the types could be the same.
+    pub fn get_two<A: Component, B: Component>(&self) -> Option<(&A, &B)> {
+        Some((self.get::<A>()?, self.get::<B>()?))
+    }
+
+    /// Returns mutable references to two components of different types at once. It is useful when
+    /// a component must be modified using data of another component of the same object. Marks both
+    /// components as changed, if change tracking is enabled.
+    ///
+    /// # Same types
+    ///
+    /// There's no way to express type inequality in trait bounds, so the method returns `None` if
+    /// `A` and `B` are the same type, it never hands out two mutable references to one component.
+    pub fn get_two_mut<A: Component, B: Component>(&mut self) -> Option<(&mut A, &mut B)> {
+        self.get_two_mut_internal(true)
+    }
+
+    fn get_two_mut_internal<A: Component, B: Component>(
+        &mut self,
+        mark_a_changed: bool,
+    ) -> Option<(&mut A, &mut B)> {
+        if TypeId::of::<A>() == TypeId::of::<B>() {
+            return None;
+        }
+
+        let a = self.position(TypeId::of::<A>())?;
+        let b = self.position(TypeId::of::<B>())?;
+        if mark_a_changed {
+            self.mark_changed(TypeId::of::<A>());
+        }
+        self.mark_changed(TypeId::of::<B>());
+
+        // Indices are different, because every component type is stored only once.
+        let (a, b) = if a < b {
+            let (left, right) = self.components.split_at_mut(b);
+            (&mut left[a], &mut right[0])
+        } else {
+            let (left, right) = self.components.split_at_mut(a);
+            (&mut right[0], &mut left[b])
+        };
+
+        Some((
+            (**a).as_any_mut().downcast_mut()?,
+            (**b).as_any_mut().downcast_mut()?,
+        ))
+    }
+
+    /// Returns a reference to a component of type `A` and a mutable reference to a component of
+    /// type `B` at once. Returns `None` if `A` and `B` are the same type, see [`Self::get_two_mut`]
+    /// for more info. Only `B` is marked as changed.
+    pub fn get_ref_mut<A: Component, B: Component>(&mut self) -> Option<(&A, &mut B)> {
+        self.get_two_mut_internal::<A, B>(false)
+            .map(|(a, b)| (&*a, b))
+    }
+
+    /// Checks whether the container has a component of the given type.
+    pub fn contains<T: Component>(&self) -> bool {
+        self.position(TypeId::of::<T>()).is_some()
+    }
+
+    /// Returns total amount of components in the container.
+    pub fn len(&self) -> usize {
+        self.components.len()
+    }
+
+    /// Checks whether the container is empty or not.
+    pub fn is_empty(&self) -> bool {
+        self.components.is_empty()
+    }
+
+    /// Returns an iterator over all components in the container.
+    pub fn iter(&self) -> impl Iterator<Item = &dyn Component> {
+        self.components.iter().map(|c| &**c)
+    }
+
+    /// Removes all components from the container.
+    pub fn clear(&mut self) {
+        self.components.clear();
+        if let Some(changed) = self.changed.as_mut() {
+            changed.clear();
+        }
+    }
+}
+
+impl ComponentProvider for ComponentContainer {
+    fn query_component_ref(&self, type_id: TypeId) -> Option<&dyn Any> {
+        self.position(type_id)
+            .map(|index| self.components[index].as_any())
+    }
+
+    fn query_component_mut(&mut self, type_id: TypeId) -> Option<&mut dyn Any> {
+        let index = self.position(type_id)?;
+        self.mark_changed(type_id);
+        Some(self.components[index].as_any_mut())
+    }
+}
+
+impl Visit for ComponentContainer {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        let mut region = visitor.enter_region(name)?;
+
+        let mut count = self.components.len() as u32;
+        count.visit("Count", &mut region)?;
+
+        if region.is_reading() {
+            self.clear();
+
+            for i in 0..count {
+                let mut component_region = region.enter_region(&format!("Component{}", i))?;
+
+                let mut id = Uuid::default();
+                id.visit("TypeUuid", &mut component_region)?;
+
+                let mut component = component_region
+                    .blackboard
+                    .get::<SerializationContext>()
+                    .ok_or_else(|| {
+                        VisitError::User(
+                            "Visitor environment must contain serialization context!".to_string(),
+                        )
+                    })?
+                    .component_constructors
+                    .try_create(&id)
+                    .ok_or_else(|| {
+                        VisitError::User(format!("Unknown component type uuid {}!", id))
+                    })?;
+
+                component.visit("Data", &mut component_region)?;
+
+                // Loaded components are new for the systems, that track changes.
+                self.mark_changed((*component).as_any().type_id());
+                self.components.push(component);
+            }
+        } else {
+            for (i, component) in self.components.iter_mut().enumerate() {
+                let mut component_region = region.enter_region(&format!("Component{}", i))?;
+
+                let mut id = component.id();
+                id.visit("TypeUuid", &mut component_region)?;
+
+                component.visit("Data", &mut component_region)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::{
+            uuid::{uuid, Uuid},
+            visitor::prelude::*,
+            TypeUuidProvider,
+        },
+        engine::SerializationContext,
+        utils::component::{ComponentContainer, ComponentProvider},
+    };
+    use std::{any::TypeId, sync::Arc};
+
+    #[derive(Visit, Default, Debug, PartialEq)]
+    struct Health {
+        value: f32,
+    }
+
+    impl TypeUuidProvider for Health {
+        fn type_uuid() -> Uuid {
+            uuid!("5b3c5e4c-5f6b-4a41-8a3c-1b7d1f0b1f43")
+        }
+    }
+
+    #[derive(Visit, Default, Debug, PartialEq)]
+    struct Inventory {
+        items: Vec<String>,
+    }
+
+    impl TypeUuidProvider for Inventory {
+        fn type_uuid() -> Uuid {
+            uuid!("0d9b2b0e-6a55-4c8e-9c2f-2f0e7f5f6a11")
+        }
+    }
+
+    #[test]
+    fn test_component_container() {
+        let mut container = ComponentContainer::default();
+        assert!(container.insert(Health { value: 1.0 }).is_none());
+        assert_eq!(
+            container.insert(Health { value: 2.0 }),
+            Some(Health { value: 1.0 })
+        );
+        assert_eq!(container.len(), 1);
+        assert!(container.contains::<Health>());
+        assert!(!container.contains::<Inventory>());
+
+        container.get_mut::<Health>().unwrap().value = 3.0;
+        assert!(container
+            .query_component_ref(TypeId::of::<Health>())
+            .and_then(|c| c.downcast_ref::<Health>())
+            .map_or(false, |h| h.value == 3.0));
+
+        assert_eq!(container.remove::<Health>(), Some(Health { value: 3.0 }));
+        assert!(container.is_empty());
+    }
+
+    #[test]
+    fn test_component_container_get_two() {
+        let mut container = ComponentContainer::default();
+        container.insert(Health { value: 1.0 });
+        assert!(container.get_two_mut::<Health, Inventory>().is_none());
+
+        container.insert(Inventory {
+            items: vec!["Potion".to_string()],
+        });
+
+        for _ in 0..2 {
+            let (inventory, health) = container.get_two_mut::<Inventory, Health>().unwrap();
+            if inventory.items.pop().is_some() {
+                health.value += 10.0;
+            }
+        }
+        assert_eq!(container.get::<Health>(), Some(&Health { value: 11.0 }));
+
+        let (health, inventory) = container.get_ref_mut::<Health, Inventory>().unwrap();
+        inventory.items.push(format!("Health {}", health.value));
+        assert_eq!(
+            container.get_two::<Inventory, Health>().unwrap().0.items,
+            vec!["Health 11".to_string()]
+        );
+
+        // Two mutable references to the same component are never handed out.
+        assert!(container.get_two_mut::<Health, Health>().is_none());
+        assert!(container.get_two::<Health, Health>().is_some());
+    }
+
+    #[test]
+    fn test_component_container_change_tracking() {
+        // Tracking is opt-in.
+        let mut container = ComponentContainer::default();
+        container.insert(Health { value: 1.0 });
+        container.get_mut::<Health>().unwrap().value = 2.0;
+        assert!(!container.is_changed::<Health>());
+        assert_eq!(container.drain_changed::<Health>().count(), 0);
+
+        container.set_change_tracking(true);
+        assert!(container.is_change_tracking_enabled());
+        assert!(!container.is_changed::<Health>());
+
+        container.get_mut::<Health>().unwrap().value = 3.0;
+        container.insert(Inventory::default());
+        assert!(container.is_changed::<Health>());
+        assert_eq!(
+            container.drain_changed::<Health>().collect::<Vec<_>>(),
+            vec![&Health { value: 3.0 }]
+        );
+        assert_eq!(container.drain_changed::<Health>().count(), 0);
+        assert_eq!(container.drain_changed::<Inventory>().count(), 1);
+
+        // Read-only access does not mark components.
+        assert!(container.get::<Health>().is_some());
+        let (_, inventory) = container.get_ref_mut::<Health, Inventory>().unwrap();
+        inventory.items.push("Potion".to_string());
+        assert!(!container.is_changed::<Health>());
+        assert!(container.is_changed::<Inventory>());
+
+        container.get_two_mut::<Health, Inventory>().unwrap();
+        container
+            .query_component_mut(TypeId::of::<Health>())
+            .unwrap();
+        assert!(container.is_changed::<Health>());
+
+        // Removed components are not reported.
+        container.remove::<Health>();
+        assert_eq!(container.drain_changed::<Health>().count(), 0);
+
+        container.set_change_tracking(false);
+        assert!(!container.is_changed::<Inventory>());
+    }
+
+    #[test]
+    fn test_component_container_save_load() {
+        let serialization_context = Arc::new(SerializationContext::new());
+        serialization_context
+            .component_constructors
+            .add::<Health>("Health")
+            .add::<Inventory>("Inventory");
+
+        let mut container = ComponentContainer::default();
+        container.insert(Health { value: 42.0 });
+        container.insert(Inventory {
+            items: vec!["Sword".to_string(), "Shield".to_string()],
+        });
+
+        let mut visitor = Visitor::new();
+        container.visit("Components", &mut visitor).unwrap();
+        let data = visitor.save_binary_to_vec().unwrap();
+
+        // Unknown component types must produce an error instead of silently losing the data.
+        let mut visitor = Visitor::load_from_memory(data.clone()).unwrap();
+        visitor
+            .blackboard
+            .register(Arc::new(SerializationContext::new()));
+        let mut loaded = ComponentContainer::default();
+        assert!(loaded.visit("Components", &mut visitor).is_err());
+
+        let mut visitor = Visitor::load_from_memory(data).unwrap();
+        visitor.blackboard.register(serialization_context);
+        let mut loaded = ComponentContainer::default();
+        loaded.visit("Components", &mut visitor).unwrap();
+
+        assert_eq!(loaded.len(), 2);
+        assert_eq!(loaded.get::<Health>(), Some(&Health { value: 42.0 }));
+        assert_eq!(
+            loaded.get::<Inventory>().unwrap().items,
+            vec!["Sword".to_string(), "Shield".to_string()]
+        );
+    }
+}
@@ -0,0 +1,812 @@
+//! Contains local avoidance for groups of agents (crowds).
+//!
+//! Pathfinding ([`crate::utils::navmesh`]) gives every agent a path to follow, but it knows nothing
+//! about other agents. When many agents share a navmesh, they tend to clump and push through each
+//! other. Local avoidance fixes that: every agent picks a velocity that is as close as possible
+//! to the velocity it wants (for example towards the next corner of its corridor), but does not
+//! lead to a collision with nearby agents in the near future. Implementation is based on "Optimal
+//! Reciprocal Collision Avoidance" (ORCA) - see <https://gamma.cs.unc.edu/ORCA/>.
+//!
+//! Avoidance is performed in XZ plane, vertical component of the velocity is left as is.
+
+#![warn(missing_docs)]
+
+use crate::{
+    core::{
+        algebra::{Vector2, Vector3},
+        pool::{Handle, Pool},
+    },
+    utils::navmesh::{Corridor, Navmesh},
+};
+
+const EPSILON: f32 = 0.00001;
+
+// Agents that move exactly towards each other (for example, when they were placed on a grid) are
+// in a perfectly symmetric configuration, in which they only slow down and never pass each other.
+// A tiny rotation of the desired velocity breaks the symmetry, and the agents pass each other by
+// the same side.
+const SYMMETRY_BREAKING_ANGLE: f32 = 0.01;
+
+/// State of an agent, that is used for local avoidance.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct AvoidanceAgent {
+    /// Position of the agent.
+    pub position: Vector3<f32>,
+    /// Current velocity of the agent.
+    pub velocity: Vector3<f32>,
+    /// Radius of the agent.
+    pub radius: f32,
+}
+
+// A half-plane of permitted velocities, permitted side is on the left of the direction.
+#[derive(Clone, Copy, Debug)]
+struct Line {
+    point: Vector2<f32>,
+    direction: Vector2<f32>,
+}
+
+fn det(a: Vector2<f32>, b: Vector2<f32>) -> f32 {
+    a.x * b.y - a.y * b.x
+}
+
+fn planar(v: Vector3<f32>) -> Vector2<f32> {
+    Vector2::new(v.x, v.z)
+}
+
+// Builds a half-plane of velocities, that does not lead to a collision with the other agent in
+// the given time horizon. Both agents take half of the responsibility to avoid the collision.
+fn orca_line(agent: &AvoidanceAgent, other: &AvoidanceAgent, time_horizon: f32, dt: f32) -> Line {
+    let velocity = planar(agent.velocity);
+    let relative_position = planar(other.position) - planar(agent.position);
+    let relative_velocity = velocity - planar(other.velocity);
+    let distance_sqr = relative_position.norm_squared();
+    let combined_radius = agent.radius + other.radius;
+    let combined_radius_sqr = combined_radius * combined_radius;
+
+    let (direction, u) = if distance_sqr > combined_radius_sqr {
+        let inv_time_horizon = 1.0 / time_horizon;
+        // Vector from cutoff center to relative velocity.
+        let w = relative_velocity - relative_position.scale(inv_time_horizon);
+        let w_length_sqr = w.norm_squared();
+        let dot = w.dot(&relative_position);
+
+        if dot < 0.0 && dot * dot > combined_radius_sqr * w_length_sqr {
+            // Project on the cutoff circle.
+            let w_length = w_length_sqr.sqrt();
+            let unit_w = w.scale(1.0 / w_length);
+            (
+                Vector2::new(unit_w.y, -unit_w.x),
+                unit_w.scale(combined_radius * inv_time_horizon - w_length),
+            )
+        } else {
+            // Project on the legs of the velocity obstacle.
+            let leg = (distance_sqr - combined_radius_sqr).sqrt();
+            let direction = if det(relative_position, w) > 0.0 {
+                Vector2::new(
+                    relative_position.x * leg - relative_position.y * combined_radius,
+                    relative_position.x * combined_radius + relative_position.y * leg,
+                )
+                .scale(1.0 / distance_sqr)
+            } else {
+                -Vector2::new(
+                    relative_position.x * leg + relative_position.y * combined_radius,
+                    -relative_position.x * combined_radius + relative_position.y * leg,
+                )
+                .scale(1.0 / distance_sqr)
+            };
+            let projection = relative_velocity.dot(&direction);
+            (direction, direction.scale(projection) - relative_velocity)
+        }
+    } else {
+        // Agents are already colliding, resolve the collision in a single step.
+        let inv_dt = 1.0 / dt.max(EPSILON);
+        let w = relative_velocity - relative_position.scale(inv_dt);
+        let w_length = w.norm();
+        let unit_w = if w_length > EPSILON {
+            w.scale(1.0 / w_length)
+        } else {
+            // Agents are at the same position and have the same velocity, push them apart in
+            // some direction.
+            Vector2::new(1.0, 0.0)
+        };
+        (
+            Vector2::new(unit_w.y, -unit_w.x),
+            unit_w.scale(combined_radius * inv_dt - w_length),
+        )
+    };
+
+    Line {
+        point: velocity + u.scale(0.5),
+        direction,
+    }
+}
+
+// Solves one-dimensional linear program on the given line, subject to the constraints of the
+// previous lines and the circular speed limit.
+fn linear_program_1(
+    lines: &[Line],
+    line_index: usize,
+    radius: f32,
+    optimal: Vector2<f32>,
+    optimize_direction: bool,
+    result: &mut Vector2<f32>,
+) -> bool {
+    let line = lines[line_index];
+    let dot = line.point.dot(&line.direction);
+    let discriminant = dot * dot + radius * radius - line.point.norm_squared();
+
+    if discriminant < 0.0 {
+        // Max speed circle fully invalidates the line.
+        return false;
+    }
+
+    let discriminant_sqrt = discriminant.sqrt();
+    let mut t_left = -dot - discriminant_sqrt;
+    let mut t_right = -dot + discriminant_sqrt;
+
+    for other in lines[..line_index].iter() {
+        let denominator = det(line.direction, other.direction);
+        let numerator = det(other.direction, line.point - other.point);
+
+        if denominator.abs() <= EPSILON {
+            // Lines are (almost) parallel.
+            if numerator < 0.0 {
+                return false;
+            }
+            continue;
+        }
+
+        let t = numerator / denominator;
+        if denominator >= 0.0 {
+            t_right = t_right.min(t);
+        } else {
+            t_left = t_left.max(t);
+        }
+
+        if t_left > t_right {
+            return false;
+        }
+    }
+
+    let t = if optimize_direction {
+        if optimal.dot(&line.direction) > 0.0 {
+            t_right
+        } else {
+            t_left
+        }
+    } else {
+        line.direction
+            .dot(&(optimal - line.point))
+            .clamp(t_left, t_right)
+    };
+
+    *result = line.point + line.direction.scale(t);
+
+    true
+}
+
+// Solves two-dimensional linear program subject to the lines and the circular speed limit.
+// Returns index of the line on which the program failed, or the amount of lines on success.
+fn linear_program_2(
+    lines: &[Line],
+    radius: f32,
+    optimal: Vector2<f32>,
+    optimize_direction: bool,
+    result: &mut Vector2<f32>,
+) -> usize {
+    *result = if optimize_direction {
+        // Optimal velocity is a unit direction in this case.
+        optimal.scale(radius)
+    } else if optimal.norm_squared() > radius * radius {
+        optimal.normalize().scale(radius)
+    } else {
+        optimal
+    };
+
+    for (index, line) in lines.iter().enumerate() {
+        if det(line.direction, line.point - *result) > 0.0 {
+            // The result does not satisfy the constraint, compute new optimal result.
+            let previous = *result;
+            if !linear_program_1(lines, index, radius, optimal, optimize_direction, result) {
+                *result = previous;
+                return index;
+            }
+        }
+    }
+
+    lines.len()
+}
+
+// Finds a velocity that minimizes the maximum penetration into the half-planes, it is used when
+// there is no velocity that satisfies every constraint (very dense crowds).
+fn linear_program_3(lines: &[Line], begin_line: usize, radius: f32, result: &mut Vector2<f32>) {
+    let mut distance = 0.0;
+    let mut projected_lines = Vec::new();
+
+    for (index, line) in lines.iter().enumerate().skip(begin_line) {
+        if det(line.direction, line.point - *result) > distance {
+            projected_lines.clear();
+
+            for other in lines[..index].iter() {
+                let determinant = det(line.direction, other.direction);
+                let point = if determinant.abs() <= EPSILON {
+                    if line.direction.dot(&other.direction) > 0.0 {
+                        // Lines are parallel and point in the same direction.
+                        continue;
+                    }
+                    // Lines are parallel and point in the opposite directions.
+                    (line.point + other.point).scale(0.5)
+                } else {
+                    line.point
+                        + line
+                            .direction
+                            .scale(det(other.direction, line.point - other.point) / determinant)
+                };
+
+                projected_lines.push(Line {
+                    point,
+                    direction: (other.direction - line.direction)
+                        .try_normalize(EPSILON)
+                        .unwrap_or_default(),
+                });
+            }
+
+            let previous = *result;
+            if linear_program_2(
+                &projected_lines,
+                radius,
+                Vector2::new(-line.direction.y, line.direction.x),
+                true,
+                result,
+            ) < projected_lines.len()
+            {
+                // This should in principle not happen, the result is by definition already in the
+                // feasible region of this linear program. If it fails, it is due to small floating
+                // point errors, and the current result is kept.
+                *result = previous;
+            }
+
+            distance = det(line.direction, line.point - *result);
+        }
+    }
+}
+
+/// Computes a velocity for the agent, that is as close as possible to the `desired_velocity`, but
+/// does not lead to a collision with any of the `neighbors` in the next `time_horizon` seconds.
+/// The neighbors are expected to do the same, so each agent takes half of the responsibility to
+/// avoid a collision. `dt` is the time step of the simulation, it is used to resolve collisions of
+/// agents that are already overlapping. Length of the resulting velocity (in XZ plane) never exceeds
+/// `max_speed`, vertical component of the desired velocity is kept as is.
+///
+/// The neighbors list should not contain the agent itself. There is no limit on the distance of
+/// the neighbors, but it is advised to pass only agents that are close enough to affect the agent
+/// in the given time horizon, because the method is O(n^2) in the number of the neighbors in the
+/// worst case.
+pub fn compute_avoidance_velocity(
+    agent: &AvoidanceAgent,
+    desired_velocity: Vector3<f32>,
+    max_speed: f32,
+    neighbors: &[AvoidanceAgent],
+    time_horizon: f32,
+    dt: f32,
+) -> Vector3<f32> {
+    let time_horizon = time_horizon.max(EPSILON);
+
+    let lines = neighbors
+        .iter()
+        .map(|neighbor| orca_line(agent, neighbor, time_horizon, dt))
+        .collect::<Vec<_>>();
+
+    let mut result = Vector2::default();
+    let failed_line = linear_program_2(
+        &lines,
+        max_speed,
+        planar(desired_velocity),
+        false,
+        &mut result,
+    );
+    if failed_line < lines.len() {
+        linear_program_3(&lines, failed_line, max_speed, &mut result);
+    }
+
+    Vector3::new(result.x, desired_velocity.y, result.y)
+}
+
+/// An agent of a crowd, see [`CrowdSimulator`] docs for more info.
+#[derive(Clone, Debug)]
+pub struct CrowdAgent {
+    position: Vector3<f32>,
+    velocity: Vector3<f32>,
+    desired_velocity: Vector3<f32>,
+    radius: f32,
+    max_speed: f32,
+    arrival_distance: f32,
+    corridor: Option<Corridor>,
+}
+
+impl Default for CrowdAgent {
+    fn default() -> Self {
+        Self {
+            position: Default::default(),
+            velocity: Default::default(),
+            desired_velocity: Default::default(),
+            radius: 0.3,
+            max_speed: 1.5,
+            arrival_distance: 0.1,
+            corridor: None,
+        }
+    }
+}
+
+impl CrowdAgent {
+    /// Returns current position of the agent.
+    pub fn position(&self) -> Vector3<f32> {
+        self.position
+    }
+
+    /// Teleports the agent to the given position.
+    pub fn set_position(&mut self, position: Vector3<f32>) {
+        self.position = position;
+    }
+
+    /// Returns actual velocity of the agent, calculated on last update of the simulator.
+    pub fn velocity(&self) -> Vector3<f32> {
+        self.velocity
+    }
+
+    /// Returns the velocity, that the agent wants to have if there would be no other agents.
+    pub fn desired_velocity(&self) -> Vector3<f32> {
+        self.desired_velocity
+    }
+
+    /// Sets the velocity, that the agent wants to have. It is used only if the agent does not
+    /// follow a corridor, otherwise the desired velocity is calculated automatically on every
+    /// update.
+    pub fn set_desired_velocity(&mut self, velocity: Vector3<f32>) {
+        self.desired_velocity = velocity;
+    }
+
+    /// Returns radius of the agent.
+    pub fn radius(&self) -> f32 {
+        self.radius
+    }
+
+    /// Sets new radius of the agent.
+    pub fn set_radius(&mut self, radius: f32) {
+        self.radius = radius.max(0.0);
+    }
+
+    /// Returns max speed of the agent.
+    pub fn max_speed(&self) -> f32 {
+        self.max_speed
+    }
+
+    /// Sets new max speed of the agent.
+    pub fn set_max_speed(&mut self, max_speed: f32) {
+        self.max_speed = max_speed.max(0.0);
+    }
+
+    /// Returns the distance to the end of the corridor, at which the agent is considered arrived.
+    pub fn arrival_distance(&self) -> f32 {
+        self.arrival_distance
+    }
+
+    /// Sets the distance to the end of the corridor, at which the agent is considered arrived.
+    pub fn set_arrival_distance(&mut self, distance: f32) {
+        self.arrival_distance = distance.max(0.0);
+    }
+
+    /// Returns current corridor of the agent (if any).
+    pub fn corridor(&self) -> Option<&Corridor> {
+        self.corridor.as_ref()
+    }
+
+    /// Sets new corridor for the agent to follow. The agent will move along the shortest path in
+    /// the corridor until it reaches its end, then the corridor is removed and the agent stops.
+    /// Use [`Navmesh::build_corridor`] to create a corridor, or [`CrowdSimulator::request_move`]
+    /// to do it in one call.
+    pub fn set_corridor(&mut self, corridor: Option<Corridor>) {
+        self.corridor = corridor;
+    }
+
+    /// Returns `true` if the agent follows a corridor.
+    pub fn is_moving_along_corridor(&self) -> bool {
+        self.corridor.is_some()
+    }
+
+    fn avoidance_state(&self) -> AvoidanceAgent {
+        AvoidanceAgent {
+            position: self.position,
+            velocity: self.velocity,
+            radius: self.radius,
+        }
+    }
+
+    fn update_desired_velocity(&mut self) {
+        if let Some(corridor) = self.corridor.as_ref() {
+            let end = corridor.end();
+            if planar(end - self.position).norm() <= self.arrival_distance {
+                self.corridor = None;
+                self.desired_velocity = Default::default();
+            } else {
+                let corner = corridor.next_corner(self.position);
+                let delta = corner - self.position;
+                let distance = delta.norm();
+                // Slow down when approaching the end of the corridor to not overshoot it.
+                let speed = if corner == end {
+                    self.max_speed.min(distance)
+                } else {
+                    self.max_speed
+                };
+                self.desired_velocity = delta
+                    .try_normalize(f32::EPSILON)
+                    .unwrap_or_default()
+                    .scale(speed);
+            }
+        }
+    }
+}
+
+/// Allows you to build crowd agent in declarative manner.
+pub struct CrowdAgentBuilder {
+    position: Vector3<f32>,
+    radius: f32,
+    max_speed: f32,
+    arrival_distance: f32,
+}
+
+impl Default for CrowdAgentBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CrowdAgentBuilder {
+    /// Creates new builder instance.
+    pub fn new() -> Self {
+        let agent = CrowdAgent::default();
+        Self {
+            position: agent.position,
+            radius: agent.radius,
+            max_speed: agent.max_speed,
+            arrival_distance: agent.arrival_distance,
+        }
+    }
+
+    /// Sets new position of the agent being built.
+    pub fn with_position(mut self, position: Vector3<f32>) -> Self {
+        self.position = position;
+        self
+    }
+
+    /// Sets new radius of the agent being built.
+    pub fn with_radius(mut self, radius: f32) -> Self {
+        self.radius = radius.max(0.0);
+        self
+    }
+
+    /// Sets new max speed of the agent being built.
+    pub fn with_max_speed(mut self, max_speed: f32) -> Self {
+        self.max_speed = max_speed.max(0.0);
+        self
+    }
+
+    /// Sets new arrival distance of the agent being built.
+    pub fn with_arrival_distance(mut self, distance: f32) -> Self {
+        self.arrival_distance = distance.max(0.0);
+        self
+    }
+
+    /// Build the agent.
+    pub fn build(self) -> CrowdAgent {
+        CrowdAgent {
+            position: self.position,
+            radius: self.radius,
+            max_speed: self.max_speed,
+            arrival_distance: self.arrival_distance,
+            ..Default::default()
+        }
+    }
+}
+
+/// Crowd simulator manages a set of agents and moves them, so they avoid each other. Each agent
+/// either follows its [`Corridor`] or moves with a velocity set by [`CrowdAgent::set_desired_velocity`],
+/// and the simulator adjusts the velocity to avoid collisions with other agents (see
+/// [`compute_avoidance_velocity`]).
+///
+/// The simulator does not keep the agents on the navmesh, the corridor only defines the direction
+/// of movement, so the agents can be pushed slightly off the navmesh in dense crowds.
+pub struct CrowdSimulator {
+    agents: Pool<CrowdAgent>,
+    time_horizon: f32,
+    neighbor_distance: f32,
+}
+
+impl Default for CrowdSimulator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CrowdSimulator {
+    /// Creates new empty crowd simulator.
+    pub fn new() -> Self {
+        Self {
+            agents: Default::default(),
+            time_horizon: 2.0,
+            neighbor_distance: 5.0,
+        }
+    }
+
+    /// Adds new agent to the simulator and returns its handle.
+    pub fn add_agent(&mut self, agent: CrowdAgent) -> Handle<CrowdAgent> {
+        self.agents.spawn(agent)
+    }
+
+    /// Removes the agent from the simulator. Returns `None` if the handle is invalid.
+    pub fn remove_agent(&mut self, handle: Handle<CrowdAgent>) -> Option<CrowdAgent> {
+        if self.agents.is_valid_handle(handle) {
+            Some(self.agents.free(handle))
+        } else {
+            None
+        }
+    }
+
+    /// Returns a reference to the agent.
+    pub fn agent(&self, handle: Handle<CrowdAgent>) -> Option<&CrowdAgent> {
+        self.agents.try_borrow(handle)
+    }
+
+    /// Returns a reference to the agent.
+    pub fn agent_mut(&mut self, handle: Handle<CrowdAgent>) -> Option<&mut CrowdAgent> {
+        self.agents.try_borrow_mut(handle)
+    }
+
+    /// Returns an iterator over every agent with its handle.
+    pub fn agents(&self) -> impl Iterator<Item = (Handle<CrowdAgent>, &CrowdAgent)> {
+        self.agents.pair_iter()
+    }
+
+    /// Returns amount of agents in the simulator.
+    pub fn agent_count(&self) -> usize {
+        self.agents.alive_count() as usize
+    }
+
+    /// Builds a corridor from the current position of the agent to the target position and makes
+    /// the agent follow it. Returns `false` if the corridor cannot be built (see
+    /// [`Navmesh::build_corridor`]) or the handle is invalid, the agent keeps its previous corridor
+    /// in this case.
+    pub fn request_move(
+        &mut self,
+        handle: Handle<CrowdAgent>,
+        navmesh: &Navmesh,
+        target: Vector3<f32>,
+    ) -> bool {
+        if let Some(agent) = self.agents.try_borrow_mut(handle) {
+            if let Some(corridor) = navmesh.build_corridor(agent.position, target) {
+                agent.corridor = Some(corridor);
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Sets the time (in seconds) for which the agents look ahead for possible collisions. Larger
+    /// values make the agents to start avoiding each other earlier, but makes them less agile in
+    /// dense crowds.
+    pub fn set_time_horizon(&mut self, time_horizon: f32) {
+        self.time_horizon = time_horizon.max(EPSILON);
+    }
+
+    /// Returns the time (in seconds) for which the agents look ahead for possible collisions.
+    pub fn time_horizon(&self) -> f32 {
+        self.time_horizon
+    }
+
+    /// Sets max distance between agents' surfaces, at which they are taken into account for
+    /// avoidance.
+    pub fn set_neighbor_distance(&mut self, distance: f32) {
+        self.neighbor_distance = distance.max(0.0);
+    }
+
+    /// Returns max distance between agents' surfaces, at which they are taken into account for
+    /// avoidance.
+    pub fn neighbor_distance(&self) -> f32 {
+        self.neighbor_distance
+    }
+
+    /// Performs single update tick of the simulation: calculates desired velocity of each agent,
+    /// adjusts it to avoid collisions with neighbors and moves the agents.
+    ///
+    /// # Notes
+    ///
+    /// Neighbors are found by brute force search, so the method is O(n^2) in the number of agents.
+    pub fn update(&mut self, dt: f32) {
+        for agent in self.agents.iter_mut() {
+            agent.update_desired_velocity();
+        }
+
+        let states = self
+            .agents
+            .pair_iter()
+            .map(|(handle, agent)| (handle, agent.avoidance_state()))
+            .collect::<Vec<_>>();
+
+        let mut velocities = Vec::with_capacity(states.len());
+        let mut neighbors = Vec::new();
+        for (handle, state) in states.iter() {
+            neighbors.clear();
+            neighbors.extend(
+                states
+                    .iter()
+                    .filter(|(other_handle, other)| {
+                        other_handle != handle
+                            && planar(other.position - state.position).norm()
+                                - state.radius
+                                - other.radius
+                                <= self.neighbor_distance
+                    })
+                    .map(|(_, other)| other.clone()),
+            );
+
+            let agent = self.agents.borrow(*handle);
+            let (sin, cos) = SYMMETRY_BREAKING_ANGLE.sin_cos();
+            let desired = agent.desired_velocity;
+            velocities.push(compute_avoidance_velocity(
+                state,
+                Vector3::new(
+                    desired.x * cos + desired.z * sin,
+                    desired.y,
+                    desired.z * cos - desired.x * sin,
+                ),
+                agent.max_speed,
+                &neighbors,
+                self.time_horizon,
+                dt,
+            ));
+        }
+
+        for ((handle, _), velocity) in states.into_iter().zip(velocities) {
+            let agent = self.agents.borrow_mut(handle);
+            agent.velocity = velocity;
+            agent.position += velocity.scale(dt);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::{algebra::Vector3, math::TriangleDefinition},
+        utils::{
+            crowd::{
+                compute_avoidance_velocity, AvoidanceAgent, CrowdAgentBuilder, CrowdSimulator,
+            },
+            navmesh::Navmesh,
+        },
+    };
+
+    #[test]
+    fn test_avoidance_velocity() {
+        let agent = AvoidanceAgent {
+            position: Vector3::new(0.0, 0.0, 0.0),
+            velocity: Vector3::new(1.0, 0.0, 0.0),
+            radius: 0.5,
+        };
+        let desired = Vector3::new(1.0, 0.0, 0.0);
+
+        // No neighbors - desired velocity is kept.
+        assert_eq!(
+            compute_avoidance_velocity(&agent, desired, 2.0, &[], 2.0, 0.1),
+            desired
+        );
+
+        // Desired velocity is limited by max speed.
+        let velocity = compute_avoidance_velocity(&agent, desired.scale(3.0), 2.0, &[], 2.0, 0.1);
+        assert!((velocity.norm() - 2.0).abs() < 0.0001);
+
+        // Far neighbor does not affect the velocity.
+        let far = AvoidanceAgent {
+            position: Vector3::new(0.0, 0.0, 50.0),
+            velocity: Default::default(),
+            radius: 0.5,
+        };
+        assert_eq!(
+            compute_avoidance_velocity(&agent, desired, 2.0, &[far], 2.0, 0.1),
+            desired
+        );
+
+        // Head-on neighbor - the agent must turn aside.
+        let oncoming = AvoidanceAgent {
+            position: Vector3::new(3.0, 0.0, 0.0),
+            velocity: Vector3::new(-1.0, 0.0, 0.0),
+            radius: 0.5,
+        };
+        let velocity = compute_avoidance_velocity(&agent, desired, 2.0, &[oncoming], 2.0, 0.1);
+        assert!(velocity.z.abs() > 0.01);
+        assert!(velocity.norm() <= 2.0 + 0.0001);
+    }
+
+    #[test]
+    fn test_crowd_head_on() {
+        let mut crowd = CrowdSimulator::new();
+        let a = crowd.add_agent(
+            CrowdAgentBuilder::new()
+                .with_position(Vector3::new(-5.0, 0.0, 0.0))
+                .with_radius(0.5)
+                .build(),
+        );
+        let b = crowd.add_agent(
+            CrowdAgentBuilder::new()
+                .with_position(Vector3::new(5.0, 0.0, 0.0))
+                .with_radius(0.5)
+                .build(),
+        );
+        crowd
+            .agent_mut(a)
+            .unwrap()
+            .set_desired_velocity(Vector3::new(1.0, 0.0, 0.0));
+        crowd
+            .agent_mut(b)
+            .unwrap()
+            .set_desired_velocity(Vector3::new(-1.0, 0.0, 0.0));
+
+        for _ in 0..300 {
+            crowd.update(1.0 / 30.0);
+
+            let distance = crowd
+                .agent(a)
+                .unwrap()
+                .position()
+                .metric_distance(&crowd.agent(b).unwrap().position());
+            assert!(distance >= 1.0 - 0.01);
+        }
+
+        // Agents must pass each other.
+        assert!(crowd.agent(a).unwrap().position().x > 3.0);
+        assert!(crowd.agent(b).unwrap().position().x < -3.0);
+
+        assert!(crowd.remove_agent(a).is_some());
+        assert!(crowd.remove_agent(a).is_none());
+        assert_eq!(crowd.agent_count(), 1);
+    }
+
+    #[test]
+    fn test_crowd_corridor() {
+        // A 10x2 strip.
+        let vertices = vec![
+            Vector3::new(0.0, 0.0, 0.0),
+            Vector3::new(10.0, 0.0, 0.0),
+            Vector3::new(10.0, 0.0, 2.0),
+            Vector3::new(0.0, 0.0, 2.0),
+        ];
+        let triangles = vec![TriangleDefinition([0, 1, 2]), TriangleDefinition([0, 2, 3])];
+        let navmesh = Navmesh::new(&triangles, &vertices);
+
+        let mut crowd = CrowdSimulator::new();
+        let a = crowd.add_agent(
+            CrowdAgentBuilder::new()
+                .with_position(Vector3::new(1.0, 0.0, 1.0))
+                .build(),
+        );
+        let b = crowd.add_agent(
+            CrowdAgentBuilder::new()
+                .with_position(Vector3::new(9.0, 0.0, 1.0))
+                .build(),
+        );
+
+        assert!(crowd.request_move(a, &navmesh, Vector3::new(9.0, 0.0, 1.0)));
+        assert!(crowd.request_move(b, &navmesh, Vector3::new(1.0, 0.0, 1.0)));
+        assert!(!crowd.request_move(a, &navmesh, Vector3::new(20.0, 0.0, 1.0)));
+
+        for _ in 0..600 {
+            crowd.update(1.0 / 30.0);
+        }
+
+        for (agent, target) in [(a, 9.0), (b, 1.0)] {
+            let agent = crowd.agent(agent).unwrap();
+            assert!(!agent.is_moving_along_corridor());
+            assert!((agent.position().x - target).abs() < 0.5);
+        }
+    }
+}
@@ -0,0 +1,198 @@
+//! Curves and easing functions for animation and tweening.
+//!
+//! Keyframe curves are provided by [`Curve`] from the core crate, it stores sorted keys with
+//! constant, linear or cubic interpolation between them, could be saved using [`crate::core::visitor::Visit`]
+//! and evaluated using [`Curve::value_at`]. This module re-exports it along with a set of standard
+//! easing functions. Every easing function maps `t` in `[0; 1]` range to `[0; 1]` range (some of
+//! them, like [`ease_in_back`], slightly overshoot), so it could be used directly as an
+//! interpolation factor:
+//!
+//! ```rust
+//! use fyrox::{core::math::lerpf, utils::curve::ease_in_out_cubic};
+//!
+//! let start = 10.0;
+//! let end = 20.0;
+//! let value = lerpf(start, end, ease_in_out_cubic(0.5));
+//! assert_eq!(value, 15.0);
+//! ```
+
+pub use crate::core::curve::{Curve, CurveKey, CurveKeyKind};
+
+use std::f32::consts::PI;
+
+/// Does no easing, returns `t` as is.
+#[inline]
+pub fn linear(t: f32) -> f32 {
+    t
+}
+
+/// Quadratic easing, accelerates from zero velocity.
+#[inline]
+pub fn ease_in_quad(t: f32) -> f32 {
+    t * t
+}
+
+/// Quadratic easing, decelerates to zero velocity.
+#[inline]
+pub fn ease_out_quad(t: f32) -> f32 {
+    1.0 - (1.0 - t) * (1.0 - t)
+}
+
+/// Quadratic easing, accelerates until halfway, then decelerates.
+#[inline]
+pub fn ease_in_out_quad(t: f32) -> f32 {
+    if t < 0.5 {
+        2.0 * t * t
+    } else {
+        1.0 - (-2.0 * t + 2.0).powi(2) * 0.5
+    }
+}
+
+/// Cubic easing, accelerates from zero velocity.
+#[inline]
+pub fn ease_in_cubic(t: f32) -> f32 {
+    t * t * t
+}
+
+/// Cubic easing, decelerates to zero velocity.
+#[inline]
+pub fn ease_out_cubic(t: f32) -> f32 {
+    1.0 - (1.0 - t).powi(3)
+}
+
+/// Cubic easing, accelerates until halfway, then decelerates.
+#[inline]
+pub fn ease_in_out_cubic(t: f32) -> f32 {
+    if t < 0.5 {
+        4.0 * t * t * t
+    } else {
+        1.0 - (-2.0 * t + 2.0).powi(3) * 0.5
+    }
+}
+
+/// Sinusoidal easing, accelerates from zero velocity.
+#[inline]
+pub fn ease_in_sine(t: f32) -> f32 {
+    1.0 - (t * PI * 0.5).cos()
+}
+
+/// Sinusoidal easing, decelerates to zero velocity.
+#[inline]
+pub fn ease_out_sine(t: f32) -> f32 {
+    (t * PI * 0.5).sin()
+}
+
+/// Sinusoidal easing, accelerates until halfway, then decelerates.
+#[inline]
+pub fn ease_in_out_sine(t: f32) -> f32 {
+    -((PI * t).cos() - 1.0) * 0.5
+}
+
+/// Exponential easing, accelerates from zero velocity.
+#[inline]
+pub fn ease_in_expo(t: f32) -> f32 {
+    if t <= 0.0 {
+        0.0
+    } else {
+        2.0f32.powf(10.0 * t - 10.0)
+    }
+}
+
+/// Exponential easing, decelerates to zero velocity.
+#[inline]
+pub fn ease_out_expo(t: f32) -> f32 {
+    if t >= 1.0 {
+        1.0
+    } else {
+        1.0 - 2.0f32.powf(-10.0 * t)
+    }
+}
+
+/// Easing that pulls back a bit before moving forward.
+#[inline]
+pub fn ease_in_back(t: f32) -> f32 {
+    const C1: f32 = 1.70158;
+    const C3: f32 = C1 + 1.0;
+    C3 * t * t * t - C1 * t * t
+}
+
+/// Easing that overshoots the target a bit before settling.
+#[inline]
+pub fn ease_out_back(t: f32) -> f32 {
+    1.0 - ease_in_back(1.0 - t)
+}
+
+/// Easing that bounces off the target like a dropped ball.
+#[inline]
+pub fn ease_out_bounce(t: f32) -> f32 {
+    const N: f32 = 7.5625;
+    const D: f32 = 2.75;
+    if t < 1.0 / D {
+        N * t * t
+    } else if t < 2.0 / D {
+        let t = t - 1.5 / D;
+        N * t * t + 0.75
+    } else if t < 2.5 / D {
+        let t = t - 2.25 / D;
+        N * t * t + 0.9375
+    } else {
+        let t = t - 2.625 / D;
+        N * t * t + 0.984375
+    }
+}
+
+/// Easing that bounces off the start like a dropped ball, played backwards.
+#[inline]
+pub fn ease_in_bounce(t: f32) -> f32 {
+    1.0 - ease_out_bounce(1.0 - t)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::utils::curve::*;
+
+    #[test]
+    fn test_easing_end_points() {
+        let functions: [fn(f32) -> f32; 16] = [
+            linear,
+            ease_in_quad,
+            ease_out_quad,
+            ease_in_out_quad,
+            ease_in_cubic,
+            ease_out_cubic,
+            ease_in_out_cubic,
+            ease_in_sine,
+            ease_out_sine,
+            ease_in_out_sine,
+            ease_in_expo,
+            ease_out_expo,
+            ease_in_back,
+            ease_out_back,
+            ease_in_bounce,
+            ease_out_bounce,
+        ];
+
+        for function in functions {
+            assert!(function(0.0).abs() < 1.0e-5);
+            assert!((function(1.0) - 1.0).abs() < 1.0e-5);
+        }
+
+        assert_eq!(ease_in_out_cubic(0.5), 0.5);
+        assert!(ease_in_cubic(0.25) < 0.25);
+        assert!(ease_out_cubic(0.25) > 0.25);
+    }
+
+    #[test]
+    fn test_curve_evaluation() {
+        let curve = Curve::from(vec![
+            CurveKey::new(0.0, 0.0, CurveKeyKind::Linear),
+            CurveKey::new(1.0, 2.0, CurveKeyKind::Constant),
+            CurveKey::new(2.0, 4.0, CurveKeyKind::Constant),
+        ]);
+
+        assert_eq!(curve.value_at(-1.0), 0.0);
+        assert_eq!(curve.value_at(0.5), 1.0);
+        assert_eq!(curve.value_at(1.5), 2.0);
+        assert_eq!(curve.value_at(3.0), 4.0);
+    }
+}
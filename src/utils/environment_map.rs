@@ -0,0 +1,473 @@
+//! Environment map utilities: conversion of equirectangular (latitude-longitude) images to cube
+//! maps, prefiltering of cube maps for specular image-based lighting and irradiance convolution
+//! for diffuse image-based lighting. Everything is done on CPU, results are regular cube map
+//! textures in linear color space, that could be used by the renderer.
+//!
+//! See [`make_environment_maps`] docs for more info.
+
+use crate::{
+    core::algebra::Vector3,
+    resource::texture::{Texture, TextureKind, TexturePixelKind},
+};
+use half::f16;
+use std::{
+    f32::consts::PI,
+    fmt::{Display, Formatter},
+};
+
+/// Amount of mip levels of a specular cube map. Mip level `n` is prefiltered for roughness
+/// `n / (SPECULAR_MIP_COUNT - 1)`, this must match the lighting shader.
+pub const SPECULAR_MIP_COUNT: u32 = 7;
+
+/// Size of a face of an irradiance cube map. Irradiance is very smooth, so it does not need much
+/// detail.
+pub const IRRADIANCE_FACE_SIZE: u32 = 16;
+
+const PREFILTER_SAMPLE_COUNT: u32 = 64;
+
+/// An error that may occur during environment map generation.
+#[derive(Debug)]
+pub enum EnvironmentMapError {
+    /// Source texture must be a non-empty rectangle texture.
+    UnsupportedTextureKind(TextureKind),
+    /// Pixel kind of the source texture is not supported.
+    UnsupportedPixelKind(TexturePixelKind),
+}
+
+impl Display for EnvironmentMapError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EnvironmentMapError::UnsupportedTextureKind(kind) => {
+                write!(
+                    f,
+                    "Unsupported texture kind {:?}, an equirectangular texture must be a \
+                    rectangle texture.",
+                    kind
+                )
+            }
+            EnvironmentMapError::UnsupportedPixelKind(pixel_kind) => {
+                write!(
+                    f,
+                    "Unsupported pixel kind {:?}, only RGB8, RGBA8, RGB32F and RGBA32F \
+                    textures are supported.",
+                    pixel_kind
+                )
+            }
+        }
+    }
+}
+
+/// Cube maps made of an equirectangular texture.
+pub struct EnvironmentMaps {
+    /// Cube map with [`SPECULAR_MIP_COUNT`] mip levels (or less, if the face size is too small),
+    /// where each next level is prefiltered for higher roughness. The first level is the sky itself.
+    pub specular: Texture,
+    /// Cube map with irradiance (divided by PI) for every direction, it is used for diffuse lighting.
+    pub irradiance: Texture,
+}
+
+/// An image in linear color space.
+pub(crate) struct LinearImage {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<Vector3<f32>>,
+}
+
+impl LinearImage {
+    fn from_texture(texture: &Texture) -> Result<Self, EnvironmentMapError> {
+        let (width, height) = match texture.kind() {
+            TextureKind::Rectangle { width, height } => (width, height),
+            kind => return Err(EnvironmentMapError::UnsupportedTextureKind(kind)),
+        };
+
+        let data = texture.mip_level_data(0);
+        let pixels = match texture.pixel_kind() {
+            TexturePixelKind::RGB32F | TexturePixelKind::RGBA32F => {
+                let stride = texture.pixel_kind().size_in_bytes().unwrap_or(12);
+                data.chunks_exact(stride)
+                    .map(|pixel| {
+                        let channel = |i: usize| {
+                            f32::from_ne_bytes([
+                                pixel[i * 4],
+                                pixel[i * 4 + 1],
+                                pixel[i * 4 + 2],
+                                pixel[i * 4 + 3],
+                            ])
+                        };
+                        Vector3::new(channel(0), channel(1), channel(2))
+                    })
+                    .collect()
+            }
+            // 8-bit images are always in sRGB color space.
+            TexturePixelKind::RGB8 | TexturePixelKind::RGBA8 => {
+                let stride = texture.pixel_kind().size_in_bytes().unwrap_or(3);
+                data.chunks_exact(stride)
+                    .map(|pixel| {
+                        Vector3::new(
+                            srgb_to_linear(pixel[0]),
+                            srgb_to_linear(pixel[1]),
+                            srgb_to_linear(pixel[2]),
+                        )
+                    })
+                    .collect()
+            }
+            pixel_kind => return Err(EnvironmentMapError::UnsupportedPixelKind(pixel_kind)),
+        };
+
+        Ok(Self {
+            width,
+            height,
+            pixels,
+        })
+    }
+
+    fn pixel(&self, x: u32, y: u32) -> Vector3<f32> {
+        self.pixels[(y * self.width + x) as usize]
+    }
+
+    // Bilinear sampling of the image, that wraps horizontally and clamps vertically.
+    fn sample_equirect(&self, direction: &Vector3<f32>) -> Vector3<f32> {
+        let u = 0.5 + direction.z.atan2(direction.x) / (2.0 * PI);
+        let v = direction.y.clamp(-1.0, 1.0).acos() / PI;
+
+        let x = u * self.width as f32 - 0.5;
+        let y = (v * self.height as f32 - 0.5).clamp(0.0, (self.height - 1) as f32);
+        let x0 = x.floor();
+        let y0 = y.floor();
+        let fx = x - x0;
+        let fy = y - y0;
+
+        let wrap = |x: f32| (x as i64).rem_euclid(self.width as i64) as u32;
+        let x0 = wrap(x0);
+        let x1 = wrap(x0 as f32 + 1.0);
+        let y0 = y0 as u32;
+        let y1 = (y0 + 1).min(self.height - 1);
+
+        let top = self.pixel(x0, y0).lerp(&self.pixel(x1, y0), fx);
+        let bottom = self.pixel(x0, y1).lerp(&self.pixel(x1, y1), fx);
+        top.lerp(&bottom, fy)
+    }
+}
+
+fn srgb_to_linear(value: u8) -> f32 {
+    let value = value as f32 / 255.0;
+    if value <= 0.04045 {
+        value / 12.92
+    } else {
+        ((value + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+/// A single level of a cube map, faces are stored in +X, -X, +Y, -Y, +Z, -Z order.
+pub(crate) struct CubeLevel {
+    pub size: u32,
+    pub pixels: Vec<Vector3<f32>>,
+}
+
+impl CubeLevel {
+    fn from_fn<F>(size: u32, mut func: F) -> Self
+    where
+        F: FnMut(Vector3<f32>) -> Vector3<f32>,
+    {
+        let mut pixels = Vec::with_capacity(6 * (size * size) as usize);
+        for face in 0..6 {
+            for y in 0..size {
+                for x in 0..size {
+                    pixels.push(func(texel_direction(size, face, x, y)));
+                }
+            }
+        }
+        Self { size, pixels }
+    }
+
+    pub fn from_equirect(image: &LinearImage, size: u32) -> Self {
+        Self::from_fn(size, |direction| image.sample_equirect(&direction))
+    }
+
+    fn sample(&self, direction: &Vector3<f32>) -> Vector3<f32> {
+        let abs = direction.abs();
+        let (face, sc, tc, ma) = if abs.x >= abs.y && abs.x >= abs.z {
+            if direction.x > 0.0 {
+                (0, -direction.z, -direction.y, abs.x)
+            } else {
+                (1, direction.z, -direction.y, abs.x)
+            }
+        } else if abs.y >= abs.z {
+            if direction.y > 0.0 {
+                (2, direction.x, direction.z, abs.y)
+            } else {
+                (3, direction.x, -direction.z, abs.y)
+            }
+        } else if direction.z > 0.0 {
+            (4, direction.x, -direction.y, abs.z)
+        } else {
+            (5, -direction.x, -direction.y, abs.z)
+        };
+
+        let to_texel = |c: f32| {
+            (((c / ma + 1.0) * 0.5 * self.size as f32) as u32).min(self.size.saturating_sub(1))
+        };
+
+        self.pixels[((face * self.size + to_texel(tc)) * self.size + to_texel(sc)) as usize]
+    }
+
+    fn downsample(&self) -> Self {
+        let size = (self.size / 2).max(1);
+        let mut pixels = Vec::with_capacity(6 * (size * size) as usize);
+        let src = |face: u32, x: u32, y: u32| {
+            let x = x.min(self.size - 1);
+            let y = y.min(self.size - 1);
+            self.pixels[((face * self.size + y) * self.size + x) as usize]
+        };
+        for face in 0..6 {
+            for y in 0..size {
+                for x in 0..size {
+                    pixels.push(
+                        (src(face, 2 * x, 2 * y)
+                            + src(face, 2 * x + 1, 2 * y)
+                            + src(face, 2 * x, 2 * y + 1)
+                            + src(face, 2 * x + 1, 2 * y + 1))
+                            * 0.25,
+                    );
+                }
+            }
+        }
+        Self { size, pixels }
+    }
+
+    /// Convolves the level with GGX lobe of the given roughness, the result has the given size.
+    pub fn prefilter(&self, size: u32, roughness: f32) -> Self {
+        Self::from_fn(size, |normal| {
+            let mut sum = Vector3::default();
+            let mut total_weight = 0.0;
+            for i in 0..PREFILTER_SAMPLE_COUNT {
+                let half = importance_sample_ggx(i, &normal, roughness);
+                let light = half.scale(2.0 * normal.dot(&half)) - normal;
+                let n_dot_l = normal.dot(&light);
+                if n_dot_l > 0.0 {
+                    sum += self.sample(&light).scale(n_dot_l);
+                    total_weight += n_dot_l;
+                }
+            }
+            if total_weight > 0.0 {
+                sum.unscale(total_weight)
+            } else {
+                self.sample(&normal)
+            }
+        })
+    }
+
+    /// Calculates irradiance (divided by PI) for every direction.
+    pub fn irradiance(&self, size: u32) -> Self {
+        let mut source = self.downsample();
+        while source.size > size {
+            source = source.downsample();
+        }
+
+        // Directions and solid angles of every source texel.
+        let texels = (0..6)
+            .flat_map(|face| {
+                (0..source.size).flat_map(move |y| (0..source.size).map(move |x| (face, x, y)))
+            })
+            .zip(source.pixels.iter())
+            .map(|((face, x, y), radiance)| {
+                let direction = texel_direction(source.size, face, x, y);
+                // Unnormalized direction is (1, s, t) in face space.
+                let s = (2.0 * (x as f32 + 0.5)) / source.size as f32 - 1.0;
+                let t = (2.0 * (y as f32 + 0.5)) / source.size as f32 - 1.0;
+                let solid_angle = (1.0 + s * s + t * t).powf(-1.5);
+                (direction, *radiance, solid_angle)
+            })
+            .collect::<Vec<_>>();
+
+        Self::from_fn(size, |normal| {
+            let mut sum = Vector3::default();
+            let mut total_weight = 0.0;
+            for (direction, radiance, solid_angle) in texels.iter() {
+                let cos = normal.dot(direction);
+                if cos > 0.0 {
+                    let weight = cos * solid_angle;
+                    sum += radiance.scale(weight);
+                    total_weight += weight;
+                }
+            }
+            if total_weight > 0.0 {
+                sum.unscale(total_weight)
+            } else {
+                Vector3::default()
+            }
+        })
+    }
+}
+
+// Direction to a center of the given texel, faces are in +X, -X, +Y, -Y, +Z, -Z order.
+fn texel_direction(size: u32, face: u32, x: u32, y: u32) -> Vector3<f32> {
+    let s = (2.0 * (x as f32 + 0.5)) / size as f32 - 1.0;
+    let t = (2.0 * (y as f32 + 0.5)) / size as f32 - 1.0;
+    match face {
+        0 => Vector3::new(1.0, -t, -s),
+        1 => Vector3::new(-1.0, -t, s),
+        2 => Vector3::new(s, 1.0, t),
+        3 => Vector3::new(s, -1.0, -t),
+        4 => Vector3::new(s, -t, 1.0),
+        _ => Vector3::new(-s, -t, -1.0),
+    }
+    .normalize()
+}
+
+fn radical_inverse(mut bits: u32) -> f32 {
+    bits = bits.rotate_right(16);
+    bits = ((bits & 0x55555555) << 1) | ((bits & 0xAAAAAAAA) >> 1);
+    bits = ((bits & 0x33333333) << 2) | ((bits & 0xCCCCCCCC) >> 2);
+    bits = ((bits & 0x0F0F0F0F) << 4) | ((bits & 0xF0F0F0F0) >> 4);
+    bits = ((bits & 0x00FF00FF) << 8) | ((bits & 0xFF00FF00) >> 8);
+    bits as f32 * 2.328_306_4e-10
+}
+
+fn importance_sample_ggx(i: u32, normal: &Vector3<f32>, roughness: f32) -> Vector3<f32> {
+    let a = roughness * roughness;
+    let phi = 2.0 * PI * i as f32 / PREFILTER_SAMPLE_COUNT as f32;
+    let xi = radical_inverse(i);
+    let cos_theta = ((1.0 - xi) / (1.0 + (a * a - 1.0) * xi)).sqrt();
+    let sin_theta = (1.0 - cos_theta * cos_theta).sqrt();
+
+    let up = if normal.z.abs() < 0.999 {
+        Vector3::z()
+    } else {
+        Vector3::x()
+    };
+    let tangent = up.cross(normal).normalize();
+    let bitangent = normal.cross(&tangent);
+
+    (tangent.scale(sin_theta * phi.cos())
+        + bitangent.scale(sin_theta * phi.sin())
+        + normal.scale(cos_theta))
+    .normalize()
+}
+
+fn levels_to_texture(levels: &[CubeLevel]) -> Option<Texture> {
+    let size = levels.first()?.size;
+    let mut bytes = Vec::new();
+    for pixel in levels.iter().flat_map(|level| level.pixels.iter()) {
+        for c in pixel.iter() {
+            bytes.extend(f16::from_f32(c.min(f16::MAX.to_f32())).to_ne_bytes());
+        }
+    }
+    Texture::from_bytes_with_mip_count(
+        TextureKind::Cube {
+            width: size,
+            height: size,
+        },
+        TexturePixelKind::RGB16F,
+        levels.len() as u32,
+        bytes,
+        false,
+    )
+}
+
+/// Returns a cube map face size, that matches the detail level of an equirectangular image of the
+/// given height. The size is a power of two in `[64; 512]` range.
+pub fn equirect_face_size(height: u32) -> u32 {
+    (height / 2).next_power_of_two().clamp(64, 512)
+}
+
+/// Converts an equirectangular texture to a specular cube map with prefiltered mip levels and an
+/// irradiance cube map. The source texture could be either HDR (RGB32F or RGBA32F, this is what
+/// `.hdr` files are loaded as) or 8-bit sRGB. Both resulting textures are in linear color space and
+/// have RGB16F pixel format, so values above 1.0 are kept intact.
+///
+/// # Performance
+///
+/// This is a heavy method, it takes tens of milliseconds for small cube maps and could take up to
+/// a second for large ones.
+pub fn make_environment_maps(
+    source: &Texture,
+    face_size: u32,
+) -> Result<EnvironmentMaps, EnvironmentMapError> {
+    let image = LinearImage::from_texture(source)?;
+    if image.width == 0 || image.height == 0 {
+        return Err(EnvironmentMapError::UnsupportedTextureKind(source.kind()));
+    }
+
+    let face_size = face_size.max(1).next_power_of_two();
+    let mip_count = SPECULAR_MIP_COUNT.min(face_size.trailing_zeros() + 1);
+
+    let mut levels = vec![CubeLevel::from_equirect(&image, face_size)];
+    for mip in 1..mip_count {
+        let roughness = mip as f32 / (SPECULAR_MIP_COUNT - 1) as f32;
+        let level = levels[mip as usize - 1].prefilter(face_size >> mip, roughness);
+        levels.push(level);
+    }
+
+    let irradiance = levels[0].irradiance(IRRADIANCE_FACE_SIZE);
+
+    Ok(EnvironmentMaps {
+        specular: levels_to_texture(&levels)
+            .ok_or(EnvironmentMapError::UnsupportedTextureKind(source.kind()))?,
+        irradiance: levels_to_texture(&[irradiance])
+            .ok_or(EnvironmentMapError::UnsupportedTextureKind(source.kind()))?,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::algebra::Vector3,
+        utils::environment_map::{CubeLevel, LinearImage},
+    };
+
+    fn sky(width: u32, height: u32, upper: f32, lower: f32) -> LinearImage {
+        LinearImage {
+            width,
+            height,
+            pixels: (0..height)
+                .flat_map(|y| {
+                    let value = if y < height / 2 { upper } else { lower };
+                    (0..width).map(move |_| Vector3::repeat(value))
+                })
+                .collect(),
+        }
+    }
+
+    fn assert_all_near(level: &CubeLevel, value: f32) {
+        for pixel in level.pixels.iter() {
+            for c in pixel.iter() {
+                assert!(
+                    (c - value).abs() < 1.0e-3 * value.max(1.0),
+                    "{} != {}",
+                    c,
+                    value
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_bright_sky_keeps_its_intensity() {
+        // Values well above 1.0 must survive every step, so the HDR pass could tonemap them.
+        let image = sky(64, 32, 100.0, 100.0);
+        let level = CubeLevel::from_equirect(&image, 16);
+        assert_all_near(&level, 100.0);
+        assert_all_near(&level.prefilter(8, 0.5), 100.0);
+        assert_all_near(&level.prefilter(4, 1.0), 100.0);
+        assert_all_near(&level.irradiance(4), 100.0);
+    }
+
+    #[test]
+    fn test_half_bright_sky() {
+        let image = sky(64, 32, 50.0, 0.0);
+        let level = CubeLevel::from_equirect(&image, 16);
+
+        let up = level.sample(&Vector3::y());
+        let down = level.sample(&-Vector3::y());
+        assert!((up.x - 50.0).abs() < 1.0e-3);
+        assert!(down.x.abs() < 1.0e-3);
+
+        let irradiance = level.irradiance(8);
+        assert!((irradiance.sample(&Vector3::y()).x - 50.0).abs() < 1.0);
+        assert!(irradiance.sample(&-Vector3::y()).x < 1.0);
+        // Half of a hemisphere around a horizontal normal is bright (normals of texels are slightly
+        // tilted, so the value is not exact).
+        assert!((irradiance.sample(&Vector3::x()).x - 25.0).abs() < 5.0);
+    }
+}
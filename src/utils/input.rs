@@ -0,0 +1,804 @@
+//! Input mapping layer. It allows you to bind named actions and axes to physical inputs (keys,
+//! mouse buttons, mouse axes, gamepad buttons and axes) and query them by name, instead of hard
+//! coding key codes in game logic. See [`InputMap`] docs for more info.
+
+use crate::{
+    core::{
+        algebra::Vector2,
+        visitor::{prelude::*, VisitorFormat},
+    },
+    event::{DeviceEvent, ElementState, Event, MouseButton, MouseScrollDelta, WindowEvent},
+    keyboard::{KeyCode, NativeKeyCode},
+};
+use fxhash::{FxHashMap, FxHashSet};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Binding value at which an action is considered pressed.
+pub const ACTION_THRESHOLD: f32 = 0.5;
+
+/// Mouse axis.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum MouseAxis {
+    /// Horizontal mouse movement (per frame).
+    X,
+    /// Vertical mouse movement (per frame).
+    Y,
+    /// Mouse wheel rotation (per frame).
+    Wheel,
+}
+
+/// Gamepad button. The names are layout-independent, for example [`GamepadButton::South`] is `A`
+/// on Xbox controllers and `Cross` on PlayStation controllers.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum GamepadButton {
+    /// Bottom face button.
+    South,
+    /// Right face button.
+    East,
+    /// Top face button.
+    North,
+    /// Left face button.
+    West,
+    /// Left bumper.
+    LeftBumper,
+    /// Right bumper.
+    RightBumper,
+    /// Left stick press.
+    LeftThumb,
+    /// Right stick press.
+    RightThumb,
+    /// Select (back, share) button.
+    Select,
+    /// Start (menu, options) button.
+    Start,
+    /// Up button of the directional pad.
+    DPadUp,
+    /// Down button of the directional pad.
+    DPadDown,
+    /// Left button of the directional pad.
+    DPadLeft,
+    /// Right button of the directional pad.
+    DPadRight,
+}
+
+/// Gamepad axis. Sticks are in `[-1; 1]` range (positive values are right and up), triggers are in
+/// `[0; 1]` range.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum GamepadAxis {
+    /// Horizontal axis of the left stick.
+    LeftStickX,
+    /// Vertical axis of the left stick.
+    LeftStickY,
+    /// Horizontal axis of the right stick.
+    RightStickX,
+    /// Vertical axis of the right stick.
+    RightStickY,
+    /// Left trigger.
+    LeftTrigger,
+    /// Right trigger.
+    RightTrigger,
+}
+
+/// Physical input that could be bound to an action or an axis.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum InputSource {
+    /// Keyboard key.
+    Key(KeyCode),
+    /// Mouse button.
+    MouseButton(MouseButton),
+    /// Mouse axis.
+    MouseAxis(MouseAxis),
+    /// Gamepad button.
+    GamepadButton(GamepadButton),
+    /// Gamepad axis.
+    GamepadAxis(GamepadAxis),
+}
+
+impl Default for InputSource {
+    fn default() -> Self {
+        Self::Key(KeyCode::Unidentified(NativeKeyCode::Unidentified))
+    }
+}
+
+impl Visit for InputSource {
+    // Sources are stored as short strings (for example `Key(KeyW)`), so the bindings are readable
+    // and editable when a map is saved in text format.
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        let mut region = visitor.enter_region(name)?;
+
+        let mut text = if region.is_reading() {
+            String::new()
+        } else {
+            ron::to_string(self).map_err(|e| VisitError::User(e.to_string()))?
+        };
+        text.visit("Source", &mut region)?;
+
+        if region.is_reading() {
+            *self = ron::from_str(&text).map_err(|e| VisitError::User(e.to_string()))?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Binding of a physical input to an action or an axis.
+#[derive(Copy, Clone, Debug, PartialEq, Visit)]
+pub struct InputBinding {
+    /// Physical input.
+    pub source: InputSource,
+    /// Multiplier for the value of the input.
+    pub scale: f32,
+    /// Whether the value of the input should be negated.
+    pub invert: bool,
+}
+
+impl Default for InputBinding {
+    fn default() -> Self {
+        Self::new(InputSource::default())
+    }
+}
+
+impl InputBinding {
+    /// Creates new binding with scale of `1.0`.
+    pub fn new(source: InputSource) -> Self {
+        Self {
+            source,
+            scale: 1.0,
+            invert: false,
+        }
+    }
+
+    /// Creates new keyboard key binding.
+    pub fn key(key: KeyCode) -> Self {
+        Self::new(InputSource::Key(key))
+    }
+
+    /// Creates new mouse button binding.
+    pub fn mouse_button(button: MouseButton) -> Self {
+        Self::new(InputSource::MouseButton(button))
+    }
+
+    /// Creates new mouse axis binding.
+    pub fn mouse_axis(axis: MouseAxis) -> Self {
+        Self::new(InputSource::MouseAxis(axis))
+    }
+
+    /// Creates new gamepad button binding.
+    pub fn gamepad_button(button: GamepadButton) -> Self {
+        Self::new(InputSource::GamepadButton(button))
+    }
+
+    /// Creates new gamepad axis binding.
+    pub fn gamepad_axis(axis: GamepadAxis) -> Self {
+        Self::new(InputSource::GamepadAxis(axis))
+    }
+
+    /// Sets new scale of the binding.
+    pub fn with_scale(mut self, scale: f32) -> Self {
+        self.scale = scale;
+        self
+    }
+
+    /// Negates the value of the binding.
+    pub fn inverted(mut self) -> Self {
+        self.invert = !self.invert;
+        self
+    }
+
+    /// Returns the value of the binding with scale and inversion applied.
+    pub fn value(&self, state: &InputState, gamepad: u32, dead_zone: f32) -> f32 {
+        let value = state.source_value(&self.source, gamepad, dead_zone) * self.scale;
+        if self.invert {
+            -value
+        } else {
+            value
+        }
+    }
+}
+
+/// Named action, for example `Jump` or `Fire`. An action is either pressed or not.
+#[derive(Clone, Debug, Default, PartialEq, Visit)]
+pub struct InputAction {
+    /// Name of the action.
+    pub name: String,
+    /// Inputs that trigger the action. The action is pressed when the value of any binding
+    /// reaches [`ACTION_THRESHOLD`].
+    pub bindings: Vec<InputBinding>,
+    #[visit(skip)]
+    pressed: bool,
+    #[visit(skip)]
+    was_pressed: bool,
+}
+
+/// Named axis, for example `MoveForward` or `LookX`.
+#[derive(Clone, Debug, Default, PartialEq, Visit)]
+pub struct InputAxis {
+    /// Name of the axis.
+    pub name: String,
+    /// Inputs that contribute to the axis. The value of the axis is the sum of the values of
+    /// every binding.
+    pub bindings: Vec<InputBinding>,
+    #[visit(skip)]
+    value: f32,
+}
+
+/// Physical input that is bound to more than one action or axis.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InputConflict {
+    /// Physical input.
+    pub source: InputSource,
+    /// Name of the first action or axis that uses the input.
+    pub first: String,
+    /// Name of the second action or axis that uses the input.
+    pub second: String,
+}
+
+#[derive(Clone, Debug, Default)]
+struct GamepadState {
+    buttons: FxHashSet<GamepadButton>,
+    axes: FxHashMap<GamepadAxis, f32>,
+}
+
+/// Raw state of input devices. It is shared across every [`InputMap`] (every player). Feed OS
+/// events to the state using [`Self::process_event`] and call [`Self::end_frame`] after every
+/// map was updated.
+///
+/// The engine does not read gamepads by itself, use any gamepad library (for example `gilrs`)
+/// and pass its events to [`Self::set_gamepad_button`] and [`Self::set_gamepad_axis`].
+#[derive(Clone, Debug, Default)]
+pub struct InputState {
+    keys: FxHashSet<KeyCode>,
+    mouse_buttons: FxHashSet<MouseButton>,
+    mouse_delta: Vector2<f32>,
+    wheel_delta: f32,
+    gamepads: FxHashMap<u32, GamepadState>,
+}
+
+impl InputState {
+    /// Creates new empty state.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Updates the state using the given OS event.
+    pub fn process_event<T>(&mut self, event: &Event<T>) {
+        match event {
+            Event::WindowEvent { event, .. } => match event {
+                WindowEvent::KeyboardInput { event, .. } => {
+                    self.set_key(event.physical_key, event.state == ElementState::Pressed)
+                }
+                WindowEvent::MouseInput { state, button, .. } => {
+                    self.set_mouse_button(*button, *state == ElementState::Pressed)
+                }
+                WindowEvent::MouseWheel { delta, .. } => match delta {
+                    MouseScrollDelta::LineDelta(_, y) => self.wheel_delta += *y,
+                    MouseScrollDelta::PixelDelta(pos) => self.wheel_delta += pos.y as f32,
+                },
+                // Release everything, otherwise keys that were released while the window
+                // was out of focus will be stuck.
+                WindowEvent::Focused(false) => {
+                    self.keys.clear();
+                    self.mouse_buttons.clear();
+                }
+                _ => (),
+            },
+            Event::DeviceEvent {
+                event: DeviceEvent::MouseMotion { delta },
+                ..
+            } => {
+                self.mouse_delta.x += delta.0 as f32;
+                self.mouse_delta.y += delta.1 as f32;
+            }
+            _ => (),
+        }
+    }
+
+    /// Resets per-frame values (mouse movement and wheel rotation). Must be called once per frame
+    /// after every map was updated.
+    pub fn end_frame(&mut self) {
+        self.mouse_delta = Vector2::default();
+        self.wheel_delta = 0.0;
+    }
+
+    /// Sets the state of the key.
+    pub fn set_key(&mut self, key: KeyCode, pressed: bool) {
+        if pressed {
+            self.keys.insert(key);
+        } else {
+            self.keys.remove(&key);
+        }
+    }
+
+    /// Returns `true` if the key is pressed.
+    pub fn is_key_pressed(&self, key: KeyCode) -> bool {
+        self.keys.contains(&key)
+    }
+
+    /// Sets the state of the mouse button.
+    pub fn set_mouse_button(&mut self, button: MouseButton, pressed: bool) {
+        if pressed {
+            self.mouse_buttons.insert(button);
+        } else {
+            self.mouse_buttons.remove(&button);
+        }
+    }
+
+    /// Returns `true` if the mouse button is pressed.
+    pub fn is_mouse_button_pressed(&self, button: MouseButton) -> bool {
+        self.mouse_buttons.contains(&button)
+    }
+
+    /// Adds the given delta to mouse movement of the current frame.
+    pub fn add_mouse_motion(&mut self, delta: Vector2<f32>) {
+        self.mouse_delta += delta;
+    }
+
+    /// Returns mouse movement of the current frame.
+    pub fn mouse_delta(&self) -> Vector2<f32> {
+        self.mouse_delta
+    }
+
+    /// Returns mouse wheel rotation of the current frame.
+    pub fn wheel_delta(&self) -> f32 {
+        self.wheel_delta
+    }
+
+    /// Sets the state of the button of the given gamepad.
+    pub fn set_gamepad_button(&mut self, gamepad: u32, button: GamepadButton, pressed: bool) {
+        let buttons = &mut self.gamepads.entry(gamepad).or_default().buttons;
+        if pressed {
+            buttons.insert(button);
+        } else {
+            buttons.remove(&button);
+        }
+    }
+
+    /// Returns `true` if the button of the given gamepad is pressed.
+    pub fn is_gamepad_button_pressed(&self, gamepad: u32, button: GamepadButton) -> bool {
+        self.gamepads
+            .get(&gamepad)
+            .map_or(false, |g| g.buttons.contains(&button))
+    }
+
+    /// Sets the value of the axis of the given gamepad.
+    pub fn set_gamepad_axis(&mut self, gamepad: u32, axis: GamepadAxis, value: f32) {
+        self.gamepads
+            .entry(gamepad)
+            .or_default()
+            .axes
+            .insert(axis, value);
+    }
+
+    /// Returns the value of the axis of the given gamepad.
+    pub fn gamepad_axis(&self, gamepad: u32, axis: GamepadAxis) -> f32 {
+        self.gamepads
+            .get(&gamepad)
+            .and_then(|g| g.axes.get(&axis).cloned())
+            .unwrap_or_default()
+    }
+
+    /// Forgets the state of the given gamepad, should be called when the gamepad is disconnected.
+    pub fn remove_gamepad(&mut self, gamepad: u32) {
+        self.gamepads.remove(&gamepad);
+    }
+
+    /// Returns the value of the input. Buttons and keys are either `0.0` or `1.0`, gamepad axes
+    /// with absolute value below `dead_zone` are `0.0`.
+    pub fn source_value(&self, source: &InputSource, gamepad: u32, dead_zone: f32) -> f32 {
+        let flag = |pressed: bool| if pressed { 1.0 } else { 0.0 };
+        match *source {
+            InputSource::Key(key) => flag(self.is_key_pressed(key)),
+            InputSource::MouseButton(button) => flag(self.is_mouse_button_pressed(button)),
+            InputSource::MouseAxis(MouseAxis::X) => self.mouse_delta.x,
+            InputSource::MouseAxis(MouseAxis::Y) => self.mouse_delta.y,
+            InputSource::MouseAxis(MouseAxis::Wheel) => self.wheel_delta,
+            InputSource::GamepadButton(button) => {
+                flag(self.is_gamepad_button_pressed(gamepad, button))
+            }
+            InputSource::GamepadAxis(axis) => {
+                let value = self.gamepad_axis(gamepad, axis);
+                if value.abs() < dead_zone {
+                    0.0
+                } else {
+                    value
+                }
+            }
+        }
+    }
+}
+
+/// Set of named actions and axes bound to physical inputs. Every player could have its own map
+/// (with its own gamepad), while all maps share the same [`InputState`]. The map could be saved
+/// and loaded, use [`VisitorFormat::Text`] to get a file that players could edit by hand.
+///
+/// ## Example
+///
+/// ```rust
+/// use fyrox::{
+///     keyboard::KeyCode,
+///     utils::input::{GamepadAxis, InputBinding, InputMap, InputState},
+/// };
+///
+/// let mut map = InputMap::new();
+/// map.add_action("Jump", vec![InputBinding::key(KeyCode::Space)]);
+/// map.add_axis(
+///     "MoveX",
+///     vec![
+///         InputBinding::key(KeyCode::KeyD),
+///         InputBinding::key(KeyCode::KeyA).inverted(),
+///         InputBinding::gamepad_axis(GamepadAxis::LeftStickX),
+///     ],
+/// );
+///
+/// let mut state = InputState::new();
+/// // Normally the state is updated by `InputState::process_event`.
+/// state.set_key(KeyCode::Space, true);
+/// state.set_key(KeyCode::KeyA, true);
+///
+/// // Once per frame.
+/// map.update(&state);
+/// state.end_frame();
+///
+/// assert!(map.is_action_just_pressed("Jump"));
+/// assert_eq!(map.axis_value("MoveX"), -1.0);
+/// ```
+#[derive(Clone, Debug, PartialEq, Visit)]
+pub struct InputMap {
+    actions: Vec<InputAction>,
+    axes: Vec<InputAxis>,
+    gamepad: u32,
+    dead_zone: f32,
+}
+
+impl Default for InputMap {
+    fn default() -> Self {
+        Self {
+            actions: Default::default(),
+            axes: Default::default(),
+            gamepad: 0,
+            dead_zone: 0.15,
+        }
+    }
+}
+
+impl InputMap {
+    /// Creates new empty map that reads the first gamepad.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets index of the gamepad the map reads from.
+    pub fn set_gamepad(&mut self, gamepad: u32) {
+        self.gamepad = gamepad;
+    }
+
+    /// Returns index of the gamepad the map reads from.
+    pub fn gamepad(&self) -> u32 {
+        self.gamepad
+    }
+
+    /// Sets dead zone of gamepad axes. Axis values with absolute value below the dead zone are
+    /// treated as zero.
+    pub fn set_dead_zone(&mut self, dead_zone: f32) {
+        self.dead_zone = dead_zone.max(0.0);
+    }
+
+    /// Returns dead zone of gamepad axes.
+    pub fn dead_zone(&self) -> f32 {
+        self.dead_zone
+    }
+
+    /// Adds new action or replaces bindings of existing one.
+    pub fn add_action<S: AsRef<str>>(&mut self, name: S, bindings: Vec<InputBinding>) {
+        match self.action_mut(name.as_ref()) {
+            Some(action) => action.bindings = bindings,
+            None => self.actions.push(InputAction {
+                name: name.as_ref().to_owned(),
+                bindings,
+                ..Default::default()
+            }),
+        }
+    }
+
+    /// Removes the action and returns it.
+    pub fn remove_action(&mut self, name: &str) -> Option<InputAction> {
+        let index = self.actions.iter().position(|a| a.name == name)?;
+        Some(self.actions.remove(index))
+    }
+
+    /// Returns a reference to the action with the given name.
+    pub fn action(&self, name: &str) -> Option<&InputAction> {
+        self.actions.iter().find(|a| a.name == name)
+    }
+
+    /// Returns a reference to the action with the given name.
+    pub fn action_mut(&mut self, name: &str) -> Option<&mut InputAction> {
+        self.actions.iter_mut().find(|a| a.name == name)
+    }
+
+    /// Returns a slice with every action of the map.
+    pub fn actions(&self) -> &[InputAction] {
+        &self.actions
+    }
+
+    /// Adds new axis or replaces bindings of existing one.
+    pub fn add_axis<S: AsRef<str>>(&mut self, name: S, bindings: Vec<InputBinding>) {
+        match self.axis_mut(name.as_ref()) {
+            Some(axis) => axis.bindings = bindings,
+            None => self.axes.push(InputAxis {
+                name: name.as_ref().to_owned(),
+                bindings,
+                ..Default::default()
+            }),
+        }
+    }
+
+    /// Removes the axis and returns it.
+    pub fn remove_axis(&mut self, name: &str) -> Option<InputAxis> {
+        let index = self.axes.iter().position(|a| a.name == name)?;
+        Some(self.axes.remove(index))
+    }
+
+    /// Returns a reference to the axis with the given name.
+    pub fn axis(&self, name: &str) -> Option<&InputAxis> {
+        self.axes.iter().find(|a| a.name == name)
+    }
+
+    /// Returns a reference to the axis with the given name.
+    pub fn axis_mut(&mut self, name: &str) -> Option<&mut InputAxis> {
+        self.axes.iter_mut().find(|a| a.name == name)
+    }
+
+    /// Returns a slice with every axis of the map.
+    pub fn axes(&self) -> &[InputAxis] {
+        &self.axes
+    }
+
+    /// Replaces `old` input with `new` input in every binding of the action or the axis with the
+    /// given name. Scale and inversion of the bindings are kept. Returns `false` if there is no
+    /// such action or axis, or it has no bindings with `old` input.
+    ///
+    /// Use [`Self::users_of`] to check whether `new` input is already used by something else.
+    pub fn rebind(&mut self, name: &str, old: InputSource, new: InputSource) -> bool {
+        let mut rebound = false;
+        let bindings = self
+            .actions
+            .iter_mut()
+            .filter(|a| a.name == name)
+            .flat_map(|a| a.bindings.iter_mut())
+            .chain(
+                self.axes
+                    .iter_mut()
+                    .filter(|a| a.name == name)
+                    .flat_map(|a| a.bindings.iter_mut()),
+            );
+        for binding in bindings {
+            if binding.source == old {
+                binding.source = new;
+                rebound = true;
+            }
+        }
+        rebound
+    }
+
+    /// Returns names of every action and axis that use the given input.
+    pub fn users_of(&self, source: InputSource) -> Vec<&str> {
+        self.named_bindings()
+            .filter(|(_, binding)| binding.source == source)
+            .map(|(name, _)| name)
+            .collect()
+    }
+
+    /// Returns every input that is bound to more than one action or axis. Some conflicts could be
+    /// intentional, for example the same key could open a door and pick up an item.
+    pub fn conflicts(&self) -> Vec<InputConflict> {
+        let mut users = FxHashMap::<InputSource, &str>::default();
+        let mut conflicts = Vec::new();
+        for (name, binding) in self.named_bindings() {
+            match users.get(&binding.source) {
+                Some(first) if *first != name => conflicts.push(InputConflict {
+                    source: binding.source,
+                    first: first.to_string(),
+                    second: name.to_owned(),
+                }),
+                Some(_) => (),
+                None => {
+                    users.insert(binding.source, name);
+                }
+            }
+        }
+        conflicts
+    }
+
+    fn named_bindings(&self) -> impl Iterator<Item = (&str, &InputBinding)> {
+        self.actions
+            .iter()
+            .flat_map(|a| a.bindings.iter().map(move |b| (a.name.as_str(), b)))
+            .chain(
+                self.axes
+                    .iter()
+                    .flat_map(|a| a.bindings.iter().map(move |b| (a.name.as_str(), b))),
+            )
+    }
+
+    /// Evaluates every action and axis using the given state. Must be called once per frame.
+    pub fn update(&mut self, state: &InputState) {
+        for action in self.actions.iter_mut() {
+            action.was_pressed = action.pressed;
+            action.pressed = action
+                .bindings
+                .iter()
+                .any(|b| b.value(state, self.gamepad, self.dead_zone) >= ACTION_THRESHOLD);
+        }
+
+        for axis in self.axes.iter_mut() {
+            axis.value = axis
+                .bindings
+                .iter()
+                .map(|b| b.value(state, self.gamepad, self.dead_zone))
+                .sum();
+        }
+    }
+
+    /// Returns `true` if the action is pressed.
+    pub fn is_action_pressed(&self, name: &str) -> bool {
+        self.action(name).map_or(false, |a| a.pressed)
+    }
+
+    /// Returns `true` if the action was pressed during the last frame.
+    pub fn is_action_just_pressed(&self, name: &str) -> bool {
+        self.action(name)
+            .map_or(false, |a| a.pressed && !a.was_pressed)
+    }
+
+    /// Returns `true` if the action was released during the last frame.
+    pub fn is_action_just_released(&self, name: &str) -> bool {
+        self.action(name)
+            .map_or(false, |a| !a.pressed && a.was_pressed)
+    }
+
+    /// Returns the value of the axis, or zero if there is no such axis. Keyboard and gamepad
+    /// bindings of the same axis could add up, clamp the value if this is unwanted.
+    pub fn axis_value(&self, name: &str) -> f32 {
+        self.axis(name).map_or(0.0, |a| a.value)
+    }
+
+    /// Saves the map to a file in the given format.
+    pub fn save<P: AsRef<Path>>(&mut self, path: P, format: VisitorFormat) -> VisitResult {
+        let mut visitor = Visitor::new();
+        self.visit("InputMap", &mut visitor)?;
+        visitor.save_to_file(path, format)
+    }
+
+    /// Loads the map from a file, the format is detected automatically.
+    pub async fn load<P: AsRef<Path>>(path: P) -> Result<Self, VisitError> {
+        let mut visitor = Visitor::load_binary(path).await?;
+        let mut map = Self::default();
+        map.visit("InputMap", &mut visitor)?;
+        Ok(map)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::visitor::prelude::*,
+        event::MouseButton,
+        keyboard::KeyCode,
+        utils::input::{
+            GamepadAxis, GamepadButton, InputBinding, InputMap, InputSource, InputState, MouseAxis,
+        },
+    };
+
+    fn player_map() -> InputMap {
+        let mut map = InputMap::new();
+        map.add_action(
+            "Jump",
+            vec![
+                InputBinding::key(KeyCode::Space),
+                InputBinding::gamepad_button(GamepadButton::South),
+            ],
+        );
+        map.add_action("Fire", vec![InputBinding::mouse_button(MouseButton::Left)]);
+        map.add_axis(
+            "MoveX",
+            vec![
+                InputBinding::key(KeyCode::KeyD),
+                InputBinding::key(KeyCode::KeyA).inverted(),
+                InputBinding::gamepad_axis(GamepadAxis::LeftStickX),
+            ],
+        );
+        map.add_axis(
+            "LookX",
+            vec![InputBinding::mouse_axis(MouseAxis::X).with_scale(0.5)],
+        );
+        map
+    }
+
+    #[test]
+    fn test_actions_and_axes() {
+        let mut map = player_map();
+        let mut state = InputState::new();
+
+        state.set_key(KeyCode::Space, true);
+        state.set_key(KeyCode::KeyD, true);
+        state.add_mouse_motion(crate::core::algebra::Vector2::new(10.0, 0.0));
+        map.update(&state);
+        state.end_frame();
+        assert!(map.is_action_pressed("Jump"));
+        assert!(map.is_action_just_pressed("Jump"));
+        assert!(!map.is_action_pressed("Fire"));
+        assert_eq!(map.axis_value("MoveX"), 1.0);
+        assert_eq!(map.axis_value("LookX"), 5.0);
+
+        map.update(&state);
+        assert!(map.is_action_pressed("Jump"));
+        assert!(!map.is_action_just_pressed("Jump"));
+        assert_eq!(map.axis_value("LookX"), 0.0);
+
+        state.set_key(KeyCode::Space, false);
+        state.set_key(KeyCode::KeyD, false);
+        state.set_gamepad_axis(0, GamepadAxis::LeftStickX, -0.75);
+        state.set_gamepad_button(1, GamepadButton::South, true);
+        map.update(&state);
+        assert!(map.is_action_just_released("Jump"));
+        assert_eq!(map.axis_value("MoveX"), -0.75);
+
+        // Second player reads the second gamepad.
+        let mut other = player_map();
+        other.set_gamepad(1);
+        other.update(&state);
+        assert!(other.is_action_pressed("Jump"));
+        assert_eq!(other.axis_value("MoveX"), 0.0);
+
+        // Dead zone.
+        state.set_gamepad_axis(0, GamepadAxis::LeftStickX, 0.1);
+        map.update(&state);
+        assert_eq!(map.axis_value("MoveX"), 0.0);
+        assert_eq!(map.axis_value("Unknown"), 0.0);
+    }
+
+    #[test]
+    fn test_rebind_and_conflicts() {
+        let mut map = player_map();
+        assert!(map.conflicts().is_empty());
+
+        assert!(map.rebind(
+            "Jump",
+            InputSource::Key(KeyCode::Space),
+            InputSource::Key(KeyCode::KeyD)
+        ));
+        assert!(!map.rebind(
+            "Jump",
+            InputSource::Key(KeyCode::Space),
+            InputSource::Key(KeyCode::KeyD)
+        ));
+        assert_eq!(
+            map.users_of(InputSource::Key(KeyCode::KeyD)),
+            vec!["Jump", "MoveX"]
+        );
+
+        let conflicts = map.conflicts();
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].source, InputSource::Key(KeyCode::KeyD));
+        assert_eq!(conflicts[0].first, "Jump");
+        assert_eq!(conflicts[0].second, "MoveX");
+    }
+
+    #[test]
+    fn test_input_map_serialization() {
+        let mut map = player_map();
+        map.set_gamepad(2);
+
+        let mut visitor = Visitor::new();
+        map.visit("InputMap", &mut visitor).unwrap();
+        let text = visitor.save_text_format();
+        assert!(text.contains("Key(KeyA)"));
+
+        let mut visitor = Visitor::load_text_format(&text).unwrap();
+        let mut loaded = InputMap::default();
+        loaded.visit("InputMap", &mut visitor).unwrap();
+        assert_eq!(loaded, map);
+    }
+}
@@ -12,7 +12,7 @@
 use crate::{
     asset::{
         manager::{ResourceManager, ResourceRegistrationError},
-        ResourceData,
+        ResourceData, ResourceStateRef,
     },
     core::{
         algebra::{Matrix3, Matrix4, Point3, Vector2, Vector3, Vector4},
@@ -24,19 +24,22 @@ use crate::{
         sstorage::ImmutableString,
         visitor::prelude::*,
     },
-    material::PropertyValue,
+    material::{Material, PropertyValue},
     resource::texture::{Texture, TextureKind, TexturePixelKind, TextureResource},
     scene::{
         light::{directional::DirectionalLight, point::PointLight, spot::SpotLight},
         mesh::{
             buffer::{VertexAttributeUsage, VertexFetchError, VertexReadTrait},
-            surface::SurfaceSharedData,
+            surface::{SurfaceData, SurfaceSharedData},
             Mesh,
         },
         node::Node,
         Scene,
     },
-    utils::{uvgen, uvgen::SurfaceDataPatch},
+    utils::{
+        uvgen,
+        uvgen::{SurfaceDataPatch, UvGenerationError},
+    },
 };
 use fxhash::FxHashMap;
 use rayon::prelude::*;
@@ -80,6 +83,7 @@ pub struct Lightmap {
 struct WorldVertex {
     world_normal: Vector3<f32>,
     world_position: Vector3<f32>,
+    tex_coord: Vector2<f32>,
     second_tex_coord: Vector2<f32>,
 }
 
@@ -95,6 +99,8 @@ struct Instance {
     source_data: SurfaceSharedData,
     data: Option<InstanceData>,
     transform: Matrix4<f32>,
+    /// Fraction of light, that is reflected by the surface. Used for indirect lighting.
+    albedo: SurfaceAlbedo,
 }
 
 impl Instance {
@@ -136,6 +142,10 @@ pub enum ProgressStage {
     GeometryCaching = 2,
     /// Actual lightmap generation.
     CalculatingLight = 3,
+    /// Calculating indirect light bounces.
+    CalculatingIndirectLight = 4,
+    /// Calculating ambient occlusion.
+    CalculatingAmbientOcclusion = 5,
 }
 
 /// Progress internals.
@@ -165,6 +175,8 @@ impl ProgressData {
             1 => ProgressStage::UvGeneration,
             2 => ProgressStage::GeometryCaching,
             3 => ProgressStage::CalculatingLight,
+            4 => ProgressStage::CalculatingIndirectLight,
+            5 => ProgressStage::CalculatingAmbientOcclusion,
             _ => unreachable!(),
         }
     }
@@ -230,19 +242,123 @@ impl From<VertexFetchError> for LightmapGenerationError {
     }
 }
 
+impl From<UvGenerationError> for LightmapGenerationError {
+    fn from(e: UvGenerationError) -> Self {
+        match e {
+            UvGenerationError::Cancelled => Self::Cancelled,
+            UvGenerationError::InvalidData(e) => Self::InvalidData(e),
+        }
+    }
+}
+
+/// Quality presets of lightmap generation, see [`LightmapSettings::from_quality`].
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum LightmapQuality {
+    /// Low resolution direct lighting only, suitable for quick previews.
+    Draft,
+    /// One indirect bounce and ambient occlusion with moderate amount of rays.
+    #[default]
+    Medium,
+    /// High resolution, two indirect bounces and ambient occlusion with lots of rays. It is
+    /// much slower than other presets.
+    High,
+}
+
+/// Settings of lightmap generation.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LightmapSettings {
+    /// Resolution of lightmaps - amount of texels per unit of length. The higher value is, the
+    /// more quality lightmap will be generated, but also it will be slow to generate.
+    pub texels_per_unit: u32,
+    /// Relative spacing between UV elements generated by the built-in UV mapper, it prevents
+    /// seams when lightmaps are rendered with bilinear filtration.
+    pub uv_spacing: f32,
+    /// Defines how much emissive surfaces contribute to the lighting, every triangle of a surface
+    /// with emissive material is treated as an area light source. Zero disables emissive lighting.
+    pub emission_scale: f32,
+    /// Defines how many times light is reflected by surfaces, zero gives direct lighting only.
+    pub bounces: u32,
+    /// Defines how much bounced light contributes to the lighting.
+    pub indirect_scale: f32,
+    /// Amount of rays per texel, that are used to gather bounced light. The more rays, the less
+    /// noise, the cost of every bounce is proportional to it.
+    pub indirect_ray_count: u32,
+    /// Enables ambient occlusion - darkening of creases, corners and places near other objects.
+    pub ambient_occlusion: bool,
+    /// Amount of rays per texel, that are used to calculate ambient occlusion.
+    pub ao_ray_count: u32,
+    /// Maximum distance at which geometry occludes a texel.
+    pub ao_max_distance: f32,
+    /// Defines how much ambient occlusion darkens the lighting, in `[0; 1]` range.
+    pub ao_strength: f32,
+}
+
+impl Default for LightmapSettings {
+    fn default() -> Self {
+        Self::from_quality(LightmapQuality::default())
+    }
+}
+
+impl LightmapSettings {
+    /// Creates settings of the given quality preset.
+    pub fn from_quality(quality: LightmapQuality) -> Self {
+        match quality {
+            LightmapQuality::Draft => Self {
+                texels_per_unit: 16,
+                uv_spacing: 0.005,
+                emission_scale: 1.0,
+                bounces: 0,
+                indirect_scale: 1.0,
+                indirect_ray_count: 16,
+                ambient_occlusion: false,
+                ao_ray_count: 16,
+                ao_max_distance: 1.0,
+                ao_strength: 1.0,
+            },
+            LightmapQuality::Medium => Self {
+                texels_per_unit: 32,
+                uv_spacing: 0.005,
+                emission_scale: 1.0,
+                bounces: 1,
+                indirect_scale: 1.0,
+                indirect_ray_count: 64,
+                ambient_occlusion: true,
+                ao_ray_count: 32,
+                ao_max_distance: 1.0,
+                ao_strength: 1.0,
+            },
+            LightmapQuality::High => Self {
+                texels_per_unit: 64,
+                uv_spacing: 0.005,
+                emission_scale: 1.0,
+                bounces: 2,
+                indirect_scale: 1.0,
+                indirect_ray_count: 256,
+                ambient_occlusion: true,
+                ao_ray_count: 128,
+                ao_max_distance: 1.0,
+                ao_strength: 1.0,
+            },
+        }
+    }
+}
+
 impl Lightmap {
     /// Generates lightmap for given scene. This method **automatically** generates secondary
     /// texture coordinates! This method is blocking, however internally it uses massive parallelism
     /// to use all available CPU power efficiently.
     ///
-    /// `texels_per_unit` defines resolution of lightmap, the higher value is, the more quality
-    /// lightmap will be generated, but also it will be slow to generate.
+    /// `settings` defines resolution and quality of lightmaps, see [`LightmapSettings`] docs.
+    /// Every bounce of indirect light traces `indirect_ray_count` rays per texel and takes
+    /// irradiance of the points hit by the rays from the previous bounce, multiplied by albedo of
+    /// the surface at the points. One or two bounces are enough in most cases. Ambient occlusion
+    /// traces `ao_ray_count` rays per texel and darkens the lighting by the fraction of rays, that
+    /// hit geometry closer than `ao_max_distance`.
     /// `progress_indicator` allows you to get info about current progress.
     /// `cancellation_token` allows you to stop generation in any time.
     pub fn new<F>(
         scene: &mut Scene,
-        texels_per_unit: u32,
-        uv_spacing: f32,
+        settings: &LightmapSettings,
         mut filter: F,
         cancellation_token: CancellationToken,
         progress_indicator: ProgressIndicator,
@@ -328,8 +444,37 @@ impl Lightmap {
             progress_indicator.advance_progress()
         }
 
+        let mut emitters = Vec::new();
+        if settings.emission_scale > 0.0 {
+            for (handle, node) in scene.graph.pair_iter() {
+                if !filter(handle, node) {
+                    continue;
+                }
+
+                if let Some(mesh) = node.cast::<Mesh>() {
+                    if !mesh.global_visibility() || !mesh.is_globally_enabled() {
+                        continue;
+                    }
+
+                    for surface in mesh.surfaces() {
+                        let radiance = surface_emission(&surface.material().lock())
+                            .scale(settings.emission_scale);
+                        if radiance.max() > 0.0 {
+                            gather_emissive_triangles(
+                                &surface.data().lock(),
+                                &mesh.global_transform(),
+                                radiance,
+                                &mut emitters,
+                            );
+                        }
+                    }
+                }
+            }
+        }
+
         let mut instances = Vec::new();
         let mut data_set = FxHashMap::default();
+        let mut albedo_textures = FxHashMap::default();
 
         'node_loop: for (handle, node) in scene.graph.pair_iter() {
             if !filter(handle, node) {
@@ -364,6 +509,7 @@ impl Lightmap {
                         transform: global_transform,
                         // Calculated down below.
                         data: None,
+                        albedo: SurfaceAlbedo::new(&material, &mut albedo_textures),
                     });
                 }
             }
@@ -378,7 +524,12 @@ impl Lightmap {
                     Err(LightmapGenerationError::Cancelled)
                 } else {
                     let mut data = data.lock();
-                    let patch = uvgen::generate_uvs(&mut data, uv_spacing)?;
+                    let patch = uvgen::generate_uvs_with_progress(
+                        &mut data,
+                        settings.uv_spacing,
+                        |_| {},
+                        &cancellation_token.0,
+                    )?;
                     progress_indicator.advance_progress();
                     Ok((patch.data_id, patch))
                 }
@@ -419,6 +570,9 @@ impl Lightmap {
                             WorldVertex {
                                 world_normal,
                                 world_position,
+                                tex_coord: view
+                                    .read_2_f32(VertexAttributeUsage::TexCoord0)
+                                    .unwrap_or_default(),
                                 second_tex_coord: view
                                     .read_2_f32(VertexAttributeUsage::TexCoord1)
                                     .unwrap(),
@@ -453,21 +607,111 @@ impl Lightmap {
 
         progress_indicator.set_stage(ProgressStage::CalculatingLight, instances.len() as u32);
 
-        let mut map: FxHashMap<Handle<Node>, Vec<LightmapEntry>> = FxHashMap::default();
+        let mut atlases = Vec::with_capacity(instances.len());
         for instance in instances.iter() {
             if cancellation_token.is_cancelled() {
                 return Err(LightmapGenerationError::Cancelled);
             }
 
-            let lightmap = generate_lightmap(instance, &instances, &lights, texels_per_unit);
-            map.entry(instance.owner).or_default().push(LightmapEntry {
-                texture: Some(TextureResource::new_ok(lightmap)),
-                lights: lights.iter().map(|light| light.handle()).collect(),
+            let atlas = TexelAtlas::new(instance.data(), settings.texels_per_unit);
+            let direct = atlas.illuminate(|texel| {
+                direct_lighting(texel, &lights, &instances)
+                    + emitters_lighting(texel, &emitters, &instances)
             });
+            atlases.push((atlas, direct));
 
             progress_indicator.advance_progress();
         }
 
+        let mut lighting = atlases
+            .iter()
+            .map(|(_, direct)| direct.clone())
+            .collect::<Vec<_>>();
+
+        if settings.bounces > 0 && settings.indirect_scale > 0.0 && settings.indirect_ray_count > 0
+        {
+            progress_indicator.set_stage(
+                ProgressStage::CalculatingIndirectLight,
+                settings.bounces * instances.len() as u32,
+            );
+
+            // Rays must be able to reach any point of the scene.
+            let max_distance = scene_extent(&instances);
+
+            // Irradiance of every texel from the previous bounce.
+            let mut previous = atlases
+                .iter()
+                .map(|(_, direct)| direct.clone())
+                .collect::<Vec<_>>();
+
+            for _ in 0..settings.bounces {
+                let mut next = Vec::with_capacity(instances.len());
+                for ((atlas, _), instance_lighting) in atlases.iter().zip(lighting.iter_mut()) {
+                    if cancellation_token.is_cancelled() {
+                        return Err(LightmapGenerationError::Cancelled);
+                    }
+
+                    let bounce = atlas.illuminate(|texel| {
+                        indirect_lighting(
+                            texel,
+                            settings.indirect_ray_count,
+                            max_distance,
+                            &instances,
+                            &atlases,
+                            &previous,
+                        )
+                    });
+                    for (total, bounced) in instance_lighting.iter_mut().zip(bounce.iter()) {
+                        *total += bounced.scale(settings.indirect_scale);
+                    }
+                    next.push(bounce);
+
+                    progress_indicator.advance_progress();
+                }
+                previous = next;
+            }
+        }
+
+        if settings.ambient_occlusion && settings.ao_ray_count > 0 && settings.ao_strength > 0.0 {
+            progress_indicator.set_stage(
+                ProgressStage::CalculatingAmbientOcclusion,
+                instances.len() as u32,
+            );
+
+            for ((atlas, _), instance_lighting) in atlases.iter().zip(lighting.iter_mut()) {
+                if cancellation_token.is_cancelled() {
+                    return Err(LightmapGenerationError::Cancelled);
+                }
+
+                let occlusion = atlas.illuminate(|texel| {
+                    Vector3::repeat(ambient_occlusion(
+                        texel,
+                        settings.ao_ray_count,
+                        settings.ao_max_distance,
+                        settings.ao_strength,
+                        &instances,
+                    ))
+                });
+                for (total, occlusion) in instance_lighting.iter_mut().zip(occlusion.iter()) {
+                    total.component_mul_assign(occlusion);
+                }
+
+                progress_indicator.advance_progress();
+            }
+        }
+
+        let mut map: FxHashMap<Handle<Node>, Vec<LightmapEntry>> = FxHashMap::default();
+        for ((instance, (atlas, _)), instance_lighting) in
+            instances.iter().zip(atlases.iter()).zip(lighting.iter())
+        {
+            map.entry(instance.owner).or_default().push(LightmapEntry {
+                texture: Some(TextureResource::new_ok(
+                    atlas.make_texture(instance_lighting),
+                )),
+                lights: lights.iter().map(|light| light.handle()).collect(),
+            });
+        }
+
         Ok(Self { map, patches })
     }
 
@@ -574,6 +818,506 @@ impl LightDefinition {
     }
 }
 
+/// A triangle of a surface with emissive material, it is used as an area light source.
+struct EmissiveTriangle {
+    center: Vector3<f32>,
+    normal: Vector3<f32>,
+    area: f32,
+    radiance: Vector3<f32>,
+}
+
+/// Calculates emitted radiance of a material the same way as the standard shader does it - by
+/// multiplying emission strength by the average color of the emission texture. Returns zero
+/// if the material has no emission texture.
+fn surface_emission(material: &Material) -> Vector3<f32> {
+    let strength = match material
+        .properties()
+        .get(&ImmutableString::new("emissionStrength"))
+    {
+        Some(PropertyValue::Vector3(strength)) => *strength,
+        _ => return Vector3::default(),
+    };
+
+    let texture = match material
+        .properties()
+        .get(&ImmutableString::new("emissionTexture"))
+    {
+        Some(PropertyValue::Sampler {
+            value: Some(texture),
+            ..
+        }) => texture,
+        _ => return Vector3::default(),
+    };
+
+    let state = texture.state();
+    if let ResourceStateRef::Ok(texture) = state.get() {
+        strength.component_mul(&average_color(texture))
+    } else {
+        Vector3::default()
+    }
+}
+
+/// Fraction of light, that is reflected by a surface. It is calculated the same way as the standard
+/// shader does it - by multiplying diffuse color by the color of the diffuse texture.
+struct SurfaceAlbedo {
+    color: Vector3<f32>,
+    /// `None` if the material has no diffuse texture or the texture cannot be sampled, in the
+    /// latter case the average color of the texture is baked into `color`.
+    texture: Option<Arc<AlbedoTexture>>,
+}
+
+impl SurfaceAlbedo {
+    /// Fetches albedo of the material, `textures` is used to share pixels of diffuse textures
+    /// between materials.
+    fn new(material: &Material, textures: &mut FxHashMap<usize, Arc<AlbedoTexture>>) -> Self {
+        let mut color = match material
+            .properties()
+            .get(&ImmutableString::new("diffuseColor"))
+        {
+            Some(PropertyValue::Color(color)) => color.srgb_to_linear().as_frgb(),
+            _ => Vector3::repeat(1.0),
+        };
+
+        let texture = match material
+            .properties()
+            .get(&ImmutableString::new("diffuseTexture"))
+        {
+            Some(PropertyValue::Sampler {
+                value: Some(texture),
+                ..
+            }) => {
+                if let Some(albedo_texture) = textures.get(&texture.key()) {
+                    Some(albedo_texture.clone())
+                } else {
+                    let state = texture.state();
+                    if let ResourceStateRef::Ok(data) = state.get() {
+                        let albedo_texture = AlbedoTexture::new(data).map(Arc::new);
+                        if let Some(albedo_texture) = albedo_texture.as_ref() {
+                            textures.insert(texture.key(), albedo_texture.clone());
+                        } else {
+                            color = color.component_mul(&average_color(data));
+                        }
+                        albedo_texture
+                    } else {
+                        None
+                    }
+                }
+            }
+            _ => None,
+        };
+
+        Self { color, texture }
+    }
+
+    /// Returns albedo at the given texture coordinates.
+    fn sample(&self, tex_coord: Vector2<f32>) -> Vector3<f32> {
+        match self.texture.as_ref() {
+            Some(texture) => self.color.component_mul(&texture.sample(tex_coord)),
+            None => self.color,
+        }
+    }
+}
+
+/// Pixels of the first mip level of a diffuse texture in linear color space.
+struct AlbedoTexture {
+    width: u32,
+    height: u32,
+    pixels: Vec<Vector3<f32>>,
+}
+
+impl AlbedoTexture {
+    /// Copies pixels of the texture. Returns `None` for textures, that are not rectangular or have
+    /// pixels of the formats that cannot be read easily (compressed, high precision).
+    fn new(texture: &Texture) -> Option<Self> {
+        let (width, height) = match texture.kind() {
+            TextureKind::Rectangle { width, height } => (width, height),
+            _ => return None,
+        };
+
+        let (pixel_size, channels) = pixel_layout(texture.pixel_kind())?;
+
+        let pixel_count = (width * height) as usize;
+        if width == 0 || height == 0 || texture.data().len() < pixel_count * pixel_size {
+            return None;
+        }
+
+        let mut to_linear = [0.0; 256];
+        for (i, value) in to_linear.iter_mut().enumerate() {
+            *value = (i as f32 / 255.0).powf(2.2);
+        }
+
+        let pixels = texture
+            .data()
+            .chunks_exact(pixel_size)
+            .take(pixel_count)
+            .map(|pixel| {
+                let [r, g, b] = channels(pixel);
+                Vector3::new(
+                    to_linear[r as usize],
+                    to_linear[g as usize],
+                    to_linear[b as usize],
+                )
+            })
+            .collect();
+
+        Some(Self {
+            width,
+            height,
+            pixels,
+        })
+    }
+
+    /// Fetches the nearest pixel at the given texture coordinates, the texture is repeated.
+    fn sample(&self, tex_coord: Vector2<f32>) -> Vector3<f32> {
+        let x = (tex_coord.x.rem_euclid(1.0) * self.width as f32) as u32;
+        let y = (tex_coord.y.rem_euclid(1.0) * self.height as f32) as u32;
+        self.pixels[(y.min(self.height - 1) * self.width + x.min(self.width - 1)) as usize]
+    }
+}
+
+/// A function that reads red, green and blue channels of a pixel.
+type ChannelsReader = fn(&[u8]) -> [u8; 3];
+
+/// Returns size of a pixel (in bytes) and a function that reads its color channels. `None` for the
+/// formats that cannot be read easily (compressed, high precision).
+fn pixel_layout(pixel_kind: TexturePixelKind) -> Option<(usize, ChannelsReader)> {
+    let layout: (usize, ChannelsReader) = match pixel_kind {
+        TexturePixelKind::R8 | TexturePixelKind::Luminance8 => (1, |p| [p[0], p[0], p[0]]),
+        TexturePixelKind::RGB8 => (3, |p| [p[0], p[1], p[2]]),
+        TexturePixelKind::RGBA8 => (4, |p| [p[0], p[1], p[2]]),
+        TexturePixelKind::BGR8 => (3, |p| [p[2], p[1], p[0]]),
+        TexturePixelKind::BGRA8 => (4, |p| [p[2], p[1], p[0]]),
+        _ => return None,
+    };
+    Some(layout)
+}
+
+/// Calculates average color of the first mip level of the texture. Pixels of the formats that
+/// cannot be read easily (compressed, high precision) are treated as white.
+fn average_color(texture: &Texture) -> Vector3<f32> {
+    let pixel_count = match texture.kind() {
+        TextureKind::Line { length } => length,
+        TextureKind::Rectangle { width, height } => width * height,
+        TextureKind::Cube { width, height } => width * height,
+        TextureKind::Volume {
+            width,
+            height,
+            depth,
+        } => width * height * depth,
+    } as usize;
+
+    let (pixel_size, channels) = match pixel_layout(texture.pixel_kind()) {
+        Some(layout) => layout,
+        None => return Vector3::repeat(1.0),
+    };
+
+    let mut sum = Vector3::<f32>::default();
+    let mut count = 0;
+    for pixel in texture.data().chunks_exact(pixel_size).take(pixel_count) {
+        let [r, g, b] = channels(pixel);
+        sum += Vector3::new(r as f32, g as f32, b as f32);
+        count += 1;
+    }
+
+    if count > 0 {
+        sum.scale(1.0 / (255.0 * count as f32))
+    } else {
+        Vector3::default()
+    }
+}
+
+/// Transforms triangles of the surface data into world space and adds them to the list of
+/// emitters.
+fn gather_emissive_triangles(
+    data: &SurfaceData,
+    transform: &Matrix4<f32>,
+    radiance: Vector3<f32>,
+    emitters: &mut Vec<EmissiveTriangle>,
+) {
+    let normal_matrix = transform
+        .basis()
+        .try_inverse()
+        .map(|m| m.transpose())
+        .unwrap_or_else(Matrix3::identity);
+
+    let vertices = data
+        .vertex_buffer
+        .iter()
+        .map(|view| {
+            (
+                transform
+                    .transform_point(&Point3::from(
+                        view.read_3_f32(VertexAttributeUsage::Position)
+                            .unwrap_or_default(),
+                    ))
+                    .coords,
+                normal_matrix
+                    * view
+                        .read_3_f32(VertexAttributeUsage::Normal)
+                        .unwrap_or_default(),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    for triangle in data.geometry_buffer.iter() {
+        let (a, na) = vertices[triangle[0] as usize];
+        let (b, nb) = vertices[triangle[1] as usize];
+        let (c, nc) = vertices[triangle[2] as usize];
+
+        emitters.extend(EmissiveTriangle::new([a, b, c], na + nb + nc, radiance));
+    }
+}
+
+impl EmissiveTriangle {
+    /// Creates new emitter from world-space vertices, `normal_hint` is used to find out which side
+    /// of the triangle emits light, since winding order may vary. Returns `None` for degenerate
+    /// triangles.
+    fn new(
+        vertices: [Vector3<f32>; 3],
+        normal_hint: Vector3<f32>,
+        radiance: Vector3<f32>,
+    ) -> Option<Self> {
+        let [a, b, c] = vertices;
+
+        let cross = (b - a).cross(&(c - a));
+        let area = cross.norm() * 0.5;
+        if area <= f32::EPSILON {
+            return None;
+        }
+
+        let mut normal = cross.scale(0.5 / area);
+        if normal.dot(&normal_hint) < 0.0 {
+            normal = -normal;
+        }
+
+        Some(Self {
+            center: (a + b + c).scale(1.0 / 3.0),
+            normal,
+            area,
+            radiance,
+        })
+    }
+}
+
+/// Checks whether the ray hits any triangle of the instances before reaching its end point.
+fn is_occluded(ray: &Ray, instances: &[Instance]) -> bool {
+    let mut query_buffer = ArrayVec::<Handle<OctreeNode>, 64>::new();
+    let shadow_bias = 0.01;
+    for instance in instances {
+        let data = instance.data();
+        data.octree.ray_query_static(ray, &mut query_buffer);
+        for &node in query_buffer.iter() {
+            match data.octree.node(node) {
+                OctreeNode::Leaf { indices, .. } => {
+                    for &triangle_index in indices {
+                        let triangle = &data.triangles[triangle_index as usize];
+                        let va = data.vertices[triangle[0] as usize].world_position;
+                        let vb = data.vertices[triangle[1] as usize].world_position;
+                        let vc = data.vertices[triangle[2] as usize].world_position;
+                        if let Some(pt) = ray.triangle_intersection_point(&[va, vb, vc]) {
+                            if ray.origin.metric_distance(&pt) + shadow_bias < ray.dir.norm() {
+                                return true;
+                            }
+                        }
+                    }
+                }
+                OctreeNode::Branch { .. } => unreachable!(),
+            }
+        }
+    }
+    false
+}
+
+/// A point where a ray hits a triangle of an instance.
+struct RayHit {
+    /// Index of the instance.
+    instance: usize,
+    /// Index of the triangle of the instance.
+    triangle: usize,
+    barycentric: (f32, f32, f32),
+}
+
+/// Finds the closest to the origin of the ray intersection with triangles of the instances.
+fn closest_hit(ray: &Ray, instances: &[Instance]) -> Option<RayHit> {
+    let mut query_buffer = ArrayVec::<Handle<OctreeNode>, 64>::new();
+    let mut closest = None;
+    let mut closest_distance = f32::MAX;
+    for (instance_index, instance) in instances.iter().enumerate() {
+        let data = instance.data();
+        data.octree.ray_query_static(ray, &mut query_buffer);
+        for &node in query_buffer.iter() {
+            match data.octree.node(node) {
+                OctreeNode::Leaf { indices, .. } => {
+                    for &triangle_index in indices {
+                        let triangle = &data.triangles[triangle_index as usize];
+                        let va = data.vertices[triangle[0] as usize].world_position;
+                        let vb = data.vertices[triangle[1] as usize].world_position;
+                        let vc = data.vertices[triangle[2] as usize].world_position;
+                        if let Some((distance, point)) = ray.triangle_intersection(&[va, vb, vc]) {
+                            if distance < closest_distance {
+                                closest_distance = distance;
+                                closest = Some(RayHit {
+                                    instance: instance_index,
+                                    triangle: triangle_index as usize,
+                                    barycentric: math::get_barycentric_coords(
+                                        &point, &va, &vb, &vc,
+                                    ),
+                                });
+                            }
+                        }
+                    }
+                }
+                OctreeNode::Branch { .. } => unreachable!(),
+            }
+        }
+    }
+    closest
+}
+
+/// Calculates size of the diagonal of the bounding box of every instance.
+fn scene_extent(instances: &[Instance]) -> f32 {
+    let mut min = Vector3::repeat(f32::MAX);
+    let mut max = Vector3::repeat(-f32::MAX);
+    for vertex in instances
+        .iter()
+        .flat_map(|instance| instance.data().vertices.iter())
+    {
+        min = min.inf(&vertex.world_position);
+        max = max.sup(&vertex.world_position);
+    }
+    (max - min).norm().max(1.0)
+}
+
+/// Hashes the position of a texel into a point in the unit square. The point is used to shift
+/// ray directions of the texel, so neighbour texels use different rays. It turns banding into
+/// noise, which is then smoothed by the blur of the lightmap, while keeping generation
+/// deterministic.
+fn texel_offset(position: &Vector3<f32>) -> Vector2<f32> {
+    // https://nullprogram.com/blog/2018/07/31/
+    fn hash(mut x: u32) -> u32 {
+        x ^= x >> 16;
+        x = x.wrapping_mul(0x7feb352d);
+        x ^= x >> 15;
+        x = x.wrapping_mul(0x846ca68b);
+        x ^= x >> 16;
+        x
+    }
+
+    let h = hash(position.x.to_bits() ^ hash(position.y.to_bits() ^ hash(position.z.to_bits())));
+    Vector2::new((h & 0xFFFF) as f32 / 65536.0, (h >> 16) as f32 / 65536.0)
+}
+
+/// Returns `index`-th point of Hammersley set of `count` points in the unit square, shifted by
+/// the given offset with wrapping.
+fn hammersley(index: u32, count: u32, offset: Vector2<f32>) -> Vector2<f32> {
+    let radical_inverse = index.reverse_bits() as f32 / 4294967296.0;
+    Vector2::new(
+        (index as f32 / count as f32 + offset.x).fract(),
+        (radical_inverse + offset.y).fract(),
+    )
+}
+
+/// Maps a point of the unit square to a direction in the hemisphere around the normal, directions
+/// are distributed proportionally to the cosine of the angle with the normal.
+fn cosine_weighted_direction(normal: Vector3<f32>, sample: Vector2<f32>) -> Vector3<f32> {
+    let helper = if normal.x.abs() < 0.9 {
+        Vector3::x()
+    } else {
+        Vector3::y()
+    };
+    let tangent = helper.cross(&normal).normalize();
+    let bitangent = normal.cross(&tangent);
+
+    let radius = sample.x.sqrt();
+    let angle = 2.0 * std::f32::consts::PI * sample.y;
+    tangent.scale(radius * angle.cos())
+        + bitangent.scale(radius * angle.sin())
+        + normal.scale((1.0 - sample.x).max(0.0).sqrt())
+}
+
+/// Calculates irradiance of the texel from the light reflected by the surfaces of the scene. Every
+/// ray takes irradiance of the point it hits from the previous bounce and multiplies it by albedo
+/// of the surface at the point. Rays are distributed by cosine, so the average of the samples is
+/// the irradiance for diffuse surfaces. Albedo is never greater than one, so the reflected light
+/// never gets brighter than the incoming light.
+fn indirect_lighting(
+    texel: &Texel,
+    ray_count: u32,
+    max_distance: f32,
+    instances: &[Instance],
+    atlases: &[(TexelAtlas, Vec<Vector3<f32>>)],
+    irradiance: &[Vec<Vector3<f32>>],
+) -> Vector3<f32> {
+    let normal = match texel.normal.try_normalize(f32::EPSILON) {
+        Some(normal) => normal,
+        None => return Vector3::default(),
+    };
+    let origin = texel.position + normal.scale(0.01);
+    let offset = texel_offset(&texel.position);
+
+    let mut sum = Vector3::default();
+    for i in 0..ray_count {
+        let dir = cosine_weighted_direction(normal, hammersley(i, ray_count, offset));
+        let hit = match closest_hit(&Ray::new(origin, dir.scale(max_distance)), instances) {
+            Some(hit) => hit,
+            None => continue,
+        };
+
+        let data = instances[hit.instance].data();
+        let triangle = &data.triangles[hit.triangle];
+        let vertex = |i: usize| &data.vertices[triangle[i] as usize];
+        let (u, v, w) = hit.barycentric;
+
+        // Back faces are inside of objects, they do not reflect any light.
+        let hit_normal = vertex(0).world_normal.scale(u)
+            + vertex(1).world_normal.scale(v)
+            + vertex(2).world_normal.scale(w);
+        if hit_normal.dot(&dir) >= 0.0 {
+            continue;
+        }
+
+        let tex_coord = vertex(0).tex_coord.scale(u)
+            + vertex(1).tex_coord.scale(v)
+            + vertex(2).tex_coord.scale(w);
+        let second_tex_coord = vertex(0).second_tex_coord.scale(u)
+            + vertex(1).second_tex_coord.scale(v)
+            + vertex(2).second_tex_coord.scale(w);
+
+        let (atlas, _) = &atlases[hit.instance];
+        sum += atlas
+            .lookup(&irradiance[hit.instance], second_tex_coord)
+            .component_mul(&instances[hit.instance].albedo.sample(tex_coord));
+    }
+    sum.scale(1.0 / ray_count as f32)
+}
+
+/// Calculates ambient occlusion factor of the texel - the fraction of the light, that is not
+/// blocked by the geometry closer than `max_distance`. `strength` defines how much occluded rays
+/// darken the texel.
+fn ambient_occlusion(
+    texel: &Texel,
+    ray_count: u32,
+    max_distance: f32,
+    strength: f32,
+    instances: &[Instance],
+) -> f32 {
+    let normal = match texel.normal.try_normalize(f32::EPSILON) {
+        Some(normal) => normal,
+        None => return 1.0,
+    };
+    let origin = texel.position + normal.scale(0.01);
+    let offset = texel_offset(&texel.position);
+
+    let occluded = (0..ray_count)
+        .filter(|&i| {
+            let dir = cosine_weighted_direction(normal, hammersley(i, ray_count, offset));
+            is_occluded(&Ray::new(origin, dir.scale(max_distance)), instances)
+        })
+        .count();
+
+    1.0 - strength.clamp(0.0, 1.0) * occluded as f32 / ray_count as f32
+}
+
 /// Computes total area of triangles in surface data and returns size of square
 /// in which triangles can fit.
 fn estimate_size(data: &InstanceData, texels_per_unit: u32) -> u32 {
@@ -595,12 +1339,7 @@ fn distance_attenuation(distance: f32, sqr_radius: f32) -> f32 {
 }
 
 /// Calculates properties of pixel (world position, normal) at given position.
-fn pick(
-    uv: Vector2<f32>,
-    grid: &Grid,
-    data: &InstanceData,
-    scale: f32,
-) -> Option<(Vector3<f32>, Vector3<f32>)> {
+fn pick(uv: Vector2<f32>, grid: &Grid, data: &InstanceData, scale: f32) -> Option<Texel> {
     if let Some(cell) = grid.pick(uv) {
         for triangle in cell.triangles.iter().map(|&ti| &data.triangles[ti]) {
             let ia = triangle[0] as usize;
@@ -630,10 +1369,10 @@ fn pick(
                     let nb = data.vertices[ib].world_normal;
                     let nc = data.vertices[ic].world_normal;
 
-                    return Some((
-                        math::barycentric_to_world(barycentric, a, b, c),
-                        math::barycentric_to_world(barycentric, na, nb, nc),
-                    ));
+                    return Some(Texel {
+                        position: math::barycentric_to_world(barycentric, a, b, c),
+                        normal: math::barycentric_to_world(barycentric, na, nb, nc),
+                    });
                 }
 
                 // Offset uv to center for conservative rasterization.
@@ -710,225 +1449,301 @@ fn smoothstep(edge0: f32, edge1: f32, x: f32) -> f32 {
     k * k * (3.0 - 2.0 * k)
 }
 
-/// Generates lightmap for given surface data with specified transform.
-///
-/// # Performance
-///
-/// This method is has linear complexity - the more complex mesh you pass, the more
-/// time it will take. Required time increases drastically if you enable shadows and
-/// global illumination (TODO), because in this case your data will be raytraced.
-fn generate_lightmap(
-    instance: &Instance,
-    other_instances: &[Instance],
+/// Calculates lighting from light sources at the given texel.
+fn direct_lighting(
+    texel: &Texel,
     lights: &[LightDefinition],
-    texels_per_unit: u32,
-) -> Texture {
-    // We have to re-generate new set of world-space vertices because UV generator
-    // may add new vertices on seams.
-    let atlas_size = estimate_size(instance.data(), texels_per_unit);
-    let scale = 1.0 / atlas_size as f32;
-    let grid = Grid::new(instance.data(), (atlas_size / 32).max(4) as usize);
-
-    let mut pixels: Vec<Vector4<u8>> =
-        vec![Vector4::new(0, 0, 0, 0); (atlas_size * atlas_size) as usize];
-
-    let half_pixel = scale * 0.5;
-    pixels
-        .par_iter_mut()
-        .enumerate()
-        .for_each(|(i, pixel): (usize, &mut Vector4<u8>)| {
-            let x = i as u32 % atlas_size;
-            let y = i as u32 / atlas_size;
-
-            let uv = Vector2::new(x as f32 * scale + half_pixel, y as f32 * scale + half_pixel);
-
-            if let Some((world_position, world_normal)) = pick(uv, &grid, instance.data(), scale) {
-                let mut pixel_color = Vector3::default();
-                for light in lights {
-                    let (light_color, mut attenuation, light_position) = match light {
-                        LightDefinition::Directional(directional) => {
-                            let attenuation = directional.intensity
-                                * lambertian(directional.direction, world_normal);
-                            (directional.color, attenuation, Vector3::default())
-                        }
-                        LightDefinition::Spot(spot) => {
-                            let d = spot.position - world_position;
-                            let distance = d.norm();
-                            let light_vec = d.scale(1.0 / distance);
-                            let spot_angle_cos = light_vec.dot(&spot.direction);
-                            let cone_factor = smoothstep(spot.edge0, spot.edge1, spot_angle_cos);
-                            let attenuation = cone_factor
-                                * spot.intensity
-                                * lambertian(light_vec, world_normal)
-                                * distance_attenuation(distance, spot.sqr_distance);
-                            (spot.color, attenuation, spot.position)
-                        }
-                        LightDefinition::Point(point) => {
-                            let d = point.position - world_position;
-                            let distance = d.norm();
-                            let light_vec = d.scale(1.0 / distance);
-                            let attenuation = point.intensity
-                                * lambertian(light_vec, world_normal)
-                                * distance_attenuation(distance, point.sqr_radius);
-                            (point.color, attenuation, point.position)
-                        }
-                    };
-                    // Shadows
-                    if attenuation >= 0.01 {
-                        let mut query_buffer = ArrayVec::<Handle<OctreeNode>, 64>::new();
-                        let shadow_bias = 0.01;
-                        let ray = Ray::from_two_points(light_position, world_position);
-                        'outer_loop: for other_instance in other_instances {
-                            other_instance
-                                .data()
-                                .octree
-                                .ray_query_static(&ray, &mut query_buffer);
-                            for &node in query_buffer.iter() {
-                                match other_instance.data().octree.node(node) {
-                                    OctreeNode::Leaf { indices, .. } => {
-                                        let other_data = other_instance.data();
-                                        for &triangle_index in indices {
-                                            let triangle =
-                                                &other_data.triangles[triangle_index as usize];
-                                            let va = other_data.vertices[triangle[0] as usize]
-                                                .world_position;
-                                            let vb = other_data.vertices[triangle[1] as usize]
-                                                .world_position;
-                                            let vc = other_data.vertices[triangle[2] as usize]
-                                                .world_position;
-                                            if let Some(pt) =
-                                                ray.triangle_intersection_point(&[va, vb, vc])
-                                            {
-                                                if ray.origin.metric_distance(&pt) + shadow_bias
-                                                    < ray.dir.norm()
-                                                {
-                                                    attenuation = 0.0;
-                                                    break 'outer_loop;
-                                                }
-                                            }
-                                        }
-                                    }
-                                    OctreeNode::Branch { .. } => unreachable!(),
-                                }
-                            }
-                        }
-                    }
-                    pixel_color += light_color.scale(attenuation);
-                }
-
-                *pixel = Vector4::new(
-                    (pixel_color.x.clamp(0.0, 1.0) * 255.0) as u8,
-                    (pixel_color.y.clamp(0.0, 1.0) * 255.0) as u8,
-                    (pixel_color.z.clamp(0.0, 1.0) * 255.0) as u8,
-                    255, // Indicates that this pixel was "filled"
-                );
+    instances: &[Instance],
+) -> Vector3<f32> {
+    let mut pixel_color = Vector3::default();
+    for light in lights {
+        let (light_color, mut attenuation, light_position) = match light {
+            LightDefinition::Directional(directional) => {
+                let attenuation =
+                    directional.intensity * lambertian(directional.direction, texel.normal);
+                (directional.color, attenuation, Vector3::default())
             }
-        });
-
-    // Prepare light map for bilinear filtration. This step is mandatory to prevent bleeding.
-    let mut rgb_pixels: Vec<Vector3<u8>> = Vec::with_capacity((atlas_size * atlas_size) as usize);
-    for y in 0..(atlas_size as i32) {
-        for x in 0..(atlas_size as i32) {
-            let fetch = |dx: i32, dy: i32| -> Option<Vector3<u8>> {
-                pixels
-                    .get(((y + dy) * (atlas_size as i32) + x + dx) as usize)
-                    .and_then(|p| {
-                        if p.w != 0 {
-                            Some(Vector3::new(p.x, p.y, p.z))
-                        } else {
-                            None
-                        }
-                    })
-            };
+            LightDefinition::Spot(spot) => {
+                let d = spot.position - texel.position;
+                let distance = d.norm();
+                let light_vec = d.scale(1.0 / distance);
+                let spot_angle_cos = light_vec.dot(&spot.direction);
+                let cone_factor = smoothstep(spot.edge0, spot.edge1, spot_angle_cos);
+                let attenuation = cone_factor
+                    * spot.intensity
+                    * lambertian(light_vec, texel.normal)
+                    * distance_attenuation(distance, spot.sqr_distance);
+                (spot.color, attenuation, spot.position)
+            }
+            LightDefinition::Point(point) => {
+                let d = point.position - texel.position;
+                let distance = d.norm();
+                let light_vec = d.scale(1.0 / distance);
+                let attenuation = point.intensity
+                    * lambertian(light_vec, texel.normal)
+                    * distance_attenuation(distance, point.sqr_radius);
+                (point.color, attenuation, point.position)
+            }
+        };
+        // Shadows
+        if attenuation >= 0.01
+            && is_occluded(
+                &Ray::from_two_points(light_position, texel.position),
+                instances,
+            )
+        {
+            attenuation = 0.0;
+        }
+        pixel_color += light_color.scale(attenuation);
+    }
+    pixel_color
+}
 
-            let src_pixel = pixels[(y * (atlas_size as i32) + x) as usize];
-            if src_pixel.w == 0 {
-                // Check neighbour pixels marked as "filled" and use it as value.
-                if let Some(west) = fetch(-1, 0) {
-                    rgb_pixels.push(west);
-                } else if let Some(east) = fetch(1, 0) {
-                    rgb_pixels.push(east);
-                } else if let Some(north) = fetch(0, -1) {
-                    rgb_pixels.push(north);
-                } else if let Some(south) = fetch(0, 1) {
-                    rgb_pixels.push(south);
-                } else if let Some(north_west) = fetch(-1, -1) {
-                    rgb_pixels.push(north_west);
-                } else if let Some(north_east) = fetch(1, -1) {
-                    rgb_pixels.push(north_east);
-                } else if let Some(south_east) = fetch(1, 1) {
-                    rgb_pixels.push(south_east);
-                } else if let Some(south_west) = fetch(-1, 1) {
-                    rgb_pixels.push(south_west);
-                } else {
-                    rgb_pixels.push(Vector3::new(0, 0, 0));
+/// Calculates lighting from area light sources at the given texel. Every emissive triangle is a
+/// small area light, its contribution is weighted by its area and the distance to it (differential
+/// form factor).
+fn emitters_lighting(
+    texel: &Texel,
+    emitters: &[EmissiveTriangle],
+    instances: &[Instance],
+) -> Vector3<f32> {
+    let mut pixel_color = Vector3::default();
+    for emitter in emitters {
+        let d = emitter.center - texel.position;
+        let sqr_distance = d.norm_squared();
+        if sqr_distance <= f32::EPSILON {
+            continue;
+        }
+        let light_vec = d.scale(1.0 / sqr_distance.sqrt());
+        let form_factor = emitter.area
+            * lambertian(light_vec, texel.normal)
+            * lambertian(-light_vec, emitter.normal)
+            // Clamp distance by the size of the emitter to prevent singularity
+            // near its surface.
+            / (std::f32::consts::PI * sqr_distance.max(emitter.area));
+        let contribution = emitter.radiance.scale(form_factor);
+        if contribution.max() >= 0.001
+            && !is_occluded(
+                &Ray::from_two_points(emitter.center + emitter.normal.scale(0.01), texel.position),
+                instances,
+            )
+        {
+            pixel_color += contribution;
+        }
+    }
+    pixel_color
+}
+
+/// A point on a surface, that corresponds to a pixel of a lightmap.
+struct Texel {
+    position: Vector3<f32>,
+    normal: Vector3<f32>,
+}
+
+/// A set of texels of a lightmap of an instance.
+struct TexelAtlas {
+    size: u32,
+    /// `None` for pixels that are not covered by any triangle.
+    texels: Vec<Option<Texel>>,
+}
+
+impl TexelAtlas {
+    fn new(data: &InstanceData, texels_per_unit: u32) -> Self {
+        // We have to re-generate new set of world-space vertices because UV generator
+        // may add new vertices on seams.
+        let atlas_size = estimate_size(data, texels_per_unit);
+        let scale = 1.0 / atlas_size as f32;
+        let grid = Grid::new(data, (atlas_size / 32).max(4) as usize);
+
+        let half_pixel = scale * 0.5;
+        let texels = (0..(atlas_size * atlas_size) as usize)
+            .into_par_iter()
+            .map(|i| {
+                let x = i as u32 % atlas_size;
+                let y = i as u32 / atlas_size;
+
+                let uv = Vector2::new(x as f32 * scale + half_pixel, y as f32 * scale + half_pixel);
+
+                pick(uv, &grid, data, scale)
+            })
+            .collect();
+
+        Self {
+            size: atlas_size,
+            texels,
+        }
+    }
+
+    /// Calculates lighting for every texel using the given function. Uncovered texels are black.
+    fn illuminate<F>(&self, func: F) -> Vec<Vector3<f32>>
+    where
+        F: Fn(&Texel) -> Vector3<f32> + Sync,
+    {
+        self.texels
+            .par_iter()
+            .map(|texel| texel.as_ref().map_or_else(Vector3::default, &func))
+            .collect()
+    }
+
+    /// Fetches lighting of the texel at the given second texture coordinates. Uncovered texels
+    /// (usually at the seams of UV elements) take lighting of a covered neighbour texel.
+    fn lookup(&self, lighting: &[Vector3<f32>], second_tex_coord: Vector2<f32>) -> Vector3<f32> {
+        let size = self.size as i32;
+        let x = ((second_tex_coord.x * self.size as f32) as i32).clamp(0, size - 1);
+        let y = ((second_tex_coord.y * self.size as f32) as i32).clamp(0, size - 1);
+        for (dx, dy) in [(0, 0), (-1, 0), (1, 0), (0, -1), (0, 1)] {
+            let (x, y) = (x + dx, y + dy);
+            if x >= 0 && y >= 0 && x < size && y < size {
+                let index = (y * size + x) as usize;
+                if self.texels[index].is_some() {
+                    return lighting[index];
                 }
-            } else {
-                rgb_pixels.push(Vector3::new(src_pixel.x, src_pixel.y, src_pixel.z))
             }
         }
+        Vector3::default()
     }
 
-    // Blur lightmap using simplest box filter.
-    let mut bytes = Vec::with_capacity((atlas_size * atlas_size * 3) as usize);
-    for y in 0..(atlas_size as i32) {
-        for x in 0..(atlas_size as i32) {
-            if x < 1 || y < 1 || x + 1 == atlas_size as i32 || y + 1 == atlas_size as i32 {
-                bytes.extend_from_slice(
-                    rgb_pixels[(y * (atlas_size as i32) + x) as usize].as_slice(),
-                );
-            } else {
-                let fetch = |dx: i32, dy: i32| -> Vector3<i16> {
-                    let u8_pixel = rgb_pixels[((y + dy) * (atlas_size as i32) + x + dx) as usize];
-                    Vector3::new(u8_pixel.x as i16, u8_pixel.y as i16, u8_pixel.z as i16)
+    /// Creates lightmap texture from the given lighting of the texels.
+    ///
+    /// # Performance
+    ///
+    /// This method is has linear complexity - the more complex mesh you pass, the more
+    /// time it will take.
+    fn make_texture(&self, lighting: &[Vector3<f32>]) -> Texture {
+        let atlas_size = self.size;
+
+        let pixels = self
+            .texels
+            .iter()
+            .zip(lighting.iter())
+            .map(|(texel, pixel_color)| {
+                if texel.is_some() {
+                    Vector4::new(
+                        (pixel_color.x.clamp(0.0, 1.0) * 255.0) as u8,
+                        (pixel_color.y.clamp(0.0, 1.0) * 255.0) as u8,
+                        (pixel_color.z.clamp(0.0, 1.0) * 255.0) as u8,
+                        255, // Indicates that this pixel was "filled"
+                    )
+                } else {
+                    Vector4::new(0, 0, 0, 0)
+                }
+            })
+            .collect::<Vec<Vector4<u8>>>();
+
+        // Prepare light map for bilinear filtration. This step is mandatory to prevent bleeding.
+        let mut rgb_pixels: Vec<Vector3<u8>> =
+            Vec::with_capacity((atlas_size * atlas_size) as usize);
+        for y in 0..(atlas_size as i32) {
+            for x in 0..(atlas_size as i32) {
+                let fetch = |dx: i32, dy: i32| -> Option<Vector3<u8>> {
+                    pixels
+                        .get(((y + dy) * (atlas_size as i32) + x + dx) as usize)
+                        .and_then(|p| {
+                            if p.w != 0 {
+                                Some(Vector3::new(p.x, p.y, p.z))
+                            } else {
+                                None
+                            }
+                        })
                 };
 
-                let north_west = fetch(-1, -1);
-                let north = fetch(0, -1);
-                let north_east = fetch(1, -1);
-                let west = fetch(-1, 0);
-                let center = fetch(0, 0);
-                let east = fetch(1, 0);
-                let south_west = fetch(-1, 1);
-                let south = fetch(0, 1);
-                let south_east = fetch(-1, 1);
-
-                let sum = north_west
-                    + north
-                    + north_east
-                    + west
-                    + center
-                    + east
-                    + south_west
-                    + south
-                    + south_east;
-
-                bytes.push((sum.x / 9).clamp(0, 255) as u8);
-                bytes.push((sum.y / 9).clamp(0, 255) as u8);
-                bytes.push((sum.z / 9).clamp(0, 255) as u8);
+                let src_pixel = pixels[(y * (atlas_size as i32) + x) as usize];
+                if src_pixel.w == 0 {
+                    // Check neighbour pixels marked as "filled" and use it as value.
+                    if let Some(west) = fetch(-1, 0) {
+                        rgb_pixels.push(west);
+                    } else if let Some(east) = fetch(1, 0) {
+                        rgb_pixels.push(east);
+                    } else if let Some(north) = fetch(0, -1) {
+                        rgb_pixels.push(north);
+                    } else if let Some(south) = fetch(0, 1) {
+                        rgb_pixels.push(south);
+                    } else if let Some(north_west) = fetch(-1, -1) {
+                        rgb_pixels.push(north_west);
+                    } else if let Some(north_east) = fetch(1, -1) {
+                        rgb_pixels.push(north_east);
+                    } else if let Some(south_east) = fetch(1, 1) {
+                        rgb_pixels.push(south_east);
+                    } else if let Some(south_west) = fetch(-1, 1) {
+                        rgb_pixels.push(south_west);
+                    } else {
+                        rgb_pixels.push(Vector3::new(0, 0, 0));
+                    }
+                } else {
+                    rgb_pixels.push(Vector3::new(src_pixel.x, src_pixel.y, src_pixel.z))
+                }
             }
         }
-    }
 
-    Texture::from_bytes(
-        TextureKind::Rectangle {
-            width: atlas_size,
-            height: atlas_size,
-        },
-        TexturePixelKind::RGB8,
-        bytes,
-        // Do not serialize content because lightmap is saved as a series of images in
-        // a common format.
-        false,
-    )
-    .unwrap()
+        // Blur lightmap using simplest box filter.
+        let mut bytes = Vec::with_capacity((atlas_size * atlas_size * 3) as usize);
+        for y in 0..(atlas_size as i32) {
+            for x in 0..(atlas_size as i32) {
+                if x < 1 || y < 1 || x + 1 == atlas_size as i32 || y + 1 == atlas_size as i32 {
+                    bytes.extend_from_slice(
+                        rgb_pixels[(y * (atlas_size as i32) + x) as usize].as_slice(),
+                    );
+                } else {
+                    let fetch = |dx: i32, dy: i32| -> Vector3<i16> {
+                        let u8_pixel =
+                            rgb_pixels[((y + dy) * (atlas_size as i32) + x + dx) as usize];
+                        Vector3::new(u8_pixel.x as i16, u8_pixel.y as i16, u8_pixel.z as i16)
+                    };
+
+                    let north_west = fetch(-1, -1);
+                    let north = fetch(0, -1);
+                    let north_east = fetch(1, -1);
+                    let west = fetch(-1, 0);
+                    let center = fetch(0, 0);
+                    let east = fetch(1, 0);
+                    let south_west = fetch(-1, 1);
+                    let south = fetch(0, 1);
+                    let south_east = fetch(-1, 1);
+
+                    let sum = north_west
+                        + north
+                        + north_east
+                        + west
+                        + center
+                        + east
+                        + south_west
+                        + south
+                        + south_east;
+
+                    bytes.push((sum.x / 9).clamp(0, 255) as u8);
+                    bytes.push((sum.y / 9).clamp(0, 255) as u8);
+                    bytes.push((sum.z / 9).clamp(0, 255) as u8);
+                }
+            }
+        }
+
+        Texture::from_bytes(
+            TextureKind::Rectangle {
+                width: atlas_size,
+                height: atlas_size,
+            },
+            TexturePixelKind::RGB8,
+            bytes,
+            // Do not serialize content because lightmap is saved as a series of images in
+            // a common format.
+            false,
+        )
+        .unwrap()
+    }
 }
 
 #[cfg(test)]
 mod test {
     use crate::scene::mesh::surface::SurfaceSharedData;
     use crate::{
-        core::algebra::{Matrix4, Vector3},
+        core::{
+            algebra::{Matrix4, Vector3},
+            color::Color,
+            pool::Handle,
+            sstorage::ImmutableString,
+        },
+        material::{shader::SamplerFallback, PropertyValue},
+        resource::texture::{Texture, TextureKind, TexturePixelKind, TextureResource},
         scene::{
             base::BaseBuilder,
             light::{point::PointLightBuilder, BaseLightBuilder},
@@ -936,10 +1751,11 @@ mod test {
                 surface::{SurfaceBuilder, SurfaceData},
                 MeshBuilder,
             },
+            node::Node,
             transform::TransformBuilder,
             Scene,
         },
-        utils::lightmap::Lightmap,
+        utils::lightmap::{Lightmap, LightmapEntry, LightmapSettings},
     };
 
     #[test]
@@ -971,8 +1787,12 @@ mod test {
 
         let lightmap = Lightmap::new(
             &mut scene,
-            64,
-            0.005,
+            &LightmapSettings {
+                texels_per_unit: 64,
+                bounces: 0,
+                ambient_occlusion: false,
+                ..Default::default()
+            },
             |_, _| true,
             Default::default(),
             Default::default(),
@@ -989,4 +1809,247 @@ mod test {
             }
         }
     }
+
+    #[test]
+    fn test_emissive_surface_lighting() {
+        let mut scene = Scene::new();
+
+        // Floor facing up.
+        MeshBuilder::new(BaseBuilder::new())
+            .with_surfaces(vec![SurfaceBuilder::new(SurfaceSharedData::new(
+                SurfaceData::make_quad(
+                    &(Matrix4::new_rotation(Vector3::x() * std::f32::consts::FRAC_PI_2)
+                        * Matrix4::new_scaling(2.0)),
+                ),
+            ))
+            .build()])
+            .build(&mut scene.graph);
+
+        // Glowing panel above the floor, facing down.
+        let panel = SurfaceBuilder::new(SurfaceSharedData::new(SurfaceData::make_quad(
+            &(Matrix4::new_translation(&Vector3::new(0.0, 1.0, 0.0))
+                * Matrix4::new_rotation(Vector3::x() * -std::f32::consts::FRAC_PI_2)),
+        )))
+        .build();
+        panel
+            .material()
+            .lock()
+            .set_property(
+                &ImmutableString::new("emissionTexture"),
+                PropertyValue::Sampler {
+                    value: Some(TextureResource::new_ok(
+                        Texture::from_bytes(
+                            TextureKind::Rectangle {
+                                width: 1,
+                                height: 1,
+                            },
+                            TexturePixelKind::RGB8,
+                            vec![255, 255, 255],
+                            false,
+                        )
+                        .unwrap(),
+                    )),
+                    fallback: SamplerFallback::Black,
+                },
+            )
+            .unwrap();
+        let panel = MeshBuilder::new(BaseBuilder::new())
+            .with_surfaces(vec![panel])
+            .build(&mut scene.graph);
+
+        let mut bake = |emission_scale: f32, bounces: u32| {
+            Lightmap::new(
+                &mut scene,
+                &LightmapSettings {
+                    texels_per_unit: 16,
+                    emission_scale,
+                    bounces,
+                    ambient_occlusion: false,
+                    ..Default::default()
+                },
+                |_, _| true,
+                Default::default(),
+                Default::default(),
+            )
+            .unwrap()
+        };
+        let is_lit = |entries: &[LightmapEntry]| {
+            entries.iter().any(|entry| {
+                let texture = entry.texture.as_ref().unwrap().data_ref();
+                texture.data().iter().any(|b| *b > 0)
+            })
+        };
+
+        // There are no light sources except the glowing panel.
+        assert!(!bake(0.0, 0).map.values().any(|entries| is_lit(entries)));
+        let direct = bake(1.0, 0);
+        assert!(direct.map.values().any(|entries| is_lit(entries)));
+        // The panel does not light itself.
+        assert!(!is_lit(&direct.map[&panel]));
+
+        // Light reflected by the floor reaches the panel.
+        let bounced = bake(1.0, 1);
+        assert!(is_lit(&bounced.map[&panel]));
+    }
+
+    struct CornellBox {
+        scene: Scene,
+        floor: Handle<Node>,
+        red_wall: Handle<Node>,
+        green_wall: Handle<Node>,
+    }
+
+    /// Creates a 2x2x2 box with gray floor, ceiling and back wall, red left wall and green right
+    /// wall. The box is open from the front and lit by a point light in its center.
+    fn make_cornell_box() -> CornellBox {
+        let mut scene = Scene::new();
+
+        let mut add_wall = |transform: Matrix4<f32>, color: Color| {
+            let surface = SurfaceBuilder::new(SurfaceSharedData::new(SurfaceData::make_quad(
+                &(transform * Matrix4::new_scaling(2.0)),
+            )))
+            .build();
+            surface
+                .material()
+                .lock()
+                .set_property(
+                    &ImmutableString::new("diffuseColor"),
+                    PropertyValue::Color(color),
+                )
+                .unwrap();
+            MeshBuilder::new(BaseBuilder::new())
+                .with_surfaces(vec![surface])
+                .build(&mut scene.graph)
+        };
+
+        let gray = Color::opaque(188, 188, 188);
+        let floor = add_wall(
+            Matrix4::new_rotation(Vector3::x() * std::f32::consts::FRAC_PI_2),
+            gray,
+        );
+        add_wall(
+            Matrix4::new_translation(&Vector3::new(0.0, 2.0, 0.0))
+                * Matrix4::new_rotation(Vector3::x() * -std::f32::consts::FRAC_PI_2),
+            gray,
+        );
+        add_wall(Matrix4::new_translation(&Vector3::new(0.0, 1.0, 1.0)), gray);
+        let red_wall = add_wall(
+            Matrix4::new_translation(&Vector3::new(-1.0, 1.0, 0.0))
+                * Matrix4::new_rotation(Vector3::y() * -std::f32::consts::FRAC_PI_2),
+            Color::opaque(255, 0, 0),
+        );
+        let green_wall = add_wall(
+            Matrix4::new_translation(&Vector3::new(1.0, 1.0, 0.0))
+                * Matrix4::new_rotation(Vector3::y() * std::f32::consts::FRAC_PI_2),
+            Color::opaque(0, 255, 0),
+        );
+
+        PointLightBuilder::new(
+            BaseLightBuilder::new(
+                BaseBuilder::new().with_local_transform(
+                    TransformBuilder::new()
+                        .with_local_position(Vector3::new(0.0, 1.0, 0.0))
+                        .build(),
+                ),
+            )
+            .with_intensity(0.5),
+        )
+        .with_radius(5.0)
+        .build(&mut scene.graph);
+
+        CornellBox {
+            scene,
+            floor,
+            red_wall,
+            green_wall,
+        }
+    }
+
+    fn bake_cornell_box(bounces: u32, ambient_occlusion: bool) -> (CornellBox, Lightmap) {
+        let mut cornell_box = make_cornell_box();
+        let lightmap = Lightmap::new(
+            &mut cornell_box.scene,
+            &LightmapSettings {
+                texels_per_unit: 16,
+                uv_spacing: 0.005,
+                emission_scale: 0.0,
+                bounces,
+                indirect_scale: 1.0,
+                indirect_ray_count: 32,
+                ambient_occlusion,
+                ao_ray_count: 32,
+                ao_max_distance: 0.5,
+                ao_strength: 1.0,
+            },
+            |_, _| true,
+            Default::default(),
+            Default::default(),
+        )
+        .unwrap();
+        (cornell_box, lightmap)
+    }
+
+    fn pixels(lightmap: &Lightmap, node: Handle<Node>) -> Vec<u8> {
+        lightmap.map[&node][0]
+            .texture
+            .as_ref()
+            .unwrap()
+            .data_ref()
+            .data()
+            .to_vec()
+    }
+
+    /// Average value of the given channel of RGB8 pixels.
+    fn channel_mean(pixels: &[u8], channel: usize) -> f32 {
+        let values = pixels.iter().skip(channel).step_by(3);
+        values.clone().map(|v| *v as f32).sum::<f32>() / values.count() as f32
+    }
+
+    fn mean(pixels: &[u8]) -> f32 {
+        pixels.iter().map(|v| *v as f32).sum::<f32>() / pixels.len() as f32
+    }
+
+    #[test]
+    fn test_cornell_box_lighting() {
+        let (direct_box, direct) = bake_cornell_box(0, false);
+        let (indirect_box, indirect) = bake_cornell_box(1, false);
+        let (occluded_box, occluded) = bake_cornell_box(0, true);
+
+        // Generation is deterministic, so the same scene gives exactly the same lightmaps.
+        let (same_box, same) = bake_cornell_box(1, true);
+        let (other_box, other) = bake_cornell_box(1, true);
+        for (a, b) in [
+            (same_box.floor, other_box.floor),
+            (same_box.red_wall, other_box.red_wall),
+            (same_box.green_wall, other_box.green_wall),
+        ] {
+            assert_eq!(pixels(&same, a), pixels(&other, b));
+        }
+
+        // The light is white, so direct lighting is gray.
+        let red_wall = pixels(&direct, direct_box.red_wall);
+        assert_eq!(channel_mean(&red_wall, 0), channel_mean(&red_wall, 1));
+
+        // Colored walls reflect their color to the opposite walls.
+        let red_wall = pixels(&indirect, indirect_box.red_wall);
+        assert!(channel_mean(&red_wall, 1) > channel_mean(&red_wall, 0) + 2.0);
+        let green_wall = pixels(&indirect, indirect_box.green_wall);
+        assert!(channel_mean(&green_wall, 0) > channel_mean(&green_wall, 1) + 2.0);
+
+        // Surfaces reflect only a part of the incoming light, so the bounced light is weaker than
+        // the direct light.
+        let direct_floor = pixels(&direct, direct_box.floor);
+        let indirect_floor = pixels(&indirect, indirect_box.floor);
+        let bounced = mean(&indirect_floor) - mean(&direct_floor);
+        assert!(bounced > 1.0);
+        assert!(bounced < mean(&direct_floor));
+
+        // Ambient occlusion only darkens the lighting, mostly near the walls.
+        let occluded_floor = pixels(&occluded, occluded_box.floor);
+        assert!(direct_floor
+            .iter()
+            .zip(occluded_floor.iter())
+            .all(|(direct, occluded)| occluded <= direct));
+        assert!(mean(&occluded_floor) < mean(&direct_floor) - 1.0);
+    }
 }
@@ -2,12 +2,21 @@
 
 //! Utilities module provides set of commonly used algorithms.
 
+pub mod aabb;
 pub mod astar;
 pub mod behavior;
 pub mod component;
+pub mod crowd;
+pub mod curve;
+pub mod environment_map;
+pub mod input;
 pub mod lightmap;
 pub mod navmesh;
+pub mod object_pool;
 pub mod raw_mesh;
+pub mod rng;
+pub mod scene_viewport;
+pub mod small_vec;
 pub mod uvgen;
 
 use crate::{
@@ -20,7 +29,8 @@ use crate::{
     keyboard::{KeyCode, ModifiersState},
     resource::texture::TextureResource,
 };
-use fyrox_ui::message::CursorIcon;
+use fyrox_sound::source::SoundSource;
+use fyrox_ui::{message::CursorIcon, UiNode};
 use half::f16;
 use std::{any::Any, hash::Hasher, sync::Arc};
 
@@ -602,6 +612,41 @@ where
     iter.find(|(_, value)| value.name() == name.as_ref())
 }
 
+/// Finds every entity with the given name in a series of entities produced by an iterator.
+pub fn find_all_by_name_ref<'a, T, I, S, K>(iter: I, name: S) -> impl Iterator<Item = (K, &'a T)>
+where
+    T: NameProvider + 'a,
+    I: Iterator<Item = (K, &'a T)>,
+    S: AsRef<str>,
+{
+    iter.filter(move |(_, value)| value.name() == name.as_ref())
+}
+
+/// Finds every entity with the given name in a series of entities produced by an iterator.
+pub fn find_all_by_name_mut<'a, T, I, S, K>(
+    iter: I,
+    name: S,
+) -> impl Iterator<Item = (K, &'a mut T)>
+where
+    T: NameProvider + 'a,
+    I: Iterator<Item = (K, &'a mut T)>,
+    S: AsRef<str>,
+{
+    iter.filter(move |(_, value)| value.name() == name.as_ref())
+}
+
+impl NameProvider for UiNode {
+    fn name(&self) -> &str {
+        self.0.name()
+    }
+}
+
+impl NameProvider for SoundSource {
+    fn name(&self) -> &str {
+        SoundSource::name(self)
+    }
+}
+
 /// Converts `Vector3<f32>` -> `Vector3<f16>`.
 pub fn vec3_f16_from_f32(v: Vector3<f32>) -> Vector3<f16> {
     v.map(f16::from_f32)
@@ -611,3 +656,53 @@ pub fn vec3_f16_from_f32(v: Vector3<f32>) -> Vector3<f16> {
 pub fn vec3_f32_from_f16(v: Vector3<f16>) -> Vector3<f32> {
     v.map(|v| v.to_f32())
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        animation::{Animation, AnimationContainer},
+        scene::{base::BaseBuilder, graph::Graph, pivot::PivotBuilder},
+        utils::{find_all_by_name_ref, find_by_name_mut, find_by_name_ref},
+    };
+
+    #[test]
+    fn test_find_by_name() {
+        let mut graph = Graph::new();
+        let a = PivotBuilder::new(BaseBuilder::new().with_name("A")).build(&mut graph);
+        let b = PivotBuilder::new(BaseBuilder::new().with_name("B")).build(&mut graph);
+        let other_b = PivotBuilder::new(BaseBuilder::new().with_name("B")).build(&mut graph);
+
+        assert_eq!(find_by_name_ref(graph.pair_iter(), "A").unwrap().0, a);
+        assert_eq!(find_by_name_mut(graph.pair_iter_mut(), "B").unwrap().0, b);
+        assert!(find_by_name_ref(graph.pair_iter(), "C").is_none());
+        assert_eq!(
+            find_all_by_name_ref(graph.pair_iter(), "B")
+                .map(|(handle, _)| handle)
+                .collect::<Vec<_>>(),
+            vec![b, other_b]
+        );
+
+        let mut animations = AnimationContainer::new();
+        let mut walk = Animation::default();
+        walk.set_name("Walk");
+        let walk = animations.add(walk);
+        let mut run = Animation::default();
+        run.set_name("Run");
+        let run = animations.add(run);
+
+        assert_eq!(
+            find_by_name_ref(animations.pair_iter(), "Walk").unwrap().0,
+            walk
+        );
+        assert_eq!(
+            find_by_name_mut(animations.pair_iter_mut(), "Run")
+                .unwrap()
+                .0,
+            run
+        );
+        assert_eq!(
+            find_all_by_name_ref(animations.pair_iter(), "Jump").count(),
+            0
+        );
+    }
+}
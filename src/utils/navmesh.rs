@@ -7,24 +7,129 @@
 
 use crate::{
     core::{
-        algebra::{Point3, Vector3},
+        algebra::{Point3, Vector2, Vector3},
         arrayvec::ArrayVec,
-        math::{self, ray::Ray, TriangleDefinition},
+        color::Color,
+        math::{self, aabb::AxisAlignedBoundingBox, ray::Ray, TriangleDefinition, TriangleEdge},
         octree::{Octree, OctreeNode},
         pool::Handle,
         reflect::prelude::*,
         visitor::{Visit, VisitResult, Visitor},
     },
-    scene::mesh::{
-        buffer::{VertexAttributeUsage, VertexReadTrait},
-        Mesh,
+    scene::{
+        debug::{Line, SceneDrawingContext},
+        mesh::{
+            buffer::{VertexAttributeUsage, VertexReadTrait},
+            Mesh,
+        },
     },
     utils::{
-        astar::{PathError, PathFinder, PathKind, PathVertex},
+        astar::{PathError, PathFinder, PathKind, PathVertex, PathfindingContext},
         raw_mesh::{RawMeshBuilder, RawVertex},
+        small_vec::{ResultBuffer, SmallVec},
     },
 };
-use fxhash::FxHashSet;
+use fxhash::{FxHashMap, FxHashSet};
+use std::cell::RefCell;
+
+/// A set of area types, every area type is a number in `0..=255` range. See [`QueryFilter`] docs
+/// for more info.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct AreaSet([u64; 4]);
+
+impl AreaSet {
+    /// Creates a set with all possible area types.
+    pub fn all() -> Self {
+        Self([u64::MAX; 4])
+    }
+
+    /// Creates an empty set.
+    pub fn empty() -> Self {
+        Self([0; 4])
+    }
+
+    /// Adds the area type to the set.
+    pub fn insert(&mut self, area: u8) {
+        self.0[(area / 64) as usize] |= 1 << (area % 64);
+    }
+
+    /// Removes the area type from the set.
+    pub fn remove(&mut self, area: u8) {
+        self.0[(area / 64) as usize] &= !(1 << (area % 64));
+    }
+
+    /// Returns `true` if the set contains the area type.
+    pub fn contains(&self, area: u8) -> bool {
+        self.0[(area / 64) as usize] & (1 << (area % 64)) != 0
+    }
+}
+
+impl FromIterator<u8> for AreaSet {
+    fn from_iter<T: IntoIterator<Item = u8>>(iter: T) -> Self {
+        let mut set = Self::empty();
+        for area in iter {
+            set.insert(area);
+        }
+        set
+    }
+}
+
+/// Query filter defines how an agent treats navmesh areas during path finding. Every navmesh triangle
+/// has an area type (see [`Navmesh::set_triangle_area`]), the filter defines which area types can be
+/// traversed and how expensive it is. For example, a zombie could use a filter without "door" area,
+/// while a human could use all the areas, but avoid "water" area by using high cost multiplier for it.
+///
+/// ```rust
+/// use fyrox::utils::navmesh::{AreaSet, QueryFilter};
+///
+/// const GROUND: u8 = 0;
+/// const WATER: u8 = 1;
+/// const DOOR: u8 = 2;
+///
+/// let zombie = QueryFilter {
+///     allowed: [GROUND, WATER].into_iter().collect::<AreaSet>(),
+///     ..Default::default()
+/// };
+/// assert!(!zombie.is_allowed(DOOR));
+///
+/// let human = QueryFilter::default().with_cost(WATER, 10.0);
+/// assert_eq!(human.cost(WATER), 10.0);
+/// assert_eq!(human.cost(GROUND), 1.0);
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct QueryFilter {
+    /// A set of area types that can be traversed.
+    pub allowed: AreaSet,
+    /// Cost multipliers of area types. Area types that are not in the map have cost multiplier of 1.0.
+    pub costs: FxHashMap<u8, f32>,
+}
+
+impl Default for QueryFilter {
+    fn default() -> Self {
+        Self {
+            allowed: AreaSet::all(),
+            costs: Default::default(),
+        }
+    }
+}
+
+impl QueryFilter {
+    /// Sets the cost multiplier of the area type.
+    pub fn with_cost(mut self, area: u8, cost: f32) -> Self {
+        self.costs.insert(area, cost);
+        self
+    }
+
+    /// Returns `true` if the area type can be traversed.
+    pub fn is_allowed(&self, area: u8) -> bool {
+        self.allowed.contains(area)
+    }
+
+    /// Returns the cost multiplier of the area type.
+    pub fn cost(&self, area: u8) -> f32 {
+        self.costs.get(&area).cloned().unwrap_or(1.0)
+    }
+}
 
 /// See module docs.
 #[derive(Clone, Debug, Default, Reflect)]
@@ -32,13 +137,70 @@ use fxhash::FxHashSet;
 pub struct Navmesh {
     octree: Octree,
     triangles: Vec<TriangleDefinition>,
+    areas: Vec<u8>,
     pathfinder: PathFinder,
     query_buffer: Vec<u32>,
+    // Calculated on demand and reset on every change of the connectivity.
+    islands: RefCell<Option<Islands>>,
+}
+
+/// Connected components of the navigational graph.
+#[derive(Clone, Debug, Default)]
+struct Islands {
+    vertex_islands: Vec<u32>,
+    count: usize,
+}
+
+impl Islands {
+    fn new(pathfinder: &PathFinder) -> Self {
+        fn find(parents: &mut [u32], mut index: u32) -> u32 {
+            while parents[index as usize] != index {
+                // Path halving keeps the trees flat.
+                let grandparent = parents[parents[index as usize] as usize];
+                parents[index as usize] = grandparent;
+                index = grandparent;
+            }
+            index
+        }
+
+        let vertices = pathfinder.vertices();
+        let mut parents = (0..vertices.len() as u32).collect::<Vec<_>>();
+        for (index, vertex) in vertices.iter().enumerate() {
+            for &neighbour in vertex.neighbours() {
+                if (neighbour as usize) < vertices.len() {
+                    let a = find(&mut parents, index as u32);
+                    let b = find(&mut parents, neighbour);
+                    // Attach to the smaller root to make island ids independent of link order.
+                    parents[a.max(b) as usize] = a.min(b);
+                }
+            }
+        }
+
+        // Number islands in order of their first vertex.
+        let mut ids = vec![u32::MAX; vertices.len()];
+        let mut count = 0;
+        let mut vertex_islands = Vec::with_capacity(vertices.len());
+        for index in 0..vertices.len() as u32 {
+            let root = find(&mut parents, index) as usize;
+            if ids[root] == u32::MAX {
+                ids[root] = count as u32;
+                count += 1;
+            }
+            vertex_islands.push(ids[root]);
+        }
+
+        Self {
+            vertex_islands,
+            count,
+        }
+    }
 }
 
 impl PartialEq for Navmesh {
     fn eq(&self, other: &Self) -> bool {
-        self.triangles == other.triangles && self.pathfinder == other.pathfinder
+        self.triangles == other.triangles
+            && self.areas == other.areas
+            && self.pathfinder == other.pathfinder
     }
 }
 
@@ -48,25 +210,17 @@ impl Visit for Navmesh {
 
         self.pathfinder.visit("PathFinder", &mut region)?;
         self.triangles.visit("Triangles", &mut region)?;
+        let _ = self.areas.visit("Areas", &mut region);
 
         drop(region);
 
         // No need to save octree, we can restore it on load.
         if visitor.is_reading() {
-            let vertices = self.pathfinder.vertices();
-            let raw_triangles = self
-                .triangles
-                .iter()
-                .map(|t| {
-                    [
-                        vertices[t[0] as usize].position,
-                        vertices[t[1] as usize].position,
-                        vertices[t[2] as usize].position,
-                    ]
-                })
-                .collect::<Vec<[Vector3<f32>; 3]>>();
+            // Navmeshes saved without area types have default area type on every triangle.
+            self.areas.resize(self.triangles.len(), 0);
 
-            self.octree = Octree::new(&raw_triangles, 32);
+            self.rebuild_octree();
+            self.invalidate_islands();
         }
 
         Ok(())
@@ -107,9 +261,11 @@ impl Navmesh {
 
         Self {
             triangles: triangles.to_vec(),
+            areas: vec![0; triangles.len()],
             octree: Octree::new(&raw_triangles, 32),
             pathfinder,
             query_buffer: Default::default(),
+            islands: Default::default(),
         }
     }
 
@@ -220,13 +376,30 @@ impl Navmesh {
                 .link_bidirect(edge.a as usize, edge.b as usize);
         }
         self.triangles.push(triangle);
+        self.areas.push(0);
+        self.invalidate_islands();
         index as u32
     }
 
+    /// Sets area type of a triangle at the given index. Area types are used to filter and weight triangles
+    /// during path finding, see [`QueryFilter`] docs for more info. Default area type is 0.
+    pub fn set_triangle_area(&mut self, index: usize, area: u8) {
+        if let Some(triangle_area) = self.areas.get_mut(index) {
+            *triangle_area = area;
+        }
+    }
+
+    /// Returns area type of a triangle at the given index.
+    pub fn triangle_area(&self, index: usize) -> Option<u8> {
+        self.areas.get(index).cloned()
+    }
+
     /// Removes a triangle at the given index from the navigational mesh. Automatically fixes vertex links in the
     /// internal navigational graph.
     pub fn remove_triangle(&mut self, index: usize) -> TriangleDefinition {
         let triangle = self.triangles.remove(index);
+        self.areas.remove(index);
+        self.invalidate_islands();
         for &vertex_index in triangle.indices() {
             let mut isolated = true;
             for other_triangle in self.triangles.iter() {
@@ -302,9 +475,154 @@ impl Navmesh {
             }
         }
 
+        self.invalidate_islands();
         self.pathfinder.remove_vertex(index)
     }
 
+    /// Replaces a part of the navmesh inside the given bounds with a new set of triangles. It is much
+    /// faster than building the whole navmesh from scratch, so it could be used to update the navmesh
+    /// after local geometry changes (a destroyed wall, an opened door, etc.).
+    ///
+    /// Triangles that have all their vertices inside the bounds are removed. New triangles are stitched
+    /// to the rest of the navmesh: their vertices are merged with the vertices of the remaining triangles
+    /// at the same positions, so the new triangles must share border vertices with the unchanged part of
+    /// the navmesh to be connected with it. Links of the navigational graph are fixed only around the
+    /// region. Remaining triangles keep their order and area types, new triangles are added at the end
+    /// with default area type. Returns the number of removed triangles.
+    pub fn rebuild_region(
+        &mut self,
+        bounds: &AxisAlignedBoundingBox,
+        new_triangles: &[[Vector3<f32>; 3]],
+    ) -> usize {
+        // Vertices that lie exactly on the bounds must be treated as inside.
+        let mut bounds = *bounds;
+        bounds.inflate(Vector3::repeat(WELD_CELL_SIZE));
+
+        let vertices = self.pathfinder.vertices();
+
+        // Vertices of removed and added triangles, their links must be rebuilt.
+        let mut affected = FxHashSet::default();
+
+        let mut removed_count = 0;
+        let mut triangles = Vec::with_capacity(self.triangles.len() + new_triangles.len());
+        let mut areas = Vec::with_capacity(self.triangles.len() + new_triangles.len());
+        for (triangle, area) in self.triangles.iter().zip(self.areas.iter()) {
+            if triangle
+                .indices()
+                .iter()
+                .all(|i| bounds.is_contains_point(vertices[*i as usize].position))
+            {
+                affected.extend(triangle.indices().iter().cloned());
+                removed_count += 1;
+            } else {
+                triangles.push(*triangle);
+                areas.push(*area);
+            }
+        }
+
+        // Border vertices of the remaining triangles, new triangles will be welded to them.
+        let mut grid = WeldGrid::default();
+        for triangle in triangles.iter() {
+            for &index in triangle.indices() {
+                let position = vertices[index as usize].position;
+                if bounds.is_contains_point(position) && grid.find(vertices, position).is_none() {
+                    grid.insert(position, index);
+                }
+            }
+        }
+
+        for positions in new_triangles {
+            let mut indices = [0; 3];
+            for (index, &position) in indices.iter_mut().zip(positions.iter()) {
+                *index = match grid.find(self.pathfinder.vertices(), position) {
+                    Some(existing) => existing,
+                    None => {
+                        let new_index = self.pathfinder.add_vertex(PathVertex::new(position));
+                        grid.insert(position, new_index);
+                        new_index
+                    }
+                };
+                affected.insert(*index);
+            }
+
+            // Skip triangles that were collapsed by welding.
+            if indices[0] != indices[1] && indices[1] != indices[2] && indices[0] != indices[2] {
+                triangles.push(TriangleDefinition(indices));
+                areas.push(0);
+            }
+        }
+
+        // Unlink affected vertices from the graph and link them again using the edges of the triangles
+        // around the region.
+        for &index in affected.iter() {
+            if let Some(vertex) = self.pathfinder.vertex_mut(index as usize) {
+                for neighbour_index in std::mem::take(&mut vertex.neighbours) {
+                    if let Some(neighbour) = self.pathfinder.vertex_mut(neighbour_index as usize) {
+                        neighbour.neighbours.retain(|n| *n != index);
+                    }
+                }
+            }
+        }
+
+        let mut used = FxHashSet::default();
+        for triangle in triangles.iter() {
+            if triangle.indices().iter().any(|i| affected.contains(i)) {
+                used.extend(triangle.indices().iter().cloned());
+                for edge in triangle.edges() {
+                    self.pathfinder
+                        .link_bidirect(edge.a as usize, edge.b as usize);
+                }
+            }
+        }
+
+        // Remove vertices that are not used by any triangle anymore.
+        if affected.iter().any(|i| !used.contains(i)) {
+            let mut remap = vec![u32::MAX; self.pathfinder.vertices().len()];
+            let mut vertices = Vec::with_capacity(self.pathfinder.vertices().len());
+            for (index, vertex) in self.pathfinder.vertices().iter().enumerate() {
+                if !affected.contains(&(index as u32)) || used.contains(&(index as u32)) {
+                    remap[index] = vertices.len() as u32;
+                    vertices.push(vertex.clone());
+                }
+            }
+            for vertex in vertices.iter_mut() {
+                for neighbour in vertex.neighbours.iter_mut() {
+                    *neighbour = remap[*neighbour as usize];
+                }
+            }
+            for triangle in triangles.iter_mut() {
+                for index in triangle.indices_mut() {
+                    *index = remap[*index as usize];
+                }
+            }
+            self.pathfinder.set_vertices(vertices);
+        }
+
+        self.triangles = triangles;
+        self.areas = areas;
+        self.rebuild_octree();
+        self.invalidate_islands();
+
+        removed_count
+    }
+
+    fn rebuild_octree(&mut self) {
+        let vertices = self.pathfinder.vertices();
+        let raw_triangles = self
+            .triangles
+            .iter()
+            .map(|t| {
+                [
+                    vertices[t[0] as usize].position,
+                    vertices[t[1] as usize].position,
+                    vertices[t[2] as usize].position,
+                ]
+            })
+            .collect::<Vec<[Vector3<f32>; 3]>>();
+
+        self.octree = Octree::new(&raw_triangles, 32);
+    }
+
     /// Returns reference to the internal array of vertices.
     pub fn vertices(&self) -> &[PathVertex] {
         self.pathfinder.vertices()
@@ -317,6 +635,7 @@ impl Navmesh {
 
     /// Adds the vertex to the navigational mesh. The vertex will **not** be connected with any other vertex.
     pub fn add_vertex(&mut self, vertex: PathVertex) -> u32 {
+        self.invalidate_islands();
         self.pathfinder.add_vertex(vertex)
     }
 
@@ -332,6 +651,7 @@ impl Navmesh {
     /// Inserts the vertex at the given index. Automatically shift indices in triangles to preserve mesh structure.
     pub fn insert_vertex(&mut self, index: u32, vertex: PathVertex) {
         self.pathfinder.insert_vertex(index, vertex);
+        self.invalidate_islands();
 
         // Shift vertex indices in triangles. Example:
         //
@@ -358,7 +678,59 @@ impl Navmesh {
         &self.octree
     }
 
-    /// Tries to build path using indices of begin and end points.
+    fn invalidate_islands(&mut self) {
+        *self.islands.get_mut() = None;
+    }
+
+    fn with_islands<T>(&self, func: impl FnOnce(&Islands) -> T) -> T {
+        let mut islands = self.islands.borrow_mut();
+        func(islands.get_or_insert_with(|| Islands::new(&self.pathfinder)))
+    }
+
+    /// Returns the amount of islands of the navmesh. An island is a set of triangles, that are connected
+    /// with each other, but not with the triangles of other islands (for example, a rooftop that cannot be
+    /// reached from the ground). Islands are calculated on the first query and cached until the next change
+    /// of the navmesh, so every island query is O(1) after that.
+    pub fn island_count(&self) -> usize {
+        self.with_islands(|islands| islands.count)
+    }
+
+    /// Returns the id of an island of a vertex at the given index, or `None` if the index is out of
+    /// bounds. Island ids are in `0..island_count` range, see [`Self::island_count`] for more info.
+    pub fn vertex_island(&self, index: usize) -> Option<usize> {
+        self.with_islands(|islands| islands.vertex_islands.get(index).map(|i| *i as usize))
+    }
+
+    /// Returns the id of an island of a triangle at the given index, or `None` if the index is out of
+    /// bounds. Island ids are in `0..island_count` range, see [`Self::island_count`] for more info.
+    pub fn triangle_island(&self, index: usize) -> Option<usize> {
+        self.triangles
+            .get(index)
+            .and_then(|triangle| self.vertex_island(triangle[0] as usize))
+    }
+
+    /// Returns `true` if both triangles are on the same island, which means that there is a path from one
+    /// triangle to the other. Returns `false` if any of the indices is out of bounds.
+    pub fn same_island(&self, triangle_a: usize, triangle_b: usize) -> bool {
+        match (
+            self.triangle_island(triangle_a),
+            self.triangle_island(triangle_b),
+        ) {
+            (Some(a), Some(b)) => a == b,
+            _ => false,
+        }
+    }
+
+    fn check_reachable(&self, from: usize, to: usize) -> Result<(), PathError> {
+        match (self.vertex_island(from), self.vertex_island(to)) {
+            (Some(a), Some(b)) if a != b => Err(PathError::Unreachable(from, to)),
+            // Invalid indices are reported by the path finder.
+            _ => Ok(()),
+        }
+    }
+
+    /// Tries to build path using indices of begin and end points. Fails with [`PathError::Unreachable`]
+    /// without any search, if the points are on different islands (see [`Self::island_count`]).
     ///
     /// Example:
     ///
@@ -376,15 +748,67 @@ impl Navmesh {
     ///     Ok(PathKind::Empty)
     /// }
     /// ```
-    pub fn build_path(
+    pub fn build_path<P>(
         &mut self,
         from: usize,
         to: usize,
-        path: &mut Vec<Vector3<f32>>,
-    ) -> Result<PathKind, PathError> {
+        path: &mut P,
+    ) -> Result<PathKind, PathError>
+    where
+        P: ResultBuffer<Vector3<f32>>,
+    {
+        self.check_reachable(from, to)?;
         self.pathfinder.build(from, to, path)
     }
 
+    /// Same as [`Self::build_path`], but uses the given filter to decide which triangles can be traversed
+    /// and how expensive it is. A link between two vertices can be traversed only if at least one of the
+    /// triangles sharing it is allowed by the filter, the cheapest of such triangles defines the cost of the
+    /// link. See [`QueryFilter`] docs for more info.
+    ///
+    /// # Notes
+    ///
+    /// Link costs are calculated on every call, so the method is O(n) in the number of triangles at least.
+    pub fn build_path_filtered<P>(
+        &self,
+        from: usize,
+        to: usize,
+        path: &mut P,
+        filter: &QueryFilter,
+    ) -> Result<PathKind, PathError>
+    where
+        P: ResultBuffer<Vector3<f32>>,
+    {
+        self.check_reachable(from, to)?;
+
+        let mut edge_costs = FxHashMap::<TriangleEdge, f32>::default();
+        for (triangle, &area) in self.triangles.iter().zip(self.areas.iter()) {
+            if filter.is_allowed(area) {
+                let cost = filter.cost(area);
+                for edge in triangle.edges() {
+                    let edge_cost = edge_costs.entry(edge).or_insert(cost);
+                    *edge_cost = edge_cost.min(cost);
+                }
+            }
+        }
+
+        self.pathfinder.build_and_convert_filtered_in(
+            &mut PathfindingContext::new(),
+            from,
+            to,
+            path,
+            |_, v| v.position,
+            |a, b| {
+                edge_costs
+                    .get(&TriangleEdge {
+                        a: a as u32,
+                        b: b as u32,
+                    })
+                    .cloned()
+            },
+        )
+    }
+
     /// Tries to pick a triangle by given ray. Returns closest result.
     pub fn ray_cast(&self, ray: Ray) -> Option<(Vector3<f32>, usize, TriangleDefinition)> {
         let mut buffer = ArrayVec::<Handle<OctreeNode>, 128>::new();
@@ -416,6 +840,578 @@ impl Navmesh {
 
         result
     }
+
+    fn probe_triangle(&self, point: Vector3<f32>) -> Option<(Vector3<f32>, usize)> {
+        self.ray_cast(Ray::new(
+            point + Vector3::new(0.0, 1.0, 0.0),
+            Vector3::new(0.0, -10.0, 0.0),
+        ))
+        .map(|(intersection, index, _)| (intersection, index))
+    }
+
+    /// Tries to build a corridor - a sequence of adjacent triangles that connects triangles under
+    /// `from` and `to` points. Unlike [`Self::build_path`], the result is not a list of waypoints,
+    /// but a strip of triangles which can be used for local steering inside of it. See [`Corridor`]
+    /// docs for more info.
+    ///
+    /// Returns `None` if any of the points does not lie above (or slightly below) the navmesh, or
+    /// if there is no path between the points.
+    ///
+    /// # Notes
+    ///
+    /// The triangle adjacency graph is built on every call, so the method is O(n) in the number of
+    /// triangles at least.
+    pub fn build_corridor(&self, from: Vector3<f32>, to: Vector3<f32>) -> Option<Corridor> {
+        let (begin, from_triangle) = self.probe_triangle(from)?;
+        let (end, to_triangle) = self.probe_triangle(to)?;
+
+        let vertices = self.pathfinder.vertices();
+        let triangle_points = |index: usize| {
+            let triangle = &self.triangles[index];
+            [
+                vertices[triangle[0] as usize].position,
+                vertices[triangle[1] as usize].position,
+                vertices[triangle[2] as usize].position,
+            ]
+        };
+
+        let mut triangle_indices = Vec::new();
+        if from_triangle == to_triangle {
+            triangle_indices.push(from_triangle);
+        } else {
+            // Build dual graph of the navmesh, where each triangle is a vertex and each shared edge
+            // is a link between two vertices.
+            let mut edge_triangles = FxHashMap::<TriangleEdge, Vec<usize>>::default();
+            for (index, triangle) in self.triangles.iter().enumerate() {
+                for edge in triangle.edges() {
+                    edge_triangles.entry(edge).or_default().push(index);
+                }
+            }
+
+            let mut dual_graph = PathFinder::new();
+            dual_graph.set_vertices(
+                (0..self.triangles.len())
+                    .map(|index| {
+                        let [a, b, c] = triangle_points(index);
+                        PathVertex::new((a + b + c).scale(1.0 / 3.0))
+                    })
+                    .collect(),
+            );
+            for triangles in edge_triangles.values() {
+                for (i, a) in triangles.iter().enumerate() {
+                    for b in triangles.iter().skip(i + 1) {
+                        dual_graph.link_bidirect(*a, *b);
+                    }
+                }
+            }
+
+            if dual_graph
+                .build_and_convert(from_triangle, to_triangle, &mut triangle_indices, |i, _| i)
+                .ok()?
+                != PathKind::Full
+            {
+                return None;
+            }
+
+            triangle_indices.reverse();
+        }
+
+        let mut portals = Vec::with_capacity(triangle_indices.len().saturating_sub(1));
+        for pair in triangle_indices.windows(2) {
+            let current = &self.triangles[pair[0]];
+            let next = &self.triangles[pair[1]];
+
+            let mut shared = current
+                .indices()
+                .iter()
+                .filter(|i| next.indices().contains(i))
+                .map(|i| vertices[*i as usize].position);
+            let (a, b) = match (shared.next(), shared.next()) {
+                (Some(a), Some(b)) => (a, b),
+                _ => return None,
+            };
+
+            // Sort portal points so the left one is always on the left side when looking from the
+            // current triangle through the portal.
+            let [ta, tb, tc] = triangle_points(pair[0]);
+            let center = (ta + tb + tc).scale(1.0 / 3.0);
+            if triangle_area_2d(center, a, b) < 0.0 {
+                portals.push((b, a));
+            } else {
+                portals.push((a, b));
+            }
+        }
+
+        Some(Corridor {
+            polygons: triangle_indices
+                .iter()
+                .map(|i| triangle_points(*i))
+                .collect(),
+            triangles: triangle_indices,
+            portals,
+            begin,
+            end,
+        })
+    }
+
+    /// Checks whether it is possible to walk in a straight line from `from` to `to` without leaving
+    /// the navmesh. Unlike [`Self::ray_cast`], which intersects a ray with the triangles, this method
+    /// walks across adjacent triangles along the segment (projected on XZ plane), so it is much
+    /// cheaper than [`Self::build_path`] and could be used for line-of-movement checks and local
+    /// obstacle avoidance. See [`RaycastResult`] docs for more info.
+    pub fn raycast(&self, from: Vector3<f32>, to: Vector3<f32>) -> RaycastResult {
+        let (begin, mut current) = match self.probe_triangle(from) {
+            Some(probe) => probe,
+            None => return RaycastResult::OffMesh,
+        };
+
+        let vertices = self.pathfinder.vertices();
+        let position = |index: u32| vertices[index as usize].position;
+        let flat = |v: Vector3<f32>| Vector2::new(v.x, v.z);
+        let cross = |a: Vector2<f32>, b: Vector2<f32>| a.x * b.y - a.y * b.x;
+
+        let origin = flat(begin);
+        let dir = flat(to) - origin;
+        let eps = 0.0001;
+
+        let mut entry_edge = None;
+        let mut fraction = 0.0f32;
+        let mut buffer = Vec::new();
+
+        // Every triangle could be visited only once, the limit protects from infinite loops on
+        // malformed meshes.
+        for _ in 0..=self.triangles.len() {
+            let triangle = &self.triangles[current];
+            let [a, b, c] = [
+                position(triangle[0]),
+                position(triangle[1]),
+                position(triangle[2]),
+            ];
+
+            let (u, v, w) = math::get_barycentric_coords_2d(flat(to), flat(a), flat(b), flat(c));
+            if u >= -eps && v >= -eps && w >= -eps {
+                return RaycastResult::Walkable {
+                    triangle: current,
+                    end: Vector3::new(to.x, a.y * u + b.y * v + c.y * w, to.z),
+                };
+            }
+
+            // Find an edge through which the segment leaves the triangle, it is the farthest one
+            // along the segment.
+            let mut exit = None;
+            for edge in triangle.edges() {
+                if entry_edge == Some(edge) {
+                    continue;
+                }
+
+                let (ea, eb) = (position(edge.a), position(edge.b));
+                let edge_dir = flat(eb) - flat(ea);
+                let denominator = cross(dir, edge_dir);
+                if denominator.abs() <= f32::EPSILON {
+                    continue;
+                }
+                let offset = flat(ea) - origin;
+                let t = cross(offset, edge_dir) / denominator;
+                let s = cross(offset, dir) / denominator;
+                if (-eps..=1.0 + eps).contains(&s)
+                    && t >= fraction - eps
+                    && exit.map_or(true, |(_, exit_t, _)| t > exit_t)
+                {
+                    exit = Some((edge, t, ea.lerp(&eb, s.clamp(0.0, 1.0))));
+                }
+            }
+
+            let (edge, t, point) = match exit {
+                Some(exit) => exit,
+                None => {
+                    // Degenerate triangle, treat its entry edge as a boundary.
+                    let edge = entry_edge.unwrap_or(triangle.edges()[0]);
+                    return RaycastResult::Blocked {
+                        triangle: current,
+                        edge,
+                        point: begin + (to - begin).scale(fraction),
+                        fraction,
+                    };
+                }
+            };
+
+            // Find a triangle on the other side of the edge. Its bounds contain the middle of the
+            // edge, so the octree can be used to find it quickly.
+            self.octree
+                .point_query(position(edge.a).lerp(&position(edge.b), 0.5), &mut buffer);
+            let neighbour = buffer.iter().map(|i| *i as usize).find(|&i| {
+                i != current
+                    && self.triangles[i].indices().contains(&edge.a)
+                    && self.triangles[i].indices().contains(&edge.b)
+            });
+
+            match neighbour {
+                Some(neighbour) => {
+                    current = neighbour;
+                    entry_edge = Some(edge);
+                    fraction = t;
+                }
+                None => {
+                    return RaycastResult::Blocked {
+                        triangle: current,
+                        edge,
+                        point,
+                        fraction: t.clamp(0.0, 1.0),
+                    }
+                }
+            }
+        }
+
+        RaycastResult::OffMesh
+    }
+
+    /// Creates a set of lines for every edge of the navmesh, which could be used to visualize it
+    /// (for example using [`SceneDrawingContext`]). Every edge is added only once. Edges of triangles
+    /// that have a color for their area type in [`NavmeshDebugColors::areas`] use that color, other
+    /// edges use either [`NavmeshDebugColors::boundary`] or [`NavmeshDebugColors::portal`] color,
+    /// depending on whether the edge is shared by multiple triangles or not.
+    pub fn to_debug_lines(&self, colors: &NavmeshDebugColors) -> Vec<Line> {
+        let mut edge_triangles = FxHashMap::<TriangleEdge, Vec<usize>>::default();
+        for (index, triangle) in self.triangles.iter().enumerate() {
+            for edge in triangle.edges() {
+                edge_triangles.entry(edge).or_default().push(index);
+            }
+        }
+
+        let vertices = self.pathfinder.vertices();
+        let mut lines = Vec::with_capacity(edge_triangles.len());
+        // Iterate over triangles instead of the map to keep the order of lines stable.
+        for triangle in self.triangles.iter() {
+            for edge in triangle.edges() {
+                let triangles = if let Some(triangles) = edge_triangles.remove(&edge) {
+                    triangles
+                } else {
+                    continue;
+                };
+
+                let color = triangles
+                    .iter()
+                    .find_map(|i| colors.areas.get(&self.areas[*i]))
+                    .cloned()
+                    .unwrap_or(if triangles.len() > 1 {
+                        colors.portal
+                    } else {
+                        colors.boundary
+                    });
+
+                lines.push(Line {
+                    begin: vertices[edge.a as usize].position,
+                    end: vertices[edge.b as usize].position,
+                    color,
+                });
+            }
+        }
+
+        lines
+    }
+
+    /// Adds the lines of the navmesh to the given drawing context, see [`Self::to_debug_lines`] for
+    /// more info.
+    pub fn debug_draw(&self, ctx: &mut SceneDrawingContext, colors: &NavmeshDebugColors) {
+        ctx.lines.extend(self.to_debug_lines(colors));
+    }
+}
+
+/// Colors of navmesh edges, see [`Navmesh::to_debug_lines`] for more info.
+#[derive(Clone, Debug, PartialEq)]
+pub struct NavmeshDebugColors {
+    /// Color of edges that belong to a single triangle, an agent can't leave the navmesh through them.
+    pub boundary: Color,
+    /// Color of edges shared by multiple triangles, an agent can move through them.
+    pub portal: Color,
+    /// Colors of edges of triangles with specific area types. For example, triangles of "door" area
+    /// could be highlighted with a distinct color.
+    pub areas: FxHashMap<u8, Color>,
+}
+
+impl Default for NavmeshDebugColors {
+    fn default() -> Self {
+        Self {
+            boundary: Color::opaque(255, 60, 0),
+            portal: Color::opaque(0, 200, 255),
+            areas: Default::default(),
+        }
+    }
+}
+
+impl NavmeshDebugColors {
+    /// Sets the color of edges of triangles with the given area type.
+    pub fn with_area_color(mut self, area: u8, color: Color) -> Self {
+        self.areas.insert(area, color);
+        self
+    }
+}
+
+/// Result of [`Navmesh::raycast`] query.
+#[derive(Clone, Debug, PartialEq)]
+pub enum RaycastResult {
+    /// Start point does not lie above (or slightly below) the navmesh.
+    OffMesh,
+    /// The whole segment lies on the navmesh.
+    Walkable {
+        /// Index of the triangle under the end point.
+        triangle: usize,
+        /// End point projected on the navmesh.
+        end: Vector3<f32>,
+    },
+    /// The segment leaves the navmesh.
+    Blocked {
+        /// Index of the last triangle on the segment.
+        triangle: usize,
+        /// Boundary edge of the triangle that was crossed. Contains indices of navmesh vertices.
+        edge: TriangleEdge,
+        /// Point on the edge where the segment leaves the navmesh.
+        point: Vector3<f32>,
+        /// Fraction of the segment (in `[0; 1]` range) that lies on the navmesh.
+        fraction: f32,
+    },
+}
+
+impl RaycastResult {
+    /// Returns `true` if the whole segment lies on the navmesh.
+    pub fn is_walkable(&self) -> bool {
+        matches!(self, Self::Walkable { .. })
+    }
+}
+
+/// Doubled signed area of a triangle projected on XZ plane. Its sign tells on which side of the
+/// `a -> b` line the point `c` is.
+fn triangle_area_2d(a: Vector3<f32>, b: Vector3<f32>, c: Vector3<f32>) -> f32 {
+    let ab = b - a;
+    let ac = c - a;
+    ac.x * ab.z - ab.x * ac.z
+}
+
+fn points_equal(a: Vector3<f32>, b: Vector3<f32>) -> bool {
+    (a - b).norm_squared() < 0.000001
+}
+
+// Must be greater than the distance used in `points_equal`, so equal points could be found in
+// adjacent cells.
+const WELD_CELL_SIZE: f32 = 0.01;
+
+/// Uniform grid of vertices, that is used to find vertices with equal positions quickly.
+#[derive(Default)]
+struct WeldGrid {
+    cells: FxHashMap<[i32; 3], Vec<u32>>,
+}
+
+impl WeldGrid {
+    fn cell(position: Vector3<f32>) -> [i32; 3] {
+        [
+            (position.x / WELD_CELL_SIZE).floor() as i32,
+            (position.y / WELD_CELL_SIZE).floor() as i32,
+            (position.z / WELD_CELL_SIZE).floor() as i32,
+        ]
+    }
+
+    fn insert(&mut self, position: Vector3<f32>, index: u32) {
+        self.cells
+            .entry(Self::cell(position))
+            .or_default()
+            .push(index);
+    }
+
+    fn find(&self, vertices: &[PathVertex], position: Vector3<f32>) -> Option<u32> {
+        let [x, y, z] = Self::cell(position);
+        for dx in -1..=1 {
+            for dy in -1..=1 {
+                for dz in -1..=1 {
+                    if let Some(cell) = self.cells.get(&[x + dx, y + dy, z + dz]) {
+                        for &index in cell {
+                            if points_equal(vertices[index as usize].position, position) {
+                                return Some(index);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        None
+    }
+}
+
+/// Corridor is a sequence of adjacent navmesh triangles that connects two points on the navmesh. It
+/// is used for smooth agent movement: an agent can freely move (steer, avoid obstacles, etc.) inside
+/// the corridor and ask for the next corner of the shortest path from its current position at any
+/// time, without re-calculating the path on the navmesh. Use [`Navmesh::build_corridor`] to create
+/// one.
+///
+/// The corridor stores copies of the triangles, so it stays valid even if the navmesh was modified.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Corridor {
+    triangles: Vec<usize>,
+    polygons: Vec<[Vector3<f32>; 3]>,
+    portals: Vec<(Vector3<f32>, Vector3<f32>)>,
+    begin: Vector3<f32>,
+    end: Vector3<f32>,
+}
+
+impl Corridor {
+    /// Returns indices of the navmesh triangles that forms the corridor, starting from the triangle
+    /// under the begin point.
+    pub fn triangles(&self) -> &[usize] {
+        &self.triangles
+    }
+
+    /// Returns the list of portals (shared edges) between adjacent triangles of the corridor. Each
+    /// portal is a pair of `(left, right)` points, where the sides are defined when looking in the
+    /// movement direction. Portal at `i` connects `i` and `i + 1` triangles.
+    pub fn portals(&self) -> &[(Vector3<f32>, Vector3<f32>)] {
+        &self.portals
+    }
+
+    /// Returns begin point of the corridor (projected on the navmesh).
+    pub fn begin(&self) -> Vector3<f32> {
+        self.begin
+    }
+
+    /// Returns end point of the corridor (projected on the navmesh).
+    pub fn end(&self) -> Vector3<f32> {
+        self.end
+    }
+
+    /// Tries to find a triangle of the corridor, that contains the given position. Vertical position
+    /// of the point is used only to select closest triangle from overlapping ones. Returns index of
+    /// the triangle in the corridor (not in the navmesh!).
+    pub fn find_triangle(&self, position: Vector3<f32>) -> Option<usize> {
+        let mut closest = None;
+        let mut closest_distance = f32::MAX;
+        for (index, [a, b, c]) in self.polygons.iter().enumerate() {
+            let (u, v, w) = math::get_barycentric_coords_2d(
+                Vector2::new(position.x, position.z),
+                Vector2::new(a.x, a.z),
+                Vector2::new(b.x, b.z),
+                Vector2::new(c.x, c.z),
+            );
+            let eps = -0.0001;
+            if u >= eps && v >= eps && w >= eps {
+                let height = a.y * u + b.y * v + c.y * w;
+                let distance = (position.y - height).abs();
+                if distance < closest_distance {
+                    closest_distance = distance;
+                    closest = Some(index);
+                }
+            }
+        }
+        closest
+    }
+
+    /// Returns next corner of the shortest path (within the corridor) from the given position to
+    /// the end of the corridor. The agent should move towards the returned point. If the position
+    /// is outside the corridor, the closest triangle is used to continue the path.
+    pub fn next_corner(&self, position: Vector3<f32>) -> Vector3<f32> {
+        let mut corners = SmallVec::<_, 1>::new();
+        self.string_pull(position, 1, &mut corners);
+        corners.first().cloned().unwrap_or(self.end)
+    }
+
+    /// Calculates the shortest path (within the corridor) from the given position to the end of the
+    /// corridor. The path is written to the `path` array and contains every corner of the path, except
+    /// the position itself. The last point of the path is always the end point of the corridor.
+    pub fn corners<P>(&self, position: Vector3<f32>, path: &mut P)
+    where
+        P: ResultBuffer<Vector3<f32>>,
+    {
+        path.clear();
+        self.string_pull(position, usize::MAX, path);
+    }
+
+    fn closest_triangle(&self, position: Vector3<f32>) -> usize {
+        self.find_triangle(position).unwrap_or_else(|| {
+            let mut closest = 0;
+            let mut closest_distance = f32::MAX;
+            for (index, [a, b, c]) in self.polygons.iter().enumerate() {
+                let distance = (a + b + c).scale(1.0 / 3.0).metric_distance(&position);
+                if distance < closest_distance {
+                    closest_distance = distance;
+                    closest = index;
+                }
+            }
+            closest
+        })
+    }
+
+    // "Simple stupid funnel algorithm" - see
+    // http://digestingduck.blogspot.com/2010/03/simple-stupid-funnel-algorithm.html
+    fn string_pull<P>(&self, position: Vector3<f32>, max_corners: usize, path: &mut P)
+    where
+        P: ResultBuffer<Vector3<f32>>,
+    {
+        let first_portal = self.closest_triangle(position);
+
+        let mut portals = SmallVec::<_, 32>::new();
+        portals.push((position, position));
+        portals.extend(self.portals[first_portal..].iter().cloned());
+        portals.push((self.end, self.end));
+
+        let mut apex = position;
+        let mut left = position;
+        let mut right = position;
+        let mut left_index = 0;
+        let mut right_index = 0;
+
+        let mut i = 1;
+        while i < portals.len() {
+            let (portal_left, portal_right) = portals[i];
+
+            // Try to narrow the funnel from the right side.
+            if triangle_area_2d(apex, right, portal_right) <= 0.0 {
+                if points_equal(apex, right) || triangle_area_2d(apex, left, portal_right) > 0.0 {
+                    right = portal_right;
+                    right_index = i;
+                } else {
+                    // Right side crossed the left one, the left point is a corner.
+                    if path.last().map_or(true, |last| !points_equal(*last, left)) {
+                        path.push(left);
+                    }
+                    if path.len() >= max_corners {
+                        return;
+                    }
+                    apex = left;
+                    right = left;
+                    right_index = left_index;
+                    i = left_index + 1;
+                    continue;
+                }
+            }
+
+            // Try to narrow the funnel from the left side.
+            if triangle_area_2d(apex, left, portal_left) >= 0.0 {
+                if points_equal(apex, left) || triangle_area_2d(apex, right, portal_left) < 0.0 {
+                    left = portal_left;
+                    left_index = i;
+                } else {
+                    // Left side crossed the right one, the right point is a corner.
+                    if path.last().map_or(true, |last| !points_equal(*last, right)) {
+                        path.push(right);
+                    }
+                    if path.len() >= max_corners {
+                        return;
+                    }
+                    apex = right;
+                    left = right;
+                    left_index = right_index;
+                    i = right_index + 1;
+                    continue;
+                }
+            }
+
+            i += 1;
+        }
+
+        if path
+            .last()
+            .map_or(true, |last| !points_equal(*last, self.end))
+        {
+            path.push(self.end);
+        }
+    }
 }
 
 /// Navmesh agent is a "pathfinding unit" that performs navigation on a mesh. It is designed to
@@ -766,9 +1762,17 @@ impl NavmeshAgentBuilder {
 #[cfg(test)]
 mod test {
     use crate::{
-        core::{algebra::Vector3, math::TriangleDefinition},
-        utils::navmesh::Navmesh,
+        core::{
+            algebra::Vector3,
+            color::Color,
+            math::{aabb::AxisAlignedBoundingBox, TriangleDefinition},
+        },
+        utils::{
+            astar::{PathError, PathKind},
+            navmesh::{AreaSet, Navmesh, NavmeshDebugColors, QueryFilter, RaycastResult},
+        },
     };
+    use fxhash::FxHashMap;
 
     fn make_navmesh() -> Navmesh {
         //             0                 1
@@ -889,4 +1893,353 @@ mod test {
         assert_eq!(navmesh.triangles().len(), 0);
         assert_eq!(navmesh.vertices().len(), 0);
     }
+
+    // Creates a flat navmesh from a set of unit square cells on XZ plane.
+    fn make_grid_navmesh(cells: &[(i32, i32)]) -> Navmesh {
+        let mut vertices = Vec::new();
+        let mut indices = FxHashMap::default();
+        let mut index_of = |x: i32, z: i32| {
+            *indices.entry((x, z)).or_insert_with(|| {
+                vertices.push(Vector3::new(x as f32, 0.0, z as f32));
+                vertices.len() as u32 - 1
+            })
+        };
+        let mut triangles = Vec::new();
+        for &(x, z) in cells {
+            let a = index_of(x, z);
+            let b = index_of(x + 1, z);
+            let c = index_of(x + 1, z + 1);
+            let d = index_of(x, z + 1);
+            triangles.push(TriangleDefinition([a, b, c]));
+            triangles.push(TriangleDefinition([a, c, d]));
+        }
+        Navmesh::new(&triangles, &vertices)
+    }
+
+    #[test]
+    fn test_corridor() {
+        // L-shaped navmesh.
+        let navmesh = make_grid_navmesh(&[(0, 0), (1, 0), (2, 0), (2, 1), (2, 2)]);
+
+        let from = Vector3::new(0.5, 0.0, 0.5);
+        let to = Vector3::new(2.5, 0.0, 2.5);
+        let corridor = navmesh.build_corridor(from, to).unwrap();
+
+        assert_eq!(corridor.portals().len(), corridor.triangles().len() - 1);
+        assert_eq!(corridor.find_triangle(from), Some(0));
+        assert_eq!(
+            corridor.find_triangle(to),
+            Some(corridor.triangles().len() - 1)
+        );
+        assert_eq!(corridor.find_triangle(Vector3::new(0.5, 0.0, 2.5)), None);
+
+        // The path must go around the inner corner of the L.
+        let corner = Vector3::new(2.0, 0.0, 1.0);
+        assert_eq!(corridor.next_corner(from), corner);
+
+        let mut path = Vec::new();
+        corridor.corners(from, &mut path);
+        assert_eq!(path, vec![corner, to]);
+
+        // Once the agent passed the corner, it should go straight to the end.
+        assert_eq!(corridor.next_corner(Vector3::new(2.2, 0.0, 0.9)), to);
+
+        // Both points in the same triangle.
+        let corridor = navmesh
+            .build_corridor(from, Vector3::new(0.9, 0.0, 0.1))
+            .unwrap();
+        assert_eq!(corridor.triangles().len(), 1);
+        assert!(corridor.portals().is_empty());
+        assert_eq!(corridor.next_corner(from), Vector3::new(0.9, 0.0, 0.1));
+
+        // Off-mesh points.
+        assert!(navmesh
+            .build_corridor(from, Vector3::new(10.0, 0.0, 10.0))
+            .is_none());
+    }
+
+    #[test]
+    fn test_corridor_disconnected() {
+        let navmesh = make_grid_navmesh(&[(0, 0), (2, 0)]);
+        assert!(navmesh
+            .build_corridor(Vector3::new(0.5, 0.0, 0.5), Vector3::new(2.5, 0.0, 0.5))
+            .is_none());
+    }
+
+    #[test]
+    fn test_raycast() {
+        // L-shaped navmesh.
+        let navmesh = make_grid_navmesh(&[(0, 0), (1, 0), (2, 0), (2, 1), (2, 2)]);
+
+        let from = Vector3::new(0.2, 0.0, 0.5);
+        assert!(navmesh
+            .raycast(from, Vector3::new(2.5, 0.0, 0.9))
+            .is_walkable());
+        assert!(navmesh
+            .raycast(Vector3::new(2.5, 0.0, 0.2), Vector3::new(2.3, 0.0, 2.8))
+            .is_walkable());
+        assert_eq!(
+            navmesh.raycast(from, Vector3::new(0.8, 0.0, 0.1)),
+            RaycastResult::Walkable {
+                triangle: 0,
+                end: Vector3::new(0.8, 0.0, 0.1)
+            }
+        );
+
+        // Cuts the inner corner of the L.
+        match navmesh.raycast(Vector3::new(0.5, 0.0, 0.5), Vector3::new(2.5, 0.0, 2.9)) {
+            RaycastResult::Blocked {
+                edge,
+                point,
+                fraction,
+                ..
+            } => {
+                let (a, b) = (
+                    navmesh.vertices()[edge.a as usize].position,
+                    navmesh.vertices()[edge.b as usize].position,
+                );
+                assert_eq!(a.z, 1.0);
+                assert_eq!(b.z, 1.0);
+                assert!((point - Vector3::new(0.5 + 0.5 / 1.2, 0.0, 1.0)).norm() < 1.0e-4);
+                assert!((fraction - 0.5 / 2.4).abs() < 1.0e-4);
+            }
+            result => panic!("unexpected result {:?}", result),
+        }
+
+        // Goes beyond the end of the mesh.
+        match navmesh.raycast(Vector3::new(0.5, 0.0, 0.5), Vector3::new(10.5, 0.0, 0.5)) {
+            RaycastResult::Blocked {
+                point, fraction, ..
+            } => {
+                assert!((point - Vector3::new(3.0, 0.0, 0.5)).norm() < 1.0e-4);
+                assert!((fraction - 0.25).abs() < 1.0e-4);
+            }
+            result => panic!("unexpected result {:?}", result),
+        }
+
+        assert_eq!(
+            navmesh.raycast(Vector3::new(0.5, 0.0, 2.5), from),
+            RaycastResult::OffMesh
+        );
+    }
+
+    #[test]
+    fn test_area_filter() {
+        // 3 ---- 4 ---- 5
+        // |  G / |  G / |
+        // |  /   |  /   |
+        // |/  W  |/  W  |
+        // 0 ---- 1 ---- 2
+        const GROUND: u8 = 0;
+        const WATER: u8 = 1;
+
+        let mut navmesh = Navmesh::new(
+            &[
+                TriangleDefinition([0, 1, 4]),
+                TriangleDefinition([0, 4, 3]),
+                TriangleDefinition([1, 2, 5]),
+                TriangleDefinition([1, 5, 4]),
+            ],
+            &[
+                Vector3::new(0.0, 0.0, 0.0),
+                Vector3::new(1.0, 0.0, 0.0),
+                Vector3::new(2.0, 0.0, 0.0),
+                Vector3::new(0.0, 0.0, 2.0),
+                Vector3::new(1.0, 0.0, 2.0),
+                Vector3::new(2.0, 0.0, 2.0),
+            ],
+        );
+        navmesh.set_triangle_area(0, WATER);
+        navmesh.set_triangle_area(2, WATER);
+        assert_eq!(navmesh.triangle_area(0), Some(WATER));
+        assert_eq!(navmesh.triangle_area(1), Some(GROUND));
+        assert_eq!(navmesh.triangle_area(4), None);
+
+        let mut path = Vec::new();
+
+        // Shortest path goes along water.
+        assert_eq!(
+            navmesh
+                .build_path_filtered(0, 2, &mut path, &QueryFilter::default())
+                .unwrap(),
+            PathKind::Full
+        );
+        assert_eq!(path.len(), 3);
+
+        // Expensive water is avoided as much as possible.
+        let filter = QueryFilter::default().with_cost(WATER, 100.0);
+        assert_eq!(
+            navmesh
+                .build_path_filtered(0, 2, &mut path, &filter)
+                .unwrap(),
+            PathKind::Full
+        );
+        assert_eq!(
+            path,
+            vec![
+                Vector3::new(2.0, 0.0, 0.0),
+                Vector3::new(1.0, 0.0, 0.0),
+                Vector3::new(1.0, 0.0, 2.0),
+                Vector3::new(0.0, 0.0, 0.0),
+            ]
+        );
+
+        // The vertex 2 can be reached only through water.
+        let filter = QueryFilter {
+            allowed: [GROUND].into_iter().collect::<AreaSet>(),
+            ..Default::default()
+        };
+        assert_ne!(
+            navmesh
+                .build_path_filtered(0, 2, &mut path, &filter)
+                .unwrap(),
+            PathKind::Full
+        );
+        assert_eq!(
+            navmesh
+                .build_path_filtered(0, 5, &mut path, &filter)
+                .unwrap(),
+            PathKind::Full
+        );
+
+        // Area types must follow their triangles.
+        navmesh.remove_triangle(0);
+        assert_eq!(navmesh.triangle_area(0), Some(GROUND));
+        assert_eq!(navmesh.triangle_area(1), Some(WATER));
+    }
+
+    #[test]
+    fn test_debug_lines() {
+        let mut navmesh = make_navmesh();
+
+        let colors = NavmeshDebugColors::default();
+        let lines = navmesh.to_debug_lines(&colors);
+        let count = |lines: &[crate::scene::debug::Line], color: Color| {
+            lines.iter().filter(|l| l.color == color).count()
+        };
+        assert_eq!(lines.len(), 8);
+        assert_eq!(count(&lines, colors.portal), 4);
+        assert_eq!(count(&lines, colors.boundary), 4);
+
+        navmesh.set_triangle_area(3, 1);
+        let colors = colors.with_area_color(1, Color::RED);
+        let lines = navmesh.to_debug_lines(&colors);
+        assert_eq!(lines.len(), 8);
+        assert_eq!(count(&lines, Color::RED), 3);
+        assert_eq!(count(&lines, colors.portal), 2);
+        assert_eq!(count(&lines, colors.boundary), 3);
+    }
+
+    // Checks that links of the navigational graph match the edges of the triangles.
+    fn check_links(navmesh: &Navmesh) {
+        let mut expected = vec![Vec::new(); navmesh.vertices().len()];
+        for triangle in navmesh.triangles() {
+            for edge in triangle.edges() {
+                expected[edge.a as usize].push(edge.b);
+                expected[edge.b as usize].push(edge.a);
+            }
+        }
+        for (vertex, expected) in navmesh.vertices().iter().zip(expected.iter_mut()) {
+            expected.sort_unstable();
+            expected.dedup();
+            let mut neighbours = vertex.neighbours.clone();
+            neighbours.sort_unstable();
+            assert_eq!(&neighbours, expected);
+        }
+    }
+
+    #[test]
+    fn test_rebuild_region() {
+        let mut cells = Vec::new();
+        for z in 0..4 {
+            for x in 0..4 {
+                cells.push((x, z));
+            }
+        }
+        let mut navmesh = make_grid_navmesh(&cells);
+        navmesh.set_triangle_area(0, 1);
+
+        // Central 2x2 cells.
+        let bounds = AxisAlignedBoundingBox::from_points(&[
+            Vector3::new(1.0, -1.0, 1.0),
+            Vector3::new(3.0, 1.0, 3.0),
+        ]);
+
+        // Triangulate the region with the other diagonals.
+        let mut new_triangles = Vec::new();
+        for z in 1..3 {
+            for x in 1..3 {
+                let a = Vector3::new(x as f32, 0.0, z as f32);
+                let b = Vector3::new(x as f32 + 1.0, 0.0, z as f32);
+                let c = Vector3::new(x as f32 + 1.0, 0.0, z as f32 + 1.0);
+                let d = Vector3::new(x as f32, 0.0, z as f32 + 1.0);
+                new_triangles.push([a, b, d]);
+                new_triangles.push([b, c, d]);
+            }
+        }
+
+        assert_eq!(navmesh.rebuild_region(&bounds, &new_triangles), 8);
+        assert_eq!(navmesh.triangles().len(), 32);
+        // Border vertices are reused, the central one is replaced.
+        assert_eq!(navmesh.vertices().len(), 25);
+        assert_eq!(navmesh.triangle_area(0), Some(1));
+        assert_eq!(navmesh.triangle_area(31), Some(0));
+        check_links(&navmesh);
+
+        let from = Vector3::new(0.5, 0.0, 0.5);
+        let to = Vector3::new(3.5, 0.0, 3.5);
+        let corridor = navmesh.build_corridor(from, to).unwrap();
+        assert!(corridor
+            .triangles()
+            .iter()
+            .any(|t| *t >= navmesh.triangles().len() - new_triangles.len()));
+
+        // Cut a hole in the navmesh.
+        assert_eq!(navmesh.rebuild_region(&bounds, &[]), 8);
+        assert_eq!(navmesh.triangles().len(), 24);
+        assert_eq!(navmesh.vertices().len(), 24);
+        check_links(&navmesh);
+
+        // The path goes around the hole.
+        let corridor = navmesh.build_corridor(from, to).unwrap();
+        let mut path = Vec::new();
+        corridor.corners(from, &mut path);
+        assert_eq!(path.len(), 2);
+        assert!(navmesh
+            .build_corridor(from, Vector3::new(2.0, 0.0, 2.0))
+            .is_none());
+    }
+
+    #[test]
+    fn test_islands() {
+        // Two cells on the ground and a separate "rooftop" cell.
+        let mut navmesh = make_grid_navmesh(&[(0, 0), (1, 0), (5, 5)]);
+        assert_eq!(navmesh.island_count(), 2);
+        assert_eq!(navmesh.triangle_island(0), Some(0));
+        assert_eq!(navmesh.triangle_island(5), Some(1));
+        assert_eq!(navmesh.triangle_island(6), None);
+        assert_eq!(navmesh.vertex_island(9), Some(1));
+        assert!(navmesh.same_island(0, 3));
+        assert!(!navmesh.same_island(0, 4));
+        assert!(!navmesh.same_island(0, 100));
+
+        let mut path = Vec::new();
+        assert!(matches!(
+            navmesh.build_path(0, 9, &mut path),
+            Err(PathError::Unreachable(0, 9))
+        ));
+        assert!(path.is_empty());
+        assert!(matches!(
+            navmesh.build_path_filtered(0, 9, &mut path, &QueryFilter::default()),
+            Err(PathError::Unreachable(0, 9))
+        ));
+        assert_eq!(navmesh.build_path(0, 5, &mut path).unwrap(), PathKind::Full);
+
+        // A ramp to the rooftop joins the islands.
+        navmesh.add_triangle(TriangleDefinition([5, 6, 9]));
+        assert_eq!(navmesh.island_count(), 1);
+        assert!(navmesh.same_island(0, 4));
+        path.clear();
+        assert_eq!(navmesh.build_path(0, 9, &mut path).unwrap(), PathKind::Full);
+    }
 }
@@ -0,0 +1,226 @@
+//! Object pool reuses heap allocations of temporary objects (vectors, strings, etc.) to avoid
+//! allocations in hot loops. See [`RecyclePool`] docs for more info.
+
+use crate::core::parking_lot::Mutex;
+use std::{
+    fmt::{Debug, Formatter},
+    ops::{Deref, DerefMut},
+};
+
+type Factory<T> = Box<dyn Fn() -> T + Send + Sync>;
+type Reset<T> = Box<dyn Fn(&mut T) + Send + Sync>;
+
+/// A free list of objects, that could be acquired and automatically returned back to the pool when
+/// they're not needed anymore. Unlike [`crate::core::pool::Pool`], it does not provide stable
+/// handles, its only purpose is to reuse objects (and their allocations).
+///
+/// A reset hook (see [`Self::with_reset`]) is called for every recycled object before it is given
+/// out again, it should be used to clear the state of the object while keeping its allocations (for
+/// example by calling `Vec::clear`).
+///
+/// ## Example
+///
+/// ```rust
+/// use fyrox::utils::object_pool::RecyclePool;
+///
+/// let pool = RecyclePool::<Vec<u32>>::default().with_reset(Vec::clear);
+///
+/// {
+///     let mut temp = pool.acquire();
+///     temp.extend_from_slice(&[1, 2, 3]);
+/// } // `temp` is returned to the pool here.
+///
+/// // The same vector is reused, but it is empty.
+/// let temp = pool.acquire();
+/// assert!(temp.is_empty());
+/// assert!(temp.capacity() >= 3);
+/// ```
+pub struct RecyclePool<T> {
+    free: Mutex<Vec<T>>,
+    factory: Factory<T>,
+    reset: Option<Reset<T>>,
+    max_free: usize,
+}
+
+impl<T: Default + 'static> Default for RecyclePool<T> {
+    fn default() -> Self {
+        Self::new(T::default)
+    }
+}
+
+impl<T> Debug for RecyclePool<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RecyclePool")
+            .field("free", &self.free.lock().len())
+            .field("max_free", &self.max_free)
+            .finish()
+    }
+}
+
+impl<T> RecyclePool<T> {
+    /// Creates new empty pool, that creates new objects using the given factory.
+    pub fn new<F>(factory: F) -> Self
+    where
+        F: Fn() -> T + Send + Sync + 'static,
+    {
+        Self {
+            free: Default::default(),
+            factory: Box::new(factory),
+            reset: None,
+            max_free: usize::MAX,
+        }
+    }
+
+    /// Sets a hook, that will be called for every recycled object before it is given out again.
+    pub fn with_reset<F>(mut self, reset: F) -> Self
+    where
+        F: Fn(&mut T) + Send + Sync + 'static,
+    {
+        self.reset = Some(Box::new(reset));
+        self
+    }
+
+    /// Sets max amount of free objects, that the pool keeps. Objects returned to the full pool are
+    /// dropped. Unlimited by default.
+    pub fn with_max_free(mut self, max_free: usize) -> Self {
+        self.max_free = max_free;
+        self
+    }
+
+    /// Takes a free object from the pool (or creates a new one if there's no free objects). The
+    /// object is returned back to the pool when the returned reference is dropped.
+    pub fn acquire(&self) -> PooledRef<'_, T> {
+        let recycled = self.free.lock().pop();
+        let object = match recycled {
+            Some(mut object) => {
+                if let Some(reset) = self.reset.as_ref() {
+                    reset(&mut object);
+                }
+                object
+            }
+            None => (self.factory)(),
+        };
+
+        PooledRef {
+            pool: self,
+            object: Some(object),
+        }
+    }
+
+    /// Puts the object to the pool, so it could be reused later.
+    pub fn release(&self, object: T) {
+        let mut free = self.free.lock();
+        if free.len() < self.max_free {
+            free.push(object);
+        }
+    }
+
+    /// Returns amount of free objects in the pool.
+    pub fn free_count(&self) -> usize {
+        self.free.lock().len()
+    }
+
+    /// Drops all free objects.
+    pub fn clear(&self) {
+        self.free.lock().clear();
+    }
+}
+
+/// A reference to an object taken from a [`RecyclePool`]. The object is returned to the pool on
+/// drop.
+pub struct PooledRef<'a, T> {
+    pool: &'a RecyclePool<T>,
+    // Always `Some` until drop or `into_inner`.
+    object: Option<T>,
+}
+
+impl<'a, T> PooledRef<'a, T> {
+    /// Takes the object out, so it won't be returned to the pool.
+    pub fn into_inner(mut self) -> T {
+        self.object.take().unwrap()
+    }
+}
+
+impl<'a, T> Deref for PooledRef<'a, T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        self.object.as_ref().unwrap()
+    }
+}
+
+impl<'a, T> DerefMut for PooledRef<'a, T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.object.as_mut().unwrap()
+    }
+}
+
+impl<'a, T: Debug> Debug for PooledRef<'a, T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        Debug::fmt(&**self, f)
+    }
+}
+
+impl<'a, T> Drop for PooledRef<'a, T> {
+    fn drop(&mut self) {
+        if let Some(object) = self.object.take() {
+            self.pool.release(object);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::utils::object_pool::RecyclePool;
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+
+    #[test]
+    fn test_recycling() {
+        let created = Arc::new(AtomicUsize::new(0));
+        let pool = {
+            let created = created.clone();
+            RecyclePool::new(move || {
+                created.fetch_add(1, Ordering::SeqCst);
+                Vec::<u32>::with_capacity(16)
+            })
+            .with_reset(Vec::clear)
+        };
+
+        {
+            let mut a = pool.acquire();
+            let mut b = pool.acquire();
+            a.push(1);
+            b.push(2);
+            assert_eq!(pool.free_count(), 0);
+        }
+        assert_eq!(pool.free_count(), 2);
+        assert_eq!(created.load(Ordering::SeqCst), 2);
+
+        for _ in 0..10 {
+            let a = pool.acquire();
+            assert!(a.is_empty());
+            assert_eq!(a.capacity(), 16);
+        }
+        assert_eq!(created.load(Ordering::SeqCst), 2);
+
+        let detached = pool.acquire().into_inner();
+        assert!(detached.is_empty());
+        assert_eq!(pool.free_count(), 1);
+
+        pool.clear();
+        assert_eq!(pool.free_count(), 0);
+    }
+
+    #[test]
+    fn test_max_free() {
+        let pool = RecyclePool::<String>::default().with_max_free(1);
+        {
+            let _a = pool.acquire();
+            let _b = pool.acquire();
+        }
+        assert_eq!(pool.free_count(), 1);
+    }
+}
@@ -1,14 +1,21 @@
 //! Raw mesh is a procedural mesh builder, all you can do with it is to insert vertices
-//! one-by-one and it will automatically build faces by skipping duplicated vertices.
+//! one-by-one (or as triangle strips and fans) and it will automatically build faces by
+//! skipping duplicated vertices.
 //! Main usage of it - optimize "triangle soup" into mesh so adjacent faces will have
-//! shared edges. Raw mesh itself does not have any methods, it is just a final result
-//! of RawMeshBuilder.
+//! shared edges. Raw mesh is a final result of RawMeshBuilder, it has only few methods for
+//! optimization (including vertex cache optimization), iteration over triangles and calculation
+//! of simple metrics.
+//! The builder could also produce line lists and point lists (see [`PrimitiveTopology`]), which
+//! are useful for debug visualization, see [`crate::scene::debug::SceneDrawingContext::draw_raw_mesh`].
 
 use crate::{
-    core::{algebra::Vector3, math::TriangleDefinition},
+    core::{
+        algebra::Vector3,
+        math::{aabb::AxisAlignedBoundingBox, TriangleDefinition},
+    },
     utils::hash_as_bytes,
 };
-use fxhash::{FxBuildHasher, FxHashSet};
+use fxhash::{FxBuildHasher, FxHashMap, FxHashSet, FxHasher};
 use std::hash::{Hash, Hasher};
 
 #[derive(Copy, Clone)]
@@ -86,6 +93,30 @@ where
         Self {
             vertices: Default::default(),
             indices: Default::default(),
+            topology: Default::default(),
+        }
+    }
+}
+
+/// Defines how the indices of a mesh are interpreted.
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum PrimitiveTopology {
+    /// Every three indices form a triangle.
+    #[default]
+    Triangles,
+    /// Every two indices form a line segment.
+    Lines,
+    /// Every index is a point.
+    Points,
+}
+
+impl PrimitiveTopology {
+    /// Returns the amount of indices of a single primitive.
+    pub fn indices_per_primitive(self) -> usize {
+        match self {
+            PrimitiveTopology::Triangles => 3,
+            PrimitiveTopology::Lines => 2,
+            PrimitiveTopology::Points => 1,
         }
     }
 }
@@ -98,15 +129,37 @@ where
 {
     vertices: FxHashSet<IndexedStorage<T>>,
     indices: Vec<u32>,
+    topology: PrimitiveTopology,
 }
 
 /// See module docs.
-#[derive(Default, Debug, Clone)]
+#[derive(Debug, Clone)]
 pub struct RawMesh<T> {
     /// Vertices of mesh.
     pub vertices: Vec<T>,
-    /// Triangles of mesh. Each triangle contains indices of vertices.
+    /// Triangles of mesh. Each triangle contains indices of vertices. Empty, if the topology of the
+    /// mesh is not [`PrimitiveTopology::Triangles`].
     pub triangles: Vec<TriangleDefinition>,
+    /// Line segments of mesh. Each segment contains indices of vertices. Empty, if the topology of
+    /// the mesh is not [`PrimitiveTopology::Lines`].
+    pub lines: Vec<[u32; 2]>,
+    /// Indices of vertices, that form points of mesh. Empty, if the topology of the mesh is not
+    /// [`PrimitiveTopology::Points`].
+    pub points: Vec<u32>,
+    /// Topology of the mesh, it defines which list of primitives is used.
+    pub topology: PrimitiveTopology,
+}
+
+impl<T> Default for RawMesh<T> {
+    fn default() -> Self {
+        Self {
+            vertices: Default::default(),
+            triangles: Default::default(),
+            lines: Default::default(),
+            points: Default::default(),
+            topology: Default::default(),
+        }
+    }
 }
 
 impl<T> RawMeshBuilder<T>
@@ -122,21 +175,144 @@ where
             // (https://internals.rust-lang.org/t/hashmap-set-new-with-capacity-and-buildhasher/15622).
             vertices: FxHashSet::with_capacity_and_hasher(vertices, FxBuildHasher::default()),
             indices: Vec::with_capacity(indices),
+            topology: Default::default(),
         }
     }
 
+    /// Sets topology of the mesh, it defines how inserted vertices form primitives. Default is
+    /// [`PrimitiveTopology::Triangles`]. Must be set before any vertex is inserted.
+    pub fn with_topology(mut self, topology: PrimitiveTopology) -> Self {
+        debug_assert!(self.indices.is_empty());
+        self.topology = topology;
+        self
+    }
+
+    /// Returns topology of the mesh.
+    pub fn topology(&self) -> PrimitiveTopology {
+        self.topology
+    }
+
     /// Inserts new vertex in mesh. Index buffer is populated automatically -
     /// when duplicate vertex is found, it not added into vertices array, but its
-    /// index gets added into indices array.
+    /// index gets added into indices array. Every three (two for lines, one for points)
+    /// consecutive vertices form a primitive, see [`Self::with_topology`].
     pub fn insert(&mut self, vertex: T) -> bool {
+        let (index, inserted) = self.insert_vertex(vertex);
+        self.indices.push(index);
+        inserted
+    }
+
+    /// Inserts a triangle strip in the mesh and converts it to a list of triangles. Every vertex
+    /// after the first two forms a triangle with two previous vertices, winding of every odd
+    /// triangle is flipped so all the triangles have the same winding as the first one. Degenerate
+    /// triangles (that are used to stitch multiple strips together) are skipped. Must be called
+    /// when the last triangle inserted by [`Self::insert`] is complete. Returns the amount of
+    /// inserted triangles.
+    ///
+    /// For [`PrimitiveTopology::Lines`] the strip is a polyline - every vertex after the first
+    /// one forms a segment with the previous vertex. For [`PrimitiveTopology::Points`] every
+    /// vertex is a point. Returns the amount of inserted primitives in both cases.
+    pub fn insert_strip<I>(&mut self, vertices: I) -> usize
+    where
+        I: IntoIterator<Item = T>,
+    {
+        let indices = self.insert_vertices(vertices);
+        let mut count = 0;
+        match self.topology {
+            PrimitiveTopology::Triangles => {
+                for (i, window) in indices.windows(3).enumerate() {
+                    let triangle = if i % 2 == 0 {
+                        [window[0], window[1], window[2]]
+                    } else {
+                        [window[1], window[0], window[2]]
+                    };
+                    count += self.push_triangle(triangle) as usize;
+                }
+            }
+            PrimitiveTopology::Lines => {
+                for window in indices.windows(2) {
+                    count += self.push_line([window[0], window[1]]) as usize;
+                }
+            }
+            PrimitiveTopology::Points => {
+                count = indices.len();
+                self.indices.extend_from_slice(&indices);
+            }
+        }
+        count
+    }
+
+    /// Inserts a triangle fan in the mesh and converts it to a list of triangles. Every vertex
+    /// after the first two forms a triangle with the first (central) vertex and the previous
+    /// vertex. Degenerate triangles are skipped. Must be called when the last triangle inserted by
+    /// [`Self::insert`] is complete. Returns the amount of inserted triangles.
+    ///
+    /// For [`PrimitiveTopology::Lines`] every vertex after the first one forms a segment with the
+    /// first (central) vertex. For [`PrimitiveTopology::Points`] every vertex is a point. Returns
+    /// the amount of inserted primitives in both cases.
+    pub fn insert_fan<I>(&mut self, vertices: I) -> usize
+    where
+        I: IntoIterator<Item = T>,
+    {
+        let indices = self.insert_vertices(vertices);
+        let mut count = 0;
+        if let Some((center, rim)) = indices.split_first() {
+            match self.topology {
+                PrimitiveTopology::Triangles => {
+                    for pair in rim.windows(2) {
+                        count += self.push_triangle([*center, pair[0], pair[1]]) as usize;
+                    }
+                }
+                PrimitiveTopology::Lines => {
+                    for index in rim {
+                        count += self.push_line([*center, *index]) as usize;
+                    }
+                }
+                PrimitiveTopology::Points => {
+                    count = indices.len();
+                    self.indices.extend_from_slice(&indices);
+                }
+            }
+        }
+        count
+    }
+
+    fn insert_vertex(&mut self, vertex: T) -> (u32, bool) {
         let mut wrapper = IndexedStorage::<T> { index: 0, vertex };
         if let Some(existing) = self.vertices.get(&wrapper) {
-            self.indices.push(existing.index);
-            false
+            (existing.index, false)
         } else {
             wrapper.index = self.vertices.len() as u32;
-            self.indices.push(wrapper.index);
+            let index = wrapper.index;
             self.vertices.insert(wrapper);
+            (index, true)
+        }
+    }
+
+    fn insert_vertices<I>(&mut self, vertices: I) -> Vec<u32>
+    where
+        I: IntoIterator<Item = T>,
+    {
+        vertices
+            .into_iter()
+            .map(|vertex| self.insert_vertex(vertex).0)
+            .collect()
+    }
+
+    fn push_triangle(&mut self, [a, b, c]: [u32; 3]) -> bool {
+        if a == b || b == c || c == a {
+            false
+        } else {
+            self.indices.extend_from_slice(&[a, b, c]);
+            true
+        }
+    }
+
+    fn push_line(&mut self, [a, b]: [u32; 2]) -> bool {
+        if a == b {
+            false
+        } else {
+            self.indices.extend_from_slice(&[a, b]);
             true
         }
     }
@@ -147,17 +323,902 @@ where
     }
 
     /// Creates new raw mesh from internal set of vertices and indices. If last "triangle" has
-    /// insufficient vertex count (less than 3), it will be discarded.
+    /// insufficient vertex count (less than 3), it will be discarded. The same applies to the last
+    /// line segment with a single vertex. Degenerate line segments (with the same vertex at both
+    /// ends) inserted by [`Self::insert`] are discarded as well as duplicated points.
     pub fn build(self) -> RawMesh<T> {
         let mut vertices = self.vertices.into_iter().collect::<Vec<_>>();
         vertices.sort_unstable_by_key(|w| w.index);
-        RawMesh {
+        let mut mesh = RawMesh {
             vertices: vertices.into_iter().map(|w| w.vertex).collect(),
-            triangles: self
-                .indices
-                .chunks_exact(3)
-                .map(|i| TriangleDefinition([i[0], i[1], i[2]]))
-                .collect(),
+            topology: self.topology,
+            ..Default::default()
+        };
+        match self.topology {
+            PrimitiveTopology::Triangles => {
+                mesh.triangles = self
+                    .indices
+                    .chunks_exact(3)
+                    .map(|i| TriangleDefinition([i[0], i[1], i[2]]))
+                    .collect();
+            }
+            PrimitiveTopology::Lines => {
+                mesh.lines = self
+                    .indices
+                    .chunks_exact(2)
+                    .filter(|i| i[0] != i[1])
+                    .map(|i| [i[0], i[1]])
+                    .collect();
+            }
+            PrimitiveTopology::Points => {
+                let mut used = FxHashSet::default();
+                mesh.points = self
+                    .indices
+                    .into_iter()
+                    .filter(|i| used.insert(*i))
+                    .collect();
+            }
+        }
+        mesh
+    }
+}
+
+/// An edge of a mesh, that is used by amount of triangles other than two. See [`ManifoldReport`]
+/// for more info.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct NonManifoldEdge {
+    /// Index of the first vertex of the edge (always less than [`Self::b`]).
+    pub a: u32,
+    /// Index of the second vertex of the edge.
+    pub b: u32,
+    /// Amount of triangles that use the edge. `1` means that the edge is a boundary of a hole,
+    /// `3` or more means that more than two triangles share the edge.
+    pub triangle_count: usize,
+}
+
+/// A result of manifoldness check of a mesh, see [`RawMesh::check_manifold`]. Vertex indices in
+/// the report are indices of the first vertex among the vertices that were considered identical.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ManifoldReport {
+    /// Edges that are used by one or more than two triangles, sorted by vertex indices.
+    pub non_manifold_edges: Vec<NonManifoldEdge>,
+    /// Vertices that are not used by any triangle.
+    pub isolated_vertices: Vec<u32>,
+    /// Indices of triangles that use the same vertices as some previous triangle (regardless of
+    /// the order of vertices). Duplicates are excluded from edge checks.
+    pub duplicate_triangles: Vec<usize>,
+    /// Indices of triangles that have two or more same vertices or vertex indices that are out of
+    /// bounds. Such triangles are excluded from edge checks.
+    pub degenerate_triangles: Vec<usize>,
+}
+
+impl ManifoldReport {
+    /// Returns `true` if the mesh is a closed manifold: every edge is shared by exactly two
+    /// triangles and there's no duplicate or degenerate triangles. Isolated vertices do not affect
+    /// the result.
+    pub fn is_manifold(&self) -> bool {
+        self.non_manifold_edges.is_empty()
+            && self.duplicate_triangles.is_empty()
+            && self.degenerate_triangles.is_empty()
+    }
+}
+
+/// Size of the vertex cache that is modelled by [`RawMesh::optimize_vertex_cache`].
+const VERTEX_CACHE_SIZE: usize = 32;
+
+// Score of a vertex for the vertex cache optimization ("Linear-Speed Vertex Cache Optimisation" by
+// Tom Forsyth). Vertices that are used by the last triangle have fixed score, so the next triangle
+// won't be the one that shares the same vertices and "reverses" the direction of the traversal.
+// Vertices with fewer remaining triangles are boosted to avoid leaving lone triangles behind.
+fn vertex_cache_score(cache_position: Option<usize>, remaining_triangles: usize) -> f32 {
+    if remaining_triangles == 0 {
+        return -1.0;
+    }
+
+    let cache_score = match cache_position {
+        Some(position) if position < 3 => 0.75,
+        Some(position) => (1.0 - (position - 3) as f32 / (VERTEX_CACHE_SIZE - 3) as f32).powf(1.5),
+        None => 0.0,
+    };
+
+    cache_score + 2.0 / (remaining_triangles as f32).sqrt()
+}
+
+fn hash_of<K: Hash>(key: &K) -> u64 {
+    let mut hasher = FxHasher::default();
+    key.hash(&mut hasher);
+    hasher.finish()
+}
+
+impl<T> RawMesh<T> {
+    /// Returns total amount of vertices in the mesh.
+    pub fn vertex_count(&self) -> usize {
+        self.vertices.len()
+    }
+
+    /// Returns total amount of triangles in the mesh.
+    pub fn triangle_count(&self) -> usize {
+        self.triangles.len()
+    }
+
+    /// Returns total amount of primitives of the mesh, that are defined by its topology.
+    pub fn primitive_count(&self) -> usize {
+        match self.topology {
+            PrimitiveTopology::Triangles => self.triangles.len(),
+            PrimitiveTopology::Lines => self.lines.len(),
+            PrimitiveTopology::Points => self.points.len(),
+        }
+    }
+
+    /// Returns an iterator over triangles of the mesh, each item contains references to the three
+    /// vertices of a triangle.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a triangle has an index of a vertex that is out of bounds.
+    pub fn triangles(&self) -> impl Iterator<Item = [&T; 3]> + '_ {
+        self.triangles.iter().map(move |triangle| {
+            [
+                &self.vertices[triangle[0] as usize],
+                &self.vertices[triangle[1] as usize],
+                &self.vertices[triangle[2] as usize],
+            ]
+        })
+    }
+
+    /// Returns an iterator over line segments of the mesh, each item contains references to the two
+    /// vertices of a segment.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a segment has an index of a vertex that is out of bounds.
+    pub fn lines(&self) -> impl Iterator<Item = [&T; 2]> + '_ {
+        self.lines
+            .iter()
+            .map(move |[a, b]| [&self.vertices[*a as usize], &self.vertices[*b as usize]])
+    }
+
+    /// Returns an iterator over points of the mesh.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a point has an index of a vertex that is out of bounds.
+    pub fn points(&self) -> impl Iterator<Item = &T> + '_ {
+        self.points.iter().map(move |i| &self.vertices[*i as usize])
+    }
+
+    /// Calculates total area of every triangle of the mesh. The `position` closure must return a
+    /// position of the given vertex.
+    ///
+    /// ```
+    /// use fyrox::{
+    ///     core::{algebra::Vector3, math::TriangleDefinition},
+    ///     utils::raw_mesh::RawMesh,
+    /// };
+    ///
+    /// let mesh = RawMesh {
+    ///     vertices: vec![
+    ///         Vector3::new(0.0, 0.0, 0.0),
+    ///         Vector3::new(2.0, 0.0, 0.0),
+    ///         Vector3::new(0.0, 2.0, 0.0),
+    ///     ],
+    ///     triangles: vec![TriangleDefinition([0, 1, 2])],
+    ///     ..Default::default()
+    /// };
+    ///
+    /// assert_eq!(mesh.surface_area(|v| *v), 2.0);
+    /// ```
+    pub fn surface_area<F>(&self, mut position: F) -> f32
+    where
+        F: FnMut(&T) -> Vector3<f32>,
+    {
+        self.triangles()
+            .map(|[a, b, c]| {
+                let a = position(a);
+                (position(b) - a).cross(&(position(c) - a)).norm() * 0.5
+            })
+            .sum()
+    }
+
+    /// Calculates axis-aligned bounding box of every vertex of the mesh (including the vertices
+    /// that are not used by any triangle). The `position` closure must return a position of the
+    /// given vertex. Returns `None` if the mesh has no vertices.
+    pub fn bounding_box<F>(&self, position: F) -> Option<AxisAlignedBoundingBox>
+    where
+        F: FnMut(&T) -> Vector3<f32>,
+    {
+        if self.vertices.is_empty() {
+            None
+        } else {
+            Some(AxisAlignedBoundingBox::from_points(
+                &self.vertices.iter().map(position).collect::<Vec<_>>(),
+            ))
+        }
+    }
+
+    /// Welds identical vertices of the mesh and rebuilds its triangles so they will use shared vertices.
+    /// It is useful for meshes that were built without [`RawMeshBuilder`] (for example with a flat list of
+    /// vertices and no sharing between triangles), it reduces memory usage and improves vertex cache usage.
+    /// Every attribute of vertices is compared, use [`Self::optimize_by`] to compare only some part of
+    /// vertices (for example only positions). The method is idempotent. Returns the amount of merged
+    /// vertices.
+    pub fn optimize(&mut self) -> usize
+    where
+        T: Hash + PartialEq,
+    {
+        let mut buckets = FxHashMap::<u64, Vec<u32>>::default();
+        let mut vertices = Vec::<T>::with_capacity(self.vertices.len());
+        let mut remap = Vec::with_capacity(self.vertices.len());
+        for vertex in std::mem::take(&mut self.vertices) {
+            let bucket = buckets.entry(hash_of(&vertex)).or_default();
+            if let Some(existing) = bucket.iter().find(|i| vertices[**i as usize] == vertex) {
+                remap.push(*existing);
+            } else {
+                let index = vertices.len() as u32;
+                bucket.push(index);
+                remap.push(index);
+                vertices.push(vertex);
+            }
+        }
+        self.remap(vertices, &remap)
+    }
+
+    /// Welds vertices of the mesh that have identical keys and rebuilds its triangles so they will use shared
+    /// vertices. The first vertex with a particular key is kept, every other vertex with the same key will be
+    /// removed. It allows you to control which attributes are compared, for example to weld vertices by
+    /// their positions only:
+    ///
+    /// ```
+    /// use fyrox::{
+    ///     core::algebra::Vector3,
+    ///     utils::raw_mesh::{RawMesh, RawVertex},
+    /// };
+    ///
+    /// struct Vertex {
+    ///     position: Vector3<f32>,
+    ///     normal: Vector3<f32>,
+    /// }
+    ///
+    /// fn weld_by_positions(mesh: &mut RawMesh<Vertex>) -> usize {
+    ///     mesh.optimize_by(|v| RawVertex::from(v.position))
+    /// }
+    /// ```
+    ///
+    /// Keep in mind, that triangles could become degenerated (with two or more same indices) when vertices
+    /// are welded partially. The method is idempotent. Returns the amount of merged vertices.
+    pub fn optimize_by<K, F>(&mut self, mut key: F) -> usize
+    where
+        K: Hash + PartialEq,
+        F: FnMut(&T) -> K,
+    {
+        let mut buckets = FxHashMap::<u64, Vec<u32>>::default();
+        let mut keys = Vec::<K>::with_capacity(self.vertices.len());
+        let mut vertices = Vec::with_capacity(self.vertices.len());
+        let mut remap = Vec::with_capacity(self.vertices.len());
+        for vertex in std::mem::take(&mut self.vertices) {
+            let vertex_key = key(&vertex);
+            let bucket = buckets.entry(hash_of(&vertex_key)).or_default();
+            if let Some(existing) = bucket.iter().find(|i| keys[**i as usize] == vertex_key) {
+                remap.push(*existing);
+            } else {
+                let index = vertices.len() as u32;
+                bucket.push(index);
+                remap.push(index);
+                keys.push(vertex_key);
+                vertices.push(vertex);
+            }
+        }
+        self.remap(vertices, &remap)
+    }
+
+    /// Checks whether the mesh is a closed manifold (watertight) and reports every problem found: edges
+    /// that are shared by amount of triangles other than two, isolated vertices, duplicate and degenerate
+    /// triangles. Vertices are considered identical if they're equal, so the mesh does not need to have
+    /// shared vertices. Use [`Self::check_manifold_by`] to compare only some part of vertices (for example
+    /// only positions, because vertices on texture seams have different texture coordinates).
+    ///
+    /// Runs in `O(vertices + triangles)` time.
+    pub fn check_manifold(&self) -> ManifoldReport
+    where
+        T: Hash + PartialEq,
+    {
+        self.check_manifold_by(|v| v)
+    }
+
+    /// Same as [`Self::check_manifold`], but vertices are considered identical if they have identical
+    /// keys.
+    pub fn check_manifold_by<'a, K, F>(&'a self, mut key: F) -> ManifoldReport
+    where
+        K: Hash + PartialEq,
+        F: FnMut(&'a T) -> K,
+    {
+        // Map every vertex to the first vertex with the same key.
+        let mut buckets = FxHashMap::<u64, Vec<u32>>::default();
+        let mut keys = Vec::<(K, u32)>::with_capacity(self.vertices.len());
+        let mut ids = Vec::with_capacity(self.vertices.len());
+        for (index, vertex) in self.vertices.iter().enumerate() {
+            let vertex_key = key(vertex);
+            let bucket = buckets.entry(hash_of(&vertex_key)).or_default();
+            if let Some(existing) = bucket.iter().find(|i| keys[**i as usize].0 == vertex_key) {
+                ids.push(keys[*existing as usize].1);
+            } else {
+                bucket.push(keys.len() as u32);
+                keys.push((vertex_key, index as u32));
+                ids.push(index as u32);
+            }
+        }
+
+        let mut report = ManifoldReport::default();
+        let mut used = vec![false; self.vertices.len()];
+        let mut unique_triangles = FxHashSet::default();
+        let mut edges = FxHashMap::<(u32, u32), usize>::default();
+        for (triangle_index, triangle) in self.triangles.iter().enumerate() {
+            let mut vertices = [0; 3];
+            let mut valid = true;
+            for (id, index) in vertices.iter_mut().zip(triangle.0.iter()) {
+                match ids.get(*index as usize) {
+                    Some(vertex_id) => *id = *vertex_id,
+                    None => valid = false,
+                }
+            }
+            if !valid
+                || vertices[0] == vertices[1]
+                || vertices[1] == vertices[2]
+                || vertices[2] == vertices[0]
+            {
+                report.degenerate_triangles.push(triangle_index);
+                continue;
+            }
+
+            for id in vertices {
+                used[id as usize] = true;
+            }
+
+            let mut sorted = vertices;
+            sorted.sort_unstable();
+            if !unique_triangles.insert(sorted) {
+                report.duplicate_triangles.push(triangle_index);
+                continue;
+            }
+
+            for (a, b) in [(0, 1), (1, 2), (2, 0)] {
+                let (a, b) = (vertices[a], vertices[b]);
+                *edges.entry((a.min(b), a.max(b))).or_default() += 1;
+            }
+        }
+
+        report.non_manifold_edges = edges
+            .into_iter()
+            .filter(|(_, count)| *count != 2)
+            .map(|((a, b), triangle_count)| NonManifoldEdge {
+                a,
+                b,
+                triangle_count,
+            })
+            .collect();
+        report
+            .non_manifold_edges
+            .sort_unstable_by_key(|edge| (edge.a, edge.b));
+
+        report.isolated_vertices = ids
+            .iter()
+            .enumerate()
+            .filter(|(_, id)| !used[**id as usize])
+            .map(|(index, _)| index as u32)
+            .collect();
+
+        report
+    }
+
+    /// Reorders triangles of the mesh to improve usage of the post-transform vertex cache of a GPU
+    /// and then reorders vertices in order of their first use to improve memory locality. Vertices
+    /// that are not used by any triangle are moved to the end, keeping their relative order. The
+    /// result is deterministic - the same mesh is always optimized in the same way.
+    ///
+    /// Returns the remapping table for vertices, where `remap[old_index] == new_index`, use it to
+    /// fix up any data that depends on vertex indices.
+    ///
+    /// Uses the "Linear-Speed Vertex Cache Optimisation" algorithm by Tom Forsyth, see
+    /// [`Self::average_cache_miss_ratio`] to measure the effect.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a triangle has an index of a vertex that is out of bounds.
+    pub fn optimize_vertex_cache(&mut self) -> Vec<u32> {
+        let vertex_count = self.vertices.len();
+
+        // Compact lists of triangles, that use each vertex.
+        let mut offsets = vec![0usize; vertex_count + 1];
+        for triangle in self.triangles.iter() {
+            for index in triangle.0 {
+                offsets[index as usize + 1] += 1;
+            }
+        }
+        let mut sum = 0;
+        for offset in offsets.iter_mut() {
+            sum += *offset;
+            *offset = sum;
+        }
+        let mut cursors = offsets.clone();
+        let mut adjacency = vec![0u32; offsets[vertex_count]];
+        for (triangle_index, triangle) in self.triangles.iter().enumerate() {
+            for index in triangle.0 {
+                let cursor = &mut cursors[index as usize];
+                adjacency[*cursor] = triangle_index as u32;
+                *cursor += 1;
+            }
+        }
+
+        let mut remaining = (0..vertex_count)
+            .map(|i| offsets[i + 1] - offsets[i])
+            .collect::<Vec<_>>();
+        let mut cache_positions = vec![None; vertex_count];
+        let mut emitted = vec![false; self.triangles.len()];
+        let mut cache = Vec::<u32>::with_capacity(VERTEX_CACHE_SIZE + 3);
+        let mut new_cache = Vec::<u32>::with_capacity(VERTEX_CACHE_SIZE + 3);
+        let mut triangles = Vec::with_capacity(self.triangles.len());
+
+        let triangle_score = |triangle: &TriangleDefinition,
+                              cache_positions: &[Option<usize>],
+                              remaining: &[usize]| {
+            triangle
+                .0
+                .iter()
+                .map(|i| vertex_cache_score(cache_positions[*i as usize], remaining[*i as usize]))
+                .sum::<f32>()
+        };
+
+        // Ties are resolved in favor of the first triangle to keep the result deterministic.
+        let mut best = None;
+        let mut best_score = f32::MIN;
+        for (triangle_index, triangle) in self.triangles.iter().enumerate() {
+            let score = triangle_score(triangle, &cache_positions, &remaining);
+            if score > best_score {
+                best_score = score;
+                best = Some(triangle_index);
+            }
+        }
+
+        let mut dead_end_cursor = 0;
+        while let Some(triangle_index) = best {
+            emitted[triangle_index] = true;
+            let triangle = self.triangles[triangle_index];
+            for index in triangle.0 {
+                remaining[index as usize] -= 1;
+            }
+
+            new_cache.clear();
+            for index in triangle.0.iter().chain(cache.iter()) {
+                if !new_cache.contains(index) {
+                    new_cache.push(*index);
+                }
+            }
+            for (position, index) in new_cache.iter().enumerate() {
+                cache_positions[*index as usize] = if position < VERTEX_CACHE_SIZE {
+                    Some(position)
+                } else {
+                    None
+                };
+            }
+            new_cache.truncate(VERTEX_CACHE_SIZE);
+            std::mem::swap(&mut cache, &mut new_cache);
+
+            triangles.push(triangle);
+
+            // The next triangle is the best one among the triangles that use cached vertices.
+            best = None;
+            best_score = f32::MIN;
+            for index in cache.iter() {
+                let index = *index as usize;
+                for adjacent in adjacency[offsets[index]..offsets[index + 1]].iter() {
+                    let adjacent = *adjacent as usize;
+                    if !emitted[adjacent] {
+                        let score =
+                            triangle_score(&self.triangles[adjacent], &cache_positions, &remaining);
+                        if score > best_score {
+                            best_score = score;
+                            best = Some(adjacent);
+                        }
+                    }
+                }
+            }
+
+            // Dead end - continue from the first triangle that wasn't emitted yet.
+            if best.is_none() {
+                while dead_end_cursor < emitted.len() && emitted[dead_end_cursor] {
+                    dead_end_cursor += 1;
+                }
+                if dead_end_cursor < emitted.len() {
+                    best = Some(dead_end_cursor);
+                }
+            }
         }
+
+        self.triangles = triangles;
+
+        // Reorder vertices in order of their first use.
+        let mut remap = vec![u32::MAX; vertex_count];
+        let mut next = 0;
+        for triangle in self.triangles.iter() {
+            for index in triangle.0 {
+                if remap[index as usize] == u32::MAX {
+                    remap[index as usize] = next;
+                    next += 1;
+                }
+            }
+        }
+        for new_index in remap.iter_mut() {
+            if *new_index == u32::MAX {
+                *new_index = next;
+                next += 1;
+            }
+        }
+
+        let mut slots = std::iter::repeat_with(|| None)
+            .take(vertex_count)
+            .collect::<Vec<_>>();
+        for (vertex, new_index) in std::mem::take(&mut self.vertices).into_iter().zip(&remap) {
+            slots[*new_index as usize] = Some(vertex);
+        }
+        let vertices = slots.into_iter().flatten().collect();
+        self.remap(vertices, &remap);
+
+        remap
+    }
+
+    /// Calculates average amount of vertex cache misses per triangle (ACMR) for a FIFO vertex cache
+    /// of the given size. It is a common metric of the efficiency of vertex cache usage, the lower
+    /// the better: `3.0` is the worst possible value, while regular grids could approach `0.5`.
+    /// Returns `0.0` if the mesh has no triangles.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a triangle has an index of a vertex that is out of bounds.
+    pub fn average_cache_miss_ratio(&self, cache_size: usize) -> f32 {
+        if self.triangles.is_empty() {
+            return 0.0;
+        }
+
+        // A vertex is in the cache if less than `cache_size` misses happened since it was loaded.
+        let mut load_times = vec![None; self.vertices.len()];
+        let mut misses = 0usize;
+        for triangle in self.triangles.iter() {
+            for index in triangle.0 {
+                let load_time = &mut load_times[index as usize];
+                if !matches!(load_time, Some(time) if misses - *time < cache_size) {
+                    *load_time = Some(misses);
+                    misses += 1;
+                }
+            }
+        }
+
+        misses as f32 / self.triangles.len() as f32
+    }
+
+    fn remap(&mut self, vertices: Vec<T>, remap: &[u32]) -> usize {
+        for triangle in self.triangles.iter_mut() {
+            for index in triangle.indices_mut() {
+                *index = remap[*index as usize];
+            }
+        }
+        for index in self
+            .lines
+            .iter_mut()
+            .flatten()
+            .chain(self.points.iter_mut())
+        {
+            *index = remap[*index as usize];
+        }
+        let merged = remap.len() - vertices.len();
+        self.vertices = vertices;
+        merged
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::{algebra::Vector3, math::TriangleDefinition},
+        utils::raw_mesh::{NonManifoldEdge, PrimitiveTopology, RawMesh, RawMeshBuilder},
+    };
+
+    #[derive(Hash, PartialEq, Debug)]
+    struct Vertex {
+        position: [i32; 3],
+        tex_coord: [i32; 2],
+    }
+
+    // Two triangles of a quad with no shared vertices.
+    fn make_flat_quad() -> RawMesh<Vertex> {
+        let vertex = |x, y, u| Vertex {
+            position: [x, y, 0],
+            tex_coord: [u, 0],
+        };
+        RawMesh {
+            vertices: vec![
+                vertex(0, 0, 0),
+                vertex(1, 0, 0),
+                vertex(1, 1, 0),
+                vertex(0, 0, 0),
+                vertex(1, 1, 1),
+                vertex(0, 1, 0),
+            ],
+            triangles: vec![TriangleDefinition([0, 1, 2]), TriangleDefinition([3, 4, 5])],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_optimize() {
+        let mut mesh = make_flat_quad();
+        assert_eq!(mesh.optimize(), 1);
+        assert_eq!(mesh.vertices.len(), 5);
+        assert_eq!(
+            mesh.triangles,
+            vec![TriangleDefinition([0, 1, 2]), TriangleDefinition([0, 3, 4])]
+        );
+
+        // Must be idempotent.
+        assert_eq!(mesh.optimize(), 0);
+        assert_eq!(mesh.vertices.len(), 5);
+    }
+
+    #[test]
+    fn test_metrics() {
+        let mesh = make_flat_quad();
+        let position =
+            |v: &Vertex| Vector3::new(v.position[0], v.position[1], v.position[2]).cast();
+        assert_eq!(mesh.vertex_count(), 6);
+        assert_eq!(mesh.triangle_count(), 2);
+        assert_eq!(mesh.triangles().nth(1).unwrap()[1].tex_coord, [1, 0]);
+        assert_eq!(mesh.surface_area(position), 1.0);
+
+        let bounds = mesh.bounding_box(position).unwrap();
+        assert_eq!(bounds.min, Vector3::new(0.0, 0.0, 0.0));
+        assert_eq!(bounds.max, Vector3::new(1.0, 1.0, 0.0));
+
+        let empty = RawMesh::<Vertex>::default();
+        assert!(empty.bounding_box(position).is_none());
+    }
+
+    #[test]
+    fn test_optimize_by_position() {
+        let mut mesh = make_flat_quad();
+        assert_eq!(mesh.optimize_by(|v| v.position), 2);
+        assert_eq!(mesh.vertices.len(), 4);
+        assert_eq!(
+            mesh.triangles,
+            vec![TriangleDefinition([0, 1, 2]), TriangleDefinition([0, 2, 3])]
+        );
+        assert_eq!(mesh.optimize_by(|v| v.position), 0);
+    }
+
+    fn make_tetrahedron() -> RawMesh<[i32; 3]> {
+        RawMesh {
+            vertices: vec![[0, 0, 0], [1, 0, 0], [0, 1, 0], [0, 0, 1]],
+            triangles: vec![
+                TriangleDefinition([0, 2, 1]),
+                TriangleDefinition([0, 1, 3]),
+                TriangleDefinition([1, 2, 3]),
+                TriangleDefinition([2, 0, 3]),
+            ],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_check_manifold() {
+        let mut mesh = make_tetrahedron();
+        let report = mesh.check_manifold();
+        assert!(report.is_manifold());
+        assert!(report.isolated_vertices.is_empty());
+
+        // A hole.
+        let removed = mesh.triangles.pop().unwrap();
+        let report = mesh.check_manifold();
+        assert!(!report.is_manifold());
+        assert_eq!(
+            report.non_manifold_edges,
+            vec![
+                NonManifoldEdge {
+                    a: 0,
+                    b: 2,
+                    triangle_count: 1
+                },
+                NonManifoldEdge {
+                    a: 0,
+                    b: 3,
+                    triangle_count: 1
+                },
+                NonManifoldEdge {
+                    a: 2,
+                    b: 3,
+                    triangle_count: 1
+                }
+            ]
+        );
+
+        // An edge shared by three triangles, an isolated vertex, a duplicate (with different
+        // winding) and a degenerate triangle.
+        mesh.triangles.push(removed);
+        mesh.vertices.push([5, 5, 5]);
+        mesh.vertices.push([1, 1, 1]);
+        mesh.triangles.push(TriangleDefinition([0, 1, 5]));
+        mesh.triangles.push(TriangleDefinition([3, 1, 2]));
+        mesh.triangles.push(TriangleDefinition([0, 0, 1]));
+        mesh.triangles.push(TriangleDefinition([0, 1, 100]));
+        let report = mesh.check_manifold();
+        assert_eq!(report.isolated_vertices, vec![4]);
+        assert_eq!(report.duplicate_triangles, vec![5]);
+        assert_eq!(report.degenerate_triangles, vec![6, 7]);
+        assert!(report.non_manifold_edges.contains(&NonManifoldEdge {
+            a: 0,
+            b: 1,
+            triangle_count: 3
+        }));
+    }
+
+    #[test]
+    fn test_check_manifold_by() {
+        // Every triangle has its own vertices, every vertex has unique texture coordinates.
+        let tetrahedron = make_tetrahedron();
+        let mut mesh = RawMesh::default();
+        for (n, [a, b, c]) in tetrahedron.triangles().enumerate() {
+            let first = mesh.vertices.len() as u32;
+            for position in [a, b, c] {
+                mesh.vertices.push(Vertex {
+                    position: *position,
+                    tex_coord: [n as i32, first as i32],
+                });
+            }
+            mesh.triangles
+                .push(TriangleDefinition([first, first + 1, first + 2]));
+        }
+
+        assert_eq!(mesh.check_manifold().non_manifold_edges.len(), 12);
+
+        let report = mesh.check_manifold_by(|v| v.position);
+        assert!(report.is_manifold());
+        assert!(report.isolated_vertices.is_empty());
+    }
+
+    #[test]
+    fn test_insert_strip_and_fan() {
+        // A strip of two quads, the last two vertices are duplicated to stitch another strip.
+        let mut builder = RawMeshBuilder::<[i32; 2]>::default();
+        let strip = [
+            [0, 0],
+            [0, 1],
+            [1, 0],
+            [1, 1],
+            [2, 0],
+            [2, 1],
+            [2, 1],
+            [3, 0],
+        ];
+        assert_eq!(builder.insert_strip(strip), 4);
+        let mesh = builder.build();
+        assert_eq!(mesh.vertex_count(), 7);
+        assert_eq!(
+            mesh.triangles,
+            vec![
+                TriangleDefinition([0, 1, 2]),
+                TriangleDefinition([2, 1, 3]),
+                TriangleDefinition([2, 3, 4]),
+                TriangleDefinition([4, 3, 5]),
+            ]
+        );
+
+        let mut builder = RawMeshBuilder::<[i32; 2]>::default();
+        builder.insert([5, 5]);
+        builder.insert([6, 5]);
+        builder.insert([6, 6]);
+        assert_eq!(builder.insert_fan([[0, 0], [1, 0], [1, 1], [0, 1]]), 2);
+        assert_eq!(builder.insert_fan([[0, 0], [1, 0]]), 0);
+        let mesh = builder.build();
+        assert_eq!(
+            mesh.triangles,
+            vec![
+                TriangleDefinition([0, 1, 2]),
+                TriangleDefinition([3, 4, 5]),
+                TriangleDefinition([3, 5, 6]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_line_and_point_topology() {
+        let mut builder =
+            RawMeshBuilder::<[i32; 2]>::default().with_topology(PrimitiveTopology::Lines);
+        // A closed polyline with a repeated vertex, that gives a degenerate segment.
+        assert_eq!(
+            builder.insert_strip([[0, 0], [1, 0], [1, 0], [1, 1], [0, 0]]),
+            3
+        );
+        assert_eq!(builder.insert_fan([[0, 0], [2, 0], [0, 2]]), 2);
+        builder.insert([5, 5]);
+        builder.insert([5, 5]);
+        builder.insert([6, 6]);
+        let mesh = builder.build();
+        assert_eq!(mesh.topology, PrimitiveTopology::Lines);
+        assert_eq!(mesh.vertex_count(), 7);
+        assert!(mesh.triangles.is_empty());
+        assert_eq!(mesh.lines, vec![[0, 1], [1, 2], [2, 0], [0, 3], [0, 4]]);
+        assert_eq!(mesh.primitive_count(), 5);
+        assert_eq!(mesh.lines().last(), Some([&[0, 0], &[0, 2]]));
+
+        let mut builder =
+            RawMeshBuilder::<[i32; 2]>::default().with_topology(PrimitiveTopology::Points);
+        builder.insert([0, 0]);
+        builder.insert([1, 0]);
+        assert_eq!(builder.insert_strip([[0, 0], [2, 0]]), 2);
+        let mesh = builder.build();
+        assert_eq!(mesh.topology, PrimitiveTopology::Points);
+        assert_eq!(mesh.points, vec![0, 1, 2]);
+        assert_eq!(
+            mesh.points().collect::<Vec<_>>(),
+            vec![&[0, 0], &[1, 0], &[2, 0]]
+        );
+    }
+
+    fn make_shuffled_grid(size: u32) -> RawMesh<[u32; 2]> {
+        let width = size + 1;
+        let mut triangles = Vec::new();
+        for y in 0..size {
+            for x in 0..size {
+                let i = y * width + x;
+                triangles.push(TriangleDefinition([i, i + 1, i + width + 1]));
+                triangles.push(TriangleDefinition([i, i + width + 1, i + width]));
+            }
+        }
+        // 7919 is a prime number, so it gives a deterministic permutation of the triangles that
+        // scatters neighbouring triangles across the index buffer.
+        let count = triangles.len();
+        RawMesh {
+            vertices: (0..width * width).map(|i| [i % width, i / width]).collect(),
+            triangles: (0..count).map(|i| triangles[(i * 7919) % count]).collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_optimize_vertex_cache() {
+        let mut mesh = make_shuffled_grid(64);
+        let original = mesh.clone();
+        // The shuffled grid has no shared vertices between adjacent triangles - the worst case.
+        assert_eq!(mesh.average_cache_miss_ratio(16), 3.0);
+
+        let remap = mesh.optimize_vertex_cache();
+
+        // Optimal value for a grid is about 0.5, the optimized mesh must get close to it.
+        assert!(mesh.average_cache_miss_ratio(16) < 0.75);
+
+        // The mesh must have the same triangles, only the order of them is changed.
+        assert_eq!(mesh.triangle_count(), original.triangle_count());
+        let mut expected = original
+            .triangles()
+            .map(|t| [*t[0], *t[1], *t[2]])
+            .collect::<Vec<_>>();
+        let mut actual = mesh
+            .triangles()
+            .map(|t| [*t[0], *t[1], *t[2]])
+            .collect::<Vec<_>>();
+        expected.sort_unstable();
+        actual.sort_unstable();
+        assert_eq!(actual, expected);
+        for (old_index, new_index) in remap.iter().enumerate() {
+            assert_eq!(
+                original.vertices[old_index],
+                mesh.vertices[*new_index as usize]
+            );
+        }
+
+        // Vertices must be in the order of the first use.
+        assert_eq!(mesh.triangles[0], TriangleDefinition([0, 1, 2]));
+
+        // Must be deterministic.
+        let mut other = original;
+        assert_eq!(other.optimize_vertex_cache(), remap);
+        assert_eq!(other.triangles, mesh.triangles);
     }
 }
@@ -0,0 +1,213 @@
+//! Deterministic pseudo-random number generator. See [`Pcg`] docs for more info.
+
+use crate::core::{reflect::prelude::*, visitor::prelude::*};
+use std::ops::Range;
+
+const MULTIPLIER: u64 = 6364136223846793005;
+const DEFAULT_STREAM: u64 = 1442695040888963407;
+
+/// Small and fast pseudo-random number generator (PCG-XSH-RR, 32-bit output). Unlike thread-local
+/// random number generators, it depends only on its seed, so the same seed (and stream) produces
+/// exactly the same sequence on every machine. This makes it suitable for replays and deterministic
+/// networking. Its state can be saved and restored using [`Visit`] trait, so the sequence continues
+/// exactly where it was stopped.
+///
+/// ## Streams
+///
+/// Every generator has a stream selector, generators with the same seed but different streams
+/// produce independent sequences. It is useful to create per-entity generators, for example by
+/// using some unique entity index as a stream: `Pcg::with_stream(world_seed, entity_index)`.
+///
+/// ## Example
+///
+/// ```rust
+/// use fyrox::utils::rng::Pcg;
+///
+/// let mut a = Pcg::new(42);
+/// let mut b = Pcg::new(42);
+/// assert_eq!(a.next_u32(), b.next_u32());
+///
+/// let damage = a.gen_range_u32(10..20);
+/// assert!((10..20).contains(&damage));
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Visit, Reflect)]
+pub struct Pcg {
+    state: u64,
+    increment: u64,
+}
+
+impl Default for Pcg {
+    fn default() -> Self {
+        Self::new(0)
+    }
+}
+
+impl Pcg {
+    /// Creates new generator with the given seed and the default stream.
+    pub fn new(seed: u64) -> Self {
+        Self::with_stream(seed, DEFAULT_STREAM)
+    }
+
+    /// Creates new generator with the given seed and the given stream. Generators with different
+    /// streams produce independent sequences even if they have the same seed.
+    pub fn with_stream(seed: u64, stream: u64) -> Self {
+        let mut rng = Self {
+            state: 0,
+            // Increment must be odd.
+            increment: (stream << 1) | 1,
+        };
+        rng.step();
+        rng.state = rng.state.wrapping_add(seed);
+        rng.step();
+        rng
+    }
+
+    fn step(&mut self) {
+        self.state = self
+            .state
+            .wrapping_mul(MULTIPLIER)
+            .wrapping_add(self.increment);
+    }
+
+    /// Returns next random 32-bit number.
+    pub fn next_u32(&mut self) -> u32 {
+        let old = self.state;
+        self.step();
+        let xor_shifted = (((old >> 18) ^ old) >> 27) as u32;
+        let rotation = (old >> 59) as u32;
+        xor_shifted.rotate_right(rotation)
+    }
+
+    /// Returns next random 64-bit number.
+    pub fn next_u64(&mut self) -> u64 {
+        ((self.next_u32() as u64) << 32) | self.next_u32() as u64
+    }
+
+    /// Returns next random number in `[0; 1)` range.
+    pub fn next_f32_unit(&mut self) -> f32 {
+        // 24 bits is the precision of f32 mantissa.
+        (self.next_u32() >> 8) as f32 * (1.0 / (1u32 << 24) as f32)
+    }
+
+    /// Returns next random number in `[0; 1)` range.
+    pub fn next_f64_unit(&mut self) -> f64 {
+        // 53 bits is the precision of f64 mantissa.
+        (self.next_u64() >> 11) as f64 * (1.0 / (1u64 << 53) as f64)
+    }
+
+    /// Returns next random boolean value.
+    pub fn next_bool(&mut self) -> bool {
+        self.next_u32() & 1 == 1
+    }
+
+    /// Returns random number in the given range (end is excluded). The distribution is uniform (no
+    /// modulo bias). Returns `range.start` if the range is empty.
+    pub fn gen_range_u32(&mut self, range: Range<u32>) -> u32 {
+        if range.end <= range.start {
+            return range.start;
+        }
+        let span = range.end - range.start;
+        // Reject values from the incomplete "tail" to keep the distribution uniform.
+        let threshold = span.wrapping_neg() % span;
+        loop {
+            let value = self.next_u32();
+            if value >= threshold {
+                return range.start + value % span;
+            }
+        }
+    }
+
+    /// Returns random number in the given range (end is excluded). Returns `range.start` if the
+    /// range is empty.
+    pub fn gen_range_i32(&mut self, range: Range<i32>) -> i32 {
+        if range.end <= range.start {
+            return range.start;
+        }
+        let span = range.end.wrapping_sub(range.start) as u32;
+        range.start.wrapping_add(self.gen_range_u32(0..span) as i32)
+    }
+
+    /// Returns random number in the given range (end is excluded). Returns `range.start` if the
+    /// range is empty.
+    pub fn gen_range_f32(&mut self, range: Range<f32>) -> f32 {
+        if range.end <= range.start {
+            return range.start;
+        }
+        let value = range.start + (range.end - range.start) * self.next_f32_unit();
+        // Rounding may produce the end value, keep it out of the range.
+        if value >= range.end {
+            range.start
+        } else {
+            value
+        }
+    }
+
+    /// Returns a reference to a random element of the given slice, or `None` if the slice is empty.
+    pub fn choose<'a, T>(&mut self, slice: &'a [T]) -> Option<&'a T> {
+        if slice.is_empty() {
+            None
+        } else {
+            slice.get(self.gen_range_u32(0..slice.len() as u32) as usize)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::visitor::{Visit, Visitor},
+        utils::rng::Pcg,
+    };
+
+    #[test]
+    fn test_determinism() {
+        let mut a = Pcg::new(123);
+        let mut b = Pcg::new(123);
+        for _ in 0..100 {
+            assert_eq!(a.next_u32(), b.next_u32());
+        }
+
+        let mut c = Pcg::with_stream(123, 1);
+        let mut d = Pcg::with_stream(123, 2);
+        assert_ne!(
+            (0..4).map(|_| c.next_u32()).collect::<Vec<_>>(),
+            (0..4).map(|_| d.next_u32()).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_ranges() {
+        let mut rng = Pcg::new(7);
+        for _ in 0..1000 {
+            assert!((5..10).contains(&rng.gen_range_u32(5..10)));
+            assert!((-3..3).contains(&rng.gen_range_i32(-3..3)));
+            assert!((-1.0..2.0).contains(&rng.gen_range_f32(-1.0..2.0)));
+            assert!((0.0..1.0).contains(&rng.next_f32_unit()));
+            assert!((0.0..1.0).contains(&rng.next_f64_unit()));
+        }
+        assert_eq!(rng.gen_range_u32(4..4), 4);
+        assert!((i32::MIN..i32::MAX).contains(&rng.gen_range_i32(i32::MIN..i32::MAX)));
+        assert!(rng.choose::<u32>(&[]).is_none());
+        assert_eq!(rng.choose(&[1]), Some(&1));
+    }
+
+    #[test]
+    fn test_save_restore() {
+        let mut rng = Pcg::new(42);
+        rng.next_u32();
+
+        let mut visitor = Visitor::new();
+        rng.visit("Rng", &mut visitor).unwrap();
+        let data = visitor.save_binary_to_vec().unwrap();
+
+        let expected = (0..10).map(|_| rng.next_u32()).collect::<Vec<_>>();
+
+        let mut visitor = Visitor::load_from_memory(data).unwrap();
+        let mut restored = Pcg::default();
+        restored.visit("Rng", &mut visitor).unwrap();
+        assert_eq!(
+            (0..10).map(|_| restored.next_u32()).collect::<Vec<_>>(),
+            expected
+        );
+    }
+}
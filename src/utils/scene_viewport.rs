@@ -0,0 +1,414 @@
+//! A widget that shows contents of a scene render target. See [`SceneViewport`] docs for more info.
+
+use crate::{
+    core::{algebra::Vector2, color::Color, math::ray::Ray, parking_lot::Mutex, pool::Handle},
+    gui::{
+        brush::Brush,
+        define_constructor, define_widget_deref,
+        draw::{CommandTexture, Draw, DrawingContext},
+        message::{MessageDirection, MouseButton, UiMessage},
+        widget::{Widget, WidgetBuilder, WidgetMessage},
+        BuildContext, Control, UiNode, UserInterface,
+    },
+    resource::texture::{TextureKind, TextureResource, TextureResourceExtension},
+    scene::camera::Camera,
+    utils::into_gui_texture,
+};
+use std::{
+    any::{Any, TypeId},
+    fmt::{Debug, Formatter},
+    ops::{Deref, DerefMut},
+    sync::{mpsc::Sender, Arc},
+};
+
+/// Kind of an input event, that was forwarded from a [`SceneViewport`].
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum SceneViewportInputKind {
+    /// A mouse button was pressed.
+    MouseDown(MouseButton),
+    /// A mouse button was released.
+    MouseUp(MouseButton),
+    /// Mouse cursor was moved.
+    MouseMove,
+    /// Mouse wheel was scrolled by the given amount of lines.
+    MouseWheel(f32),
+}
+
+/// An input event, that was forwarded from a [`SceneViewport`].
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct SceneViewportInput {
+    /// Kind of the event.
+    pub kind: SceneViewportInputKind,
+    /// Cursor position in normalized viewport coordinates. `(0.0, 0.0)` is the left top corner of
+    /// the viewport and `(1.0, 1.0)` is the right bottom corner.
+    pub position: Vector2<f32>,
+    /// Size of the render target (in pixels) at the moment of the event.
+    pub frame_size: Vector2<f32>,
+}
+
+impl SceneViewportInput {
+    /// Returns cursor position in pixels of the render target.
+    pub fn frame_position(&self) -> Vector2<f32> {
+        self.position.component_mul(&self.frame_size)
+    }
+
+    /// Creates a picking ray that goes through the cursor position using the given camera. The
+    /// camera must render into the render target of the viewport.
+    pub fn make_ray(&self, camera: &Camera) -> Ray {
+        camera.make_ray(self.frame_position(), self.frame_size)
+    }
+}
+
+/// A callback, that receives every input event of a [`SceneViewport`].
+#[derive(Clone)]
+#[allow(clippy::type_complexity)]
+pub struct SceneViewportCallback(pub Arc<Mutex<dyn FnMut(&SceneViewportInput) + Send>>);
+
+impl SceneViewportCallback {
+    /// Wraps the given closure in the callback.
+    pub fn new<F: FnMut(&SceneViewportInput) + Send + 'static>(callback: F) -> Self {
+        Self(Arc::new(Mutex::new(callback)))
+    }
+}
+
+impl PartialEq for SceneViewportCallback {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Debug for SceneViewportCallback {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "SceneViewportCallback")
+    }
+}
+
+/// A set of messages that could be used to alter [`SceneViewport`] widget state at runtime.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SceneViewportMessage {
+    /// Sets new input callback of the viewport, `None` disables input forwarding.
+    InputCallback(Option<SceneViewportCallback>),
+    /// Sets new minimal time (in seconds) between two consecutive resizes of the render target.
+    ResizeDelay(f32),
+}
+
+impl SceneViewportMessage {
+    define_constructor!(
+        /// Creates [`SceneViewportMessage::InputCallback`] message.
+        SceneViewportMessage:InputCallback => fn input_callback(Option<SceneViewportCallback>), layout: false
+    );
+    define_constructor!(
+        /// Creates [`SceneViewportMessage::ResizeDelay`] message.
+        SceneViewportMessage:ResizeDelay => fn resize_delay(f32), layout: false
+    );
+}
+
+/// Scene viewport is a widget that shows contents of a render target of a scene. It owns the render
+/// target and keeps its size equal to the size of the widget, so the picture is never stretched and
+/// cameras of the scene always have correct aspect ratio. Resizing is throttled (see
+/// [`SceneViewportBuilder::with_resize_delay`]), so dragging a window border won't re-create scene
+/// frame buffers every frame.
+///
+/// The render target must be assigned to a scene once, the widget changes its size in-place:
+///
+/// ```rust,no_run
+/// # use fyrox::{
+/// #     core::pool::Handle,
+/// #     gui::{widget::WidgetBuilder, UiNode, UserInterface},
+/// #     scene::Scene,
+/// #     utils::scene_viewport::{SceneViewport, SceneViewportBuilder, SceneViewportCallback},
+/// # };
+/// fn create_viewport(ui: &mut UserInterface, scene: &mut Scene) -> Handle<UiNode> {
+///     let viewport = SceneViewportBuilder::new(WidgetBuilder::new())
+///         .with_input_callback(SceneViewportCallback::new(|input| {
+///             println!("{:?} at {}", input.kind, input.position);
+///         }))
+///         .build(&mut ui.build_ctx());
+///
+///     scene.render_target = Some(
+///         ui.node(viewport)
+///             .cast::<SceneViewport>()
+///             .unwrap()
+///             .render_target()
+///             .clone(),
+///     );
+///
+///     viewport
+/// }
+/// ```
+///
+/// ## Input forwarding
+///
+/// Mouse events, that the widget receives, are passed to an optional input callback. Cursor position
+/// is converted to normalized viewport coordinates, use [`SceneViewportInput::make_ray`] to do
+/// picking.
+#[derive(Clone)]
+pub struct SceneViewport {
+    /// Base widget of the viewport.
+    pub widget: Widget,
+    render_target: TextureResource,
+    resize_delay: f32,
+    resize_timer: f32,
+    input_callback: Option<SceneViewportCallback>,
+}
+
+define_widget_deref!(SceneViewport);
+
+impl SceneViewport {
+    /// Returns the render target of the viewport. It must be assigned to a scene which should be
+    /// shown in the viewport.
+    pub fn render_target(&self) -> &TextureResource {
+        &self.render_target
+    }
+
+    /// Returns current size (in pixels) of the render target.
+    pub fn frame_size(&self) -> Vector2<f32> {
+        if let TextureKind::Rectangle { width, height } = self.render_target.data_ref().kind() {
+            Vector2::new(width as f32, height as f32)
+        } else {
+            Vector2::default()
+        }
+    }
+
+    fn forward_input(&self, kind: SceneViewportInputKind, screen_position: Vector2<f32>) {
+        if let Some(callback) = self.input_callback.as_ref() {
+            let size = self.actual_local_size();
+            let local_position = self.screen_to_local(screen_position);
+            let input = SceneViewportInput {
+                kind,
+                position: Vector2::new(
+                    local_position.x / size.x.max(1.0),
+                    local_position.y / size.y.max(1.0),
+                ),
+                frame_size: self.frame_size(),
+            };
+            (callback.0.lock())(&input);
+        }
+    }
+}
+
+impl Control for SceneViewport {
+    fn query_component(&self, type_id: TypeId) -> Option<&dyn Any> {
+        if type_id == TypeId::of::<Self>() {
+            Some(self)
+        } else {
+            None
+        }
+    }
+
+    fn draw(&self, drawing_context: &mut DrawingContext) {
+        let bounds = self.widget.bounding_rect();
+        // Render targets are vertically flipped, because UI has origin at left top corner while
+        // graphics API has it at left bottom corner.
+        drawing_context.push_rect_filled(
+            &bounds,
+            Some(&[
+                Vector2::new(0.0, 1.0),
+                Vector2::new(1.0, 1.0),
+                Vector2::new(1.0, 0.0),
+                Vector2::new(0.0, 0.0),
+            ]),
+        );
+        drawing_context.commit(
+            self.clip_bounds(),
+            self.widget.background(),
+            CommandTexture::Texture(into_gui_texture(self.render_target.clone())),
+            None,
+        );
+    }
+
+    fn update(&mut self, dt: f32, _sender: &Sender<UiMessage>) {
+        self.resize_timer -= dt;
+        if self.resize_timer > 0.0 {
+            return;
+        }
+
+        let size = self.actual_local_size();
+        let width = (size.x.round() as u32).max(1);
+        let height = (size.y.round() as u32).max(1);
+
+        let mut render_target = self.render_target.data_ref();
+        let need_resize = match render_target.kind() {
+            TextureKind::Rectangle {
+                width: current_width,
+                height: current_height,
+            } => current_width != width || current_height != height,
+            _ => false,
+        };
+        if need_resize {
+            render_target.set_render_target_size(width, height);
+            self.resize_timer = self.resize_delay;
+        }
+    }
+
+    fn handle_routed_message(&mut self, ui: &mut UserInterface, message: &mut UiMessage) {
+        self.widget.handle_routed_message(ui, message);
+
+        if let Some(msg) = message.data::<WidgetMessage>() {
+            if message.destination() == self.handle && self.input_callback.is_some() {
+                match *msg {
+                    WidgetMessage::MouseDown { pos, button } => {
+                        ui.capture_mouse(self.handle);
+                        self.forward_input(SceneViewportInputKind::MouseDown(button), pos);
+                    }
+                    WidgetMessage::MouseUp { pos, button } => {
+                        ui.release_mouse_capture();
+                        self.forward_input(SceneViewportInputKind::MouseUp(button), pos);
+                    }
+                    WidgetMessage::MouseMove { pos, .. } => {
+                        self.forward_input(SceneViewportInputKind::MouseMove, pos);
+                    }
+                    WidgetMessage::MouseWheel { pos, amount } => {
+                        self.forward_input(SceneViewportInputKind::MouseWheel(amount), pos);
+                    }
+                    _ => (),
+                }
+            }
+        } else if let Some(msg) = message.data::<SceneViewportMessage>() {
+            if message.destination() == self.handle
+                && message.direction() == MessageDirection::ToWidget
+            {
+                match msg {
+                    SceneViewportMessage::InputCallback(callback) => {
+                        self.input_callback = callback.clone();
+                    }
+                    SceneViewportMessage::ResizeDelay(delay) => {
+                        self.resize_delay = *delay;
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Scene viewport builder is used to create [`SceneViewport`] widget instances and register them
+/// in the user interface.
+pub struct SceneViewportBuilder {
+    widget_builder: WidgetBuilder,
+    render_target: Option<TextureResource>,
+    resize_delay: f32,
+    input_callback: Option<SceneViewportCallback>,
+}
+
+impl SceneViewportBuilder {
+    /// Creates new scene viewport builder with the base widget builder specified.
+    pub fn new(widget_builder: WidgetBuilder) -> Self {
+        Self {
+            widget_builder,
+            render_target: None,
+            resize_delay: 0.1,
+            input_callback: None,
+        }
+    }
+
+    /// Sets an existing render target, that will be used by the viewport. It must be created with
+    /// [`TextureResourceExtension::new_render_target`]. If not set, the viewport will create a new
+    /// one.
+    pub fn with_render_target(mut self, render_target: TextureResource) -> Self {
+        self.render_target = Some(render_target);
+        self
+    }
+
+    /// Sets minimal time (in seconds) between two consecutive resizes of the render target. Default
+    /// is 0.1 seconds.
+    pub fn with_resize_delay(mut self, resize_delay: f32) -> Self {
+        self.resize_delay = resize_delay;
+        self
+    }
+
+    /// Sets a callback, that will receive input events of the viewport.
+    pub fn with_input_callback(mut self, callback: SceneViewportCallback) -> Self {
+        self.input_callback = Some(callback);
+        self
+    }
+
+    /// Builds the [`SceneViewport`] widget and adds it to the UI and returns its handle.
+    pub fn build(mut self, ctx: &mut BuildContext) -> Handle<UiNode> {
+        if self.widget_builder.background.is_none() {
+            self.widget_builder.background = Some(Brush::Solid(Color::WHITE))
+        }
+
+        let viewport = SceneViewport {
+            widget: self.widget_builder.build(),
+            render_target: self
+                .render_target
+                .unwrap_or_else(|| TextureResource::new_render_target(1, 1)),
+            resize_delay: self.resize_delay,
+            resize_timer: 0.0,
+            input_callback: self.input_callback,
+        };
+        ctx.add_node(UiNode::new(viewport))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::{algebra::Vector2, parking_lot::Mutex},
+        gui::{
+            message::{MessageDirection, MouseButton},
+            widget::{WidgetBuilder, WidgetMessage},
+            UserInterface,
+        },
+        utils::scene_viewport::{
+            SceneViewport, SceneViewportBuilder, SceneViewportCallback, SceneViewportInput,
+            SceneViewportInputKind,
+        },
+    };
+    use std::sync::Arc;
+
+    #[test]
+    fn test_scene_viewport() {
+        let screen_size = Vector2::new(1000.0, 1000.0);
+        let mut ui = UserInterface::new(screen_size);
+
+        let inputs = Arc::new(Mutex::new(Vec::<SceneViewportInput>::new()));
+        let inputs_clone = inputs.clone();
+        let viewport =
+            SceneViewportBuilder::new(WidgetBuilder::new().with_width(200.0).with_height(100.0))
+                .with_resize_delay(1.0)
+                .with_input_callback(SceneViewportCallback::new(move |input| {
+                    inputs_clone.lock().push(*input)
+                }))
+                .build(&mut ui.build_ctx());
+
+        let frame_size = |ui: &UserInterface| {
+            ui.node(viewport)
+                .cast::<SceneViewport>()
+                .unwrap()
+                .frame_size()
+        };
+
+        ui.update(screen_size, 0.0);
+        assert_eq!(frame_size(&ui), Vector2::new(200.0, 100.0));
+
+        // Resizing is throttled.
+        ui.send_message(WidgetMessage::width(
+            viewport,
+            MessageDirection::ToWidget,
+            400.0,
+        ));
+        while ui.poll_message().is_some() {}
+        ui.update(screen_size, 0.5);
+        assert_eq!(frame_size(&ui), Vector2::new(200.0, 100.0));
+        ui.update(screen_size, 0.6);
+        assert_eq!(frame_size(&ui), Vector2::new(400.0, 100.0));
+
+        ui.send_message(WidgetMessage::mouse_down(
+            viewport,
+            MessageDirection::FromWidget,
+            Vector2::new(100.0, 25.0),
+            MouseButton::Left,
+        ));
+        while ui.poll_message().is_some() {}
+
+        assert_eq!(
+            inputs.lock().as_slice(),
+            &[SceneViewportInput {
+                kind: SceneViewportInputKind::MouseDown(MouseButton::Left),
+                position: Vector2::new(0.25, 0.25),
+                frame_size: Vector2::new(400.0, 100.0),
+            }]
+        );
+    }
+}
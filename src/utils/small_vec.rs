@@ -0,0 +1,316 @@
+//! Small vector is a growable collection, that stores first `N` items inline (without heap
+//! allocation) and moves them to the heap only when there's more items. See [`SmallVec`] docs for
+//! more info.
+
+use crate::core::arrayvec::{self, ArrayVec};
+use std::{
+    fmt::{Debug, Formatter},
+    iter::FromIterator,
+    ops::{Deref, DerefMut},
+};
+
+/// A growable buffer for results of queries (for example paths of [`super::astar::PathFinder`]).
+/// It is implemented for [`Vec`] and [`SmallVec`], so the caller decides whether small results
+/// should be stored without heap allocation.
+pub trait ResultBuffer<T>: DerefMut<Target = [T]> {
+    /// Removes every item from the buffer.
+    fn clear(&mut self);
+
+    /// Adds an item at the end of the buffer.
+    fn push(&mut self, item: T);
+}
+
+impl<T> ResultBuffer<T> for Vec<T> {
+    fn clear(&mut self) {
+        Vec::clear(self)
+    }
+
+    fn push(&mut self, item: T) {
+        Vec::push(self, item)
+    }
+}
+
+impl<T, const N: usize> ResultBuffer<T> for SmallVec<T, N> {
+    fn clear(&mut self) {
+        SmallVec::clear(self)
+    }
+
+    fn push(&mut self, item: T) {
+        SmallVec::push(self, item)
+    }
+}
+
+#[derive(Clone)]
+enum Storage<T, const N: usize> {
+    Inline(ArrayVec<T, N>),
+    Heap(Vec<T>),
+}
+
+/// A vector, that stores up to `N` items inline and "spills" them to the heap when there's more
+/// items. It is useful for hot paths that usually produce small results, such as short paths or
+/// a few contacts, since typical results won't allocate memory at all. Once the vector is spilled,
+/// it stays on the heap (even after [`Self::clear`]) to re-use the allocated memory.
+///
+/// The vector dereferences to a slice, so every slice method is available. Use [`Self::into_vec`]
+/// or [`From`] to convert it to [`Vec`].
+///
+/// ```rust
+/// use fyrox::utils::small_vec::SmallVec;
+///
+/// let mut numbers = SmallVec::<u32, 2>::new();
+/// numbers.push(1);
+/// numbers.push(2);
+/// assert!(!numbers.is_spilled());
+/// numbers.push(3);
+/// assert!(numbers.is_spilled());
+/// assert_eq!(numbers.into_vec(), vec![1, 2, 3]);
+/// ```
+#[derive(Clone)]
+pub struct SmallVec<T, const N: usize> {
+    storage: Storage<T, N>,
+}
+
+impl<T, const N: usize> Default for SmallVec<T, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, const N: usize> SmallVec<T, N> {
+    /// Creates new empty vector, it does not allocate memory.
+    pub fn new() -> Self {
+        Self {
+            storage: Storage::Inline(ArrayVec::new()),
+        }
+    }
+
+    /// Returns `true` if the items are stored on the heap.
+    pub fn is_spilled(&self) -> bool {
+        matches!(self.storage, Storage::Heap(_))
+    }
+
+    /// Returns amount of items that the vector could hold without allocating memory.
+    pub fn capacity(&self) -> usize {
+        match &self.storage {
+            Storage::Inline(_) => N,
+            Storage::Heap(heap) => heap.capacity(),
+        }
+    }
+
+    /// Adds an item at the end of the vector. Moves the items to the heap, if there's no more
+    /// space for the item inline.
+    pub fn push(&mut self, item: T) {
+        match &mut self.storage {
+            Storage::Inline(inline) => {
+                if let Err(err) = inline.try_push(item) {
+                    let mut heap = Vec::with_capacity((N * 2).max(4));
+                    heap.extend(inline.drain(..));
+                    heap.push(err.element());
+                    self.storage = Storage::Heap(heap);
+                }
+            }
+            Storage::Heap(heap) => heap.push(item),
+        }
+    }
+
+    /// Removes the last item of the vector and returns it, or `None` if the vector is empty.
+    pub fn pop(&mut self) -> Option<T> {
+        match &mut self.storage {
+            Storage::Inline(inline) => inline.pop(),
+            Storage::Heap(heap) => heap.pop(),
+        }
+    }
+
+    /// Shortens the vector, keeping first `len` items.
+    pub fn truncate(&mut self, len: usize) {
+        match &mut self.storage {
+            Storage::Inline(inline) => inline.truncate(len),
+            Storage::Heap(heap) => heap.truncate(len),
+        }
+    }
+
+    /// Removes every item from the vector. Allocated memory (if any) is kept.
+    pub fn clear(&mut self) {
+        match &mut self.storage {
+            Storage::Inline(inline) => inline.clear(),
+            Storage::Heap(heap) => heap.clear(),
+        }
+    }
+
+    /// Returns a slice with all items of the vector.
+    pub fn as_slice(&self) -> &[T] {
+        match &self.storage {
+            Storage::Inline(inline) => inline.as_slice(),
+            Storage::Heap(heap) => heap.as_slice(),
+        }
+    }
+
+    /// Returns a mutable slice with all items of the vector.
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        match &mut self.storage {
+            Storage::Inline(inline) => inline.as_mut_slice(),
+            Storage::Heap(heap) => heap.as_mut_slice(),
+        }
+    }
+
+    /// Converts the vector into [`Vec`]. Spilled vector is converted without copying.
+    pub fn into_vec(self) -> Vec<T> {
+        match self.storage {
+            Storage::Inline(inline) => inline.into_iter().collect(),
+            Storage::Heap(heap) => heap,
+        }
+    }
+}
+
+impl<T, const N: usize> Deref for SmallVec<T, N> {
+    type Target = [T];
+
+    fn deref(&self) -> &Self::Target {
+        self.as_slice()
+    }
+}
+
+impl<T, const N: usize> DerefMut for SmallVec<T, N> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.as_mut_slice()
+    }
+}
+
+impl<T, const N: usize> AsRef<[T]> for SmallVec<T, N> {
+    fn as_ref(&self) -> &[T] {
+        self.as_slice()
+    }
+}
+
+impl<T: Debug, const N: usize> Debug for SmallVec<T, N> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T: PartialEq, const N: usize> PartialEq for SmallVec<T, N> {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_slice() == other.as_slice()
+    }
+}
+
+impl<T: Eq, const N: usize> Eq for SmallVec<T, N> {}
+
+impl<T, const N: usize> From<Vec<T>> for SmallVec<T, N> {
+    fn from(vec: Vec<T>) -> Self {
+        Self {
+            storage: Storage::Heap(vec),
+        }
+    }
+}
+
+impl<T, const N: usize> From<SmallVec<T, N>> for Vec<T> {
+    fn from(small_vec: SmallVec<T, N>) -> Self {
+        small_vec.into_vec()
+    }
+}
+
+impl<T, const N: usize> Extend<T> for SmallVec<T, N> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for item in iter {
+            self.push(item);
+        }
+    }
+}
+
+impl<T, const N: usize> FromIterator<T> for SmallVec<T, N> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut small_vec = Self::new();
+        small_vec.extend(iter);
+        small_vec
+    }
+}
+
+/// An owning iterator over the items of [`SmallVec`].
+pub enum IntoIter<T, const N: usize> {
+    /// Iterator over inline items.
+    Inline(arrayvec::IntoIter<T, N>),
+    /// Iterator over spilled items.
+    Heap(std::vec::IntoIter<T>),
+}
+
+impl<T, const N: usize> Iterator for IntoIter<T, N> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            IntoIter::Inline(inline) => inline.next(),
+            IntoIter::Heap(heap) => heap.next(),
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match self {
+            IntoIter::Inline(inline) => inline.size_hint(),
+            IntoIter::Heap(heap) => heap.size_hint(),
+        }
+    }
+}
+
+impl<T, const N: usize> IntoIterator for SmallVec<T, N> {
+    type Item = T;
+    type IntoIter = IntoIter<T, N>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        match self.storage {
+            Storage::Inline(inline) => IntoIter::Inline(inline.into_iter()),
+            Storage::Heap(heap) => IntoIter::Heap(heap.into_iter()),
+        }
+    }
+}
+
+impl<'a, T, const N: usize> IntoIterator for &'a SmallVec<T, N> {
+    type Item = &'a T;
+    type IntoIter = std::slice::Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, T, const N: usize> IntoIterator for &'a mut SmallVec<T, N> {
+    type Item = &'a mut T;
+    type IntoIter = std::slice::IterMut<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::utils::small_vec::SmallVec;
+
+    #[test]
+    fn test_small_vec_spill() {
+        let mut small_vec = SmallVec::<u32, 4>::new();
+        small_vec.extend(0..4);
+        assert!(!small_vec.is_spilled());
+        assert_eq!(small_vec.capacity(), 4);
+        assert_eq!(small_vec.as_slice(), &[0, 1, 2, 3]);
+
+        small_vec.push(4);
+        assert!(small_vec.is_spilled());
+        assert_eq!(small_vec.len(), 5);
+        small_vec.reverse();
+        assert_eq!(small_vec.pop(), Some(0));
+
+        // Memory is re-used after clearing.
+        small_vec.clear();
+        assert!(small_vec.is_empty());
+        assert!(small_vec.is_spilled());
+
+        let small_vec = (0..3).collect::<SmallVec<u32, 4>>();
+        assert_eq!(small_vec.clone().into_vec(), vec![0, 1, 2]);
+        assert_eq!(small_vec.into_iter().sum::<u32>(), 3);
+        assert_eq!(
+            SmallVec::<u32, 2>::from(vec![1, 2, 3]).into_vec(),
+            vec![1, 2, 3]
+        );
+    }
+}
@@ -1,10 +1,14 @@
 //! UV Map generator. Used to generate second texture coordinates for lightmaps.
 //!
 //! Current implementation uses simple planar mapping.
+//!
+//! The generator is deterministic - the same input data and parameters always produce exactly the
+//! same output, so baked results (for example lightmaps) could be cached by hash of the input.
 use crate::{
     core::{
         algebra::Vector2,
         instant,
+        log::Log,
         math::{self, PlaneClass, TriangleDefinition, Vector2Ext},
         rectpack::RectPacker,
         reflect::prelude::*,
@@ -12,9 +16,9 @@ use crate::{
     },
     scene::mesh::{
         buffer::{
-            TriangleBufferRefMut, VertexAttributeDataType, VertexAttributeDescriptor,
-            VertexAttributeUsage, VertexBufferRefMut, VertexFetchError, VertexReadTrait,
-            VertexWriteTrait,
+            TriangleBuffer, TriangleBufferRefMut, VertexAttributeDataType,
+            VertexAttributeDescriptor, VertexAttributeUsage, VertexBufferRefMut, VertexFetchError,
+            VertexReadTrait, VertexWriteTrait,
         },
         surface::SurfaceData,
         Mesh,
@@ -22,6 +26,11 @@ use crate::{
 };
 use fyrox_core::visitor::BinaryBlob;
 use rayon::prelude::*;
+use std::{
+    cmp::Ordering,
+    fmt::{Display, Formatter},
+    sync::atomic::{self, AtomicBool},
+};
 
 /// A part of uv map.
 #[derive(Debug)]
@@ -30,6 +39,8 @@ pub struct UvMesh {
     triangles: Vec<usize>,
     uv_max: Vector2<f32>,
     uv_min: Vector2<f32>,
+    // Linear scale of the mesh in the atlas, square root of the importance of the mesh.
+    scale: f32,
 }
 
 impl UvMesh {
@@ -38,6 +49,7 @@ impl UvMesh {
             triangles: vec![first_triangle],
             uv_max: Vector2::new(-f32::MAX, -f32::MAX),
             uv_min: Vector2::new(f32::MAX, f32::MAX),
+            scale: 1.0,
         }
     }
 
@@ -55,6 +67,10 @@ impl UvMesh {
     pub fn area(&self) -> f32 {
         self.width() * self.height()
     }
+
+    fn scaled_area(&self) -> f32 {
+        self.area() * self.scale * self.scale
+    }
 }
 
 /// A set of faces with triangles belonging to faces.
@@ -215,13 +231,73 @@ fn generate_uv_box(data: &SurfaceData) -> UvBox {
     uv_box
 }
 
-/// Generates a set of UV meshes.
-pub fn generate_uv_meshes(
+/// Minimal importance of a triangle, smaller (including negative) values are clamped to it, so
+/// every chart receives at least some space in the atlas.
+pub const MIN_IMPORTANCE: f32 = 0.01;
+
+/// Calculates importance of each mesh as an average importance of its triangles, weighted by their
+/// projected area.
+fn assign_importance(meshes: &mut [UvMesh], uv_box: &UvBox, importance: &dyn Fn(usize) -> f32) {
+    for mesh in meshes.iter_mut() {
+        let mut total_area = 0.0;
+        let mut weighted_sum = 0.0;
+        let mut sum = 0.0;
+        for &triangle_index in mesh.triangles.iter() {
+            let [a, b, c] = uv_box.projections[triangle_index];
+            let area = 0.5 * (b - a).perp(&(c - a)).abs();
+            // `max` also replaces NaN with the minimal value.
+            let weight = importance(triangle_index).max(MIN_IMPORTANCE);
+            total_area += area;
+            weighted_sum += weight * area;
+            sum += weight;
+        }
+        let mesh_importance = if total_area > 0.0 {
+            weighted_sum / total_area
+        } else {
+            sum / mesh.triangles.len() as f32
+        };
+        mesh.scale = mesh_importance.sqrt();
+    }
+}
+
+/// An error that may occur during UV generation.
+#[derive(Debug)]
+pub enum UvGenerationError {
+    /// Generation was cancelled by user. Surface data is left untouched in this case.
+    Cancelled,
+    /// Vertex buffer of a surface lacks required data.
+    InvalidData(VertexFetchError),
+}
+
+impl std::error::Error for UvGenerationError {}
+
+impl Display for UvGenerationError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            UvGenerationError::Cancelled => {
+                write!(f, "UV generation was cancelled by the user.")
+            }
+            UvGenerationError::InvalidData(v) => {
+                write!(f, "Vertex buffer of a surface lacks required data {v}.")
+            }
+        }
+    }
+}
+
+impl From<VertexFetchError> for UvGenerationError {
+    fn from(e: VertexFetchError) -> Self {
+        Self::InvalidData(e)
+    }
+}
+
+/// Adds secondary texture coordinates attribute (if needed) and splits vertices at boundary
+/// between each face of the box.
+fn make_seams(
     uv_box: &UvBox,
     data_id: u64,
     vertex_buffer_mut: &mut VertexBufferRefMut,
     geometry_buffer_mut: &mut TriangleBufferRefMut,
-) -> (Vec<UvMesh>, SurfaceDataPatch) {
+) -> SurfaceDataPatch {
     let mut mesh_patch = SurfaceDataPatch {
         data_id,
         ..Default::default()
@@ -242,7 +318,7 @@ pub fn generate_uv_meshes(
             .unwrap();
     }
 
-    // Step 1. Split vertices at boundary between each face. This step multiplies the
+    // Split vertices at boundary between each face. This step multiplies the
     // number of vertices at boundary so we'll get separate texture coordinates at
     // seams.
     make_seam(
@@ -290,13 +366,26 @@ pub fn generate_uv_meshes(
         &mut mesh_patch,
     );
 
-    // Step 2. Find separate "meshes" on uv map. After box mapping we will most likely
-    // end up with set of faces, some of them may form meshes and each such mesh must
-    // be moved with all faces it has.
+    mesh_patch
+}
+
+/// Finds separate "meshes" on uv map. After box mapping we will most likely end up with set of
+/// faces, some of them may form meshes and each such mesh must be moved with all faces it has.
+/// Returns `None` if the search was cancelled. `progress` is called with values in [0; 1] range.
+fn find_uv_meshes(
+    uv_box: &UvBox,
+    geometry_buffer: &TriangleBuffer,
+    progress: &mut dyn FnMut(f32),
+    cancellation_flag: &AtomicBool,
+) -> Option<Vec<UvMesh>> {
     let mut meshes = Vec::new();
-    let mut removed_triangles = vec![false; geometry_buffer_mut.len()];
-    for triangle_index in 0..geometry_buffer_mut.len() {
+    let mut removed_triangles = vec![false; geometry_buffer.len()];
+    for triangle_index in 0..geometry_buffer.len() {
         if !removed_triangles[triangle_index] {
+            if cancellation_flag.load(atomic::Ordering::Relaxed) {
+                return None;
+            }
+
             // Start off random triangle and continue gather adjacent triangles one by one.
             let mut mesh = UvMesh::new(triangle_index);
             removed_triangles[triangle_index] = true;
@@ -304,10 +393,9 @@ pub fn generate_uv_meshes(
             let mut last_triangle = 1;
             let mut i = 0;
             while i < last_triangle {
-                let triangle = &geometry_buffer_mut[mesh.triangles[i]];
+                let triangle = &geometry_buffer[mesh.triangles[i]];
                 // Push all adjacent triangles into mesh. This is brute force implementation.
-                for (other_triangle_index, other_triangle) in geometry_buffer_mut.iter().enumerate()
-                {
+                for (other_triangle_index, other_triangle) in geometry_buffer.iter().enumerate() {
                     if !removed_triangles[other_triangle_index] {
                         'vertex_loop: for &vertex_index in triangle.indices() {
                             for &other_vertex_index in other_triangle.indices() {
@@ -341,9 +429,32 @@ pub fn generate_uv_meshes(
                     .per_component_max(&mesh.uv_max);
             }
             meshes.push(mesh);
+
+            progress((triangle_index + 1) as f32 / geometry_buffer.len() as f32);
         }
     }
 
+    Some(meshes)
+}
+
+/// Generates a set of UV meshes.
+pub fn generate_uv_meshes(
+    uv_box: &UvBox,
+    data_id: u64,
+    vertex_buffer_mut: &mut VertexBufferRefMut,
+    geometry_buffer_mut: &mut TriangleBufferRefMut,
+) -> (Vec<UvMesh>, SurfaceDataPatch) {
+    let mesh_patch = make_seams(uv_box, data_id, vertex_buffer_mut, geometry_buffer_mut);
+
+    // The search cannot be cancelled here, so it always returns meshes.
+    let meshes = find_uv_meshes(
+        uv_box,
+        geometry_buffer_mut,
+        &mut |_| {},
+        &AtomicBool::new(false),
+    )
+    .unwrap_or_default();
+
     (meshes, mesh_patch)
 }
 
@@ -352,17 +463,95 @@ pub fn generate_uv_meshes(
 /// # Performance
 ///
 /// This method utilizes lots of "brute force" algorithms, so it is not fast as it
-/// could be in ideal case. It also allocates some memory for internal needs.
+/// could be in ideal case. It also allocates some memory for internal needs. Use
+/// [`generate_uvs_with_progress`] if you need to track progress or to cancel the
+/// generation.
 pub fn generate_uvs(
     data: &mut SurfaceData,
     spacing: f32,
 ) -> Result<SurfaceDataPatch, VertexFetchError> {
+    generate_uvs_weighted(data, spacing, |_| 1.0)
+}
+
+/// Generates UV map for given surface data, same as [`generate_uvs`], but distributes the space
+/// of the atlas according to the importance of triangles. `importance` is called with an index
+/// of a triangle of the surface data and must return its relative weight, the default weight is
+/// `1.0`. Every chart receives the area proportional to the average weight of its triangles, so a
+/// chart with weight `4.0` gets twice higher texel density than a chart with weight `1.0`. Weights
+/// less than [`MIN_IMPORTANCE`] are clamped.
+///
+/// Importance allows to spend the lightmap budget on surfaces that matter (for example - a floor
+/// is usually more important than a ceiling). Per-material importance could be expressed by
+/// assigning the same weight to all triangles of a surface.
+pub fn generate_uvs_weighted<I>(
+    data: &mut SurfaceData,
+    spacing: f32,
+    importance: I,
+) -> Result<SurfaceDataPatch, VertexFetchError>
+where
+    I: Fn(usize) -> f32,
+{
+    match generate_uvs_weighted_with_progress(
+        data,
+        spacing,
+        importance,
+        |_| {},
+        &AtomicBool::new(false),
+    ) {
+        Ok(patch) => Ok(patch),
+        Err(UvGenerationError::InvalidData(e)) => Err(e),
+        Err(UvGenerationError::Cancelled) => unreachable!("generation cannot be cancelled"),
+    }
+}
+
+/// Generates UV map for given surface data, same as [`generate_uvs`], but reports its
+/// progress (in [0; 1] range) to the given callback and periodically checks the given
+/// cancellation flag (it could be shared with other threads using `Arc<AtomicBool>`).
+/// Cancellation is not immediate, the flag is checked between charts and packing iterations.
+///
+/// The surface data is modified only if the generation was successful, when it was
+/// cancelled, [`UvGenerationError::Cancelled`] is returned and the data is left untouched.
+pub fn generate_uvs_with_progress<F>(
+    data: &mut SurfaceData,
+    spacing: f32,
+    progress: F,
+    cancellation_flag: &AtomicBool,
+) -> Result<SurfaceDataPatch, UvGenerationError>
+where
+    F: FnMut(f32),
+{
+    generate_uvs_weighted_with_progress(data, spacing, |_| 1.0, progress, cancellation_flag)
+}
+
+/// Generates UV map for given surface data using importance of triangles, same as
+/// [`generate_uvs_weighted`], but reports its progress and could be cancelled, see
+/// [`generate_uvs_with_progress`] for more info.
+pub fn generate_uvs_weighted_with_progress<I, F>(
+    data: &mut SurfaceData,
+    spacing: f32,
+    importance: I,
+    mut progress: F,
+    cancellation_flag: &AtomicBool,
+) -> Result<SurfaceDataPatch, UvGenerationError>
+where
+    I: Fn(usize) -> f32,
+    F: FnMut(f32),
+{
+    let is_cancelled = || cancellation_flag.load(atomic::Ordering::Relaxed);
+
+    progress(0.0);
+
     let uv_box = generate_uv_box(data);
 
     let data_id = data.content_hash();
-    let mut vertex_buffer_mut = data.vertex_buffer.modify();
-    let mut geometry_buffer_mut = data.geometry_buffer.modify();
-    let (mut meshes, mut patch) = generate_uv_meshes(
+
+    // Work with copies of the buffers, so cancellation won't leave the data half-modified.
+    let mut vertex_buffer = data.vertex_buffer.clone();
+    let mut geometry_buffer = data.geometry_buffer.clone();
+
+    let mut vertex_buffer_mut = vertex_buffer.modify();
+    let mut geometry_buffer_mut = geometry_buffer.modify();
+    let mut patch = make_seams(
         &uv_box,
         data_id,
         &mut vertex_buffer_mut,
@@ -370,11 +559,35 @@ pub fn generate_uvs(
     );
     drop(geometry_buffer_mut);
 
+    if is_cancelled() {
+        return Err(UvGenerationError::Cancelled);
+    }
+    progress(0.1);
+
+    let mut meshes = find_uv_meshes(
+        &uv_box,
+        &geometry_buffer,
+        &mut |p| progress(0.1 + p * 0.7),
+        cancellation_flag,
+    )
+    .ok_or(UvGenerationError::Cancelled)?;
+
+    assign_importance(&mut meshes, &uv_box, &importance);
+
     // Step 4. Arrange and scale all meshes on uv map so it fits into [0;1] range.
-    let area = meshes.iter().fold(0.0, |area, mesh| area + mesh.area());
+    let area = meshes
+        .iter()
+        .fold(0.0, |area, mesh| area + mesh.scaled_area());
     let square_side = area.sqrt() + spacing * meshes.len() as f32;
 
-    meshes.sort_unstable_by(|a, b| b.area().partial_cmp(&a.area()).unwrap());
+    // Larger meshes go first. Meshes with the same area are ordered by their first triangle, so
+    // the layout does not depend on the order in which meshes were found.
+    meshes.sort_by(|a, b| {
+        b.scaled_area()
+            .partial_cmp(&a.scaled_area())
+            .unwrap_or(Ordering::Equal)
+            .then_with(|| a.triangles[0].cmp(&b.triangles[0]))
+    });
 
     let mut rects = Vec::new();
 
@@ -384,10 +597,16 @@ pub fn generate_uvs(
     // This should be large enough to fit all meshes, but small to prevent losing of space.
     // We'll use iterative approach to pack everything as tight as possible: at each iteration
     // scale will be increased until packer is able to pack everything.
+    const MAX_PACKING_ITERATIONS: usize = 100;
     let mut empiric_scale = 1.1;
     let mut scale = 1.0;
     let mut packer = RectPacker::new(1.0, 1.0);
-    'try_loop: for _ in 0..100 {
+    'try_loop: for iteration in 0..MAX_PACKING_ITERATIONS {
+        if is_cancelled() {
+            return Err(UvGenerationError::Cancelled);
+        }
+        progress(0.8 + 0.15 * iteration as f32 / MAX_PACKING_ITERATIONS as f32);
+
         rects.clear();
 
         // Calculate size of atlas for packer, we'll scale it later on.
@@ -397,8 +616,8 @@ pub fn generate_uvs(
         packer.clear();
         for mesh in meshes.iter() {
             if let Some(rect) = packer.find_free(
-                mesh.width() * scale + twice_spacing,
-                mesh.height() * scale + twice_spacing,
+                mesh.width() * scale * mesh.scale + twice_spacing,
+                mesh.height() * scale * mesh.scale + twice_spacing,
             ) {
                 rects.push(rect);
             } else {
@@ -407,13 +626,19 @@ pub fn generate_uvs(
                 continue 'try_loop;
             }
         }
+
+        break;
+    }
+
+    if is_cancelled() {
+        return Err(UvGenerationError::Cancelled);
     }
 
     for (i, rect) in rects.into_iter().enumerate() {
         let mesh = &meshes[i];
 
         for &triangle_index in mesh.triangles.iter() {
-            for (&vertex_index, &projection) in data.geometry_buffer[triangle_index]
+            for (&vertex_index, &projection) in geometry_buffer[triangle_index]
                 .indices()
                 .iter()
                 .zip(&uv_box.projections[triangle_index])
@@ -423,7 +648,7 @@ pub fn generate_uvs(
                     .unwrap()
                     .write_2_f32(
                         VertexAttributeUsage::TexCoord1,
-                        (projection - mesh.uv_min).scale(scale)
+                        (projection - mesh.uv_min).scale(scale * mesh.scale)
                             + Vector2::new(spacing, spacing)
                             + rect.position,
                     )?;
@@ -431,7 +656,7 @@ pub fn generate_uvs(
         }
     }
 
-    patch.triangles = data.geometry_buffer.triangles_ref().to_vec();
+    patch.triangles = geometry_buffer.triangles_ref().to_vec();
 
     for view in vertex_buffer_mut.iter() {
         patch
@@ -439,6 +664,13 @@ pub fn generate_uvs(
             .push(view.read_2_f32(VertexAttributeUsage::TexCoord1)?);
     }
 
+    drop(vertex_buffer_mut);
+
+    data.vertex_buffer = vertex_buffer;
+    data.geometry_buffer = geometry_buffer;
+
+    progress(1.0);
+
     Ok(patch)
 }
 
@@ -451,12 +683,113 @@ pub fn generate_uvs_mesh(
 
     let data_set = mesh.surfaces().iter().map(|s| s.data()).collect::<Vec<_>>();
 
-    let patches = data_set
-        .into_par_iter()
-        .map(|data| generate_uvs(&mut data.lock(), spacing))
-        .collect::<Result<Vec<SurfaceDataPatch>, VertexFetchError>>()?;
+    // Surfaces could share the same data, in this case the data is processed multiple times and
+    // the result depends on the order of processing. To keep it deterministic, only the first
+    // occurrence of each data is processed in parallel, the rest are processed afterwards in the
+    // order of surfaces.
+    let is_first_occurrence = data_set
+        .iter()
+        .enumerate()
+        .map(|(i, data)| !data_set[..i].iter().any(|other| other.key() == data.key()))
+        .collect::<Vec<_>>();
+
+    let mut patches = data_set
+        .par_iter()
+        .zip(is_first_occurrence.par_iter())
+        .map(|(data, &is_first)| {
+            if is_first {
+                generate_uvs(&mut data.lock(), spacing).map(Some)
+            } else {
+                Ok(None)
+            }
+        })
+        .collect::<Result<Vec<Option<SurfaceDataPatch>>, VertexFetchError>>()?;
+
+    for (patch, data) in patches.iter_mut().zip(data_set.iter()) {
+        if patch.is_none() {
+            *patch = Some(generate_uvs(&mut data.lock(), spacing)?);
+        }
+    }
 
-    println!("Generate UVs: {:?}", instant::Instant::now() - last);
+    let patches = patches.into_iter().flatten().collect::<Vec<_>>();
+
+    Log::info(format!(
+        "Generate UVs: {:?}",
+        instant::Instant::now() - last
+    ));
 
     Ok(patches)
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::algebra::Matrix4,
+        scene::mesh::surface::SurfaceData,
+        utils::uvgen::{generate_uvs, generate_uvs_weighted, SurfaceDataPatch},
+    };
+
+    fn patch_bytes(patch: &SurfaceDataPatch) -> Vec<u8> {
+        let mut bytes = patch.data_id.to_le_bytes().to_vec();
+        for triangle in patch.triangles.iter() {
+            for index in triangle.indices() {
+                bytes.extend(index.to_le_bytes());
+            }
+        }
+        for tex_coord in patch.second_tex_coords.iter() {
+            bytes.extend(tex_coord.x.to_le_bytes());
+            bytes.extend(tex_coord.y.to_le_bytes());
+        }
+        for vertex in patch.additional_vertices.iter() {
+            bytes.extend(vertex.to_le_bytes());
+        }
+        bytes
+    }
+
+    #[test]
+    fn test_generate_uvs_is_deterministic() {
+        // Charts of a cube have the same area, which is the worst case for their ordering.
+        let makers: [fn() -> SurfaceData; 2] = [
+            || SurfaceData::make_cube(Matrix4::identity()),
+            || SurfaceData::make_sphere(16, 16, 1.0, &Matrix4::identity()),
+        ];
+
+        for make_data in makers {
+            let mut first = make_data();
+            let mut second = make_data();
+
+            let first_patch = generate_uvs(&mut first, 0.005).unwrap();
+            let second_patch = generate_uvs(&mut second, 0.005).unwrap();
+
+            assert!(!first_patch.second_tex_coords.is_empty());
+            assert_eq!(patch_bytes(&first_patch), patch_bytes(&second_patch));
+            assert_eq!(first.content_hash(), second.content_hash());
+        }
+    }
+
+    #[test]
+    fn test_generate_uvs_weighted() {
+        // Every face of the cube is a separate chart of two triangles, the front face is four
+        // times more important than others.
+        let mut data = SurfaceData::make_cube(Matrix4::identity());
+        let patch =
+            generate_uvs_weighted(&mut data, 0.005, |i| if i < 2 { 4.0 } else { 1.0 }).unwrap();
+
+        let uv_area = |triangles: std::ops::Range<usize>| {
+            patch.triangles[triangles]
+                .iter()
+                .map(|t| {
+                    let [a, b, c] = t.0.map(|i| patch.second_tex_coords[i as usize]);
+                    0.5 * (b - a).perp(&(c - a)).abs()
+                })
+                .sum::<f32>()
+        };
+
+        assert!(patch
+            .second_tex_coords
+            .iter()
+            .all(|uv| (0.0..=1.0).contains(&uv.x) && (0.0..=1.0).contains(&uv.y)));
+        let ratio = uv_area(0..2) / uv_area(2..4);
+        assert!((ratio - 4.0).abs() < 0.01, "{}", ratio);
+    }
+}
@@ -0,0 +1,63 @@
+//! Engine shutdown must stop every thread that was started by the engine. The test lives in its
+//! own binary, because the amount of threads is counted for the whole process.
+
+#![cfg(target_os = "linux")]
+
+use fyrox::{
+    asset::manager::ResourceManager,
+    engine::{Engine, EngineInitParams, SerializationContext},
+};
+use std::{sync::Arc, thread, time::Duration};
+
+fn thread_count() -> usize {
+    std::fs::read_to_string("/proc/self/status")
+        .unwrap()
+        .lines()
+        .find_map(|line| line.strip_prefix("Threads:"))
+        .and_then(|count| count.trim().parse().ok())
+        .unwrap()
+}
+
+// A worker thread reports that it has stopped right before it exits, so give the OS a moment
+// to remove it.
+fn settled_thread_count(expected: usize) -> usize {
+    for _ in 0..100 {
+        if thread_count() == expected {
+            break;
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+    thread_count()
+}
+
+fn create_engine() -> Engine {
+    let resource_manager = ResourceManager::new();
+    resource_manager.set_loader_thread_count(4);
+    Engine::new(EngineInitParams {
+        graphics_context_params: Default::default(),
+        serialization_context: Arc::new(SerializationContext::new()),
+        resource_manager,
+    })
+    .unwrap()
+}
+
+#[test]
+fn test_engine_shutdown_does_not_leak_threads() {
+    // Let lazily initialized global threads (if any) start.
+    create_engine().shutdown().unwrap();
+    let initial = thread_count();
+
+    for i in 0..10 {
+        let engine = create_engine();
+        assert!(thread_count() > initial);
+        if i % 2 == 0 {
+            engine.shutdown().unwrap();
+        } else {
+            // Dropping the engine does not stop the resource manager, since it could be shared.
+            let resource_manager = engine.resource_manager.clone();
+            drop(engine);
+            assert!(resource_manager.shutdown(Duration::from_secs(10)));
+        }
+        assert_eq!(settled_thread_count(initial), initial);
+    }
+}